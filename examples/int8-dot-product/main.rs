@@ -0,0 +1,290 @@
+// This example demonstrates how to use 8-bit integer arithmetic in a compute shader, to compute
+// the dot products of many pairs of `i8vec4` vectors. This is the core operation of quantized
+// neural network inference.
+//
+// 8-bit integers in shaders require two device features: `shader_int8` to do arithmetic on them,
+// and `storage_buffer8_bit_access` to read them from storage buffers. Vulkano checks the SPIR-V
+// capabilities that a shader declares against the features that are enabled on the device, so
+// forgetting to enable one of them results in an error when the shader module is created.
+//
+// Many devices can compute dot products of 8-bit vectors with a single instruction. Shaders can
+// use it through the `OpSDot` instruction, which requires the `shader_integer_dot_product` feature
+// of Vulkan 1.3 or the `khr_shader_integer_dot_product` extension. GLSL has no built-in function
+// for it in the version of shaderc that vulkano uses, so the shader declares it with
+// `GL_EXT_spirv_intrinsics`. Whether the instruction is actually faster than doing the arithmetic
+// by hand is reported by the `integer_dot_product*` device properties.
+
+use std::sync::Arc;
+use vulkano::{
+    buffer::{Buffer, BufferCreateInfo, BufferUsage},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferLevel,
+        CommandBufferUsage, RecordingCommandBuffer,
+    },
+    descriptor_set::{
+        allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
+    },
+    device::{
+        physical::PhysicalDeviceType, Device, DeviceCreateInfo, DeviceExtensions, DeviceFeatures,
+        QueueCreateInfo, QueueFlags,
+    },
+    instance::{Instance, InstanceCreateFlags, InstanceCreateInfo},
+    memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator},
+    pipeline::{
+        compute::ComputePipelineCreateInfo, layout::PipelineDescriptorSetLayoutCreateInfo,
+        ComputePipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    sync::{self, GpuFuture},
+    Version, VulkanLibrary,
+};
+
+const ELEMENT_COUNT: u32 = 65536;
+
+fn main() {
+    let library = VulkanLibrary::new().unwrap();
+    let instance = Instance::new(
+        library,
+        InstanceCreateInfo {
+            flags: InstanceCreateFlags::ENUMERATE_PORTABILITY,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    // The features needed to read and do arithmetic on 8-bit integers, and to use the dot product
+    // instruction. The first two were promoted to core in Vulkan 1.2, so we only consider devices
+    // that support that version. The last one is core in Vulkan 1.3, and needs an extension
+    // before that.
+    let device_features = DeviceFeatures {
+        shader_int8: true,
+        storage_buffer8_bit_access: true,
+        shader_integer_dot_product: true,
+        ..DeviceFeatures::empty()
+    };
+    let (physical_device, queue_family_index) = instance
+        .enumerate_physical_devices()
+        .unwrap()
+        .filter(|p| p.api_version() >= Version::V1_2)
+        .filter(|p| {
+            p.api_version() >= Version::V1_3
+                || p.supported_extensions().khr_shader_integer_dot_product
+        })
+        .filter(|p| p.supported_features().contains(&device_features))
+        .filter_map(|p| {
+            p.queue_family_properties()
+                .iter()
+                .position(|q| q.queue_flags.intersects(QueueFlags::COMPUTE))
+                .map(|i| (p, i as u32))
+        })
+        .min_by_key(|(p, _)| match p.properties().device_type {
+            PhysicalDeviceType::DiscreteGpu => 0,
+            PhysicalDeviceType::IntegratedGpu => 1,
+            PhysicalDeviceType::VirtualGpu => 2,
+            PhysicalDeviceType::Cpu => 3,
+            PhysicalDeviceType::Other => 4,
+            _ => 5,
+        })
+        .expect("no device supports 8-bit integer dot products in shaders");
+
+    println!(
+        "Using device: {} (type: {:?})",
+        physical_device.properties().device_name,
+        physical_device.properties().device_type,
+    );
+
+    let device_extensions = DeviceExtensions {
+        khr_shader_integer_dot_product: physical_device.api_version() < Version::V1_3,
+        ..DeviceExtensions::empty()
+    };
+
+    let properties = physical_device.properties();
+    match (
+        properties.integer_dot_product4x8_bit_packed_signed_accelerated,
+        properties.integer_dot_product8_bit_signed_accelerated,
+    ) {
+        (Some(packed), Some(unpacked)) => {
+            println!("Accelerated 8-bit dot products: packed: {packed}, unpacked: {unpacked}")
+        }
+        _ => println!("The device does not report integer dot product acceleration"),
+    }
+
+    let (device, mut queues) = Device::new(
+        physical_device,
+        DeviceCreateInfo {
+            enabled_extensions: device_extensions,
+            enabled_features: device_features,
+            queue_create_infos: vec![QueueCreateInfo {
+                queue_family_index,
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let queue = queues.next().unwrap();
+
+    let pipeline = {
+        mod cs {
+            vulkano_shaders::shader! {
+                ty: "compute",
+                src: r"
+                    #version 450
+                    #extension GL_EXT_shader_explicit_arithmetic_types_int8 : require
+                    #extension GL_EXT_shader_8bit_storage : require
+                    #extension GL_EXT_spirv_intrinsics : require
+
+                    // `OpSDot` from `SPV_KHR_integer_dot_product`, which needs the `DotProduct`
+                    // and `DotProductInput4x8Bit` capabilities. The result is 32 bits wide, so the
+                    // sum of the products can't overflow.
+                    spirv_instruction(
+                        extensions = ["SPV_KHR_integer_dot_product"],
+                        capabilities = [6019, 6017],
+                        id = 4450
+                    )
+                    int sdot(i8vec4 a, i8vec4 b);
+
+                    layout(local_size_x = 64, local_size_y = 1, local_size_z = 1) in;
+
+                    layout(set = 0, binding = 0) readonly buffer Lhs {
+                        i8vec4 lhs[];
+                    };
+
+                    layout(set = 0, binding = 1) readonly buffer Rhs {
+                        i8vec4 rhs[];
+                    };
+
+                    layout(set = 0, binding = 2) writeonly buffer Result {
+                        int result[];
+                    };
+
+                    void main() {
+                        uint idx = gl_GlobalInvocationID.x;
+
+                        result[idx] = sdot(lhs[idx], rhs[idx]);
+                    }
+                ",
+            }
+        }
+        let cs = cs::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let stage = PipelineShaderStageCreateInfo::new(cs);
+        let layout = PipelineLayout::new(
+            device.clone(),
+            PipelineDescriptorSetLayoutCreateInfo::from_stages([&stage])
+                .into_pipeline_layout_create_info(device.clone())
+                .unwrap(),
+        )
+        .unwrap();
+        ComputePipeline::new(
+            device.clone(),
+            None,
+            ComputePipelineCreateInfo::stage_layout(stage, layout),
+        )
+        .unwrap()
+    };
+
+    let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+    let descriptor_set_allocator = Arc::new(StandardDescriptorSetAllocator::new(
+        device.clone(),
+        Default::default(),
+    ));
+    let command_buffer_allocator = Arc::new(StandardCommandBufferAllocator::new(
+        device.clone(),
+        Default::default(),
+    ));
+
+    let lhs = |n: u32| -> [i8; 4] { [0, 1, 2, 3].map(|i| ((n * 4 + i) % 256) as u8 as i8) };
+    let rhs = |n: u32| -> [i8; 4] { [0, 1, 2, 3].map(|i| ((n + i * 7) % 19) as i8 - 9) };
+
+    let buffer_create_info = BufferCreateInfo {
+        usage: BufferUsage::STORAGE_BUFFER,
+        ..Default::default()
+    };
+    let allocation_create_info = AllocationCreateInfo {
+        memory_type_filter: MemoryTypeFilter::PREFER_DEVICE | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+        ..Default::default()
+    };
+    let lhs_buffer = Buffer::from_iter(
+        memory_allocator.clone(),
+        buffer_create_info.clone(),
+        allocation_create_info.clone(),
+        (0..ELEMENT_COUNT).map(lhs),
+    )
+    .unwrap();
+    let rhs_buffer = Buffer::from_iter(
+        memory_allocator.clone(),
+        buffer_create_info.clone(),
+        allocation_create_info.clone(),
+        (0..ELEMENT_COUNT).map(rhs),
+    )
+    .unwrap();
+    let result_buffer = Buffer::from_iter(
+        memory_allocator,
+        buffer_create_info,
+        allocation_create_info,
+        (0..ELEMENT_COUNT).map(|_| 0i32),
+    )
+    .unwrap();
+
+    let layout = &pipeline.layout().set_layouts()[0];
+    let set = DescriptorSet::new(
+        descriptor_set_allocator,
+        layout.clone(),
+        [
+            WriteDescriptorSet::buffer(0, lhs_buffer),
+            WriteDescriptorSet::buffer(1, rhs_buffer),
+            WriteDescriptorSet::buffer(2, result_buffer.clone()),
+        ],
+        [],
+    )
+    .unwrap();
+
+    let mut builder = RecordingCommandBuffer::new(
+        command_buffer_allocator,
+        queue.queue_family_index(),
+        CommandBufferLevel::Primary,
+        CommandBufferBeginInfo {
+            usage: CommandBufferUsage::OneTimeSubmit,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    builder
+        .bind_pipeline_compute(pipeline.clone())
+        .unwrap()
+        .bind_descriptor_sets(
+            PipelineBindPoint::Compute,
+            pipeline.layout().clone(),
+            0,
+            set,
+        )
+        .unwrap();
+
+    unsafe {
+        builder.dispatch([ELEMENT_COUNT / 64, 1, 1]).unwrap();
+    }
+
+    let command_buffer = builder.end().unwrap();
+
+    let future = sync::now(device)
+        .then_execute(queue, command_buffer)
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap();
+    future.wait(None).unwrap();
+
+    let result_buffer_content = result_buffer.read().unwrap();
+    for n in 0..ELEMENT_COUNT {
+        let expected: i32 = lhs(n)
+            .into_iter()
+            .zip(rhs(n))
+            .map(|(l, r)| l as i32 * r as i32)
+            .sum();
+        assert_eq!(result_buffer_content[n as usize], expected);
+    }
+
+    println!("Success");
+}
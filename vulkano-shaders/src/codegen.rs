@@ -1,12 +1,13 @@
 use crate::{
+    descriptor_sets, push_constants, specialization_constants,
     structs::{self, TypeRegistry},
-    MacroInput,
+    vertex_input, MacroInput, SourceLanguage,
 };
 use heck::ToSnakeCase;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 pub use shaderc::{CompilationArtifact, IncludeType, ResolvedInclude, ShaderKind};
-use shaderc::{CompileOptions, Compiler, EnvVersion, TargetEnv};
+use shaderc::{CompileOptions, Compiler, EnvVersion, OptimizationLevel, TargetEnv};
 use std::{
     cell::RefCell,
     fs,
@@ -143,6 +144,29 @@ pub(super) fn compile(
     base_path: &Path,
     code: &str,
     shader_kind: ShaderKind,
+    entry_point: &str,
+) -> Result<(CompilationArtifact, Vec<String>), String> {
+    compile_with_optimization(
+        input,
+        path,
+        base_path,
+        code,
+        shader_kind,
+        entry_point,
+        OptimizationLevel::Zero,
+    )
+}
+
+/// Compiles the source code like [`compile`], and then runs the optimizer of SPIRV-Tools
+/// (`spirv-opt`) on the module with the passes for `optimization_level`.
+pub(super) fn compile_with_optimization(
+    input: &MacroInput,
+    path: Option<String>,
+    base_path: &Path,
+    code: &str,
+    shader_kind: ShaderKind,
+    entry_point: &str,
+    optimization_level: OptimizationLevel,
 ) -> Result<(CompilationArtifact, Vec<String>), String> {
     let includes = RefCell::new(Vec::new());
     let compiler = Compiler::new().ok_or("failed to create GLSL compiler")?;
@@ -153,14 +177,27 @@ pub(super) fn compile(
         TargetEnv::Vulkan,
         input.vulkan_version.unwrap_or(EnvVersion::Vulkan1_0) as u32,
     );
+    if input.source_language == SourceLanguage::Hlsl {
+        compile_options.set_source_language(shaderc::SourceLanguage::HLSL);
+
+        // Honor `register(...)` bindings and `[[vk::binding(...)]]` attributes, and lay out
+        // constant buffers the way HLSL does.
+        compile_options.set_hlsl_io_mapping(true);
+        compile_options.set_hlsl_offsets(true);
+    }
 
     if let Some(spirv_version) = input.spirv_version {
         compile_options.set_target_spirv(spirv_version);
     }
 
+    compile_options.set_optimization_level(optimization_level);
+
     let root_source_path = path.as_deref().unwrap_or(
         // An arbitrary placeholder file name for embedded shaders.
-        "shader.glsl",
+        match input.source_language {
+            SourceLanguage::Hlsl => "shader.hlsl",
+            _ => "shader.glsl",
+        },
     );
 
     // Specify the file resolution callback for the `#include` directive.
@@ -191,7 +228,7 @@ pub(super) fn compile(
             code,
             shader_kind,
             root_source_path,
-            "main",
+            entry_point,
             Some(&compile_options),
         )
         .map_err(|e| e.to_string().replace("(s): ", "(s):\n"))?;
@@ -201,11 +238,160 @@ pub(super) fn compile(
     Ok((content, includes.into_inner()))
 }
 
+#[cfg(feature = "wgsl")]
+pub(super) fn compile_wgsl(
+    input: &MacroInput,
+    code: &str,
+    shader_kind: ShaderKind,
+    entry_point: &str,
+) -> Result<Vec<u32>, String> {
+    use naga::{
+        back::spv::{self, PipelineOptions, WriterFlags},
+        valid::{Capabilities, ValidationFlags, Validator},
+    };
+
+    let shader_stage = match shader_kind {
+        ShaderKind::Vertex => naga::ShaderStage::Vertex,
+        ShaderKind::Fragment => naga::ShaderStage::Fragment,
+        ShaderKind::Compute => naga::ShaderStage::Compute,
+        _ => {
+            return Err(
+                "WGSL only supports the `vertex`, `fragment` and `compute` shader types".into(),
+            )
+        }
+    };
+
+    let module = naga::front::wgsl::parse_str(code).map_err(|err| err.emit_to_string(code))?;
+    let module_info = Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|err| err.emit_to_string(code))?;
+
+    let lang_version = match input.spirv_version {
+        Some(shaderc::SpirvVersion::V1_1) => (1, 1),
+        Some(shaderc::SpirvVersion::V1_2) => (1, 2),
+        Some(shaderc::SpirvVersion::V1_3) => (1, 3),
+        Some(shaderc::SpirvVersion::V1_4) => (1, 4),
+        Some(shaderc::SpirvVersion::V1_5) => (1, 5),
+        Some(shaderc::SpirvVersion::V1_6) => (1, 6),
+        _ => (1, 0),
+    };
+
+    let mut flags = WriterFlags::empty();
+
+    if cfg!(feature = "shaderc-debug") {
+        flags |= WriterFlags::DEBUG;
+    }
+
+    let options = spv::Options {
+        lang_version,
+        flags,
+        ..Default::default()
+    };
+    let pipeline_options = PipelineOptions {
+        shader_stage,
+        entry_point: entry_point.to_owned(),
+    };
+
+    spv::write_vec(&module, &module_info, &options, Some(&pipeline_options))
+        .map_err(|err| err.to_string())
+}
+
+/// Compiles HLSL source code with DXC, which is loaded from the `dxcompiler` shared library.
+#[cfg(feature = "dxc")]
+pub(super) fn compile_dxc(
+    input: &MacroInput,
+    path: Option<&str>,
+    base_path: &Path,
+    code: &str,
+    shader_kind: ShaderKind,
+    entry_point: &str,
+) -> Result<Vec<u32>, String> {
+    let (major, minor) = input.shader_model.unwrap_or((6, 0));
+    let stage = match shader_kind {
+        ShaderKind::Vertex => "vs",
+        ShaderKind::TessControl => "hs",
+        ShaderKind::TessEvaluation => "ds",
+        ShaderKind::Geometry => "gs",
+        ShaderKind::Fragment => "ps",
+        ShaderKind::Compute => "cs",
+        ShaderKind::Task => "as",
+        ShaderKind::Mesh => "ms",
+        ShaderKind::RayGeneration
+        | ShaderKind::AnyHit
+        | ShaderKind::ClosestHit
+        | ShaderKind::Miss
+        | ShaderKind::Intersection
+        | ShaderKind::Callable => {
+            if minor < 3 {
+                return Err("ray tracing shaders require at least shader model 6_3".into());
+            }
+
+            "lib"
+        }
+        _ => return Err("the shader type is not supported by DXC".into()),
+    };
+    let target_profile = format!("{stage}_{major}_{minor}");
+
+    let target_env = match input.vulkan_version {
+        Some(EnvVersion::Vulkan1_1) => "-fspv-target-env=vulkan1.1",
+        Some(EnvVersion::Vulkan1_2) => "-fspv-target-env=vulkan1.2",
+        Some(EnvVersion::Vulkan1_3) => "-fspv-target-env=vulkan1.3",
+        _ => "-fspv-target-env=vulkan1.0",
+    };
+
+    let mut include_directories: Vec<String> = Vec::new();
+
+    if let Some(path) = path {
+        if let Some(parent) = base_path.join(path).parent() {
+            include_directories.push(parent.to_string_lossy().into_owned());
+        }
+    }
+
+    include_directories.extend(
+        input
+            .include_directories
+            .iter()
+            .map(|dir| dir.to_string_lossy().into_owned()),
+    );
+
+    let mut args = vec!["-spirv", target_env];
+    args.extend(
+        include_directories
+            .iter()
+            .flat_map(|dir| ["-I", dir.as_str()]),
+    );
+
+    if cfg!(feature = "shaderc-debug") {
+        args.push("-Zi");
+    }
+
+    let defines: Vec<_> = input
+        .macro_defines
+        .iter()
+        .map(|(name, value)| (name.as_str(), Some(value.as_str())))
+        .collect();
+
+    let bytes = hassle_rs::compile_hlsl(
+        path.unwrap_or("shader.hlsl"),
+        code,
+        entry_point,
+        &target_profile,
+        &args,
+        &defines,
+    )
+    .map_err(|err| err.to_string())?;
+
+    vulkano::shader::spirv::bytes_to_words(&bytes)
+        .map(|words| words.into_owned())
+        .map_err(|err| format!("DXC produced invalid SPIR-V: {err}"))
+}
+
 pub(super) fn reflect(
     input: &MacroInput,
     source: LitStr,
     name: String,
     words: &[u32],
+    optimized_words: Option<&[u32]>,
     input_paths: Vec<String>,
     type_registry: &mut TypeRegistry,
 ) -> Result<(TokenStream, TokenStream), Error> {
@@ -226,13 +412,51 @@ pub(super) fn reflect(
         }
     });
 
-    let load_name = if shader.name.is_empty() {
-        format_ident!("load")
+    let descriptor_sets = descriptor_sets::write_descriptor_sets(input, &shader);
+    let specialization_constants =
+        specialization_constants::write_specialization_constants(&shader);
+    let push_constants = push_constants::write_push_constants(input, &shader);
+    let vertex_structs = vertex_input::write_vertex_structs(input, &shader)?;
+
+    let (load_name, code_name) = if shader.name.is_empty() {
+        (format_ident!("load"), format_ident!("code"))
     } else {
-        format_ident!("load_{}", shader.name.to_snake_case())
+        let name = shader.name.to_snake_case();
+        (
+            format_ident!("load_{}", name),
+            format_ident!("code_{}", name),
+        )
+    };
+
+    // The shader is reflected before it's optimized, because the optimizer may strip the names and
+    // the unused resources that the generated code is based on.
+    let (words, code_doc) = match optimized_words {
+        Some(optimized_words) => {
+            let size = words.len() * 4;
+            let optimized_size = optimized_words.len() * 4;
+            let doc = format!(
+                "Returns the SPIR-V code of the shader.\n\nThe code was optimized with \
+                `spirv-opt`, which changed its size from {size} to {optimized_size} bytes ({:+} \
+                bytes).",
+                optimized_size as isize - size as isize,
+            );
+
+            (optimized_words, doc)
+        }
+        None => (words, "Returns the SPIR-V code of the shader.".to_owned()),
     };
 
     let shader_code = quote! {
+        #[doc = #code_doc]
+        #[inline]
+        pub fn #code_name() -> &'static [u32] {
+            let _bytes = ( #( #include_bytes ),* );
+
+            static WORDS: &[u32] = &[ #( #words ),* ];
+
+            WORDS
+        }
+
         /// Loads the shader as a `ShaderModule`.
         #[allow(unsafe_code)]
         #[inline]
@@ -242,20 +466,22 @@ pub(super) fn reflect(
             ::std::sync::Arc<::vulkano::shader::ShaderModule>,
             ::vulkano::Validated<::vulkano::VulkanError>,
         > {
-            let _bytes = ( #( #include_bytes ),* );
-
-            static WORDS: &[u32] = &[ #( #words ),* ];
-
             unsafe {
                 ::vulkano::shader::ShaderModule::new(
                     device,
-                    ::vulkano::shader::ShaderModuleCreateInfo::new(WORDS),
+                    ::vulkano::shader::ShaderModuleCreateInfo::new(#code_name()),
                 )
             }
         }
+
+        #descriptor_sets
+        #specialization_constants
+        #push_constants
+        #vertex_structs
     };
 
-    let structs = structs::write_structs(input, &shader, type_registry)?;
+    let mut structs = structs::write_structs(input, &shader, type_registry)?;
+    structs.extend(structs::write_layout_checks(input, &shader, type_registry)?);
 
     Ok((shader_code, structs))
 }
@@ -266,7 +492,7 @@ mod tests {
     use proc_macro2::Span;
     use quote::ToTokens;
     use shaderc::SpirvVersion;
-    use syn::{File, Item};
+    use syn::{File, Item, TraitItem};
     use vulkano::shader::reflect;
 
     fn spv_to_words(data: &[u8]) -> Vec<u32> {
@@ -298,6 +524,7 @@ mod tests {
             LitStr::new("../tests/frag.spv", Span::call_site()),
             String::new(),
             &insts,
+            None,
             Vec::new(),
             &mut type_registry,
         )
@@ -321,6 +548,7 @@ mod tests {
                 void main() {}
             "#,
             ShaderKind::Vertex,
+            "main",
         )
         .expect("cannot resolve include files");
 
@@ -341,6 +569,7 @@ mod tests {
                 void main() {}
             "#,
             ShaderKind::Vertex,
+            "main",
         )
         .expect("cannot resolve include files");
 
@@ -373,6 +602,7 @@ mod tests {
                 void main() {}
             "#,
             ShaderKind::Vertex,
+            "main",
         )
         .expect("cannot resolve include files");
 
@@ -410,6 +640,7 @@ mod tests {
                 "#,
             ),
             ShaderKind::Vertex,
+            "main",
         )
         .expect("cannot resolve include files");
 
@@ -439,6 +670,7 @@ mod tests {
                 void main() {}
             "#,
             ShaderKind::Vertex,
+            "main",
         )
         .expect("cannot resolve include files");
 
@@ -476,6 +708,7 @@ mod tests {
             Path::new(""),
             need_defines,
             ShaderKind::Vertex,
+            "main",
         );
         assert!(compile_no_defines.is_err());
 
@@ -488,10 +721,96 @@ mod tests {
             Path::new(""),
             need_defines,
             ShaderKind::Vertex,
+            "main",
         )
         .expect("setting shader macros did not work");
     }
 
+    #[test]
+    fn hlsl_compile() {
+        let (artifact, _) = compile(
+            &MacroInput {
+                source_language: SourceLanguage::Hlsl,
+                ..MacroInput::empty()
+            },
+            None,
+            Path::new(""),
+            r#"
+                struct VSOutput {
+                    float4 position : SV_Position;
+                };
+
+                VSOutput VSMain(float3 position : POSITION) {
+                    VSOutput output;
+                    output.position = float4(position, 1.0);
+                    return output;
+                }
+            "#,
+            ShaderKind::Vertex,
+            "VSMain",
+        )
+        .expect("compiling HLSL failed");
+
+        let spirv = Spirv::new(artifact.as_binary()).unwrap();
+        assert_eq!(reflect::entry_points(&spirv).count(), 1);
+    }
+
+    #[cfg(feature = "wgsl")]
+    #[test]
+    fn wgsl_compile() {
+        let words = compile_wgsl(
+            &MacroInput {
+                source_language: SourceLanguage::Wgsl,
+                ..MacroInput::empty()
+            },
+            r#"
+                @group(0) @binding(0)
+                var<storage, read_write> data: array<u32>;
+
+                @compute @workgroup_size(64)
+                fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+                    data[id.x] *= 2u;
+                }
+            "#,
+            ShaderKind::Compute,
+            "main",
+        )
+        .expect("compiling WGSL failed");
+
+        let spirv = Spirv::new(&words).unwrap();
+        assert_eq!(reflect::entry_points(&spirv).count(), 1);
+    }
+
+    #[cfg(feature = "dxc")]
+    #[test]
+    fn dxc_compile() {
+        let words = compile_dxc(
+            &MacroInput {
+                source_language: SourceLanguage::Hlsl,
+                hlsl_compiler: crate::HlslCompiler::Dxc,
+                shader_model: Some((6, 6)),
+                ..MacroInput::empty()
+            },
+            None,
+            Path::new(""),
+            r#"
+                [[vk::binding(0, 0)]]
+                RWStructuredBuffer<uint> data;
+
+                [numthreads(64, 1, 1)]
+                void CSMain(uint3 id : SV_DispatchThreadID) {
+                    data[id.x] *= 2;
+                }
+            "#,
+            ShaderKind::Compute,
+            "CSMain",
+        )
+        .expect("compiling HLSL with DXC failed");
+
+        let spirv = Spirv::new(&words).unwrap();
+        assert_eq!(reflect::entry_points(&spirv).count(), 1);
+    }
+
     /// `entrypoint1.frag.glsl`:
     /// ```glsl
     /// #version 450
@@ -602,6 +921,7 @@ mod tests {
             LitStr::new("../tests/multiple_entrypoints.spv", Span::call_site()),
             String::new(),
             &insts,
+            None,
             Vec::new(),
             &mut type_registry,
         )
@@ -673,6 +993,7 @@ mod tests {
                 }
             "#,
             ShaderKind::Vertex,
+            "main",
         )
         .unwrap()
     }
@@ -712,6 +1033,7 @@ mod tests {
             ),
             String::new(),
             artifact.as_binary(),
+            None,
             Vec::new(),
             &mut type_registry,
         )
@@ -745,4 +1067,449 @@ mod tests {
             quote!({pub data: f32,}).to_string()
         );
     }
+
+    #[test]
+    fn reflect_descriptor_sets() {
+        let (artifact, _) = descriptor_calculation_with_multiple_functions_shader();
+
+        let mut type_registry = TypeRegistry::default();
+        let (shader_code, _) = reflect(
+            &MacroInput {
+                generate_descriptor_sets: true,
+                ..MacroInput::empty()
+            },
+            LitStr::new(
+                "descriptor_calculation_with_multiple_functions_shader",
+                Span::call_site(),
+            ),
+            String::new(),
+            artifact.as_binary(),
+            None,
+            Vec::new(),
+            &mut type_registry,
+        )
+        .expect("reflecting spv failed");
+
+        let file: File = syn::parse2(shader_code).unwrap();
+        let sets: Vec<_> = file
+            .items
+            .iter()
+            .filter_map(|item| {
+                if let Item::Struct(s) = item {
+                    Some(s)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        assert_eq!(sets.len(), 3);
+
+        let set1 = sets.iter().find(|s| s.ident == "Set1").unwrap();
+        assert_eq!(
+            set1.fields.to_token_stream().to_string(),
+            quote!({pub bo: ::vulkano::buffer::Subbuffer<Buffer>,}).to_string()
+        );
+
+        let set3 = sets.iter().find(|s| s.ident == "Set3").unwrap();
+        assert_eq!(
+            set3.fields.to_token_stream().to_string(),
+            quote!({
+                pub texture_sampler: ::std::sync::Arc<::vulkano::image::sampler::Sampler>,
+                pub image_texture: ::std::sync::Arc<::vulkano::image::view::ImageView>,
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn variants() {
+        let input: MacroInput = syn::parse_str(
+            r#"
+                ty: "compute",
+                variants: { SHADOWS: [0, 1], MSAA_SAMPLES: [1, 4] },
+                src: "
+                    #version 450
+                    #if !defined(SHADOWS) || !defined(MSAA_SAMPLES)
+                    #error the variant macros must be defined
+                    #endif
+
+                    layout(local_size_x = MSAA_SAMPLES) in;
+
+                    void main() {}
+                ",
+            "#,
+        )
+        .unwrap();
+
+        let permutations = crate::variants::permutations(&input);
+        assert_eq!(
+            permutations,
+            [
+                [("SHADOWS".to_owned(), 0), ("MSAA_SAMPLES".to_owned(), 1)],
+                [("SHADOWS".to_owned(), 0), ("MSAA_SAMPLES".to_owned(), 4)],
+                [("SHADOWS".to_owned(), 1), ("MSAA_SAMPLES".to_owned(), 1)],
+                [("SHADOWS".to_owned(), 1), ("MSAA_SAMPLES".to_owned(), 4)],
+            ],
+        );
+
+        let file: File = syn::parse2(crate::shader_inner(input).unwrap()).unwrap();
+
+        let functions: Vec<_> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Fn(f) => Some(f.sig.ident.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(functions, ["load", "load_variant"]);
+
+        let variant = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                Item::Struct(s) if s.ident == "Variant" => Some(s),
+                _ => None,
+            })
+            .unwrap();
+        let fields: Vec<_> = variant
+            .fields
+            .iter()
+            .map(|field| field.ident.as_ref().unwrap().to_string())
+            .collect();
+        assert_eq!(fields, ["shadows", "msaa_samples"]);
+    }
+
+    #[test]
+    fn verify_layouts() {
+        let input: MacroInput = syn::parse_str(
+            r#"
+                ty: "compute",
+                verify_layouts: { Params: crate::Params },
+                src: "
+                    #version 450
+
+                    layout(set = 0, binding = 0) uniform Params {
+                        vec3 direction;
+                        float intensity;
+                        mat4 transform;
+                    };
+
+                    void main() {}
+                ",
+            "#,
+        )
+        .unwrap();
+
+        let file: File = syn::parse2(crate::shader_inner(input).unwrap()).unwrap();
+        let checks: Vec<_> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Const(c) if c.ident == "_" => Some(c.to_token_stream().to_string()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].contains("crate :: Params"));
+        assert!(checks[0].contains("field `transform` of `crate::Params` must be at offset 16"));
+
+        let input: MacroInput = syn::parse_str(
+            r#"
+                ty: "compute",
+                verify_layouts: { Missing: crate::Missing },
+                src: "
+                    #version 450
+
+                    void main() {}
+                ",
+            "#,
+        )
+        .unwrap();
+
+        assert!(crate::shader_inner(input).is_err());
+    }
+
+    #[test]
+    fn reflect_specialization_constants() {
+        let (artifact, _) = compile(
+            &MacroInput::empty(),
+            None,
+            Path::new(""),
+            r#"
+                #version 450
+
+                layout(constant_id = 0) const uint workgroup_size = 64;
+                layout(constant_id = 2) const float scale = 1.5;
+                layout(constant_id = 5) const bool enable_clamp = true;
+
+                layout(local_size_x_id = 0) in;
+                layout(set = 0, binding = 0) buffer Data { float data[]; };
+
+                void main() {
+                    float value = data[gl_GlobalInvocationID.x] * scale;
+                    data[gl_GlobalInvocationID.x] = enable_clamp ? clamp(value, 0.0, 1.0) : value;
+                }
+            "#,
+            ShaderKind::Compute,
+            "main",
+        )
+        .unwrap();
+
+        let mut type_registry = TypeRegistry::default();
+        let (shader_code, _) = reflect(
+            &MacroInput::empty(),
+            LitStr::new("reflect_specialization_constants", Span::call_site()),
+            String::new(),
+            artifact.as_binary(),
+            None,
+            Vec::new(),
+            &mut type_registry,
+        )
+        .expect("reflecting spv failed");
+
+        let file: File = syn::parse2(shader_code).unwrap();
+        let constants = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                Item::Struct(s) if s.ident == "SpecializationConstants" => Some(s),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(
+            constants.fields.to_token_stream().to_string(),
+            quote!({
+                pub workgroup_size: u32,
+                pub scale: f32,
+                pub enable_clamp: bool,
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn reflect_unused_specialization_constants() {
+        let (artifact, _) = compile(
+            &MacroInput::empty(),
+            None,
+            Path::new(""),
+            r#"
+                #version 450
+
+                layout(constant_id = 0) const uint unused = 1;
+                layout(constant_id = 1) const uint array_len = 4;
+                layout(constant_id = 2) const float scale = 1.5;
+
+                layout(set = 0, binding = 0) buffer Data { float data[array_len]; };
+
+                float scaled(float value) {
+                    return value * scale;
+                }
+
+                void main() {
+                    data[0] = scaled(data[1]);
+                }
+            "#,
+            ShaderKind::Compute,
+            "main",
+        )
+        .unwrap();
+
+        let spirv = Spirv::new(artifact.as_binary()).unwrap();
+        let (entry_point, _) = reflect::entry_points(&spirv).next().unwrap();
+        let mut constants: Vec<_> =
+            reflect::entry_point_specialization_constants(&spirv, entry_point)
+                .into_iter()
+                .collect();
+        constants.sort_unstable();
+        assert_eq!(constants, [1, 2]);
+    }
+
+    #[test]
+    fn reflect_vertex_structs() {
+        let input = MacroInput {
+            generate_vertex_structs: true,
+            ..MacroInput::empty()
+        };
+        let (artifact, _) = compile(
+            &input,
+            None,
+            Path::new(""),
+            r#"
+                #version 450
+
+                layout(location = 0) in vec3 position;
+                layout(location = 1) in vec2 texCoord;
+                layout(location = 2) in uint material;
+                layout(location = 3) in mat4 instance_transform;
+
+                void main() {
+                    gl_Position = instance_transform * vec4(position, float(material))
+                        + vec4(texCoord, 0.0, float(gl_VertexIndex));
+                }
+            "#,
+            ShaderKind::Vertex,
+            "main",
+        )
+        .unwrap();
+
+        let mut type_registry = TypeRegistry::default();
+        let (shader_code, _) = reflect(
+            &input,
+            LitStr::new("reflect_vertex_structs", Span::call_site()),
+            String::new(),
+            artifact.as_binary(),
+            None,
+            Vec::new(),
+            &mut type_registry,
+        )
+        .expect("reflecting spv failed");
+
+        let file: File = syn::parse2(shader_code).unwrap();
+        let vertex = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                Item::Struct(s) if s.ident == "Vertex" => Some(s),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(
+            vertex.fields.to_token_stream().to_string(),
+            quote!({
+                #[format(R32G32B32_SFLOAT)]
+                pub position: [f32; 3usize],
+                #[format(R32G32_SFLOAT)]
+                #[name("texCoord")]
+                pub tex_coord: [f32; 2usize],
+                #[format(R32_UINT)]
+                pub material: u32,
+                #[format(R32G32B32A32_SFLOAT)]
+                pub instance_transform: [[f32; 4usize]; 4usize],
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn optimize() {
+        let input: MacroInput = syn::parse_str(
+            r#"
+                ty: "compute",
+                optimize: "size",
+                src: "
+                    #version 450
+
+                    layout(local_size_x = 64) in;
+
+                    layout(set = 0, binding = 0) buffer Data {
+                        float values[];
+                    } data;
+
+                    float square(float value) {
+                        return value * value;
+                    }
+
+                    void main() {
+                        uint index = gl_GlobalInvocationID.x;
+                        data.values[index] = square(data.values[index]);
+                    }
+                ",
+            "#,
+        )
+        .unwrap();
+
+        let file: File = syn::parse2(crate::shader_inner(input).unwrap()).unwrap();
+        let code = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                Item::Fn(f) if f.sig.ident == "code" => Some(f),
+                _ => None,
+            })
+            .unwrap();
+        assert!(code
+            .attrs
+            .iter()
+            .any(|attr| attr.to_token_stream().to_string().contains("spirv-opt")));
+
+        // The structs are generated from the unoptimized shader, which still has the names.
+        assert!(file.items.iter().any(|item| match item {
+            Item::Struct(s) => s.ident == "Data",
+            _ => false,
+        }));
+
+        let input: Result<MacroInput, _> = syn::parse_str(
+            r#"
+                ty: "compute",
+                optimize: "fast",
+                src: "void main() {}",
+            "#,
+        );
+        assert!(input.is_err());
+    }
+
+    #[test]
+    fn push_constants() {
+        let input: MacroInput = syn::parse_str(
+            r#"
+                ty: "vertex",
+                src: "
+                    #version 450
+
+                    layout(push_constant) uniform PushData {
+                        mat4 transform;
+                        vec4 color;
+                    } push_data;
+
+                    void main() {
+                        gl_Position = push_data.transform * push_data.color;
+                    }
+                ",
+            "#,
+        )
+        .unwrap();
+
+        let file: File = syn::parse2(crate::shader_inner(input).unwrap()).unwrap();
+        let range = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                Item::Const(c) if c.ident == "PUSH_CONSTANT_RANGE" => Some(c),
+                _ => None,
+            })
+            .unwrap();
+        let range = range.expr.to_token_stream().to_string();
+        assert!(range.contains("ShaderStages :: VERTEX"));
+        assert!(range.contains("offset : 0u32"));
+        assert!(range.contains("size : 80u32"));
+
+        let ext = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                Item::Trait(t) if t.ident == "PushConstantsExt" => Some(t),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(ext.items.len(), 1);
+
+        match &ext.items[0] {
+            TraitItem::Fn(f) => {
+                assert_eq!(f.sig.ident, "set_push_constants");
+                assert!(f
+                    .sig
+                    .inputs
+                    .to_token_stream()
+                    .to_string()
+                    .contains("push_constants : PushData"));
+            }
+            _ => panic!("expected a method"),
+        }
+    }
 }
@@ -0,0 +1,309 @@
+use crate::{codegen::Shader, structs, MacroInput};
+use ahash::HashMap;
+use heck::{ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::BTreeMap;
+use syn::Ident;
+use vulkano::{
+    descriptor_set::layout::DescriptorType,
+    shader::{
+        reflect,
+        spirv::{Decoration, Id, Instruction, StorageClass},
+        ShaderStage, ShaderStages,
+    },
+};
+
+struct Binding {
+    descriptor_type: DescriptorType,
+    descriptor_count: Option<u32>,
+    stages: ShaderStages,
+    name: Option<String>,
+}
+
+/// Writes a Rust struct for every descriptor set that is used by the shader, with a field for
+/// every binding in the set.
+pub(super) fn write_descriptor_sets(input: &MacroInput, shader: &Shader) -> TokenStream {
+    if !input.generate_descriptor_sets {
+        return TokenStream::new();
+    }
+
+    let mut sets: BTreeMap<u32, BTreeMap<u32, Binding>> = BTreeMap::new();
+
+    for (_, info) in reflect::entry_points(&shader.spirv) {
+        for (&(set, binding), reqs) in &info.descriptor_binding_requirements {
+            let entry = sets
+                .entry(set)
+                .or_default()
+                .entry(binding)
+                .or_insert_with(|| Binding {
+                    descriptor_type: reqs.descriptor_types[0],
+                    descriptor_count: reqs.descriptor_count,
+                    stages: ShaderStages::empty(),
+                    name: info.descriptor_binding_names.get(&(set, binding)).cloned(),
+                });
+            entry.stages |= reqs.stages;
+        }
+    }
+
+    let variables = descriptor_variables(shader);
+    let prefix = shader.name.to_upper_camel_case();
+
+    sets.into_iter()
+        .filter_map(|(set, bindings)| {
+            write_descriptor_set(input, shader, &variables, &prefix, set, &bindings)
+        })
+        .collect()
+}
+
+fn write_descriptor_set(
+    input: &MacroInput,
+    shader: &Shader,
+    variables: &HashMap<(u32, u32), Id>,
+    prefix: &str,
+    set: u32,
+    bindings: &BTreeMap<u32, Binding>,
+) -> Option<TokenStream> {
+    let struct_ident = format_ident!("{}Set{}", prefix, set);
+    let mut field_idents: Vec<Ident> = Vec::with_capacity(bindings.len());
+    let mut field_types = Vec::with_capacity(bindings.len());
+    let mut writes = Vec::with_capacity(bindings.len());
+    let mut layout_bindings = Vec::with_capacity(bindings.len());
+
+    for (&binding_num, binding) in bindings {
+        // Runtime-sized arrays need a variable descriptor count, and inline uniform blocks are
+        // written as raw bytes. Neither can be described by a plain struct field, so the whole
+        // set is left out.
+        let descriptor_count = binding.descriptor_count?;
+
+        if binding.descriptor_type == DescriptorType::InlineUniformBlock {
+            return None;
+        }
+
+        let field_ident = binding
+            .name
+            .as_deref()
+            .map(ToSnakeCase::to_snake_case)
+            .and_then(|name| syn::parse_str::<Ident>(&name).ok())
+            .filter(|ident| !field_idents.contains(ident))
+            .unwrap_or_else(|| format_ident!("binding{binding_num}"));
+
+        let buffer_contents = || {
+            variables
+                .get(&(set, binding_num))
+                .and_then(|&variable_id| block_ident(input, shader, variable_id))
+                .map_or_else(|| quote! { [u8] }, |ident| quote! { #ident })
+        };
+
+        let (element_type, write_single, write_array) = match binding.descriptor_type {
+            DescriptorType::Sampler => (
+                quote! { ::std::sync::Arc<::vulkano::image::sampler::Sampler> },
+                quote! { sampler },
+                quote! { sampler_array },
+            ),
+            DescriptorType::CombinedImageSampler => (
+                quote! {
+                    (
+                        ::std::sync::Arc<::vulkano::image::view::ImageView>,
+                        ::std::sync::Arc<::vulkano::image::sampler::Sampler>,
+                    )
+                },
+                quote! { image_view_sampler },
+                quote! { image_view_sampler_array },
+            ),
+            DescriptorType::SampledImage
+            | DescriptorType::StorageImage
+            | DescriptorType::InputAttachment => (
+                quote! { ::std::sync::Arc<::vulkano::image::view::ImageView> },
+                quote! { image_view },
+                quote! { image_view_array },
+            ),
+            DescriptorType::UniformTexelBuffer | DescriptorType::StorageTexelBuffer => (
+                quote! { ::std::sync::Arc<::vulkano::buffer::view::BufferView> },
+                quote! { buffer_view },
+                quote! { buffer_view_array },
+            ),
+            DescriptorType::UniformBuffer
+            | DescriptorType::StorageBuffer
+            | DescriptorType::UniformBufferDynamic
+            | DescriptorType::StorageBufferDynamic => {
+                let contents = buffer_contents();
+
+                (
+                    quote! { ::vulkano::buffer::Subbuffer<#contents> },
+                    quote! { buffer },
+                    quote! { buffer_array },
+                )
+            }
+            DescriptorType::AccelerationStructure => (
+                quote! {
+                    ::std::sync::Arc<::vulkano::acceleration_structure::AccelerationStructure>
+                },
+                quote! { acceleration_structure },
+                quote! { acceleration_structure_array },
+            ),
+            _ => return None,
+        };
+
+        let write = if descriptor_count == 1 {
+            field_types.push(element_type);
+
+            if binding.descriptor_type == DescriptorType::CombinedImageSampler {
+                quote! {
+                    ::vulkano::descriptor_set::WriteDescriptorSet::#write_single(
+                        #binding_num,
+                        self.#field_ident.0,
+                        self.#field_ident.1,
+                    )
+                }
+            } else {
+                quote! {
+                    ::vulkano::descriptor_set::WriteDescriptorSet::#write_single(
+                        #binding_num,
+                        self.#field_ident,
+                    )
+                }
+            }
+        } else {
+            let count = descriptor_count as usize;
+            field_types.push(quote! { [#element_type; #count] });
+
+            quote! {
+                ::vulkano::descriptor_set::WriteDescriptorSet::#write_array(
+                    #binding_num,
+                    0,
+                    self.#field_ident,
+                )
+            }
+        };
+        writes.push(write);
+
+        let descriptor_type = format_ident!("{:?}", binding.descriptor_type);
+        let stages = binding.stages.into_iter().map(|stage: ShaderStage| {
+            let stage = format_ident!("{}", format!("{stage:?}").to_shouty_snake_case());
+            quote! { ::vulkano::shader::ShaderStages::#stage }
+        });
+
+        layout_bindings.push(quote! {
+            (
+                #binding_num,
+                ::vulkano::descriptor_set::layout::DescriptorSetLayoutBinding {
+                    descriptor_count: #descriptor_count,
+                    stages: ::vulkano::shader::ShaderStages::empty() #( | #stages )*,
+                    ..::vulkano::descriptor_set::layout::DescriptorSetLayoutBinding::descriptor_type(
+                        ::vulkano::descriptor_set::layout::DescriptorType::#descriptor_type,
+                    )
+                },
+            )
+        });
+
+        field_idents.push(field_ident);
+    }
+
+    let doc = format!(
+        "The resources that are bound to descriptor set {set}, with one field per binding."
+    );
+
+    Some(quote! {
+        #[doc = #doc]
+        #[allow(non_camel_case_types, non_snake_case)]
+        pub struct #struct_ident {
+            #( pub #field_idents: #field_types, )*
+        }
+
+        #[allow(unused)]
+        impl #struct_ident {
+            /// The number of the descriptor set.
+            pub const SET: u32 = #set;
+
+            /// Returns the writes that bind every resource in `self` to its binding.
+            pub fn into_writes(
+                self,
+            ) -> ::std::vec::Vec<::vulkano::descriptor_set::WriteDescriptorSet> {
+                ::std::vec![ #( #writes ),* ]
+            }
+
+            /// Returns the create info of a descriptor set layout that is compatible with the
+            /// shader.
+            pub fn layout_create_info(
+            ) -> ::vulkano::descriptor_set::layout::DescriptorSetLayoutCreateInfo {
+                ::vulkano::descriptor_set::layout::DescriptorSetLayoutCreateInfo {
+                    bindings: [ #( #layout_bindings ),* ].into_iter().collect(),
+                    ..::std::default::Default::default()
+                }
+            }
+
+            /// Creates a descriptor set layout that is compatible with the shader.
+            pub fn layout(
+                device: ::std::sync::Arc<::vulkano::device::Device>,
+            ) -> ::std::result::Result<
+                ::std::sync::Arc<::vulkano::descriptor_set::layout::DescriptorSetLayout>,
+                ::vulkano::Validated<::vulkano::VulkanError>,
+            > {
+                ::vulkano::descriptor_set::layout::DescriptorSetLayout::new(
+                    device,
+                    Self::layout_create_info(),
+                )
+            }
+        }
+    })
+}
+
+/// Returns the descriptor variables of the shader, by set and binding number.
+fn descriptor_variables(shader: &Shader) -> HashMap<(u32, u32), Id> {
+    shader
+        .spirv
+        .global_variables()
+        .iter()
+        .filter_map(|instruction| match *instruction {
+            Instruction::Variable {
+                result_id,
+                storage_class:
+                    StorageClass::StorageBuffer | StorageClass::Uniform | StorageClass::UniformConstant,
+                ..
+            } => {
+                let mut set = None;
+                let mut binding = None;
+
+                for instruction in shader.spirv.id(result_id).decorations() {
+                    match *instruction {
+                        Instruction::Decorate {
+                            decoration: Decoration::DescriptorSet { descriptor_set },
+                            ..
+                        } => set = Some(descriptor_set),
+                        Instruction::Decorate {
+                            decoration: Decoration::Binding { binding_point },
+                            ..
+                        } => binding = Some(binding_point),
+                        _ => (),
+                    }
+                }
+
+                Some(((set?, binding?), result_id))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the identifier of the generated struct for the block that `variable_id` points to.
+fn block_ident(input: &MacroInput, shader: &Shader, variable_id: Id) -> Option<Ident> {
+    let mut type_id = match *shader.spirv.id(variable_id).instruction() {
+        Instruction::Variable { result_type_id, .. } => {
+            match *shader.spirv.id(result_type_id).instruction() {
+                Instruction::TypePointer { ty, .. } => ty,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    while let Instruction::TypeArray { element_type, .. }
+    | Instruction::TypeRuntimeArray { element_type, .. } =
+        *shader.spirv.id(type_id).instruction()
+    {
+        type_id = element_type;
+    }
+
+    structs::struct_ident(input, shader, type_id)
+}
@@ -37,12 +37,35 @@
 //!   `Result<Arc<ShaderModule>, Validated<VulkanError>>`. Before doing so, it checks every
 //!   capability instruction in the shader data, verifying that the passed-in `Device` has the
 //!   appropriate features enabled.
+//! - The `code` function, which returns the SPIR-V code of the shader as a `&'static [u32]`. This
+//!   can be passed to [`ShaderRequirements::new`] before the device is created, to find out which
+//!   device extensions and features have to be enabled for the shader to be loaded.
 //! - If the `shaders` option is used, then instead of one `load` constructor, there is one for
 //!   each shader. They are named based on the provided names, `load_first`, `load_second` etc.
+//!   The same goes for `code`, which becomes `code_first`, `code_second` etc.
 //! - A Rust struct translated from each struct contained in the shader data. By default, each
 //!   structure has a `Clone` and a `Copy` implementation. This behavior could be customized
 //!   through the `custom_derives` macro option (see below for details). Each struct also has an
 //!   implementation of [`BufferContents`], so that it can be read from/written to a buffer.
+//! - If the shader has specialization constants, a `SpecializationConstants` struct with one
+//!   field per constant, named and typed after the constant in the shader, whose `Default`
+//!   implementation returns the values given in the shader. It implements the
+//!   [`SpecializationConstants`] trait, and its `entry_point` and `stage_create_info` methods
+//!   specialize a `ShaderModule` and return the entry point or the create info of a pipeline
+//!   shader stage. Unlike passing a map of constant IDs, providing a value for a constant that the
+//!   module doesn't have is an error. If the shader has several entry points, there is one struct
+//!   per entry point, with the name of the entry point prepended, and with `shaders`, the name of
+//!   the entry is prepended too, for example `VsSpecializationConstants`. Each of these structs
+//!   only has fields for the constants that its entry point uses.
+//! - If the shader uses push constants, a `PUSH_CONSTANT_RANGE` constant with the offset, size and
+//!   stage of the range that the entry point uses, and a `PushConstantsExt` trait that is
+//!   implemented for [`RecordingCommandBuffer`]. Its `set_push_constants` method takes the struct
+//!   generated for the push constant block, and calls the command buffer's
+//!   `push_constants_for_range` method, which checks the range against the layout of the bound
+//!   pipeline instead of trusting a hand-written offset. As with specialization constants, the
+//!   names of the entry point and of the `shaders` entry are added when needed, for example
+//!   `VS_PUSH_CONSTANT_RANGE`, `VsPushConstantsExt` and `set_vs_push_constants`. These are only
+//!   generated along with the structs.
 //!
 //! All of these generated items will be accessed through the module where the macro was invoked.
 //! If you wanted to store the `ShaderModule` in a struct of your own, you could do something like
@@ -119,12 +142,57 @@
 //! Provides the path to the GLSL source to be compiled, relative to your `Cargo.toml`. Cannot be
 //! used in conjunction with the `src` or `bytes` field.
 //!
+//! ## `entry_point: "..."`
+//!
+//! Specifies the name of the entry point function in the source. Defaults to `main`. This is
+//! mostly useful for HLSL, where a single source file commonly contains several entry points.
+//! When using the `shaders` option, each entry can specify its own `entry_point`. Cannot be used
+//! in conjunction with the `bytes` or `bytes_env` field.
+//!
 //! ## `bytes: "..."`
 //!
 //! Provides the path to precompiled SPIR-V bytecode, relative to your `Cargo.toml`. Cannot be used
 //! in conjunction with the `src` or `path` field, and may also not specify a shader `ty` type.
 //! This allows using shaders compiled through a separate build system.
 //!
+//! ## `bytes_env: "..."`
+//!
+//! Like `bytes`, but the path to the SPIR-V binary is read from the given env variable when the
+//! macro is expanded. The path must be absolute. This is meant for SPIR-V that is produced by
+//! your build script, in particular by [rust-gpu]'s `spirv-builder`, which exports the path of
+//! each compiled shader crate through `cargo:rustc-env`:
+//!
+//! ```ignore
+//! // build.rs
+//! use spirv_builder::{MetadataPrintout, SpirvBuilder};
+//!
+//! fn main() {
+//!     SpirvBuilder::new("shaders", "spirv-unknown-vulkan1.2")
+//!         .print_metadata(MetadataPrintout::Full)
+//!         .build()
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! ```ignore
+//! // src/main.rs
+//! mod shaders {
+//!     vulkano_shaders::shader! {
+//!         bytes_env: "shaders.spv",
+//!     }
+//! }
+//!
+//! let module = shaders::load(device.clone())?;
+//! let vs = module.entry_point("main_vs").unwrap();
+//! let fs = module.entry_point("main_fs").unwrap();
+//! ```
+//!
+//! rust-gpu places every entry point of a shader crate in a single module, so the entry points
+//! are looked up by name with [`ShaderModule::entry_point`], or enumerated with
+//! [`ShaderModule::entry_points`]. The Rust structs are generated from the SPIR-V as usual, with
+//! the path separators in rust-gpu's fully qualified type names replaced by underscores (so
+//! `shaders::Material` becomes `shaders__Material`).
+//!
 //! ## `root_path_env: "..."`
 //!
 //! Instead of searching relative to your `Cargo.toml`, search relative to some other folder
@@ -147,7 +215,72 @@
 //! shaders. The macro checks that the source structs with the same names between different shaders
 //! have the same declaration signature, and throws a compile-time error if they don't.
 //!
-//! Each entry expects a `src`, `path`, `bytes`, and `ty` pairs same as above.
+//! Each entry expects a `src`, `path`, `bytes` or `bytes_env`, and `ty` pairs same as above, and
+//! optionally an `entry_point`. As for the single shader case, `ty` must not be given for `bytes`
+//! and `bytes_env`.
+//!
+//! ## `lang: "..."`
+//!
+//! Specifies the language of the `src` or `path` source code. It can be any of the following:
+//!
+//! - `glsl`
+//! - `hlsl`
+//! - `wgsl` (requires the `wgsl` feature)
+//!
+//! The default is `glsl`. By default, HLSL is compiled with the HLSL frontend of [shaderc], so no
+//! separate DXC installation is required. Resource bindings are taken from `register(...)` and
+//! `[[vk::binding(...)]]` annotations, and constant buffers use HLSL packing rules. For
+//! Vulkan-specific HLSL extensions and newer language features that only DXC supports, use
+//! `compiler: "dxc"`. The generated loaders and Rust structs are the same as for GLSL.
+//!
+//! WGSL is compiled with [naga], which makes it possible to share shaders with wgpu-based tooling.
+//! Only the `vertex`, `fragment` and `compute` shader types are supported, `include` and `define`
+//! have no effect, and the module is generated for the single entry point given by `ty` and
+//! `entry_point`.
+//!
+//! ```
+//! mod ps {
+//!     vulkano_shaders::shader! {
+//!         ty: "fragment",
+//!         lang: "hlsl",
+//!         entry_point: "PSMain",
+//!         src: r"
+//!             float4 PSMain() : SV_Target0 {
+//!                 return float4(1.0, 0.0, 0.0, 1.0);
+//!             }
+//!         ",
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ## `compiler: "..."`
+//!
+//! Specifies the compiler that is used for HLSL sources. It can be either `shaderc` (the default)
+//! or `dxc`, which requires the `dxc` feature. DXC is loaded at compile time from the
+//! `dxcompiler` shared library, which must be installed on the machine that builds your crate,
+//! for example as part of the Vulkan SDK. `optimize` is not supported with DXC, and included
+//! files are not tracked, so changing them doesn't trigger a recompilation.
+//!
+//! ## `shader_model: "..."`
+//!
+//! Specifies the HLSL shader model to compile for when using `compiler: "dxc"`, in the form
+//! `6_0` through `6_8`. Defaults to `6_0`. The target profile is derived from this and the shader
+//! type, for example `ps_6_6` for a fragment shader with `shader_model: "6_6"`. Ray tracing
+//! shader types are compiled as libraries and require at least shader model `6_3`.
+//!
+//! ```ignore
+//! mod cs {
+//!     vulkano_shaders::shader! {
+//!         ty: "compute",
+//!         lang: "hlsl",
+//!         compiler: "dxc",
+//!         shader_model: "6_6",
+//!         entry_point: "CSMain",
+//!         path: "shaders/blur.hlsl",
+//!     }
+//! }
+//! ```
 //!
 //! ## `include: ["...", "...", ...]`
 //!
@@ -163,6 +296,50 @@
 //! Adds the given macro definitions to the pre-processor. This is equivalent to passing the
 //! `-DNAME=VALUE` argument on the command line.
 //!
+//! ## `variants: { NAME: [VALUE, ...], ... }`
+//!
+//! Compiles a separate version of the shader for every combination of the given macro values,
+//! which is useful for ubershaders that are specialized with `#if` directives. Each name is
+//! defined as a pre-processor macro in the same way as with `define`, and the values must be
+//! non-negative integers. All combinations are compiled when your crate is built, so a mistake in
+//! any of them is reported as a compile error.
+//!
+//! The macro generates a `Variant` struct with a `u32` field for every name, converted to snake
+//! case, and a `load_variant` function next to `load`. When using `shaders`, the function is named
+//! after each entry, for example `load_first_variant`. The `load` function, the generated Rust
+//! structs, and `Variant::default` use the first value listed for each name. `Variant::ALL`
+//! lists every combination. Passing a combination that wasn't compiled returns an error.
+//!
+//! ```
+//! mod fs {
+//!     vulkano_shaders::shader! {
+//!         ty: "fragment",
+//!         variants: { SHADOWS: [0, 1], MSAA: [1, 4] },
+//!         src: r"
+//!             #version 450
+//!
+//!             layout(location = 0) out vec4 f_color;
+//!
+//!             void main() {
+//!             #if SHADOWS
+//!                 f_color = vec4(0.5 / MSAA, 0.0, 0.0, 1.0);
+//!             #else
+//!                 f_color = vec4(1.0, 0.0, 0.0, 1.0);
+//!             #endif
+//!             }
+//!         ",
+//!     }
+//! }
+//!
+//! # fn load(device: std::sync::Arc<vulkano::device::Device>) {
+//! let module = fs::load_variant(device, fs::Variant { shadows: 1, msaa: 4 }).unwrap();
+//! # }
+//! # fn main() {}
+//! ```
+//!
+//! Variants can't be combined with `bytes`, `bytes_env` or WGSL sources. Since every combination
+//! is embedded in the binary, the number of variants grows quickly with the number of names.
+//!
 //! ## `vulkan_version: "major.minor"` and `spirv_version: "major.minor"`
 //!
 //! Sets the Vulkan and SPIR-V versions to compile into, respectively. These map directly to the
@@ -172,6 +349,17 @@
 //! The generated code must be supported by the device at runtime. If not, then an error will be
 //! returned when calling `load`.
 //!
+//! ## `optimize: "performance"` or `optimize: "size"`
+//!
+//! Runs the optimizer of [SPIRV-Tools] (`spirv-opt`) on the compiled code, with the passes that
+//! favor performance or code size respectively. Unoptimized SPIR-V makes some drivers, mobile
+//! drivers in particular, take noticeably longer to compile pipelines. The change in size is
+//! reported in the documentation of the generated `code` function.
+//!
+//! The Rust code that the macro generates is reflected from the unoptimized code, since the
+//! optimizer may remove names and unused resources. Precompiled SPIR-V binaries and WGSL sources
+//! can't be optimized.
+//!
 //! ## `generate_structs: true`
 //!
 //! Generate rust structs that represent the structs contained in the shader. They all implement
@@ -179,6 +367,68 @@
 //! the layout of the struct manually. However, some use-cases, such as Rust-GPU, may not have any
 //! use for such structs, and may choose to disable them.
 //!
+//! ## `generate_descriptor_sets: true`
+//!
+//! Generate a Rust struct for every descriptor set that the shader uses, with one field per
+//! binding, typed after the resource that the binding expects. Disabled by default. For example,
+//! a shader with a uniform buffer `scene` of type `SceneData` at binding 0 and a combined image
+//! sampler `albedo` at binding 1 of set 0 gets the following struct:
+//!
+//! ```ignore
+//! pub struct Set0 {
+//!     pub scene: Subbuffer<SceneData>,
+//!     pub albedo: (Arc<ImageView>, Arc<Sampler>),
+//! }
+//! ```
+//!
+//! Each struct has an `into_writes` method that returns the [`WriteDescriptorSet`]s for its
+//! resources, and `layout_create_info` and `layout` functions that describe a descriptor set
+//! layout compatible with the shader. Forgetting a binding or passing the wrong kind of resource
+//! then becomes a compile error rather than a validation error when the descriptor set is created.
+//!
+//! The fields are named after the variables in the shader, and fall back to `bindingN`. Fixed-size
+//! arrays of descriptors become Rust arrays. When using `shaders`, the name of each entry is
+//! prepended to the struct name, for example `VsSet0`. Descriptor sets that contain a
+//! runtime-sized array or an inline uniform block are not generated. When `generate_structs` is
+//! disabled, buffers are typed as `Subbuffer<[u8]>`.
+//!
+//! ## `generate_vertex_structs: true`
+//!
+//! Generate a Rust struct for every vertex shader entry point, with one field per input variable,
+//! that derives [`BufferContents`] and [`Vertex`] so that it can be used as the vertex type of a
+//! vertex buffer. Disabled by default. The CPU-side vertex layout then always matches the inputs
+//! of the shader, and `Vertex::per_vertex().definition(&entry_point)` can't fail because of a
+//! mismatch. For example, a vertex shader with the following inputs:
+//!
+//! ```glsl
+//! layout(location = 0) in vec3 position;
+//! layout(location = 1) in vec2 texCoord;
+//! ```
+//!
+//! gets the following struct:
+//!
+//! ```ignore
+//! #[derive(BufferContents, Vertex, Clone, Copy)]
+//! #[repr(C)]
+//! pub struct Vertex {
+//!     #[format(R32G32B32_SFLOAT)]
+//!     pub position: [f32; 3],
+//!     #[format(R32G32_SFLOAT)]
+//!     #[name("texCoord")]
+//!     pub tex_coord: [f32; 2],
+//! }
+//! ```
+//!
+//! The fields are ordered by location and named after the variables in the shader, converted to
+//! snake case. Every input must therefore have a name, and built-in inputs such as
+//! `gl_VertexIndex` are left out. Each field uses the 32-bit or 64-bit format with the number of
+//! components of the input, and arrays and matrices become arrays that occupy consecutive
+//! locations. The derives listed in `custom_derives` are added as well. When using `shaders`, the
+//! name of each entry is prepended to the struct name, for example `VsVertex`, and when a shader
+//! has several vertex entry points, so is the name of the entry point. Since all fields are taken
+//! from a single buffer binding, use the generated struct as a starting point if the vertex data
+//! is split over several buffers.
+//!
 //! ## `custom_derives: [Clone, Default, PartialEq, ...]`
 //!
 //! Extends the list of derive macros that are added to the `derive` attribute of Rust structs that
@@ -188,6 +438,28 @@
 //! none of the derives are applied on the struct, except [`BufferContents`], which is always
 //! derived.
 //!
+//! ## `verify_layouts: { ShaderStruct: path::to::RustType, ... }`
+//!
+//! Checks at compile time that Rust types that you wrote yourself have the same layout as structs
+//! in the shader, such as uniform blocks, storage blocks and push constant blocks. For each entry,
+//! every member of the shader struct must have a field with the same name in the Rust type, at
+//! the same offset that the shader uses and with at least the same size, and the Rust type must be
+//! at least as large as the shader struct. Otherwise, compilation fails with an error naming the
+//! mismatching field and the offset that it must have:
+//!
+//! ```ignore
+//! vulkano_shaders::shader! {
+//!     ty: "vertex",
+//!     path: "shaders/scene.vert",
+//!     verify_layouts: { SceneData: crate::SceneData },
+//! }
+//! ```
+//!
+//! Only the members of the listed structs are checked; nested structs must be listed separately.
+//! Structs that contain a runtime-sized array can't be verified. If you'd rather not maintain the
+//! padding yourself, the structs generated with `generate_structs` already match the layout that
+//! the shader uses.
+//!
 //! ## `linalg_type: "..."`
 //!
 //! Specifies the way that linear algebra types should be generated. It can be any of the
@@ -214,14 +486,28 @@
 //! |-----------------------------|---------------------------------------------------------|
 //! | `shaderc-build-from-source` | Build the `shaderc` library from source when compiling. |
 //! | `shaderc-debug`             | Compile shaders with debug information included.        |
+//! | `wgsl`                      | Enable `lang: "wgsl"` using [naga].                     |
+//! | `dxc`                       | Enable `compiler: "dxc"` for HLSL using [hassle-rs].    |
 //!
 //! [`cargo-env-vars`]: https://doc.rust-lang.org/cargo/reference/environment-variables.html
+//! [shaderc]: https://github.com/google/shaderc
+//! [SPIRV-Tools]: https://github.com/KhronosGroup/SPIRV-Tools
+//! [naga]: https://crates.io/crates/naga
+//! [hassle-rs]: https://crates.io/crates/hassle-rs
 //! [cargo-expand]: https://github.com/dtolnay/cargo-expand
 //! [`ShaderModule`]: vulkano::shader::ShaderModule
+//! [`ShaderRequirements::new`]: vulkano::shader::ShaderRequirements::new
+//! [`WriteDescriptorSet`]: vulkano::descriptor_set::WriteDescriptorSet
+//! [`ShaderModule::entry_point`]: vulkano::shader::ShaderModule::entry_point
+//! [`ShaderModule::entry_points`]: vulkano::shader::ShaderModule::entry_points
+//! [rust-gpu]: https://github.com/Rust-GPU/rust-gpu
 //! [pipeline]: vulkano::pipeline
 //! [`set_target_env`]: shaderc::CompileOptions::set_target_env
 //! [`set_target_spirv`]: shaderc::CompileOptions::set_target_spirv
 //! [`BufferContents`]: vulkano::buffer::BufferContents
+//! [`Vertex`]: vulkano::pipeline::graphics::vertex_input::Vertex
+//! [`SpecializationConstants`]: vulkano::shader::SpecializationConstants
+//! [`RecordingCommandBuffer`]: vulkano::command_buffer::RecordingCommandBuffer
 
 #![doc(html_logo_url = "https://raw.githubusercontent.com/vulkano-rs/vulkano/master/logo.png")]
 #![recursion_limit = "1024"]
@@ -230,7 +516,7 @@ use crate::codegen::ShaderKind;
 use ahash::HashMap;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
-use shaderc::{EnvVersion, SpirvVersion};
+use shaderc::{EnvVersion, OptimizationLevel, SpirvVersion};
 use std::{
     env, fs, mem,
     path::{Path, PathBuf},
@@ -239,12 +525,17 @@ use structs::TypeRegistry;
 use syn::{
     braced, bracketed, parenthesized,
     parse::{Parse, ParseStream, Result},
-    parse_macro_input, parse_quote, Error, Ident, LitBool, LitStr, Path as SynPath, Token,
+    parse_macro_input, parse_quote, Error, Ident, LitBool, LitInt, LitStr, Path as SynPath, Token,
 };
 
 mod codegen;
+mod descriptor_sets;
+mod push_constants;
 mod rust_gpu;
+mod specialization_constants;
 mod structs;
+mod variants;
+mod vertex_input;
 
 #[proc_macro]
 pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -286,21 +577,41 @@ fn shader_inner(mut input: MacroInput) -> Result<TokenStream> {
     let mut types_code = Vec::with_capacity(shaders.len());
     let mut type_registry = TypeRegistry::default();
 
-    for (name, (shader_kind, source_kind)) in shaders {
+    for (name, (shader_kind, source_kind, entry_point)) in shaders {
+        let entry_point = entry_point
+            .as_ref()
+            .map_or("main".to_owned(), LitStr::value);
+
         let (code, types) = match source_kind {
             SourceKind::Src(source) => {
-                let (artifact, includes) = codegen::compile(
-                    &input,
+                let (words, unoptimized_words, includes) = compile_variants(
+                    &mut input,
+                    &source,
                     None,
                     root_path,
                     &source.value(),
                     shader_kind.unwrap(),
-                )
-                .map_err(|err| Error::new_spanned(&source, err))?;
-
-                let words = artifact.as_binary();
-
-                codegen::reflect(&input, source, name, words, includes, &mut type_registry)?
+                    &entry_point,
+                )?;
+                let mut words = words.into_iter();
+                let default_words = words.next().unwrap();
+                let variant_words: Vec<_> = words.collect();
+                let load_variant = variants::write_load_variant(&input, &name, &variant_words);
+
+                let (mut code, types) = codegen::reflect(
+                    &input,
+                    source,
+                    name,
+                    unoptimized_words.as_deref().unwrap_or(&default_words),
+                    unoptimized_words
+                        .is_some()
+                        .then_some(default_words.as_slice()),
+                    includes,
+                    &mut type_registry,
+                )?;
+                code.extend(load_variant);
+
+                (code, types)
             }
             SourceKind::Path(path) => {
                 let full_path = root_path.join(path.value());
@@ -316,20 +627,36 @@ fn shader_inner(mut input: MacroInput) -> Result<TokenStream> {
                 let source_code = fs::read_to_string(&full_path)
                     .or_else(|err| bail!(path, "failed to read source `{full_path:?}`: {err}"))?;
 
-                let (artifact, mut includes) = codegen::compile(
-                    &input,
+                let (words, unoptimized_words, mut includes) = compile_variants(
+                    &mut input,
+                    &path,
                     Some(path.value()),
                     root_path,
                     &source_code,
                     shader_kind.unwrap(),
-                )
-                .map_err(|err| Error::new_spanned(&path, err))?;
-
-                let words = artifact.as_binary();
+                    &entry_point,
+                )?;
+                let mut words = words.into_iter();
+                let default_words = words.next().unwrap();
+                let variant_words: Vec<_> = words.collect();
+                let load_variant = variants::write_load_variant(&input, &name, &variant_words);
 
                 includes.push(full_path.into_os_string().into_string().unwrap());
 
-                codegen::reflect(&input, path, name, words, includes, &mut type_registry)?
+                let (mut code, types) = codegen::reflect(
+                    &input,
+                    path,
+                    name,
+                    unoptimized_words.as_deref().unwrap_or(&default_words),
+                    unoptimized_words
+                        .is_some()
+                        .then_some(default_words.as_slice()),
+                    includes,
+                    &mut type_registry,
+                )?;
+                code.extend(load_variant);
+
+                (code, types)
             }
             SourceKind::Bytes(path) => {
                 let full_path = root_path.join(path.value());
@@ -342,13 +669,50 @@ fn shader_inner(mut input: MacroInput) -> Result<TokenStream> {
                     );
                 }
 
-                let bytes = fs::read(&full_path)
-                    .or_else(|err| bail!(path, "failed to read source `{full_path:?}`: {err}"))?;
+                let words = read_spirv(&path, &full_path)?;
+                let includes = vec![full_path.into_os_string().into_string().unwrap()];
 
-                let words = vulkano::shader::spirv::bytes_to_words(&bytes)
-                    .or_else(|err| bail!(path, "failed to read source `{full_path:?}`: {err}"))?;
+                codegen::reflect(
+                    &input,
+                    path,
+                    name,
+                    &words,
+                    None,
+                    includes,
+                    &mut type_registry,
+                )?
+            }
+            SourceKind::BytesEnv(env_name) => {
+                let full_path = match env::var_os(env_name.value()) {
+                    Some(full_path) => PathBuf::from(full_path),
+                    None => bail!(
+                        env_name,
+                        "failed to get the path to the SPIR-V binary from the env variable \
+                        `{}`, make sure that it is set by your build script",
+                        env_name.value(),
+                    ),
+                };
 
-                codegen::reflect(&input, path, name, &words, Vec::new(), &mut type_registry)?
+                if !full_path.is_file() {
+                    bail!(
+                        env_name,
+                        "file `{full_path:?}` specified by the env variable `{}` was not found",
+                        env_name.value(),
+                    );
+                }
+
+                let words = read_spirv(&env_name, &full_path)?;
+                let includes = vec![full_path.into_os_string().into_string().unwrap()];
+
+                codegen::reflect(
+                    &input,
+                    env_name,
+                    name,
+                    &words,
+                    None,
+                    includes,
+                    &mut type_registry,
+                )?
             }
         };
 
@@ -356,8 +720,21 @@ fn shader_inner(mut input: MacroInput) -> Result<TokenStream> {
         types_code.push(types);
     }
 
+    for (name, _) in &input.verify_layouts {
+        if !type_registry.is_verified(name) {
+            bail!(
+                name,
+                "no struct named `{name}` with an explicit layout was found in the shaders; only \
+                structs that are used in buffers or push constants can be verified",
+            );
+        }
+    }
+
+    let variant_struct = variants::write_variant_struct(&input);
+
     let result = quote! {
         #( #shaders_code )*
+        #variant_struct
         #( #types_code )*
     };
 
@@ -369,21 +746,127 @@ fn shader_inner(mut input: MacroInput) -> Result<TokenStream> {
     Ok(result)
 }
 
+/// Compiles the source code once for every variant, in the order returned by
+/// [`variants::permutations`]. If no variants are declared, the code is compiled once.
+///
+/// When optimizing, the default variant is also compiled without optimizations. This code is
+/// returned separately, to be reflected.
+fn compile_variants(
+    input: &mut MacroInput,
+    span: &LitStr,
+    path: Option<String>,
+    root_path: &Path,
+    code: &str,
+    shader_kind: ShaderKind,
+    entry_point: &str,
+) -> Result<(Vec<Vec<u32>>, Option<Vec<u32>>, Vec<String>)> {
+    let permutations = variants::permutations(input);
+    let mut all_words = Vec::with_capacity(permutations.len());
+    let mut unoptimized_words = None;
+    let mut all_includes: Vec<String> = Vec::new();
+
+    for permutation in permutations {
+        let macro_defines_len = input.macro_defines.len();
+        input.macro_defines.extend(
+            permutation
+                .into_iter()
+                .map(|(name, value)| (name, value.to_string())),
+        );
+
+        let result = match input.source_language {
+            #[cfg(feature = "wgsl")]
+            SourceLanguage::Wgsl => codegen::compile_wgsl(input, code, shader_kind, entry_point)
+                .map(|words| (words, Vec::new())),
+            #[cfg(feature = "dxc")]
+            SourceLanguage::Hlsl if input.hlsl_compiler == HlslCompiler::Dxc => {
+                codegen::compile_dxc(
+                    input,
+                    path.as_deref(),
+                    root_path,
+                    code,
+                    shader_kind,
+                    entry_point,
+                )
+                .map(|words| (words, Vec::new()))
+            }
+            _ => codegen::compile_with_optimization(
+                input,
+                path.clone(),
+                root_path,
+                code,
+                shader_kind,
+                entry_point,
+                input.optimize.unwrap_or(OptimizationLevel::Zero),
+            )
+            .map(|(artifact, includes)| (artifact.as_binary().to_owned(), includes)),
+        };
+        let unoptimized_result = (input.optimize.is_some() && all_words.is_empty()).then(|| {
+            codegen::compile(
+                input,
+                path.clone(),
+                root_path,
+                code,
+                shader_kind,
+                entry_point,
+            )
+            .map(|(artifact, _)| artifact.as_binary().to_owned())
+        });
+
+        input.macro_defines.truncate(macro_defines_len);
+
+        let (words, includes) = result.map_err(|err| Error::new_spanned(span, err))?;
+
+        if let Some(result) = unoptimized_result {
+            unoptimized_words = Some(result.map_err(|err| Error::new_spanned(span, err))?);
+        }
+
+        for include in includes {
+            if !all_includes.contains(&include) {
+                all_includes.push(include);
+            }
+        }
+
+        all_words.push(words);
+    }
+
+    Ok((all_words, unoptimized_words, all_includes))
+}
+
+fn read_spirv(span: &LitStr, full_path: &Path) -> Result<Vec<u32>> {
+    let bytes = fs::read(full_path)
+        .or_else(|err| bail!(span, "failed to read source `{full_path:?}`: {err}"))?;
+
+    vulkano::shader::spirv::bytes_to_words(&bytes)
+        .map(|words| words.into_owned())
+        .or_else(|err| bail!(span, "failed to read source `{full_path:?}`: {err}"))
+}
+
 enum SourceKind {
     Src(LitStr),
     Path(LitStr),
     Bytes(LitStr),
+    BytesEnv(LitStr),
 }
 
 struct MacroInput {
     root_path_env: Option<LitStr>,
     include_directories: Vec<PathBuf>,
     macro_defines: Vec<(String, String)>,
-    shaders: HashMap<String, (Option<ShaderKind>, SourceKind)>,
+    variants: Vec<(Ident, Vec<u32>)>,
+    shaders: HashMap<String, (Option<ShaderKind>, SourceKind, Option<LitStr>)>,
+    source_language: SourceLanguage,
+    #[cfg_attr(not(feature = "dxc"), allow(dead_code))]
+    hlsl_compiler: HlslCompiler,
+    #[cfg_attr(not(feature = "dxc"), allow(dead_code))]
+    shader_model: Option<(u32, u32)>,
     spirv_version: Option<SpirvVersion>,
     vulkan_version: Option<EnvVersion>,
+    optimize: Option<OptimizationLevel>,
     generate_structs: bool,
+    generate_descriptor_sets: bool,
+    generate_vertex_structs: bool,
     custom_derives: Vec<SynPath>,
+    verify_layouts: Vec<(Ident, SynPath)>,
     linalg_type: LinAlgType,
     dump: LitBool,
 }
@@ -395,11 +878,19 @@ impl MacroInput {
             root_path_env: None,
             include_directories: Vec::new(),
             macro_defines: Vec::new(),
+            variants: Vec::new(),
             shaders: HashMap::default(),
+            source_language: SourceLanguage::Glsl,
+            hlsl_compiler: HlslCompiler::Shaderc,
+            shader_model: None,
             vulkan_version: None,
             spirv_version: None,
+            optimize: None,
             generate_structs: true,
+            generate_descriptor_sets: false,
+            generate_vertex_structs: false,
             custom_derives: Vec::new(),
+            verify_layouts: Vec::new(),
             linalg_type: LinAlgType::default(),
             dump: LitBool::new(false, Span::call_site()),
         }
@@ -413,16 +904,24 @@ impl Parse for MacroInput {
         let mut root_path_env = None;
         let mut include_directories = Vec::new();
         let mut macro_defines = Vec::new();
+        let mut variants: Vec<(Ident, Vec<u32>)> = Vec::new();
         let mut shaders = HashMap::default();
+        let mut source_language = None;
+        let mut hlsl_compiler: Option<(LitStr, HlslCompiler)> = None;
+        let mut shader_model: Option<(LitStr, (u32, u32))> = None;
         let mut vulkan_version = None;
         let mut spirv_version = None;
+        let mut optimize: Option<(LitStr, OptimizationLevel)> = None;
         let mut generate_structs = None;
+        let mut generate_descriptor_sets = None;
+        let mut generate_vertex_structs = None;
         let mut custom_derives = None;
+        let mut verify_layouts: Vec<(Ident, SynPath)> = Vec::new();
         let mut linalg_type = None;
         let mut dump = None;
 
         fn parse_shader_fields(
-            output: &mut (Option<ShaderKind>, Option<SourceKind>),
+            output: &mut (Option<ShaderKind>, Option<SourceKind>, Option<LitStr>),
             name: &str,
             input: ParseStream<'_>,
         ) -> Result<()> {
@@ -461,18 +960,32 @@ impl Parse for MacroInput {
                     if output.1.is_some() {
                         bail!(
                             lit,
-                            "only one of `src`, `path`, or `bytes` can be defined per shader entry",
+                            "only one of `src`, `path`, `bytes` or `bytes_env` can be defined per \
+                            shader entry",
                         );
                     }
 
                     output.1 = Some(SourceKind::Bytes(lit));
                 }
+                "bytes_env" => {
+                    let lit = input.parse::<LitStr>()?;
+                    if output.1.is_some() {
+                        bail!(
+                            lit,
+                            "only one of `src`, `path`, `bytes` or `bytes_env` can be defined per \
+                            shader entry",
+                        );
+                    }
+
+                    output.1 = Some(SourceKind::BytesEnv(lit));
+                }
                 "path" => {
                     let lit = input.parse::<LitStr>()?;
                     if output.1.is_some() {
                         bail!(
                             lit,
-                            "only one of `src`, `path` or `bytes` can be defined per shader entry",
+                            "only one of `src`, `path`, `bytes` or `bytes_env` can be defined per \
+                            shader entry",
                         );
                     }
 
@@ -483,12 +996,21 @@ impl Parse for MacroInput {
                     if output.1.is_some() {
                         bail!(
                             lit,
-                            "only one of `src`, `path` or `bytes` can be defined per shader entry",
+                            "only one of `src`, `path`, `bytes` or `bytes_env` can be defined per \
+                            shader entry",
                         );
                     }
 
                     output.1 = Some(SourceKind::Src(lit));
                 }
+                "entry_point" => {
+                    let lit = input.parse::<LitStr>()?;
+                    if output.2.is_some() {
+                        bail!(lit, "field `entry_point` is already defined");
+                    }
+
+                    output.2 = Some(lit);
+                }
                 _ => unreachable!(),
             }
 
@@ -501,11 +1023,12 @@ impl Parse for MacroInput {
             let field = field_ident.to_string();
 
             match field.as_str() {
-                "bytes" | "src" | "path" | "ty" => {
+                "bytes" | "bytes_env" | "src" | "path" | "ty" | "entry_point" => {
                     if shaders.len() > 1 || (shaders.len() == 1 && !shaders.contains_key("")) {
                         bail!(
                             field_ident,
-                            "only one of `src`, `path`, `bytes` or `shaders` can be defined",
+                            "only one of `src`, `path`, `bytes`, `bytes_env` or `shaders` can be \
+                            defined",
                         );
                     }
 
@@ -515,7 +1038,8 @@ impl Parse for MacroInput {
                     if !shaders.is_empty() {
                         bail!(
                             field_ident,
-                            "only one of `src`, `path`, `bytes` or `shaders` can be defined",
+                            "only one of `src`, `path`, `bytes`, `bytes_env` or `shaders` can be \
+                            defined",
                         );
                     }
 
@@ -541,7 +1065,7 @@ impl Parse for MacroInput {
                             let field = field_ident.to_string();
 
                             match field.as_str() {
-                                "bytes" | "src" | "path" | "ty" => {
+                                "bytes" | "bytes_env" | "src" | "path" | "ty" | "entry_point" => {
                                     parse_shader_fields(
                                         shaders.entry(name.clone()).or_default(),
                                         &field,
@@ -550,8 +1074,8 @@ impl Parse for MacroInput {
                                 }
                                 field => bail!(
                                     field_ident,
-                                    "expected `bytes`, `src`, `path` or `ty` as a field, found \
-                                    `{field}`",
+                                    "expected `bytes`, `bytes_env`, `src`, `path`, `ty` or \
+                                    `entry_point` as a field, found `{field}`",
                                 ),
                             }
 
@@ -565,10 +1089,17 @@ impl Parse for MacroInput {
                         }
 
                         match shaders.get(&name).unwrap() {
-                            (None, _) => bail!(
+                            (None, Some(SourceKind::Bytes(_) | SourceKind::BytesEnv(_)), _) => (),
+                            (Some(_), Some(SourceKind::Bytes(_) | SourceKind::BytesEnv(_)), _) => {
+                                bail!(
+                                    "one may not specify a shader type for shader `{name}` when \
+                                    including precompiled SPIR-V binaries",
+                                )
+                            }
+                            (None, _, _) => bail!(
                                 "please specify a type for shader `{name}` e.g. `ty: \"vertex\"`",
                             ),
-                            (_, None) => bail!(
+                            (_, None, _) => bail!(
                                 "please specify a source for shader `{name}` e.g. \
                                 `path: \"entry_point.glsl\"`",
                             ),
@@ -598,6 +1129,117 @@ impl Parse for MacroInput {
                         }
                     }
                 }
+                "variants" => {
+                    if !variants.is_empty() {
+                        bail!(field_ident, "field `variants` is already defined");
+                    }
+
+                    let in_braces;
+                    braced!(in_braces in input);
+
+                    while !in_braces.is_empty() {
+                        let name_ident = in_braces.parse::<Ident>()?;
+
+                        if variants.iter().any(|(name, _)| *name == name_ident) {
+                            bail!(name_ident, "variant `{name_ident}` is already defined");
+                        }
+
+                        in_braces.parse::<Token![:]>()?;
+
+                        let in_brackets;
+                        bracketed!(in_brackets in in_braces);
+                        let mut values = Vec::new();
+
+                        while !in_brackets.is_empty() {
+                            let lit = in_brackets.parse::<LitInt>()?;
+                            let value = lit.base10_parse::<u32>()?;
+
+                            if values.contains(&value) {
+                                bail!(lit, "value `{value}` is already listed");
+                            }
+
+                            values.push(value);
+
+                            if !in_brackets.is_empty() {
+                                in_brackets.parse::<Token![,]>()?;
+                            }
+                        }
+
+                        if values.is_empty() {
+                            bail!(
+                                name_ident,
+                                "variant `{name_ident}` must have at least one value",
+                            );
+                        }
+
+                        variants.push((name_ident, values));
+
+                        if !in_braces.is_empty() {
+                            in_braces.parse::<Token![,]>()?;
+                        }
+                    }
+                }
+                "lang" => {
+                    let lit = input.parse::<LitStr>()?;
+                    if source_language.is_some() {
+                        bail!(lit, "field `lang` is already defined");
+                    }
+
+                    source_language = Some(match lit.value().as_str() {
+                        "glsl" => SourceLanguage::Glsl,
+                        "hlsl" => SourceLanguage::Hlsl,
+                        "wgsl" => SourceLanguage::Wgsl,
+                        lang => bail!(lit, "expected `glsl`, `hlsl` or `wgsl`, found `{lang}`"),
+                    });
+
+                    if source_language == Some(SourceLanguage::Wgsl) && cfg!(not(feature = "wgsl"))
+                    {
+                        bail!(
+                            lit,
+                            "WGSL support requires the `wgsl` feature of vulkano-shaders"
+                        );
+                    }
+                }
+                "compiler" => {
+                    let lit = input.parse::<LitStr>()?;
+                    if hlsl_compiler.is_some() {
+                        bail!(lit, "field `compiler` is already defined");
+                    }
+
+                    let compiler = match lit.value().as_str() {
+                        "shaderc" => HlslCompiler::Shaderc,
+                        "dxc" => HlslCompiler::Dxc,
+                        compiler => bail!(lit, "expected `shaderc` or `dxc`, found `{compiler}`"),
+                    };
+
+                    if compiler == HlslCompiler::Dxc && cfg!(not(feature = "dxc")) {
+                        bail!(
+                            lit,
+                            "compiling with DXC requires the `dxc` feature of vulkano-shaders",
+                        );
+                    }
+
+                    hlsl_compiler = Some((lit, compiler));
+                }
+                "shader_model" => {
+                    let lit = input.parse::<LitStr>()?;
+                    if shader_model.is_some() {
+                        bail!(lit, "field `shader_model` is already defined");
+                    }
+
+                    let value = lit.value();
+                    let version = value.split_once('_').and_then(|(major, minor)| {
+                        Some((major.parse().ok()?, minor.parse().ok()?))
+                    });
+
+                    match version {
+                        Some(version @ (6, 0..=8)) => shader_model = Some((lit, version)),
+                        _ => bail!(
+                            lit,
+                            "expected a shader model between `6_0` and `6_8`, found `{value}`",
+                        ),
+                    }
+                }
                 "root_path_env" => {
                     let lit = input.parse::<LitStr>()?;
                     if root_path_env.is_some() {
@@ -654,6 +1296,19 @@ impl Parse for MacroInput {
                         ),
                     });
                 }
+                "optimize" => {
+                    let lit = input.parse::<LitStr>()?;
+                    if optimize.is_some() {
+                        bail!(lit, "field `optimize` is already defined");
+                    }
+
+                    let optimization_level = match lit.value().as_str() {
+                        "performance" => OptimizationLevel::Performance,
+                        "size" => OptimizationLevel::Size,
+                        level => bail!(lit, "expected `performance` or `size`, found `{level}`"),
+                    };
+                    optimize = Some((lit, optimization_level));
+                }
                 "generate_structs" => {
                     let lit = input.parse::<LitBool>()?;
                     if generate_structs.is_some() {
@@ -661,6 +1316,20 @@ impl Parse for MacroInput {
                     }
                     generate_structs = Some(lit.value);
                 }
+                "generate_descriptor_sets" => {
+                    let lit = input.parse::<LitBool>()?;
+                    if generate_descriptor_sets.is_some() {
+                        bail!(lit, "field `generate_descriptor_sets` is already defined");
+                    }
+                    generate_descriptor_sets = Some(lit.value);
+                }
+                "generate_vertex_structs" => {
+                    let lit = input.parse::<LitBool>()?;
+                    if generate_vertex_structs.is_some() {
+                        bail!(lit, "field `generate_vertex_structs` is already defined");
+                    }
+                    generate_vertex_structs = Some(lit.value);
+                }
                 "custom_derives" => {
                     let in_brackets;
                     bracketed!(in_brackets in input);
@@ -680,6 +1349,33 @@ impl Parse for MacroInput {
                         }
                     }
                 }
+                "verify_layouts" => {
+                    if !verify_layouts.is_empty() {
+                        bail!(field_ident, "field `verify_layouts` is already defined");
+                    }
+
+                    let in_braces;
+                    braced!(in_braces in input);
+
+                    while !in_braces.is_empty() {
+                        let name_ident = in_braces.parse::<Ident>()?;
+
+                        if verify_layouts.iter().any(|(name, _)| *name == name_ident) {
+                            bail!(
+                                name_ident,
+                                "the layout of struct `{name_ident}` is already verified",
+                            );
+                        }
+
+                        in_braces.parse::<Token![:]>()?;
+                        let path = in_braces.parse::<SynPath>()?;
+                        verify_layouts.push((name_ident, path));
+
+                        if !in_braces.is_empty() {
+                            in_braces.parse::<Token![,]>()?;
+                        }
+                    }
+                }
                 "types_meta" => {
                     bail!(
                         field_ident,
@@ -713,9 +1409,11 @@ impl Parse for MacroInput {
                 }
                 field => bail!(
                     field_ident,
-                    "expected `bytes`, `src`, `path`, `ty`, `shaders`, `define`, `include`, \
-                    `vulkan_version`, `spirv_version`, `generate_structs`, `custom_derives`, \
-                    `linalg_type` or `dump` as a field, found `{field}`",
+                    "expected `bytes`, `bytes_env`, `src`, `path`, `ty`, `entry_point`, `shaders`, \
+                    `lang`, `compiler`, `shader_model`, `define`, `variants`, `include`, \
+                    `vulkan_version`, `spirv_version`, `optimize`, `generate_structs`, \
+                    `generate_descriptor_sets`, `generate_vertex_structs`, `custom_derives`, \
+                    `verify_layouts`, `linalg_type` or `dump` as a field, found `{field}`",
                 ),
             }
 
@@ -730,46 +1428,140 @@ impl Parse for MacroInput {
 
         match shaders.get("") {
             // if source is bytes, the shader type should not be declared
-            Some((None, Some(SourceKind::Bytes(_)))) => {}
-            Some((_, Some(SourceKind::Bytes(_)))) => {
+            Some((None, Some(SourceKind::Bytes(_) | SourceKind::BytesEnv(_)), _)) => {}
+            Some((_, Some(SourceKind::Bytes(_) | SourceKind::BytesEnv(_)), _)) => {
                 bail!(
                     r#"one may not specify a shader type when including precompiled SPIR-V binaries. Please remove the `ty:` declaration"#
                 );
             }
-            Some((None, _)) => {
+            Some((None, _, _)) => {
                 bail!(r#"please specify the type of the shader e.g. `ty: "vertex"`"#);
             }
-            Some((_, None)) => {
+            Some((_, None, _)) => {
                 bail!(r#"please specify the source of the shader e.g. `src: "<GLSL code>"`"#);
             }
             _ => {}
         }
 
+        for (_, source_kind, entry_point) in shaders.values() {
+            if let (Some(SourceKind::Bytes(_) | SourceKind::BytesEnv(_)), Some(lit)) =
+                (source_kind, entry_point)
+            {
+                bail!(
+                    lit,
+                    "`entry_point` can't be used with precompiled SPIR-V binaries, which may \
+                    contain any number of entry points; look them up by name with \
+                    `ShaderModule::entry_point` instead",
+                );
+            }
+        }
+
+        if let Some((lit, compiler)) = &hlsl_compiler {
+            if *compiler == HlslCompiler::Dxc && source_language != Some(SourceLanguage::Hlsl) {
+                bail!(
+                    lit,
+                    "DXC can only be used to compile HLSL, add `lang: \"hlsl\"`"
+                );
+            }
+        }
+
+        if let Some((lit, _)) = &shader_model {
+            if !matches!(hlsl_compiler, Some((_, HlslCompiler::Dxc))) {
+                bail!(
+                    lit,
+                    "the shader model can only be selected when compiling with DXC, add \
+                    `compiler: \"dxc\"`",
+                );
+            }
+        }
+
+        if let Some((name, _)) = variants.first() {
+            if source_language == Some(SourceLanguage::Wgsl) {
+                bail!(
+                    name,
+                    "variants are not supported for WGSL, which has no preprocessor"
+                );
+            }
+
+            if shaders.values().any(|(_, source_kind, _)| {
+                matches!(
+                    source_kind,
+                    Some(SourceKind::Bytes(_) | SourceKind::BytesEnv(_))
+                )
+            }) {
+                bail!(
+                    name,
+                    "variants can't be used with precompiled SPIR-V binaries"
+                );
+            }
+        }
+
+        if let Some((lit, _)) = &optimize {
+            if source_language == Some(SourceLanguage::Wgsl) {
+                bail!(lit, "optimizing is not supported for WGSL");
+            }
+
+            if matches!(hlsl_compiler, Some((_, HlslCompiler::Dxc))) {
+                bail!(lit, "optimizing is not supported when compiling with DXC");
+            }
+
+            if shaders.values().any(|(_, source_kind, _)| {
+                matches!(
+                    source_kind,
+                    Some(SourceKind::Bytes(_) | SourceKind::BytesEnv(_))
+                )
+            }) {
+                bail!(lit, "precompiled SPIR-V binaries can't be optimized");
+            }
+        }
+
         Ok(MacroInput {
             root_path_env,
             include_directories,
             macro_defines,
+            variants,
             shaders: shaders
                 .into_iter()
-                .map(|(key, (shader_kind, shader_source))| {
-                    (key, (shader_kind, shader_source.unwrap()))
+                .map(|(key, (shader_kind, shader_source, entry_point))| {
+                    (key, (shader_kind, shader_source.unwrap(), entry_point))
                 })
                 .collect(),
+            source_language: source_language.unwrap_or_default(),
+            hlsl_compiler: hlsl_compiler.map_or(HlslCompiler::Shaderc, |(_, compiler)| compiler),
+            shader_model: shader_model.map(|(_, version)| version),
             vulkan_version,
             spirv_version,
+            optimize: optimize.map(|(_, optimization_level)| optimization_level),
             generate_structs: generate_structs.unwrap_or(true),
+            generate_descriptor_sets: generate_descriptor_sets.unwrap_or(false),
+            generate_vertex_structs: generate_vertex_structs.unwrap_or(false),
             custom_derives: custom_derives.unwrap_or_else(|| {
                 vec![
                     parse_quote! { ::std::clone::Clone },
                     parse_quote! { ::std::marker::Copy },
                 ]
             }),
+            verify_layouts,
             linalg_type: linalg_type.unwrap_or_default(),
             dump: dump.unwrap_or_else(|| LitBool::new(false, Span::call_site())),
         })
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum SourceLanguage {
+    #[default]
+    Glsl,
+    Hlsl,
+    Wgsl,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HlslCompiler {
+    Shaderc,
+    Dxc,
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 enum LinAlgType {
     #[default]
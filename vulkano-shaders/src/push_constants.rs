@@ -0,0 +1,177 @@
+use crate::{codegen::Shader, structs, MacroInput};
+use heck::{ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+use vulkano::shader::{
+    reflect,
+    spirv::{ExecutionModel, Id, Instruction, StorageClass},
+    BlockInfo, ShaderStage,
+};
+
+/// Writes the push constant range of every entry point of the shader that uses push constants,
+/// and an extension trait for `RecordingCommandBuffer` with a method for every entry point that
+/// sets its push constants from the generated struct of the push constant block.
+pub(super) fn write_push_constants(input: &MacroInput, shader: &Shader) -> TokenStream {
+    let entry_points: Vec<_> = reflect::entry_points(&shader.spirv)
+        .map(|(_, info)| info)
+        .collect();
+    let mut ranges = Vec::new();
+    let mut method_signatures = Vec::new();
+    let mut method_impls = Vec::new();
+
+    for info in &entry_points {
+        let (range, block) = match (&info.push_constant_requirements, &info.push_constant_block) {
+            (Some(range), Some(block)) => (range, block),
+            _ => continue,
+        };
+
+        let struct_ident = match push_constant_struct_ident(input, shader, block) {
+            Some(struct_ident) => struct_ident,
+            None => continue,
+        };
+
+        // Ray tracing pipelines are not tracked by the command buffer.
+        let pipeline_bind_point = match info.execution_model {
+            ExecutionModel::GLCompute => quote! { Compute },
+            ExecutionModel::Vertex
+            | ExecutionModel::TessellationControl
+            | ExecutionModel::TessellationEvaluation
+            | ExecutionModel::Geometry
+            | ExecutionModel::Fragment
+            | ExecutionModel::TaskNV
+            | ExecutionModel::TaskEXT
+            | ExecutionModel::MeshNV
+            | ExecutionModel::MeshEXT => quote! { Graphics },
+            _ => continue,
+        };
+
+        let mut name = shader.name.clone();
+
+        if entry_points.len() > 1 {
+            name.push('_');
+            name.push_str(&info.name);
+        }
+
+        let (range_ident, method_ident) = if name.is_empty() {
+            (
+                format_ident!("PUSH_CONSTANT_RANGE"),
+                format_ident!("set_push_constants"),
+            )
+        } else {
+            (
+                format_ident!("{}_PUSH_CONSTANT_RANGE", name.to_shouty_snake_case()),
+                format_ident!("set_{}_push_constants", name.to_snake_case()),
+            )
+        };
+
+        let stage = ShaderStage::from(info.execution_model);
+        let stage = format_ident!("{}", format!("{stage:?}").to_shouty_snake_case());
+        let offset = range.offset;
+        let size = range.size;
+        let range_doc = format!(
+            "The range of the push constants that the `{}` entry point uses.",
+            info.name,
+        );
+        let method_doc = format!(
+            "Sets the push constants of the `{}` entry point, using the layout of the pipeline \
+            that is currently bound.",
+            info.name,
+        );
+
+        ranges.push(quote! {
+            #[doc = #range_doc]
+            pub const #range_ident: ::vulkano::pipeline::layout::PushConstantRange =
+                ::vulkano::pipeline::layout::PushConstantRange {
+                    stages: ::vulkano::shader::ShaderStages::#stage,
+                    offset: #offset,
+                    size: #size,
+                };
+        });
+
+        let signature = quote! {
+            fn #method_ident(
+                &mut self,
+                push_constants: #struct_ident,
+            ) -> ::std::result::Result<&mut Self, ::std::boxed::Box<::vulkano::ValidationError>>
+        };
+
+        method_signatures.push(quote! {
+            #[doc = #method_doc]
+            #signature;
+        });
+        method_impls.push(quote! {
+            #[inline]
+            #signature {
+                self.push_constants_for_range(
+                    ::vulkano::pipeline::PipelineBindPoint::#pipeline_bind_point,
+                    #range_ident,
+                    push_constants,
+                )
+            }
+        });
+    }
+
+    if ranges.is_empty() {
+        return TokenStream::new();
+    }
+
+    let trait_ident = format_ident!("{}PushConstantsExt", shader.name.to_upper_camel_case());
+
+    quote! {
+        #( #ranges )*
+
+        /// Extends `RecordingCommandBuffer` with methods that set the push constants of the entry
+        /// points of the shader, checking them against the layout of the bound pipeline.
+        pub trait #trait_ident {
+            #( #method_signatures )*
+        }
+
+        impl #trait_ident for ::vulkano::command_buffer::RecordingCommandBuffer {
+            #( #method_impls )*
+        }
+    }
+}
+
+/// Returns the identifier of the generated struct for the push constant block `block`.
+fn push_constant_struct_ident(
+    input: &MacroInput,
+    shader: &Shader,
+    block: &BlockInfo,
+) -> Option<Ident> {
+    let struct_ids: Vec<Id> = shader
+        .spirv
+        .global_variables()
+        .iter()
+        .filter_map(|instruction| match *instruction {
+            Instruction::Variable {
+                result_type_id,
+                storage_class: StorageClass::PushConstant,
+                ..
+            } => match *shader.spirv.id(result_type_id).instruction() {
+                Instruction::TypePointer { ty, .. } => Some(ty),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    let struct_id = match struct_ids.as_slice() {
+        &[struct_id] => struct_id,
+        struct_ids => *struct_ids.iter().find(|&&struct_id| {
+            shader
+                .spirv
+                .id(struct_id)
+                .names()
+                .iter()
+                .any(|instruction| {
+                    matches!(
+                        instruction,
+                        Instruction::Name { name, .. } if Some(name) == block.name.as_ref(),
+                    )
+                })
+        })?,
+    };
+
+    structs::struct_ident(input, shader, struct_id)
+}
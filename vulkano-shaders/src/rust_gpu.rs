@@ -20,6 +20,7 @@ mod tests {
             LitStr::new("rust-gpu vertex shader", Span::call_site()),
             String::new(),
             &insts,
+            None,
             Vec::new(),
             &mut type_registry,
         )
@@ -36,6 +37,7 @@ mod tests {
             LitStr::new("rust-gpu vertex shader", Span::call_site()),
             String::new(),
             &insts,
+            None,
             Vec::new(),
             &mut type_registry,
         )
@@ -0,0 +1,197 @@
+use crate::codegen::Shader;
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use std::collections::BTreeMap;
+use syn::Ident;
+use vulkano::shader::{reflect, EntryPointInfo, SpecializationConstant};
+
+/// Writes a Rust struct for every entry point of the shader, with a field for every
+/// specialization constant that the entry point uses.
+pub(super) fn write_specialization_constants(shader: &Shader) -> TokenStream {
+    let constants: BTreeMap<_, _> = reflect::specialization_constants(&shader.spirv)
+        .into_iter()
+        .collect();
+
+    if constants.is_empty() {
+        return TokenStream::new();
+    }
+
+    let names = reflect::specialization_constant_names(&shader.spirv);
+    let mut all_field_idents: Vec<Ident> = Vec::with_capacity(constants.len());
+    let mut fields = BTreeMap::new();
+
+    for (&constant_id, &default_value) in &constants {
+        let field_ident = names
+            .get(&constant_id)
+            .map(|name| name.to_snake_case())
+            .and_then(|name| syn::parse_str::<Ident>(&name).ok())
+            .filter(|ident| !all_field_idents.contains(ident))
+            .unwrap_or_else(|| format_ident!("constant_{constant_id}"));
+
+        let (field_type, default) = match default_value {
+            SpecializationConstant::Bool(value) => (quote! { bool }, quote! { #value }),
+            SpecializationConstant::U8(value) => (quote! { u8 }, quote! { #value }),
+            SpecializationConstant::U16(value) => (quote! { u16 }, quote! { #value }),
+            SpecializationConstant::U32(value) => (quote! { u32 }, quote! { #value }),
+            SpecializationConstant::U64(value) => (quote! { u64 }, quote! { #value }),
+            SpecializationConstant::I8(value) => (quote! { i8 }, quote! { #value }),
+            SpecializationConstant::I16(value) => (quote! { i16 }, quote! { #value }),
+            SpecializationConstant::I32(value) => (quote! { i32 }, quote! { #value }),
+            SpecializationConstant::I64(value) => (quote! { i64 }, quote! { #value }),
+            SpecializationConstant::F16(value) => {
+                let bits = value.to_bits();
+
+                (
+                    quote! { ::vulkano::half::f16 },
+                    quote! { ::vulkano::half::f16::from_bits(#bits) },
+                )
+            }
+            SpecializationConstant::F32(value) => {
+                let bits = value.to_bits();
+
+                (quote! { f32 }, quote! { f32::from_bits(#bits) })
+            }
+            SpecializationConstant::F64(value) => {
+                let bits = value.to_bits();
+
+                (quote! { f64 }, quote! { f64::from_bits(#bits) })
+            }
+        };
+
+        all_field_idents.push(field_ident.clone());
+        fields.insert(constant_id, (field_ident, field_type, default));
+    }
+
+    let (entry_point_ids, entry_points): (Vec<_>, Vec<_>) =
+        reflect::entry_points(&shader.spirv).unzip();
+    let prefix = shader.name.to_upper_camel_case();
+
+    entry_point_ids
+        .iter()
+        .zip(&entry_points)
+        .map(|(&entry_point_id, info)| {
+            let used_constants =
+                reflect::entry_point_specialization_constants(&shader.spirv, entry_point_id);
+            let mut constant_ids = Vec::new();
+            let mut field_idents = Vec::new();
+            let mut field_types = Vec::new();
+            let mut defaults = Vec::new();
+
+            for (constant_id, (field_ident, field_type, default)) in &fields {
+                if used_constants.contains(constant_id) {
+                    constant_ids.push(constant_id);
+                    field_idents.push(field_ident);
+                    field_types.push(field_type);
+                    defaults.push(default);
+                }
+            }
+
+            let struct_ident = struct_ident(&prefix, info, &entry_points);
+            let entry_point_name = &info.name;
+            let execution_model = format_ident!("{:?}", info.execution_model);
+            let doc = format!(
+                "The values of the specialization constants of the shader, for the `{}` entry \
+                point. The default value of each field is the one given in the shader.",
+                entry_point_name,
+            );
+
+            quote! {
+                #[doc = #doc]
+                #[derive(Clone, Copy, Debug, PartialEq)]
+                #[allow(non_camel_case_types, non_snake_case)]
+                pub struct #struct_ident {
+                    #( pub #field_idents: #field_types, )*
+                }
+
+                impl ::std::default::Default for #struct_ident {
+                    #[inline]
+                    fn default() -> Self {
+                        Self {
+                            #( #field_idents: #defaults, )*
+                        }
+                    }
+                }
+
+                impl ::vulkano::shader::SpecializationConstants for #struct_ident {
+                    #[inline]
+                    fn specialization_info(
+                        &self,
+                    ) -> ::std::vec::Vec<(u32, ::vulkano::shader::SpecializationConstant)> {
+                        ::std::vec![
+                            #( (#constant_ids, self.#field_idents.into()), )*
+                        ]
+                    }
+                }
+
+                #[allow(unused)]
+                impl #struct_ident {
+                    /// The name of the entry point that the constants belong to.
+                    pub const ENTRY_POINT: &'static str = #entry_point_name;
+
+                    /// Specializes `module` with the constants, and returns the entry point.
+                    pub fn entry_point(
+                        &self,
+                        module: &::std::sync::Arc<::vulkano::shader::ShaderModule>,
+                    ) -> ::std::result::Result<
+                        ::vulkano::shader::EntryPoint,
+                        ::std::boxed::Box<::vulkano::ValidationError>,
+                    > {
+                        module
+                            .specialize_with(self)?
+                            .entry_point_with_execution(
+                                Self::ENTRY_POINT,
+                                ::vulkano::shader::spirv::ExecutionModel::#execution_model,
+                            )
+                            .ok_or_else(|| {
+                                ::std::boxed::Box::new(::vulkano::ValidationError {
+                                    context: "module".into(),
+                                    problem: "does not contain the entry point that the \
+                                        specialization constants belong to"
+                                        .into(),
+                                    ..::std::default::Default::default()
+                                })
+                            })
+                    }
+
+                    /// Specializes `module` with the constants, and returns the create info of a
+                    /// pipeline shader stage for the entry point.
+                    pub fn stage_create_info(
+                        &self,
+                        module: &::std::sync::Arc<::vulkano::shader::ShaderModule>,
+                    ) -> ::std::result::Result<
+                        ::vulkano::pipeline::PipelineShaderStageCreateInfo,
+                        ::std::boxed::Box<::vulkano::ValidationError>,
+                    > {
+                        self.entry_point(module)
+                            .map(::vulkano::pipeline::PipelineShaderStageCreateInfo::new)
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+fn struct_ident(prefix: &str, info: &EntryPointInfo, entry_points: &[EntryPointInfo]) -> Ident {
+    if entry_points.len() == 1 {
+        return format_ident!("{}SpecializationConstants", prefix);
+    }
+
+    let name = info.name.to_upper_camel_case();
+
+    if entry_points
+        .iter()
+        .filter(|other| other.name == info.name)
+        .count()
+        > 1
+    {
+        format_ident!(
+            "{}{}{:?}SpecializationConstants",
+            prefix,
+            name,
+            info.execution_model,
+        )
+    } else {
+        format_ident!("{}{}SpecializationConstants", prefix, name)
+    }
+}
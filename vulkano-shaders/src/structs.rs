@@ -1,14 +1,15 @@
 use crate::{bail, codegen::Shader, LinAlgType, MacroInput};
-use ahash::HashMap;
+use ahash::{HashMap, HashSet};
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote, ToTokens, TokenStreamExt};
 use std::{cmp::Ordering, num::NonZeroUsize};
-use syn::{Error, Ident, Result};
+use syn::{Error, Ident, LitStr, Path, Result};
 use vulkano::shader::spirv::{Decoration, Id, Instruction};
 
 #[derive(Default)]
 pub struct TypeRegistry {
     registered_structs: HashMap<Ident, RegisteredType>,
+    verified_structs: HashSet<Ident>,
 }
 
 impl TypeRegistry {
@@ -32,6 +33,11 @@ impl TypeRegistry {
             Ok(true)
         }
     }
+
+    /// Returns whether a layout check was written for the struct named `ident`.
+    pub(super) fn is_verified(&self, ident: &Ident) -> bool {
+        self.verified_structs.contains(ident)
+    }
 }
 
 struct RegisteredType {
@@ -134,6 +140,155 @@ pub(super) fn write_structs(
     Ok(structs)
 }
 
+/// Writes compile-time checks that the Rust types listed in `verify_layouts` have the same layout
+/// as the structs of the same name in the SPIR-V document.
+pub(super) fn write_layout_checks(
+    input: &MacroInput,
+    shader: &Shader,
+    type_registry: &mut TypeRegistry,
+) -> Result<TokenStream> {
+    let mut checks = TokenStream::new();
+
+    if input.verify_layouts.is_empty() {
+        return Ok(checks);
+    }
+
+    for (struct_id, member_type_ids) in shader
+        .spirv
+        .types()
+        .iter()
+        .filter_map(|instruction| match *instruction {
+            Instruction::TypeStruct {
+                result_id,
+                ref member_types,
+            } => Some((result_id, member_types)),
+            _ => None,
+        })
+        .filter(|&(struct_id, _)| has_defined_layout(shader, struct_id))
+    {
+        let struct_ty = TypeStruct::new(shader, struct_id, member_type_ids)?;
+
+        let path = match input
+            .verify_layouts
+            .iter()
+            .find(|(ident, _)| *ident == struct_ty.ident)
+        {
+            Some((_, path)) => path,
+            None => continue,
+        };
+
+        // Structs that are shared between shaders only need to be checked once.
+        if !type_registry
+            .verified_structs
+            .insert(struct_ty.ident.clone())
+        {
+            continue;
+        }
+
+        checks.extend(write_layout_check(shader, &struct_ty, path)?);
+    }
+
+    Ok(checks)
+}
+
+fn write_layout_check(shader: &Shader, ty: &TypeStruct, path: &Path) -> Result<TokenStream> {
+    let struct_ident = &ty.ident;
+    let path_str = path.to_token_stream().to_string().replace(' ', "");
+
+    let struct_size = match ty.size() {
+        Some(size) => size,
+        None => bail!(
+            shader.source,
+            "the layout of struct `{struct_ident}` can't be verified, because it contains a \
+            runtime-sized array",
+        ),
+    };
+
+    let member_idents = ty.members.iter().map(|member| &member.ident);
+    let member_offsets = ty.members.iter().map(|member| member.offset);
+    // This can't panic, because the struct is sized.
+    let member_sizes = ty.members.iter().map(|member| member.ty.size().unwrap());
+    let offset_messages = ty.members.iter().map(|member| {
+        let (member_ident, offset) = (&member.ident, member.offset);
+
+        LitStr::new(
+            &format!(
+                "layout mismatch: field `{member_ident}` of `{path_str}` must be at offset \
+                {offset}, where member `{member_ident}` of the shader struct `{struct_ident}` is",
+            ),
+            Span::call_site(),
+        )
+    });
+    let size_messages = ty.members.iter().map(|member| {
+        let member_ident = &member.ident;
+        let size = member.ty.size().unwrap();
+
+        LitStr::new(
+            &format!(
+                "layout mismatch: field `{member_ident}` of `{path_str}` must be at least {size} \
+                bytes large, to hold member `{member_ident}` of the shader struct \
+                `{struct_ident}`",
+            ),
+            Span::call_site(),
+        )
+    });
+    let struct_size_message = LitStr::new(
+        &format!(
+            "layout mismatch: `{path_str}` must be at least {struct_size} bytes large, to hold \
+            the shader struct `{struct_ident}`",
+        ),
+        Span::call_site(),
+    );
+
+    Ok(quote! {
+        #[allow(unsafe_code)]
+        const _: () = {
+            const fn size_of_pointee<T>(_: *const T) -> usize {
+                ::std::mem::size_of::<T>()
+            }
+
+            let uninit = ::std::mem::MaybeUninit::<#path>::uninit();
+            let base = uninit.as_ptr();
+
+            #(
+                // SAFETY: `addr_of!` doesn't create a reference to the uninitialized field.
+                let field = unsafe { ::std::ptr::addr_of!((*base).#member_idents) };
+                // SAFETY: Both pointers are derived from the same allocation.
+                let offset = unsafe { field.cast::<u8>().offset_from(base.cast::<u8>()) } as usize;
+
+                if offset != #member_offsets {
+                    ::std::panic!(#offset_messages);
+                }
+
+                if size_of_pointee(field) < #member_sizes {
+                    ::std::panic!(#size_messages);
+                }
+            )*
+
+            if ::std::mem::size_of::<#path>() < #struct_size {
+                ::std::panic!(#struct_size_message);
+            }
+        };
+    })
+}
+
+/// Returns the identifier of the Rust struct that is generated for `struct_id`, or `None` if no
+/// struct is generated for it.
+pub(super) fn struct_ident(input: &MacroInput, shader: &Shader, struct_id: Id) -> Option<Ident> {
+    if !input.generate_structs || !has_defined_layout(shader, struct_id) {
+        return None;
+    }
+
+    match shader.spirv.id(struct_id).instruction() {
+        Instruction::TypeStruct { member_types, .. } => {
+            TypeStruct::new(shader, struct_id, member_types)
+                .ok()
+                .map(|ty| ty.ident)
+        }
+        _ => None,
+    }
+}
+
 fn has_defined_layout(shader: &Shader, struct_id: Id) -> bool {
     for member_info in shader.spirv.id(struct_id).members() {
         let mut offset_found = false;
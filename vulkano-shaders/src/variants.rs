@@ -0,0 +1,156 @@
+use crate::MacroInput;
+use heck::ToSnakeCase;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+
+/// Returns every combination of the values of the declared variants, as the macro definitions
+/// that select it.
+///
+/// The first combination uses the first value of each variant, which is also the default.
+pub(super) fn permutations(input: &MacroInput) -> Vec<Vec<(String, u32)>> {
+    input
+        .variants
+        .iter()
+        .fold(vec![Vec::new()], |permutations, (name, values)| {
+            permutations
+                .iter()
+                .flat_map(|permutation| {
+                    values.iter().map(move |&value| {
+                        let mut permutation = permutation.clone();
+                        permutation.push((name.to_string(), value));
+                        permutation
+                    })
+                })
+                .collect()
+        })
+}
+
+fn field_ident(name: &Ident) -> Ident {
+    format_ident!("{}", name.to_string().to_snake_case())
+}
+
+/// Writes the `Variant` struct that selects one of the declared variants.
+pub(super) fn write_variant_struct(input: &MacroInput) -> TokenStream {
+    if input.variants.is_empty() {
+        return TokenStream::new();
+    }
+
+    let field_idents: Vec<_> = input
+        .variants
+        .iter()
+        .map(|(name, _)| field_ident(name))
+        .collect();
+    let field_docs = input.variants.iter().map(|(name, values)| {
+        let values = values
+            .iter()
+            .map(|value| format!("`{value}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("The value of `{name}`, one of {values}.")
+    });
+    let defaults = input.variants.iter().map(|(_, values)| values[0]);
+    let all = permutations(input).into_iter().map(|permutation| {
+        let values = permutation.iter().map(|&(_, value)| value);
+
+        quote! { Variant { #( #field_idents: #values ),* } }
+    });
+
+    quote! {
+        /// Selects the values of the preprocessor macros that a variant of the shader is compiled
+        /// with.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub struct Variant {
+            #(
+                #[doc = #field_docs]
+                pub #field_idents: u32,
+            )*
+        }
+
+        #[allow(unused)]
+        impl Variant {
+            /// Every variant that the shader was compiled with.
+            pub const ALL: &'static [Variant] = &[ #( #all ),* ];
+        }
+
+        impl ::std::default::Default for Variant {
+            /// Returns the variant that uses the first value of each macro.
+            #[inline]
+            fn default() -> Self {
+                Variant { #( #field_idents: #defaults ),* }
+            }
+        }
+    }
+}
+
+/// Writes the function that loads the shader for a given `Variant`.
+///
+/// `words` contains the compiled code of each permutation, in the order returned by
+/// [`permutations`], except the first one which is loaded through the regular `load` function.
+pub(super) fn write_load_variant(
+    input: &MacroInput,
+    name: &str,
+    words: &[Vec<u32>],
+) -> TokenStream {
+    if input.variants.is_empty() {
+        return TokenStream::new();
+    }
+
+    let (load_name, load_variant_name) = if name.is_empty() {
+        (format_ident!("load"), format_ident!("load_variant"))
+    } else {
+        let name = name.to_snake_case();
+
+        (
+            format_ident!("load_{}", name),
+            format_ident!("load_{}_variant", name),
+        )
+    };
+    let field_idents = input.variants.iter().map(|(name, _)| field_ident(name));
+
+    let mut permutations = permutations(input).into_iter().map(|permutation| {
+        let values = permutation.into_iter().map(|(_, value)| value);
+
+        quote! { ( #( #values, )* ) }
+    });
+    let default_pattern = permutations.next().unwrap();
+    let patterns = permutations;
+    let words = words.iter().map(|words| quote! { &[ #( #words ),* ] });
+
+    quote! {
+        /// Loads the given variant of the shader as a `ShaderModule`.
+        #[allow(unsafe_code)]
+        #[inline]
+        pub fn #load_variant_name(
+            device: ::std::sync::Arc<::vulkano::device::Device>,
+            variant: Variant,
+        ) -> ::std::result::Result<
+            ::std::sync::Arc<::vulkano::shader::ShaderModule>,
+            ::vulkano::Validated<::vulkano::VulkanError>,
+        > {
+            let words: &'static [u32] = match ( #( variant.#field_idents, )* ) {
+                #default_pattern => return #load_name(device),
+                #( #patterns => #words, )*
+                _ => {
+                    return ::std::result::Result::Err(
+                        ::std::boxed::Box::new(::vulkano::ValidationError {
+                            context: "variant".into(),
+                            problem: "is not one of the variants that the shader was compiled \
+                                with"
+                                .into(),
+                            ..::std::default::Default::default()
+                        })
+                        .into(),
+                    );
+                }
+            };
+
+            unsafe {
+                ::vulkano::shader::ShaderModule::new(
+                    device,
+                    ::vulkano::shader::ShaderModuleCreateInfo::new(words),
+                )
+            }
+        }
+    }
+}
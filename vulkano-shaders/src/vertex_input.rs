@@ -0,0 +1,156 @@
+use crate::{bail, codegen::Shader, MacroInput};
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Ident, LitStr, Result};
+use vulkano::{
+    format::NumericType,
+    shader::{reflect, spirv::ExecutionModel, EntryPointInfo, InterfaceVariableInfo},
+};
+
+/// Writes a Rust struct for every vertex shader entry point, with a field for every user-defined
+/// input variable, that can be used as the vertex type of a vertex buffer.
+pub(super) fn write_vertex_structs(input: &MacroInput, shader: &Shader) -> Result<TokenStream> {
+    if !input.generate_vertex_structs {
+        return Ok(TokenStream::new());
+    }
+
+    let entry_points: Vec<_> = reflect::entry_points(&shader.spirv)
+        .map(|(_, info)| info)
+        .filter(|info| info.execution_model == ExecutionModel::Vertex)
+        .collect();
+    let prefix = shader.name.to_upper_camel_case();
+    let mut structs = TokenStream::new();
+
+    for info in &entry_points {
+        let mut variables: Vec<_> = info
+            .input_interface
+            .iter()
+            .filter(|variable| variable.location.is_some())
+            .collect();
+
+        if variables.is_empty() {
+            continue;
+        }
+
+        variables.sort_by_key(|variable| (variable.location, variable.component));
+
+        let struct_ident = struct_ident(&prefix, info, &entry_points);
+        let mut field_idents = Vec::with_capacity(variables.len());
+        let mut fields = Vec::with_capacity(variables.len());
+
+        for variable in variables {
+            fields.push(write_field(shader, variable, &mut field_idents)?);
+        }
+
+        let custom_derives = &input.custom_derives;
+        let doc = format!(
+            "A vertex type with a field for every input variable of the `{}` entry point.",
+            info.name,
+        );
+
+        structs.extend(quote! {
+            #[doc = #doc]
+            #[allow(non_camel_case_types, non_snake_case)]
+            #[derive(
+                ::vulkano::buffer::BufferContents,
+                ::vulkano::pipeline::graphics::vertex_input::Vertex
+                #(, #custom_derives )*
+            )]
+            #[repr(C)]
+            pub struct #struct_ident {
+                #( #fields )*
+            }
+        });
+    }
+
+    Ok(structs)
+}
+
+fn write_field(
+    shader: &Shader,
+    variable: &InterfaceVariableInfo,
+    field_idents: &mut Vec<Ident>,
+) -> Result<TokenStream> {
+    // Built-in variables, which have no location, are filtered out by the caller.
+    let location = variable.location.unwrap();
+
+    let name = match &variable.name {
+        Some(name) => name,
+        None => bail!(
+            shader.source,
+            "the vertex input variable at location {location} has no name, so no vertex struct \
+            can be generated for it",
+        ),
+    };
+
+    let numeric_type = match variable.numeric_type {
+        Some(numeric_type) => numeric_type,
+        None => bail!(
+            shader.source,
+            "the vertex input variable `{name}` is not made up of numeric scalars, vectors or matrices",
+        ),
+    };
+
+    let (component_type, format_suffix) = match (numeric_type, variable.is_64bit) {
+        (NumericType::Float, false) => (quote! { f32 }, "SFLOAT"),
+        (NumericType::Float, true) => (quote! { f64 }, "SFLOAT"),
+        (NumericType::Int, false) => (quote! { i32 }, "SINT"),
+        (NumericType::Int, true) => (quote! { i64 }, "SINT"),
+        (NumericType::Uint, false) => (quote! { u32 }, "UINT"),
+        (NumericType::Uint, true) => (quote! { u64 }, "UINT"),
+    };
+    let bits = if variable.is_64bit { 64 } else { 32 };
+    let format = format_ident!(
+        "{}_{}",
+        ["R", "G", "B", "A"]
+            .iter()
+            .take(variable.num_components as usize)
+            .map(|component| format!("{component}{bits}"))
+            .collect::<String>(),
+        format_suffix,
+    );
+
+    let element_type = match variable.num_components {
+        1 => component_type,
+        num_components => {
+            let num_components = num_components as usize;
+            quote! { [#component_type; #num_components] }
+        }
+    };
+    let field_type = match variable.num_elements {
+        1 => element_type,
+        num_elements => {
+            let num_elements = num_elements as usize;
+            quote! { [#element_type; #num_elements] }
+        }
+    };
+
+    // The vertex input state is matched to the shader by name, so the original name is given to
+    // the `Vertex` derive whenever the field is named differently.
+    let field_ident = syn::parse_str::<Ident>(&name.to_snake_case())
+        .ok()
+        .filter(|ident| !field_idents.contains(ident))
+        .unwrap_or_else(|| format_ident!("location_{location}"));
+    let name_attr = if field_ident == name {
+        TokenStream::new()
+    } else {
+        let name = LitStr::new(name, Span::call_site());
+        quote! { #[name(#name)] }
+    };
+    field_idents.push(field_ident.clone());
+
+    Ok(quote! {
+        #[format(#format)]
+        #name_attr
+        pub #field_ident: #field_type,
+    })
+}
+
+fn struct_ident(prefix: &str, info: &EntryPointInfo, entry_points: &[EntryPointInfo]) -> Ident {
+    if entry_points.len() == 1 {
+        return format_ident!("{}Vertex", prefix);
+    }
+
+    format_ident!("{}{}Vertex", prefix, info.name.to_upper_camel_case())
+}
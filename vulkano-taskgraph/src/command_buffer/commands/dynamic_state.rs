@@ -8,8 +8,9 @@ use vulkano::{
         color_blend::LogicOp,
         conservative_rasterization::ConservativeRasterizationMode,
         depth_stencil::{CompareOp, StencilFaces, StencilOp},
+        discard_rectangle::DiscardRectangleMode,
         input_assembly::PrimitiveTopology,
-        rasterization::{CullMode, FrontFace},
+        rasterization::{CullMode, FrontFace, LineRasterizationMode},
         vertex_input::{
             VertexInputAttributeDescription, VertexInputBindingDescription, VertexInputRate,
             VertexInputState,
@@ -246,6 +247,42 @@ impl RecordingCommandBuffer<'_> {
         self
     }
 
+    /// Sets whether the discard rectangle test is enabled for future draw calls.
+    pub unsafe fn set_discard_rectangle_enable(&mut self, enable: bool) -> Result<&mut Self> {
+        Ok(unsafe { self.set_discard_rectangle_enable_unchecked(enable) })
+    }
+
+    pub unsafe fn set_discard_rectangle_enable_unchecked(&mut self, enable: bool) -> &mut Self {
+        let fns = self.device().fns();
+        unsafe {
+            (fns.ext_discard_rectangles
+                .cmd_set_discard_rectangle_enable_ext)(self.handle(), enable.into())
+        };
+
+        self
+    }
+
+    /// Sets the dynamic discard rectangle mode for future draw calls.
+    pub unsafe fn set_discard_rectangle_mode(
+        &mut self,
+        mode: DiscardRectangleMode,
+    ) -> Result<&mut Self> {
+        Ok(unsafe { self.set_discard_rectangle_mode_unchecked(mode) })
+    }
+
+    pub unsafe fn set_discard_rectangle_mode_unchecked(
+        &mut self,
+        mode: DiscardRectangleMode,
+    ) -> &mut Self {
+        let fns = self.device().fns();
+        unsafe {
+            (fns.ext_discard_rectangles
+                .cmd_set_discard_rectangle_mode_ext)(self.handle(), mode.into())
+        };
+
+        self
+    }
+
     /// Sets the dynamic front face for future draw calls.
     pub unsafe fn set_front_face(&mut self, face: FrontFace) -> Result<&mut Self> {
         Ok(unsafe { self.set_front_face_unchecked(face) })
@@ -778,4 +815,42 @@ impl RecordingCommandBuffer<'_> {
 
         self
     }
+
+    /// Sets the dynamic line rasterization mode for future draw calls.
+    pub unsafe fn set_line_rasterization_mode(
+        &mut self,
+        line_rasterization_mode: LineRasterizationMode,
+    ) -> Result<&mut Self> {
+        Ok(unsafe { self.set_line_rasterization_mode_unchecked(line_rasterization_mode) })
+    }
+
+    pub unsafe fn set_line_rasterization_mode_unchecked(
+        &mut self,
+        line_rasterization_mode: LineRasterizationMode,
+    ) -> &mut Self {
+        let fns = self.device().fns();
+        unsafe {
+            (fns.ext_extended_dynamic_state3
+                .cmd_set_line_rasterization_mode_ext)(
+                self.handle(), line_rasterization_mode.into()
+            )
+        };
+
+        self
+    }
+
+    /// Sets whether dynamic line stippling is enabled for future draw calls.
+    pub unsafe fn set_line_stipple_enable(&mut self, enable: bool) -> Result<&mut Self> {
+        Ok(unsafe { self.set_line_stipple_enable_unchecked(enable) })
+    }
+
+    pub unsafe fn set_line_stipple_enable_unchecked(&mut self, enable: bool) -> &mut Self {
+        let fns = self.device().fns();
+        unsafe {
+            (fns.ext_extended_dynamic_state3
+                .cmd_set_line_stipple_enable_ext)(self.handle(), enable.into())
+        };
+
+        self
+    }
 }
@@ -11,10 +11,11 @@ use smallvec::{smallvec, SmallVec};
 use std::{cell::RefCell, cmp, error::Error, fmt, mem, ops::Range, sync::Arc};
 use vulkano::{
     device::{Device, DeviceOwned, Queue, QueueFlags},
-    image::{Image, ImageLayout},
+    image::{transient::TransientImageAllocator, AllocateImageError, Image, ImageLayout},
+    memory::allocator::MemoryTypeFilter,
     swapchain::Swapchain,
     sync::{semaphore::Semaphore, AccessFlags, PipelineStages},
-    VulkanError,
+    Validated, VulkanError,
 };
 
 impl<W: ?Sized> TaskGraph<W> {
@@ -26,6 +27,8 @@ impl<W: ?Sized> TaskGraph<W> {
     /// - There must be no accesses that are incompatible with the queue family type of the task
     ///   node.
     /// - There must be no accesses that are unsupported by the device.
+    /// - If the graph was [decompiled] from an executable graph that has [transient images], that
+    ///   executable graph must not have any pending executions.
     ///
     /// # Panics
     ///
@@ -42,7 +45,10 @@ impl<W: ?Sized> TaskGraph<W> {
     ///   disregarding the direction of the edges.
     /// - It must have no [directed cycles]: if you were to walk starting from any node following
     ///   the direction of the edges, there must be no way to end up at the node you started at.
+    /// - Creating the [transient images] and allocating their memory must succeed.
     ///
+    /// [decompiled]: ExecutableTaskGraph::decompile
+    /// [transient images]: Self::add_transient_image
     /// [weakly connected]: https://en.wikipedia.org/wiki/Connectivity_(graph_theory)#Connected_vertices_and_graphs
     /// [directed cycles]: https://en.wikipedia.org/wiki/Cycle_(graph_theory)#Directed_circuit_and_directed_cycle
     pub unsafe fn compile(
@@ -90,6 +96,11 @@ impl<W: ?Sized> TaskGraph<W> {
                 Ok(queue_family_indices) => queue_family_indices,
                 Err(kind) => return Err(CompileError::new(self, kind)),
             };
+
+        if let Err(kind) = unsafe { self.allocate_transient_images(&topological_order) } {
+            return Err(CompileError::new(self, kind));
+        }
+
         let mut queues_by_queue_family_index: SmallVec<[_; 8]> =
             smallvec![None; *queue_family_indices.iter().max().unwrap() as usize + 1];
 
@@ -133,7 +144,10 @@ impl<W: ?Sized> TaskGraph<W> {
                     let barrier_stage = &mut barrier_stages[id.index() as usize];
 
                     if *barrier_stage == BarrierStage::Stage0 {
-                        if !id.is::<Swapchain>() {
+                        // Aliased transient images are transitioned right before their first
+                        // access instead, as their memory may be in use by another image until
+                        // then.
+                        if !id.is::<Swapchain>() && !self.resources.is_aliased_transient_image(id) {
                             if id.is::<Image>() {
                                 state.transition_image(id, access);
                             } else if access.access_mask.contains_reads() {
@@ -190,6 +204,8 @@ impl<W: ?Sized> TaskGraph<W> {
                     if *barrier_stage == BarrierStage::Stage1 {
                         if id.is::<Swapchain>() {
                             state.wait_acquire(unsafe { id.parametrize() }, access);
+                        } else if self.resources.is_aliased_transient_image(id) {
+                            state.discard_image(id, access);
                         }
 
                         *barrier_stage = BarrierStage::Stage2;
@@ -442,6 +458,93 @@ impl<W: ?Sized> TaskGraph<W> {
         levels
     }
 
+    /// Creates the transient images and allocates their memory, aliasing the memory of images
+    /// whose lifetimes don't overlap. Images allocated by a previous compilation are removed.
+    ///
+    /// The lifetime of an image is the range of positions in `topological_order` of the task
+    /// nodes accessing it. This only corresponds to the order in which the task nodes execute if
+    /// they all execute on the same queue, so the memory of the images isn't aliased otherwise.
+    unsafe fn allocate_transient_images(
+        &mut self,
+        topological_order: &[NodeIndex],
+    ) -> Result<(), CompileErrorKind> {
+        let device = self.device().clone();
+        let resources = &mut self.resources;
+
+        if resources.transient_images.is_empty() {
+            return Ok(());
+        }
+
+        for image in &mut resources.transient_images {
+            if let Some(physical_id) = image.physical_id.take() {
+                resources.physical_map.remove(&physical_id.erase());
+
+                // SAFETY: The caller must ensure that the executable graph that the image was
+                // allocated for has no pending executions.
+                let _ = unsafe { resources.physical_resources.remove_image(physical_id) };
+            }
+        }
+
+        let mut lifetimes = vec![None::<(u32, u32)>; resources.transient_images.len()];
+        let mut queue_family_indices = SmallVec::<[_; 2]>::new();
+
+        for (position, &node_index) in (0..).zip(topological_order) {
+            let node = unsafe { self.nodes.node_unchecked(node_index) };
+            let NodeInner::Task(task_node) = &node.inner else {
+                unreachable!();
+            };
+
+            if !queue_family_indices.contains(&task_node.queue_family_index) {
+                queue_family_indices.push(task_node.queue_family_index);
+            }
+
+            for (id, _) in task_node.accesses.iter() {
+                if let Some(index) = resources
+                    .transient_images
+                    .iter()
+                    .position(|image| image.virtual_id.erase() == id)
+                {
+                    let lifetime = &mut lifetimes[index];
+                    *lifetime =
+                        Some(lifetime.map_or((position, position), |(start, _)| (start, position)));
+                }
+            }
+        }
+
+        let aliased = queue_family_indices.len() == 1 && resources.transient_images.len() > 1;
+        let mut allocator = TransientImageAllocator::new(device);
+
+        for (image, lifetime) in resources.transient_images.iter().zip(lifetimes) {
+            // Giving all images the same lifetime prevents any aliasing.
+            let lifetime = match lifetime {
+                Some((start, end)) if aliased => start..=end,
+                _ => 0..=0,
+            };
+
+            let _ = allocator.add_image(image.create_info.clone(), lifetime);
+        }
+
+        // SAFETY: Aliased images are discarded right before their first access, and the accesses
+        // of all tasks are synchronized with each other, as they execute on the same queue.
+        let images = unsafe { allocator.allocate(MemoryTypeFilter::PREFER_DEVICE) }
+            .map_err(CompileErrorKind::AllocateTransientImages)?;
+
+        for (transient_image, image) in resources
+            .transient_images
+            .iter_mut()
+            .zip(images.into_images())
+        {
+            let physical_id = resources.physical_resources.add_image(image);
+            resources
+                .physical_map
+                .insert(physical_id.erase(), transient_image.virtual_id.erase());
+            transient_image.physical_id = Some(physical_id);
+            transient_image.aliased = aliased;
+        }
+
+        Ok(())
+    }
+
     /// Assigns a queue family index to each task node. Returns a vector of the used queue family
     /// indices in topological order.
     unsafe fn queue_family_indices(
@@ -692,6 +795,25 @@ impl<'a> CompileState<'a> {
         self.memory_barrier_inner(id, src, dst);
     }
 
+    fn discard_image(&mut self, id: Id, access: ResourceAccess) {
+        // The image may share its memory with any image that was accessed before, so we must wait
+        // for all previous commands.
+        let src = ResourceAccess {
+            stage_mask: PipelineStages::ALL_COMMANDS,
+            access_mask: AccessFlags::MEMORY_WRITE,
+            image_layout: ImageLayout::Undefined,
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        };
+        let dst = ResourceAccess {
+            queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            ..access
+        };
+
+        self.prev_accesses[id.index() as usize] = access;
+
+        self.memory_barrier_inner(id, src, dst);
+    }
+
     fn transition_image(&mut self, id: Id, access: ResourceAccess) {
         debug_assert_ne!(
             self.prev_accesses[id.index() as usize].image_layout,
@@ -1013,6 +1135,7 @@ pub enum CompileErrorKind {
     Unconnected,
     Cycle,
     InsufficientQueues,
+    AllocateTransientImages(Validated<AllocateImageError>),
     VulkanError(VulkanError),
 }
 
@@ -1036,6 +1159,9 @@ impl<W: ?Sized> fmt::Display for CompileError<W> {
             CompileErrorKind::InsufficientQueues => {
                 f.write_str("the given queues are not sufficient for the requirements of a task")
             }
+            CompileErrorKind::AllocateTransientImages(_) => {
+                f.write_str("allocating the transient images failed")
+            }
             CompileErrorKind::VulkanError(_) => f.write_str("a runtime error occurred"),
         }
     }
@@ -1044,6 +1170,7 @@ impl<W: ?Sized> fmt::Display for CompileError<W> {
 impl<W: ?Sized> Error for CompileError<W> {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match &self.kind {
+            CompileErrorKind::AllocateTransientImages(err) => Some(err),
             CompileErrorKind::VulkanError(err) => Some(err),
             _ => None,
         }
@@ -1098,14 +1225,19 @@ mod linear_map {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::graph::ResourceMap;
     use crate::{
         resource::{AccessType, ImageLayoutType},
         tests::test_queues,
     };
     use std::marker::PhantomData;
     use vulkano::{
-        buffer::BufferCreateInfo, image::ImageCreateInfo, swapchain::SwapchainCreateInfo,
+        buffer::BufferCreateInfo,
+        format::Format,
+        image::{ImageCreateInfo, ImageMemory, ImageUsage},
+        swapchain::SwapchainCreateInfo,
         sync::Sharing,
+        VulkanObject,
     };
 
     #[test]
@@ -2220,6 +2352,161 @@ mod tests {
         }
     }
 
+    #[test]
+    fn transient_images() {
+        let (resources, queues) = test_queues!();
+        let compile_info = CompileInfo {
+            queues: &queues.iter().collect::<Vec<_>>(),
+            ..Default::default()
+        };
+
+        let create_info = ImageCreateInfo {
+            format: Format::R8G8B8A8_UNORM,
+            extent: [64, 64, 1],
+            usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED,
+            ..Default::default()
+        };
+
+        // ┌───┐  ┌───┐  ┌───┐
+        // │ A ├─►│ B ├─►│ C │
+        // └───┘  └───┘  └───┘
+        let mut graph = TaskGraph::<()>::new(&resources, 10, 10);
+        let image1 = graph.add_transient_image(&create_info);
+        let image2 = graph.add_transient_image(&create_info);
+        let image3 = graph.add_transient_image(&create_info);
+        let node1 = graph
+            .create_task_node("A", QueueFamilyType::Graphics, PhantomData)
+            .image_access(
+                image1,
+                AccessType::ColorAttachmentWrite,
+                ImageLayoutType::Optimal,
+            )
+            .build();
+        let node2 = graph
+            .create_task_node("B", QueueFamilyType::Graphics, PhantomData)
+            .image_access(
+                image1,
+                AccessType::FragmentShaderSampledRead,
+                ImageLayoutType::Optimal,
+            )
+            .image_access(
+                image2,
+                AccessType::ColorAttachmentWrite,
+                ImageLayoutType::Optimal,
+            )
+            .build();
+        let node3 = graph
+            .create_task_node("C", QueueFamilyType::Graphics, PhantomData)
+            .image_access(
+                image2,
+                AccessType::FragmentShaderSampledRead,
+                ImageLayoutType::Optimal,
+            )
+            .image_access(
+                image3,
+                AccessType::ColorAttachmentWrite,
+                ImageLayoutType::Optimal,
+            )
+            .build();
+        graph.add_edge(node1, node2).unwrap();
+        graph.add_edge(node2, node3).unwrap();
+
+        let graph = unsafe { graph.compile(&compile_info) }.unwrap();
+
+        assert_matches_instructions!(
+            graph,
+            PipelineBarrier {
+                buffer_barriers: [],
+                image_barriers: [
+                    {
+                        src_stage_mask: ALL_COMMANDS,
+                        src_access_mask: MEMORY_WRITE,
+                        dst_stage_mask: COLOR_ATTACHMENT_OUTPUT,
+                        dst_access_mask: COLOR_ATTACHMENT_WRITE,
+                        old_layout: Undefined,
+                        new_layout: ColorAttachmentOptimal,
+                        image: image1,
+                    },
+                ],
+            },
+            ExecuteTask { node: node1 },
+            PipelineBarrier {
+                buffer_barriers: [],
+                image_barriers: [
+                    {
+                        src_stage_mask: COLOR_ATTACHMENT_OUTPUT,
+                        src_access_mask: COLOR_ATTACHMENT_WRITE,
+                        dst_stage_mask: FRAGMENT_SHADER,
+                        dst_access_mask: SHADER_SAMPLED_READ,
+                        old_layout: ColorAttachmentOptimal,
+                        new_layout: ShaderReadOnlyOptimal,
+                        image: image1,
+                    },
+                    {
+                        src_stage_mask: ALL_COMMANDS,
+                        src_access_mask: MEMORY_WRITE,
+                        dst_stage_mask: COLOR_ATTACHMENT_OUTPUT,
+                        dst_access_mask: COLOR_ATTACHMENT_WRITE,
+                        old_layout: Undefined,
+                        new_layout: ColorAttachmentOptimal,
+                        image: image2,
+                    },
+                ],
+            },
+            ExecuteTask { node: node2 },
+            PipelineBarrier {
+                buffer_barriers: [],
+                image_barriers: [
+                    {
+                        src_stage_mask: COLOR_ATTACHMENT_OUTPUT,
+                        src_access_mask: COLOR_ATTACHMENT_WRITE,
+                        dst_stage_mask: FRAGMENT_SHADER,
+                        dst_access_mask: SHADER_SAMPLED_READ,
+                        old_layout: ColorAttachmentOptimal,
+                        new_layout: ShaderReadOnlyOptimal,
+                        image: image2,
+                    },
+                    {
+                        src_stage_mask: ALL_COMMANDS,
+                        src_access_mask: MEMORY_WRITE,
+                        dst_stage_mask: COLOR_ATTACHMENT_OUTPUT,
+                        dst_access_mask: COLOR_ATTACHMENT_WRITE,
+                        old_layout: Undefined,
+                        new_layout: ColorAttachmentOptimal,
+                        image: image3,
+                    },
+                ],
+            },
+            ExecuteTask { node: node3 },
+            FlushSubmit,
+            Submit,
+        );
+
+        let memory = |virtual_id: Id<Image>| {
+            let physical_id = graph
+                .graph
+                .resources
+                .transient_images
+                .iter()
+                .find(|image| image.virtual_id == virtual_id)
+                .unwrap()
+                .physical_id
+                .unwrap();
+            let ImageMemory::Normal(memory) =
+                resources.image(physical_id).unwrap().image().memory()
+            else {
+                unreachable!();
+            };
+
+            (memory[0].device_memory().handle(), memory[0].offset())
+        };
+
+        // The lifetimes of the first and the last image don't overlap, so they share memory.
+        assert_eq!(memory(image1), memory(image3));
+        assert_ne!(memory(image1), memory(image2));
+        assert!(ResourceMap::new(&graph).unwrap().is_exhaustive());
+    }
+
     struct MatchingState {
         submission_index: usize,
         instruction_index: usize,
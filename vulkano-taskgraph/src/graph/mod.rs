@@ -18,7 +18,7 @@ use std::{
 use vulkano::{
     buffer::{Buffer, BufferCreateInfo},
     device::{Device, DeviceOwned, Queue},
-    image::{Image, ImageCreateInfo, ImageLayout},
+    image::{Image, ImageCreateFlags, ImageCreateInfo, ImageLayout},
     swapchain::{Swapchain, SwapchainCreateInfo},
     sync::{semaphore::Semaphore, AccessFlags, PipelineStages},
 };
@@ -29,7 +29,21 @@ mod execute;
 /// The task graph is a [directed acyclic graph] consisting of [`Task`] nodes, with edges
 /// representing happens-before relations.
 ///
+/// Each task node declares the resources it accesses, and how, using
+/// [`TaskNodeBuilder::buffer_access`], [`TaskNodeBuilder::image_access`] and so on. When the graph
+/// is [compiled], the execution order of the task nodes is derived from the edges, and the
+/// accesses are used to derive the pipeline barriers, image layout transitions and queue family
+/// ownership transfers between the nodes. Task nodes are assigned to the given queues based on
+/// their [`QueueFamilyType`], with semaphores inserted between nodes that execute on different
+/// queues.
+///
+/// Virtual resources are bound to physical resources when the graph is executed, using a
+/// [`ResourceMap`]. The exception are [transient images], which the graph allocates itself when
+/// it's compiled, aliasing the memory of transient images whose lifetimes don't overlap.
+///
 /// [directed acyclic graph]: https://en.wikipedia.org/wiki/Directed_acyclic_graph
+/// [compiled]: Self::compile
+/// [transient images]: Self::add_transient_image
 pub struct TaskGraph<W: ?Sized> {
     nodes: Nodes<W>,
     resources: Resources,
@@ -63,6 +77,16 @@ pub(crate) struct Resources {
     physical_map: HashMap<Id, Id>,
     host_reads: Vec<Id<Buffer>>,
     host_writes: Vec<Id<Buffer>>,
+    transient_images: Vec<TransientImage>,
+}
+
+struct TransientImage {
+    virtual_id: Id<Image>,
+    create_info: ImageCreateInfo,
+    // The physical image allocated by the last compilation, if any.
+    physical_id: Option<Id<Image>>,
+    // Whether the memory of the image is shared with other transient images.
+    aliased: bool,
 }
 
 impl<W: ?Sized> TaskGraph<W> {
@@ -86,6 +110,7 @@ impl<W: ?Sized> TaskGraph<W> {
                 physical_map: HashMap::default(),
                 host_reads: Vec::new(),
                 host_writes: Vec::new(),
+                transient_images: Vec::new(),
             },
         }
     }
@@ -204,6 +229,29 @@ impl<W: ?Sized> TaskGraph<W> {
         self.resources.add_image(create_info)
     }
 
+    /// Add a transient [virtual image resource] to the task graph.
+    ///
+    /// Unlike other virtual resources, a transient image is not bound to a physical resource by
+    /// you. Instead, the image is created and its memory allocated when the graph is [compiled],
+    /// and it's bound automatically when the graph is executed. The lifetime of a transient image
+    /// spans from the first to the last task node accessing it in execution order, and transient
+    /// images whose lifetimes don't overlap share the same memory. This is only done if all task
+    /// nodes execute on the same queue family; otherwise, each transient image gets its own
+    /// memory.
+    ///
+    /// The contents of a transient image are undefined at the start of its lifetime, so the first
+    /// task node accessing it must not read it.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.flags` contains [`ImageCreateFlags::DISJOINT`].
+    ///
+    /// [compiled]: Self::compile
+    #[must_use]
+    pub fn add_transient_image(&mut self, create_info: &ImageCreateInfo) -> Id<Image> {
+        self.resources.add_transient_image(create_info)
+    }
+
     /// Add a [virtual swapchain resource] to the task graph.
     #[must_use]
     pub fn add_swapchain(&mut self, create_info: &SwapchainCreateInfo) -> Id<Swapchain> {
@@ -383,6 +431,20 @@ impl Resources {
         unsafe { Id::new(slot) }
     }
 
+    fn add_transient_image(&mut self, create_info: &ImageCreateInfo) -> Id<Image> {
+        assert!(!create_info.flags.intersects(ImageCreateFlags::DISJOINT));
+
+        let virtual_id = self.add_image(create_info);
+        self.transient_images.push(TransientImage {
+            virtual_id,
+            create_info: create_info.clone(),
+            physical_id: None,
+            aliased: false,
+        });
+
+        virtual_id
+    }
+
     fn add_swapchain(&mut self, create_info: &SwapchainCreateInfo) -> Id<Swapchain> {
         let mut tag = Swapchain::TAG | Id::VIRTUAL_BIT;
 
@@ -482,6 +544,14 @@ impl Resources {
         unsafe { self.inner.iter_unprotected() }.map(|(slot, v)| (unsafe { Id::new(slot) }, v))
     }
 
+    fn is_aliased_transient_image(&self, id: Id) -> bool {
+        id.is::<Image>()
+            && self
+                .transient_images
+                .iter()
+                .any(|image| image.virtual_id.erase() == id && image.aliased)
+    }
+
     pub(crate) fn contains_host_buffer_access(
         &self,
         mut id: Id<Buffer>,
@@ -0,0 +1,229 @@
+//! Transcoding Basis Universal textures to a format that the device supports.
+//!
+//! Block-compressed formats are not supported everywhere: desktop GPUs support the BC formats,
+//! while mobile GPUs support ASTC and ETC2. A [Basis Universal] texture is stored once in an
+//! intermediate format, and transcoded when it's loaded to whichever of these formats the device
+//! supports. [`TranscodeTarget::choose`] picks the best target for a physical device, and
+//! [`transcode`] produces [`TextureData`] for it, which can be given to a [`TextureManager`] or
+//! uploaded directly with [`upload`].
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+//! # use vulkano_util::context::VulkanoContext;
+//! use vulkano::image::ImageUsage;
+//! use vulkano_util::basis::{self, TranscodeTarget};
+//!
+//! # let context: VulkanoContext = todo!();
+//! # let command_buffer_allocator: Arc<StandardCommandBufferAllocator> = todo!();
+//! let bytes = std::fs::read("textures/albedo.basis").unwrap();
+//! let target = TranscodeTarget::choose(context.device().physical_device(), true);
+//! let texture_data = basis::transcode(&bytes, target, true).unwrap();
+//!
+//! let (image, upload_future) = basis::upload(
+//!     context.memory_allocator().clone(),
+//!     command_buffer_allocator,
+//!     context.graphics_queue().clone(),
+//!     &texture_data,
+//!     ImageUsage::SAMPLED,
+//! )
+//! .unwrap();
+//! ```
+//!
+//! Only `.basis` files are supported, since the `basis-universal` crate doesn't expose the
+//! transcoder for KTX2 containers.
+//!
+//! [Basis Universal]: https://github.com/BinomialLLC/basis_universal
+//! [`TextureManager`]: crate::texture_manager::TextureManager
+
+use crate::texture_manager::TextureData;
+use basis_universal::{TranscodeParameters, Transcoder, TranscoderTextureFormat};
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    sync::{Arc, Once},
+};
+use vulkano::{
+    command_buffer::{allocator::CommandBufferAllocator, CommandBufferExecFuture},
+    device::{physical::PhysicalDevice, Queue},
+    format::{Format, FormatFeatures},
+    image::{Image, ImageCreateInfo, ImageType, ImageUsage, UploadImageError},
+    memory::allocator::MemoryAllocator,
+    sync::future::NowFuture,
+    Validated,
+};
+
+/// A format that a Basis Universal texture can be transcoded to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TranscodeTarget {
+    /// BC7, which is supported by desktop GPUs.
+    Bc7,
+
+    /// ASTC with 4×4 blocks, which is supported by most mobile GPUs.
+    Astc4x4,
+
+    /// ETC2 with alpha, which is supported by older mobile GPUs.
+    Etc2,
+
+    /// Uncompressed 8-bit RGBA, which every device supports, at four times the size of the
+    /// compressed targets.
+    Rgba8,
+}
+
+impl TranscodeTarget {
+    /// Returns the best target that `physical_device` supports for sampled images, trying the
+    /// compressed targets in the order [`Bc7`], [`Astc4x4`], [`Etc2`], and falling back to
+    /// [`Rgba8`].
+    ///
+    /// [`Bc7`]: Self::Bc7
+    /// [`Astc4x4`]: Self::Astc4x4
+    /// [`Etc2`]: Self::Etc2
+    /// [`Rgba8`]: Self::Rgba8
+    pub fn choose(physical_device: &PhysicalDevice, srgb: bool) -> Self {
+        [Self::Bc7, Self::Astc4x4, Self::Etc2]
+            .into_iter()
+            .find(|target| target.is_supported(physical_device, srgb))
+            .unwrap_or(Self::Rgba8)
+    }
+
+    /// Returns whether `physical_device` supports sampled images of the format of this target.
+    pub fn is_supported(self, physical_device: &PhysicalDevice, srgb: bool) -> bool {
+        physical_device
+            .format_properties(self.format(srgb))
+            .map_or(false, |properties| {
+                properties
+                    .optimal_tiling_features
+                    .contains(FormatFeatures::SAMPLED_IMAGE)
+            })
+    }
+
+    /// Returns the format of the transcoded texture.
+    pub fn format(self, srgb: bool) -> Format {
+        match (self, srgb) {
+            (Self::Bc7, false) => Format::BC7_UNORM_BLOCK,
+            (Self::Bc7, true) => Format::BC7_SRGB_BLOCK,
+            (Self::Astc4x4, false) => Format::ASTC_4x4_UNORM_BLOCK,
+            (Self::Astc4x4, true) => Format::ASTC_4x4_SRGB_BLOCK,
+            (Self::Etc2, false) => Format::ETC2_R8G8B8A8_UNORM_BLOCK,
+            (Self::Etc2, true) => Format::ETC2_R8G8B8A8_SRGB_BLOCK,
+            (Self::Rgba8, false) => Format::R8G8B8A8_UNORM,
+            (Self::Rgba8, true) => Format::R8G8B8A8_SRGB,
+        }
+    }
+
+    fn transcoder_format(self) -> TranscoderTextureFormat {
+        match self {
+            Self::Bc7 => TranscoderTextureFormat::BC7_RGBA,
+            Self::Astc4x4 => TranscoderTextureFormat::ASTC_4x4_RGBA,
+            Self::Etc2 => TranscoderTextureFormat::ETC2_RGBA,
+            Self::Rgba8 => TranscoderTextureFormat::RGBA32,
+        }
+    }
+}
+
+/// Transcodes the first image of a `.basis` file, including all of its mip levels, to `target`.
+///
+/// `srgb` selects between the sRGB and the UNORM variant of the format, and should match how the
+/// texture was encoded. Basis Universal doesn't record this reliably.
+pub fn transcode(
+    data: &[u8],
+    target: TranscodeTarget,
+    srgb: bool,
+) -> Result<TextureData, TranscodeError> {
+    static INIT: Once = Once::new();
+    INIT.call_once(basis_universal::transcoder_init);
+
+    let mut transcoder = Transcoder::new();
+
+    if !transcoder.validate_header(data) || transcoder.image_count(data) == 0 {
+        return Err(TranscodeError::InvalidData);
+    }
+
+    let level_count = transcoder.image_level_count(data, 0);
+    let description = transcoder
+        .image_level_description(data, 0, 0)
+        .ok_or(TranscodeError::InvalidData)?;
+
+    transcoder
+        .prepare_transcoding(data)
+        .map_err(|_| TranscodeError::InvalidData)?;
+
+    let mip_levels = (0..level_count)
+        .map(|level_index| {
+            transcoder
+                .transcode_image_level(
+                    data,
+                    target.transcoder_format(),
+                    TranscodeParameters {
+                        image_index: 0,
+                        level_index,
+                        ..Default::default()
+                    },
+                )
+                .map_err(|_| TranscodeError::TranscodeFailed { level_index })
+        })
+        .collect::<Result<Vec<_>, _>>();
+
+    transcoder.end_transcoding();
+
+    Ok(TextureData {
+        format: target.format(srgb),
+        extent: [description.original_width, description.original_height],
+        mip_levels: mip_levels?,
+    })
+}
+
+/// Creates an image in device-local memory from the most detailed mip level of `texture_data`,
+/// and uploads it with [`Image::from_iter_device_local`].
+///
+/// The image has a single mip level. To stream in the other mip levels as well, give the
+/// texture data to a [`TextureManager`] instead.
+///
+/// [`TextureManager`]: crate::texture_manager::TextureManager
+pub fn upload(
+    memory_allocator: Arc<dyn MemoryAllocator>,
+    command_buffer_allocator: Arc<dyn CommandBufferAllocator>,
+    queue: Arc<Queue>,
+    texture_data: &TextureData,
+    usage: ImageUsage,
+) -> Result<(Arc<Image>, CommandBufferExecFuture<NowFuture>), Validated<UploadImageError>> {
+    let [width, height] = texture_data.extent;
+
+    Image::from_iter_device_local(
+        memory_allocator,
+        command_buffer_allocator,
+        queue,
+        ImageCreateInfo {
+            image_type: ImageType::Dim2d,
+            format: texture_data.format,
+            extent: [width, height, 1],
+            usage,
+            ..Default::default()
+        },
+        texture_data.mip_levels[0].iter().copied(),
+    )
+}
+
+/// Error that can happen when transcoding a Basis Universal texture.
+#[derive(Clone, Debug)]
+pub enum TranscodeError {
+    /// The data is not a valid `.basis` file, or contains no images.
+    InvalidData,
+
+    /// Transcoding a mip level failed.
+    TranscodeFailed { level_index: u32 },
+}
+
+impl Error for TranscodeError {}
+
+impl Display for TranscodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::InvalidData => write!(f, "the data is not a valid Basis Universal file"),
+            Self::TranscodeFailed { level_index } => {
+                write!(f, "failed to transcode mip level {}", level_index)
+            }
+        }
+    }
+}
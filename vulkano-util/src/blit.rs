@@ -0,0 +1,849 @@
+//! Blitting images with a compute shader fallback.
+//!
+//! [`RecordingCommandBuffer::blit_image`] copies between images while scaling and converting the
+//! format, but only for formats that support the [`BLIT_SRC`] and [`BLIT_DST`] format features,
+//! and only between formats of the same numeric type. Many formats that are otherwise perfectly
+//! usable, such as `E5B9G9R9_UFLOAT_PACK32` or depth formats when converting to a color format,
+//! can't be blitted. [`ImageBlitter`] records a blit when the implementation supports it, and
+//! otherwise does the same work with a built-in compute shader that samples the source image and
+//! writes the destination image as a storage image.
+//!
+//! The compute fallback has the following requirements:
+//!
+//! - Both images must be 2D images with a single sample per texel.
+//! - `src_image` must have the `SAMPLED` usage, and its format must support being sampled (with
+//!   linear filtering, if [`Filter::Linear`] is used). The color aspect or the depth aspect of
+//!   the source can be read, but not the stencil aspect.
+//! - `dst_image` must have the `STORAGE` usage, and its format must support storage image
+//!   writes. Formats that have no GLSL image format qualifier additionally need the
+//!   [`shader_storage_image_write_without_format`] feature to be enabled.
+//! - Neither format can have an integer numeric type.
+//!
+//! The compute fallback binds its own compute pipeline, descriptor set 0 and push constants. If
+//! the fallback was used, any compute pipeline state that was bound to the command buffer before
+//! must be bound again before the next dispatch.
+//!
+//! The compute shaders are compiled at runtime the first time they are needed, so this module is
+//! only available when the `shaderc` feature of `vulkano-util` is enabled.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::{
+//! #     command_buffer::{BlitImageInfo, RecordingCommandBuffer},
+//! #     descriptor_set::allocator::StandardDescriptorSetAllocator,
+//! #     device::Device,
+//! #     image::Image,
+//! # };
+//! use vulkano_util::blit::{BlitMethod, ImageBlitter};
+//!
+//! # let device: Arc<Device> = return;
+//! # let builder: &mut RecordingCommandBuffer = return;
+//! # let hdr_image: Arc<Image> = return;
+//! # let ldr_image: Arc<Image> = return;
+//! let descriptor_set_allocator = Arc::new(StandardDescriptorSetAllocator::new(
+//!     device.clone(),
+//!     Default::default(),
+//! ));
+//! let blitter = ImageBlitter::new(device, descriptor_set_allocator);
+//!
+//! let method = blitter
+//!     .blit_image(builder, BlitImageInfo::images(hdr_image, ldr_image))
+//!     .unwrap();
+//! println!("blitted with {method:?}");
+//! ```
+//!
+//! [`RecordingCommandBuffer::blit_image`]: vulkano::command_buffer::RecordingCommandBuffer::blit_image
+//! [`BLIT_SRC`]: vulkano::format::FormatFeatures::BLIT_SRC
+//! [`BLIT_DST`]: vulkano::format::FormatFeatures::BLIT_DST
+//! [`shader_storage_image_write_without_format`]: vulkano::device::DeviceFeatures::shader_storage_image_write_without_format
+
+use crate::shader::{compile_glsl, GlslCompileInfo, ShaderCompileError};
+use ahash::HashMap;
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    sync::{Arc, Mutex},
+};
+use vulkano::{
+    command_buffer::{BlitImageInfo, ImageBlit, RecordingCommandBuffer},
+    descriptor_set::{allocator::DescriptorSetAllocator, DescriptorSet, WriteDescriptorSet},
+    device::{Device, DeviceOwned},
+    format::{Format, FormatFeatures, NumericType},
+    image::{
+        sampler::{Filter, Sampler, SamplerCreateInfo},
+        view::{ImageView, ImageViewCreateInfo, ImageViewType},
+        Image, ImageAspects, ImageSubresourceRange, ImageType, ImageUsage, SampleCount,
+    },
+    pipeline::{
+        compute::ComputePipelineCreateInfo, layout::PipelineDescriptorSetLayoutCreateInfo,
+        ComputePipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    shader::ShaderStage,
+    Validated, ValidationError, VulkanError,
+};
+
+const SHADER_SOURCE: &str = r"
+    #version 450
+
+    layout(local_size_x = 8, local_size_y = 8) in;
+
+    layout(set = 0, binding = 0) uniform sampler2D src;
+    layout(DST_LAYOUT) writeonly uniform image2D dst;
+
+    layout(push_constant) uniform PushConstants {
+        vec2 src_origin;
+        vec2 src_step;
+        vec2 dst_offset;
+        vec2 dst_extent;
+    } pc;
+
+    void main() {
+        vec2 id = vec2(gl_GlobalInvocationID.xy);
+
+        if (any(greaterThanEqual(id, pc.dst_extent))) {
+            return;
+        }
+
+        vec2 uv = pc.src_origin + (id + 0.5) * pc.src_step;
+        imageStore(dst, ivec2(pc.dst_offset + id), textureLod(src, uv, 0.0));
+    }
+";
+
+/// The way that [`ImageBlitter::blit_image`] performed a blit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlitMethod {
+    /// The blit was recorded with [`RecordingCommandBuffer::blit_image`].
+    ///
+    /// [`RecordingCommandBuffer::blit_image`]: vulkano::command_buffer::RecordingCommandBuffer::blit_image
+    Blit,
+
+    /// The blit was performed with the compute shader fallback.
+    Compute,
+}
+
+/// Records image blits, falling back to a compute shader when the formats can't be blitted.
+///
+/// See [the module-level documentation] for more details.
+///
+/// [the module-level documentation]: self
+#[derive(Debug)]
+pub struct ImageBlitter {
+    device: Arc<Device>,
+    descriptor_set_allocator: Arc<dyn DescriptorSetAllocator>,
+    samplers: Mutex<HashMap<Filter, Arc<Sampler>>>,
+    // Keyed by the GLSL image format qualifier of the destination, or `None` for writes without a
+    // format.
+    pipelines: Mutex<HashMap<Option<&'static str>, Arc<ComputePipeline>>>,
+}
+
+impl ImageBlitter {
+    /// Creates a new `ImageBlitter`.
+    ///
+    /// `descriptor_set_allocator` is used to allocate the descriptor sets of the compute fallback.
+    #[inline]
+    pub fn new(
+        device: Arc<Device>,
+        descriptor_set_allocator: Arc<dyn DescriptorSetAllocator>,
+    ) -> Self {
+        ImageBlitter {
+            device,
+            descriptor_set_allocator,
+            samplers: Mutex::default(),
+            pipelines: Mutex::default(),
+        }
+    }
+
+    /// Returns the method that [`blit_image`] would use for `blit_image_info`, or `None` if
+    /// neither method can perform it.
+    ///
+    /// [`blit_image`]: Self::blit_image
+    pub fn method(&self, blit_image_info: &BlitImageInfo) -> Option<BlitMethod> {
+        if supports_blit(blit_image_info) {
+            Some(BlitMethod::Blit)
+        } else if self.validate_compute(blit_image_info).is_ok() {
+            Some(BlitMethod::Compute)
+        } else {
+            None
+        }
+    }
+
+    /// Blits between two images, recording the commands into `builder`.
+    ///
+    /// If the implementation supports blitting between the formats of the images, this records a
+    /// regular blit. Otherwise, the blit is performed with a compute shader, in which case
+    /// `blit_image_info.src_image_layout` and `blit_image_info.dst_image_layout` are ignored.
+    ///
+    /// When the compute fallback is used, the compute pipeline, descriptor set 0 and the push
+    /// constants that are bound to `builder` are replaced, and are not restored afterwards.
+    ///
+    /// Returns the method that was used.
+    pub fn blit_image(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        blit_image_info: BlitImageInfo,
+    ) -> Result<BlitMethod, ImageBlitError> {
+        if supports_blit(&blit_image_info) {
+            builder.blit_image(blit_image_info)?;
+
+            return Ok(BlitMethod::Blit);
+        }
+
+        let dst_qualifier = self.validate_compute(&blit_image_info)?;
+        let pipeline = self.pipeline(dst_qualifier)?;
+        let sampler = self.sampler(blit_image_info.filter)?;
+        let layout = pipeline.layout().clone();
+
+        builder.bind_pipeline_compute(pipeline.clone())?;
+
+        for region in &blit_image_info.regions {
+            for layer_offset in 0..region.src_subresource.array_layers.len() as u32 {
+                let src_view = ImageView::new(
+                    blit_image_info.src_image.clone(),
+                    layer_view_create_info(
+                        &blit_image_info.src_image,
+                        region.src_subresource.aspects,
+                        region.src_subresource.mip_level,
+                        region.src_subresource.array_layers.start + layer_offset,
+                        ImageUsage::SAMPLED,
+                    ),
+                )?;
+                let dst_view = ImageView::new(
+                    blit_image_info.dst_image.clone(),
+                    layer_view_create_info(
+                        &blit_image_info.dst_image,
+                        region.dst_subresource.aspects,
+                        region.dst_subresource.mip_level,
+                        region.dst_subresource.array_layers.start + layer_offset,
+                        ImageUsage::STORAGE,
+                    ),
+                )?;
+                let descriptor_set = DescriptorSet::new(
+                    self.descriptor_set_allocator.clone(),
+                    layout.set_layouts()[0].clone(),
+                    [
+                        WriteDescriptorSet::image_view_sampler(0, src_view, sampler.clone()),
+                        WriteDescriptorSet::image_view(1, dst_view),
+                    ],
+                    [],
+                )?;
+
+                let (push_constants, group_counts) =
+                    region_push_constants(&blit_image_info.src_image, region);
+
+                builder
+                    .bind_descriptor_sets(
+                        PipelineBindPoint::Compute,
+                        layout.clone(),
+                        0,
+                        descriptor_set,
+                    )?
+                    .push_constants(layout.clone(), 0, push_constants)?;
+
+                // SAFETY: The shader only reads the source through a sampler, and only writes to
+                // the texels between `region.dst_offsets`, which `validate_compute` checked to be
+                // within the extent of the destination subresource.
+                unsafe { builder.dispatch(group_counts) }?;
+            }
+        }
+
+        Ok(BlitMethod::Compute)
+    }
+
+    /// Checks whether the compute fallback can perform the blit, and returns the format
+    /// qualifier to use for the destination.
+    fn validate_compute(
+        &self,
+        blit_image_info: &BlitImageInfo,
+    ) -> Result<Option<&'static str>, Box<ValidationError>> {
+        let &BlitImageInfo {
+            ref src_image,
+            ref dst_image,
+            filter,
+            ..
+        } = blit_image_info;
+
+        for (name, image) in [("src_image", src_image), ("dst_image", dst_image)] {
+            if image.image_type() != ImageType::Dim2d {
+                return Err(Box::new(ValidationError {
+                    context: format!("{name}.image_type()").into(),
+                    problem: "is not `ImageType::Dim2d`, which the compute fallback requires"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+
+            if image.samples() != SampleCount::Sample1 {
+                return Err(Box::new(ValidationError {
+                    context: format!("{name}.samples()").into(),
+                    problem: "is not `SampleCount::Sample1`, which the compute fallback requires"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        if !src_image.usage().intersects(ImageUsage::SAMPLED) {
+            return Err(Box::new(ValidationError {
+                context: "src_image.usage()".into(),
+                problem: "does not contain `ImageUsage::SAMPLED`, which the compute fallback \
+                    requires"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        let mut src_required_features = FormatFeatures::SAMPLED_IMAGE;
+
+        if filter == Filter::Linear {
+            src_required_features |= FormatFeatures::SAMPLED_IMAGE_FILTER_LINEAR;
+        }
+
+        if !src_image.format_features().contains(src_required_features) {
+            return Err(Box::new(ValidationError {
+                context: "src_image.format_features()".into(),
+                problem: format!(
+                    "does not contain `{src_required_features:?}`, which the compute fallback \
+                    requires"
+                )
+                .into(),
+                ..Default::default()
+            }));
+        }
+
+        if !dst_image.usage().intersects(ImageUsage::STORAGE) {
+            return Err(Box::new(ValidationError {
+                context: "dst_image.usage()".into(),
+                problem: "does not contain `ImageUsage::STORAGE`, which the compute fallback \
+                    requires"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        if !dst_image
+            .format_features()
+            .intersects(FormatFeatures::STORAGE_IMAGE)
+        {
+            return Err(Box::new(ValidationError {
+                context: "dst_image.format_features()".into(),
+                problem: "does not contain `FormatFeatures::STORAGE_IMAGE`, which the compute \
+                    fallback requires"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        for (region_index, region) in blit_image_info.regions.iter().enumerate() {
+            let src_aspects = region.src_subresource.aspects;
+            let src_numeric_type = if src_aspects == ImageAspects::COLOR {
+                src_image.format().numeric_format_color()
+            } else if src_aspects == ImageAspects::DEPTH {
+                src_image.format().numeric_format_depth()
+            } else {
+                None
+            };
+
+            if src_numeric_type.map(|format| format.numeric_type()) != Some(NumericType::Float) {
+                return Err(Box::new(ValidationError {
+                    context: format!("regions[{region_index}].src_subresource.aspects").into(),
+                    problem: "does not select a single color or depth aspect with a \
+                        floating-point numeric type, which the compute fallback requires"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+
+            if region.dst_subresource.aspects != ImageAspects::COLOR
+                || dst_image
+                    .format()
+                    .numeric_format_color()
+                    .map(|format| format.numeric_type())
+                    != Some(NumericType::Float)
+            {
+                return Err(Box::new(ValidationError {
+                    context: format!("regions[{region_index}].dst_subresource.aspects").into(),
+                    problem: "does not select the color aspect of a format with a \
+                        floating-point numeric type, which the compute fallback requires"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+
+            for (name, image, subresource, offsets) in [
+                (
+                    "src",
+                    src_image,
+                    &region.src_subresource,
+                    &region.src_offsets,
+                ),
+                (
+                    "dst",
+                    dst_image,
+                    &region.dst_subresource,
+                    &region.dst_offsets,
+                ),
+            ] {
+                if subresource.mip_level >= image.mip_levels() {
+                    return Err(Box::new(ValidationError {
+                        context: format!("regions[{region_index}].{name}_subresource.mip_level")
+                            .into(),
+                        problem: format!("is not less than `{name}_image.mip_levels()`").into(),
+                        ..Default::default()
+                    }));
+                }
+
+                if subresource.array_layers.end > image.array_layers() {
+                    return Err(Box::new(ValidationError {
+                        context: format!(
+                            "regions[{region_index}].{name}_subresource.array_layers.end"
+                        )
+                        .into(),
+                        problem: format!("is greater than `{name}_image.array_layers()`").into(),
+                        ..Default::default()
+                    }));
+                }
+
+                let extent = mip_extent(image, subresource.mip_level);
+
+                for axis in 0..2 {
+                    if offsets[0][axis] > extent[axis] || offsets[1][axis] > extent[axis] {
+                        return Err(Box::new(ValidationError {
+                            context: format!("regions[{region_index}].{name}_offsets").into(),
+                            problem: format!(
+                                "coordinate {axis} is greater than the extent of the mip level \
+                                of `{name}_image`"
+                            )
+                            .into(),
+                            ..Default::default()
+                        }));
+                    }
+                }
+
+                if offsets[0][2] != 0 || offsets[1][2] != 1 {
+                    return Err(Box::new(ValidationError {
+                        context: format!("regions[{region_index}].{name}_offsets").into(),
+                        problem: "coordinate 2 is not `0` and `1` respectively, which 2D images \
+                            require"
+                            .into(),
+                        ..Default::default()
+                    }));
+                }
+            }
+
+            if region.src_subresource.array_layers.len()
+                != region.dst_subresource.array_layers.len()
+            {
+                return Err(Box::new(ValidationError {
+                    context: format!("regions[{region_index}]").into(),
+                    problem: "`src_subresource.array_layers` and \
+                        `dst_subresource.array_layers` do not have the same length"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        if dst_image
+            .format_features()
+            .intersects(FormatFeatures::STORAGE_WRITE_WITHOUT_FORMAT)
+            && self
+                .device
+                .enabled_features()
+                .shader_storage_image_write_without_format
+        {
+            return Ok(None);
+        }
+
+        match glsl_format_qualifier(dst_image.format()) {
+            Some(qualifier) => Ok(Some(qualifier)),
+            None => Err(Box::new(ValidationError {
+                context: "dst_image.format()".into(),
+                problem: "has no GLSL image format qualifier, and writing to it without a \
+                    format is not supported"
+                    .into(),
+                ..Default::default()
+            })),
+        }
+    }
+
+    fn pipeline(
+        &self,
+        dst_qualifier: Option<&'static str>,
+    ) -> Result<Arc<ComputePipeline>, ImageBlitError> {
+        let mut pipelines = self.pipelines.lock().unwrap();
+
+        if let Some(pipeline) = pipelines.get(&dst_qualifier) {
+            return Ok(pipeline.clone());
+        }
+
+        let dst_layout = match dst_qualifier {
+            Some(qualifier) => format!("set = 0, binding = 1, {qualifier}"),
+            None => "set = 0, binding = 1".to_owned(),
+        };
+        let module = compile_glsl(
+            self.device.clone(),
+            SHADER_SOURCE,
+            &GlslCompileInfo {
+                source_name: "blit.comp".to_owned(),
+                macro_defines: vec![("DST_LAYOUT".to_owned(), Some(dst_layout))],
+                ..GlslCompileInfo::new(ShaderStage::Compute)
+            },
+        )?;
+        let stage = PipelineShaderStageCreateInfo::new(module.entry_point("main").unwrap());
+        let layout = PipelineLayout::new(
+            self.device.clone(),
+            PipelineDescriptorSetLayoutCreateInfo::from_stages([&stage])
+                .into_pipeline_layout_create_info(self.device.clone())
+                .map_err(|err| err.error)?,
+        )?;
+        let pipeline = ComputePipeline::new(
+            self.device.clone(),
+            None,
+            ComputePipelineCreateInfo::stage_layout(stage, layout),
+        )?;
+        pipelines.insert(dst_qualifier, pipeline.clone());
+
+        Ok(pipeline)
+    }
+
+    fn sampler(&self, filter: Filter) -> Result<Arc<Sampler>, Validated<VulkanError>> {
+        let mut samplers = self.samplers.lock().unwrap();
+
+        if let Some(sampler) = samplers.get(&filter) {
+            return Ok(sampler.clone());
+        }
+
+        let sampler = Sampler::new(
+            self.device.clone(),
+            SamplerCreateInfo {
+                mag_filter: filter,
+                min_filter: filter,
+                ..Default::default()
+            },
+        )?;
+        samplers.insert(filter, sampler.clone());
+
+        Ok(sampler)
+    }
+}
+
+unsafe impl DeviceOwned for ImageBlitter {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+/// Returns whether the implementation can blit between the images of `blit_image_info`.
+fn supports_blit(blit_image_info: &BlitImageInfo) -> bool {
+    let &BlitImageInfo {
+        ref src_image,
+        ref dst_image,
+        filter,
+        ..
+    } = blit_image_info;
+
+    let src_format = src_image.format();
+    let dst_format = dst_image.format();
+
+    if !src_image.usage().intersects(ImageUsage::TRANSFER_SRC)
+        || !dst_image.usage().intersects(ImageUsage::TRANSFER_DST)
+        || !src_image
+            .format_features()
+            .intersects(FormatFeatures::BLIT_SRC)
+        || !dst_image
+            .format_features()
+            .intersects(FormatFeatures::BLIT_DST)
+    {
+        return false;
+    }
+
+    if filter == Filter::Linear
+        && !src_image
+            .format_features()
+            .intersects(FormatFeatures::SAMPLED_IMAGE_FILTER_LINEAR)
+    {
+        return false;
+    }
+
+    // Depth/stencil images can only be blitted to images of the same format, without filtering.
+    if src_format
+        .aspects()
+        .intersects(ImageAspects::DEPTH | ImageAspects::STENCIL)
+        || dst_format
+            .aspects()
+            .intersects(ImageAspects::DEPTH | ImageAspects::STENCIL)
+    {
+        return src_format == dst_format && filter == Filter::Nearest;
+    }
+
+    // Integer formats can only be blitted to formats of the same numeric type.
+    let src_type = src_format.numeric_format_color().map(|f| f.numeric_type());
+    let dst_type = dst_format.numeric_format_color().map(|f| f.numeric_type());
+
+    src_type == dst_type
+}
+
+fn layer_view_create_info(
+    image: &Image,
+    aspects: ImageAspects,
+    mip_level: u32,
+    array_layer: u32,
+    usage: ImageUsage,
+) -> ImageViewCreateInfo {
+    ImageViewCreateInfo {
+        view_type: ImageViewType::Dim2d,
+        format: image.format(),
+        subresource_range: ImageSubresourceRange {
+            aspects,
+            mip_levels: mip_level..mip_level + 1,
+            array_layers: array_layer..array_layer + 1,
+        },
+        usage,
+        ..Default::default()
+    }
+}
+
+/// Computes the push constants and the workgroup counts for one layer of `region`.
+///
+/// The mapping matches `vkCmdBlitImage`: the center of each destination texel is mapped linearly
+/// from the destination offsets to the source offsets, which flips the image if the offsets are
+/// in a different order.
+fn region_push_constants(src_image: &Image, region: &ImageBlit) -> ([f32; 8], [u32; 3]) {
+    let src_mip_extent = mip_extent(src_image, region.src_subresource.mip_level);
+    let mut push_constants = [0.0; 8];
+    let mut group_counts = [1; 3];
+
+    for axis in 0..2 {
+        let [src0, src1] = [
+            region.src_offsets[0][axis] as f32,
+            region.src_offsets[1][axis] as f32,
+        ];
+        let [dst0, dst1] = [
+            region.dst_offsets[0][axis] as f32,
+            region.dst_offsets[1][axis] as f32,
+        ];
+        let dst_min = dst0.min(dst1);
+        let dst_extent = (dst1 - dst0).abs();
+        let step = if dst_extent == 0.0 {
+            0.0
+        } else {
+            (src1 - src0) / (dst1 - dst0)
+        };
+        let src_size = src_mip_extent[axis] as f32;
+
+        push_constants[axis] = (src0 + (dst_min - dst0) * step) / src_size;
+        push_constants[2 + axis] = step / src_size;
+        push_constants[4 + axis] = dst_min;
+        push_constants[6 + axis] = dst_extent;
+        group_counts[axis] = (dst_extent as u32).div_ceil(8);
+    }
+
+    (push_constants, group_counts)
+}
+
+/// Returns the width and height of `mip_level` of `image`.
+fn mip_extent(image: &Image, mip_level: u32) -> [u32; 2] {
+    let extent = image.extent();
+
+    [
+        (extent[0] >> mip_level).max(1),
+        (extent[1] >> mip_level).max(1),
+    ]
+}
+
+/// Returns the GLSL image format qualifier for `format`, if it has one.
+fn glsl_format_qualifier(format: Format) -> Option<&'static str> {
+    Some(match format {
+        Format::R32G32B32A32_SFLOAT => "rgba32f",
+        Format::R16G16B16A16_SFLOAT => "rgba16f",
+        Format::R32G32_SFLOAT => "rg32f",
+        Format::R16G16_SFLOAT => "rg16f",
+        Format::B10G11R11_UFLOAT_PACK32 => "r11f_g11f_b10f",
+        Format::R32_SFLOAT => "r32f",
+        Format::R16_SFLOAT => "r16f",
+        Format::R16G16B16A16_UNORM => "rgba16",
+        Format::A2B10G10R10_UNORM_PACK32 => "rgb10_a2",
+        Format::R8G8B8A8_UNORM => "rgba8",
+        Format::R16G16_UNORM => "rg16",
+        Format::R8G8_UNORM => "rg8",
+        Format::R16_UNORM => "r16",
+        Format::R8_UNORM => "r8",
+        Format::R16G16B16A16_SNORM => "rgba16_snorm",
+        Format::R8G8B8A8_SNORM => "rgba8_snorm",
+        Format::R16G16_SNORM => "rg16_snorm",
+        Format::R8G8_SNORM => "rg8_snorm",
+        Format::R16_SNORM => "r16_snorm",
+        Format::R8_SNORM => "r8_snorm",
+        _ => return None,
+    })
+}
+
+/// Error that can happen when blitting with an [`ImageBlitter`].
+#[derive(Clone, Debug)]
+pub enum ImageBlitError {
+    /// Neither method supports the blit, or recording a command failed validation.
+    ValidationError(Box<ValidationError>),
+
+    /// Compiling the compute shader failed.
+    ShaderCompile(ShaderCompileError),
+
+    /// Creating an object for the compute fallback failed.
+    VulkanError(Validated<VulkanError>),
+}
+
+impl Error for ImageBlitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ValidationError(err) => Some(err),
+            Self::ShaderCompile(err) => Some(err),
+            Self::VulkanError(err) => Some(err),
+        }
+    }
+}
+
+impl Display for ImageBlitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::ValidationError(_) => write!(f, "the blit is not supported or not valid"),
+            Self::ShaderCompile(_) => write!(f, "failed to compile the blit shader"),
+            Self::VulkanError(_) => write!(f, "failed to create an object for the blit"),
+        }
+    }
+}
+
+impl From<Box<ValidationError>> for ImageBlitError {
+    fn from(err: Box<ValidationError>) -> Self {
+        Self::ValidationError(err)
+    }
+}
+
+impl From<ShaderCompileError> for ImageBlitError {
+    fn from(err: ShaderCompileError) -> Self {
+        Self::ShaderCompile(err)
+    }
+}
+
+impl From<Validated<VulkanError>> for ImageBlitError {
+    fn from(err: Validated<VulkanError>) -> Self {
+        Self::VulkanError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vulkano::{
+        descriptor_set::allocator::StandardDescriptorSetAllocator,
+        device::{DeviceCreateInfo, QueueCreateInfo},
+        image::ImageCreateInfo,
+        instance::Instance,
+        memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+        VulkanLibrary,
+    };
+
+    fn blitter() -> Option<(ImageBlitter, Arc<StandardMemoryAllocator>)> {
+        let library = VulkanLibrary::new().ok()?;
+        let instance = Instance::new(library, Default::default()).ok()?;
+        let physical_device = instance.enumerate_physical_devices().ok()?.next()?;
+        let (device, _) = Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                queue_create_infos: vec![QueueCreateInfo::default()],
+                ..Default::default()
+            },
+        )
+        .ok()?;
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+        let descriptor_set_allocator = Arc::new(StandardDescriptorSetAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+
+        Some((
+            ImageBlitter::new(device, descriptor_set_allocator),
+            memory_allocator,
+        ))
+    }
+
+    fn image(
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        extent: u32,
+        mip_levels: u32,
+        usage: ImageUsage,
+    ) -> Arc<Image> {
+        Image::new(
+            memory_allocator.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: Format::R8G8B8A8_UNORM,
+                extent: [extent, extent, 1],
+                mip_levels,
+                usage,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn region_push_constants_flip() {
+        let Some((_, memory_allocator)) = blitter() else {
+            return;
+        };
+        let src_image = image(&memory_allocator, 64, 1, ImageUsage::SAMPLED);
+        let region = ImageBlit {
+            src_offsets: [[0, 0, 0], [64, 64, 1]],
+            dst_offsets: [[32, 32, 0], [0, 0, 1]],
+            ..Default::default()
+        };
+
+        let (push_constants, group_counts) = region_push_constants(&src_image, &region);
+
+        // The destination is flipped and half the size, so the first destination texel samples
+        // the far end of the source, and each step moves back by two source texels.
+        assert_eq!(push_constants[0..2], [1.0, 1.0]);
+        assert_eq!(push_constants[2..4], [-2.0 / 64.0, -2.0 / 64.0]);
+        assert_eq!(push_constants[4..6], [0.0, 0.0]);
+        assert_eq!(push_constants[6..8], [32.0, 32.0]);
+        assert_eq!(group_counts, [4, 4, 1]);
+    }
+
+    #[test]
+    fn offsets_out_of_bounds() {
+        let Some((blitter, memory_allocator)) = blitter() else {
+            return;
+        };
+        let src_image = image(&memory_allocator, 64, 1, ImageUsage::SAMPLED);
+        let dst_image = image(&memory_allocator, 32, 2, ImageUsage::STORAGE);
+        let blit_image_info = BlitImageInfo::images(src_image, dst_image);
+
+        if blitter.validate_compute(&blit_image_info).is_err() {
+            // The implementation doesn't support the compute fallback for these images.
+            return;
+        }
+
+        let mut invalid_info = blit_image_info.clone();
+        invalid_info.regions[0].dst_offsets[1][0] = 33;
+        let err = blitter.validate_compute(&invalid_info).unwrap_err();
+        assert_eq!(err.context, "regions[0].dst_offsets");
+
+        let mut invalid_info = blit_image_info.clone();
+        invalid_info.regions[0].src_offsets[1][1] = 65;
+        let err = blitter.validate_compute(&invalid_info).unwrap_err();
+        assert_eq!(err.context, "regions[0].src_offsets");
+
+        // The offsets cover mip level 0, which is larger than mip level 1.
+        let mut invalid_info = blit_image_info.clone();
+        invalid_info.regions[0].dst_subresource.mip_level = 1;
+        let err = blitter.validate_compute(&invalid_info).unwrap_err();
+        assert_eq!(err.context, "regions[0].dst_offsets");
+
+        let mut invalid_info = blit_image_info;
+        invalid_info.regions[0].dst_subresource.mip_level = 2;
+        let err = blitter.validate_compute(&invalid_info).unwrap_err();
+        assert_eq!(err.context, "regions[0].dst_subresource.mip_level");
+    }
+}
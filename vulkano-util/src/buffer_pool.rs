@@ -0,0 +1,275 @@
+//! Recycling buffers for short-lived data.
+//!
+//! Data that only lives for a frame or two, such as staging data and readbacks, would otherwise
+//! need a new buffer every time, and allocating and freeing memory that often takes a noticeable
+//! amount of time on some platforms. [`BufferPool`] rounds the requested sizes up to size classes,
+//! and gives out buffers that were used before whenever one with the same size class and usage is
+//! available.
+//!
+//! Buffers are handed back to the pool with [`reclaim`](BufferPool::reclaim), together with the
+//! future of the last submission that uses them. They are only given out again once that future
+//! has finished and nothing else refers to them anymore.
+//!
+//! ```no_run
+//! # use vulkano::command_buffer::RecordingCommandBuffer;
+//! # use vulkano_util::context::VulkanoContext;
+//! use std::sync::Arc;
+//! use vulkano::{buffer::BufferUsage, sync::GpuFuture};
+//! use vulkano_util::buffer_pool::BufferPool;
+//!
+//! # let context: VulkanoContext = todo!();
+//! # let builder: RecordingCommandBuffer = todo!();
+//! let buffer_pool = BufferPool::new(context.memory_allocator().clone(), Default::default());
+//!
+//! // Each frame:
+//! let staging_buffer = buffer_pool
+//!     .allocate(4096, BufferUsage::TRANSFER_SRC)
+//!     .unwrap();
+//! // Record commands that use `staging_buffer` into `builder`...
+//! let future = builder
+//!     .end()
+//!     .unwrap()
+//!     .execute(context.graphics_queue().clone())
+//!     .unwrap()
+//!     .then_signal_fence_and_flush()
+//!     .unwrap();
+//! buffer_pool.reclaim([staging_buffer], Arc::new(future));
+//! ```
+
+use ahash::HashMap;
+use std::{
+    fmt::{Debug, Error as FmtError, Formatter},
+    mem,
+    sync::{Arc, Mutex},
+};
+use vulkano::{
+    buffer::{AllocateBufferError, Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
+    sync::future::{FenceSignalFuture, GpuFuture},
+    DeviceSize, Validated,
+};
+
+/// Gives out buffers by size class and usage, reusing the buffers that were reclaimed once the
+/// device has finished with them.
+pub struct BufferPool {
+    memory_allocator: Arc<dyn MemoryAllocator>,
+    memory_type_filter: MemoryTypeFilter,
+    min_size: DeviceSize,
+    state: Mutex<PoolState>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    // Buffers that can be given out, by usage and size.
+    free: HashMap<(BufferUsage, DeviceSize), Vec<Arc<Buffer>>>,
+    // Buffers that were reclaimed, but may still be in use.
+    reclaimed: Vec<Reclaimed>,
+}
+
+struct Reclaimed {
+    // `None` once the future has finished.
+    future: Option<Box<dyn Finish>>,
+    buffers: Vec<Arc<Buffer>>,
+}
+
+impl BufferPool {
+    /// Creates a new `BufferPool` that allocates its buffers from `memory_allocator`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.min_size` is not a power of two.
+    pub fn new(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        create_info: BufferPoolCreateInfo,
+    ) -> Self {
+        let BufferPoolCreateInfo {
+            memory_type_filter,
+            min_size,
+        } = create_info;
+
+        assert!(min_size.is_power_of_two());
+
+        BufferPool {
+            memory_allocator,
+            memory_type_filter,
+            min_size,
+            state: Mutex::new(PoolState::default()),
+        }
+    }
+
+    /// Returns the memory allocator.
+    #[inline]
+    pub fn memory_allocator(&self) -> &Arc<dyn MemoryAllocator> {
+        &self.memory_allocator
+    }
+
+    /// Returns a subbuffer of `size` bytes, of a buffer that was created with `usage`.
+    ///
+    /// The size of the buffer is `size` rounded up to the next power of two, or the minimum size
+    /// of the pool if that's larger. If a reclaimed buffer with this size and usage is no longer
+    /// in use, it is returned instead of a new buffer, with its previous contents.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `size` is zero.
+    pub fn allocate(
+        &self,
+        size: DeviceSize,
+        usage: BufferUsage,
+    ) -> Result<Subbuffer<[u8]>, Validated<AllocateBufferError>> {
+        assert_ne!(size, 0);
+
+        let class_size = size.max(self.min_size).next_power_of_two();
+        let reused = {
+            let mut state = self.state.lock().unwrap();
+            state.collect_finished();
+
+            state.free.get_mut(&(usage, class_size)).and_then(Vec::pop)
+        };
+
+        let subbuffer = match reused {
+            Some(buffer) => Subbuffer::new(buffer),
+            None => Buffer::new_slice::<u8>(
+                self.memory_allocator.clone(),
+                BufferCreateInfo {
+                    usage,
+                    ..Default::default()
+                },
+                AllocationCreateInfo {
+                    memory_type_filter: self.memory_type_filter,
+                    ..Default::default()
+                },
+                class_size,
+            )?,
+        };
+
+        Ok(subbuffer.slice(..size))
+    }
+
+    /// Hands `buffers` back to the pool, to be reused once `future` has finished.
+    ///
+    /// `future` must be the future of the last submission that uses the buffers, and must be
+    /// flushed for its fence to ever be signaled. Even after it has finished, a buffer is only
+    /// reused once all other subbuffers that refer to it have been dropped.
+    ///
+    /// The buffers must have been allocated from this pool. Buffers whose size isn't one of the
+    /// size classes of the pool are dropped instead.
+    pub fn reclaim<T, F>(
+        &self,
+        buffers: impl IntoIterator<Item = Subbuffer<T>>,
+        future: Arc<FenceSignalFuture<F>>,
+    ) where
+        T: ?Sized,
+        F: GpuFuture + Send + 'static,
+    {
+        let buffers: Vec<_> = buffers
+            .into_iter()
+            .map(|subbuffer| subbuffer.buffer().clone())
+            .filter(|buffer| buffer.size().is_power_of_two() && buffer.size() >= self.min_size)
+            .collect();
+
+        if buffers.is_empty() {
+            return;
+        }
+
+        self.state.lock().unwrap().reclaimed.push(Reclaimed {
+            future: Some(Box::new(future)),
+            buffers,
+        });
+    }
+
+    /// Drops all buffers that are currently not in use, freeing their memory.
+    pub fn trim(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.collect_finished();
+        state.free.clear();
+    }
+}
+
+impl Debug for BufferPool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_struct("BufferPool")
+            .field("memory_allocator", &self.memory_allocator)
+            .field("memory_type_filter", &self.memory_type_filter)
+            .field("min_size", &self.min_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PoolState {
+    // Moves the reclaimed buffers that are no longer in use to the free lists.
+    fn collect_finished(&mut self) {
+        let PoolState { free, reclaimed } = self;
+
+        reclaimed.retain_mut(|reclaimed| {
+            if let Some(future) = &mut reclaimed.future {
+                if !future.finish() {
+                    return true;
+                }
+
+                reclaimed.future = None;
+            }
+
+            for buffer in mem::take(&mut reclaimed.buffers) {
+                // Subbuffers that the application still holds on to, and command buffers that
+                // haven't been dropped yet, keep the buffer in use.
+                if Arc::strong_count(&buffer) == 1 {
+                    free.entry((buffer.usage(), buffer.size()))
+                        .or_default()
+                        .push(buffer);
+                } else {
+                    reclaimed.buffers.push(buffer);
+                }
+            }
+
+            !reclaimed.buffers.is_empty()
+        });
+    }
+}
+
+/// Parameters to create a new [`BufferPool`].
+#[derive(Clone, Debug)]
+pub struct BufferPoolCreateInfo {
+    /// The memory type filter that the buffers are allocated with.
+    ///
+    /// The default value is [`MemoryTypeFilter::PREFER_HOST`] |
+    /// [`MemoryTypeFilter::HOST_SEQUENTIAL_WRITE`], which suits staging buffers. For readback
+    /// buffers, use [`MemoryTypeFilter::HOST_RANDOM_ACCESS`] instead.
+    pub memory_type_filter: MemoryTypeFilter,
+
+    /// The size of the smallest size class. Smaller requests are rounded up to this size.
+    ///
+    /// This must be a power of two.
+    ///
+    /// The default value is 256.
+    pub min_size: DeviceSize,
+}
+
+impl Default for BufferPoolCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+            min_size: 256,
+        }
+    }
+}
+
+// A future that the pool waits on before reusing buffers.
+trait Finish: Send {
+    // Cleans up the future if it has finished, and returns whether it has.
+    fn finish(&mut self) -> bool;
+}
+
+impl<F> Finish for Arc<FenceSignalFuture<F>>
+where
+    F: GpuFuture + Send,
+{
+    fn finish(&mut self) -> bool {
+        // Cleaning up drops the command buffers, which releases the buffers that they use.
+        self.cleanup_finished();
+
+        self.is_signaled().unwrap_or(true)
+    }
+}
@@ -0,0 +1,298 @@
+//! Running compute shaders with a single call.
+//!
+//! Dispatching a compute shader normally involves creating a pipeline layout and a pipeline,
+//! allocating a descriptor set, recording a command buffer and submitting it. [`ComputeRunner`]
+//! does all of this in [`run`](ComputeRunner::run), and keeps the pipelines it creates around so
+//! that running the same entry point again is cheap.
+//!
+//! [`VulkanoContext`] owns a runner for its compute queue, which the [`run`] function of this
+//! module uses:
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::{buffer::Subbuffer, shader::ShaderModule};
+//! use vulkano::descriptor_set::WriteDescriptorSet;
+//! use vulkano_util::{
+//!     compute,
+//!     context::{VulkanoConfig, VulkanoContext},
+//! };
+//!
+//! # let shader: Arc<ShaderModule> = todo!();
+//! # let data: Subbuffer<[u32]> = todo!();
+//! let context = VulkanoContext::new(VulkanoConfig::compute_only());
+//! let entry_point = shader.entry_point("main").unwrap();
+//!
+//! let future = unsafe {
+//!     compute::run(
+//!         &context,
+//!         entry_point,
+//!         [WriteDescriptorSet::buffer(0, data.clone())],
+//!         (),
+//!         [1024 / 64, 1, 1],
+//!     )
+//! }
+//! .unwrap();
+//! future.wait(None).unwrap();
+//! ```
+
+use crate::context::VulkanoContext;
+use ahash::HashMap;
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    mem::size_of,
+    sync::{Arc, Mutex},
+};
+use vulkano::{
+    buffer::BufferContents,
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferExecError,
+        CommandBufferExecFuture, CommandBufferLevel, CommandBufferUsage, RecordingCommandBuffer,
+    },
+    descriptor_set::{
+        allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
+    },
+    device::{DeviceOwned, Queue},
+    pipeline::{
+        compute::ComputePipelineCreateInfo, layout::PipelineDescriptorSetLayoutCreateInfo,
+        ComputePipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    shader::EntryPoint,
+    sync::{
+        future::{FenceSignalFuture, NowFuture},
+        GpuFuture,
+    },
+    Validated, ValidationError, VulkanError,
+};
+
+/// A future that represents the moment a dispatch started with [`ComputeRunner::run`] has
+/// finished executing.
+pub type ComputeFuture = FenceSignalFuture<CommandBufferExecFuture<NowFuture>>;
+
+/// Runs compute shaders on a queue, caching the pipelines that are created for them.
+#[derive(Debug)]
+pub struct ComputeRunner {
+    queue: Arc<Queue>,
+    command_buffer_allocator: Arc<StandardCommandBufferAllocator>,
+    descriptor_set_allocator: Arc<StandardDescriptorSetAllocator>,
+    // The entry point is kept alive so that the address of its module can't be reused.
+    pipelines: Mutex<HashMap<(usize, String), (EntryPoint, Arc<ComputePipeline>)>>,
+}
+
+impl ComputeRunner {
+    /// Creates a new `ComputeRunner` that submits its dispatches to `queue`.
+    pub fn new(queue: Arc<Queue>) -> Self {
+        let command_buffer_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            queue.device().clone(),
+            Default::default(),
+        ));
+        let descriptor_set_allocator = Arc::new(StandardDescriptorSetAllocator::new(
+            queue.device().clone(),
+            Default::default(),
+        ));
+
+        ComputeRunner {
+            queue,
+            command_buffer_allocator,
+            descriptor_set_allocator,
+            pipelines: Mutex::new(HashMap::default()),
+        }
+    }
+
+    /// Returns the queue that dispatches are submitted to.
+    #[inline]
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
+
+    /// Returns the compute pipeline for `entry_point`, creating it if it isn't cached yet.
+    ///
+    /// The pipeline layout is derived from the descriptor bindings and push constants that the
+    /// entry point uses.
+    pub fn pipeline(&self, entry_point: EntryPoint) -> Result<Arc<ComputePipeline>, ComputeError> {
+        let key = (
+            Arc::as_ptr(entry_point.module()) as usize,
+            entry_point.info().name.clone(),
+        );
+        let mut pipelines = self.pipelines.lock().unwrap();
+
+        if let Some((_, pipeline)) = pipelines.get(&key) {
+            return Ok(pipeline.clone());
+        }
+
+        let device = self.queue.device();
+        let stage = PipelineShaderStageCreateInfo::new(entry_point.clone());
+        let layout = PipelineLayout::new(
+            device.clone(),
+            PipelineDescriptorSetLayoutCreateInfo::from_stages([&stage])
+                .into_pipeline_layout_create_info(device.clone())
+                .map_err(|err| ComputeError::Pipeline(err.error))?,
+        )
+        .map_err(ComputeError::Pipeline)?;
+        let pipeline = ComputePipeline::new(
+            device.clone(),
+            None,
+            ComputePipelineCreateInfo::stage_layout(stage, layout),
+        )
+        .map_err(ComputeError::Pipeline)?;
+        pipelines.insert(key, (entry_point, pipeline.clone()));
+
+        Ok(pipeline)
+    }
+
+    /// Dispatches `entry_point` with `group_counts` work groups, and returns a future that is
+    /// signaled once it has finished executing.
+    ///
+    /// The resources in `descriptor_writes` are bound to descriptor set 0, and `push_constants`
+    /// are pushed at offset 0. If the entry point doesn't use push constants, `()` can be passed.
+    ///
+    /// # Safety
+    ///
+    /// - The general [shader safety requirements](vulkano::shader#safety) apply.
+    pub unsafe fn run<Pc>(
+        &self,
+        entry_point: EntryPoint,
+        descriptor_writes: impl IntoIterator<Item = WriteDescriptorSet>,
+        push_constants: Pc,
+        group_counts: [u32; 3],
+    ) -> Result<ComputeFuture, ComputeError>
+    where
+        Pc: BufferContents,
+    {
+        let pipeline = self.pipeline(entry_point)?;
+        let layout = pipeline.layout().clone();
+        let descriptor_writes: Vec<_> = descriptor_writes.into_iter().collect();
+
+        let mut builder = RecordingCommandBuffer::new(
+            self.command_buffer_allocator.clone(),
+            self.queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .map_err(ComputeError::Submission)?;
+        builder
+            .bind_pipeline_compute(pipeline)
+            .map_err(ComputeError::Record)?;
+
+        match layout.set_layouts().first() {
+            Some(set_layout) => {
+                let descriptor_set = DescriptorSet::new(
+                    self.descriptor_set_allocator.clone(),
+                    set_layout.clone(),
+                    descriptor_writes,
+                    [],
+                )
+                .map_err(ComputeError::DescriptorSet)?;
+                builder
+                    .bind_descriptor_sets(
+                        PipelineBindPoint::Compute,
+                        layout.clone(),
+                        0,
+                        descriptor_set,
+                    )
+                    .map_err(ComputeError::Record)?;
+            }
+            None if !descriptor_writes.is_empty() => {
+                return Err(ComputeError::DescriptorSet(Validated::ValidationError(
+                    Box::new(ValidationError {
+                        context: "descriptor_writes".into(),
+                        problem: "is not empty, but the entry point doesn't use any descriptor \
+                            sets"
+                            .into(),
+                        ..Default::default()
+                    }),
+                )));
+            }
+            None => (),
+        }
+
+        if size_of::<Pc>() != 0 {
+            builder
+                .push_constants(layout, 0, push_constants)
+                .map_err(ComputeError::Record)?;
+        }
+
+        unsafe { builder.dispatch(group_counts) }.map_err(ComputeError::Record)?;
+
+        let command_buffer = builder.end().map_err(ComputeError::Submission)?;
+
+        command_buffer
+            .execute(self.queue.clone())
+            .map_err(ComputeError::Execution)?
+            .then_signal_fence_and_flush()
+            .map_err(ComputeError::Submission)
+    }
+}
+
+/// Dispatches `entry_point` on the compute queue of `context`, using the runner that the context
+/// owns.
+///
+/// See [`ComputeRunner::run`] for more information.
+///
+/// # Safety
+///
+/// - The general [shader safety requirements](vulkano::shader#safety) apply.
+pub unsafe fn run<Pc>(
+    context: &VulkanoContext,
+    entry_point: EntryPoint,
+    descriptor_writes: impl IntoIterator<Item = WriteDescriptorSet>,
+    push_constants: Pc,
+    group_counts: [u32; 3],
+) -> Result<ComputeFuture, ComputeError>
+where
+    Pc: BufferContents,
+{
+    unsafe {
+        context
+            .compute_runner()
+            .run(entry_point, descriptor_writes, push_constants, group_counts)
+    }
+}
+
+/// Error that can happen when running a compute shader with a [`ComputeRunner`].
+#[derive(Clone, Debug)]
+pub enum ComputeError {
+    /// Creating the pipeline or its layout failed.
+    Pipeline(Validated<VulkanError>),
+
+    /// Creating the descriptor set failed, for example because the descriptor writes don't match
+    /// the descriptor bindings of the entry point.
+    DescriptorSet(Validated<VulkanError>),
+
+    /// Recording the dispatch failed.
+    Record(Box<ValidationError>),
+
+    /// Creating or submitting the command buffer failed.
+    Submission(Validated<VulkanError>),
+
+    /// Executing the command buffer on the queue failed.
+    Execution(CommandBufferExecError),
+}
+
+impl Error for ComputeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Pipeline(err) => Some(err),
+            Self::DescriptorSet(err) => Some(err),
+            Self::Record(err) => Some(err),
+            Self::Submission(err) => Some(err),
+            Self::Execution(err) => Some(err),
+        }
+    }
+}
+
+impl Display for ComputeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::Pipeline(_) => write!(f, "failed to create the compute pipeline"),
+            Self::DescriptorSet(_) => write!(f, "failed to create the descriptor set"),
+            Self::Record(_) => write!(f, "failed to record the dispatch"),
+            Self::Submission(_) => write!(f, "failed to submit the dispatch"),
+            Self::Execution(_) => write!(f, "failed to execute the dispatch"),
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use std::sync::Arc;
+use crate::compute::ComputeRunner;
+use std::{
+    mem::take,
+    sync::{Arc, OnceLock},
+};
 #[cfg(target_os = "macos")]
 use vulkano::instance::InstanceCreateFlags;
 use vulkano::{
@@ -15,13 +19,22 @@ use vulkano::{
     Version, VulkanLibrary,
 };
 
+const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+
 /// A configuration struct to pass various creation options to create [`VulkanoContext`].
 ///
 /// Instance extensions that are required for surface creation will be appended to the config when
-/// creating [`VulkanoContext`].
+/// creating [`VulkanoContext`], unless `headless` is `true`.
 pub struct VulkanoConfig {
     pub instance_create_info: InstanceCreateInfo,
 
+    /// Layers to enable on the instance if they are available.
+    ///
+    /// Layers that are not installed are skipped, instead of making instance creation fail.
+    /// [`VulkanoContext::missing_layers`] returns the ones that were skipped. Layers that must be
+    /// present can be added to `instance_create_info.enabled_layers` instead.
+    pub optional_layers: Vec<String>,
+
     /// Pass the `DebugUtilsMessengerCreateInfo` to create the debug callback
     /// for printing debug information at runtime.
     pub debug_create_info: Option<DebugUtilsMessengerCreateInfo>,
@@ -38,6 +51,24 @@ pub struct VulkanoConfig {
 
     /// Print your selected device name at start.
     pub print_device_name: bool,
+
+    /// Don't enable the instance extensions that are required for surface creation.
+    ///
+    /// A headless context can't be used to create windows, only to render offscreen. See
+    /// [`VulkanoConfig::headless()`] for a configuration that is suitable for this.
+    pub headless: bool,
+
+    /// Don't require graphics support from the device, and don't create a graphics queue.
+    ///
+    /// See [`VulkanoConfig::compute_only()`] for a configuration that is suitable for this.
+    pub compute_only: bool,
+
+    /// Create a separate queue for transfer operations, preferably from a queue family that
+    /// only supports transfer operations.
+    ///
+    /// If this is `false`, or if the device has no queue to spare for it, the transfer queue is
+    /// the same as the compute queue.
+    pub transfer_queue: bool,
 }
 
 impl Default for VulkanoConfig {
@@ -60,6 +91,7 @@ impl Default for VulkanoConfig {
 
                 ..Default::default()
             },
+            optional_layers: Vec::new(),
             debug_create_info: None,
             device_filter_fn: Arc::new(move |p| {
                 p.supported_extensions().contains(&device_extensions)
@@ -75,6 +107,71 @@ impl Default for VulkanoConfig {
             print_device_name: false,
             device_extensions,
             device_features: DeviceFeatures::empty(),
+            headless: false,
+            compute_only: false,
+            transfer_queue: false,
+        }
+    }
+}
+
+impl VulkanoConfig {
+    /// Adds the Khronos validation layer to the optional layers of the configuration.
+    ///
+    /// The layer is only enabled if it is installed, for example as part of the Vulkan SDK, so
+    /// that the same configuration also works on machines without it.
+    pub fn with_validation(mut self) -> Self {
+        if !self
+            .optional_layers
+            .iter()
+            .any(|layer| layer == VALIDATION_LAYER)
+        {
+            self.optional_layers.push(VALIDATION_LAYER.to_owned());
+        }
+
+        self
+    }
+
+    /// Returns a configuration for a context that doesn't need a window or surface, for example
+    /// for batch rendering or tests.
+    ///
+    /// No surface extensions are enabled on the instance, and the device isn't required to
+    /// support `khr_swapchain`. Any device that has a queue family supporting graphics operations
+    /// is accepted, with the same priorities as the default configuration.
+    ///
+    /// To render and read back the results, see [`HeadlessRenderer`].
+    ///
+    /// [`HeadlessRenderer`]: crate::headless::HeadlessRenderer
+    pub fn headless() -> Self {
+        VulkanoConfig {
+            device_filter_fn: Arc::new(|p| {
+                p.queue_family_properties()
+                    .iter()
+                    .any(|q| q.queue_flags.intersects(QueueFlags::GRAPHICS))
+            }),
+            device_extensions: DeviceExtensions::empty(),
+            headless: true,
+            ..Default::default()
+        }
+    }
+
+    /// Returns a configuration for a context that only does compute work, without a window or
+    /// surface.
+    ///
+    /// Like [`VulkanoConfig::headless()`], but any device that has a queue family supporting
+    /// compute operations is accepted, even if it doesn't support graphics. The context has a
+    /// compute queue and a transfer queue, but no graphics queue.
+    pub fn compute_only() -> Self {
+        VulkanoConfig {
+            device_filter_fn: Arc::new(|p| {
+                p.queue_family_properties()
+                    .iter()
+                    .any(|q| q.queue_flags.intersects(QueueFlags::COMPUTE))
+            }),
+            device_extensions: DeviceExtensions::empty(),
+            headless: true,
+            compute_only: true,
+            transfer_queue: true,
+            ..Default::default()
         }
     }
 }
@@ -97,11 +194,14 @@ impl Default for VulkanoConfig {
 /// ```
 pub struct VulkanoContext {
     instance: Arc<Instance>,
+    missing_layers: Vec<String>,
     _debug_utils_messenger: Option<DebugUtilsMessenger>,
     device: Arc<Device>,
-    graphics_queue: Arc<Queue>,
+    graphics_queue: Option<Arc<Queue>>,
     compute_queue: Arc<Queue>,
+    transfer_queue: Arc<Queue>,
     memory_allocator: Arc<StandardMemoryAllocator>,
+    compute_runner: OnceLock<ComputeRunner>,
 }
 
 impl Default for VulkanoContext {
@@ -131,19 +231,37 @@ impl VulkanoContext {
         // Append required extensions
         // HACK: This should be replaced with `Surface::required_extensions`, but will need to
         // happen in the next minor version bump. It should have been done before releasing 0.34.
-        config.instance_create_info.enabled_extensions = library
-            .supported_extensions()
-            .intersection(&InstanceExtensions {
-                khr_surface: true,
-                khr_xlib_surface: true,
-                khr_xcb_surface: true,
-                khr_wayland_surface: true,
-                khr_android_surface: true,
-                khr_win32_surface: true,
-                ext_metal_surface: true,
-                ..InstanceExtensions::empty()
-            })
-            .union(&config.instance_create_info.enabled_extensions);
+        if !config.headless {
+            config.instance_create_info.enabled_extensions = library
+                .supported_extensions()
+                .intersection(&InstanceExtensions {
+                    khr_surface: true,
+                    khr_xlib_surface: true,
+                    khr_xcb_surface: true,
+                    khr_wayland_surface: true,
+                    khr_android_surface: true,
+                    khr_win32_surface: true,
+                    ext_metal_surface: true,
+                    ..InstanceExtensions::empty()
+                })
+                .union(&config.instance_create_info.enabled_extensions);
+        }
+
+        // Enable the optional layers that are available
+        let available_layers = available_layers(&library);
+        let mut missing_layers = Vec::new();
+
+        for layer in take(&mut config.optional_layers) {
+            if config.instance_create_info.enabled_layers.contains(&layer) {
+                continue;
+            }
+
+            if available_layers.contains(&layer) {
+                config.instance_create_info.enabled_layers.push(layer);
+            } else {
+                missing_layers.push(layer);
+            }
+        }
 
         // Create instance
         let instance =
@@ -172,70 +290,126 @@ impl VulkanoContext {
         }
 
         // Create device
-        let (device, graphics_queue, compute_queue) = Self::create_device(
+        let (device, graphics_queue, compute_queue, transfer_queue) = Self::create_device(
             physical_device,
             config.device_extensions,
             config.device_features,
+            !config.compute_only,
+            config.transfer_queue,
         );
 
         let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
 
         Self {
             instance,
+            missing_layers,
             _debug_utils_messenger,
             device,
             graphics_queue,
             compute_queue,
+            transfer_queue,
             memory_allocator,
+            compute_runner: OnceLock::new(),
         }
     }
 
-    /// Creates vulkano device with required queue families and required extensions. Creates a
-    /// separate queue for compute if possible. If not, same queue as graphics is used.
+    /// Creates vulkano device with required queue families and required extensions.
+    ///
+    /// Queues are taken from separate queue families where possible. If a queue family is shared,
+    /// a separate queue of that family is used if it has one to spare. Otherwise, the queue is
+    /// shared as well.
     fn create_device(
         physical_device: Arc<PhysicalDevice>,
         device_extensions: DeviceExtensions,
         device_features: DeviceFeatures,
-    ) -> (Arc<Device>, Arc<Queue>, Arc<Queue>) {
-        let queue_family_graphics = physical_device
-            .queue_family_properties()
-            .iter()
-            .enumerate()
-            .map(|(i, q)| (i as u32, q))
-            .find(|(_i, q)| q.queue_flags.intersects(QueueFlags::GRAPHICS))
-            .map(|(i, _)| i)
-            .expect("could not find a queue that supports graphics");
-        // Try finding a separate queue for compute
-        let queue_family_compute = physical_device
-            .queue_family_properties()
-            .iter()
-            .enumerate()
-            .map(|(i, q)| (i as u32, q))
-            .find(|(i, q)| {
-                q.queue_flags.intersects(QueueFlags::COMPUTE) && *i != queue_family_graphics
+        graphics: bool,
+        transfer: bool,
+    ) -> (Arc<Device>, Option<Arc<Queue>>, Arc<Queue>, Arc<Queue>) {
+        let queue_family_properties = physical_device.queue_family_properties();
+        let find_queue_family = |filter: &dyn Fn(u32, QueueFlags) -> bool| {
+            queue_family_properties
+                .iter()
+                .enumerate()
+                .map(|(i, q)| (i as u32, q.queue_flags))
+                .find(|&(i, flags)| filter(i, flags))
+                .map(|(i, _)| i)
+        };
+
+        let queue_family_graphics = graphics.then(|| {
+            find_queue_family(&|_i, flags| flags.intersects(QueueFlags::GRAPHICS))
+                .expect("could not find a queue that supports graphics")
+        });
+        // Try finding a separate queue family for compute
+        let queue_family_compute = find_queue_family(&|i, flags| {
+            flags.intersects(QueueFlags::COMPUTE) && Some(i) != queue_family_graphics
+        })
+        .or(queue_family_graphics)
+        .expect("could not find a queue that supports compute");
+        // Try finding a transfer-only queue family, then any other unused queue family. Graphics
+        // and compute queues support transfer operations implicitly.
+        let queue_family_transfer = transfer.then(|| {
+            find_queue_family(&|_i, flags| {
+                flags.intersects(QueueFlags::TRANSFER)
+                    && !flags.intersects(QueueFlags::GRAPHICS | QueueFlags::COMPUTE)
+            })
+            .or_else(|| {
+                find_queue_family(&|i, flags| {
+                    flags.intersects(
+                        QueueFlags::GRAPHICS | QueueFlags::COMPUTE | QueueFlags::TRANSFER,
+                    ) && Some(i) != queue_family_graphics
+                        && i != queue_family_compute
+                })
             })
-            .map(|(i, _)| i);
-        let is_separate_compute_queue = queue_family_compute.is_some();
+            .unwrap_or(queue_family_compute)
+        });
 
-        let queue_create_infos = if let Some(queue_family_compute) = queue_family_compute {
-            vec![
-                QueueCreateInfo {
-                    queue_family_index: queue_family_graphics,
-                    ..Default::default()
-                },
-                QueueCreateInfo {
-                    queue_family_index: queue_family_compute,
-                    ..Default::default()
-                },
-            ]
-        } else {
-            vec![QueueCreateInfo {
-                queue_family_index: queue_family_graphics,
-                ..Default::default()
-            }]
-        };
+        // For each requested queue, the index of its `QueueCreateInfo` and the index of the queue
+        // within it.
+        let mut queue_create_infos: Vec<QueueCreateInfo> = Vec::new();
+        let queue_indices = [
+            queue_family_graphics,
+            Some(queue_family_compute),
+            queue_family_transfer,
+        ]
+        .map(|queue_family_index| {
+            queue_family_index.map(|queue_family_index| {
+                let create_info_index = match queue_create_infos
+                    .iter()
+                    .position(|info| info.queue_family_index == queue_family_index)
+                {
+                    Some(index) => index,
+                    None => {
+                        queue_create_infos.push(QueueCreateInfo {
+                            queue_family_index,
+                            queues: Vec::new(),
+                            ..Default::default()
+                        });
+                        queue_create_infos.len() - 1
+                    }
+                };
+                let queues = &mut queue_create_infos[create_info_index].queues;
 
-        let (device, mut queues) = {
+                // Share the last queue if the queue family has no more queues.
+                if queues.len()
+                    < queue_family_properties[queue_family_index as usize].queue_count as usize
+                {
+                    queues.push(0.5);
+                }
+
+                (create_info_index, queues.len() - 1)
+            })
+        });
+        let queue_offsets: Vec<usize> = queue_create_infos
+            .iter()
+            .scan(0, |offset, info| {
+                let current = *offset;
+                *offset += info.queues.len();
+
+                Some(current)
+            })
+            .collect();
+
+        let (device, queues) = {
             Device::new(
                 physical_device,
                 DeviceCreateInfo {
@@ -247,13 +421,15 @@ impl VulkanoContext {
             )
             .expect("failed to create device")
         };
-        let gfx_queue = queues.next().unwrap();
-        let compute_queue = if is_separate_compute_queue {
-            queues.next().unwrap()
-        } else {
-            gfx_queue.clone()
-        };
-        (device, gfx_queue, compute_queue)
+        let queues: Vec<_> = queues.collect();
+        let [gfx_queue, compute_queue, transfer_queue] = queue_indices.map(|indices| {
+            indices.map(|(create_info_index, queue_index)| {
+                queues[queue_offsets[create_info_index] + queue_index].clone()
+            })
+        });
+        let compute_queue = compute_queue.unwrap();
+        let transfer_queue = transfer_queue.unwrap_or_else(|| compute_queue.clone());
+        (device, gfx_queue, compute_queue, transfer_queue)
     }
 
     /// Returns the name of the device.
@@ -283,6 +459,19 @@ impl VulkanoContext {
         &self.instance
     }
 
+    /// Returns the layers that are enabled on the instance.
+    #[inline]
+    pub fn enabled_layers(&self) -> &[String] {
+        self.instance.enabled_layers()
+    }
+
+    /// Returns the layers from [`VulkanoConfig::optional_layers`] that were not enabled, because
+    /// they are not available.
+    #[inline]
+    pub fn missing_layers(&self) -> &[String] {
+        &self.missing_layers
+    }
+
     /// Returns the device.
     #[inline]
     pub fn device(&self) -> &Arc<Device> {
@@ -290,9 +479,23 @@ impl VulkanoContext {
     }
 
     /// Returns the graphics queue.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the context was created with `compute_only` set in its
+    ///   [`VulkanoConfig`].
     #[inline]
     pub fn graphics_queue(&self) -> &Arc<Queue> {
-        &self.graphics_queue
+        self.graphics_queue
+            .as_ref()
+            .expect("the context was created without a graphics queue")
+    }
+
+    /// Returns the graphics queue, or `None` if the context was created with
+    /// `compute_only` set in its [`VulkanoConfig`].
+    #[inline]
+    pub fn try_graphics_queue(&self) -> Option<&Arc<Queue>> {
+        self.graphics_queue.as_ref()
     }
 
     /// Returns the compute queue.
@@ -303,9 +506,38 @@ impl VulkanoContext {
         &self.compute_queue
     }
 
+    /// Returns the transfer queue.
+    ///
+    /// This is the same as the compute queue, unless the context was created with
+    /// `transfer_queue` set in its [`VulkanoConfig`] and the device had a queue to spare for it.
+    #[inline]
+    pub fn transfer_queue(&self) -> &Arc<Queue> {
+        &self.transfer_queue
+    }
+
     /// Returns the memory allocator.
     #[inline]
     pub fn memory_allocator(&self) -> &Arc<StandardMemoryAllocator> {
         &self.memory_allocator
     }
+
+    /// Returns the runner for compute shaders on the compute queue, which is created the first
+    /// time this is called.
+    ///
+    /// This is the runner that [`compute::run`](crate::compute::run) uses.
+    #[inline]
+    pub fn compute_runner(&self) -> &ComputeRunner {
+        self.compute_runner
+            .get_or_init(|| ComputeRunner::new(self.compute_queue.clone()))
+    }
+}
+
+/// Returns the names of the instance layers that are available with `library`.
+///
+/// Returns an empty list if the layers could not be enumerated.
+pub fn available_layers(library: &VulkanLibrary) -> Vec<String> {
+    library
+        .layer_properties()
+        .map(|layers| layers.map(|layer| layer.name().to_owned()).collect())
+        .unwrap_or_default()
 }
@@ -0,0 +1,658 @@
+//! Culling instances on the device and drawing the ones that are visible.
+//!
+//! Culling on the host requires the bounds of every instance to be tested every frame, and the
+//! draw commands to be recorded or uploaded again whenever the set of visible instances changes.
+//! [`GpuCuller`] does this work with a compute shader instead: every instance whose bounding
+//! sphere is inside the view frustum, and optionally not hidden behind the depth of the previous
+//! frame, gets a [`DrawIndexedIndirectCommand`] in a compacted draw buffer, and the number of
+//! these commands is written to a count buffer. [`GpuCuller::draw`] then draws them with a single
+//! [`draw_indexed_indirect_count`] call.
+//!
+//! No explicit barriers are needed between the two: the command buffer knows that the draw
+//! buffer and the count buffer are written by the compute shader and then read as indirect
+//! buffers, and inserts the pipeline barriers that this requires.
+//!
+//! Drawing with a count buffer requires the [`draw_indirect_count`] feature, and instances with a
+//! `first_instance` other than zero require the [`draw_indirect_first_instance`] feature.
+//!
+//! The compute shader is compiled at runtime the first time it is needed, so this module is only
+//! available when the `shaderc` feature of `vulkano-util` is enabled.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::{
+//! #     buffer::Subbuffer,
+//! #     command_buffer::{DrawIndexedIndirectCommand, RecordingCommandBuffer},
+//! #     descriptor_set::allocator::StandardDescriptorSetAllocator,
+//! #     device::Device,
+//! # };
+//! use vulkano_util::culling::{CullInfo, CullInstance, GpuCuller};
+//!
+//! # let device: Arc<Device> = return;
+//! # let compute_builder: &mut RecordingCommandBuffer = return;
+//! # let graphics_builder: &mut RecordingCommandBuffer = return;
+//! # let instances: Subbuffer<[CullInstance]> = return;
+//! # let draw_buffer: Subbuffer<[DrawIndexedIndirectCommand]> = return;
+//! # let count_buffer: Subbuffer<u32> = return;
+//! # let view_projection: [[f32; 4]; 4] = return;
+//! let descriptor_set_allocator = Arc::new(StandardDescriptorSetAllocator::new(
+//!     device.clone(),
+//!     Default::default(),
+//! ));
+//! let culler = GpuCuller::new(device, descriptor_set_allocator);
+//!
+//! // Before the render pass:
+//! culler
+//!     .cull(
+//!         compute_builder,
+//!         CullInfo::new(
+//!             instances,
+//!             draw_buffer.clone(),
+//!             count_buffer.clone(),
+//!             view_projection,
+//!         ),
+//!     )
+//!     .unwrap();
+//!
+//! // Inside the render pass, after binding the graphics pipeline and the index buffer:
+//! unsafe { culler.draw(graphics_builder, draw_buffer, count_buffer) }.unwrap();
+//! ```
+//!
+//! [`draw_indexed_indirect_count`]: RecordingCommandBuffer::draw_indexed_indirect_count
+//! [`draw_indirect_count`]: vulkano::device::DeviceFeatures::draw_indirect_count
+//! [`draw_indirect_first_instance`]: vulkano::device::DeviceFeatures::draw_indirect_first_instance
+
+use crate::shader::{compile_glsl, GlslCompileInfo, ShaderCompileError};
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    sync::{Arc, Mutex},
+};
+use vulkano::{
+    buffer::{BufferContents, BufferUsage, Subbuffer},
+    command_buffer::{DrawIndexedIndirectCommand, RecordingCommandBuffer},
+    descriptor_set::{allocator::DescriptorSetAllocator, DescriptorSet, WriteDescriptorSet},
+    device::{Device, DeviceOwned},
+    image::{
+        mip_level_extent,
+        sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+        view::ImageView,
+    },
+    pipeline::{
+        compute::ComputePipelineCreateInfo, layout::PipelineDescriptorSetLayoutCreateInfo,
+        ComputePipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    shader::ShaderStage,
+    Validated, ValidationError, VulkanError,
+};
+
+const SHADER_SOURCE: &str = r"
+    #version 450
+
+    layout(local_size_x = 64) in;
+
+    struct Instance {
+        vec4 bounding_sphere;
+        uint index_count;
+        uint first_index;
+        int vertex_offset;
+        uint first_instance;
+    };
+
+    struct DrawCommand {
+        uint index_count;
+        uint instance_count;
+        uint first_index;
+        int vertex_offset;
+        uint first_instance;
+    };
+
+    layout(set = 0, binding = 0) readonly buffer Instances {
+        Instance instances[];
+    };
+
+    layout(set = 0, binding = 1) writeonly buffer DrawCommands {
+        DrawCommand draw_commands[];
+    };
+
+    layout(set = 0, binding = 2) buffer DrawCount {
+        uint draw_count;
+    };
+
+    layout(push_constant) uniform PushConstants {
+        mat4 view_projection;
+        vec2 depth_pyramid_extent;
+        uint instance_count;
+    } pc;
+
+    #ifdef OCCLUSION
+    layout(set = 0, binding = 3) uniform sampler2D depth_pyramid;
+
+    // Tests the screen-space bounds of the sphere against the farthest depth that the depth
+    // pyramid has for them, at the level where the bounds cover at most 2x2 texels.
+    bool is_occluded(vec3 center, float radius) {
+        vec2 uv_min = vec2(1.0);
+        vec2 uv_max = vec2(0.0);
+        float nearest_depth = 1.0;
+
+        for (int i = 0; i < 8; i++) {
+            vec3 corner = center + radius * vec3(
+                (i & 1) != 0 ? 1.0 : -1.0,
+                (i & 2) != 0 ? 1.0 : -1.0,
+                (i & 4) != 0 ? 1.0 : -1.0
+            );
+            vec4 clip = pc.view_projection * vec4(corner, 1.0);
+
+            // The bounds cross the camera plane, so their projection is unbounded.
+            if (clip.w <= 0.0) {
+                return false;
+            }
+
+            vec3 ndc = clip.xyz / clip.w;
+            uv_min = min(uv_min, ndc.xy * 0.5 + 0.5);
+            uv_max = max(uv_max, ndc.xy * 0.5 + 0.5);
+            nearest_depth = min(nearest_depth, ndc.z);
+        }
+
+        uv_min = clamp(uv_min, 0.0, 1.0);
+        uv_max = clamp(uv_max, 0.0, 1.0);
+
+        vec2 extent = (uv_max - uv_min) * pc.depth_pyramid_extent;
+        float level = ceil(log2(max(max(extent.x, extent.y), 1.0)));
+        float depth = max(
+            max(
+                textureLod(depth_pyramid, uv_min, level).r,
+                textureLod(depth_pyramid, vec2(uv_max.x, uv_min.y), level).r
+            ),
+            max(
+                textureLod(depth_pyramid, vec2(uv_min.x, uv_max.y), level).r,
+                textureLod(depth_pyramid, uv_max, level).r
+            )
+        );
+
+        return nearest_depth > depth;
+    }
+    #endif
+
+    void main() {
+        uint index = gl_GlobalInvocationID.x;
+
+        if (index >= pc.instance_count) {
+            return;
+        }
+
+        Instance instance = instances[index];
+        vec3 center = instance.bounding_sphere.xyz;
+        float radius = instance.bounding_sphere.w;
+
+        // The rows of the view-projection matrix give the frustum planes.
+        mat4 rows = transpose(pc.view_projection);
+        vec4 planes[6] = vec4[](
+            rows[3] + rows[0],
+            rows[3] - rows[0],
+            rows[3] + rows[1],
+            rows[3] - rows[1],
+            rows[2],
+            rows[3] - rows[2]
+        );
+
+        for (int i = 0; i < 6; i++) {
+            // Planes are not normalized, so the radius is scaled instead. A plane of an infinite
+            // projection has a zero normal, and culls nothing.
+            float scale = length(planes[i].xyz);
+
+            if (dot(planes[i].xyz, center) + planes[i].w < -radius * scale) {
+                return;
+            }
+        }
+
+    #ifdef OCCLUSION
+        if (is_occluded(center, radius)) {
+            return;
+        }
+    #endif
+
+        uint draw_index = atomicAdd(draw_count, 1);
+        draw_commands[draw_index] = DrawCommand(
+            instance.index_count,
+            1,
+            instance.first_index,
+            instance.vertex_offset,
+            instance.first_instance
+        );
+    }
+";
+
+/// An instance that is culled by a [`GpuCuller`].
+///
+/// Every visible instance results in a [`DrawIndexedIndirectCommand`] with an `instance_count`
+/// of 1, and the other members copied from the instance.
+#[derive(BufferContents, Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct CullInstance {
+    /// The center of the bounding sphere of the instance, in world space, followed by its
+    /// radius.
+    pub bounding_sphere: [f32; 4],
+
+    /// The number of indices to draw.
+    pub index_count: u32,
+
+    /// The index of the first index to draw.
+    pub first_index: u32,
+
+    /// The value that is added to each index before indexing into the vertex buffers. This is
+    /// interpreted as a signed integer.
+    pub vertex_offset: u32,
+
+    /// The instance index of the draw, which the vertex shader can use to look up the data of the
+    /// instance.
+    pub first_instance: u32,
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct PushConstants {
+    view_projection: [[f32; 4]; 4],
+    depth_pyramid_extent: [f32; 2],
+    instance_count: u32,
+}
+
+/// Parameters to cull instances with [`GpuCuller::cull`].
+#[derive(Clone, Debug)]
+pub struct CullInfo {
+    /// The instances to cull.
+    ///
+    /// The buffer must have been created with the [`STORAGE_BUFFER`] usage, and the length must
+    /// not be greater than the [`max_draw_indirect_count`] device limit.
+    ///
+    /// There is no default value.
+    ///
+    /// [`STORAGE_BUFFER`]: BufferUsage::STORAGE_BUFFER
+    /// [`max_draw_indirect_count`]: vulkano::device::DeviceProperties::max_draw_indirect_count
+    pub instances: Subbuffer<[CullInstance]>,
+
+    /// The buffer that the draw commands of the visible instances are written to, in no
+    /// particular order.
+    ///
+    /// The buffer must have been created with the [`STORAGE_BUFFER`] and [`INDIRECT_BUFFER`]
+    /// usages, and must be at least as long as `instances`.
+    ///
+    /// There is no default value.
+    ///
+    /// [`STORAGE_BUFFER`]: BufferUsage::STORAGE_BUFFER
+    /// [`INDIRECT_BUFFER`]: BufferUsage::INDIRECT_BUFFER
+    pub draw_buffer: Subbuffer<[DrawIndexedIndirectCommand]>,
+
+    /// The buffer that the number of draw commands is written to.
+    ///
+    /// The buffer must have been created with the [`STORAGE_BUFFER`], [`INDIRECT_BUFFER`] and
+    /// [`TRANSFER_DST`] usages.
+    ///
+    /// There is no default value.
+    ///
+    /// [`STORAGE_BUFFER`]: BufferUsage::STORAGE_BUFFER
+    /// [`INDIRECT_BUFFER`]: BufferUsage::INDIRECT_BUFFER
+    /// [`TRANSFER_DST`]: BufferUsage::TRANSFER_DST
+    pub count_buffer: Subbuffer<u32>,
+
+    /// The matrix that transforms world space to clip space, in column-major order. The frustum
+    /// is derived from it.
+    ///
+    /// There is no default value.
+    pub view_projection: [[f32; 4]; 4],
+
+    /// A depth pyramid to cull instances that are hidden behind it.
+    ///
+    /// Each texel of a mip level of the pyramid must contain the farthest depth of the texels of
+    /// the previous mip level that it covers, and the first mip level must cover the whole
+    /// viewport. The pyramid is usually built from the depth buffer of the previous frame, which
+    /// means that it must have been rendered with `view_projection` of that frame, and depth
+    /// must increase with the distance to the camera.
+    ///
+    /// The image view must have been created with the [`SAMPLED`] usage, and its format must
+    /// support being sampled.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`SAMPLED`]: vulkano::image::ImageUsage::SAMPLED
+    pub depth_pyramid: Option<Arc<ImageView>>,
+}
+
+impl CullInfo {
+    /// Returns a `CullInfo` with the specified `instances`, `draw_buffer`, `count_buffer` and
+    /// `view_projection`.
+    #[inline]
+    pub fn new(
+        instances: Subbuffer<[CullInstance]>,
+        draw_buffer: Subbuffer<[DrawIndexedIndirectCommand]>,
+        count_buffer: Subbuffer<u32>,
+        view_projection: [[f32; 4]; 4],
+    ) -> Self {
+        Self {
+            instances,
+            draw_buffer,
+            count_buffer,
+            view_projection,
+            depth_pyramid: None,
+        }
+    }
+}
+
+/// Culls instances with a compute shader, and draws the visible ones with an indirect draw.
+///
+/// See [the module-level documentation] for more details.
+///
+/// [the module-level documentation]: self
+#[derive(Debug)]
+pub struct GpuCuller {
+    device: Arc<Device>,
+    descriptor_set_allocator: Arc<dyn DescriptorSetAllocator>,
+    sampler: Mutex<Option<Arc<Sampler>>>,
+    // Indexed by whether occlusion culling is enabled.
+    pipelines: Mutex<[Option<Arc<ComputePipeline>>; 2]>,
+}
+
+impl GpuCuller {
+    /// Creates a new `GpuCuller`.
+    ///
+    /// `descriptor_set_allocator` is used to allocate the descriptor sets of the compute shader.
+    #[inline]
+    pub fn new(
+        device: Arc<Device>,
+        descriptor_set_allocator: Arc<dyn DescriptorSetAllocator>,
+    ) -> Self {
+        GpuCuller {
+            device,
+            descriptor_set_allocator,
+            sampler: Mutex::default(),
+            pipelines: Mutex::default(),
+        }
+    }
+
+    /// Records the commands that cull the instances of `cull_info` into `builder`.
+    ///
+    /// This resets the count buffer to zero, and dispatches the compute shader that writes the
+    /// draw commands of the visible instances. It must be recorded outside of a render pass.
+    pub fn cull(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        cull_info: CullInfo,
+    ) -> Result<(), GpuCullError> {
+        self.validate_cull(&cull_info)?;
+
+        let CullInfo {
+            instances,
+            draw_buffer,
+            count_buffer,
+            view_projection,
+            depth_pyramid,
+        } = cull_info;
+
+        let instance_count = instances.len() as u32;
+        let pipeline = self.pipeline(depth_pyramid.is_some())?;
+        let layout = pipeline.layout().clone();
+
+        let mut descriptor_writes = vec![
+            WriteDescriptorSet::buffer(0, instances),
+            WriteDescriptorSet::buffer(1, draw_buffer),
+            WriteDescriptorSet::buffer(2, count_buffer.clone()),
+        ];
+        let mut depth_pyramid_extent = [0.0; 2];
+
+        if let Some(depth_pyramid) = depth_pyramid {
+            let base_mip_level = depth_pyramid.subresource_range().mip_levels.start;
+            let extent = mip_level_extent(depth_pyramid.image().extent(), base_mip_level).unwrap();
+            depth_pyramid_extent = [extent[0] as f32, extent[1] as f32];
+            descriptor_writes.push(WriteDescriptorSet::image_view_sampler(
+                3,
+                depth_pyramid,
+                self.sampler()?,
+            ));
+        }
+
+        let descriptor_set = DescriptorSet::new(
+            self.descriptor_set_allocator.clone(),
+            layout.set_layouts()[0].clone(),
+            descriptor_writes,
+            [],
+        )?;
+
+        builder.fill_buffer(count_buffer.into_slice(), 0)?;
+
+        if instance_count == 0 {
+            return Ok(());
+        }
+
+        builder
+            .bind_pipeline_compute(pipeline)?
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                layout.clone(),
+                0,
+                descriptor_set,
+            )?
+            .push_constants(
+                layout,
+                0,
+                PushConstants {
+                    view_projection,
+                    depth_pyramid_extent,
+                    instance_count,
+                },
+            )?;
+
+        // SAFETY: The shader only reads the instances below `instance_count`, and writes at most
+        // one draw command for each of them, which fit in the draw buffer.
+        unsafe { builder.dispatch([instance_count.div_ceil(64), 1, 1]) }?;
+
+        Ok(())
+    }
+
+    fn validate_cull(&self, cull_info: &CullInfo) -> Result<(), Box<ValidationError>> {
+        let &CullInfo {
+            ref instances,
+            ref draw_buffer,
+            ref count_buffer,
+            view_projection: _,
+            depth_pyramid: _,
+        } = cull_info;
+
+        let properties = self.device.physical_device().properties();
+
+        if instances.len() > properties.max_draw_indirect_count as u64 {
+            return Err(Box::new(ValidationError {
+                context: "cull_info.instances".into(),
+                problem: "the length is greater than the `max_draw_indirect_count` device limit"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        if draw_buffer.len() < instances.len() {
+            return Err(Box::new(ValidationError {
+                context: "cull_info.draw_buffer".into(),
+                problem: "the length is less than the length of `cull_info.instances`".into(),
+                ..Default::default()
+            }));
+        }
+
+        let indirect_usage = BufferUsage::STORAGE_BUFFER | BufferUsage::INDIRECT_BUFFER;
+
+        if !draw_buffer.buffer().usage().contains(indirect_usage) {
+            return Err(Box::new(ValidationError {
+                context: "cull_info.draw_buffer.buffer().usage()".into(),
+                problem: "does not contain both `BufferUsage::STORAGE_BUFFER` and \
+                    `BufferUsage::INDIRECT_BUFFER`"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        if !count_buffer
+            .buffer()
+            .usage()
+            .contains(indirect_usage | BufferUsage::TRANSFER_DST)
+        {
+            return Err(Box::new(ValidationError {
+                context: "cull_info.count_buffer.buffer().usage()".into(),
+                problem: "does not contain all of `BufferUsage::STORAGE_BUFFER`, \
+                    `BufferUsage::INDIRECT_BUFFER` and `BufferUsage::TRANSFER_DST`"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Records an indexed indirect draw of the draw commands that [`cull`] wrote to
+    /// `draw_buffer` and `count_buffer`, into `builder`.
+    ///
+    /// This is the same as calling [`draw_indexed_indirect_count`] with the length of
+    /// `draw_buffer` as the maximum draw count. The graphics pipeline, the index buffer and any
+    /// other resources that the pipeline uses must have been bound beforehand.
+    ///
+    /// # Safety
+    ///
+    /// - The general [shader safety requirements] apply.
+    /// - For every instance that was culled, the range of indices given by `first_index` and
+    ///   `index_count` must be in range of the bound index buffer, and the indices, offset by
+    ///   `vertex_offset`, and `first_instance` must be in range of the bound vertex buffers.
+    ///
+    /// [`cull`]: Self::cull
+    /// [`draw_indexed_indirect_count`]: RecordingCommandBuffer::draw_indexed_indirect_count
+    /// [shader safety requirements]: vulkano::shader#safety
+    pub unsafe fn draw(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        draw_buffer: Subbuffer<[DrawIndexedIndirectCommand]>,
+        count_buffer: Subbuffer<u32>,
+    ) -> Result<(), Box<ValidationError>> {
+        let max_draw_count = draw_buffer.len() as u32;
+
+        // SAFETY: `cull` writes a count that is at most the number of instances, which is
+        // limited to `max_draw_indirect_count` and to the length of the draw buffer. The caller
+        // must uphold the rest.
+        unsafe { builder.draw_indexed_indirect_count(draw_buffer, count_buffer, max_draw_count) }?;
+
+        Ok(())
+    }
+
+    fn pipeline(&self, occlusion: bool) -> Result<Arc<ComputePipeline>, GpuCullError> {
+        let mut pipelines = self.pipelines.lock().unwrap();
+
+        if let Some(pipeline) = &pipelines[occlusion as usize] {
+            return Ok(pipeline.clone());
+        }
+
+        let macro_defines = if occlusion {
+            vec![("OCCLUSION".to_owned(), None)]
+        } else {
+            Vec::new()
+        };
+        let module = compile_glsl(
+            self.device.clone(),
+            SHADER_SOURCE,
+            &GlslCompileInfo {
+                source_name: "cull.comp".to_owned(),
+                macro_defines,
+                ..GlslCompileInfo::new(ShaderStage::Compute)
+            },
+        )?;
+        let stage = PipelineShaderStageCreateInfo::new(module.entry_point("main").unwrap());
+        let layout = PipelineLayout::new(
+            self.device.clone(),
+            PipelineDescriptorSetLayoutCreateInfo::from_stages([&stage])
+                .into_pipeline_layout_create_info(self.device.clone())
+                .map_err(|err| err.error)?,
+        )?;
+        let pipeline = ComputePipeline::new(
+            self.device.clone(),
+            None,
+            ComputePipelineCreateInfo::stage_layout(stage, layout),
+        )?;
+        pipelines[occlusion as usize] = Some(pipeline.clone());
+
+        Ok(pipeline)
+    }
+
+    fn sampler(&self) -> Result<Arc<Sampler>, Validated<VulkanError>> {
+        let mut sampler = self.sampler.lock().unwrap();
+
+        if let Some(sampler) = &*sampler {
+            return Ok(sampler.clone());
+        }
+
+        let new_sampler = Sampler::new(
+            self.device.clone(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Nearest,
+                min_filter: Filter::Nearest,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                ..Default::default()
+            },
+        )?;
+        *sampler = Some(new_sampler.clone());
+
+        Ok(new_sampler)
+    }
+}
+
+unsafe impl DeviceOwned for GpuCuller {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+/// Error that can happen when culling with a [`GpuCuller`].
+#[derive(Clone, Debug)]
+pub enum GpuCullError {
+    /// The parameters are not valid, or recording a command failed validation.
+    ValidationError(Box<ValidationError>),
+
+    /// Compiling the compute shader failed.
+    ShaderCompile(ShaderCompileError),
+
+    /// Creating an object for the compute shader failed.
+    VulkanError(Validated<VulkanError>),
+}
+
+impl Error for GpuCullError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ValidationError(err) => Some(err),
+            Self::ShaderCompile(err) => Some(err),
+            Self::VulkanError(err) => Some(err),
+        }
+    }
+}
+
+impl Display for GpuCullError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::ValidationError(_) => write!(f, "the culling parameters are not valid"),
+            Self::ShaderCompile(_) => write!(f, "failed to compile the culling shader"),
+            Self::VulkanError(_) => write!(f, "failed to create an object for culling"),
+        }
+    }
+}
+
+impl From<Box<ValidationError>> for GpuCullError {
+    fn from(err: Box<ValidationError>) -> Self {
+        Self::ValidationError(err)
+    }
+}
+
+impl From<ShaderCompileError> for GpuCullError {
+    fn from(err: ShaderCompileError) -> Self {
+        Self::ShaderCompile(err)
+    }
+}
+
+impl From<Validated<VulkanError>> for GpuCullError {
+    fn from(err: Validated<VulkanError>) -> Self {
+        Self::VulkanError(err)
+    }
+}
@@ -0,0 +1,222 @@
+//! Capturing the output of `debugPrintfEXT` in shaders.
+//!
+//! The `GL_EXT_debug_printf` GLSL extension lets shaders print values with `debugPrintfEXT`. The
+//! output is not produced by the driver, but by the Khronos validation layer, which instruments the
+//! shaders and reports the printed text as debug messages. Getting this to work requires several
+//! pieces to be in place:
+//!
+//! - The instance must enable the validation layer, the `ext_debug_utils` and
+//!   `ext_validation_features` extensions, and the [`DebugPrintf`] validation feature.
+//! - The device must enable the `khr_shader_non_semantic_info` extension, which the compiled
+//!   shaders use to carry the printf instructions.
+//! - A debug messenger must listen for the messages, and tell them apart from other validation
+//!   messages.
+//!
+//! [`DebugPrintfCapture`] takes care of all three. Call [`DebugPrintfCapture::configure_instance`]
+//! and [`DebugPrintfCapture::device_extensions`] while setting up, create the capture once the
+//! instance exists, and then call [`DebugPrintfCapture::drain`] once per frame to get the messages
+//! that were printed since the previous call.
+//!
+//! ```no_run
+//! use vulkano_util::{
+//!     context::{VulkanoConfig, VulkanoContext},
+//!     debug_printf::DebugPrintfCapture,
+//! };
+//!
+//! let mut config = VulkanoConfig::default();
+//! DebugPrintfCapture::configure_instance(&mut config.instance_create_info);
+//! config.device_extensions = config
+//!     .device_extensions
+//!     .union(&DebugPrintfCapture::device_extensions());
+//!
+//! let context = VulkanoContext::new(config);
+//! let printf = DebugPrintfCapture::new(context.instance().clone()).unwrap();
+//!
+//! loop {
+//!     // ...submit work and wait for the frame to finish...
+//!
+//!     for message in printf.drain() {
+//!         println!("{}", message.output);
+//!     }
+//! #   break;
+//! }
+//! ```
+//!
+//! Messages are only delivered once the work that printed them has finished executing on the
+//! device, so drain after waiting for the frame's fence to get all of its output.
+//!
+//! [`DebugPrintf`]: ValidationFeatureEnable::DebugPrintf
+
+use std::sync::{
+    mpsc::{self, Receiver},
+    Arc, Mutex,
+};
+use vulkano::{
+    device::DeviceExtensions,
+    instance::{
+        debug::{
+            DebugUtilsMessageSeverity, DebugUtilsMessageType, DebugUtilsMessenger,
+            DebugUtilsMessengerCallback, DebugUtilsMessengerCallbackData,
+            DebugUtilsMessengerCreateInfo, ValidationFeatureEnable,
+        },
+        Instance, InstanceCreateInfo, InstanceExtensions,
+    },
+    Validated, VulkanError,
+};
+
+const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+
+/// Receives the output of `debugPrintfEXT` calls in shaders.
+///
+/// The messages are buffered until they are retrieved with [`drain`](Self::drain). Dropping the
+/// capture stops listening for messages.
+#[derive(Debug)]
+pub struct DebugPrintfCapture {
+    _messenger: DebugUtilsMessenger,
+    receiver: Mutex<Receiver<DebugPrintfMessage>>,
+}
+
+impl DebugPrintfCapture {
+    /// Modifies `create_info` so that the instance created from it reports `debugPrintfEXT`
+    /// output.
+    ///
+    /// This enables the Khronos validation layer, the extensions it needs to receive
+    /// its configuration and report messages, and the `DebugPrintf` validation feature. Anything
+    /// that is already enabled is left in place. Note that the `GpuAssisted` validation feature
+    /// can't be enabled at the same time as `DebugPrintf`.
+    pub fn configure_instance(create_info: &mut InstanceCreateInfo) {
+        if !create_info
+            .enabled_layers
+            .iter()
+            .any(|layer| layer == VALIDATION_LAYER)
+        {
+            create_info.enabled_layers.push(VALIDATION_LAYER.to_owned());
+        }
+
+        create_info.enabled_extensions =
+            create_info.enabled_extensions.union(&InstanceExtensions {
+                ext_debug_utils: true,
+                ext_validation_features: true,
+                ..InstanceExtensions::empty()
+            });
+
+        if !create_info
+            .enabled_validation_features
+            .contains(&ValidationFeatureEnable::DebugPrintf)
+        {
+            create_info
+                .enabled_validation_features
+                .push(ValidationFeatureEnable::DebugPrintf);
+        }
+    }
+
+    /// Returns the device extensions that must be enabled for shaders that use `debugPrintfEXT`.
+    #[inline]
+    pub fn device_extensions() -> DeviceExtensions {
+        DeviceExtensions {
+            khr_shader_non_semantic_info: true,
+            ..DeviceExtensions::empty()
+        }
+    }
+
+    /// Starts capturing `debugPrintfEXT` output reported to `instance`.
+    ///
+    /// `instance` must have been created from a create info that was passed to
+    /// [`configure_instance`](Self::configure_instance), or otherwise no messages will be
+    /// received.
+    pub fn new(instance: Arc<Instance>) -> Result<Self, Validated<VulkanError>> {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+
+        // SAFETY: The callback doesn't call into Vulkan.
+        let user_callback = unsafe {
+            DebugUtilsMessengerCallback::new(move |_severity, _ty, callback_data| {
+                if let Some(message) = DebugPrintfMessage::from_callback_data(&callback_data) {
+                    let _ = sender.lock().unwrap().send(message);
+                }
+            })
+        };
+
+        let messenger = DebugUtilsMessenger::new(
+            instance,
+            DebugUtilsMessengerCreateInfo {
+                // Depending on the version and settings of the layer, the messages are reported
+                // as either information or warnings.
+                message_severity: DebugUtilsMessageSeverity::INFO
+                    | DebugUtilsMessageSeverity::WARNING,
+                message_type: DebugUtilsMessageType::GENERAL
+                    | DebugUtilsMessageType::VALIDATION
+                    | DebugUtilsMessageType::PERFORMANCE,
+                ..DebugUtilsMessengerCreateInfo::user_callback(user_callback)
+            },
+        )?;
+
+        Ok(Self {
+            _messenger: messenger,
+            receiver: Mutex::new(receiver),
+        })
+    }
+
+    /// Returns the messages that were received since the previous call, in the order in which
+    /// they were reported.
+    pub fn drain(&self) -> Vec<DebugPrintfMessage> {
+        self.receiver.lock().unwrap().try_iter().collect()
+    }
+}
+
+/// A single `debugPrintfEXT` call, as reported by the validation layer.
+#[derive(Clone, Debug)]
+pub struct DebugPrintfMessage {
+    /// The text printed by the shader.
+    pub output: String,
+
+    /// The full message reported by the validation layer. Depending on the layer settings, this
+    /// can include the command buffer, pipeline, shader stage and invocation that printed the
+    /// message.
+    pub message: String,
+
+    /// The names of the debug labels that were active in the command buffer.
+    pub command_buffer_labels: Vec<String>,
+
+    /// The names of the objects that the message refers to, for objects that have been given
+    /// a name.
+    pub object_names: Vec<String>,
+}
+
+impl DebugPrintfMessage {
+    fn from_callback_data(callback_data: &DebugUtilsMessengerCallbackData<'_>) -> Option<Self> {
+        // Older versions of the layer use `UNASSIGNED-DEBUG-PRINTF` or `WARNING-DEBUG-PRINTF`.
+        if !callback_data
+            .message_id_name
+            .is_some_and(|name| name.contains("DEBUG-PRINTF"))
+        {
+            return None;
+        }
+
+        let message = callback_data.message;
+
+        // The text from the shader comes after the location information, which is either
+        // separated by an empty line or, in older versions, by ` | `.
+        let output = match message.split_once("\n\n") {
+            Some((_, output)) => output,
+            None => message
+                .rsplit_once(" | ")
+                .map_or(message, |(_, output)| output),
+        };
+
+        Some(DebugPrintfMessage {
+            output: output.trim().to_owned(),
+            message: message.to_owned(),
+            command_buffer_labels: callback_data
+                .cmd_buf_labels
+                .clone()
+                .map(|label| label.label_name.to_owned())
+                .collect(),
+            object_names: callback_data
+                .objects
+                .clone()
+                .filter_map(|object| object.object_name.map(ToOwned::to_owned))
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,235 @@
+//! Rendering without a window, for batch renderers and tests.
+//!
+//! A [`VulkanoContext`] that is created with [`VulkanoConfig::headless`] doesn't need a surface
+//! or a window system at all. [`HeadlessRenderer`] takes the place of
+//! [`VulkanoWindowRenderer`](crate::renderer::VulkanoWindowRenderer) for such a context: it
+//! creates images to render to, and reads their contents back to the host once rendering has
+//! finished.
+//!
+//! ```no_run
+//! use vulkano::{format::Format, image::ImageUsage, sync};
+//! use vulkano_util::{
+//!     context::{VulkanoConfig, VulkanoContext},
+//!     headless::HeadlessRenderer,
+//! };
+//!
+//! let context = VulkanoContext::new(VulkanoConfig::headless());
+//! let renderer = HeadlessRenderer::new(&context);
+//!
+//! let target = renderer.create_render_target(
+//!     Format::R8G8B8A8_UNORM,
+//!     [1024, 1024],
+//!     ImageUsage::COLOR_ATTACHMENT,
+//! );
+//!
+//! // Render to `target`, and keep the future of the submission...
+//! let future = sync::now(context.device().clone());
+//!
+//! let pixels = renderer.read_image(target.image().clone(), future).unwrap();
+//! ```
+//!
+//! [`VulkanoConfig::headless`]: crate::context::VulkanoConfig::headless()
+
+use crate::{context::VulkanoContext, readback::image_data_size};
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    sync::Arc,
+};
+use vulkano::{
+    buffer::{AllocateBufferError, Buffer, BufferCreateInfo, BufferUsage},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferExecError,
+        CommandBufferLevel, CommandBufferUsage, CopyImageToBufferInfo, RecordingCommandBuffer,
+    },
+    device::Queue,
+    format::Format,
+    image::{view::ImageView, Image, ImageCreateInfo, ImageType, ImageUsage},
+    memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator},
+    sync::{GpuFuture, HostAccessError},
+    Validated, ValidationError, VulkanError,
+};
+
+/// Creates render targets and reads them back, without a window.
+#[derive(Debug)]
+pub struct HeadlessRenderer {
+    queue: Arc<Queue>,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    command_buffer_allocator: Arc<StandardCommandBufferAllocator>,
+}
+
+impl HeadlessRenderer {
+    /// Creates a new `HeadlessRenderer` that uses the graphics queue and the memory allocator of
+    /// `context`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `context` has no graphics queue.
+    pub fn new(context: &VulkanoContext) -> Self {
+        let command_buffer_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            context.device().clone(),
+            Default::default(),
+        ));
+
+        HeadlessRenderer {
+            queue: context.graphics_queue().clone(),
+            memory_allocator: context.memory_allocator().clone(),
+            command_buffer_allocator,
+        }
+    }
+
+    /// Returns the queue that readbacks are submitted to.
+    #[inline]
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
+
+    /// Returns the memory allocator.
+    #[inline]
+    pub fn memory_allocator(&self) -> &Arc<StandardMemoryAllocator> {
+        &self.memory_allocator
+    }
+
+    /// Returns the command buffer allocator, which can also be used to record the rendering
+    /// commands.
+    #[inline]
+    pub fn command_buffer_allocator(&self) -> &Arc<StandardCommandBufferAllocator> {
+        &self.command_buffer_allocator
+    }
+
+    /// Creates a 2D image with the given `format` and `extent`, and returns a view of it.
+    ///
+    /// The image is created with `usage`, and additionally with the [`TRANSFER_SRC`] usage so
+    /// that it can be passed to [`read_image`](Self::read_image).
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the image or the image view can't be created.
+    ///
+    /// [`TRANSFER_SRC`]: ImageUsage::TRANSFER_SRC
+    pub fn create_render_target(
+        &self,
+        format: Format,
+        extent: [u32; 2],
+        usage: ImageUsage,
+    ) -> Arc<ImageView> {
+        ImageView::new_default(
+            Image::new(
+                self.memory_allocator.clone(),
+                ImageCreateInfo {
+                    image_type: ImageType::Dim2d,
+                    format,
+                    extent: [extent[0], extent[1], 1],
+                    usage: usage | ImageUsage::TRANSFER_SRC,
+                    ..Default::default()
+                },
+                AllocationCreateInfo::default(),
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    /// Copies all array layers of the first mip level of `image` to host memory, once `before`
+    /// has finished executing, and returns the tightly packed texel data.
+    ///
+    /// This blocks the current thread until the copy has finished. The image must have been
+    /// created with the [`TRANSFER_SRC`] usage, and must have a single aspect: color formats, or
+    /// depth-only or stencil-only formats.
+    ///
+    /// [`TRANSFER_SRC`]: ImageUsage::TRANSFER_SRC
+    pub fn read_image(
+        &self,
+        image: Arc<Image>,
+        before: impl GpuFuture,
+    ) -> Result<Vec<u8>, ReadbackError> {
+        let size = image_data_size(&image);
+
+        let buffer = Buffer::new_slice::<u8>(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+                ..Default::default()
+            },
+            size,
+        )
+        .map_err(ReadbackError::Allocation)?;
+
+        let mut builder = RecordingCommandBuffer::new(
+            self.command_buffer_allocator.clone(),
+            self.queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .map_err(ReadbackError::Submission)?;
+        builder
+            .copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(image, buffer.clone()))
+            .map_err(ReadbackError::Copy)?;
+        let command_buffer = builder.end().map_err(ReadbackError::Submission)?;
+
+        before
+            .then_execute(self.queue.clone(), command_buffer)
+            .map_err(ReadbackError::Execution)?
+            .then_signal_fence_and_flush()
+            .map_err(ReadbackError::Submission)?
+            .wait(None)
+            .map_err(ReadbackError::Submission)?;
+
+        let data = buffer.read().map_err(ReadbackError::Read)?.to_vec();
+
+        Ok(data)
+    }
+}
+
+/// Error that can happen when reading back data with a [`HeadlessRenderer`] or an
+/// [`AsyncReadback`](crate::readback::AsyncReadback).
+#[derive(Clone, Debug)]
+pub enum ReadbackError {
+    /// Allocating the buffer to copy the data to failed.
+    Allocation(Validated<AllocateBufferError>),
+
+    /// Recording the copy from the source failed, for example because the source wasn't created
+    /// with the `TRANSFER_SRC` usage.
+    Copy(Box<ValidationError>),
+
+    /// Creating or submitting the command buffer, or waiting for it to finish, failed.
+    Submission(Validated<VulkanError>),
+
+    /// Executing the command buffer on the queue failed.
+    Execution(CommandBufferExecError),
+
+    /// Reading the buffer from the host failed.
+    Read(HostAccessError),
+}
+
+impl Error for ReadbackError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Allocation(err) => Some(err),
+            Self::Copy(err) => Some(err),
+            Self::Submission(err) => Some(err),
+            Self::Execution(err) => Some(err),
+            Self::Read(err) => Some(err),
+        }
+    }
+}
+
+impl Display for ReadbackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::Allocation(_) => write!(f, "failed to allocate the readback buffer"),
+            Self::Copy(_) => write!(f, "failed to record the copy from the source"),
+            Self::Submission(_) => write!(f, "failed to submit the readback"),
+            Self::Execution(_) => write!(f, "failed to execute the readback"),
+            Self::Read(_) => write!(f, "failed to read the readback buffer"),
+        }
+    }
+}
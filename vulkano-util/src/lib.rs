@@ -1,3 +1,38 @@
+//! Utility functionality to make usage of vulkano easier.
+//!
+//! # Cargo features
+//!
+//! | Feature                     | Description                                             |
+//! |-----------------------------|---------------------------------------------------------|
+//! | `basis-universal`           | Enables the `basis` module for transcoding Basis        |
+//! |                             | Universal textures.                                     |
+//! | `shaderc`                   | Enables the `shader` module for runtime GLSL compiling, |
+//! |                             | and the `blit`, `culling` and `primitives` modules      |
+//! |                             | which depend on it.                                     |
+//! | `shaderc-build-from-source` | Build the `shaderc` library from source when compiling. |
+
+#[cfg(feature = "basis-universal")]
+pub mod basis;
+#[cfg(feature = "shaderc")]
+pub mod blit;
+pub mod buffer_pool;
+pub mod compute;
 pub mod context;
+#[cfg(feature = "shaderc")]
+pub mod culling;
+pub mod debug_printf;
+pub mod headless;
+pub mod msaa;
+pub mod pipeline_compiler;
+pub mod predication;
+#[cfg(feature = "shaderc")]
+pub mod primitives;
+pub mod readback;
 pub mod renderer;
+#[cfg(feature = "shaderc")]
+pub mod shader;
+pub mod streaming;
+pub mod surface_format;
+pub mod texture_manager;
+pub mod validation;
 pub mod window;
@@ -0,0 +1,357 @@
+//! Multisampled render targets that follow the size of a swapchain.
+//!
+//! With multisample anti-aliasing, rendering is done to images that have multiple samples per
+//! texel, which are then resolved into a single-sampled image, such as a swapchain image.
+//! [`MsaaTargets`] owns the multisampled color image and optionally a depth image, recreates them
+//! when the swapchain is resized, and describes how the color image is resolved, either for a
+//! render pass or for dynamic rendering.
+//!
+//! ```no_run
+//! # use vulkano_util::{context::VulkanoContext, renderer::VulkanoWindowRenderer};
+//! use vulkano::{format::Format, image::SampleCount};
+//! use vulkano_util::msaa::{MsaaTargets, MsaaTargetsCreateInfo};
+//!
+//! # let context: VulkanoContext = todo!();
+//! # let renderer: &mut VulkanoWindowRenderer = todo!();
+//! let mut msaa_targets = MsaaTargets::new(
+//!     context.memory_allocator().clone(),
+//!     renderer.swapchain_image_size(),
+//!     MsaaTargetsCreateInfo {
+//!         samples: SampleCount::Sample4,
+//!         color_format: renderer.swapchain_format(),
+//!         depth_format: Some(Format::D16_UNORM),
+//!     },
+//! );
+//!
+//! // Each frame:
+//! let future = renderer
+//!     .acquire(None, |swapchain_image_views| {
+//!         let [width, height, _] = swapchain_image_views[0].image().extent();
+//!         msaa_targets.resize([width, height]);
+//!     })
+//!     .unwrap();
+//! let rendering_info = msaa_targets.rendering_info(
+//!     renderer.swapchain_image_view(),
+//!     [0.0, 0.0, 0.0, 1.0].into(),
+//! );
+//! ```
+
+use crate::renderer::DEFAULT_IMAGE_FORMAT;
+use std::sync::Arc;
+use vulkano::{
+    command_buffer::{RenderingAttachmentInfo, RenderingAttachmentResolveInfo, RenderingInfo},
+    device::{Device, DeviceOwned},
+    format::{ClearValue, Format},
+    image::{
+        view::ImageView, Image, ImageAspects, ImageCreateInfo, ImageLayout, ImageType, ImageUsage,
+        SampleCount,
+    },
+    memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+    render_pass::{
+        AttachmentDescription, AttachmentLoadOp, AttachmentReference, AttachmentStoreOp,
+        Framebuffer, FramebufferCreateInfo, RenderPass, RenderPassCreateInfo, SubpassDescription,
+    },
+    Validated, VulkanError,
+};
+
+/// Multisampled color and depth images that are resized together with a swapchain.
+#[derive(Debug)]
+pub struct MsaaTargets {
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    samples: SampleCount,
+    color: Arc<ImageView>,
+    depth: Option<Arc<ImageView>>,
+}
+
+impl MsaaTargets {
+    /// Creates new `MsaaTargets` with the given `extent`, which should be the extent of the
+    /// images that are resolved into.
+    ///
+    /// If the device doesn't support `create_info.samples` for framebuffer attachments, the
+    /// highest supported sample count below it is used instead.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the images or image views can't be created.
+    pub fn new(
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        extent: [u32; 2],
+        create_info: MsaaTargetsCreateInfo,
+    ) -> Self {
+        let MsaaTargetsCreateInfo {
+            samples,
+            color_format,
+            depth_format,
+        } = create_info;
+
+        let samples =
+            Self::supported_samples(memory_allocator.device(), samples, depth_format.is_some());
+        let color = Self::create_image(&memory_allocator, color_format, extent, samples);
+        let depth = depth_format
+            .map(|format| Self::create_image(&memory_allocator, format, extent, samples));
+
+        MsaaTargets {
+            memory_allocator,
+            samples,
+            color,
+            depth,
+        }
+    }
+
+    /// Returns the highest sample count, not higher than `samples`, that framebuffer attachments
+    /// support on `device`.
+    fn supported_samples(device: &Device, samples: SampleCount, depth: bool) -> SampleCount {
+        let properties = device.physical_device().properties();
+        let mut supported = properties.framebuffer_color_sample_counts;
+
+        if depth {
+            supported &= properties.framebuffer_depth_sample_counts;
+        }
+
+        [
+            SampleCount::Sample64,
+            SampleCount::Sample32,
+            SampleCount::Sample16,
+            SampleCount::Sample8,
+            SampleCount::Sample4,
+            SampleCount::Sample2,
+        ]
+        .into_iter()
+        .find(|&candidate| {
+            u32::from(candidate) <= u32::from(samples) && supported.contains_enum(candidate)
+        })
+        .unwrap_or(SampleCount::Sample1)
+    }
+
+    fn create_image(
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        format: Format,
+        extent: [u32; 2],
+        samples: SampleCount,
+    ) -> Arc<ImageView> {
+        let attachment_usage = if format
+            .aspects()
+            .intersects(ImageAspects::DEPTH | ImageAspects::STENCIL)
+        {
+            ImageUsage::DEPTH_STENCIL_ATTACHMENT
+        } else {
+            ImageUsage::COLOR_ATTACHMENT
+        };
+
+        ImageView::new_default(
+            Image::new(
+                memory_allocator.clone(),
+                ImageCreateInfo {
+                    image_type: ImageType::Dim2d,
+                    format,
+                    extent: [extent[0], extent[1], 1],
+                    usage: attachment_usage | ImageUsage::TRANSIENT_ATTACHMENT,
+                    samples,
+                    ..Default::default()
+                },
+                AllocationCreateInfo::default(),
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    /// Recreates the images with the new `extent`, if it differs from the current extent.
+    ///
+    /// This should be called whenever the swapchain is recreated, for example in the
+    /// `on_recreate_swapchain` callback of
+    /// [`VulkanoWindowRenderer::acquire`](crate::renderer::VulkanoWindowRenderer::acquire).
+    /// Framebuffers that use the images must be recreated afterwards.
+    pub fn resize(&mut self, extent: [u32; 2]) {
+        if self.extent() == extent {
+            return;
+        }
+
+        self.color = Self::create_image(
+            &self.memory_allocator,
+            self.color.format(),
+            extent,
+            self.samples,
+        );
+        self.depth = self.depth.as_ref().map(|depth| {
+            Self::create_image(&self.memory_allocator, depth.format(), extent, self.samples)
+        });
+    }
+
+    /// Returns the number of samples of the images.
+    #[inline]
+    pub fn samples(&self) -> SampleCount {
+        self.samples
+    }
+
+    /// Returns the extent of the images.
+    #[inline]
+    pub fn extent(&self) -> [u32; 2] {
+        let [width, height, _] = self.color.image().extent();
+        [width, height]
+    }
+
+    /// Returns the multisampled color image.
+    #[inline]
+    pub fn color(&self) -> &Arc<ImageView> {
+        &self.color
+    }
+
+    /// Returns the multisampled depth image, if one was requested.
+    #[inline]
+    pub fn depth(&self) -> Option<&Arc<ImageView>> {
+        self.depth.as_ref()
+    }
+
+    /// Returns the info to begin dynamic rendering to the multisampled images, with the color
+    /// image being resolved into `resolve_view` at the end.
+    ///
+    /// The color image is cleared with `clear_color`, and the depth image, if any, is cleared to
+    /// `1.0`. The contents of the multisampled images are not stored.
+    pub fn rendering_info(
+        &self,
+        resolve_view: Arc<ImageView>,
+        clear_color: ClearValue,
+    ) -> RenderingInfo {
+        RenderingInfo {
+            color_attachments: vec![Some(RenderingAttachmentInfo {
+                load_op: AttachmentLoadOp::Clear,
+                store_op: AttachmentStoreOp::DontCare,
+                clear_value: Some(clear_color),
+                resolve_info: Some(RenderingAttachmentResolveInfo::image_view(resolve_view)),
+                ..RenderingAttachmentInfo::image_view(self.color.clone())
+            })],
+            depth_attachment: self.depth.as_ref().map(|depth| RenderingAttachmentInfo {
+                load_op: AttachmentLoadOp::Clear,
+                store_op: AttachmentStoreOp::DontCare,
+                clear_value: Some(ClearValue::Depth(1.0)),
+                ..RenderingAttachmentInfo::image_view(depth.clone())
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a render pass with a single subpass that renders to the multisampled images, and
+    /// resolves the color image into an attachment with the format `resolve_format`.
+    ///
+    /// The attachments are, in order: the multisampled color image, the resolve image and the
+    /// multisampled depth image, if any. This is the order that [`framebuffer`] uses.
+    ///
+    /// [`framebuffer`]: Self::framebuffer
+    pub fn render_pass(
+        &self,
+        resolve_format: Format,
+    ) -> Result<Arc<RenderPass>, Validated<VulkanError>> {
+        let mut attachments = vec![
+            AttachmentDescription {
+                format: self.color.format(),
+                samples: self.samples,
+                load_op: AttachmentLoadOp::Clear,
+                store_op: AttachmentStoreOp::DontCare,
+                initial_layout: ImageLayout::ColorAttachmentOptimal,
+                final_layout: ImageLayout::ColorAttachmentOptimal,
+                ..Default::default()
+            },
+            AttachmentDescription {
+                format: resolve_format,
+                samples: SampleCount::Sample1,
+                load_op: AttachmentLoadOp::DontCare,
+                store_op: AttachmentStoreOp::Store,
+                initial_layout: ImageLayout::ColorAttachmentOptimal,
+                final_layout: ImageLayout::ColorAttachmentOptimal,
+                ..Default::default()
+            },
+        ];
+        let depth_stencil_attachment = self.depth.as_ref().map(|depth| {
+            attachments.push(AttachmentDescription {
+                format: depth.format(),
+                samples: self.samples,
+                load_op: AttachmentLoadOp::Clear,
+                store_op: AttachmentStoreOp::DontCare,
+                initial_layout: ImageLayout::DepthStencilAttachmentOptimal,
+                final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+                ..Default::default()
+            });
+
+            AttachmentReference {
+                attachment: 2,
+                layout: ImageLayout::DepthStencilAttachmentOptimal,
+                ..Default::default()
+            }
+        });
+
+        RenderPass::new(
+            self.memory_allocator.device().clone(),
+            RenderPassCreateInfo {
+                attachments,
+                subpasses: vec![SubpassDescription {
+                    color_attachments: vec![Some(AttachmentReference {
+                        attachment: 0,
+                        layout: ImageLayout::ColorAttachmentOptimal,
+                        ..Default::default()
+                    })],
+                    color_resolve_attachments: vec![Some(AttachmentReference {
+                        attachment: 1,
+                        layout: ImageLayout::ColorAttachmentOptimal,
+                        ..Default::default()
+                    })],
+                    depth_stencil_attachment,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates a framebuffer for a render pass that was created with [`render_pass`], that
+    /// resolves into `resolve_view`.
+    ///
+    /// [`render_pass`]: Self::render_pass
+    pub fn framebuffer(
+        &self,
+        render_pass: Arc<RenderPass>,
+        resolve_view: Arc<ImageView>,
+    ) -> Result<Arc<Framebuffer>, Validated<VulkanError>> {
+        let mut attachments = vec![self.color.clone(), resolve_view];
+        attachments.extend(self.depth.clone());
+
+        Framebuffer::new(
+            render_pass,
+            FramebufferCreateInfo {
+                attachments,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// Parameters to create new [`MsaaTargets`].
+#[derive(Clone, Debug)]
+pub struct MsaaTargetsCreateInfo {
+    /// The number of samples per texel.
+    ///
+    /// The default value is [`SampleCount::Sample4`].
+    pub samples: SampleCount,
+
+    /// The format of the color image. This should be the same as the format of the images that
+    /// are resolved into, such as the swapchain format.
+    ///
+    /// The default value is [`DEFAULT_IMAGE_FORMAT`].
+    pub color_format: Format,
+
+    /// The format of the depth image, or `None` to not create a depth image.
+    ///
+    /// The default value is `None`.
+    pub depth_format: Option<Format>,
+}
+
+impl Default for MsaaTargetsCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            samples: SampleCount::Sample4,
+            color_format: DEFAULT_IMAGE_FORMAT,
+            depth_format: None,
+        }
+    }
+}
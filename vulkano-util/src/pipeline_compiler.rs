@@ -0,0 +1,315 @@
+//! Compiling pipelines on background threads.
+//!
+//! Creating a pipeline can take a long time, because the driver compiles the shaders of the
+//! pipeline to machine code. Doing this on the thread that renders the frames causes hitches,
+//! most noticeably the first time that an object with a new material comes into view.
+//!
+//! A [`PipelineCompiler`] owns a pool of worker threads that create pipelines in the background.
+//! Submitting a create info returns a [`PendingPipeline`] immediately, which can be polled each
+//! frame until the pipeline is ready. In the meantime, the object can be skipped or drawn with a
+//! fallback pipeline. When the pipeline is needed right away, [`PendingPipeline::wait`] stalls
+//! until it is ready, compiling it on the calling thread if no worker has started on it yet.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::{
+//! #     device::Device,
+//! #     pipeline::{graphics::GraphicsPipelineCreateInfo, GraphicsPipeline},
+//! # };
+//! use vulkano_util::pipeline_compiler::PipelineCompiler;
+//!
+//! # let device: Arc<Device> = todo!();
+//! # let create_info: GraphicsPipelineCreateInfo = todo!();
+//! # let fallback_pipeline: Arc<GraphicsPipeline> = todo!();
+//! let compiler = PipelineCompiler::new(device, Default::default());
+//! let pending = compiler.compile_graphics(create_info);
+//!
+//! // Every frame:
+//! let pipeline = match pending.try_get() {
+//!     Some(result) => result.unwrap(),
+//!     None => fallback_pipeline.clone(),
+//! };
+//! ```
+//!
+//! Pipeline creation in vulkano can't be deferred with a
+//! [`DeferredOperation`](vulkano::deferred::DeferredOperation), so each pipeline is compiled by a
+//! single thread. Giving the compiler a [`PipelineCache`] lets the driver reuse the work
+//! of earlier compilations, which is safe to do from multiple threads at once.
+
+use std::{
+    fmt::{Debug, Error as FmtError, Formatter},
+    mem,
+    num::NonZeroUsize,
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Condvar, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+use vulkano::{
+    device::Device,
+    pipeline::{
+        cache::PipelineCache, compute::ComputePipelineCreateInfo,
+        graphics::GraphicsPipelineCreateInfo, ComputePipeline, GraphicsPipeline,
+    },
+    Validated, VulkanError,
+};
+
+/// The result of compiling a pipeline.
+pub type PipelineResult<P> = Result<Arc<P>, Validated<VulkanError>>;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Compiles pipelines on a pool of worker threads.
+///
+/// Pipelines are compiled in the order in which they are submitted. Dropping the compiler blocks
+/// until all pipelines that were submitted to it have been compiled.
+pub struct PipelineCompiler {
+    device: Arc<Device>,
+    cache: Option<Arc<PipelineCache>>,
+    sender: Option<Sender<Job>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl PipelineCompiler {
+    /// Creates a new `PipelineCompiler`, and starts its worker threads.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if a worker thread could not be spawned.
+    pub fn new(device: Arc<Device>, create_info: PipelineCompilerCreateInfo) -> Self {
+        let PipelineCompilerCreateInfo {
+            thread_count,
+            cache,
+        } = create_info;
+
+        let thread_count = thread_count
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get);
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let threads = (0..thread_count)
+            .map(|index| {
+                let receiver = receiver.clone();
+
+                thread::Builder::new()
+                    .name(format!("vulkano-pipeline-compiler-{}", index))
+                    .spawn(move || loop {
+                        // The lock is released before the job runs, so that the other workers
+                        // can pick up jobs in the meantime.
+                        let job = receiver.lock().unwrap().recv();
+
+                        match job {
+                            Ok(job) => job(),
+                            Err(_) => break,
+                        }
+                    })
+                    .expect("failed to spawn a pipeline compiler thread")
+            })
+            .collect();
+
+        PipelineCompiler {
+            device,
+            cache,
+            sender: Some(sender),
+            threads,
+        }
+    }
+
+    /// Returns the device that pipelines are created for.
+    #[inline]
+    pub fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+
+    /// Returns the pipeline cache that is used when creating pipelines.
+    #[inline]
+    pub fn cache(&self) -> Option<&Arc<PipelineCache>> {
+        self.cache.as_ref()
+    }
+
+    /// Returns the number of worker threads.
+    #[inline]
+    pub fn thread_count(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Queues a graphics pipeline to be compiled.
+    pub fn compile_graphics(
+        &self,
+        create_info: GraphicsPipelineCreateInfo,
+    ) -> PendingPipeline<GraphicsPipeline> {
+        let device = self.device.clone();
+        let cache = self.cache.clone();
+
+        self.submit(move || GraphicsPipeline::new(device, cache, create_info))
+    }
+
+    /// Queues a compute pipeline to be compiled.
+    pub fn compile_compute(
+        &self,
+        create_info: ComputePipelineCreateInfo,
+    ) -> PendingPipeline<ComputePipeline> {
+        let device = self.device.clone();
+        let cache = self.cache.clone();
+
+        self.submit(move || ComputePipeline::new(device, cache, create_info))
+    }
+
+    fn submit<P>(
+        &self,
+        compile: impl FnOnce() -> PipelineResult<P> + Send + 'static,
+    ) -> PendingPipeline<P>
+    where
+        P: Send + Sync + 'static,
+    {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(PendingState::Queued(Box::new(compile))),
+            condvar: Condvar::new(),
+        });
+
+        let job_shared = shared.clone();
+        let job: Job = Box::new(move || job_shared.run());
+
+        // The workers only stop receiving when the compiler is dropped, so sending can't fail.
+        let _ = self.sender.as_ref().unwrap().send(job);
+
+        PendingPipeline { shared }
+    }
+}
+
+impl Drop for PipelineCompiler {
+    fn drop(&mut self) {
+        // Closing the channel makes the workers stop once the queue is empty.
+        self.sender = None;
+
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Debug for PipelineCompiler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_struct("PipelineCompiler")
+            .field("device", &self.device)
+            .field("cache", &self.cache)
+            .field("thread_count", &self.threads.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parameters to create a new [`PipelineCompiler`].
+#[derive(Clone, Debug, Default)]
+pub struct PipelineCompilerCreateInfo {
+    /// The number of worker threads.
+    ///
+    /// If this is `None`, the number of threads that the system can run in parallel is used.
+    ///
+    /// The default value is `None`.
+    pub thread_count: Option<NonZeroUsize>,
+
+    /// The pipeline cache to use when creating pipelines.
+    ///
+    /// The default value is `None`.
+    pub cache: Option<Arc<PipelineCache>>,
+}
+
+/// A pipeline that was submitted to a [`PipelineCompiler`], and may not be ready yet.
+///
+/// Dropping a `PendingPipeline` before it's ready doesn't cancel the compilation.
+pub struct PendingPipeline<P> {
+    shared: Arc<Shared<P>>,
+}
+
+impl<P> PendingPipeline<P> {
+    /// Returns whether compiling the pipeline has finished, either successfully or not.
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        matches!(*self.shared.state.lock().unwrap(), PendingState::Done(_))
+    }
+
+    /// Returns the result of compiling the pipeline if it's ready, or `None` if it's still
+    /// pending.
+    pub fn try_get(&self) -> Option<PipelineResult<P>> {
+        match &*self.shared.state.lock().unwrap() {
+            PendingState::Done(result) => Some(result.clone()),
+            _ => None,
+        }
+    }
+
+    /// Blocks the current thread until the pipeline is ready, and returns the result.
+    ///
+    /// If no worker thread has started compiling the pipeline yet, it's compiled on the current
+    /// thread instead of waiting for the pipelines that were submitted before it.
+    pub fn wait(&self) -> PipelineResult<P> {
+        self.shared.run();
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        loop {
+            match &*state {
+                PendingState::Done(result) => return result.clone(),
+                _ => state = self.shared.condvar.wait(state).unwrap(),
+            }
+        }
+    }
+}
+
+impl<P> Clone for PendingPipeline<P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        PendingPipeline {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<P> Debug for PendingPipeline<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        let state = match &*self.shared.state.lock().unwrap() {
+            PendingState::Queued(_) => "queued",
+            PendingState::Compiling => "compiling",
+            PendingState::Done(Ok(_)) => "ready",
+            PendingState::Done(Err(_)) => "failed",
+        };
+
+        f.debug_struct("PendingPipeline")
+            .field("state", &state)
+            .finish()
+    }
+}
+
+struct Shared<P> {
+    state: Mutex<PendingState<P>>,
+    condvar: Condvar,
+}
+
+enum PendingState<P> {
+    Queued(Box<dyn FnOnce() -> PipelineResult<P> + Send>),
+    Compiling,
+    Done(PipelineResult<P>),
+}
+
+impl<P> Shared<P> {
+    // Compiles the pipeline if nobody has started on it yet. Whichever of the worker and
+    // `PendingPipeline::wait` gets here first does the work.
+    fn run(&self) {
+        let compile = {
+            let mut state = self.state.lock().unwrap();
+
+            match mem::replace(&mut *state, PendingState::Compiling) {
+                PendingState::Queued(compile) => compile,
+                other => {
+                    *state = other;
+                    return;
+                }
+            }
+        };
+
+        let result = compile();
+        *self.state.lock().unwrap() = PendingState::Done(result);
+        self.condvar.notify_all();
+    }
+}
@@ -0,0 +1,423 @@
+//! Skipping the draws of objects that were occluded in the previous frame.
+//!
+//! [`OcclusionPredicator`] combines occlusion queries with conditional rendering. Every frame, a
+//! cheap proxy of each object, usually its bounding box, is drawn inside an occlusion query. At
+//! the start of the next frame, the results of these queries are copied into a buffer of
+//! predicates on the device, and the real draws of each object are wrapped in conditional
+//! rendering that reads its predicate. Objects whose proxy had no samples pass the depth test are
+//! then skipped without the host ever having to wait for the query results.
+//!
+//! The results are one frame old, so an object that becomes visible is drawn one frame late. To
+//! limit this, the proxies should be drawn after the occluders, with depth testing enabled but
+//! depth and color writes disabled, and outside of any predicated region so that occluded objects
+//! are tested again every frame. Objects that were not queried in the previous frame are always
+//! drawn.
+//!
+//! The predicator keeps one query pool and one predicate buffer for every frame in flight, and
+//! cycles through them with [`begin_frame`].
+//!
+//! Conditional rendering requires the [`conditional_rendering`] feature.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::{command_buffer::RecordingCommandBuffer, memory::allocator::MemoryAllocator};
+//! use vulkano_util::predication::{OcclusionPredicator, OcclusionPredicatorCreateInfo};
+//!
+//! # let memory_allocator: Arc<dyn MemoryAllocator> = return;
+//! # let builder: &mut RecordingCommandBuffer = return;
+//! let mut predicator = OcclusionPredicator::new(
+//!     memory_allocator,
+//!     OcclusionPredicatorCreateInfo {
+//!         object_count: 256,
+//!         ..Default::default()
+//!     },
+//! )
+//! .unwrap();
+//!
+//! // Every frame, before the render pass:
+//! unsafe { predicator.begin_frame(builder) }.unwrap();
+//!
+//! // Inside the render pass, after drawing the occluders:
+//! for object in 0..256 {
+//!     predicator.begin_query(builder, object).unwrap();
+//!     // Draw the bounding box of `object`.
+//!     predicator.end_query(builder).unwrap();
+//! }
+//!
+//! for object in 0..256 {
+//!     predicator.begin_predicated(builder, object).unwrap();
+//!     // Draw `object`.
+//!     predicator.end_predicated(builder).unwrap();
+//! }
+//! ```
+//!
+//! [`begin_frame`]: OcclusionPredicator::begin_frame
+//! [`conditional_rendering`]: vulkano::device::DeviceFeatures::conditional_rendering
+
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    sync::Arc,
+};
+use vulkano::{
+    buffer::{AllocateBufferError, Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::{ConditionalRenderingBeginInfo, RecordingCommandBuffer},
+    device::{Device, DeviceOwned},
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
+    query::{QueryControlFlags, QueryPool, QueryPoolCreateInfo, QueryResultFlags, QueryType},
+    Validated, ValidationError, VulkanError,
+};
+
+/// Issues occlusion queries for objects and predicates their draws in the next frame on the
+/// results.
+///
+/// See the [module-level documentation] for more information.
+///
+/// [module-level documentation]: self
+#[derive(Debug)]
+pub struct OcclusionPredicator {
+    device: Arc<Device>,
+    object_count: u32,
+    query_flags: QueryControlFlags,
+    frames: Vec<FrameResources>,
+    current_frame: Option<usize>,
+    active_query: Option<u32>,
+}
+
+#[derive(Debug)]
+struct FrameResources {
+    query_pool: Arc<QueryPool>,
+    predicates: Subbuffer<[u32]>,
+    // Whether the query of each object was issued in the frame that last used these resources.
+    issued: Vec<bool>,
+}
+
+impl OcclusionPredicator {
+    /// Creates a new `OcclusionPredicator`.
+    pub fn new(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        create_info: OcclusionPredicatorCreateInfo,
+    ) -> Result<Self, OcclusionPredicatorError> {
+        let OcclusionPredicatorCreateInfo {
+            object_count,
+            frames_in_flight,
+            precise,
+        } = create_info;
+
+        assert!(object_count != 0);
+
+        let device = memory_allocator.device().clone();
+        let frames = (0..frames_in_flight.max(1))
+            .map(|_| {
+                let query_pool = QueryPool::new(
+                    device.clone(),
+                    QueryPoolCreateInfo {
+                        query_count: object_count,
+                        ..QueryPoolCreateInfo::query_type(QueryType::Occlusion)
+                    },
+                )
+                .map_err(OcclusionPredicatorError::QueryPoolCreation)?;
+
+                let predicates = Buffer::new_slice::<u32>(
+                    memory_allocator.clone(),
+                    BufferCreateInfo {
+                        usage: BufferUsage::TRANSFER_DST | BufferUsage::CONDITIONAL_RENDERING,
+                        ..Default::default()
+                    },
+                    AllocationCreateInfo {
+                        memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                        ..Default::default()
+                    },
+                    object_count.into(),
+                )
+                .map_err(OcclusionPredicatorError::BufferAllocation)?;
+
+                Ok(FrameResources {
+                    query_pool,
+                    predicates,
+                    issued: vec![false; object_count as usize],
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(OcclusionPredicator {
+            device,
+            object_count,
+            query_flags: if precise {
+                QueryControlFlags::PRECISE
+            } else {
+                QueryControlFlags::empty()
+            },
+            frames,
+            current_frame: None,
+            active_query: None,
+        })
+    }
+
+    /// Returns the number of objects that can be queried and predicated.
+    #[inline]
+    pub fn object_count(&self) -> u32 {
+        self.object_count
+    }
+
+    /// Starts a new frame.
+    ///
+    /// This records commands that turn the results of the queries of the previous frame into the
+    /// predicates for this frame, and that reset the queries for this frame. It must be recorded
+    /// outside of a render pass, before any other method is called for the frame.
+    ///
+    /// # Safety
+    ///
+    /// - The command buffers that the commands of each frame are recorded into must be submitted
+    ///   to the same queue, in the order that `begin_frame` was called.
+    /// - When the command buffer of a frame is submitted, the command buffer of the frame
+    ///   `frames_in_flight` frames earlier must have finished executing.
+    pub unsafe fn begin_frame(
+        &mut self,
+        builder: &mut RecordingCommandBuffer,
+    ) -> Result<(), Box<ValidationError>> {
+        if self.active_query.is_some() {
+            return Err(Box::new(ValidationError {
+                problem: "a query of the previous frame is still active".into(),
+                ..Default::default()
+            }));
+        }
+
+        let previous_frame = self.current_frame;
+        let current_frame = previous_frame.map_or(0, |index| (index + 1) % self.frames.len());
+        let predicates = self.frames[current_frame].predicates.clone();
+
+        // Objects that were not queried in the previous frame are always drawn.
+        builder.fill_buffer(predicates.clone(), 1)?;
+
+        if let Some(previous_frame) = previous_frame {
+            let FrameResources {
+                query_pool, issued, ..
+            } = &self.frames[previous_frame];
+
+            for (start, end) in issued_runs(issued) {
+                builder.copy_query_pool_results(
+                    query_pool.clone(),
+                    start..end,
+                    predicates.clone().slice(u64::from(start)..u64::from(end)),
+                    QueryResultFlags::WAIT,
+                )?;
+            }
+        }
+
+        let FrameResources {
+            query_pool, issued, ..
+        } = &mut self.frames[current_frame];
+        builder.reset_query_pool(query_pool.clone(), 0..self.object_count)?;
+        issued.fill(false);
+
+        self.current_frame = Some(current_frame);
+
+        Ok(())
+    }
+
+    /// Begins the occlusion query of the object `object`.
+    ///
+    /// The proxy of the object should be drawn between this and [`end_query`]. Each object can
+    /// be queried at most once per frame.
+    ///
+    /// [`end_query`]: Self::end_query
+    pub fn begin_query(
+        &mut self,
+        builder: &mut RecordingCommandBuffer,
+        object: u32,
+    ) -> Result<(), Box<ValidationError>> {
+        let current_frame = self.validate_object(object)?;
+
+        if self.active_query.is_some() {
+            return Err(Box::new(ValidationError {
+                problem: "the query of another object is already active".into(),
+                ..Default::default()
+            }));
+        }
+
+        let FrameResources {
+            query_pool, issued, ..
+        } = &mut self.frames[current_frame];
+
+        if issued[object as usize] {
+            return Err(Box::new(ValidationError {
+                context: "object".into(),
+                problem: "was already queried in this frame".into(),
+                ..Default::default()
+            }));
+        }
+
+        // SAFETY: The query was reset in `begin_frame`, and has not been used since.
+        unsafe { builder.begin_query(query_pool.clone(), object, self.query_flags) }?;
+
+        issued[object as usize] = true;
+        self.active_query = Some(object);
+
+        Ok(())
+    }
+
+    /// Ends the occlusion query that was begun with [`begin_query`].
+    ///
+    /// [`begin_query`]: Self::begin_query
+    pub fn end_query(
+        &mut self,
+        builder: &mut RecordingCommandBuffer,
+    ) -> Result<(), Box<ValidationError>> {
+        let object = self.active_query.ok_or_else(|| {
+            Box::new(ValidationError {
+                problem: "no query is active".into(),
+                ..Default::default()
+            })
+        })?;
+        let query_pool = &self.frames[self.current_frame.unwrap()].query_pool;
+
+        builder.end_query(query_pool.clone(), object)?;
+
+        self.active_query = None;
+
+        Ok(())
+    }
+
+    /// Begins conditional rendering that discards the following commands if the object `object`
+    /// was occluded in the previous frame.
+    ///
+    /// The real draws of the object should be recorded between this and [`end_predicated`].
+    ///
+    /// [`end_predicated`]: Self::end_predicated
+    pub fn begin_predicated(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        object: u32,
+    ) -> Result<(), Box<ValidationError>> {
+        let current_frame = self.validate_object(object)?;
+        let predicate = self.frames[current_frame]
+            .predicates
+            .clone()
+            .index(object.into());
+
+        builder.begin_conditional_rendering(ConditionalRenderingBeginInfo::buffer(predicate))?;
+
+        Ok(())
+    }
+
+    /// Ends the conditional rendering that was begun with [`begin_predicated`].
+    ///
+    /// [`begin_predicated`]: Self::begin_predicated
+    pub fn end_predicated(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+    ) -> Result<(), Box<ValidationError>> {
+        builder.end_conditional_rendering()?;
+
+        Ok(())
+    }
+
+    fn validate_object(&self, object: u32) -> Result<usize, Box<ValidationError>> {
+        let current_frame = self.current_frame.ok_or_else(|| {
+            Box::new(ValidationError {
+                problem: "`begin_frame` has not been called yet".into(),
+                ..Default::default()
+            })
+        })?;
+
+        if object >= self.object_count {
+            return Err(Box::new(ValidationError {
+                context: "object".into(),
+                problem: "is not less than the object count of the predicator".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(current_frame)
+    }
+}
+
+unsafe impl DeviceOwned for OcclusionPredicator {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+/// Parameters to create a new [`OcclusionPredicator`].
+#[derive(Clone, Debug)]
+pub struct OcclusionPredicatorCreateInfo {
+    /// The number of objects that can be queried and predicated. Objects are identified by their
+    /// index, which must be less than this value.
+    ///
+    /// The value must not be zero.
+    ///
+    /// The default value is `1024`.
+    pub object_count: u32,
+
+    /// The maximum number of frames whose command buffers can be executing at the same time.
+    ///
+    /// The default value is `2`.
+    pub frames_in_flight: u32,
+
+    /// Whether the queries should count the exact number of samples that passed, rather than
+    /// only whether any did. This is not needed for predication, and can be slower.
+    ///
+    /// If set to `true`, the [`occlusion_query_precise`] feature must be enabled on the device.
+    ///
+    /// The default value is `false`.
+    ///
+    /// [`occlusion_query_precise`]: vulkano::device::DeviceFeatures::occlusion_query_precise
+    pub precise: bool,
+}
+
+impl Default for OcclusionPredicatorCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        OcclusionPredicatorCreateInfo {
+            object_count: 1024,
+            frames_in_flight: 2,
+            precise: false,
+        }
+    }
+}
+
+/// Error that can happen when creating an [`OcclusionPredicator`].
+#[derive(Clone, Debug)]
+pub enum OcclusionPredicatorError {
+    /// Creating a query pool failed.
+    QueryPoolCreation(Validated<VulkanError>),
+
+    /// Allocating a predicate buffer failed.
+    BufferAllocation(Validated<AllocateBufferError>),
+}
+
+impl Error for OcclusionPredicatorError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::QueryPoolCreation(err) => Some(err),
+            Self::BufferAllocation(err) => Some(err),
+        }
+    }
+}
+
+impl Display for OcclusionPredicatorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::QueryPoolCreation(_) => write!(f, "failed to create a query pool"),
+            Self::BufferAllocation(_) => write!(f, "failed to allocate a predicate buffer"),
+        }
+    }
+}
+
+// Returns the ranges of consecutive objects whose query was issued.
+fn issued_runs(issued: &[bool]) -> impl Iterator<Item = (u32, u32)> + '_ {
+    let mut index = 0;
+
+    std::iter::from_fn(move || {
+        let start = index + issued[index..].iter().position(|&issued| issued)?;
+        let len = issued[start..]
+            .iter()
+            .position(|&issued| !issued)
+            .unwrap_or(issued.len() - start);
+        index = start + len;
+
+        Some((start as u32, index as u32))
+    })
+}
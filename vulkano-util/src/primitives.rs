@@ -0,0 +1,796 @@
+//! Parallel primitives that run on the GPU: exclusive scan, segmented reduce and radix sort.
+//!
+//! These are the building blocks of many GPU algorithms, such as stream compaction for culling or
+//! sorting particles by depth. [`GpuPrimitives`] records them into a command buffer, operating on
+//! [`Subbuffer`]s of `u32` values.
+//!
+//! The kernels are configured based on the capabilities of the device. The workgroup size is the
+//! largest power of two up to 256 that the device supports, and if the device supports subgroup
+//! arithmetic operations in compute shaders, they are used for the reductions and scans within a
+//! workgroup. Otherwise, the kernels fall back to scans and reductions in workgroup-shared memory.
+//!
+//! All buffers that are passed in must have been created with the [`STORAGE_BUFFER`] usage. The
+//! temporary buffers that the primitives need are allocated from the memory allocator that is
+//! given to [`GpuPrimitives::new`], and are kept alive by the command buffer.
+//!
+//! The compute shaders are compiled at runtime the first time they are needed, so this module is
+//! only available when the `shaderc` feature of `vulkano-util` is enabled.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::{
+//! #     buffer::Subbuffer,
+//! #     command_buffer::RecordingCommandBuffer,
+//! #     descriptor_set::allocator::StandardDescriptorSetAllocator,
+//! #     memory::allocator::StandardMemoryAllocator,
+//! # };
+//! use vulkano_util::primitives::GpuPrimitives;
+//!
+//! # let memory_allocator: Arc<StandardMemoryAllocator> = return;
+//! # let descriptor_set_allocator: Arc<StandardDescriptorSetAllocator> = return;
+//! # let builder: &mut RecordingCommandBuffer = return;
+//! # let depths: Subbuffer<[u32]> = return;
+//! # let particle_indices: Subbuffer<[u32]> = return;
+//! let primitives = GpuPrimitives::new(memory_allocator, descriptor_set_allocator);
+//!
+//! // Sort the particles by depth.
+//! primitives
+//!     .radix_sort_by_key(builder, depths, particle_indices)
+//!     .unwrap();
+//! ```
+//!
+//! [`STORAGE_BUFFER`]: vulkano::buffer::BufferUsage::STORAGE_BUFFER
+
+use crate::shader::{compile_glsl, GlslCompileInfo, ShaderCompileError};
+use ahash::HashMap;
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    mem,
+    sync::{Arc, Mutex},
+};
+use vulkano::{
+    buffer::{AllocateBufferError, Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::RecordingCommandBuffer,
+    descriptor_set::{allocator::DescriptorSetAllocator, DescriptorSet, WriteDescriptorSet},
+    device::{physical::SubgroupFeatures, Device, DeviceOwned},
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
+    pipeline::{
+        compute::ComputePipelineCreateInfo, layout::PipelineDescriptorSetLayoutCreateInfo,
+        ComputePipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    shader::{ShaderStage, ShaderStages},
+    DeviceSize, Validated, ValidationError, Version, VulkanError,
+};
+
+/// The number of bits of the key that each radix sort pass sorts by.
+const RADIX_BITS: u32 = 4;
+
+/// The number of distinct digits of each radix sort pass.
+const RADIX: u32 = 1 << RADIX_BITS;
+
+const COMMON_SOURCE: &str = r"
+    #version 450
+
+    #ifdef USE_SUBGROUPS
+    #extension GL_KHR_shader_subgroup_basic : require
+    #extension GL_KHR_shader_subgroup_arithmetic : require
+    #endif
+
+    layout(local_size_x = WORKGROUP_SIZE) in;
+
+    shared uint shared_data[WORKGROUP_SIZE];
+    shared uint shared_total;
+
+    // Returns the sum of `value` over all invocations before this one in the workgroup, and the
+    // sum over the whole workgroup in `total`. Must be called in uniform control flow.
+    uint workgroup_exclusive_add(uint value, out uint total) {
+        uint lid = gl_LocalInvocationID.x;
+
+    #ifdef USE_SUBGROUPS
+        uint inclusive = subgroupInclusiveAdd(value);
+
+        if (gl_SubgroupInvocationID == gl_SubgroupSize - 1) {
+            shared_data[gl_SubgroupID] = inclusive;
+        }
+
+        barrier();
+
+        if (gl_SubgroupID == 0) {
+            bool active = gl_SubgroupInvocationID < gl_NumSubgroups;
+            uint subgroup_total = active ? shared_data[gl_SubgroupInvocationID] : 0u;
+            uint subgroup_offset = subgroupExclusiveAdd(subgroup_total);
+
+            if (active) {
+                shared_data[gl_SubgroupInvocationID] = subgroup_offset;
+            }
+
+            if (subgroupElect()) {
+                shared_total = subgroupAdd(subgroup_total);
+            }
+        }
+
+        barrier();
+        uint result = shared_data[gl_SubgroupID] + inclusive - value;
+        total = shared_total;
+    #else
+        shared_data[lid] = value;
+        barrier();
+
+        for (uint offset = 1; offset < WORKGROUP_SIZE; offset <<= 1) {
+            uint other = lid >= offset ? shared_data[lid - offset] : 0u;
+            barrier();
+            shared_data[lid] += other;
+            barrier();
+        }
+
+        uint result = shared_data[lid] - value;
+        total = shared_data[WORKGROUP_SIZE - 1];
+    #endif
+
+        // Make sure that the shared memory can be reused by the next call.
+        barrier();
+
+        return result;
+    }
+";
+
+const SCAN_BLOCKS_SOURCE: &str = r"
+    layout(set = 0, binding = 0) readonly buffer Input { uint input_data[]; };
+    layout(set = 0, binding = 1) writeonly buffer Output { uint output_data[]; };
+    layout(set = 0, binding = 2) writeonly buffer BlockSums { uint block_sums[]; };
+
+    layout(push_constant) uniform PushConstants { uint count; } pc;
+
+    void main() {
+        uint gid = gl_GlobalInvocationID.x;
+        uint value = gid < pc.count ? input_data[gid] : 0u;
+        uint total;
+        uint prefix = workgroup_exclusive_add(value, total);
+
+        if (gid < pc.count) {
+            output_data[gid] = prefix;
+        }
+
+        if (gl_LocalInvocationID.x == 0) {
+            block_sums[gl_WorkGroupID.x] = total;
+        }
+    }
+";
+
+const ADD_BLOCK_SUMS_SOURCE: &str = r"
+    layout(set = 0, binding = 0) buffer Data { uint data[]; };
+    layout(set = 0, binding = 1) readonly buffer BlockSums { uint block_sums[]; };
+
+    layout(push_constant) uniform PushConstants { uint count; } pc;
+
+    void main() {
+        uint gid = gl_GlobalInvocationID.x;
+
+        if (gid < pc.count) {
+            data[gid] += block_sums[gl_WorkGroupID.x];
+        }
+    }
+";
+
+const SEGMENTED_REDUCE_SOURCE: &str = r"
+    #if defined(OP_MIN)
+    #define COMBINE(a, b) min(a, b)
+    #define IDENTITY 0xFFFFFFFFu
+    #define SUBGROUP_REDUCE subgroupMin
+    #elif defined(OP_MAX)
+    #define COMBINE(a, b) max(a, b)
+    #define IDENTITY 0u
+    #define SUBGROUP_REDUCE subgroupMax
+    #else
+    #define COMBINE(a, b) ((a) + (b))
+    #define IDENTITY 0u
+    #define SUBGROUP_REDUCE subgroupAdd
+    #endif
+
+    layout(set = 0, binding = 0) readonly buffer Values { uint values[]; };
+    layout(set = 0, binding = 1) readonly buffer SegmentOffsets { uint segment_offsets[]; };
+    layout(set = 0, binding = 2) writeonly buffer Output { uint output_data[]; };
+
+    layout(push_constant) uniform PushConstants {
+        uint value_count;
+        uint segment_count;
+    } pc;
+
+    uint workgroup_reduce(uint value) {
+    #ifdef USE_SUBGROUPS
+        uint reduced = SUBGROUP_REDUCE(value);
+
+        if (subgroupElect()) {
+            shared_data[gl_SubgroupID] = reduced;
+        }
+
+        barrier();
+        uint result = IDENTITY;
+
+        for (uint i = 0; i < gl_NumSubgroups; i++) {
+            result = COMBINE(result, shared_data[i]);
+        }
+    #else
+        uint lid = gl_LocalInvocationID.x;
+        shared_data[lid] = value;
+        barrier();
+
+        for (uint stride = WORKGROUP_SIZE / 2; stride > 0; stride >>= 1) {
+            if (lid < stride) {
+                shared_data[lid] = COMBINE(shared_data[lid], shared_data[lid + stride]);
+            }
+
+            barrier();
+        }
+
+        uint result = shared_data[0];
+    #endif
+
+        barrier();
+
+        return result;
+    }
+
+    void main() {
+        // The segment index is uniform across the workgroup, so the barriers are reached by all
+        // invocations.
+        for (uint segment = gl_WorkGroupID.x; segment < pc.segment_count;
+             segment += gl_NumWorkGroups.x)
+        {
+            uint start = segment_offsets[segment];
+            uint end = segment + 1 < pc.segment_count
+                ? segment_offsets[segment + 1]
+                : pc.value_count;
+            end = min(end, pc.value_count);
+            uint value = IDENTITY;
+
+            for (uint i = start + gl_LocalInvocationID.x; i < end; i += WORKGROUP_SIZE) {
+                value = COMBINE(value, values[i]);
+            }
+
+            uint result = workgroup_reduce(value);
+
+            if (gl_LocalInvocationID.x == 0) {
+                output_data[segment] = result;
+            }
+        }
+    }
+";
+
+const RADIX_HISTOGRAM_SOURCE: &str = r"
+    layout(set = 0, binding = 0) readonly buffer Keys { uint keys[]; };
+    layout(set = 0, binding = 1) writeonly buffer Histogram { uint histogram[]; };
+
+    layout(push_constant) uniform PushConstants {
+        uint count;
+        uint shift;
+    } pc;
+
+    shared uint digit_counts[RADIX];
+
+    void main() {
+        uint gid = gl_GlobalInvocationID.x;
+        uint lid = gl_LocalInvocationID.x;
+
+        if (lid < RADIX) {
+            digit_counts[lid] = 0;
+        }
+
+        barrier();
+
+        if (gid < pc.count) {
+            atomicAdd(digit_counts[(keys[gid] >> pc.shift) & (RADIX - 1)], 1u);
+        }
+
+        barrier();
+
+        // Digit-major, so that the exclusive scan of the histogram gives the destination of the
+        // first element with each digit in each workgroup.
+        if (lid < RADIX) {
+            histogram[lid * gl_NumWorkGroups.x + gl_WorkGroupID.x] = digit_counts[lid];
+        }
+    }
+";
+
+const RADIX_SCATTER_SOURCE: &str = r"
+    layout(set = 0, binding = 0) readonly buffer KeysIn { uint keys_in[]; };
+    layout(set = 0, binding = 1) readonly buffer ValuesIn { uint values_in[]; };
+    layout(set = 0, binding = 2) writeonly buffer KeysOut { uint keys_out[]; };
+    layout(set = 0, binding = 3) writeonly buffer ValuesOut { uint values_out[]; };
+    layout(set = 0, binding = 4) readonly buffer Offsets { uint offsets[]; };
+
+    layout(push_constant) uniform PushConstants {
+        uint count;
+        uint shift;
+    } pc;
+
+    void main() {
+        uint gid = gl_GlobalInvocationID.x;
+        bool valid = gid < pc.count;
+        uint key = valid ? keys_in[gid] : 0u;
+        uint digit = (key >> pc.shift) & (RADIX - 1);
+        uint rank = 0;
+
+        // Ranking each digit separately keeps the sort stable: elements with the same digit keep
+        // their order within the workgroup.
+        for (uint d = 0; d < RADIX; d++) {
+            uint total;
+            uint prefix = workgroup_exclusive_add(valid && digit == d ? 1u : 0u, total);
+
+            if (digit == d) {
+                rank = prefix;
+            }
+        }
+
+        if (valid) {
+            uint dst = offsets[digit * gl_NumWorkGroups.x + gl_WorkGroupID.x] + rank;
+            keys_out[dst] = key;
+            values_out[dst] = values_in[gid];
+        }
+    }
+";
+
+/// The operation that [`GpuPrimitives::segmented_reduce`] combines the values with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReduceOp {
+    /// The sum of the values, wrapping on overflow. The result of an empty segment is `0`.
+    Add,
+
+    /// The minimum of the values. The result of an empty segment is `u32::MAX`.
+    Min,
+
+    /// The maximum of the values. The result of an empty segment is `0`.
+    Max,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Kernel {
+    ScanBlocks,
+    AddBlockSums,
+    SegmentedReduce(ReduceOp),
+    RadixHistogram,
+    RadixScatter,
+}
+
+/// Records parallel primitives into command buffers.
+///
+/// See [the module-level documentation] for more details.
+///
+/// [the module-level documentation]: self
+#[derive(Debug)]
+pub struct GpuPrimitives {
+    memory_allocator: Arc<dyn MemoryAllocator>,
+    descriptor_set_allocator: Arc<dyn DescriptorSetAllocator>,
+    workgroup_size: u32,
+    use_subgroups: bool,
+    pipelines: Mutex<HashMap<Kernel, Arc<ComputePipeline>>>,
+}
+
+impl GpuPrimitives {
+    /// Creates a new `GpuPrimitives`.
+    ///
+    /// `memory_allocator` is used to allocate temporary buffers, and `descriptor_set_allocator`
+    /// to allocate the descriptor sets of the kernels.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `memory_allocator` and `descriptor_set_allocator` don't belong to the same
+    ///   device.
+    pub fn new(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        descriptor_set_allocator: Arc<dyn DescriptorSetAllocator>,
+    ) -> Self {
+        assert_eq!(memory_allocator.device(), descriptor_set_allocator.device());
+
+        let (workgroup_size, use_subgroups) = kernel_config(memory_allocator.device());
+
+        GpuPrimitives {
+            memory_allocator,
+            descriptor_set_allocator,
+            workgroup_size,
+            use_subgroups,
+            pipelines: Mutex::default(),
+        }
+    }
+
+    /// Returns the number of invocations in each workgroup of the kernels.
+    #[inline]
+    pub fn workgroup_size(&self) -> u32 {
+        self.workgroup_size
+    }
+
+    /// Returns whether the kernels use subgroup operations.
+    #[inline]
+    pub fn uses_subgroups(&self) -> bool {
+        self.use_subgroups
+    }
+
+    /// Records an exclusive prefix sum of `input` into `output`: each element of `output` is set
+    /// to the sum of all elements of `input` before it, wrapping on overflow.
+    ///
+    /// `input` and `output` can be the same buffer.
+    pub fn exclusive_scan(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        input: Subbuffer<[u32]>,
+        output: Subbuffer<[u32]>,
+    ) -> Result<(), PrimitiveError> {
+        if input.len() != output.len() {
+            return Err(Box::new(ValidationError {
+                context: "output".into(),
+                problem: "does not have the same length as `input`".into(),
+                ..Default::default()
+            })
+            .into());
+        }
+
+        if input.len() == 0 {
+            return Ok(());
+        }
+
+        self.record_scan(builder, input, output)
+    }
+
+    fn record_scan(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        input: Subbuffer<[u32]>,
+        output: Subbuffer<[u32]>,
+    ) -> Result<(), PrimitiveError> {
+        let count = input.len() as u32;
+        let group_count = count.div_ceil(self.workgroup_size);
+        let block_sums = self.temporary_buffer(group_count)?;
+
+        self.dispatch(
+            builder,
+            Kernel::ScanBlocks,
+            [
+                WriteDescriptorSet::buffer(0, input),
+                WriteDescriptorSet::buffer(1, output.clone()),
+                WriteDescriptorSet::buffer(2, block_sums.clone()),
+            ],
+            [count],
+            group_count,
+        )?;
+
+        if group_count > 1 {
+            // Scanning the sums of the blocks gives the offset to add to each block.
+            let block_offsets = self.temporary_buffer(group_count)?;
+            self.record_scan(builder, block_sums, block_offsets.clone())?;
+
+            self.dispatch(
+                builder,
+                Kernel::AddBlockSums,
+                [
+                    WriteDescriptorSet::buffer(0, output),
+                    WriteDescriptorSet::buffer(1, block_offsets),
+                ],
+                [count],
+                group_count,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Records a reduction of each segment of `values` with `op`, writing the result of segment
+    /// `i` to `output[i]`.
+    ///
+    /// `segment_offsets` contains the index of the first value of each segment, in increasing
+    /// order. Each segment ends where the next one begins, and the last segment ends at the end
+    /// of `values`. `output` must have the same length as `segment_offsets`.
+    pub fn segmented_reduce(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        values: Subbuffer<[u32]>,
+        segment_offsets: Subbuffer<[u32]>,
+        output: Subbuffer<[u32]>,
+        op: ReduceOp,
+    ) -> Result<(), PrimitiveError> {
+        if segment_offsets.len() != output.len() {
+            return Err(Box::new(ValidationError {
+                context: "output".into(),
+                problem: "does not have the same length as `segment_offsets`".into(),
+                ..Default::default()
+            })
+            .into());
+        }
+
+        if segment_offsets.len() == 0 {
+            return Ok(());
+        }
+
+        let value_count = values.len() as u32;
+        let segment_count = segment_offsets.len() as u32;
+        let max_group_count = self
+            .device()
+            .physical_device()
+            .properties()
+            .max_compute_work_group_count[0];
+
+        self.dispatch(
+            builder,
+            Kernel::SegmentedReduce(op),
+            [
+                WriteDescriptorSet::buffer(0, values),
+                WriteDescriptorSet::buffer(1, segment_offsets),
+                WriteDescriptorSet::buffer(2, output),
+            ],
+            [value_count, segment_count],
+            segment_count.min(max_group_count),
+        )
+    }
+
+    /// Records a stable sort of `keys` in increasing order, reordering `values` along with them.
+    ///
+    /// This is a least-significant-digit radix sort, that sorts by 4 bits of the keys in each of
+    /// its 8 passes. `keys` and `values` must have the same length.
+    pub fn radix_sort_by_key(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        keys: Subbuffer<[u32]>,
+        values: Subbuffer<[u32]>,
+    ) -> Result<(), PrimitiveError> {
+        if keys.len() != values.len() {
+            return Err(Box::new(ValidationError {
+                context: "values".into(),
+                problem: "does not have the same length as `keys`".into(),
+                ..Default::default()
+            })
+            .into());
+        }
+
+        if keys.len() <= 1 {
+            return Ok(());
+        }
+
+        let count = keys.len() as u32;
+        let group_count = count.div_ceil(self.workgroup_size);
+        let histogram = self.temporary_buffer(RADIX * group_count)?;
+        let offsets = self.temporary_buffer(RADIX * group_count)?;
+        let mut src = (keys, values);
+        let mut dst = (self.temporary_buffer(count)?, self.temporary_buffer(count)?);
+
+        // There is an even number of passes, so the result ends up in the original buffers.
+        for pass in 0..u32::BITS / RADIX_BITS {
+            let shift = pass * RADIX_BITS;
+
+            self.dispatch(
+                builder,
+                Kernel::RadixHistogram,
+                [
+                    WriteDescriptorSet::buffer(0, src.0.clone()),
+                    WriteDescriptorSet::buffer(1, histogram.clone()),
+                ],
+                [count, shift],
+                group_count,
+            )?;
+            self.record_scan(builder, histogram.clone(), offsets.clone())?;
+            self.dispatch(
+                builder,
+                Kernel::RadixScatter,
+                [
+                    WriteDescriptorSet::buffer(0, src.0.clone()),
+                    WriteDescriptorSet::buffer(1, src.1.clone()),
+                    WriteDescriptorSet::buffer(2, dst.0.clone()),
+                    WriteDescriptorSet::buffer(3, dst.1.clone()),
+                    WriteDescriptorSet::buffer(4, offsets.clone()),
+                ],
+                [count, shift],
+                group_count,
+            )?;
+
+            mem::swap(&mut src, &mut dst);
+        }
+
+        Ok(())
+    }
+
+    fn temporary_buffer(&self, len: u32) -> Result<Subbuffer<[u32]>, PrimitiveError> {
+        Buffer::new_slice(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::STORAGE_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                ..Default::default()
+            },
+            DeviceSize::from(len),
+        )
+        .map_err(PrimitiveError::Allocation)
+    }
+
+    fn dispatch<const N: usize>(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        kernel: Kernel,
+        descriptor_writes: impl IntoIterator<Item = WriteDescriptorSet>,
+        push_constants: [u32; N],
+        group_count: u32,
+    ) -> Result<(), PrimitiveError> {
+        let pipeline = self.pipeline(kernel)?;
+        let layout = pipeline.layout().clone();
+        let descriptor_set = DescriptorSet::new(
+            self.descriptor_set_allocator.clone(),
+            layout.set_layouts()[0].clone(),
+            descriptor_writes,
+            [],
+        )?;
+
+        builder
+            .bind_pipeline_compute(pipeline)?
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                layout.clone(),
+                0,
+                descriptor_set,
+            )?
+            .push_constants(layout, 0, push_constants)?;
+
+        // SAFETY: The kernels check all buffer accesses against the lengths given in the push
+        // constants, which are the lengths of the buffers.
+        unsafe { builder.dispatch([group_count, 1, 1]) }?;
+
+        Ok(())
+    }
+
+    fn pipeline(&self, kernel: Kernel) -> Result<Arc<ComputePipeline>, PrimitiveError> {
+        let mut pipelines = self.pipelines.lock().unwrap();
+
+        if let Some(pipeline) = pipelines.get(&kernel) {
+            return Ok(pipeline.clone());
+        }
+
+        let (name, source) = match kernel {
+            Kernel::ScanBlocks => ("scan_blocks.comp", SCAN_BLOCKS_SOURCE),
+            Kernel::AddBlockSums => ("add_block_sums.comp", ADD_BLOCK_SUMS_SOURCE),
+            Kernel::SegmentedReduce(_) => ("segmented_reduce.comp", SEGMENTED_REDUCE_SOURCE),
+            Kernel::RadixHistogram => ("radix_histogram.comp", RADIX_HISTOGRAM_SOURCE),
+            Kernel::RadixScatter => ("radix_scatter.comp", RADIX_SCATTER_SOURCE),
+        };
+        let mut macro_defines = vec![
+            (
+                "WORKGROUP_SIZE".to_owned(),
+                Some(self.workgroup_size.to_string()),
+            ),
+            ("RADIX".to_owned(), Some(RADIX.to_string())),
+        ];
+
+        if self.use_subgroups {
+            macro_defines.push(("USE_SUBGROUPS".to_owned(), None));
+        }
+
+        match kernel {
+            Kernel::SegmentedReduce(ReduceOp::Min) => {
+                macro_defines.push(("OP_MIN".to_owned(), None));
+            }
+            Kernel::SegmentedReduce(ReduceOp::Max) => {
+                macro_defines.push(("OP_MAX".to_owned(), None));
+            }
+            _ => (),
+        }
+
+        let device = self.device();
+        let module = compile_glsl(
+            device.clone(),
+            &format!("{COMMON_SOURCE}{source}"),
+            &GlslCompileInfo {
+                source_name: name.to_owned(),
+                macro_defines,
+                ..GlslCompileInfo::new(ShaderStage::Compute)
+            },
+        )?;
+        let stage = PipelineShaderStageCreateInfo::new(module.entry_point("main").unwrap());
+        let layout = PipelineLayout::new(
+            device.clone(),
+            PipelineDescriptorSetLayoutCreateInfo::from_stages([&stage])
+                .into_pipeline_layout_create_info(device.clone())
+                .map_err(|err| err.error)?,
+        )?;
+        let pipeline = ComputePipeline::new(
+            device.clone(),
+            None,
+            ComputePipelineCreateInfo::stage_layout(stage, layout),
+        )?;
+        pipelines.insert(kernel, pipeline.clone());
+
+        Ok(pipeline)
+    }
+}
+
+unsafe impl DeviceOwned for GpuPrimitives {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.memory_allocator.device()
+    }
+}
+
+/// Returns the workgroup size of the kernels, and whether they can use subgroup operations.
+fn kernel_config(device: &Device) -> (u32, bool) {
+    let properties = device.physical_device().properties();
+    let max_invocations = properties
+        .max_compute_work_group_invocations
+        .min(properties.max_compute_work_group_size[0])
+        .min(256);
+    // The largest power of two that fits. The minimum limit is 128, so this is at least `RADIX`.
+    let mut workgroup_size = 1 << (u32::BITS - 1 - max_invocations.leading_zeros());
+
+    let subgroup_size = properties.subgroup_size.unwrap_or(0);
+    let use_subgroups = device.api_version() >= Version::V1_1
+        && properties
+            .subgroup_supported_stages
+            .map_or(false, |stages| stages.intersects(ShaderStages::COMPUTE))
+        && properties
+            .subgroup_supported_operations
+            .map_or(false, |operations| {
+                operations.contains(SubgroupFeatures::BASIC | SubgroupFeatures::ARITHMETIC)
+            })
+        && subgroup_size >= 4;
+
+    if use_subgroups {
+        // The scan of the subgroup totals is done by a single subgroup.
+        workgroup_size = workgroup_size.min(subgroup_size * subgroup_size);
+    }
+
+    (workgroup_size, use_subgroups)
+}
+
+/// Error that can happen when recording a primitive with [`GpuPrimitives`].
+#[derive(Clone, Debug)]
+pub enum PrimitiveError {
+    /// The arguments are not valid, or recording a command failed validation.
+    ValidationError(Box<ValidationError>),
+
+    /// Compiling a kernel failed.
+    ShaderCompile(ShaderCompileError),
+
+    /// Allocating a temporary buffer failed.
+    Allocation(Validated<AllocateBufferError>),
+
+    /// Creating an object for a kernel failed.
+    VulkanError(Validated<VulkanError>),
+}
+
+impl Error for PrimitiveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ValidationError(err) => Some(err),
+            Self::ShaderCompile(err) => Some(err),
+            Self::Allocation(err) => Some(err),
+            Self::VulkanError(err) => Some(err),
+        }
+    }
+}
+
+impl Display for PrimitiveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::ValidationError(_) => write!(f, "the primitive is not valid"),
+            Self::ShaderCompile(_) => write!(f, "failed to compile a kernel"),
+            Self::Allocation(_) => write!(f, "failed to allocate a temporary buffer"),
+            Self::VulkanError(_) => write!(f, "failed to create an object for a kernel"),
+        }
+    }
+}
+
+impl From<Box<ValidationError>> for PrimitiveError {
+    fn from(err: Box<ValidationError>) -> Self {
+        Self::ValidationError(err)
+    }
+}
+
+impl From<ShaderCompileError> for PrimitiveError {
+    fn from(err: ShaderCompileError) -> Self {
+        Self::ShaderCompile(err)
+    }
+}
+
+impl From<Validated<VulkanError>> for PrimitiveError {
+    fn from(err: Validated<VulkanError>) -> Self {
+        Self::VulkanError(err)
+    }
+}
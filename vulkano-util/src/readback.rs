@@ -0,0 +1,304 @@
+//! Reading data back from the device without blocking the thread that renders.
+//!
+//! Picking, exposure adjustment based on a histogram and screenshots all need data that the
+//! device produced, but waiting for the device to finish stalls the thread that records the
+//! frames. [`AsyncReadback`] records a copy to a host-visible buffer into a command buffer that
+//! the application submits itself, and has a background thread wait for the fence of the
+//! submission. Once the fence is signaled, the data is delivered to a callback or sent through a
+//! channel.
+//!
+//! ```no_run
+//! # use vulkano::{command_buffer::RecordingCommandBuffer, image::Image};
+//! # use vulkano_util::context::VulkanoContext;
+//! use std::sync::Arc;
+//! use vulkano::sync::GpuFuture;
+//! use vulkano_util::readback::AsyncReadback;
+//!
+//! # let context: VulkanoContext = todo!();
+//! # let mut builder: RecordingCommandBuffer = todo!();
+//! # let image: Arc<Image> = todo!();
+//! let async_readback = AsyncReadback::new(context.memory_allocator().clone());
+//!
+//! // After recording the commands that write to `image`:
+//! let readback = async_readback.copy_image(&mut builder, image).unwrap();
+//! let future = builder
+//!     .end()
+//!     .unwrap()
+//!     .execute(context.graphics_queue().clone())
+//!     .unwrap()
+//!     .then_signal_fence_and_flush()
+//!     .unwrap();
+//! let receiver = async_readback.channel(readback, Arc::new(future));
+//!
+//! // In a later frame:
+//! if let Ok(result) = receiver.try_recv() {
+//!     let pixels = result.unwrap();
+//! }
+//! ```
+
+use crate::headless::ReadbackError;
+use std::{
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+use vulkano::{
+    buffer::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::{CopyBufferInfo, CopyImageToBufferInfo, RecordingCommandBuffer},
+    image::Image,
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
+    sync::future::{FenceSignalFuture, GpuFuture},
+    DeviceSize, Validated,
+};
+
+/// How often the background thread checks whether the fences of pending readbacks have been
+/// signaled.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Records copies to host-visible buffers, and delivers their contents from a background thread
+/// once the device has finished writing them.
+///
+/// Dropping the `AsyncReadback` blocks until all pending readbacks have been delivered.
+#[derive(Debug)]
+pub struct AsyncReadback {
+    memory_allocator: Arc<dyn MemoryAllocator>,
+    sender: Option<Sender<Box<dyn Deliver>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AsyncReadback {
+    /// Creates a new `AsyncReadback` that allocates its readback buffers from
+    /// `memory_allocator`, and starts its background thread.
+    pub fn new(memory_allocator: Arc<dyn MemoryAllocator>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("vulkano-util readback".into())
+            .spawn(move || run(receiver))
+            .expect("failed to spawn the readback thread");
+
+        AsyncReadback {
+            memory_allocator,
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    /// Records a copy of `src` to a new host-visible buffer into `builder`.
+    ///
+    /// `src` must have been created with the [`TRANSFER_SRC`] usage.
+    ///
+    /// [`TRANSFER_SRC`]: BufferUsage::TRANSFER_SRC
+    pub fn copy_buffer<T>(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        src: Subbuffer<T>,
+    ) -> Result<Readback, ReadbackError>
+    where
+        T: ?Sized,
+    {
+        let buffer = self.readback_buffer(src.size())?;
+        builder
+            .copy_buffer(CopyBufferInfo::buffers(src, buffer.clone()))
+            .map_err(ReadbackError::Copy)?;
+
+        Ok(Readback { buffer })
+    }
+
+    /// Records a copy of all array layers of the first mip level of `src` to a new host-visible
+    /// buffer into `builder`. The texel data is tightly packed.
+    ///
+    /// `src` must have been created with the [`TRANSFER_SRC`] usage, and must have a single
+    /// aspect: color formats, or depth-only or stencil-only formats.
+    ///
+    /// [`TRANSFER_SRC`]: vulkano::image::ImageUsage::TRANSFER_SRC
+    pub fn copy_image(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        src: Arc<Image>,
+    ) -> Result<Readback, ReadbackError> {
+        let buffer = self.readback_buffer(image_data_size(&src))?;
+        builder
+            .copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(src, buffer.clone()))
+            .map_err(ReadbackError::Copy)?;
+
+        Ok(Readback { buffer })
+    }
+
+    fn readback_buffer(&self, size: DeviceSize) -> Result<Subbuffer<[u8]>, ReadbackError> {
+        Buffer::new_slice::<u8>(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+                ..Default::default()
+            },
+            size,
+        )
+        .map_err(ReadbackError::Allocation)
+    }
+
+    /// Calls `callback` from the background thread with the contents of `readback`, once the
+    /// fence of `future` has been signaled.
+    ///
+    /// `future` must include the execution of the command buffer that the copy of `readback` was
+    /// recorded into, and must be flushed for its fence to ever be signaled. The callback should
+    /// return quickly, as it delays the delivery of the other readbacks.
+    pub fn on_complete<F>(
+        &self,
+        readback: Readback,
+        future: Arc<FenceSignalFuture<F>>,
+        callback: impl FnOnce(Result<&[u8], ReadbackError>) + Send + 'static,
+    ) where
+        F: GpuFuture + Send + 'static,
+    {
+        // The thread only stops once `self` is dropped.
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Box::new(PendingReadback {
+                buffer: readback.buffer,
+                future,
+                callback: Some(callback),
+            }))
+            .unwrap();
+    }
+
+    /// Returns a receiver that the contents of `readback` are sent to, once the fence of `future`
+    /// has been signaled.
+    ///
+    /// The requirements of [`on_complete`](Self::on_complete) apply. Use [`Receiver::try_recv`] to
+    /// check for the data without blocking.
+    pub fn channel<F>(
+        &self,
+        readback: Readback,
+        future: Arc<FenceSignalFuture<F>>,
+    ) -> Receiver<Result<Vec<u8>, ReadbackError>>
+    where
+        F: GpuFuture + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        self.on_complete(readback, future, move |result| {
+            // The application may have dropped the receiver if it's no longer interested.
+            let _ = sender.send(result.map(<[u8]>::to_vec));
+        });
+
+        receiver
+    }
+}
+
+impl Drop for AsyncReadback {
+    fn drop(&mut self) {
+        // Disconnecting the channel makes the thread stop once it has delivered everything.
+        self.sender = None;
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A copy to a host-visible buffer that was recorded with [`AsyncReadback`], whose contents
+/// haven't been delivered yet.
+#[derive(Debug)]
+#[must_use = "the readback must be passed to `AsyncReadback::on_complete` or \
+    `AsyncReadback::channel` to receive its contents"]
+pub struct Readback {
+    buffer: Subbuffer<[u8]>,
+}
+
+impl Readback {
+    /// Returns the buffer that is copied to.
+    #[inline]
+    pub fn buffer(&self) -> &Subbuffer<[u8]> {
+        &self.buffer
+    }
+}
+
+/// Returns the size of the tightly packed texel data of all array layers of the first mip level
+/// of `image`.
+pub(crate) fn image_data_size(image: &Image) -> DeviceSize {
+    let format = image.format();
+    let extent = image.extent();
+    let block_extent = format.block_extent();
+
+    (0..3)
+        .map(|i| DeviceSize::from(extent[i].div_ceil(block_extent[i])))
+        .product::<DeviceSize>()
+        * DeviceSize::from(image.array_layers())
+        * format.block_size()
+}
+
+fn run(receiver: Receiver<Box<dyn Deliver>>) {
+    let mut pending: Vec<Box<dyn Deliver>> = Vec::new();
+    let mut disconnected = false;
+
+    while !(disconnected && pending.is_empty()) {
+        if disconnected {
+            thread::sleep(POLL_INTERVAL);
+        } else if pending.is_empty() {
+            // There is nothing to poll, so there's no need to wake up until a readback arrives.
+            match receiver.recv() {
+                Ok(readback) => pending.push(readback),
+                Err(_) => disconnected = true,
+            }
+        } else {
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(readback) => pending.push(readback),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => disconnected = true,
+            }
+        }
+
+        pending.retain_mut(|readback| !readback.deliver());
+    }
+}
+
+// A readback that the background thread can deliver.
+trait Deliver: Send {
+    // Delivers the readback if its fence has been signaled, and returns whether it has.
+    fn deliver(&mut self) -> bool;
+}
+
+struct PendingReadback<F, C>
+where
+    F: GpuFuture,
+{
+    buffer: Subbuffer<[u8]>,
+    future: Arc<FenceSignalFuture<F>>,
+    callback: Option<C>,
+}
+
+impl<F, C> Deliver for PendingReadback<F, C>
+where
+    F: GpuFuture + Send,
+    C: FnOnce(Result<&[u8], ReadbackError>) + Send,
+{
+    fn deliver(&mut self) -> bool {
+        match self.future.is_signaled() {
+            Ok(false) => false,
+            Ok(true) => {
+                let callback = self.callback.take().unwrap();
+
+                match self.buffer.read() {
+                    Ok(data) => callback(Ok(&*data)),
+                    Err(err) => callback(Err(ReadbackError::Read(err))),
+                }
+
+                true
+            }
+            Err(err) => {
+                let callback = self.callback.take().unwrap();
+                callback(Err(ReadbackError::Submission(Validated::Error(err))));
+
+                true
+            }
+        }
+    }
+}
@@ -1,12 +1,23 @@
-use crate::{context::VulkanoContext, window::WindowDescriptor};
+use crate::{
+    context::VulkanoContext, surface_format::choose_surface_format, window::WindowDescriptor,
+};
 use ahash::HashMap;
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use vulkano::{
-    device::{Device, Queue},
+    device::{Device, DeviceOwned, Queue},
     format::Format,
-    image::{view::ImageView, Image, ImageCreateInfo, ImageType, ImageUsage},
+    image::{
+        view::{ImageView, ImageViewCreateInfo},
+        Image, ImageCreateInfo, ImageType, ImageUsage,
+    },
     memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
-    swapchain::{self, PresentMode, Surface, Swapchain, SwapchainCreateInfo, SwapchainPresentInfo},
+    swapchain::{
+        self, PresentGravity, PresentMode, PresentScaling, Surface, SurfaceInfo, Swapchain,
+        SwapchainAcquireFuture, SwapchainCreateInfo, SwapchainPresentInfo,
+    },
     sync::{self, GpuFuture},
     Validated, VulkanError,
 };
@@ -36,6 +47,10 @@ pub struct VulkanoWindowRenderer {
     previous_frame_end: Option<Box<dyn GpuFuture>>,
     image_index: u32,
     present_mode: PresentMode,
+    present_scaling: Option<PresentScaling>,
+    present_gravity: Option<[PresentGravity; 2]>,
+    frame_interval: Option<Duration>,
+    last_frame_start: Option<Instant>,
 }
 
 impl VulkanoWindowRenderer {
@@ -72,6 +87,10 @@ impl VulkanoWindowRenderer {
             previous_frame_end,
             image_index: 0,
             present_mode: descriptor.present_mode,
+            present_scaling: descriptor.present_scaling,
+            present_gravity: descriptor.present_gravity,
+            frame_interval: descriptor.frame_interval,
+            last_frame_start: None,
         }
     }
 
@@ -88,15 +107,18 @@ impl VulkanoWindowRenderer {
             .physical_device()
             .surface_capabilities(&surface, Default::default())
             .unwrap();
-        let image_format = device
-            .physical_device()
-            .surface_formats(&surface, Default::default())
-            .unwrap()[0]
-            .0;
+        let surface_format = choose_surface_format(
+            device.physical_device(),
+            &surface,
+            &[],
+            ImageUsage::COLOR_ATTACHMENT,
+        )
+        .unwrap();
         let (swapchain, images) = Swapchain::new(device, surface, {
             let mut create_info = SwapchainCreateInfo {
                 min_image_count: surface_capabilities.min_image_count.max(2),
-                image_format,
+                image_format: surface_format.format,
+                image_color_space: surface_format.color_space,
                 image_extent: window.inner_size().into(),
                 image_usage: ImageUsage::COLOR_ATTACHMENT,
                 composite_alpha: surface_capabilities
@@ -108,6 +130,13 @@ impl VulkanoWindowRenderer {
             };
             // Get present mode from window descriptor
             create_info.present_mode = window_descriptor.present_mode;
+            Self::set_present_scaling(
+                &device,
+                &surface,
+                window_descriptor.present_scaling,
+                window_descriptor.present_gravity,
+                &mut create_info,
+            );
             swapchain_create_info_modify(&mut create_info);
             create_info
         })
@@ -120,6 +149,52 @@ impl VulkanoWindowRenderer {
         (swapchain, images)
     }
 
+    /// Sets `present_scaling` and `present_gravity` in `create_info`, if they are supported.
+    fn set_present_scaling(
+        device: &Arc<Device>,
+        surface: &Surface,
+        present_scaling: Option<PresentScaling>,
+        present_gravity: Option<[PresentGravity; 2]>,
+        create_info: &mut SwapchainCreateInfo,
+    ) {
+        if present_scaling.is_none() && present_gravity.is_none() {
+            return;
+        }
+
+        if !(device.enabled_extensions().ext_swapchain_maintenance1
+            && device
+                .instance()
+                .enabled_extensions()
+                .ext_surface_maintenance1)
+        {
+            return;
+        }
+
+        // The supported scaling depends on the present mode.
+        let surface_capabilities = match device.physical_device().surface_capabilities(
+            surface,
+            SurfaceInfo {
+                present_mode: Some(create_info.present_mode),
+                ..Default::default()
+            },
+        ) {
+            Ok(surface_capabilities) => surface_capabilities,
+            Err(_) => return,
+        };
+
+        create_info.scaling_behavior = present_scaling.filter(|&scaling| {
+            surface_capabilities
+                .supported_present_scaling
+                .contains_enum(scaling)
+        });
+        create_info.present_gravity = present_gravity.filter(|gravity| {
+            gravity
+                .iter()
+                .zip(surface_capabilities.supported_present_gravity)
+                .all(|(&gravity, supported)| supported.contains_enum(gravity))
+        });
+    }
+
     /// Set window renderer present mode. This triggers a swapchain recreation.
     #[inline]
     pub fn set_present_mode(&mut self, present_mode: PresentMode) {
@@ -129,6 +204,37 @@ impl VulkanoWindowRenderer {
         }
     }
 
+    /// Sets the minimum time between the starts of two frames of this window, or `None` to
+    /// render a frame whenever one is requested.
+    ///
+    /// This lets windows that don't change often, such as tool panels, be rendered less
+    /// frequently than the main window. [`VulkanoWindows::acquire_all`] skips windows whose next
+    /// frame is not due yet.
+    ///
+    /// [`VulkanoWindows::acquire_all`]: crate::window::VulkanoWindows::acquire_all
+    #[inline]
+    pub fn set_frame_interval(&mut self, frame_interval: Option<Duration>) {
+        self.frame_interval = frame_interval;
+    }
+
+    /// Returns the minimum time between the starts of two frames of this window.
+    #[inline]
+    pub fn frame_interval(&self) -> Option<Duration> {
+        self.frame_interval
+    }
+
+    /// Returns whether at least the frame interval has passed since the previous frame of this
+    /// window was acquired.
+    #[inline]
+    pub fn is_frame_due(&self) -> bool {
+        match (self.frame_interval, self.last_frame_start) {
+            (Some(frame_interval), Some(last_frame_start)) => {
+                last_frame_start.elapsed() >= frame_interval
+            }
+            _ => true,
+        }
+    }
+
     /// Return swapchain image format.
     #[inline]
     pub fn swapchain_format(&self) -> Format {
@@ -220,7 +326,25 @@ impl VulkanoWindowRenderer {
         self.recreate_swapchain = true;
     }
 
-    /// Add interim image view that resizes with window.
+    /// Returns a view of a single array layer of the current swapchain image.
+    ///
+    /// This is only useful if the swapchain was created with an `image_array_layers` value
+    /// greater than 1, for example for stereoscopic presentation, and each layer is rendered to
+    /// separately. To render to all layers at once using multiview, use
+    /// [`swapchain_image_view`](Self::swapchain_image_view) instead.
+    #[inline]
+    pub fn swapchain_image_layer_view(&self, array_layer: u32) -> Arc<ImageView> {
+        let image = self.final_views[self.image_index as usize].image();
+
+        ImageView::new(
+            image.clone(),
+            ImageViewCreateInfo::from_image_array_layer(image, array_layer),
+        )
+        .unwrap()
+    }
+
+    /// Add interim image view that resizes with window. The image has as many array layers as
+    /// the swapchain images.
     #[inline]
     pub fn add_additional_image_view(&mut self, key: usize, format: Format, usage: ImageUsage) {
         let final_view_image = self.final_views[0].image();
@@ -231,6 +355,7 @@ impl VulkanoWindowRenderer {
                     image_type: ImageType::Dim2d,
                     format,
                     extent: final_view_image.extent(),
+                    array_layers: final_view_image.array_layers(),
                     usage,
                     ..Default::default()
                 },
@@ -266,6 +391,18 @@ impl VulkanoWindowRenderer {
         timeout: Option<Duration>,
         on_recreate_swapchain: impl FnOnce(&[Arc<ImageView>]),
     ) -> Result<Box<dyn GpuFuture>, VulkanError> {
+        let acquire_future = self.acquire_image(timeout, on_recreate_swapchain)?;
+        let future = self.take_previous_frame_end().join(acquire_future);
+
+        Ok(future.boxed())
+    }
+
+    /// Acquires the next swapchain image, without waiting for the previous frame of this window.
+    pub(crate) fn acquire_image(
+        &mut self,
+        timeout: Option<Duration>,
+        on_recreate_swapchain: impl FnOnce(&[Arc<ImageView>]),
+    ) -> Result<SwapchainAcquireFuture, VulkanError> {
         // Recreate swap chain if needed (when resizing of window occurs or swapchain is outdated)
         // Also resize render views if needed
         if self.recreate_swapchain {
@@ -290,10 +427,22 @@ impl VulkanoWindowRenderer {
         }
         // Update our image index
         self.image_index = image_index;
+        self.last_frame_start = Some(Instant::now());
 
-        let future = self.previous_frame_end.take().unwrap().join(acquire_future);
+        Ok(acquire_future)
+    }
 
-        Ok(future.boxed())
+    /// Takes the future representing the end of the previous frame of this window, leaving one
+    /// that is already reached in its place.
+    pub(crate) fn take_previous_frame_end(&mut self) -> Box<dyn GpuFuture> {
+        self.previous_frame_end
+            .replace(sync::now(self.graphics_queue.device().clone()).boxed())
+            .unwrap()
+    }
+
+    /// Returns the information to present the current swapchain image.
+    pub(crate) fn swapchain_present_info(&self) -> SwapchainPresentInfo {
+        SwapchainPresentInfo::swapchain_image_index(self.swapchain.clone(), self.image_index)
     }
 
     /// Finishes rendering by presenting the swapchain. Pass your last future as an input to this
@@ -305,13 +454,7 @@ impl VulkanoWindowRenderer {
     #[inline]
     pub fn present(&mut self, after_future: Box<dyn GpuFuture>, wait_future: bool) {
         let future = after_future
-            .then_swapchain_present(
-                self.graphics_queue.clone(),
-                SwapchainPresentInfo::swapchain_image_index(
-                    self.swapchain.clone(),
-                    self.image_index,
-                ),
-            )
+            .then_swapchain_present(self.graphics_queue.clone(), self.swapchain_present_info())
             .then_signal_fence_and_flush();
         match future.map_err(Validated::unwrap) {
             Ok(mut future) => {
@@ -345,14 +488,27 @@ impl VulkanoWindowRenderer {
             return;
         }
 
+        let mut create_info = SwapchainCreateInfo {
+            image_extent,
+            // Use present mode from current state
+            present_mode: self.present_mode,
+            ..self.swapchain.create_info()
+        };
+
+        // The supported scaling depends on the present mode.
+        if self.present_mode != self.swapchain.present_mode() {
+            Self::set_present_scaling(
+                self.swapchain.device(),
+                self.swapchain.surface(),
+                self.present_scaling,
+                self.present_gravity,
+                &mut create_info,
+            );
+        }
+
         let (new_swapchain, new_images) = self
             .swapchain
-            .recreate(SwapchainCreateInfo {
-                image_extent,
-                // Use present mode from current state
-                present_mode: self.present_mode,
-                ..self.swapchain.create_info()
-            })
+            .recreate(create_info)
             .expect("failed to recreate swapchain");
 
         self.swapchain = new_swapchain;
@@ -0,0 +1,311 @@
+//! Runtime compilation of GLSL shaders.
+//!
+//! The `vulkano-shaders` crate compiles shaders while your crate is being built. That doesn't help
+//! when the shader source is only known at runtime, for example when shaders are authored by the
+//! user or loaded from data files. This module provides the runtime equivalent: GLSL source is
+//! compiled to SPIR-V with [shaderc] and then loaded with [`ShaderModule::new`], which performs
+//! the same reflection-driven validation as the modules created by the `shader!` macro, checking
+//! the SPIR-V version, capabilities and extensions used by the shader against the device.
+//!
+//! This module is only available when the `shaderc` feature of `vulkano-util` is enabled.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::{device::Device, shader::ShaderStage};
+//! use vulkano_util::shader::{compile_glsl, GlslCompileInfo};
+//!
+//! # let device: Arc<Device> = return;
+//! let source = r"
+//!     #version 450
+//!
+//!     layout(local_size_x = 64) in;
+//!     layout(set = 0, binding = 0) buffer Data { uint data[]; };
+//!
+//!     void main() {
+//!         data[gl_GlobalInvocationID.x] *= 2;
+//!     }
+//! ";
+//!
+//! let module = compile_glsl(
+//!     device.clone(),
+//!     source,
+//!     &GlslCompileInfo::new(ShaderStage::Compute),
+//! )
+//! .unwrap();
+//! let entry_point = module.entry_point("main").unwrap();
+//! ```
+//!
+//! [shaderc]: https://crates.io/crates/shaderc
+
+use shaderc::{
+    CompileOptions, Compiler, EnvVersion, IncludeType, ResolvedInclude, ShaderKind, SpirvVersion,
+    TargetEnv,
+};
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    fs,
+    path::PathBuf,
+    sync::Arc,
+};
+use vulkano::{
+    device::Device,
+    shader::{ShaderModule, ShaderModuleCreateInfo, ShaderStage},
+    Validated, Version, VulkanError,
+};
+
+/// Parameters to compile GLSL source code at runtime.
+#[derive(Clone, Debug)]
+pub struct GlslCompileInfo {
+    /// The stage that the shader is compiled for.
+    ///
+    /// There is no default value.
+    pub stage: ShaderStage,
+
+    /// The name of the entry point function in the source.
+    ///
+    /// The default value is `main`.
+    pub entry_point: String,
+
+    /// The name of the source, used in error messages and to resolve relative
+    /// `#include "..."` directives.
+    ///
+    /// The default value is `shader.glsl`.
+    pub source_name: String,
+
+    /// The directories to search for `#include <...>` directives.
+    ///
+    /// The default value is empty.
+    pub include_directories: Vec<PathBuf>,
+
+    /// Preprocessor macros to define, equivalent to passing `-DNAME=VALUE` on the command line.
+    ///
+    /// The default value is empty.
+    pub macro_defines: Vec<(String, Option<String>)>,
+
+    /// The Vulkan version to target. If `None`, the API version of the device is used.
+    ///
+    /// The default value is `None`.
+    pub vulkan_version: Option<Version>,
+
+    /// The SPIR-V version to generate. If `None`, the default version for `vulkan_version` is
+    /// used.
+    ///
+    /// The default value is `None`.
+    pub spirv_version: Option<Version>,
+
+    /// Whether to include debug information in the generated SPIR-V.
+    ///
+    /// The default value is `false`.
+    pub generate_debug_info: bool,
+}
+
+impl GlslCompileInfo {
+    /// Returns a `GlslCompileInfo` with the specified `stage`.
+    #[inline]
+    pub fn new(stage: ShaderStage) -> Self {
+        Self {
+            stage,
+            entry_point: "main".to_owned(),
+            source_name: "shader.glsl".to_owned(),
+            include_directories: Vec::new(),
+            macro_defines: Vec::new(),
+            vulkan_version: None,
+            spirv_version: None,
+            generate_debug_info: false,
+        }
+    }
+}
+
+/// Compiles GLSL `source` and creates a [`ShaderModule`] from the result.
+///
+/// The generated module is validated against `device` in the same way as modules loaded through
+/// the `shader!` macro.
+pub fn compile_glsl(
+    device: Arc<Device>,
+    source: &str,
+    compile_info: &GlslCompileInfo,
+) -> Result<Arc<ShaderModule>, ShaderCompileError> {
+    let vulkan_version = compile_info
+        .vulkan_version
+        .unwrap_or_else(|| device.api_version());
+    let words = compile_glsl_to_spirv(source, compile_info, vulkan_version)?;
+
+    // SAFETY: The code was generated by shaderc, which only produces valid SPIR-V.
+    unsafe { ShaderModule::new(device, ShaderModuleCreateInfo::new(&words)) }
+        .map_err(ShaderCompileError::ModuleCreation)
+}
+
+/// Compiles GLSL `source` into SPIR-V words, targeting `vulkan_version`.
+///
+/// If `compile_info.vulkan_version` is `Some`, it takes precedence over `vulkan_version`.
+pub fn compile_glsl_to_spirv(
+    source: &str,
+    compile_info: &GlslCompileInfo,
+    vulkan_version: Version,
+) -> Result<Vec<u32>, ShaderCompileError> {
+    let GlslCompileInfo {
+        stage,
+        ref entry_point,
+        ref source_name,
+        ref include_directories,
+        ref macro_defines,
+        vulkan_version: compile_vulkan_version,
+        spirv_version,
+        generate_debug_info,
+    } = *compile_info;
+
+    let shader_kind = shader_kind(stage)?;
+    let compiler = Compiler::new().ok_or(ShaderCompileError::CompilerUnavailable)?;
+    let mut compile_options =
+        CompileOptions::new().ok_or(ShaderCompileError::CompilerUnavailable)?;
+
+    let vulkan_version = compile_vulkan_version.unwrap_or(vulkan_version);
+    compile_options.set_target_env(TargetEnv::Vulkan, env_version(vulkan_version) as u32);
+
+    if let Some(spirv_version) = spirv_version {
+        compile_options.set_target_spirv(target_spirv(spirv_version)?);
+    }
+
+    compile_options.set_include_callback(
+        |requested_source_path, directive_type, contained_within_path, _recursion_depth| {
+            let resolved_path = match directive_type {
+                IncludeType::Relative => PathBuf::from(contained_within_path)
+                    .parent()
+                    .map(|parent| parent.join(requested_source_path))
+                    .filter(|path| path.is_file()),
+                IncludeType::Standard => include_directories
+                    .iter()
+                    .map(|directory| directory.join(requested_source_path))
+                    .find(|path| path.is_file()),
+            }
+            .ok_or_else(|| format!("failed to find the include file `{requested_source_path}`"))?;
+
+            let content = fs::read_to_string(&resolved_path).map_err(|err| {
+                format!("failed to read the include file `{resolved_path:?}`: {err}")
+            })?;
+
+            Ok(ResolvedInclude {
+                resolved_name: resolved_path.to_string_lossy().into_owned(),
+                content,
+            })
+        },
+    );
+
+    for (name, value) in macro_defines {
+        compile_options.add_macro_definition(name, value.as_deref());
+    }
+
+    if generate_debug_info {
+        compile_options.set_generate_debug_info();
+    }
+
+    let artifact = compiler
+        .compile_into_spirv(
+            source,
+            shader_kind,
+            source_name,
+            entry_point,
+            Some(&compile_options),
+        )
+        .map_err(|err| ShaderCompileError::Compilation(err.to_string()))?;
+
+    Ok(artifact.as_binary().to_owned())
+}
+
+fn shader_kind(stage: ShaderStage) -> Result<ShaderKind, ShaderCompileError> {
+    Ok(match stage {
+        ShaderStage::Vertex => ShaderKind::Vertex,
+        ShaderStage::TessellationControl => ShaderKind::TessControl,
+        ShaderStage::TessellationEvaluation => ShaderKind::TessEvaluation,
+        ShaderStage::Geometry => ShaderKind::Geometry,
+        ShaderStage::Fragment => ShaderKind::Fragment,
+        ShaderStage::Compute => ShaderKind::Compute,
+        ShaderStage::Raygen => ShaderKind::RayGeneration,
+        ShaderStage::AnyHit => ShaderKind::AnyHit,
+        ShaderStage::ClosestHit => ShaderKind::ClosestHit,
+        ShaderStage::Miss => ShaderKind::Miss,
+        ShaderStage::Intersection => ShaderKind::Intersection,
+        ShaderStage::Callable => ShaderKind::Callable,
+        ShaderStage::Task => ShaderKind::Task,
+        ShaderStage::Mesh => ShaderKind::Mesh,
+        stage => return Err(ShaderCompileError::UnsupportedStage(stage)),
+    })
+}
+
+fn env_version(version: Version) -> EnvVersion {
+    if version >= Version::V1_3 {
+        EnvVersion::Vulkan1_3
+    } else if version >= Version::V1_2 {
+        EnvVersion::Vulkan1_2
+    } else if version >= Version::V1_1 {
+        EnvVersion::Vulkan1_1
+    } else {
+        EnvVersion::Vulkan1_0
+    }
+}
+
+fn target_spirv(version: Version) -> Result<SpirvVersion, ShaderCompileError> {
+    Ok(match (version.major, version.minor) {
+        (1, 0) => SpirvVersion::V1_0,
+        (1, 1) => SpirvVersion::V1_1,
+        (1, 2) => SpirvVersion::V1_2,
+        (1, 3) => SpirvVersion::V1_3,
+        (1, 4) => SpirvVersion::V1_4,
+        (1, 5) => SpirvVersion::V1_5,
+        (1, 6) => SpirvVersion::V1_6,
+        _ => return Err(ShaderCompileError::UnsupportedSpirvVersion(version)),
+    })
+}
+
+/// Error that can happen when compiling a shader at runtime.
+#[derive(Clone, Debug)]
+pub enum ShaderCompileError {
+    /// The shader compiler could not be initialized.
+    CompilerUnavailable,
+
+    /// The shader stage is not supported by the compiler.
+    UnsupportedStage(ShaderStage),
+
+    /// The requested SPIR-V version is not supported by the compiler.
+    UnsupportedSpirvVersion(Version),
+
+    /// The source code failed to compile. Contains the messages from the compiler.
+    Compilation(String),
+
+    /// The compiled code could not be used to create a shader module on the device.
+    ModuleCreation(Validated<VulkanError>),
+}
+
+impl Error for ShaderCompileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ModuleCreation(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ShaderCompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::CompilerUnavailable => write!(f, "failed to initialize the shader compiler"),
+            Self::UnsupportedStage(stage) => {
+                write!(
+                    f,
+                    "the shader stage {stage:?} is not supported by the compiler"
+                )
+            }
+            Self::UnsupportedSpirvVersion(version) => {
+                write!(
+                    f,
+                    "SPIR-V version {version} is not supported by the compiler"
+                )
+            }
+            Self::Compilation(messages) => {
+                write!(f, "failed to compile the shader:\n{messages}")
+            }
+            Self::ModuleCreation(_) => write!(f, "failed to create the shader module"),
+        }
+    }
+}
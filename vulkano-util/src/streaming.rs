@@ -0,0 +1,503 @@
+//! Uploading data to buffers and images in the background, on a dedicated transfer queue.
+//!
+//! Streaming assets while rendering means copying a lot of data to device-local memory, and doing
+//! the copies on the graphics queue takes time away from rendering. [`TransferStreamer`] owns a
+//! separate queue, preferably from a queue family that only supports transfer operations, and a
+//! background thread that batches the upload requests it receives into command buffers and
+//! submits them to that queue.
+//!
+//! Each upload returns an [`UploadTicket`]. Once the upload has been submitted, the ticket gives
+//! out an [`UploadFuture`], which can be used like any other future: executing a command buffer
+//! on the graphics queue after it makes the graphics queue wait for the upload on the device,
+//! without blocking the thread that records the frame.
+//!
+//! ```no_run
+//! use vulkano::{
+//!     buffer::{Buffer, BufferCreateInfo, BufferUsage},
+//!     memory::allocator::{AllocationCreateInfo, MemoryTypeFilter},
+//!     sync::GpuFuture,
+//! };
+//! use vulkano_util::{
+//!     context::{VulkanoConfig, VulkanoContext},
+//!     streaming::TransferStreamer,
+//! };
+//!
+//! let context = VulkanoContext::new(VulkanoConfig::default());
+//! let streamer = TransferStreamer::new(
+//!     context.transfer_queue().clone(),
+//!     context.memory_allocator().clone(),
+//!     Default::default(),
+//! );
+//!
+//! let vertex_buffer = Buffer::new_slice::<u8>(
+//!     context.memory_allocator().clone(),
+//!     BufferCreateInfo {
+//!         usage: BufferUsage::VERTEX_BUFFER | BufferUsage::TRANSFER_DST,
+//!         ..Default::default()
+//!     },
+//!     AllocationCreateInfo {
+//!         memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+//!         ..Default::default()
+//!     },
+//!     1024,
+//! )
+//! .unwrap();
+//!
+//! let ticket = streamer.upload_buffer(vec![0; 1024], vertex_buffer);
+//!
+//! // Later, while rendering a frame that uses the vertex buffer:
+//! let upload = ticket.wait().unwrap();
+//! # let command_buffer: std::sync::Arc<vulkano::command_buffer::CommandBuffer> = todo!();
+//! let future = upload
+//!     .then_execute(context.graphics_queue().clone(), command_buffer)
+//!     .unwrap();
+//! ```
+//!
+//! The uploads are submitted on a queue that is usually from a different queue family than the
+//! queues that use the uploaded data. Buffers and images that are uploaded to must therefore
+//! either be created with [`Sharing::Concurrent`] for all of these queue families, or have their
+//! ownership transferred to the queue family that uses them.
+//!
+//! [`Sharing::Concurrent`]: vulkano::sync::Sharing::Concurrent
+
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Condvar, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+use vulkano::{
+    buffer::{AllocateBufferError, Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferExecError,
+        CommandBufferExecFuture, CommandBufferLevel, CommandBufferUsage, CopyBufferInfo,
+        CopyBufferToImageInfo, RecordingCommandBuffer,
+    },
+    device::{DeviceOwned, Queue},
+    image::{mip_level_extent, Image},
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
+    sync::future::{FenceSignalFuture, GpuFuture, NowFuture},
+    DeviceSize, Validated, ValidationError, VulkanError,
+};
+
+/// How long the background thread waits for new requests before it checks whether submitted
+/// uploads have finished.
+const CLEANUP_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A future that represents the moment an upload has finished executing on the device.
+///
+/// All uploads that were submitted in the same batch share the same future.
+pub type UploadFuture = Arc<FenceSignalFuture<CommandBufferExecFuture<NowFuture>>>;
+
+/// Uploads data to buffers and images on a dedicated queue, from a background thread.
+///
+/// Dropping the streamer submits the requests that are still pending, and then blocks until all
+/// submitted uploads have finished executing.
+#[derive(Debug)]
+pub struct TransferStreamer {
+    queue: Arc<Queue>,
+    sender: Option<Sender<UploadRequest>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TransferStreamer {
+    /// Creates a new `TransferStreamer` that submits uploads to `queue`, and allocates its
+    /// staging buffers from `memory_allocator`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `queue` and `memory_allocator` don't belong to the same device.
+    pub fn new(
+        queue: Arc<Queue>,
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        create_info: TransferStreamerCreateInfo,
+    ) -> Self {
+        assert_eq!(queue.device(), memory_allocator.device());
+
+        let command_buffer_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            queue.device().clone(),
+            Default::default(),
+        ));
+        let (sender, receiver) = mpsc::channel();
+        let worker = Worker {
+            queue: queue.clone(),
+            memory_allocator,
+            command_buffer_allocator,
+            max_batch_size: create_info.max_batch_size,
+            in_flight: Vec::new(),
+        };
+        let thread = thread::Builder::new()
+            .name("vulkano-util transfer streamer".into())
+            .spawn(move || worker.run(receiver))
+            .expect("failed to spawn the transfer streamer thread");
+
+        TransferStreamer {
+            queue,
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the queue that the uploads are submitted to.
+    #[inline]
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
+
+    /// Requests that `data` is copied to `dst`.
+    ///
+    /// The size of `data` must be equal to the size of `dst`, and `dst` must have been created
+    /// with the [`TRANSFER_DST`] usage.
+    ///
+    /// [`TRANSFER_DST`]: BufferUsage::TRANSFER_DST
+    pub fn upload_buffer<T>(&self, data: impl Into<Vec<u8>>, dst: Subbuffer<T>) -> UploadTicket
+    where
+        T: ?Sized,
+    {
+        self.request(data.into(), UploadDst::Buffer(dst.into_bytes()))
+    }
+
+    /// Requests that `data` is copied to all array layers of the first mip level of `dst`.
+    ///
+    /// The size of `data` must be the size of the texel data of these subresources, tightly
+    /// packed, and `dst` must have been created with the [`TRANSFER_DST`] usage.
+    ///
+    /// [`TRANSFER_DST`]: vulkano::image::ImageUsage::TRANSFER_DST
+    pub fn upload_image(&self, data: impl Into<Vec<u8>>, dst: Arc<Image>) -> UploadTicket {
+        self.request(data.into(), UploadDst::Image(dst, 0))
+    }
+
+    /// Requests that `data` is copied to all array layers of mip level `mip_level` of `dst`.
+    ///
+    /// The size of `data` must be the size of the texel data of these subresources, tightly
+    /// packed, and `dst` must have been created with the [`TRANSFER_DST`] usage.
+    ///
+    /// [`TRANSFER_DST`]: vulkano::image::ImageUsage::TRANSFER_DST
+    pub fn upload_image_mip_level(
+        &self,
+        data: impl Into<Vec<u8>>,
+        dst: Arc<Image>,
+        mip_level: u32,
+    ) -> UploadTicket {
+        self.request(data.into(), UploadDst::Image(dst, mip_level))
+    }
+
+    fn request(&self, data: Vec<u8>, dst: UploadDst) -> UploadTicket {
+        let ticket = UploadTicket {
+            state: Arc::new(TicketState {
+                result: Mutex::new(None),
+                condvar: Condvar::new(),
+            }),
+        };
+
+        // The thread only stops once the sender is dropped, so sending can't fail.
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(UploadRequest {
+                data,
+                dst,
+                ticket: ticket.clone(),
+            })
+            .unwrap();
+
+        ticket
+    }
+}
+
+impl Drop for TransferStreamer {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Parameters to create a new [`TransferStreamer`].
+#[derive(Clone, Debug)]
+pub struct TransferStreamerCreateInfo {
+    /// The number of bytes after which no more requests are added to a batch.
+    ///
+    /// Requests that arrive while a batch is submitted are recorded into the same command buffer,
+    /// until the total size of their data reaches this value.
+    ///
+    /// The default value is 64 MiB.
+    pub max_batch_size: DeviceSize,
+}
+
+impl Default for TransferStreamerCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_batch_size: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// The result of an upload request, which becomes available once the upload has been submitted.
+#[derive(Clone)]
+pub struct UploadTicket {
+    state: Arc<TicketState>,
+}
+
+struct TicketState {
+    result: Mutex<Option<Result<UploadFuture, UploadError>>>,
+    condvar: Condvar,
+}
+
+impl UploadTicket {
+    /// Returns the future of the upload if it has been submitted, or `None` if it is still
+    /// pending.
+    pub fn try_future(&self) -> Option<Result<UploadFuture, UploadError>> {
+        self.state.result.lock().unwrap().clone()
+    }
+
+    /// Blocks the current thread until the upload has been submitted, and returns its future.
+    ///
+    /// This doesn't wait for the upload to finish executing on the device.
+    pub fn wait(&self) -> Result<UploadFuture, UploadError> {
+        let mut result = self.state.result.lock().unwrap();
+
+        loop {
+            if let Some(result) = &*result {
+                return result.clone();
+            }
+
+            result = self.state.condvar.wait(result).unwrap();
+        }
+    }
+
+    /// Returns whether the upload has finished executing on the device.
+    ///
+    /// Returns `false` if the upload is still pending or failed.
+    pub fn is_complete(&self) -> bool {
+        match &*self.state.result.lock().unwrap() {
+            Some(Ok(future)) => future.is_signaled().unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    fn complete(&self, result: Result<UploadFuture, UploadError>) {
+        *self.state.result.lock().unwrap() = Some(result);
+        self.state.condvar.notify_all();
+    }
+}
+
+/// Error that can happen when uploading data with a [`TransferStreamer`].
+#[derive(Clone, Debug)]
+pub enum UploadError {
+    /// Allocating the staging buffer for the data failed.
+    StagingAllocation(Validated<AllocateBufferError>),
+
+    /// Recording the copy to the destination failed, for example because the size of the data
+    /// doesn't match the destination.
+    Copy(Box<ValidationError>),
+
+    /// Creating or submitting the command buffer failed.
+    Submission(Validated<VulkanError>),
+
+    /// Executing the command buffer on the queue failed.
+    Execution(CommandBufferExecError),
+}
+
+impl Error for UploadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::StagingAllocation(err) => Some(err),
+            Self::Copy(err) => Some(err),
+            Self::Submission(err) => Some(err),
+            Self::Execution(err) => Some(err),
+        }
+    }
+}
+
+impl Display for UploadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::StagingAllocation(_) => write!(f, "failed to allocate the staging buffer"),
+            Self::Copy(_) => write!(f, "failed to record the copy to the destination"),
+            Self::Submission(_) => write!(f, "failed to submit the upload"),
+            Self::Execution(_) => write!(f, "failed to execute the upload"),
+        }
+    }
+}
+
+struct UploadRequest {
+    data: Vec<u8>,
+    dst: UploadDst,
+    ticket: UploadTicket,
+}
+
+enum UploadDst {
+    Buffer(Subbuffer<[u8]>),
+    Image(Arc<Image>, u32),
+}
+
+struct Worker {
+    queue: Arc<Queue>,
+    memory_allocator: Arc<dyn MemoryAllocator>,
+    command_buffer_allocator: Arc<StandardCommandBufferAllocator>,
+    max_batch_size: DeviceSize,
+    // Submitted uploads that may not have finished yet. Their futures are kept here so that
+    // dropping the last one doesn't block one of the threads that uses them.
+    in_flight: Vec<UploadFuture>,
+}
+
+impl Worker {
+    fn run(mut self, receiver: Receiver<UploadRequest>) {
+        loop {
+            let request = match receiver.recv_timeout(CLEANUP_INTERVAL) {
+                Ok(request) => request,
+                Err(RecvTimeoutError::Timeout) => {
+                    self.cleanup_finished();
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+
+            let mut batch_size = request.data.len() as DeviceSize;
+            let mut batch = vec![request];
+
+            while batch_size < self.max_batch_size {
+                match receiver.try_recv() {
+                    Ok(request) => {
+                        batch_size += request.data.len() as DeviceSize;
+                        batch.push(request);
+                    }
+                    Err(_) => break,
+                }
+            }
+
+            self.submit(batch);
+            self.cleanup_finished();
+        }
+    }
+
+    fn submit(&mut self, batch: Vec<UploadRequest>) {
+        let mut builder = match RecordingCommandBuffer::new(
+            self.command_buffer_allocator.clone(),
+            self.queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        ) {
+            Ok(builder) => builder,
+            Err(err) => {
+                for request in batch {
+                    request
+                        .ticket
+                        .complete(Err(UploadError::Submission(err.clone())));
+                }
+
+                return;
+            }
+        };
+
+        let mut tickets = Vec::with_capacity(batch.len());
+
+        for request in batch {
+            match self.record(&mut builder, request.data, request.dst) {
+                Ok(()) => tickets.push(request.ticket),
+                Err(err) => request.ticket.complete(Err(err)),
+            }
+        }
+
+        if tickets.is_empty() {
+            return;
+        }
+
+        let result = builder
+            .end()
+            .map_err(UploadError::Submission)
+            .and_then(|command_buffer| {
+                command_buffer
+                    .execute(self.queue.clone())
+                    .map_err(UploadError::Execution)
+            })
+            .and_then(|future| {
+                future
+                    .then_signal_fence_and_flush()
+                    .map_err(UploadError::Submission)
+            })
+            .map(Arc::new);
+
+        if let Ok(future) = &result {
+            self.in_flight.push(future.clone());
+        }
+
+        for ticket in tickets {
+            ticket.complete(result.clone());
+        }
+    }
+
+    fn record(
+        &self,
+        builder: &mut RecordingCommandBuffer,
+        data: Vec<u8>,
+        dst: UploadDst,
+    ) -> Result<(), UploadError> {
+        if let UploadDst::Buffer(dst) = &dst {
+            if data.len() as DeviceSize != dst.size() {
+                return Err(UploadError::Copy(Box::new(ValidationError {
+                    problem: "the size of the data is not equal to the size of the destination \
+                        buffer"
+                        .into(),
+                    ..Default::default()
+                })));
+            }
+        }
+
+        let staging_buffer = Buffer::from_iter(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            data,
+        )
+        .map_err(UploadError::StagingAllocation)?;
+
+        match dst {
+            UploadDst::Buffer(dst) => {
+                builder.copy_buffer(CopyBufferInfo::buffers(staging_buffer, dst))
+            }
+            UploadDst::Image(dst, mip_level) => {
+                let mut copy_info = CopyBufferToImageInfo::buffer_image(staging_buffer, dst);
+                let region = &mut copy_info.regions[0];
+                region.image_subresource.mip_level = mip_level;
+                region.image_extent =
+                    mip_level_extent(region.image_extent, mip_level).ok_or_else(|| {
+                        UploadError::Copy(Box::new(ValidationError {
+                            problem: "the mip level is not less than the number of mip levels \
+                                that an image with the extent of the destination can have"
+                                .into(),
+                            ..Default::default()
+                        }))
+                    })?;
+
+                builder.copy_buffer_to_image(copy_info)
+            }
+        }
+        .map_err(UploadError::Copy)?;
+
+        Ok(())
+    }
+
+    fn cleanup_finished(&mut self) {
+        self.in_flight
+            .retain(|future| !future.is_signaled().unwrap_or(true));
+    }
+}
@@ -0,0 +1,348 @@
+//! Choosing the format and color space of a swapchain.
+//!
+//! A surface supports a list of format and color space pairs, in an order chosen by the driver.
+//! Simply taking the first pair works on most systems, but breaks on monitors with HDR enabled,
+//! where the first pair can have a color space that the application doesn't expect.
+//! [`choose_surface_format`] instead ranks the pairs against a list of preferences, in order, and
+//! checks that the device supports the chosen format for the intended image usage.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::{device::Device, swapchain::{Surface, SwapchainCreateInfo}};
+//! use vulkano::image::ImageUsage;
+//! use vulkano_util::surface_format::{choose_surface_format, SurfaceFormatPreference};
+//!
+//! # let device: Arc<Device> = todo!();
+//! # let surface: Arc<Surface> = todo!();
+//! let choice = choose_surface_format(
+//!     device.physical_device(),
+//!     &surface,
+//!     &[SurfaceFormatPreference::Hdr10, SurfaceFormatPreference::Srgb],
+//!     ImageUsage::COLOR_ATTACHMENT,
+//! )
+//! .unwrap();
+//! println!("{}", choice.explanation);
+//!
+//! let create_info = SwapchainCreateInfo {
+//!     image_format: choice.format,
+//!     image_color_space: choice.color_space,
+//!     image_usage: ImageUsage::COLOR_ATTACHMENT,
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! All color spaces other than [`ColorSpace::SrgbNonLinear`] require the
+//! [`ext_swapchain_colorspace`] extension to be enabled on the instance. Without it, only the
+//! [`SurfaceFormatPreference::Srgb`] preference can be satisfied.
+//!
+//! [`ext_swapchain_colorspace`]: vulkano::instance::InstanceExtensions::ext_swapchain_colorspace
+
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+};
+use vulkano::{
+    device::physical::PhysicalDevice,
+    format::{Format, FormatFeatures, NumericFormat},
+    image::ImageUsage,
+    swapchain::{ColorSpace, Surface},
+    Validated, VulkanError,
+};
+
+/// A kind of output that the application would like to render to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SurfaceFormatPreference {
+    /// Standard dynamic range output, with a format that converts the linear values written by
+    /// shaders to sRGB automatically.
+    ///
+    /// This matches the [`ColorSpace::SrgbNonLinear`] color space with an `_SRGB` format.
+    Srgb,
+
+    /// HDR10 output, with values encoded with the SMPTE ST 2084 perceptual quantizer by the
+    /// application.
+    ///
+    /// This matches the [`ColorSpace::Hdr10St2084`] color space with a 10-bit or 16-bit format.
+    Hdr10,
+
+    /// Extended linear sRGB output, where values above 1.0 are brighter than SDR white.
+    ///
+    /// This matches the [`ColorSpace::ExtendedSrgbLinear`] color space with a 16-bit
+    /// floating-point format.
+    ScRgb,
+
+    /// Standard dynamic range output with a gamut that is wider than sRGB.
+    ///
+    /// This matches the [`ColorSpace::DisplayP3NonLinear`], [`ColorSpace::DciP3NonLinear`] and
+    /// [`ColorSpace::AdobeRgbNonLinear`] color spaces, in that order, with a normalized format.
+    WideGamut,
+}
+
+impl SurfaceFormatPreference {
+    // Returns the rank of the pair for this preference, lower is better, or `None` if it doesn't
+    // match.
+    fn rank(self, format: Format, color_space: ColorSpace) -> Option<u32> {
+        match self {
+            Self::Srgb => (color_space == ColorSpace::SrgbNonLinear
+                && format.numeric_format_color() == Some(NumericFormat::SRGB))
+            .then_some(0),
+            Self::Hdr10 => {
+                if color_space != ColorSpace::Hdr10St2084 {
+                    return None;
+                }
+
+                match format {
+                    Format::A2B10G10R10_UNORM_PACK32 | Format::A2R10G10B10_UNORM_PACK32 => Some(0),
+                    Format::R16G16B16A16_UNORM | Format::R16G16B16A16_SFLOAT => Some(1),
+                    _ => None,
+                }
+            }
+            Self::ScRgb => (color_space == ColorSpace::ExtendedSrgbLinear
+                && format == Format::R16G16B16A16_SFLOAT)
+                .then_some(0),
+            Self::WideGamut => {
+                let color_space_rank = match color_space {
+                    ColorSpace::DisplayP3NonLinear => 0,
+                    ColorSpace::DciP3NonLinear => 2,
+                    ColorSpace::AdobeRgbNonLinear => 4,
+                    _ => return None,
+                };
+
+                // Prefer more bits per component, because a wider gamut spreads the same number
+                // of steps over a larger range of colors.
+                let format_rank = match format.numeric_format_color() {
+                    Some(NumericFormat::UNORM | NumericFormat::SRGB)
+                        if format.components()[0] >= 10 =>
+                    {
+                        0
+                    }
+                    Some(NumericFormat::UNORM | NumericFormat::SRGB) => 1,
+                    _ => return None,
+                };
+
+                Some(color_space_rank + format_rank)
+            }
+        }
+    }
+
+    fn color_spaces(self) -> &'static [ColorSpace] {
+        match self {
+            Self::Srgb => &[ColorSpace::SrgbNonLinear],
+            Self::Hdr10 => &[ColorSpace::Hdr10St2084],
+            Self::ScRgb => &[ColorSpace::ExtendedSrgbLinear],
+            Self::WideGamut => &[
+                ColorSpace::DisplayP3NonLinear,
+                ColorSpace::DciP3NonLinear,
+                ColorSpace::AdobeRgbNonLinear,
+            ],
+        }
+    }
+}
+
+impl Display for SurfaceFormatPreference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::Srgb => write!(f, "sRGB"),
+            Self::Hdr10 => write!(f, "HDR10"),
+            Self::ScRgb => write!(f, "scRGB"),
+            Self::WideGamut => write!(f, "wide gamut"),
+        }
+    }
+}
+
+/// The format and color space that were chosen by [`choose_surface_format`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SurfaceFormatChoice {
+    /// The chosen format.
+    pub format: Format,
+
+    /// The chosen color space.
+    pub color_space: ColorSpace,
+
+    /// The preference that the pair satisfies, or `None` if none of the preferences could be
+    /// satisfied and a fallback was chosen.
+    pub preference: Option<SurfaceFormatPreference>,
+
+    /// A human-readable explanation of why the pair was chosen, including why any preferences
+    /// that came before it could not be satisfied.
+    pub explanation: String,
+}
+
+impl Display for SurfaceFormatChoice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(f, "{}", self.explanation)
+    }
+}
+
+/// Chooses the format and color space pair of `surface` that best matches `preferences`.
+///
+/// The preferences are tried in order, and the first one that the surface and the physical
+/// device can satisfy is chosen. Only pairs whose format supports `image_usage` with optimal
+/// tiling are considered. If none of the preferences can be satisfied, or if `preferences` is
+/// empty, the first pair with the [`ColorSpace::SrgbNonLinear`] color space is chosen, and if
+/// there is none, the first pair with any color space.
+pub fn choose_surface_format(
+    physical_device: &PhysicalDevice,
+    surface: &Surface,
+    preferences: &[SurfaceFormatPreference],
+    image_usage: ImageUsage,
+) -> Result<SurfaceFormatChoice, SurfaceFormatError> {
+    let surface_formats = physical_device
+        .surface_formats(surface, Default::default())
+        .map_err(SurfaceFormatError::VulkanError)?;
+    let required_features = format_features(image_usage);
+    let colorspace_extension_enabled = physical_device
+        .instance()
+        .enabled_extensions()
+        .ext_swapchain_colorspace;
+
+    let (supported, unsupported): (Vec<_>, Vec<_>) =
+        surface_formats.iter().partition(|&&(format, _)| {
+            physical_device
+                .format_properties(format)
+                .map_or(false, |properties| {
+                    properties
+                        .optimal_tiling_features
+                        .contains(required_features)
+                })
+        });
+    let mut notes = Vec::new();
+
+    for &preference in preferences {
+        let best = supported
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &&(format, color_space))| {
+                preference
+                    .rank(format, color_space)
+                    .map(|rank| ((rank, index), format, color_space))
+            })
+            .min_by_key(|&(key, _, _)| key);
+
+        if let Some((_, format, color_space)) = best {
+            notes.push(format!(
+                "chose {:?} with {:?}, because it satisfies the {} preference",
+                format, color_space, preference,
+            ));
+
+            return Ok(SurfaceFormatChoice {
+                format,
+                color_space,
+                preference: Some(preference),
+                explanation: notes.join("; "),
+            });
+        }
+
+        let reason = if unsupported
+            .iter()
+            .any(|&&(format, color_space)| preference.rank(format, color_space).is_some())
+        {
+            format!(
+                "the device doesn't support {:?} for its formats",
+                image_usage
+            )
+        } else if !colorspace_extension_enabled
+            && preference
+                .color_spaces()
+                .iter()
+                .all(|&color_space| color_space != ColorSpace::SrgbNonLinear)
+        {
+            "the `ext_swapchain_colorspace` instance extension is not enabled".to_owned()
+        } else {
+            "the surface doesn't offer a matching format and color space".to_owned()
+        };
+
+        notes.push(format!(
+            "{} is not available, because {}",
+            preference, reason
+        ));
+    }
+
+    let fallback = supported
+        .iter()
+        .find(|&&&(_, color_space)| color_space == ColorSpace::SrgbNonLinear)
+        .or_else(|| supported.first())
+        .map(|&&pair| pair);
+
+    match fallback {
+        Some((format, color_space)) => {
+            notes.push(format!(
+                "chose {:?} with {:?}, because it is the first format that the surface offers{}",
+                format,
+                color_space,
+                if color_space == ColorSpace::SrgbNonLinear {
+                    " in the standard sRGB color space"
+                } else {
+                    ""
+                },
+            ));
+
+            Ok(SurfaceFormatChoice {
+                format,
+                color_space,
+                preference: None,
+                explanation: notes.join("; "),
+            })
+        }
+        None => Err(SurfaceFormatError::NoSupportedFormat { image_usage }),
+    }
+}
+
+// Returns the format features that are needed to create images with `usage`.
+fn format_features(usage: ImageUsage) -> FormatFeatures {
+    let mut features = FormatFeatures::empty();
+
+    if usage.intersects(ImageUsage::TRANSFER_SRC) {
+        features |= FormatFeatures::TRANSFER_SRC;
+    }
+
+    if usage.intersects(ImageUsage::TRANSFER_DST) {
+        features |= FormatFeatures::TRANSFER_DST;
+    }
+
+    if usage.intersects(ImageUsage::SAMPLED) {
+        features |= FormatFeatures::SAMPLED_IMAGE;
+    }
+
+    if usage.intersects(ImageUsage::STORAGE) {
+        features |= FormatFeatures::STORAGE_IMAGE;
+    }
+
+    if usage.intersects(ImageUsage::COLOR_ATTACHMENT) {
+        features |= FormatFeatures::COLOR_ATTACHMENT;
+    }
+
+    features
+}
+
+/// Error that can happen when choosing a surface format.
+#[derive(Clone, Debug)]
+pub enum SurfaceFormatError {
+    /// Querying the formats of the surface failed.
+    VulkanError(Validated<VulkanError>),
+
+    /// None of the formats that the surface offers support the requested image usage.
+    NoSupportedFormat { image_usage: ImageUsage },
+}
+
+impl Error for SurfaceFormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::VulkanError(err) => Some(err),
+            Self::NoSupportedFormat { .. } => None,
+        }
+    }
+}
+
+impl Display for SurfaceFormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::VulkanError(_) => write!(f, "failed to query the formats of the surface"),
+            Self::NoSupportedFormat { image_usage } => write!(
+                f,
+                "none of the formats of the surface support the image usage {:?}",
+                image_usage,
+            ),
+        }
+    }
+}
@@ -0,0 +1,881 @@
+//! Streaming textures in and out of device memory, within a memory budget.
+//!
+//! Scenes with many textures can't keep all of them in device memory at full resolution.
+//! [`TextureManager`] loads textures on a background thread, keeps their texel data in host
+//! memory, and uploads only as many of their mip levels as fit within a budget, using a
+//! [`TransferStreamer`]. Textures with a higher priority are given their more detailed mip levels
+//! first, and textures with a lower priority are evicted down to their smaller mip levels, or out
+//! of device memory entirely, when the budget runs out.
+//!
+//! Every texture is given a stable index into an array of sampled images in a descriptor set, so
+//! that shaders can access all textures through a single binding. The index stays the same while
+//! the texture's mip levels are streamed in and out. Until the texture is resident, and after it
+//! has been evicted, the index refers to a fallback image view.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::{descriptor_set::DescriptorSet, image::view::ImageView};
+//! # use vulkano_util::context::VulkanoContext;
+//! use vulkano::format::Format;
+//! use vulkano_util::{
+//!     streaming::TransferStreamer,
+//!     texture_manager::{TextureData, TextureManager, TextureManagerCreateInfo},
+//! };
+//!
+//! # let context: VulkanoContext = todo!();
+//! # let descriptor_set: Arc<DescriptorSet> = todo!();
+//! # let fallback: Arc<ImageView> = todo!();
+//! let streamer = Arc::new(TransferStreamer::new(
+//!     context.transfer_queue().clone(),
+//!     context.memory_allocator().clone(),
+//!     Default::default(),
+//! ));
+//! let mut texture_manager = TextureManager::new(
+//!     streamer,
+//!     context.memory_allocator().clone(),
+//!     descriptor_set,
+//!     0,
+//!     fallback,
+//!     TextureManagerCreateInfo {
+//!         queue_family_indices: vec![context.graphics_queue().queue_family_index()],
+//!         ..Default::default()
+//!     },
+//! );
+//!
+//! let albedo = texture_manager
+//!     .load_file("textures/albedo.raw", 1.0, |bytes| {
+//!         Ok(TextureData {
+//!             format: Format::R8G8B8A8_SRGB,
+//!             extent: [1024, 1024],
+//!             mip_levels: vec![bytes],
+//!         })
+//!     })
+//!     .unwrap();
+//!
+//! // Each frame, before recording the commands that use the descriptor set:
+//! texture_manager.update().unwrap();
+//! // Pass `albedo.index()` to the shader, for example in a push constant.
+//! ```
+//!
+//! The descriptor set is updated while it may be in use, so the binding must have been created
+//! with the [`UPDATE_AFTER_BIND`] flag, unless the descriptor set is only used by command buffers
+//! that have been dropped by the time [`update`](TextureManager::update) is called. The binding
+//! should also have the [`PARTIALLY_BOUND`] flag, since the indices that aren't given out are
+//! never written.
+//!
+//! [`UPDATE_AFTER_BIND`]: vulkano::descriptor_set::layout::DescriptorBindingFlags::UPDATE_AFTER_BIND
+//! [`PARTIALLY_BOUND`]: vulkano::descriptor_set::layout::DescriptorBindingFlags::PARTIALLY_BOUND
+
+use crate::streaming::{TransferStreamer, UploadError, UploadTicket};
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+    fs,
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+use vulkano::{
+    descriptor_set::{
+        layout::{DescriptorBindingFlags, DescriptorType},
+        DescriptorSet, WriteDescriptorSet,
+    },
+    device::DeviceOwned,
+    format::Format,
+    image::{
+        max_mip_levels, mip_level_extent, view::ImageView, AllocateImageError, Image,
+        ImageCreateInfo, ImageType, ImageUsage,
+    },
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator},
+    sync::Sharing,
+    DeviceSize, Validated, ValidationError, VulkanError,
+};
+
+/// The function that loads a texture on the background thread.
+type LoadFn = Box<dyn FnOnce() -> Result<TextureData, Box<dyn Error + Send + Sync>> + Send>;
+
+/// Streams the mip levels of textures in and out of device memory, and binds them to stable
+/// indices of a descriptor set binding.
+pub struct TextureManager {
+    streamer: Arc<TransferStreamer>,
+    memory_allocator: Arc<dyn MemoryAllocator>,
+    descriptor_set: Arc<DescriptorSet>,
+    binding: u32,
+    fallback: Arc<ImageView>,
+    budget: DeviceSize,
+    frames_in_flight: u32,
+    queue_family_indices: Vec<u32>,
+    slots: Vec<Slot>,
+    free_indices: Vec<u32>,
+    // Indices whose descriptor must be written in the next update.
+    dirty_indices: Vec<u32>,
+    // Image views that were replaced, with the number of updates since then. They are kept alive
+    // until the device is assumed to be done with them.
+    retired: Vec<(Arc<ImageView>, u32)>,
+    sender: Option<Sender<LoadRequest>>,
+    receiver: Receiver<LoadResult>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TextureManager {
+    /// Creates a new `TextureManager`, which uploads textures with `streamer`, allocates their
+    /// images from `memory_allocator`, and binds them to `binding` of `descriptor_set`.
+    ///
+    /// The number of textures that can be loaded at the same time is the descriptor count of the
+    /// binding, or the variable descriptor count of the descriptor set if the binding was created
+    /// with the [`VARIABLE_DESCRIPTOR_COUNT`] flag.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `streamer`, `memory_allocator`, `descriptor_set` and `fallback` don't all
+    ///   belong to the same device.
+    /// - Panics if the layout of `descriptor_set` doesn't contain `binding`, or if the descriptor
+    ///   type of the binding is not [`DescriptorType::SampledImage`].
+    ///
+    /// [`VARIABLE_DESCRIPTOR_COUNT`]: DescriptorBindingFlags::VARIABLE_DESCRIPTOR_COUNT
+    pub fn new(
+        streamer: Arc<TransferStreamer>,
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        descriptor_set: Arc<DescriptorSet>,
+        binding: u32,
+        fallback: Arc<ImageView>,
+        create_info: TextureManagerCreateInfo,
+    ) -> Self {
+        let device = streamer.queue().device();
+        assert_eq!(device, memory_allocator.device());
+        assert_eq!(device, descriptor_set.device());
+        assert_eq!(device, fallback.device());
+
+        let binding_info = descriptor_set
+            .layout()
+            .bindings()
+            .get(&binding)
+            .expect("the layout of the descriptor set doesn't contain the binding");
+        assert_eq!(
+            binding_info.descriptor_type,
+            DescriptorType::SampledImage,
+            "the descriptor type of the binding must be `DescriptorType::SampledImage`",
+        );
+
+        let capacity = if binding_info
+            .binding_flags
+            .intersects(DescriptorBindingFlags::VARIABLE_DESCRIPTOR_COUNT)
+        {
+            descriptor_set.variable_descriptor_count()
+        } else {
+            binding_info.descriptor_count
+        };
+
+        let mut queue_family_indices = vec![streamer.queue().queue_family_index()];
+
+        for queue_family_index in create_info.queue_family_indices {
+            if !queue_family_indices.contains(&queue_family_index) {
+                queue_family_indices.push(queue_family_index);
+            }
+        }
+
+        let (sender, requests) = mpsc::channel();
+        let (results, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("vulkano-util texture loader".into())
+            .spawn(move || load_textures(requests, results))
+            .expect("failed to spawn the texture loader thread");
+
+        TextureManager {
+            streamer,
+            memory_allocator,
+            descriptor_set,
+            binding,
+            fallback,
+            budget: create_info.budget,
+            frames_in_flight: create_info.frames_in_flight,
+            queue_family_indices,
+            slots: (0..capacity).map(|_| Slot::default()).collect(),
+            free_indices: (0..capacity).rev().collect(),
+            dirty_indices: Vec::new(),
+            retired: Vec::new(),
+            sender: Some(sender),
+            receiver,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the descriptor set that the textures are bound to.
+    #[inline]
+    pub fn descriptor_set(&self) -> &Arc<DescriptorSet> {
+        &self.descriptor_set
+    }
+
+    /// Returns the memory budget, in bytes.
+    #[inline]
+    pub fn budget(&self) -> DeviceSize {
+        self.budget
+    }
+
+    /// Sets the memory budget, in bytes. The new budget is enforced on the next call to
+    /// [`update`](Self::update).
+    #[inline]
+    pub fn set_budget(&mut self, budget: DeviceSize) {
+        self.budget = budget;
+    }
+
+    /// Returns the number of bytes of texel data that are currently in device memory, including
+    /// the data of uploads that haven't finished yet.
+    pub fn memory_usage(&self) -> DeviceSize {
+        self.slots
+            .iter()
+            .filter_map(|slot| match &slot.texture.as_ref()?.state {
+                TextureState::Loaded {
+                    resident, pending, ..
+                } => Some(
+                    resident.as_ref().map_or(0, |residency| residency.size)
+                        + pending.as_ref().map_or(0, |pending| pending.residency.size),
+                ),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Loads a texture by calling `load` on the background thread, and returns its ID, or `None`
+    /// if all indices of the binding are in use.
+    ///
+    /// The index of the texture refers to the fallback image view after the next call to
+    /// [`update`](Self::update), until the texture becomes resident. Textures with a higher
+    /// `priority` are given their mip levels first.
+    pub fn load(
+        &mut self,
+        priority: f32,
+        load: impl FnOnce() -> Result<TextureData, Box<dyn Error + Send + Sync>> + Send + 'static,
+    ) -> Option<TextureId> {
+        let index = self.free_indices.pop()?;
+        let slot = &mut self.slots[index as usize];
+        slot.texture = Some(Texture {
+            priority,
+            state: TextureState::Loading,
+        });
+        self.dirty_indices.push(index);
+
+        let id = TextureId {
+            index,
+            generation: slot.generation,
+        };
+
+        // The thread only stops once the sender is dropped, so sending can't fail.
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(LoadRequest {
+                id,
+                load: Box::new(load),
+            })
+            .unwrap();
+
+        Some(id)
+    }
+
+    /// Loads a texture by reading the file at `path` and calling `decode` with its contents, both
+    /// on the background thread.
+    ///
+    /// This is otherwise the same as [`load`](Self::load).
+    pub fn load_file(
+        &mut self,
+        path: impl Into<PathBuf>,
+        priority: f32,
+        decode: impl FnOnce(Vec<u8>) -> Result<TextureData, Box<dyn Error + Send + Sync>>
+            + Send
+            + 'static,
+    ) -> Option<TextureId> {
+        let path = path.into();
+
+        self.load(priority, move || decode(fs::read(path)?))
+    }
+
+    /// Removes a texture, and frees its index once the next call to [`update`](Self::update) has
+    /// bound the fallback image view to it.
+    ///
+    /// Returns `false` if `id` doesn't refer to a texture of this manager.
+    pub fn remove(&mut self, id: TextureId) -> bool {
+        let Some(texture) = self.texture_mut(id).and_then(|slot| slot.texture.take()) else {
+            return false;
+        };
+
+        self.retire(texture.state);
+        self.slots[id.index as usize].generation += 1;
+        self.dirty_indices.push(id.index);
+
+        true
+    }
+
+    /// Sets the priority of a texture. The new priority is taken into account on the next call to
+    /// [`update`](Self::update).
+    ///
+    /// Returns `false` if `id` doesn't refer to a texture of this manager.
+    pub fn set_priority(&mut self, id: TextureId, priority: f32) -> bool {
+        match self.texture_mut(id).and_then(|slot| slot.texture.as_mut()) {
+            Some(texture) => {
+                texture.priority = priority;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the status of a texture, or `None` if `id` doesn't refer to a texture of this
+    /// manager.
+    pub fn status(&self, id: TextureId) -> Option<TextureStatus> {
+        let slot = self.slots.get(id.index as usize)?;
+
+        if slot.generation != id.generation {
+            return None;
+        }
+
+        Some(match &slot.texture.as_ref()?.state {
+            TextureState::Loading => TextureStatus::Loading,
+            TextureState::Failed(err) => TextureStatus::Failed(err.clone()),
+            TextureState::Loaded {
+                resident: Some(residency),
+                ..
+            } => TextureStatus::Resident {
+                base_mip_level: residency.base_mip_level,
+            },
+            TextureState::Loaded { resident: None, .. } => TextureStatus::Evicted,
+        })
+    }
+
+    /// Processes the textures that finished loading and uploading, streams mip levels in and out
+    /// of device memory according to the priorities of the textures and the budget, and writes
+    /// the image views of the textures whose resident mip levels changed to the descriptor set.
+    ///
+    /// This should be called once per frame, before recording the commands that use the
+    /// descriptor set.
+    pub fn update(&mut self) -> Result<(), Box<ValidationError>> {
+        let frames_in_flight = self.frames_in_flight;
+        self.retired.retain_mut(|(_, age)| {
+            *age += 1;
+            *age <= frames_in_flight
+        });
+
+        self.receive_loaded();
+        self.finish_uploads();
+        self.enforce_budget();
+
+        let writes: Vec<_> = self
+            .dirty_indices
+            .iter()
+            .map(|&index| {
+                let slot = &self.slots[index as usize];
+                let image_view = match slot.texture.as_ref().map(|texture| &texture.state) {
+                    Some(TextureState::Loaded {
+                        resident: Some(residency),
+                        ..
+                    }) => residency.image_view.clone(),
+                    _ => self.fallback.clone(),
+                };
+
+                WriteDescriptorSet::image_view_array(self.binding, index, [image_view])
+            })
+            .collect();
+        self.descriptor_set.update_shared(writes, [])?;
+
+        for index in self.dirty_indices.drain(..) {
+            let slot = &self.slots[index as usize];
+
+            // Removed textures keep their index until the fallback has been bound to it.
+            if slot.texture.is_none() && !self.free_indices.contains(&index) {
+                self.free_indices.push(index);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn texture_mut(&mut self, id: TextureId) -> Option<&mut Slot> {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+    }
+
+    fn retire(&mut self, state: TextureState) {
+        if let TextureState::Loaded {
+            resident, pending, ..
+        } = state
+        {
+            self.retired.extend(
+                resident
+                    .into_iter()
+                    .chain(pending.map(|pending| pending.residency))
+                    .map(|residency| (residency.image_view, 0)),
+            );
+        }
+    }
+
+    fn receive_loaded(&mut self) {
+        while let Ok(LoadResult { id, result }) = self.receiver.try_recv() {
+            let Some(texture) = self
+                .texture_mut(id)
+                .and_then(|slot| slot.texture.as_mut())
+                .filter(|texture| matches!(texture.state, TextureState::Loading))
+            else {
+                continue;
+            };
+
+            texture.state = match result {
+                Ok(data) => match data.validate() {
+                    Ok(()) => TextureState::Loaded {
+                        data,
+                        resident: None,
+                        pending: None,
+                    },
+                    Err(err) => TextureState::Failed(TextureError::InvalidData(err)),
+                },
+                Err(err) => TextureState::Failed(TextureError::Load(err.into())),
+            };
+        }
+    }
+
+    fn finish_uploads(&mut self) {
+        for index in 0..self.slots.len() {
+            let Some(texture) = self.slots[index].texture.as_mut() else {
+                continue;
+            };
+            let TextureState::Loaded {
+                resident, pending, ..
+            } = &mut texture.state
+            else {
+                continue;
+            };
+            let Some(upload) = pending else {
+                continue;
+            };
+
+            let error = upload
+                .tickets
+                .iter()
+                .find_map(|ticket| ticket.try_future().and_then(Result::err));
+
+            if let Some(err) = error {
+                let state = std::mem::replace(
+                    &mut texture.state,
+                    TextureState::Failed(TextureError::Upload(err)),
+                );
+                self.retire(state);
+                self.dirty_indices.push(index as u32);
+            } else if upload.tickets.iter().all(UploadTicket::is_complete) {
+                let upload = pending.take().unwrap();
+                let previous = resident.replace(upload.residency);
+                self.retired.extend(
+                    previous
+                        .into_iter()
+                        .map(|residency| (residency.image_view, 0)),
+                );
+                self.dirty_indices.push(index as u32);
+            }
+        }
+    }
+
+    fn enforce_budget(&mut self) {
+        let mut order: Vec<usize> = (0..self.slots.len())
+            .filter(|&index| {
+                matches!(
+                    self.slots[index].texture,
+                    Some(Texture {
+                        state: TextureState::Loaded { .. },
+                        ..
+                    }),
+                )
+            })
+            .collect();
+        order.sort_by(|&a, &b| {
+            let priority = |index: usize| self.slots[index].texture.as_ref().unwrap().priority;
+            priority(b).total_cmp(&priority(a))
+        });
+
+        let mut remaining = self.budget;
+
+        for index in order {
+            let texture = self.slots[index].texture.as_ref().unwrap();
+            let TextureState::Loaded {
+                data,
+                resident,
+                pending,
+            } = &texture.state
+            else {
+                unreachable!()
+            };
+
+            // The most detailed mip level that fits within what is left of the budget.
+            let target = (0..data.mip_levels.len() as u32)
+                .find(|&base_mip_level| data.size(base_mip_level) <= remaining);
+            let resident_base = resident.as_ref().map(|residency| residency.base_mip_level);
+            let pending_base = pending
+                .as_ref()
+                .map(|pending| pending.residency.base_mip_level);
+
+            if let Some(base_mip_level) = target {
+                remaining -= data.size(base_mip_level);
+            }
+
+            if target == pending_base.or(resident_base) {
+                continue;
+            }
+
+            if target.is_some() && target == resident_base {
+                // The texture is back to the mip level that is already bound, so the upload to a
+                // different mip level is no longer needed.
+                let Some(Texture {
+                    state: TextureState::Loaded { pending, .. },
+                    ..
+                }) = self.slots[index].texture.as_mut()
+                else {
+                    unreachable!()
+                };
+
+                let previous = pending.take().unwrap();
+                self.retired.push((previous.residency.image_view, 0));
+
+                continue;
+            }
+
+            match target {
+                Some(base_mip_level) => match self.upload(data, base_mip_level) {
+                    Ok(upload) => {
+                        let Some(Texture {
+                            state: TextureState::Loaded { pending, .. },
+                            ..
+                        }) = self.slots[index].texture.as_mut()
+                        else {
+                            unreachable!()
+                        };
+
+                        if let Some(previous) = pending.replace(upload) {
+                            self.retired.push((previous.residency.image_view, 0));
+                        }
+                    }
+                    Err(err) => {
+                        let texture = self.slots[index].texture.as_mut().unwrap();
+                        let state =
+                            std::mem::replace(&mut texture.state, TextureState::Failed(err));
+                        self.retire(state);
+                        self.dirty_indices.push(index as u32);
+                    }
+                },
+                None => {
+                    let Some(Texture {
+                        state:
+                            TextureState::Loaded {
+                                resident, pending, ..
+                            },
+                        ..
+                    }) = self.slots[index].texture.as_mut()
+                    else {
+                        unreachable!()
+                    };
+
+                    self.retired.extend(
+                        resident
+                            .take()
+                            .into_iter()
+                            .chain(pending.take().map(|pending| pending.residency))
+                            .map(|residency| (residency.image_view, 0)),
+                    );
+                    self.dirty_indices.push(index as u32);
+                }
+            }
+        }
+    }
+
+    fn upload(
+        &self,
+        data: &TextureData,
+        base_mip_level: u32,
+    ) -> Result<PendingUpload, TextureError> {
+        let [width, height] = data.extent;
+        let extent = mip_level_extent([width, height, 1], base_mip_level).unwrap();
+        let sharing = if self.queue_family_indices.len() > 1 {
+            Sharing::Concurrent(self.queue_family_indices.iter().copied().collect())
+        } else {
+            Sharing::Exclusive
+        };
+
+        let image = Image::new(
+            self.memory_allocator.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: data.format,
+                extent,
+                mip_levels: data.mip_levels.len() as u32 - base_mip_level,
+                usage: ImageUsage::TRANSFER_DST | ImageUsage::SAMPLED,
+                sharing,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .map_err(TextureError::ImageCreation)?;
+        let image_view =
+            ImageView::new_default(image.clone()).map_err(TextureError::ImageViewCreation)?;
+
+        let tickets = data.mip_levels[base_mip_level as usize..]
+            .iter()
+            .zip(0..)
+            .map(|(level_data, mip_level)| {
+                self.streamer
+                    .upload_image_mip_level(level_data.clone(), image.clone(), mip_level)
+            })
+            .collect();
+
+        Ok(PendingUpload {
+            residency: Residency {
+                image_view,
+                base_mip_level,
+                size: data.size(base_mip_level),
+            },
+            tickets,
+        })
+    }
+}
+
+impl Drop for TextureManager {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Parameters to create a new [`TextureManager`].
+#[derive(Clone, Debug)]
+pub struct TextureManagerCreateInfo {
+    /// The number of bytes of texel data that may be in device memory at the same time.
+    ///
+    /// While a texture is streamed to a different mip level, both its old and its new images are
+    /// in device memory, so the actual memory usage can briefly exceed the budget.
+    ///
+    /// The default value is 256 MiB.
+    pub budget: DeviceSize,
+
+    /// The number of calls to [`update`](TextureManager::update) after which an image view that
+    /// was replaced in the descriptor set is dropped.
+    ///
+    /// This should be at least the number of frames that can be in flight at the same time, so
+    /// that the device has finished using the image view by the time it is dropped.
+    ///
+    /// The default value is 3.
+    pub frames_in_flight: u32,
+
+    /// The queue families that use the textures, in addition to the queue family of the queue of
+    /// the streamer. The images of the textures are created with concurrent sharing between all
+    /// of these queue families.
+    ///
+    /// The default value is empty.
+    pub queue_family_indices: Vec<u32>,
+}
+
+impl Default for TextureManagerCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            budget: 256 * 1024 * 1024,
+            frames_in_flight: 3,
+            queue_family_indices: Vec::new(),
+        }
+    }
+}
+
+/// Identifies a texture of a [`TextureManager`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureId {
+    index: u32,
+    generation: u32,
+}
+
+impl TextureId {
+    /// Returns the index of the texture in the array of the descriptor set binding.
+    #[inline]
+    pub fn index(self) -> u32 {
+        self.index
+    }
+}
+
+/// The texel data of a 2D texture.
+#[derive(Clone, Debug)]
+pub struct TextureData {
+    /// The format of the texture.
+    pub format: Format,
+
+    /// The width and height of the most detailed mip level.
+    pub extent: [u32; 2],
+
+    /// The texel data of each mip level, starting with the most detailed one. The data of each
+    /// level must be tightly packed.
+    ///
+    /// Only the mip levels that are provided are used. There must be at least one.
+    pub mip_levels: Vec<Vec<u8>>,
+}
+
+impl TextureData {
+    fn validate(&self) -> Result<(), Box<ValidationError>> {
+        let &Self {
+            format: _,
+            extent: [width, height],
+            ref mip_levels,
+        } = self;
+
+        if width == 0 || height == 0 {
+            return Err(Box::new(ValidationError {
+                context: "extent".into(),
+                problem: "one of the elements is zero".into(),
+                ..Default::default()
+            }));
+        }
+
+        if mip_levels.is_empty() {
+            return Err(Box::new(ValidationError {
+                context: "mip_levels".into(),
+                problem: "is empty".into(),
+                ..Default::default()
+            }));
+        }
+
+        if mip_levels.len() as u32 > max_mip_levels([width, height, 1]) {
+            return Err(Box::new(ValidationError {
+                context: "mip_levels".into(),
+                problem: "the length is greater than the maximum number of mip levels for \
+                    `extent`"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    // The size of the texel data of the mip levels starting at `base_mip_level`.
+    fn size(&self, base_mip_level: u32) -> DeviceSize {
+        self.mip_levels[base_mip_level as usize..]
+            .iter()
+            .map(|level_data| level_data.len() as DeviceSize)
+            .sum()
+    }
+}
+
+/// The status of a texture of a [`TextureManager`].
+#[derive(Clone, Debug)]
+pub enum TextureStatus {
+    /// The texture is being loaded on the background thread.
+    Loading,
+
+    /// The texture has been loaded, but none of its mip levels are in device memory, because it
+    /// doesn't fit within the budget or its upload hasn't finished yet. Its index refers to the
+    /// fallback image view.
+    Evicted,
+
+    /// The mip levels of the texture from `base_mip_level` onwards are in device memory, and
+    /// bound to its index.
+    Resident { base_mip_level: u32 },
+
+    /// Loading or uploading the texture failed. Its index refers to the fallback image view.
+    Failed(TextureError),
+}
+
+/// Error that can happen when loading or uploading a texture with a [`TextureManager`].
+#[derive(Clone, Debug)]
+pub enum TextureError {
+    /// The function that loads the texture returned an error.
+    Load(Arc<dyn Error + Send + Sync>),
+
+    /// The texture data that was loaded is not valid.
+    InvalidData(Box<ValidationError>),
+
+    /// Creating the image for the texture failed.
+    ImageCreation(Validated<AllocateImageError>),
+
+    /// Creating the image view for the texture failed.
+    ImageViewCreation(Validated<VulkanError>),
+
+    /// Uploading the texel data failed.
+    Upload(UploadError),
+}
+
+impl Error for TextureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Load(err) => Some(err.as_ref()),
+            Self::InvalidData(err) => Some(err),
+            Self::ImageCreation(err) => Some(err),
+            Self::ImageViewCreation(err) => Some(err),
+            Self::Upload(err) => Some(err),
+        }
+    }
+}
+
+impl Display for TextureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self {
+            Self::Load(_) => write!(f, "failed to load the texture"),
+            Self::InvalidData(_) => write!(f, "the texture data is not valid"),
+            Self::ImageCreation(_) => write!(f, "failed to create the image"),
+            Self::ImageViewCreation(_) => write!(f, "failed to create the image view"),
+            Self::Upload(_) => write!(f, "failed to upload the texture"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Slot {
+    // Incremented when the texture is removed, so that IDs of removed textures are rejected.
+    generation: u32,
+    texture: Option<Texture>,
+}
+
+struct Texture {
+    priority: f32,
+    state: TextureState,
+}
+
+enum TextureState {
+    Loading,
+    Failed(TextureError),
+    Loaded {
+        data: TextureData,
+        // The image that is bound to the index of the texture.
+        resident: Option<Residency>,
+        // The image that replaces `resident` once its upload has finished.
+        pending: Option<PendingUpload>,
+    },
+}
+
+struct Residency {
+    image_view: Arc<ImageView>,
+    base_mip_level: u32,
+    size: DeviceSize,
+}
+
+struct PendingUpload {
+    residency: Residency,
+    tickets: Vec<UploadTicket>,
+}
+
+struct LoadRequest {
+    id: TextureId,
+    load: LoadFn,
+}
+
+struct LoadResult {
+    id: TextureId,
+    result: Result<TextureData, Box<dyn Error + Send + Sync>>,
+}
+
+fn load_textures(requests: Receiver<LoadRequest>, results: Sender<LoadResult>) {
+    for LoadRequest { id, load } in requests {
+        let result = load();
+
+        if results.send(LoadResult { id, result }).is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Capturing validation layer messages, for failing tests on validation errors.
+//!
+//! The Khronos validation layer reports problems that vulkano doesn't check through debug
+//! messages, which by default are only printed to the standard output. [`ValidationCapture`]
+//! installs a debug messenger that collects these messages instead, so that a test can assert
+//! that a piece of code produced no validation errors, or that it produced an error with a
+//! specific VUID.
+//!
+//! ```no_run
+//! use vulkano_util::{
+//!     context::{VulkanoConfig, VulkanoContext},
+//!     validation::ValidationCapture,
+//! };
+//!
+//! let mut config = VulkanoConfig::default();
+//! ValidationCapture::configure_instance(&mut config.instance_create_info);
+//!
+//! let context = VulkanoContext::new(config);
+//! let validation = ValidationCapture::new(context.instance().clone()).unwrap();
+//!
+//! validation.assert_no_errors(|| {
+//!     // ...record and submit work, and wait for it to finish...
+//! });
+//!
+//! validation.expect_vuid("VUID-vkCmdDraw-None-02700", || {
+//!     // ...record a draw with a mismatched render pass...
+//! });
+//! ```
+//!
+//! Most messages are reported while the offending function is being called, on the thread that
+//! calls it, so they are captured by the closure that made the call. Messages of the
+//! GPU-assisted validation features are only reported once the work has finished executing on
+//! the device, so the closure must wait for that. The messages of all threads that use the
+//! instance are captured, so tests that share an instance should not run at the same time.
+
+use std::{
+    fmt::{Display, Error as FmtError, Formatter},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+};
+use vulkano::{
+    instance::{
+        debug::{
+            DebugUtilsMessageSeverity, DebugUtilsMessageType, DebugUtilsMessenger,
+            DebugUtilsMessengerCallback, DebugUtilsMessengerCallbackData,
+            DebugUtilsMessengerCreateInfo,
+        },
+        Instance, InstanceCreateInfo, InstanceExtensions,
+    },
+    Validated, VulkanError,
+};
+
+const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+
+/// Receives the messages that the validation layer reports.
+///
+/// The messages are buffered until they are retrieved. Dropping the capture stops listening for
+/// messages.
+#[derive(Debug)]
+pub struct ValidationCapture {
+    _messenger: DebugUtilsMessenger,
+    receiver: Mutex<Receiver<ValidationMessage>>,
+}
+
+impl ValidationCapture {
+    /// Modifies `create_info` so that the instance created from it enables the Khronos
+    /// validation layer and can report its messages.
+    ///
+    /// Anything that is already enabled is left in place.
+    pub fn configure_instance(create_info: &mut InstanceCreateInfo) {
+        if !create_info
+            .enabled_layers
+            .iter()
+            .any(|layer| layer == VALIDATION_LAYER)
+        {
+            create_info.enabled_layers.push(VALIDATION_LAYER.to_owned());
+        }
+
+        create_info.enabled_extensions =
+            create_info.enabled_extensions.union(&InstanceExtensions {
+                ext_debug_utils: true,
+                ..InstanceExtensions::empty()
+            });
+    }
+
+    /// Starts capturing the warnings and errors that are reported to `instance`.
+    ///
+    /// `instance` must have the validation layer and the `ext_debug_utils` extension enabled,
+    /// for example by creating it from a create info that was passed to
+    /// [`configure_instance`](Self::configure_instance). Otherwise, no messages will be received.
+    pub fn new(instance: Arc<Instance>) -> Result<Self, Validated<VulkanError>> {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+
+        // SAFETY: The callback doesn't call into Vulkan.
+        let user_callback = unsafe {
+            DebugUtilsMessengerCallback::new(move |severity, ty, callback_data| {
+                let message = ValidationMessage::from_callback_data(severity, ty, &callback_data);
+                let _ = sender.lock().unwrap().send(message);
+            })
+        };
+
+        let messenger = DebugUtilsMessenger::new(
+            instance,
+            DebugUtilsMessengerCreateInfo {
+                message_severity: DebugUtilsMessageSeverity::ERROR
+                    | DebugUtilsMessageSeverity::WARNING,
+                message_type: DebugUtilsMessageType::GENERAL
+                    | DebugUtilsMessageType::VALIDATION
+                    | DebugUtilsMessageType::PERFORMANCE,
+                ..DebugUtilsMessengerCreateInfo::user_callback(user_callback)
+            },
+        )?;
+
+        Ok(Self {
+            _messenger: messenger,
+            receiver: Mutex::new(receiver),
+        })
+    }
+
+    /// Returns the messages that were received since the previous call, in the order in which
+    /// they were reported.
+    pub fn drain(&self) -> Vec<ValidationMessage> {
+        self.receiver.lock().unwrap().try_iter().collect()
+    }
+
+    /// Calls `f`, and returns the messages that were received while it was running.
+    ///
+    /// Messages that were received before `f` was called are discarded.
+    pub fn capture<R>(&self, f: impl FnOnce() -> R) -> (R, Vec<ValidationMessage>) {
+        self.drain();
+        let result = f();
+
+        (result, self.drain())
+    }
+
+    /// Calls `f`, and panics if any validation errors were received while it was running.
+    ///
+    /// Warnings are ignored.
+    #[track_caller]
+    pub fn assert_no_errors<R>(&self, f: impl FnOnce() -> R) -> R {
+        let (result, messages) = self.capture(f);
+        let errors: Vec<_> = messages
+            .iter()
+            .filter(|message| message.is_error())
+            .map(ToString::to_string)
+            .collect();
+
+        assert!(
+            errors.is_empty(),
+            "{} validation error(s) were reported:\n{}",
+            errors.len(),
+            errors.join("\n"),
+        );
+
+        result
+    }
+
+    /// Calls `f`, and panics if no validation error with the VUID `vuid` was received while it
+    /// was running.
+    ///
+    /// Other messages are ignored.
+    #[track_caller]
+    pub fn expect_vuid<R>(&self, vuid: &str, f: impl FnOnce() -> R) -> R {
+        let (result, messages) = self.capture(f);
+
+        assert!(
+            messages
+                .iter()
+                .any(|message| message.is_error() && message.has_vuid(vuid)),
+            "expected a validation error with VUID `{}`, but {}",
+            vuid,
+            if messages.is_empty() {
+                "no messages were reported".to_owned()
+            } else {
+                format!(
+                    "only these messages were reported:\n{}",
+                    messages
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                )
+            },
+        );
+
+        result
+    }
+}
+
+/// A single message reported by the validation layer.
+#[derive(Clone, Debug)]
+pub struct ValidationMessage {
+    /// The severity of the message.
+    pub severity: DebugUtilsMessageSeverity,
+
+    /// The type of the message.
+    pub ty: DebugUtilsMessageType,
+
+    /// The identifier of the message. For validation errors, this is usually the VUID of the
+    /// valid usage rule that was broken.
+    pub message_id_name: Option<String>,
+
+    /// The full text of the message.
+    pub message: String,
+}
+
+impl ValidationMessage {
+    fn from_callback_data(
+        severity: DebugUtilsMessageSeverity,
+        ty: DebugUtilsMessageType,
+        callback_data: &DebugUtilsMessengerCallbackData<'_>,
+    ) -> Self {
+        ValidationMessage {
+            severity,
+            ty,
+            message_id_name: callback_data.message_id_name.map(ToOwned::to_owned),
+            message: callback_data.message.to_owned(),
+        }
+    }
+
+    /// Returns whether the message was reported as an error.
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.severity.intersects(DebugUtilsMessageSeverity::ERROR)
+    }
+
+    /// Returns whether the message is about the VUID `vuid`.
+    ///
+    /// Some versions of the layer report a different identifier than the VUID, but always
+    /// mention the VUID in the text of the message, so both are checked.
+    #[inline]
+    pub fn has_vuid(&self, vuid: &str) -> bool {
+        self.message_id_name.as_deref() == Some(vuid) || self.message.contains(vuid)
+    }
+}
+
+impl Display for ValidationMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match &self.message_id_name {
+            Some(message_id_name) => write!(f, "[{}] {}", message_id_name, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
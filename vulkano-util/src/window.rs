@@ -4,8 +4,17 @@
 
 use crate::{context::VulkanoContext, renderer::VulkanoWindowRenderer};
 use ahash::HashMap;
-use std::collections::hash_map::{Iter, IterMut};
-use vulkano::swapchain::{PresentMode, SwapchainCreateInfo};
+use std::{
+    collections::hash_map::{Iter, IterMut},
+    sync::Arc,
+    time::Duration,
+};
+use vulkano::{
+    image::view::ImageView,
+    swapchain::{PresentGravity, PresentMode, PresentScaling, SwapchainCreateInfo},
+    sync::{self, GpuFuture},
+    Validated, VulkanError,
+};
 use winit::{
     dpi::LogicalSize,
     window::{CursorGrabMode, WindowId},
@@ -34,10 +43,35 @@ use winit::{
 ///     // You should now have two windows.
 /// }
 /// ```
+///
+/// All windows share the device of the [`VulkanoContext`], but each has its own swapchain,
+/// present mode and [frame interval]. Windows can be rendered one at a time with
+/// [`VulkanoWindowRenderer::acquire`] and [`VulkanoWindowRenderer::present`], or all together
+/// with [`acquire_all`] and [`present_all`]:
+///
+/// ```no_run
+/// # use vulkano::sync::GpuFuture;
+/// # use vulkano_util::window::VulkanoWindows;
+/// # let vulkano_windows: &mut VulkanoWindows = return;
+/// if let Some((window_ids, future)) = vulkano_windows.acquire_all(None, |_, _| {}) {
+///     for &id in &window_ids {
+///         let renderer = vulkano_windows.get_renderer(id).unwrap();
+///         // Record the commands that render to `renderer.swapchain_image_view()`.
+///     }
+///
+///     // Execute the command buffers after `future`, then present all windows at once.
+///     vulkano_windows.present_all(&window_ids, future, false);
+/// }
+/// ```
+///
+/// [frame interval]: VulkanoWindowRenderer::set_frame_interval
+/// [`acquire_all`]: Self::acquire_all
+/// [`present_all`]: Self::present_all
 #[derive(Default)]
 pub struct VulkanoWindows {
     windows: HashMap<WindowId, VulkanoWindowRenderer>,
     primary: Option<WindowId>,
+    previous_frame_end: Option<Box<dyn GpuFuture>>,
 }
 
 impl VulkanoWindows {
@@ -225,6 +259,123 @@ impl VulkanoWindows {
         }
     }
 
+    /// Acquires the next swapchain image of every window whose next frame is due, for rendering
+    /// to all of them in one frame.
+    ///
+    /// Windows that are minimized, whose [frame interval] has not passed yet, or whose swapchain
+    /// is out of date are skipped. `on_recreate_swapchain` is called with the id of the window
+    /// whenever the swapchain of a window gets recreated.
+    ///
+    /// Returns the ids of the windows that were acquired, and a future representing the time
+    /// after which all of their swapchain images have been acquired and their previous frames
+    /// ended. Returns `None` if no window was acquired. Execute your command buffers after the
+    /// future, and finish rendering by calling [`present_all`] with the returned ids.
+    ///
+    /// [frame interval]: VulkanoWindowRenderer::set_frame_interval
+    /// [`present_all`]: Self::present_all
+    pub fn acquire_all(
+        &mut self,
+        timeout: Option<Duration>,
+        mut on_recreate_swapchain: impl FnMut(WindowId, &[Arc<ImageView>]),
+    ) -> Option<(Vec<WindowId>, Box<dyn GpuFuture>)> {
+        let mut window_ids = Vec::new();
+        let mut future = self.previous_frame_end.take();
+
+        for (&id, renderer) in &mut self.windows {
+            let window_size = renderer.window().inner_size();
+
+            if window_size.width == 0 || window_size.height == 0 || !renderer.is_frame_due() {
+                continue;
+            }
+
+            let acquire_future = match renderer.acquire_image(timeout, |image_views| {
+                on_recreate_swapchain(id, image_views)
+            }) {
+                Ok(acquire_future) => acquire_future,
+                Err(_) => continue,
+            };
+            let window_future = renderer.take_previous_frame_end().join(acquire_future);
+
+            future = Some(match future {
+                Some(future) => future.join(window_future).boxed(),
+                None => window_future.boxed(),
+            });
+            window_ids.push(id);
+        }
+
+        if window_ids.is_empty() {
+            self.previous_frame_end = future;
+
+            return None;
+        }
+
+        Some((window_ids, future.unwrap()))
+    }
+
+    /// Finishes rendering to the windows returned by [`acquire_all`], by presenting all of their
+    /// swapchains with a single present operation. Pass your last future as an input to this
+    /// function.
+    ///
+    /// If the present operation reports that a swapchain is out of date, the swapchains of all
+    /// of the windows are recreated at the beginning of their next frame.
+    ///
+    /// [`acquire_all`]: Self::acquire_all
+    pub fn present_all(
+        &mut self,
+        window_ids: &[WindowId],
+        after_future: Box<dyn GpuFuture>,
+        wait_future: bool,
+    ) {
+        let mut future = after_future;
+        let mut queue = None;
+
+        for id in window_ids {
+            if let Some(renderer) = self.windows.get(id) {
+                let graphics_queue = renderer.graphics_queue();
+                future = future
+                    .then_swapchain_present(
+                        graphics_queue.clone(),
+                        renderer.swapchain_present_info(),
+                    )
+                    .boxed();
+                queue = Some(graphics_queue);
+            }
+        }
+
+        let device = match queue {
+            Some(queue) => queue.device().clone(),
+            None => return,
+        };
+
+        match future
+            .then_signal_fence_and_flush()
+            .map_err(Validated::unwrap)
+        {
+            Ok(mut future) => {
+                if wait_future {
+                    future.wait(None).unwrap_or_else(|e| println!("{e}"))
+                } else {
+                    future.cleanup_finished();
+                }
+
+                self.previous_frame_end = Some(future.boxed());
+            }
+            Err(VulkanError::OutOfDate) => {
+                for id in window_ids {
+                    if let Some(renderer) = self.windows.get_mut(id) {
+                        renderer.resize();
+                    }
+                }
+
+                self.previous_frame_end = Some(sync::now(device).boxed());
+            }
+            Err(e) => {
+                println!("failed to flush future: {e}");
+                self.previous_frame_end = Some(sync::now(device).boxed());
+            }
+        }
+    }
+
     /// Return iterator over window renderers.
     #[inline]
     pub fn iter(&self) -> Iter<'_, WindowId, VulkanoWindowRenderer> {
@@ -332,6 +483,21 @@ pub struct WindowDescriptor {
     ///
     /// Used to select whether or not VSync is used
     pub present_mode: PresentMode,
+    /// How the swapchain images are scaled when their size doesn't match the size of the window,
+    /// for example while the window is being resized.
+    ///
+    /// This is only used if the
+    /// [`ext_swapchain_maintenance1`](vulkano::device::DeviceExtensions::ext_swapchain_maintenance1)
+    /// extension is enabled on the device, the
+    /// [`ext_surface_maintenance1`](vulkano::instance::InstanceExtensions::ext_surface_maintenance1)
+    /// extension is enabled on the instance, and the surface supports the value. Otherwise, it is
+    /// ignored.
+    pub present_scaling: Option<PresentScaling>,
+    /// Where the swapchain images are placed in the window, along the X and Y axes, when their
+    /// size doesn't match the size of the window.
+    ///
+    /// This is only used under the same conditions as `present_scaling`.
+    pub present_gravity: Option<[PresentGravity; 2]>,
     /// Sets whether the window is resizable.
     pub resizable: bool,
     /// Sets whether the window should have borders and bars.
@@ -342,6 +508,11 @@ pub struct WindowDescriptor {
     pub cursor_locked: bool,
     /// Sets the [`WindowMode`].
     pub mode: WindowMode,
+    /// The minimum time between the starts of two frames of the window, or `None` to render a
+    /// frame whenever one is requested.
+    ///
+    /// See [`VulkanoWindowRenderer::set_frame_interval`].
+    pub frame_interval: Option<Duration>,
     /// Sets whether the background of the window should be transparent.
     pub transparent: bool,
 }
@@ -357,11 +528,14 @@ impl Default for WindowDescriptor {
             resize_constraints: WindowResizeConstraints::default(),
             scale_factor_override: None,
             present_mode: PresentMode::Fifo,
+            present_scaling: None,
+            present_gravity: None,
             resizable: true,
             decorations: true,
             cursor_locked: false,
             cursor_visible: true,
             mode: WindowMode::Windowed,
+            frame_interval: None,
             transparent: false,
         }
     }
@@ -170,6 +170,12 @@ fn features_output(members: &[FeaturesMember]) -> TokenStream {
     });
     let arr_len = members.len();
 
+    let from_str_for_features_items = members.iter().map(|FeaturesMember { name, raw, .. }| {
+        quote! {
+            #raw => { features.#name = true; }
+        }
+    });
+
     let write_items = members.iter().map(
         |FeaturesMember {
              name,
@@ -445,6 +451,21 @@ fn features_output(members: &[FeaturesMember]) -> TokenStream {
             }
         }
 
+        impl<'a> FromIterator<&'a str> for DeviceFeatures {
+            fn from_iter<I>(iter: I) -> Self
+                where I: IntoIterator<Item = &'a str>
+            {
+                let mut features = Self::empty();
+                for name in iter {
+                    match name {
+                        #(#from_str_for_features_items)*
+                        _ => (),
+                    }
+                }
+                features
+            }
+        }
+
         impl IntoIterator for DeviceFeatures {
             type Item = (&'static str, bool);
             type IntoIter = std::array::IntoIter<Self::Item, #arr_len>;
@@ -368,6 +368,7 @@ fn formats_output(members: &[FormatMember]) -> TokenStream {
     quote! {
         /// An enumeration of all the possible formats.
         #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[repr(i32)]
         #[allow(non_camel_case_types)]
         #[non_exhaustive]
@@ -272,6 +272,17 @@ impl<'r> VkRegistryData<'r> {
                                         },
                                     ..
                                 }) if extends == "VkResult" => Some(name.as_str()),
+                                // Treat PipelineCompileRequired as an error condition, so that
+                                // creating a pipeline can fail with it
+                                InterfaceItem::Enum(Enum {
+                                    name,
+                                    spec: EnumSpec::Offset { extends, .. },
+                                    ..
+                                }) if extends == "VkResult"
+                                    && name == "VK_PIPELINE_COMPILE_REQUIRED" =>
+                                {
+                                    Some(name.as_str())
+                                }
                                 _ => None,
                             }));
                         }
@@ -111,6 +111,50 @@ struct OperandMember {
     name: Ident,
     ty: TokenStream,
     parse: TokenStream,
+    ids: Option<OperandIds>,
+}
+
+/// Where the `Id`s are in an operand whose type contains `Id`s.
+#[derive(Clone, Copy, Debug)]
+struct OperandIds {
+    kind: OperandIdsKind,
+    quantifier: Option<char>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum OperandIdsKind {
+    Id,
+    PairBoth,
+    PairFirst,
+    PairSecond,
+    SpecConstantInstruction,
+}
+
+impl OperandIds {
+    fn extend_ids(self, name: &Ident) -> TokenStream {
+        match (self.kind, self.quantifier) {
+            (OperandIdsKind::Id, None) => quote! { ids.push(*#name); },
+            (OperandIdsKind::Id, Some(_)) => quote! { ids.extend(#name.iter().copied()); },
+            (OperandIdsKind::PairBoth, None) => quote! { ids.extend([#name.0, #name.1]); },
+            (OperandIdsKind::PairBoth, Some(_)) => {
+                quote! { ids.extend(#name.iter().flat_map(|&(a, b)| [a, b])); }
+            }
+            (OperandIdsKind::PairFirst, None) => quote! { ids.push(#name.0); },
+            (OperandIdsKind::PairFirst, Some(_)) => {
+                quote! { ids.extend(#name.iter().map(|&(a, _)| a)); }
+            }
+            (OperandIdsKind::PairSecond, None) => quote! { ids.push(#name.1); },
+            (OperandIdsKind::PairSecond, Some(_)) => {
+                quote! { ids.extend(#name.iter().map(|&(_, b)| b)); }
+            }
+            (OperandIdsKind::SpecConstantInstruction, None) => {
+                quote! { ids.extend(#name.operand_ids()); }
+            }
+            (OperandIdsKind::SpecConstantInstruction, Some(_)) => {
+                quote! { ids.extend(#name.iter().flat_map(|x| x.operand_ids())); }
+            }
+        }
+    }
 }
 
 fn instruction_output(members: &[InstructionMember], spec_constant: bool) -> TokenStream {
@@ -454,6 +498,28 @@ fn instruction_output(members: &[InstructionMember], spec_constant: bool) -> Tok
         }
     };
 
+    let operand_ids_items = members.iter().filter_map(|member| {
+        let name = &member.name;
+        let id_operands: Vec<_> = member
+            .operands
+            .iter()
+            .filter_map(|operand| Some((&operand.name, operand.ids?)))
+            .collect();
+
+        if id_operands.is_empty() {
+            return None;
+        }
+
+        let names = id_operands.iter().map(|(name, _)| name);
+        let extend = id_operands.iter().map(|(name, ids)| ids.extend_ids(name));
+
+        Some(quote! {
+            Self::#name { #(#names,)* .. } => {
+                #(#extend)*
+            }
+        })
+    });
+
     let opcode_error = if spec_constant {
         format_ident!("UnknownSpecConstantOpcode")
     } else {
@@ -479,6 +545,22 @@ fn instruction_output(members: &[InstructionMember], spec_constant: bool) -> Tok
             }
 
             #result_fns
+
+            /// Returns the `Id` operands of the instruction, excluding `result_id` and
+            /// `result_type_id`.
+            ///
+            /// `Id`s that are parameters of an enumerant operand, such as those in image operands
+            /// or in the mode of an `ExecutionModeId` instruction, are not included.
+            pub fn operand_ids(&self) -> Vec<Id> {
+                let mut ids = Vec::new();
+
+                match self {
+                    #(#operand_ids_items)*
+                    _ => (),
+                }
+
+                ids
+            }
         }
     }
 }
@@ -564,7 +646,28 @@ fn instruction_members(grammar: &SpirvGrammar) -> Vec<InstructionMember> {
                         _ => parse.clone(),
                     };
 
-                    OperandMember { name, ty, parse }
+                    let ids = match operand.kind.as_str() {
+                        "IdResult" | "IdResultType" => None,
+                        "PairIdRefIdRef" => Some(OperandIdsKind::PairBoth),
+                        "PairIdRefLiteralInteger" => Some(OperandIdsKind::PairFirst),
+                        "PairLiteralIntegerIdRef" => Some(OperandIdsKind::PairSecond),
+                        "LiteralSpecConstantOpInteger" => {
+                            Some(OperandIdsKind::SpecConstantInstruction)
+                        }
+                        kind if kind.starts_with("Id") => Some(OperandIdsKind::Id),
+                        _ => None,
+                    }
+                    .map(|kind| OperandIds {
+                        kind,
+                        quantifier: operand.quantifier,
+                    });
+
+                    OperandMember {
+                        name,
+                        ty,
+                        parse,
+                        ids,
+                    }
                 })
                 .collect::<Vec<_>>();
 
@@ -759,7 +862,12 @@ fn bit_enum_members(grammar: &SpirvGrammar) -> Vec<(Ident, Vec<KindEnumMember>)>
                             );
                             let (ty, parse) = parameter_kinds[param.kind.as_str()].clone();
 
-                            OperandMember { name, ty, parse }
+                            OperandMember {
+                                name,
+                                ty,
+                                parse,
+                                ids: None,
+                            }
                         })
                         .collect();
 
@@ -911,7 +1019,12 @@ fn value_enum_members(grammar: &SpirvGrammar) -> Vec<(Ident, Vec<KindEnumMember>
                             );
                             let (ty, parse) = parameter_kinds[param.kind.as_str()].clone();
 
-                            OperandMember { name, ty, parse }
+                            OperandMember {
+                                name,
+                                ty,
+                                parse,
+                                ids: None,
+                            }
                         })
                         .collect();
 
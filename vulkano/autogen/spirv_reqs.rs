@@ -23,12 +23,15 @@ pub fn write(vk_data: &VkRegistryData<'_>, grammar: &SpirvGrammar) {
         .unwrap()
         .enumerants
         .as_slice();
-    let spirv_capabilities_output = spirv_reqs_output(
-        &spirv_capabilities_members(&vk_data.spirv_capabilities, grammar_enumerants),
-        false,
-    );
-    let spirv_extensions_output =
-        spirv_reqs_output(&spirv_extensions_members(&vk_data.spirv_extensions), true);
+    let spirv_capabilities_members =
+        spirv_capabilities_members(&vk_data.spirv_capabilities, grammar_enumerants);
+    let spirv_capabilities_output = spirv_reqs_output(&spirv_capabilities_members, false);
+    let spirv_extensions_members = spirv_extensions_members(&vk_data.spirv_extensions);
+    let spirv_capabilities_requirements_output =
+        spirv_requirements_output(&spirv_capabilities_members, false);
+    let spirv_extensions_output = spirv_reqs_output(&spirv_extensions_members, true);
+    let spirv_extensions_requirements_output =
+        spirv_requirements_output(&spirv_extensions_members, true);
     write_file(
         "spirv_reqs.rs",
         format!(
@@ -38,6 +41,8 @@ pub fn write(vk_data: &VkRegistryData<'_>, grammar: &SpirvGrammar) {
         quote! {
             #spirv_capabilities_output
             #spirv_extensions_output
+            #spirv_capabilities_requirements_output
+            #spirv_extensions_requirements_output
         },
     );
 }
@@ -243,6 +248,107 @@ fn spirv_reqs_output(members: &[SpirvReqsMember], is_extension: bool) -> TokenSt
     }
 }
 
+fn spirv_requirements_output(members: &[SpirvReqsMember], is_extension: bool) -> TokenStream {
+    let fn_def = if is_extension {
+        quote! { spirv_extension_requirements(item: &str) }
+    } else {
+        quote! { spirv_capability_requirements(item: Capability) }
+    };
+
+    let items = members.iter().map(
+        |SpirvReqsMember {
+             name,
+             requires_one_of,
+             requires_properties: _,
+         }| {
+            let arm = if is_extension {
+                quote! { #name }
+            } else {
+                let name = format_ident!("{}", name);
+                quote! { Capability::#name }
+            };
+
+            let &RequiresOneOf {
+                api_version,
+                ref device_extensions,
+                instance_extensions: _,
+                ref device_features,
+            } = requires_one_of;
+
+            let requires_one_of_items = api_version
+                .iter()
+                .map(|(major, minor)| {
+                    let version = format_ident!("V{}_{}", major, minor);
+                    quote! {
+                        crate::RequiresAllOf(&[
+                            crate::Requires::APIVersion(crate::Version::#version),
+                        ]),
+                    }
+                })
+                .chain(device_extensions.iter().map(|name| {
+                    quote! {
+                        crate::RequiresAllOf(&[
+                            crate::Requires::DeviceExtension(#name),
+                        ]),
+                    }
+                }))
+                .chain(device_features.iter().map(|name| {
+                    quote! {
+                        crate::RequiresAllOf(&[
+                            crate::Requires::DeviceFeature(#name),
+                        ]),
+                    }
+                }));
+            let alternatives_items = api_version
+                .iter()
+                .map(|(major, minor)| {
+                    let version = format_ident!("V{}_{}", major, minor);
+                    quote! {
+                        SpirvRequirement::APIVersion(crate::Version::#version),
+                    }
+                })
+                .chain(device_extensions.iter().map(|name| {
+                    let ident = format_ident!("{}", name);
+                    quote! {
+                        SpirvRequirement::DeviceExtensions(crate::device::DeviceExtensions {
+                            #ident: true,
+                            ..crate::device::DeviceExtensions::empty()
+                        }),
+                    }
+                }))
+                .chain(device_features.iter().map(|name| {
+                    let ident = format_ident!("{}", name);
+                    quote! {
+                        SpirvRequirement::DeviceFeatures(crate::device::DeviceFeatures {
+                            #ident: true,
+                            ..crate::device::DeviceFeatures::empty()
+                        }),
+                    }
+                }));
+
+            quote! {
+                #arm => Some((
+                    crate::RequiresOneOf(&[
+                        #(#requires_one_of_items)*
+                    ]),
+                    vec![
+                        #(#alternatives_items)*
+                    ],
+                )),
+            }
+        },
+    );
+
+    quote! {
+        fn #fn_def -> Option<(crate::RequiresOneOf, Vec<SpirvRequirement>)> {
+            match item {
+                #(#items)*
+                _ => None,
+            }
+        }
+    }
+}
+
 fn spirv_capabilities_members(
     capabilities: &[&SpirvExtOrCap],
     grammar_enumerants: &[SpirvKindEnumerant],
@@ -0,0 +1,216 @@
+// Compares the CPU time of recording many small commands with `RecordingCommandBuffer`, which
+// keeps every resource alive and registers every use per command, against recording them with
+// `RawRecordingCommandBuffer` and retiring the resources against an epoch once.
+//
+// Run with `cargo bench -p vulkano --bench epoch_tracking`.
+
+use std::{
+    hint::black_box,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use vulkano::{
+    buffer::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::{
+        allocator::{CommandBufferAllocator, StandardCommandBufferAllocator},
+        sys::RawRecordingCommandBuffer,
+        CommandBufferBeginInfo, CommandBufferLevel, CommandBufferUsage, RecordingCommandBuffer,
+    },
+    device::{
+        physical::PhysicalDeviceType, Device, DeviceCreateInfo, DeviceFeatures, QueueCreateInfo,
+        QueueFlags,
+    },
+    instance::{Instance, InstanceCreateFlags, InstanceCreateInfo},
+    memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator},
+    sync::{epoch::EpochTracker, semaphore::SemaphoreSignalInfo},
+    VulkanLibrary,
+};
+
+const COMMANDS: u64 = 10_000;
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let Some((device, queue_family_index)) = create_device() else {
+        println!("no device with the `timeline_semaphore` feature was found, skipping");
+        return;
+    };
+
+    let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+    let command_buffer_allocator: Arc<dyn CommandBufferAllocator> = Arc::new(
+        StandardCommandBufferAllocator::new(device.clone(), Default::default()),
+    );
+    let buffer = Buffer::new_slice::<u32>(
+        memory_allocator,
+        BufferCreateInfo {
+            usage: BufferUsage::TRANSFER_DST,
+            ..Default::default()
+        },
+        AllocationCreateInfo {
+            memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+            ..Default::default()
+        },
+        COMMANDS,
+    )
+    .unwrap();
+    let dst_buffers: Vec<_> = (0..COMMANDS)
+        .map(|index| buffer.clone().slice(index..index + 1))
+        .collect();
+    let tracker = EpochTracker::new(device.clone()).unwrap();
+
+    let tracked = measure(|| {
+        record_tracked(&command_buffer_allocator, queue_family_index, &dst_buffers);
+    });
+    let epoch = measure(|| {
+        record_epoch(
+            &command_buffer_allocator,
+            queue_family_index,
+            &buffer,
+            &dst_buffers,
+            &tracker,
+        );
+    });
+
+    println!("{COMMANDS} fill_buffer commands, best of {ITERATIONS} iterations:");
+    report("RecordingCommandBuffer", tracked);
+    report("RawRecordingCommandBuffer + EpochTracker", epoch);
+    println!(
+        "per-command tracking takes {:.1}x as long",
+        tracked.as_secs_f64() / epoch.as_secs_f64(),
+    );
+}
+
+fn create_device() -> Option<(Arc<Device>, u32)> {
+    let library = VulkanLibrary::new().ok()?;
+    let instance = Instance::new(
+        library,
+        InstanceCreateInfo {
+            flags: InstanceCreateFlags::ENUMERATE_PORTABILITY,
+            ..Default::default()
+        },
+    )
+    .ok()?;
+
+    let enabled_features = DeviceFeatures {
+        timeline_semaphore: true,
+        ..DeviceFeatures::empty()
+    };
+    let (physical_device, queue_family_index) = instance
+        .enumerate_physical_devices()
+        .ok()?
+        .filter(|p| p.supported_features().contains(&enabled_features))
+        .filter_map(|p| {
+            p.queue_family_properties()
+                .iter()
+                .position(|q| q.queue_flags.intersects(QueueFlags::GRAPHICS))
+                .map(|i| (p, i as u32))
+        })
+        .min_by_key(|(p, _)| match p.properties().device_type {
+            PhysicalDeviceType::DiscreteGpu => 0,
+            PhysicalDeviceType::IntegratedGpu => 1,
+            PhysicalDeviceType::VirtualGpu => 2,
+            PhysicalDeviceType::Cpu => 3,
+            PhysicalDeviceType::Other => 4,
+            _ => 5,
+        })?;
+
+    let (device, _) = Device::new(
+        physical_device,
+        DeviceCreateInfo {
+            enabled_features,
+            queue_create_infos: vec![QueueCreateInfo {
+                queue_family_index,
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+    )
+    .ok()?;
+
+    Some((device, queue_family_index))
+}
+
+// Every command clones the subbuffer into the command buffer, and registers its use for automatic
+// synchronization.
+fn record_tracked(
+    command_buffer_allocator: &Arc<dyn CommandBufferAllocator>,
+    queue_family_index: u32,
+    dst_buffers: &[Subbuffer<[u32]>],
+) {
+    let mut builder = RecordingCommandBuffer::new(
+        command_buffer_allocator.clone(),
+        queue_family_index,
+        CommandBufferLevel::Primary,
+        CommandBufferBeginInfo {
+            usage: CommandBufferUsage::OneTimeSubmit,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    for dst_buffer in dst_buffers {
+        builder.fill_buffer(dst_buffer.clone(), 0).unwrap();
+    }
+
+    black_box(builder.end().unwrap());
+}
+
+// The commands only borrow the subbuffers, and the buffer is kept alive by retiring it against
+// the epoch once.
+fn record_epoch(
+    command_buffer_allocator: &Arc<dyn CommandBufferAllocator>,
+    queue_family_index: u32,
+    buffer: &Subbuffer<[u32]>,
+    dst_buffers: &[Subbuffer<[u32]>],
+    tracker: &EpochTracker,
+) {
+    let epoch = tracker.next_epoch();
+    let mut builder = RawRecordingCommandBuffer::new(
+        command_buffer_allocator.clone(),
+        queue_family_index,
+        CommandBufferLevel::Primary,
+        CommandBufferBeginInfo {
+            usage: CommandBufferUsage::OneTimeSubmit,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    for dst_buffer in dst_buffers {
+        unsafe { builder.fill_buffer(dst_buffer, 0) }.unwrap();
+    }
+
+    tracker.retire(epoch, buffer.clone());
+    black_box(unsafe { builder.end() }.unwrap());
+
+    // Nothing is submitted, so complete the epoch from the host.
+    unsafe {
+        tracker.semaphore().signal(SemaphoreSignalInfo {
+            value: epoch.value(),
+            ..Default::default()
+        })
+    }
+    .unwrap();
+    tracker.collect().unwrap();
+}
+
+fn measure(mut f: impl FnMut()) -> Duration {
+    // Warm up the allocators.
+    f();
+
+    (0..ITERATIONS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(name: &str, duration: Duration) {
+    println!(
+        "  {name}: {duration:?} ({:?} per command)",
+        duration / COMMANDS as u32,
+    );
+}
@@ -17,6 +17,27 @@ fn main() {
         println!("cargo:rustc-link-lib=framework=Foundation");
     }
 
+    if target.contains("apple-darwin") && env::var_os("CARGO_FEATURE_MOLTENVK_STATIC").is_some() {
+        // The static library is looked for in `MOLTENVK_LIB_DIR` if it is set, and in the
+        // default library search paths otherwise.
+        println!("cargo:rerun-if-env-changed=MOLTENVK_LIB_DIR");
+        if let Some(lib_dir) = env::var_os("MOLTENVK_LIB_DIR") {
+            println!(
+                "cargo:rustc-link-search=native={}",
+                lib_dir.to_string_lossy()
+            );
+        }
+        println!("cargo:rustc-link-lib=c++");
+        println!("cargo:rustc-link-lib=static=MoltenVK");
+        println!("cargo:rustc-link-lib=framework=Metal");
+        println!("cargo:rustc-link-lib=framework=IOSurface");
+        println!("cargo:rustc-link-lib=framework=CoreGraphics");
+        println!("cargo:rustc-link-lib=framework=QuartzCore");
+        println!("cargo:rustc-link-lib=framework=AppKit");
+        println!("cargo:rustc-link-lib=framework=IOKit");
+        println!("cargo:rustc-link-lib=framework=Foundation");
+    }
+
     // Run autogen
     println!("cargo:rerun-if-changed=vk.xml");
     println!("cargo:rerun-if-changed=spirv.core.grammar.json");
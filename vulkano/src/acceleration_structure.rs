@@ -64,6 +64,17 @@
 //! An update operation may not change the inactive status of an item: active items must remain
 //! active in the update and inactive items must remain inactive.
 //!
+//! # Motion
+//!
+//! With the [`ray_tracing_motion_blur`] feature, an acceleration structure can contain geometry
+//! that moves over the time interval from 0.0 to 1.0, so that rays traced at different times see
+//! the scene at different positions. The acceleration structure must be created with
+//! [`AccelerationStructureCreateFlags::MOTION`] and built with
+//! [`BuildAccelerationStructureFlags::MOTION`]. Bottom-level structures can then specify the
+//! triangle vertices at time 1.0 in
+//! [`AccelerationStructureGeometryTrianglesData::motion_vertex_data`], and top-level structures
+//! use [`AccelerationStructureMotionInstance`] in place of [`AccelerationStructureInstance`].
+//!
 //! # Accessing an acceleration structure in a shader
 //!
 //! Acceleration structures can be bound to and accessed in any shader type. They are accessed
@@ -79,10 +90,26 @@
 //! acceleration structure to a descriptor set using
 //! [`WriteDescriptorSet::acceleration_structure`].
 //!
+//! ## Fetching triangle vertex positions
+//!
+//! If the [`ray_tracing_position_fetch`] feature is enabled on the device, then shaders can read
+//! the vertex positions of the triangle that a ray hit, directly from the acceleration structure.
+//! In GLSL, this uses the `GL_EXT_ray_tracing_position_fetch` extension, which provides the
+//! `gl_HitTriangleVertexPositionsEXT` built-in in hit shaders, and the
+//! `rayQueryGetIntersectionTriangleVertexPositionsEXT` function for ray queries. This avoids
+//! having to bind a separate copy of the vertex data to the shader.
+//!
+//! The bottom-level acceleration structures that are read from in this way must have been built
+//! with the [`BuildAccelerationStructureFlags::ALLOW_DATA_ACCESS`] flag. When creating a shader
+//! module, Vulkano checks that the feature is enabled if the shader declares the
+//! `RayTracingPositionFetchKHR` capability.
+//!
 //! [`build_acceleration_structure`]: crate::command_buffer::RecordingCommandBuffer::build_acceleration_structure
 //! [`build_acceleration_structure_indirect`]: crate::command_buffer::RecordingCommandBuffer::build_acceleration_structure_indirect
 //! [`DescriptorType::AccelerationStructure`]: crate::descriptor_set::layout::DescriptorType::AccelerationStructure
 //! [`WriteDescriptorSet::acceleration_structure`]: crate::descriptor_set::WriteDescriptorSet::acceleration_structure
+//! [`ray_tracing_motion_blur`]: crate::device::DeviceFeatures::ray_tracing_motion_blur
+//! [`ray_tracing_position_fetch`]: crate::device::DeviceFeatures::ray_tracing_position_fetch
 
 use crate::{
     buffer::{BufferUsage, IndexBuffer, Subbuffer},
@@ -90,11 +117,12 @@ use crate::{
     format::{Format, FormatFeatures},
     instance::InstanceOwnedDebugWrapper,
     macros::{impl_id_counter, vulkan_bitflags, vulkan_enum},
+    micromap::{Micromap, MicromapType, MicromapUsage},
     DeviceAddress, DeviceSize, NonNullDeviceAddress, Packed24_8, Requires, RequiresAllOf,
     RequiresOneOf, Validated, ValidationError, VulkanError, VulkanObject,
 };
 use bytemuck::{Pod, Zeroable};
-use std::{fmt::Debug, hash::Hash, mem::MaybeUninit, num::NonZeroU64, ptr, sync::Arc};
+use std::{ffi::c_void, fmt::Debug, hash::Hash, mem::MaybeUninit, num::NonZeroU64, ptr, sync::Arc};
 
 /// An opaque data structure that is used to accelerate spatial queries on geometry data.
 #[derive(Debug)]
@@ -169,10 +197,11 @@ impl AccelerationStructure {
             create_flags,
             ref buffer,
             ty,
+            motion_max_instances,
             _ne: _,
         } = &create_info;
 
-        let create_info_vk = ash::vk::AccelerationStructureCreateInfoKHR {
+        let mut create_info_vk = ash::vk::AccelerationStructureCreateInfoKHR {
             create_flags: create_flags.into(),
             buffer: buffer.buffer().handle(),
             offset: buffer.offset(),
@@ -181,6 +210,17 @@ impl AccelerationStructure {
             device_address: 0, // TODO: allow user to specify
             ..Default::default()
         };
+        let mut motion_info_vk = None;
+
+        if let Some(max_instances) = motion_max_instances {
+            let next = motion_info_vk.insert(ash::vk::AccelerationStructureMotionInfoNV {
+                max_instances,
+                ..Default::default()
+            });
+
+            next.p_next = create_info_vk.p_next;
+            create_info_vk.p_next = <*const _>::cast(next);
+        }
 
         let handle = {
             let fns = device.fns();
@@ -215,6 +255,7 @@ impl AccelerationStructure {
             create_flags,
             buffer,
             ty,
+            motion_max_instances: _,
             _ne: _,
         } = create_info;
 
@@ -345,6 +386,16 @@ pub struct AccelerationStructureCreateInfo {
     /// The default value is [`AccelerationStructureType::Generic`].
     pub ty: AccelerationStructureType,
 
+    /// If `create_flags` includes [`AccelerationStructureCreateFlags::MOTION`], specifies the
+    /// maximum number of instances that can be used when building the acceleration structure as
+    /// a top-level acceleration structure.
+    ///
+    /// If this is `Some`, then `create_flags` must include
+    /// [`AccelerationStructureCreateFlags::MOTION`].
+    ///
+    /// The default value is `None`.
+    pub motion_max_instances: Option<u32>,
+
     /* TODO: enable
     // TODO: document
     pub device_address: DeviceAddress, */
@@ -359,6 +410,7 @@ impl AccelerationStructureCreateInfo {
             create_flags: AccelerationStructureCreateFlags::empty(),
             buffer,
             ty: AccelerationStructureType::Generic,
+            motion_max_instances: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -368,6 +420,7 @@ impl AccelerationStructureCreateInfo {
             create_flags,
             ref buffer,
             ty,
+            motion_max_instances,
             _ne: _,
         } = self;
 
@@ -376,6 +429,30 @@ impl AccelerationStructureCreateInfo {
                 .set_vuids(&["VUID-VkAccelerationStructureCreateInfoKHR-createFlags-parameter"])
         })?;
 
+        if create_flags.intersects(AccelerationStructureCreateFlags::MOTION)
+            && !device.enabled_features().ray_tracing_motion_blur
+        {
+            return Err(Box::new(ValidationError {
+                context: "create_flags".into(),
+                problem: "contains `AccelerationStructureCreateFlags::MOTION`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "ray_tracing_motion_blur",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        if motion_max_instances.is_some()
+            && !create_flags.intersects(AccelerationStructureCreateFlags::MOTION)
+        {
+            return Err(Box::new(ValidationError {
+                problem: "`motion_max_instances` is `Some`, but `create_flags` does not contain \
+                    `AccelerationStructureCreateFlags::MOTION`"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
         ty.validate_device(device).map_err(|err| {
             err.add_context("ty")
                 .set_vuids(&["VUID-VkAccelerationStructureCreateInfoKHR-type-parameter"])
@@ -429,12 +506,17 @@ vulkan_bitflags! {
         RequiresAllOf([DeviceExtension(ext_descriptor_buffer)]),
     ]),*/
 
-    /* TODO: enable
-    // TODO: document
+    /// The acceleration structure can be built with
+    /// [`BuildAccelerationStructureFlags::MOTION`], so that it can contain motion instances or
+    /// motion triangles.
+    ///
+    /// The [`ray_tracing_motion_blur`] feature must be enabled on the device.
+    ///
+    /// [`ray_tracing_motion_blur`]: crate::device::DeviceFeatures::ray_tracing_motion_blur
     MOTION = MOTION_NV
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(nv_ray_tracing_motion_blur)]),
-    ]),*/
+    ]),
 }
 
 /// Geometries and other parameters for an acceleration structure build operation.
@@ -570,6 +652,69 @@ impl AccelerationStructureBuildGeometryInfo {
         if let Some(dst_acceleration_structure) = dst_acceleration_structure {
             // VUID-VkAccelerationStructureBuildGeometryInfoKHR-commonparent
             assert_eq!(device, dst_acceleration_structure.device().as_ref());
+
+            if flags.intersects(BuildAccelerationStructureFlags::MOTION)
+                && !dst_acceleration_structure
+                    .create_flags()
+                    .intersects(AccelerationStructureCreateFlags::MOTION)
+            {
+                return Err(Box::new(ValidationError {
+                    problem: "`flags` contains `BuildAccelerationStructureFlags::MOTION`, but \
+                        `dst_acceleration_structure` was not created with \
+                        `AccelerationStructureCreateFlags::MOTION`"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        match geometries {
+            AccelerationStructureGeometries::Triangles(geometries) => {
+                if !flags.intersects(BuildAccelerationStructureFlags::MOTION) {
+                    if let Some(index) = geometries
+                        .iter()
+                        .position(|triangles_data| triangles_data.motion_vertex_data.is_some())
+                    {
+                        return Err(Box::new(ValidationError {
+                            problem: format!(
+                                "`geometries[{}].motion_vertex_data` is `Some`, but `flags` does \
+                                not contain `BuildAccelerationStructureFlags::MOTION`",
+                                index
+                            )
+                            .into(),
+                            ..Default::default()
+                        }));
+                    }
+                }
+            }
+            AccelerationStructureGeometries::Aabbs(_) => (),
+            AccelerationStructureGeometries::Instances(instances_data) => {
+                match (
+                    &instances_data.data,
+                    flags.intersects(BuildAccelerationStructureFlags::MOTION),
+                ) {
+                    (AccelerationStructureGeometryInstancesDataType::Values(_), true) => {
+                        return Err(Box::new(ValidationError {
+                            problem: "`flags` contains `BuildAccelerationStructureFlags::MOTION`, \
+                                but `geometries.data` is \
+                                `AccelerationStructureGeometryInstancesDataType::Values`"
+                                .into(),
+                            ..Default::default()
+                        }));
+                    }
+                    (AccelerationStructureGeometryInstancesDataType::MotionValues(_), false) => {
+                        return Err(Box::new(ValidationError {
+                            problem: "`geometries.data` is \
+                                `AccelerationStructureGeometryInstancesDataType::MotionValues`, \
+                                but `flags` does not contain \
+                                `BuildAccelerationStructureFlags::MOTION`"
+                                .into(),
+                            ..Default::default()
+                        }));
+                    }
+                    _ => (),
+                }
+            }
         }
 
         if let BuildAccelerationStructureMode::Update(src_acceleration_structure) = mode {
@@ -598,6 +743,48 @@ impl AccelerationStructureBuildGeometryInfo {
     ) -> (
         ash::vk::AccelerationStructureBuildGeometryInfoKHR<'static>,
         Vec<ash::vk::AccelerationStructureGeometryKHR<'static>>,
+        AccelerationStructureGeometriesExtensionsVk,
+    ) {
+        self.to_vulkan_with(
+            |buffer| ash::vk::DeviceOrHostAddressConstKHR {
+                device_address: buffer.device_address().unwrap().get(),
+            },
+            |buffer| ash::vk::DeviceOrHostAddressKHR {
+                device_address: buffer.device_address().unwrap().get(),
+            },
+        )
+    }
+
+    /// Same as `to_vulkan`, but uses host addresses for use with host build commands.
+    pub(crate) fn to_vulkan_host(
+        &self,
+    ) -> (
+        ash::vk::AccelerationStructureBuildGeometryInfoKHR<'static>,
+        Vec<ash::vk::AccelerationStructureGeometryKHR<'static>>,
+        AccelerationStructureGeometriesExtensionsVk,
+    ) {
+        self.to_vulkan_with(
+            |buffer| ash::vk::DeviceOrHostAddressConstKHR {
+                host_address: buffer
+                    .mapped_slice()
+                    .map_or(ptr::null(), |ptr| ptr.as_ptr() as *const c_void),
+            },
+            |buffer| ash::vk::DeviceOrHostAddressKHR {
+                host_address: buffer
+                    .mapped_slice()
+                    .map_or(ptr::null_mut(), |ptr| ptr.as_ptr() as *mut c_void),
+            },
+        )
+    }
+
+    fn to_vulkan_with(
+        &self,
+        const_address: impl Fn(&Subbuffer<[u8]>) -> ash::vk::DeviceOrHostAddressConstKHR,
+        address: impl Fn(&Subbuffer<[u8]>) -> ash::vk::DeviceOrHostAddressKHR,
+    ) -> (
+        ash::vk::AccelerationStructureBuildGeometryInfoKHR<'static>,
+        Vec<ash::vk::AccelerationStructureGeometryKHR<'static>>,
+        AccelerationStructureGeometriesExtensionsVk,
     ) {
         let &Self {
             flags,
@@ -608,12 +795,53 @@ impl AccelerationStructureBuildGeometryInfo {
             _ne: _,
         } = self;
 
+        // The extension structs are referenced by pointer from the geometries, so they must be
+        // created first, and must not move afterwards.
+        let mut extensions_vk = AccelerationStructureGeometriesExtensionsVk {
+            triangles_vk: match geometries {
+                AccelerationStructureGeometries::Triangles(geometries) => geometries
+                    .iter()
+                    .map(
+                        |triangles_data| AccelerationStructureTrianglesExtensionsVk {
+                            motion_vk: triangles_data.motion_vertex_data.as_ref().map(
+                                |motion_vertex_data| {
+                                    ash::vk::AccelerationStructureGeometryMotionTrianglesDataNV {
+                                        vertex_data: const_address(motion_vertex_data),
+                                        ..Default::default()
+                                    }
+                                },
+                            ),
+                            opacity_micromap_vk: triangles_data
+                                .opacity_micromap
+                                .as_ref()
+                                .map(|opacity_micromap| opacity_micromap.to_vulkan(&const_address)),
+                        },
+                    )
+                    .collect(),
+                _ => Vec::new(),
+            },
+        };
+
+        for triangles_vk in &mut extensions_vk.triangles_vk {
+            let AccelerationStructureTrianglesExtensionsVk {
+                motion_vk,
+                opacity_micromap_vk,
+            } = triangles_vk;
+
+            if let (Some(motion_vk), Some((opacity_micromap_vk, _))) =
+                (motion_vk, opacity_micromap_vk)
+            {
+                motion_vk.p_next = <*const _>::cast(opacity_micromap_vk);
+            }
+        }
+
         let (ty, geometries_vk): (_, Vec<_>) = match geometries {
             AccelerationStructureGeometries::Triangles(geometries) => (
                 ash::vk::AccelerationStructureTypeKHR::BOTTOM_LEVEL,
                 geometries
                     .iter()
-                    .map(|triangles_data| {
+                    .zip(&extensions_vk.triangles_vk)
+                    .map(|(triangles_data, triangles_vk)| {
                         let &AccelerationStructureGeometryTrianglesData {
                             flags,
                             vertex_format,
@@ -622,6 +850,8 @@ impl AccelerationStructureBuildGeometryInfo {
                             max_vertex,
                             ref index_data,
                             ref transform_data,
+                            motion_vertex_data: _,
+                            opacity_micromap: _,
                             _ne,
                         } = triangles_data;
 
@@ -629,15 +859,11 @@ impl AccelerationStructureBuildGeometryInfo {
                             geometry_type: ash::vk::GeometryTypeKHR::TRIANGLES,
                             geometry: ash::vk::AccelerationStructureGeometryDataKHR {
                                 triangles: ash::vk::AccelerationStructureGeometryTrianglesDataKHR {
+                                    p_next: triangles_vk.p_next(),
                                     vertex_format: vertex_format.into(),
-                                    vertex_data: ash::vk::DeviceOrHostAddressConstKHR {
-                                        device_address: vertex_data.as_ref().map_or(
-                                            0,
-                                            |vertex_data| {
-                                                vertex_data.device_address().unwrap().into()
-                                            },
-                                        ),
-                                    },
+                                    vertex_data: vertex_data
+                                        .as_ref()
+                                        .map_or(Default::default(), &const_address),
                                     vertex_stride: vertex_stride as DeviceSize,
                                     max_vertex,
                                     index_type: index_data
@@ -645,26 +871,16 @@ impl AccelerationStructureBuildGeometryInfo {
                                         .map_or(ash::vk::IndexType::NONE_KHR, |index_data| {
                                             index_data.index_type().into()
                                         }),
-                                    index_data: ash::vk::DeviceOrHostAddressConstKHR {
-                                        device_address: index_data.as_ref().map_or(
-                                            0,
-                                            |index_data| {
-                                                index_data
-                                                    .as_bytes()
-                                                    .device_address()
-                                                    .unwrap()
-                                                    .get()
-                                            },
-                                        ),
-                                    },
-                                    transform_data: ash::vk::DeviceOrHostAddressConstKHR {
-                                        device_address: transform_data.as_ref().map_or(
-                                            0,
-                                            |transform_data| {
-                                                transform_data.device_address().unwrap().get()
-                                            },
-                                        ),
-                                    },
+                                    index_data: index_data
+                                        .as_ref()
+                                        .map_or(Default::default(), |index_data| {
+                                            const_address(index_data.as_bytes())
+                                        }),
+                                    transform_data: transform_data
+                                        .as_ref()
+                                        .map_or(Default::default(), |transform_data| {
+                                            const_address(transform_data.as_bytes())
+                                        }),
                                     ..Default::default()
                                 },
                             },
@@ -690,11 +906,9 @@ impl AccelerationStructureBuildGeometryInfo {
                             geometry_type: ash::vk::GeometryTypeKHR::AABBS,
                             geometry: ash::vk::AccelerationStructureGeometryDataKHR {
                                 aabbs: ash::vk::AccelerationStructureGeometryAabbsDataKHR {
-                                    data: ash::vk::DeviceOrHostAddressConstKHR {
-                                        device_address: data.as_ref().map_or(0, |data| {
-                                            data.device_address().unwrap().into()
-                                        }),
-                                    },
+                                    data: data.as_ref().map_or(Default::default(), |data| {
+                                        const_address(data.as_bytes())
+                                    }),
                                     stride: stride as DeviceSize,
                                     ..Default::default()
                                 },
@@ -716,19 +930,18 @@ impl AccelerationStructureBuildGeometryInfo {
                     let (array_of_pointers, data) = match data {
                         AccelerationStructureGeometryInstancesDataType::Values(data) => (
                             ash::vk::FALSE,
-                            ash::vk::DeviceOrHostAddressConstKHR {
-                                device_address: data
-                                    .as_ref()
-                                    .map_or(0, |data| data.device_address().unwrap().into()),
-                            },
+                            data.as_ref()
+                                .map_or(Default::default(), |data| const_address(data.as_bytes())),
+                        ),
+                        AccelerationStructureGeometryInstancesDataType::MotionValues(data) => (
+                            ash::vk::FALSE,
+                            data.as_ref()
+                                .map_or(Default::default(), |data| const_address(data.as_bytes())),
                         ),
                         AccelerationStructureGeometryInstancesDataType::Pointers(data) => (
                             ash::vk::TRUE,
-                            ash::vk::DeviceOrHostAddressConstKHR {
-                                device_address: data
-                                    .as_ref()
-                                    .map_or(0, |data| data.device_address().unwrap().into()),
-                            },
+                            data.as_ref()
+                                .map_or(Default::default(), |data| const_address(data.as_bytes())),
                         ),
                     };
 
@@ -767,18 +980,45 @@ impl AccelerationStructureBuildGeometryInfo {
                 geometry_count: 0,
                 p_geometries: ptr::null(),
                 pp_geometries: ptr::null(),
-                scratch_data: ash::vk::DeviceOrHostAddressKHR {
-                    device_address: scratch_data.as_ref().map_or(0, |scratch_data| {
-                        scratch_data.device_address().unwrap().into()
-                    }),
-                },
+                scratch_data: scratch_data.as_ref().map_or(Default::default(), &address),
                 ..Default::default()
             },
             geometries_vk,
+            extensions_vk,
         )
     }
 }
 
+/// Extension structs for the geometries of an acceleration structure build, that must be kept
+/// alive for as long as the Vulkan geometries are used.
+pub(crate) struct AccelerationStructureGeometriesExtensionsVk {
+    triangles_vk: Vec<AccelerationStructureTrianglesExtensionsVk>,
+}
+
+// The raw pointers only point into the struct's own heap allocations.
+unsafe impl Send for AccelerationStructureGeometriesExtensionsVk {}
+unsafe impl Sync for AccelerationStructureGeometriesExtensionsVk {}
+
+struct AccelerationStructureTrianglesExtensionsVk {
+    motion_vk: Option<ash::vk::AccelerationStructureGeometryMotionTrianglesDataNV<'static>>,
+    opacity_micromap_vk: Option<(
+        ash::vk::AccelerationStructureTrianglesOpacityMicromapEXT<'static>,
+        Vec<ash::vk::MicromapUsageEXT>,
+    )>,
+}
+
+impl AccelerationStructureTrianglesExtensionsVk {
+    fn p_next(&self) -> *const c_void {
+        if let Some(motion_vk) = &self.motion_vk {
+            <*const _>::cast(motion_vk)
+        } else if let Some((opacity_micromap_vk, _)) = &self.opacity_micromap_vk {
+            <*const _>::cast(opacity_micromap_vk)
+        } else {
+            ptr::null()
+        }
+    }
+}
+
 vulkan_bitflags! {
     #[non_exhaustive]
 
@@ -807,33 +1047,50 @@ vulkan_bitflags! {
     /// times or suboptimal trace performance.
     LOW_MEMORY = LOW_MEMORY,
 
-    /* TODO: enable
-    // TODO: document
+    /// Allows shaders to read the vertex positions of the triangles in the acceleration
+    /// structure, using the `HitTriangleVertexPositionsKHR` built-in or the
+    /// `OpRayQueryGetIntersectionTriangleVertexPositionsKHR` instruction.
+    ///
+    /// Reading the positions of a triangle in an acceleration structure that was not built with
+    /// this flag is undefined behavior.
+    ALLOW_DATA_ACCESS = ALLOW_DATA_ACCESS
+    RequiresOneOf([
+        RequiresAllOf([DeviceExtension(khr_ray_tracing_position_fetch)]),
+    ]),
+
+    /// The acceleration structure is built with motion over time. For a bottom-level
+    /// acceleration structure, this allows triangle geometries to specify
+    /// [`AccelerationStructureGeometryTrianglesData::motion_vertex_data`]. For a top-level
+    /// acceleration structure, the instances are [`AccelerationStructureMotionInstance`] values
+    /// instead of [`AccelerationStructureInstance`] values.
+    ///
+    /// The destination acceleration structure must have been created with
+    /// [`AccelerationStructureCreateFlags::MOTION`].
     MOTION = MOTION_NV
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(nv_ray_tracing_motion_blur)]),
-    ]), */
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Allows the opacity micromaps attached to the geometries to be replaced when updating
+    /// the acceleration structure.
     ALLOW_OPACITY_MICROMAP_UPDATE = ALLOW_OPACITY_MICROMAP_UPDATE_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_opacity_micromap)]),
-    ]), */
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Allows [`GeometryInstanceFlags::DISABLE_OPACITY_MICROMAPS`] to be used on instances that
+    /// refer to this acceleration structure.
     ALLOW_DISABLE_OPACITY_MICROMAPS = ALLOW_DISABLE_OPACITY_MICROMAPS_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_opacity_micromap)]),
-    ]), */
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Allows the data of the attached opacity micromaps to be changed, after which the
+    /// acceleration structure must be updated.
     ALLOW_OPACITY_MICROMAP_DATA_UPDATE = ALLOW_OPACITY_MICROMAP_DATA_UPDATE_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_opacity_micromap)]),
-    ]), */
+    ]),
 
     /* TODO: enable
     // TODO: document
@@ -983,6 +1240,26 @@ pub struct AccelerationStructureGeometryTrianglesData {
     /// The default value is `None`.
     pub transform_data: Option<Subbuffer<TransformMatrix>>,
 
+    /// Optionally, the vertex data at time 1.0, for a triangle geometry that moves over time.
+    /// The vertices in `vertex_data` are then used at time 0.0, and the positions at other times
+    /// are interpolated between the two. `motion_vertex_data` must have the same format, stride
+    /// and number of vertices as `vertex_data`.
+    ///
+    /// If this is `Some`, then the acceleration structure must be built with
+    /// [`BuildAccelerationStructureFlags::MOTION`].
+    ///
+    /// The default value is `None`.
+    pub motion_vertex_data: Option<Subbuffer<[u8]>>,
+
+    /// Optionally, an opacity micromap that is attached to the triangles.
+    ///
+    /// If this is `Some`, the [`ext_opacity_micromap`] extension must be enabled on the device.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`ext_opacity_micromap`]: crate::device::DeviceExtensions::ext_opacity_micromap
+    pub opacity_micromap: Option<AccelerationStructureTrianglesOpacityMicromap>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -999,6 +1276,8 @@ impl AccelerationStructureGeometryTrianglesData {
             max_vertex: 0,
             index_data: None,
             transform_data: None,
+            motion_vertex_data: None,
+            opacity_micromap: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -1012,6 +1291,8 @@ impl AccelerationStructureGeometryTrianglesData {
             max_vertex: _,
             ref index_data,
             transform_data: _,
+            ref motion_vertex_data,
+            ref opacity_micromap,
             _ne: _,
         } = self;
 
@@ -1074,10 +1355,168 @@ impl AccelerationStructureGeometryTrianglesData {
             }
         }
 
+        if motion_vertex_data.is_some() && !device.enabled_extensions().nv_ray_tracing_motion_blur {
+            return Err(Box::new(ValidationError {
+                context: "motion_vertex_data".into(),
+                problem: "is `Some`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "nv_ray_tracing_motion_blur",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        if let Some(opacity_micromap) = opacity_micromap {
+            if !device.enabled_extensions().ext_opacity_micromap {
+                return Err(Box::new(ValidationError {
+                    context: "opacity_micromap".into(),
+                    problem: "is `Some`".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                        "ext_opacity_micromap",
+                    )])]),
+                    ..Default::default()
+                }));
+            }
+
+            opacity_micromap
+                .validate(device)
+                .map_err(|err| err.add_context("opacity_micromap"))?;
+        }
+
         Ok(())
     }
 }
 
+/// An opacity micromap that is attached to a triangle geometry.
+#[derive(Clone, Debug)]
+pub struct AccelerationStructureTrianglesOpacityMicromap {
+    /// The micromap to attach.
+    ///
+    /// There is no default value.
+    pub micromap: Arc<Micromap>,
+
+    /// The indices of the micromap triangles that are used by each triangle of the geometry.
+    /// An index can also be one of the values of [`OpacityMicromapSpecialIndex`], as returned by
+    /// [`OpacityMicromapSpecialIndex::as_u32`].
+    ///
+    /// [`OpacityMicromapSpecialIndex`]: crate::micromap::OpacityMicromapSpecialIndex
+    /// [`OpacityMicromapSpecialIndex::as_u32`]: crate::micromap::OpacityMicromapSpecialIndex::as_u32
+    ///
+    /// If this is `None`, each triangle uses the micromap triangle with the same index as the
+    /// triangle itself.
+    ///
+    /// The default value is `None`.
+    pub index_data: Option<IndexBuffer>,
+
+    /// A value that is added to every non-special index in `index_data`.
+    ///
+    /// The default value is 0.
+    pub base_triangle: u32,
+
+    /// How many triangles of the geometry use each combination of format and subdivision level.
+    ///
+    /// The default value is empty.
+    pub usage_counts: Vec<MicromapUsage>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl AccelerationStructureTrianglesOpacityMicromap {
+    /// Returns a `AccelerationStructureTrianglesOpacityMicromap` with the specified `micromap`.
+    #[inline]
+    pub fn new(micromap: Arc<Micromap>) -> Self {
+        Self {
+            micromap,
+            index_data: None,
+            base_triangle: 0,
+            usage_counts: Vec::new(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+
+    pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
+        let &Self {
+            ref micromap,
+            ref index_data,
+            base_triangle: _,
+            ref usage_counts,
+            _ne: _,
+        } = self;
+
+        // VUID-VkAccelerationStructureTrianglesOpacityMicromapEXT-micromap-parameter
+        assert_eq!(device, micromap.device().as_ref());
+
+        if micromap.ty() != MicromapType::OpacityMicromap {
+            return Err(Box::new(ValidationError {
+                context: "micromap".into(),
+                problem: "is not an opacity micromap".into(),
+                ..Default::default()
+            }));
+        }
+
+        if let Some(index_data) = index_data {
+            if !matches!(index_data, IndexBuffer::U16(_) | IndexBuffer::U32(_)) {
+                return Err(Box::new(ValidationError {
+                    context: "index_data".into(),
+                    problem: "is not `IndexBuffer::U16` or `IndexBuffer::U32`".into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        for (index, usage_count) in usage_counts.iter().enumerate() {
+            usage_count
+                .validate(device)
+                .map_err(|err| err.add_context(format!("usage_counts[{}]", index)))?;
+        }
+
+        Ok(())
+    }
+
+    fn to_vulkan(
+        &self,
+        const_address: impl Fn(&Subbuffer<[u8]>) -> ash::vk::DeviceOrHostAddressConstKHR,
+    ) -> (
+        ash::vk::AccelerationStructureTrianglesOpacityMicromapEXT<'static>,
+        Vec<ash::vk::MicromapUsageEXT>,
+    ) {
+        let &Self {
+            ref micromap,
+            ref index_data,
+            base_triangle,
+            ref usage_counts,
+            _ne: _,
+        } = self;
+
+        let usage_counts_vk: Vec<_> = usage_counts.iter().map(MicromapUsage::to_vulkan).collect();
+
+        (
+            ash::vk::AccelerationStructureTrianglesOpacityMicromapEXT {
+                index_type: index_data
+                    .as_ref()
+                    .map_or(ash::vk::IndexType::NONE_KHR, |index_data| {
+                        index_data.index_type().into()
+                    }),
+                index_buffer: index_data
+                    .as_ref()
+                    .map_or(Default::default(), |index_data| {
+                        const_address(index_data.as_bytes())
+                    }),
+                index_stride: index_data
+                    .as_ref()
+                    .map_or(0, |index_data| index_data.index_type().size()),
+                base_triangle,
+                usage_counts_count: usage_counts_vk.len() as u32,
+                // The `Vec`'s heap allocation doesn't move when the `Vec` itself is moved.
+                p_usage_counts: usage_counts_vk.as_ptr(),
+                micromap: micromap.handle(),
+                ..Default::default()
+            },
+            usage_counts_vk,
+        )
+    }
+}
+
 /// A 3x4 transformation matrix.
 ///
 /// The first three columns must be a 3x3 invertible matrix.
@@ -1217,8 +1656,18 @@ pub enum AccelerationStructureGeometryInstancesDataType {
     /// but must be `Some` otherwise.
     Values(Option<Subbuffer<[AccelerationStructureInstance]>>),
 
+    /// The data buffer contains an array of [`AccelerationStructureMotionInstance`] structures
+    /// directly.
+    ///
+    /// The acceleration structure must be built with [`BuildAccelerationStructureFlags::MOTION`].
+    ///
+    /// The inner value can be `None` when calling [`Device::acceleration_structure_build_sizes`],
+    /// but must be `Some` otherwise.
+    MotionValues(Option<Subbuffer<[AccelerationStructureMotionInstance]>>),
+
     /// The data buffer contains an array of pointers to [`AccelerationStructureInstance`]
-    /// structures.
+    /// structures, or to [`AccelerationStructureMotionInstance`] structures if the acceleration
+    /// structure is built with [`BuildAccelerationStructureFlags::MOTION`].
     ///
     /// The inner value can be `None` when calling [`Device::acceleration_structure_build_sizes`],
     /// but must be `Some` otherwise.
@@ -1234,6 +1683,15 @@ impl From<Subbuffer<[AccelerationStructureInstance]>>
     }
 }
 
+impl From<Subbuffer<[AccelerationStructureMotionInstance]>>
+    for AccelerationStructureGeometryInstancesDataType
+{
+    #[inline]
+    fn from(value: Subbuffer<[AccelerationStructureMotionInstance]>) -> Self {
+        Self::MotionValues(Some(value))
+    }
+}
+
 impl From<Subbuffer<[DeviceSize]>> for AccelerationStructureGeometryInstancesDataType {
     #[inline]
     fn from(value: Subbuffer<[DeviceSize]>) -> Self {
@@ -1289,6 +1747,233 @@ impl Default for AccelerationStructureInstance {
     }
 }
 
+/// Specifies a bottom-level acceleration structure instance that can move over time, when
+/// building a top-level structure with [`BuildAccelerationStructureFlags::MOTION`].
+///
+/// A motion instance holds one of three kinds of instance, which can be created with the `From`
+/// implementations of this type:
+/// - [`AccelerationStructureInstance`], which does not move.
+/// - [`AccelerationStructureMatrixMotionInstance`], which is interpolated between two
+///   transformation matrices.
+/// - [`AccelerationStructureSrtMotionInstance`], which is interpolated between two
+///   scale-rotation-translation transforms.
+///
+/// The instances in the data buffer have a stride of 160 bytes, so the structure is padded at the
+/// end.
+#[derive(Clone, Copy, Debug, PartialEq, Zeroable, Pod)]
+#[repr(C)]
+pub struct AccelerationStructureMotionInstance {
+    ty: u32,
+    flags: u32,
+    data: [u32; 36],
+    _padding: [u32; 2],
+}
+
+impl AccelerationStructureMotionInstance {
+    /// Returns the flags of the motion instance.
+    #[inline]
+    pub fn flags(&self) -> AccelerationStructureMotionInstanceFlags {
+        ash::vk::AccelerationStructureMotionInstanceFlagsNV::from_raw(self.flags).into()
+    }
+
+    /// Returns the motion instance with its flags replaced by `flags`.
+    ///
+    /// The default value is empty.
+    #[inline]
+    pub fn with_flags(mut self, flags: AccelerationStructureMotionInstanceFlags) -> Self {
+        self.flags = ash::vk::AccelerationStructureMotionInstanceFlagsNV::from(flags).as_raw();
+        self
+    }
+
+    fn from_data<T: Pod>(
+        ty: ash::vk::AccelerationStructureMotionInstanceTypeNV,
+        value: &T,
+    ) -> Self {
+        let mut data = [0u32; 36];
+        let bytes = bytemuck::bytes_of(value);
+        bytemuck::cast_slice_mut::<_, u8>(&mut data)[..bytes.len()].copy_from_slice(bytes);
+
+        Self {
+            ty: ty.as_raw() as u32,
+            flags: 0,
+            data,
+            _padding: [0; 2],
+        }
+    }
+}
+
+impl Default for AccelerationStructureMotionInstance {
+    #[inline]
+    fn default() -> Self {
+        AccelerationStructureInstance::default().into()
+    }
+}
+
+impl From<AccelerationStructureInstance> for AccelerationStructureMotionInstance {
+    #[inline]
+    fn from(value: AccelerationStructureInstance) -> Self {
+        Self::from_data(
+            ash::vk::AccelerationStructureMotionInstanceTypeNV::STATIC,
+            &value,
+        )
+    }
+}
+
+impl From<AccelerationStructureMatrixMotionInstance> for AccelerationStructureMotionInstance {
+    #[inline]
+    fn from(value: AccelerationStructureMatrixMotionInstance) -> Self {
+        Self::from_data(
+            ash::vk::AccelerationStructureMotionInstanceTypeNV::MATRIX_MOTION,
+            &value,
+        )
+    }
+}
+
+impl From<AccelerationStructureSrtMotionInstance> for AccelerationStructureMotionInstance {
+    #[inline]
+    fn from(value: AccelerationStructureSrtMotionInstance) -> Self {
+        Self::from_data(
+            ash::vk::AccelerationStructureMotionInstanceTypeNV::SRT_MOTION,
+            &value,
+        )
+    }
+}
+
+vulkan_bitflags! {
+    #[non_exhaustive]
+
+    /// Flags for a motion instance in a top-level acceleration structure.
+    AccelerationStructureMotionInstanceFlags = AccelerationStructureMotionInstanceFlagsNV(u32);
+}
+
+/// A bottom-level acceleration structure instance whose transformation is interpolated between
+/// two matrices over time.
+#[derive(Clone, Copy, Debug, PartialEq, Zeroable, Pod)]
+#[repr(C)]
+pub struct AccelerationStructureMatrixMotionInstance {
+    /// The 3x4 transformation matrix at time 0.0.
+    ///
+    /// The default value is a 3x3 identity matrix, with the fourth column filled with zeroes.
+    pub transform_t0: TransformMatrix,
+
+    /// The 3x4 transformation matrix at time 1.0.
+    ///
+    /// The default value is a 3x3 identity matrix, with the fourth column filled with zeroes.
+    pub transform_t1: TransformMatrix,
+
+    /// Same as [`AccelerationStructureInstance::instance_custom_index_and_mask`].
+    pub instance_custom_index_and_mask: Packed24_8,
+
+    /// Same as
+    /// [`AccelerationStructureInstance::instance_shader_binding_table_record_offset_and_flags`].
+    pub instance_shader_binding_table_record_offset_and_flags: Packed24_8,
+
+    /// Same as [`AccelerationStructureInstance::acceleration_structure_reference`].
+    pub acceleration_structure_reference: DeviceAddress,
+}
+
+impl Default for AccelerationStructureMatrixMotionInstance {
+    #[inline]
+    fn default() -> Self {
+        let identity = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+        ];
+
+        Self {
+            transform_t0: identity,
+            transform_t1: identity,
+            instance_custom_index_and_mask: Packed24_8::new(0, 0xff),
+            instance_shader_binding_table_record_offset_and_flags: Packed24_8::new(0, 0),
+            acceleration_structure_reference: 0,
+        }
+    }
+}
+
+/// A bottom-level acceleration structure instance whose transformation is interpolated between
+/// two scale-rotation-translation transforms over time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
+#[repr(C)]
+pub struct AccelerationStructureSrtMotionInstance {
+    /// The transform at time 0.0.
+    ///
+    /// The default value is the identity transform.
+    pub transform_t0: SrtData,
+
+    /// The transform at time 1.0.
+    ///
+    /// The default value is the identity transform.
+    pub transform_t1: SrtData,
+
+    /// Same as [`AccelerationStructureInstance::instance_custom_index_and_mask`].
+    pub instance_custom_index_and_mask: Packed24_8,
+
+    /// Same as
+    /// [`AccelerationStructureInstance::instance_shader_binding_table_record_offset_and_flags`].
+    pub instance_shader_binding_table_record_offset_and_flags: Packed24_8,
+
+    /// Same as [`AccelerationStructureInstance::acceleration_structure_reference`].
+    pub acceleration_structure_reference: DeviceAddress,
+}
+
+/// A transform consisting of a scale/shear, a rotation and a translation, which is applied to
+/// a vertex in that order.
+///
+/// The scale/shear is given by the upper triangular matrix
+/// ```text
+/// sx  a   b   pvx
+/// 0   sy  c   pvy
+/// 0   0   sz  pvz
+/// ```
+/// where `pv` is the pivot point that the instance rotates around. The rotation is the unit
+/// quaternion `q`, and the translation `t` is applied after translating the vertex back by the
+/// pivot point.
+#[derive(Clone, Copy, Debug, PartialEq, Zeroable, Pod)]
+#[repr(C)]
+pub struct SrtData {
+    pub sx: f32,
+    pub a: f32,
+    pub b: f32,
+    pub pvx: f32,
+    pub sy: f32,
+    pub c: f32,
+    pub pvy: f32,
+    pub sz: f32,
+    pub pvz: f32,
+    pub qx: f32,
+    pub qy: f32,
+    pub qz: f32,
+    pub qw: f32,
+    pub tx: f32,
+    pub ty: f32,
+    pub tz: f32,
+}
+
+impl Default for SrtData {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            sx: 1.0,
+            a: 0.0,
+            b: 0.0,
+            pvx: 0.0,
+            sy: 1.0,
+            c: 0.0,
+            pvy: 0.0,
+            sz: 1.0,
+            pvz: 0.0,
+            qx: 0.0,
+            qy: 0.0,
+            qz: 0.0,
+            qw: 1.0,
+            tx: 0.0,
+            ty: 0.0,
+            tz: 0.0,
+        }
+    }
+}
+
 vulkan_bitflags! {
     #[non_exhaustive]
 
@@ -1307,19 +1992,23 @@ vulkan_bitflags! {
     /// Geometries in this instance will act as if [`GeometryFlags::OPAQUE`] were not specified.
     FORCE_NO_OPAQUE = FORCE_NO_OPAQUE,
 
-    /* TODO: enable
-    // TODO: document
+    /// Opacity micromaps in this instance will act as if they used
+    /// [`OpacityMicromapFormat::TwoState`].
+    ///
+    /// [`OpacityMicromapFormat::TwoState`]: crate::micromap::OpacityMicromapFormat::TwoState
     FORCE_OPACITY_MICROMAP_2_STATE = FORCE_OPACITY_MICROMAP_2_STATE_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_opacity_micromap)]),
-    ]), */
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Opacity micromaps are ignored for this instance.
+    ///
+    /// The acceleration structure that the instance refers to must have been built with
+    /// [`BuildAccelerationStructureFlags::ALLOW_DISABLE_OPACITY_MICROMAPS`].
     DISABLE_OPACITY_MICROMAPS = DISABLE_OPACITY_MICROMAPS_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_opacity_micromap)]),
-    ]), */
+    ]),
 }
 
 impl From<GeometryInstanceFlags> for u8 {
@@ -1642,3 +2331,38 @@ pub struct AccelerationStructureBuildSizesInfo {
 
     pub _ne: crate::NonExhaustive,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AccelerationStructureMotionInstance, AccelerationStructureMotionInstanceFlags,
+        AccelerationStructureSrtMotionInstance,
+    };
+    use std::mem::size_of;
+
+    #[test]
+    fn motion_instance_stride() {
+        // Motion instances that are not given as an array of pointers must have a stride of 160
+        // bytes.
+        assert_eq!(size_of::<AccelerationStructureMotionInstance>(), 160);
+    }
+
+    #[test]
+    fn motion_instance_layout() {
+        let instance =
+            AccelerationStructureMotionInstance::from(AccelerationStructureSrtMotionInstance {
+                acceleration_structure_reference: 0x0123_4567_89ab_cdef,
+                ..Default::default()
+            })
+            .with_flags(AccelerationStructureMotionInstanceFlags::empty());
+        assert_eq!(
+            instance.flags(),
+            AccelerationStructureMotionInstanceFlags::empty()
+        );
+
+        // The union starts after `type` and `flags`, and the reference is its last member.
+        let bytes = bytemuck::bytes_of(&instance);
+        assert_eq!(bytes[144..152], 0x0123_4567_89ab_cdef_u64.to_ne_bytes());
+        assert!(bytes[152..].iter().all(|&byte| byte == 0));
+    }
+}
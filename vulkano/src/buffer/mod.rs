@@ -71,18 +71,26 @@
 
 pub use self::{subbuffer::*, sys::*, usage::*};
 use crate::{
-    device::{physical::PhysicalDevice, Device, DeviceOwned},
+    command_buffer::{
+        allocator::CommandBufferAllocator, CommandBufferBeginInfo, CommandBufferExecError,
+        CommandBufferExecFuture, CommandBufferLevel, CommandBufferUsage, CopyBufferInfo,
+        RecordingCommandBuffer,
+    },
+    device::{physical::PhysicalDevice, Device, DeviceOwned, Queue},
     macros::{vulkan_bitflags, vulkan_enum},
     memory::{
         allocator::{
-            AllocationCreateInfo, AllocationType, DeviceLayout, MemoryAllocator,
-            MemoryAllocatorError,
+            set_object_name_from_tag, AllocationCreateInfo, AllocationType, DeviceLayout,
+            MemoryAllocator, MemoryAllocatorError, MemoryTypeFilter,
         },
         DedicatedAllocation, ExternalMemoryHandleType, ExternalMemoryHandleTypes,
         ExternalMemoryProperties, MemoryRequirements, ResourceMemory,
     },
     range_map::RangeMap,
-    sync::{future::AccessError, AccessConflict, CurrentAccess, Sharing},
+    sync::{
+        future::{AccessError, NowFuture},
+        AccessConflict, CurrentAccess, Sharing,
+    },
     DeviceSize, NonNullDeviceAddress, NonZeroDeviceSize, Requires, RequiresAllOf, RequiresOneOf,
     Validated, ValidationError, Version, VulkanError, VulkanObject,
 };
@@ -231,12 +239,17 @@ impl Buffer {
     ///
     /// > **Note**: This only works with memory types that are host-visible. If you want to upload
     /// > data to a buffer allocated in device-local memory, you will need to create a staging
-    /// > buffer and copy the contents over.
+    /// > buffer and copy the contents over, which [`from_data_device_local`] and
+    /// > [`from_iter_device_local`] do for you.
     ///
     /// # Panics
     ///
     /// - Panics if `create_info.size` is not zero.
     /// - Panics if the chosen memory type is not host-visible.
+    ///
+    /// [`from_data_device_local`]: Self::from_data_device_local
+    /// [`from_iter_device_local`]: Self::from_iter_device_local
+    #[track_caller]
     pub fn from_data<T>(
         allocator: Arc<dyn MemoryAllocator>,
         create_info: BufferCreateInfo,
@@ -261,13 +274,18 @@ impl Buffer {
     ///
     /// > **Note**: This only works with memory types that are host-visible. If you want to upload
     /// > data to a buffer allocated in device-local memory, you will need to create a staging
-    /// > buffer and copy the contents over.
+    /// > buffer and copy the contents over, which [`from_data_device_local`] and
+    /// > [`from_iter_device_local`] do for you.
     ///
     /// # Panics
     ///
     /// - Panics if `create_info.size` is not zero.
     /// - Panics if the chosen memory type is not host-visible.
     /// - Panics if `iter` is empty.
+    ///
+    /// [`from_data_device_local`]: Self::from_data_device_local
+    /// [`from_iter_device_local`]: Self::from_iter_device_local
+    #[track_caller]
     pub fn from_iter<T, I>(
         allocator: Arc<dyn MemoryAllocator>,
         create_info: BufferCreateInfo,
@@ -298,12 +316,110 @@ impl Buffer {
         Ok(buffer)
     }
 
+    /// Creates a new `Buffer` in device-local memory and uploads `data` to it. Returns a
+    /// [`Subbuffer`] spanning the whole buffer, together with a future that represents the upload.
+    ///
+    /// The data is written to a host-visible staging buffer, and a command buffer that copies it
+    /// to the new buffer is recorded and submitted to `queue`. [`BufferUsage::TRANSFER_DST`] is
+    /// added to `create_info.usage` for this. The staging buffer is kept alive by the future
+    /// until the copy has finished.
+    ///
+    /// The buffer must not be accessed until the upload has finished. Either wait for the future,
+    /// or join it with the future of the work that uses the buffer.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.size` is not zero.
+    #[track_caller]
+    pub fn from_data_device_local<T>(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        command_buffer_allocator: Arc<dyn CommandBufferAllocator>,
+        queue: Arc<Queue>,
+        create_info: BufferCreateInfo,
+        data: T,
+    ) -> Result<(Subbuffer<T>, CommandBufferExecFuture<NowFuture>), Validated<UploadBufferError>>
+    where
+        T: BufferContents,
+    {
+        let staging_buffer = Buffer::from_data(
+            memory_allocator.clone(),
+            staging_buffer_create_info(&create_info),
+            staging_allocation_create_info(),
+            data,
+        )
+        .map_err(|err| err.map(UploadBufferError::AllocateBuffer))?;
+        let buffer = Buffer::new_sized(
+            memory_allocator,
+            device_local_create_info(create_info),
+            device_local_allocation_create_info(),
+        )
+        .map_err(|err| err.map(UploadBufferError::AllocateBuffer))?;
+        let future = upload(
+            command_buffer_allocator,
+            queue,
+            staging_buffer,
+            buffer.clone(),
+        )?;
+
+        Ok((buffer, future))
+    }
+
+    /// Creates a new `Buffer` in device-local memory and uploads all elements of `iter` to it.
+    /// Returns a [`Subbuffer`] spanning the whole buffer, together with a future that represents
+    /// the upload.
+    ///
+    /// See [`from_data_device_local`] for how the upload is done.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.size` is not zero.
+    /// - Panics if `iter` is empty.
+    ///
+    /// [`from_data_device_local`]: Self::from_data_device_local
+    #[track_caller]
+    pub fn from_iter_device_local<T, I>(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        command_buffer_allocator: Arc<dyn CommandBufferAllocator>,
+        queue: Arc<Queue>,
+        create_info: BufferCreateInfo,
+        iter: I,
+    ) -> Result<(Subbuffer<[T]>, CommandBufferExecFuture<NowFuture>), Validated<UploadBufferError>>
+    where
+        T: BufferContents,
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let staging_buffer = Buffer::from_iter(
+            memory_allocator.clone(),
+            staging_buffer_create_info(&create_info),
+            staging_allocation_create_info(),
+            iter,
+        )
+        .map_err(|err| err.map(UploadBufferError::AllocateBuffer))?;
+        let buffer = Buffer::new_slice(
+            memory_allocator,
+            device_local_create_info(create_info),
+            device_local_allocation_create_info(),
+            staging_buffer.len(),
+        )
+        .map_err(|err| err.map(UploadBufferError::AllocateBuffer))?;
+        let future = upload(
+            command_buffer_allocator,
+            queue,
+            staging_buffer,
+            buffer.clone(),
+        )?;
+
+        Ok((buffer, future))
+    }
+
     /// Creates a new uninitialized `Buffer` for sized data. Returns a [`Subbuffer`] spanning the
     /// whole buffer.
     ///
     /// # Panics
     ///
     /// - Panics if `create_info.size` is not zero.
+    #[track_caller]
     pub fn new_sized<T>(
         allocator: Arc<dyn MemoryAllocator>,
         create_info: BufferCreateInfo,
@@ -330,6 +446,7 @@ impl Buffer {
     ///
     /// - Panics if `create_info.size` is not zero.
     /// - Panics if `len` is zero.
+    #[track_caller]
     pub fn new_slice<T>(
         allocator: Arc<dyn MemoryAllocator>,
         create_info: BufferCreateInfo,
@@ -349,6 +466,7 @@ impl Buffer {
     ///
     /// - Panics if `create_info.size` is not zero.
     /// - Panics if `len` is zero.
+    #[track_caller]
     pub fn new_unsized<T>(
         allocator: Arc<dyn MemoryAllocator>,
         create_info: BufferCreateInfo,
@@ -375,6 +493,7 @@ impl Buffer {
     /// # Panics
     ///
     /// - Panics if `create_info.size` is not zero.
+    #[track_caller]
     pub fn new(
         allocator: Arc<dyn MemoryAllocator>,
         mut create_info: BufferCreateInfo,
@@ -397,6 +516,11 @@ impl Buffer {
                 Validated::Error(err) => Validated::Error(AllocateBufferError::CreateBuffer(err)),
                 Validated::ValidationError(err) => err.into(),
             })?;
+
+        if let Some(tag) = &allocation_info.tag {
+            set_object_name_from_tag(&raw_buffer, tag);
+        }
+
         let mut requirements = *raw_buffer.memory_requirements();
         requirements.layout = requirements.layout.align_to(layout.alignment()).unwrap();
 
@@ -422,6 +546,10 @@ impl Buffer {
     }
 
     fn from_raw(inner: RawBuffer, memory: BufferMemory) -> Self {
+        if let Some(resources) = inner.device().resources() {
+            resources.bind_buffer_memory(&inner, &memory);
+        }
+
         let state = Mutex::new(BufferState::new(inner.size()));
 
         Buffer {
@@ -566,6 +694,63 @@ impl Hash for Buffer {
     }
 }
 
+fn staging_buffer_create_info(create_info: &BufferCreateInfo) -> BufferCreateInfo {
+    BufferCreateInfo {
+        usage: BufferUsage::TRANSFER_SRC,
+        sharing: create_info.sharing.clone(),
+        ..Default::default()
+    }
+}
+
+#[track_caller]
+fn staging_allocation_create_info() -> AllocationCreateInfo {
+    AllocationCreateInfo {
+        memory_type_filter: MemoryTypeFilter::PREFER_HOST | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+        ..Default::default()
+    }
+}
+
+fn device_local_create_info(mut create_info: BufferCreateInfo) -> BufferCreateInfo {
+    create_info.usage |= BufferUsage::TRANSFER_DST;
+
+    create_info
+}
+
+#[track_caller]
+fn device_local_allocation_create_info() -> AllocationCreateInfo {
+    AllocationCreateInfo {
+        memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+        ..Default::default()
+    }
+}
+
+// Records and submits a command buffer that copies `staging_buffer` to `buffer`.
+fn upload<T: ?Sized>(
+    command_buffer_allocator: Arc<dyn CommandBufferAllocator>,
+    queue: Arc<Queue>,
+    staging_buffer: Subbuffer<T>,
+    buffer: Subbuffer<T>,
+) -> Result<CommandBufferExecFuture<NowFuture>, Validated<UploadBufferError>> {
+    let mut builder = RecordingCommandBuffer::new(
+        command_buffer_allocator,
+        queue.queue_family_index(),
+        CommandBufferLevel::Primary,
+        CommandBufferBeginInfo {
+            usage: CommandBufferUsage::OneTimeSubmit,
+            ..Default::default()
+        },
+    )
+    .map_err(|err| err.map(UploadBufferError::CommandBuffer))?;
+    builder.copy_buffer(CopyBufferInfo::buffers(staging_buffer, buffer))?;
+    let command_buffer = builder
+        .end()
+        .map_err(|err| err.map(UploadBufferError::CommandBuffer))?;
+
+    command_buffer
+        .execute(queue)
+        .map_err(|err| UploadBufferError::Execute(err).into())
+}
+
 /// Error that can happen when allocating a new buffer.
 #[derive(Clone, Debug)]
 pub enum AllocateBufferError {
@@ -600,6 +785,43 @@ impl From<AllocateBufferError> for Validated<AllocateBufferError> {
     }
 }
 
+/// Error that can happen when creating a new buffer in device-local memory and uploading data to
+/// it.
+#[derive(Clone, Debug)]
+pub enum UploadBufferError {
+    AllocateBuffer(AllocateBufferError),
+    CommandBuffer(VulkanError),
+    Execute(CommandBufferExecError),
+}
+
+impl Error for UploadBufferError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::AllocateBuffer(err) => Some(err),
+            Self::CommandBuffer(err) => Some(err),
+            Self::Execute(err) => Some(err),
+        }
+    }
+}
+
+impl Display for UploadBufferError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AllocateBuffer(_) => write!(f, "allocating a buffer for the upload failed"),
+            Self::CommandBuffer(_) => {
+                write!(f, "recording the command buffer for the upload failed")
+            }
+            Self::Execute(_) => write!(f, "executing the command buffer for the upload failed"),
+        }
+    }
+}
+
+impl From<UploadBufferError> for Validated<UploadBufferError> {
+    fn from(err: UploadBufferError) -> Self {
+        Self::Error(err)
+    }
+}
+
 /// The current state of a buffer.
 #[derive(Debug)]
 pub(crate) struct BufferState {
@@ -757,6 +979,30 @@ impl BufferState {
         Ok(())
     }
 
+    /// Like `check_gpu_write`, but allows the range to still be locked by `pending_writes`
+    /// earlier submissions of the same command buffer to the same queue.
+    pub(crate) fn check_gpu_write_resubmit(
+        &self,
+        range: Range<DeviceSize>,
+        pending_writes: usize,
+    ) -> Result<(), AccessError> {
+        for (_range, state) in self.ranges.range(&range) {
+            match &state.current_access {
+                CurrentAccess::Shared {
+                    cpu_reads: 0,
+                    gpu_reads: 0,
+                } => (),
+                &CurrentAccess::GpuExclusive {
+                    gpu_reads: 0,
+                    gpu_writes,
+                } if gpu_writes <= pending_writes => (),
+                _ => return Err(AccessError::AlreadyInUse),
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) unsafe fn gpu_write_lock(&mut self, range: Range<DeviceSize>) {
         self.ranges.split_at(&range.start);
         self.ranges.split_at(&range.end);
@@ -842,15 +1088,22 @@ vulkan_bitflags! {
     /// [`sparse_residency_aliased`]: crate::device::DeviceFeatures::sparse_residency_aliased
     SPARSE_ALIASED = SPARSE_ALIASED,*/
 
-    /* TODO: enable
     /// The buffer is protected, and can only be used in combination with protected memory and other
     /// protected objects.
     ///
-    /// The device API version must be at least 1.1.
+    /// The [`protected_memory`] feature must be enabled on the device.
+    ///
+    /// The [`StandardMemoryAllocator`] never allocates from protected memory types, so the memory
+    /// for protected buffers must come from an allocator whose
+    /// [`memory_type_bits`](crate::memory::allocator::GenericMemoryAllocatorCreateInfo::memory_type_bits)
+    /// includes them.
+    ///
+    /// [`protected_memory`]: crate::device::DeviceFeatures::protected_memory
+    /// [`StandardMemoryAllocator`]: crate::memory::allocator::StandardMemoryAllocator
     PROTECTED = PROTECTED
     RequiresOneOf([
         RequiresAllOf([APIVersion(V1_1)]),
-    ]),*/
+    ]),
 
     /* TODO: enable
     /// The buffer's device address can be saved and reused on a subsequent run.
@@ -1043,3 +1296,170 @@ impl From<Subbuffer<[u32]>> for IndexBuffer {
         Self::U32(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer};
+    use crate::{
+        command_buffer::{
+            allocator::{CommandBufferAllocator, StandardCommandBufferAllocator},
+            CommandBufferBeginInfo, CommandBufferLevel, CommandBufferUsage, CopyBufferInfo,
+            RecordingCommandBuffer,
+        },
+        device::Queue,
+        memory::allocator::{
+            AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter, StandardMemoryAllocator,
+        },
+        sync::GpuFuture,
+    };
+    use std::sync::Arc;
+
+    // Copies `buffer` to a new host-visible buffer, and returns the new buffer once the copy has
+    // finished.
+    fn read_back(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        command_buffer_allocator: Arc<dyn CommandBufferAllocator>,
+        queue: Arc<Queue>,
+        buffer: Subbuffer<[u32]>,
+    ) -> Subbuffer<[u32]> {
+        let readback_buffer = Buffer::new_slice::<u32>(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+                ..Default::default()
+            },
+            buffer.len(),
+        )
+        .unwrap();
+
+        let mut cbb = RecordingCommandBuffer::new(
+            command_buffer_allocator,
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        cbb.copy_buffer(CopyBufferInfo::buffers(buffer, readback_buffer.clone()))
+            .unwrap();
+        cbb.end()
+            .unwrap()
+            .execute(queue)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        readback_buffer
+    }
+
+    #[test]
+    fn from_data_device_local() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+        let command_buffer_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device,
+            Default::default(),
+        ));
+
+        let (buffer, future) = Buffer::from_data_device_local(
+            memory_allocator.clone(),
+            command_buffer_allocator.clone(),
+            queue.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::STORAGE_BUFFER | BufferUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            [1u32, 2, 3, 4],
+        )
+        .unwrap();
+        assert!(buffer.buffer().usage().contains(BufferUsage::TRANSFER_DST));
+        assert_eq!(buffer.size(), 16);
+
+        // The copy to read the buffer back waits for the upload because it is submitted after it
+        // to the same queue, and the upload is finished before that.
+        future
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let readback_buffer = read_back(
+            memory_allocator,
+            command_buffer_allocator,
+            queue,
+            buffer.reinterpret(),
+        );
+        assert_eq!(&*readback_buffer.read().unwrap(), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn from_iter_device_local() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+        let command_buffer_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device,
+            Default::default(),
+        ));
+
+        let (buffer, future) = Buffer::from_iter_device_local(
+            memory_allocator.clone(),
+            command_buffer_allocator.clone(),
+            queue.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::VERTEX_BUFFER | BufferUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            (0..64u32).map(|i| i * 3),
+        )
+        .unwrap();
+        assert_eq!(buffer.len(), 64);
+
+        future
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let readback_buffer = read_back(memory_allocator, command_buffer_allocator, queue, buffer);
+        assert!(readback_buffer
+            .read()
+            .unwrap()
+            .iter()
+            .copied()
+            .eq((0..64).map(|i| i * 3)));
+    }
+
+    #[test]
+    fn from_data_device_local_sized_create_info() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+        let command_buffer_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device,
+            Default::default(),
+        ));
+
+        // The size is taken from the data, so giving one is a programming error.
+        assert_should_panic!({
+            let _ = Buffer::from_data_device_local(
+                memory_allocator,
+                command_buffer_allocator,
+                queue,
+                BufferCreateInfo {
+                    size: 4,
+                    usage: BufferUsage::UNIFORM_BUFFER,
+                    ..Default::default()
+                },
+                0u32,
+            );
+        });
+    }
+}
@@ -258,18 +258,178 @@ where
     }
 
     fn validate_reinterpret(&self, new_layout: BufferContentsLayout) {
-        assert!(is_aligned(self.memory_offset(), new_layout.alignment()));
+        if let Err(err) = self.validate_cast(new_layout) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Changes the `T` generic parameter of the subbuffer to the desired type, or returns an error
+    /// if the subbuffer is not correctly aligned and sized for `U`.
+    ///
+    /// This is the fallible version of [`reinterpret`]. It can be used to view the same memory as
+    /// different types, for example to carve typed subbuffers out of one large byte subbuffer,
+    /// without having to uphold the requirements of `reinterpret` by hand. Because all
+    /// [`BufferContents`] types can hold any bit pattern, any subbuffer that passes the checks can
+    /// be safely viewed as `U`.
+    ///
+    /// [`reinterpret`]: Self::reinterpret
+    pub fn try_cast<U>(self) -> Result<Subbuffer<U>, Box<ValidationError>>
+    where
+        U: BufferContents + ?Sized,
+    {
+        self.validate_cast(U::LAYOUT)?;
+
+        Ok(unsafe { self.reinterpret_unchecked_inner() })
+    }
+
+    /// Same as [`try_cast`], except it works with a reference to the subbuffer.
+    ///
+    /// [`try_cast`]: Self::try_cast
+    pub fn try_cast_ref<U>(&self) -> Result<&Subbuffer<U>, Box<ValidationError>>
+    where
+        U: BufferContents + ?Sized,
+    {
+        self.validate_cast(U::LAYOUT)?;
+
+        Ok(unsafe { self.reinterpret_unchecked_ref_inner() })
+    }
+
+    /// Narrows the subbuffer down to the field of `T` that `f` returns a reference to.
+    ///
+    /// This lets you target a single member of a struct-typed subbuffer, for example in a
+    /// descriptor write or a copy command, without having to compute its byte offset by hand:
+    ///
+    /// ```
+    /// # use vulkano::buffer::{BufferContents, Subbuffer};
+    /// #[derive(BufferContents)]
+    /// #[repr(C)]
+    /// struct Scene {
+    ///     camera: [[f32; 4]; 4],
+    ///     lights: [[f32; 4]; 16],
+    /// }
+    ///
+    /// # fn example(subbuffer: Subbuffer<Scene>) {
+    /// let lights: Subbuffer<[[f32; 4]; 16]> = subbuffer.project(|scene| &scene.lights);
+    /// # }
+    /// ```
+    ///
+    /// `f` is called with a zeroed instance of `T` in host memory, not with the contents of the
+    /// subbuffer, so it must not depend on the values of any fields. If `T` is unsized, the
+    /// instance has a single element, and if `f` returns a slice that ends where the instance
+    /// ends, such as the trailing slice of `T`, the returned subbuffer extends to the end of
+    /// `self`. Otherwise, the returned subbuffer has the size of the reference that `f` returns.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `f` returns a reference that doesn't point into the instance of `T` it was
+    ///   given.
+    pub fn project<U>(mut self, f: impl FnOnce(&T) -> &U) -> Subbuffer<U>
+    where
+        U: BufferContents + ?Sized,
+    {
+        #[derive(Clone, Copy)]
+        #[repr(C, align(64))]
+        struct Block([u8; 64]);
+
+        // `BufferContentsLayout` guarantees that the alignment doesn't exceed 64.
+        let size = (T::LAYOUT.head_size() + T::LAYOUT.element_size().unwrap_or(0)) as usize;
+        let mut blocks = vec![Block([0; 64]); size.div_ceil(64).max(1)];
+        let bytes = ptr::slice_from_raw_parts_mut(blocks.as_mut_ptr().cast::<u8>(), size);
+
+        // SAFETY: `bytes` is aligned to 64 and its length is valid for the layout of `T`, and
+        // `BufferContents` types can hold any bit pattern, including all zeroes.
+        let instance = unsafe { &*T::ptr_from_slice(NonNull::new(bytes).unwrap()) };
+        let field = f(instance);
+
+        let offset = (field as *const U).cast::<u8>() as usize;
+        let offset = offset.wrapping_sub((instance as *const T).cast::<u8>() as usize);
+        let field_size = mem::size_of_val(field);
+
+        assert!(
+            offset <= size && field_size <= size - offset,
+            "the reference returned by `f` must point to a field of the instance it was given",
+        );
+
+        // The trailing slice of the instance has a single element, but the one of the subbuffer
+        // can have any number of them.
+        let is_tail = !T::LAYOUT.is_sized() && !U::LAYOUT.is_sized() && offset + field_size == size;
+
+        self.offset += offset as DeviceSize;
+        self.size = if is_tail {
+            self.size - offset as DeviceSize
+        } else {
+            field_size as DeviceSize
+        };
+
+        self.reinterpret()
+    }
+
+    fn validate_cast(&self, new_layout: BufferContentsLayout) -> Result<(), Box<ValidationError>> {
+        if !is_aligned(self.memory_offset(), new_layout.alignment()) {
+            return Err(Box::new(ValidationError {
+                context: "self.memory_offset()".into(),
+                problem: format!(
+                    "is not a multiple of the alignment of the new type ({})",
+                    new_layout.alignment().as_devicesize(),
+                )
+                .into(),
+                ..Default::default()
+            }));
+        }
 
         if new_layout.is_sized() {
-            assert_eq!(self.size, new_layout.unwrap_sized().size());
+            let size = new_layout.unwrap_sized().size();
+
+            if self.size != size {
+                return Err(Box::new(ValidationError {
+                    context: "self.size()".into(),
+                    problem: format!("does not equal the size of the new type ({})", size).into(),
+                    ..Default::default()
+                }));
+            }
         } else {
-            assert!(self.size > new_layout.head_size());
-            assert_eq!(
-                (self.size - new_layout.head_size()) % new_layout.element_size().unwrap(),
-                0,
-            );
-            assert!(is_aligned(self.size(), new_layout.alignment()));
+            let head_size = new_layout.head_size();
+            let element_size = new_layout.element_size().unwrap();
+
+            if self.size <= head_size {
+                return Err(Box::new(ValidationError {
+                    context: "self.size()".into(),
+                    problem: format!(
+                        "is not greater than the size of the head of the new type ({})",
+                        head_size,
+                    )
+                    .into(),
+                    ..Default::default()
+                }));
+            }
+
+            if (self.size - head_size) % element_size != 0 {
+                return Err(Box::new(ValidationError {
+                    context: "self.size()".into(),
+                    problem: format!(
+                        "minus the size of the head of the new type ({}) is not a multiple of \
+                        the size of its elements ({})",
+                        head_size, element_size,
+                    )
+                    .into(),
+                    ..Default::default()
+                }));
+            }
+
+            if !is_aligned(self.size, new_layout.alignment()) {
+                return Err(Box::new(ValidationError {
+                    context: "self.size()".into(),
+                    problem: format!(
+                        "is not a multiple of the alignment of the new type ({})",
+                        new_layout.alignment().as_devicesize(),
+                    )
+                    .into(),
+                    ..Default::default()
+                }));
+            }
         }
+
+        Ok(())
     }
 
     /// Locks the subbuffer in order to read its content from the host.
@@ -1318,4 +1478,100 @@ mod tests {
             assert_should_panic!({ buffer.align_to(layout) });
         }
     }
+
+    #[test]
+    fn try_cast() {
+        let (device, _) = gfx_dev_and_queue!();
+        let allocator = Arc::new(StandardMemoryAllocator::new_default(device));
+
+        let buffer = Buffer::new_slice::<u64>(
+            allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+            4,
+        )
+        .unwrap()
+        .into_bytes();
+
+        {
+            let (left, right) = buffer.clone().split_at(16);
+            assert_eq!(left.try_cast::<[u32; 4]>().unwrap().size(), 16);
+            assert_eq!(right.try_cast::<[f32]>().unwrap().len(), 4);
+        }
+
+        {
+            assert!(buffer.try_cast_ref::<[u64; 4]>().is_ok());
+            assert!(buffer.try_cast_ref::<[u64; 3]>().is_err());
+        }
+
+        {
+            let (_, right) = buffer.clone().split_at(1);
+            assert!(right.clone().try_cast::<[u32]>().is_err());
+            assert!(right.try_cast::<[[u8; 3]]>().is_err());
+        }
+
+        {
+            let (_, right) = buffer.split_at(28);
+            assert!(right.try_cast::<[u64]>().is_err());
+        }
+    }
+
+    #[test]
+    fn project() {
+        #[derive(BufferContents)]
+        #[repr(C)]
+        struct Test {
+            a: u32,
+            b: [f32; 3],
+            c: u64,
+            d: [u16],
+        }
+
+        let (device, _) = gfx_dev_and_queue!();
+        let allocator = Arc::new(StandardMemoryAllocator::new_default(device));
+
+        let buffer = Buffer::new_unsized::<Test>(
+            allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+            4,
+        )
+        .unwrap();
+
+        {
+            let b = buffer.clone().project(|test| &test.b);
+            assert_eq!(b.offset(), buffer.offset() + 4);
+            assert_eq!(b.size(), 12);
+        }
+
+        {
+            let c = buffer.clone().project(|test| &test.c);
+            assert_eq!(c.offset(), buffer.offset() + 16);
+            assert_eq!(c.size(), 8);
+        }
+
+        {
+            let d = buffer.clone().project(|test| &test.d);
+            assert_eq!(d.offset(), buffer.offset() + 24);
+            assert_eq!(d.len(), 4);
+        }
+
+        {
+            // A slice of a field that is not the tail only covers that field.
+            let b = buffer.clone().project(|test| &test.b[1..]);
+            assert_eq!(b.offset(), buffer.offset() + 8);
+            assert_eq!(b.len(), 2);
+        }
+
+        {
+            static OUTSIDE: u32 = 0;
+            assert_should_panic!({ buffer.project(|_| &OUTSIDE) });
+        }
+    }
 }
@@ -53,13 +53,19 @@ impl RawBuffer {
     /// - Panics if `create_info.size` is zero.
     /// - Panics if `create_info.usage` is empty.
     #[inline]
+    #[track_caller]
     pub fn new(
         device: Arc<Device>,
         create_info: BufferCreateInfo,
     ) -> Result<Self, Validated<VulkanError>> {
         Self::validate_new(&device, &create_info)?;
 
-        unsafe { Ok(Self::new_unchecked(device, create_info)?) }
+        let raw_buffer = unsafe { Self::new_unchecked(device, create_info)? };
+        raw_buffer
+            .device()
+            .set_auto_debug_utils_object_name(&raw_buffer, "Buffer");
+
+        Ok(raw_buffer)
     }
 
     fn validate_new(
@@ -210,7 +216,7 @@ impl RawBuffer {
                 .unwrap();
         }
 
-        RawBuffer {
+        let raw_buffer = RawBuffer {
             handle,
             device: InstanceOwnedDebugWrapper(device),
             id: Self::next_id(),
@@ -221,7 +227,12 @@ impl RawBuffer {
             external_memory_handle_types,
             memory_requirements,
             needs_destruction,
+        };
+        if let Some(resources) = raw_buffer.device.resources() {
+            resources.add_buffer(&raw_buffer);
         }
+
+        raw_buffer
     }
 
     fn get_memory_requirements(device: &Device, handle: ash::vk::Buffer) -> MemoryRequirements {
@@ -400,13 +411,28 @@ impl RawBuffer {
             }
         }
 
-        if memory_type
+        if self.flags.intersects(BufferCreateFlags::PROTECTED) {
+            if !memory_type
+                .property_flags
+                .intersects(MemoryPropertyFlags::PROTECTED)
+            {
+                return Err(Box::new(ValidationError {
+                    problem: "`self.flags()` contains `BufferCreateFlags::PROTECTED`, but the \
+                        `property_flags` of the memory type of `allocation.device_memory()` does \
+                        not contain `MemoryPropertyFlags::PROTECTED`"
+                        .into(),
+                    vuids: &["VUID-VkBindBufferMemoryInfo-None-01898"],
+                    ..Default::default()
+                }));
+            }
+        } else if memory_type
             .property_flags
             .intersects(MemoryPropertyFlags::PROTECTED)
         {
             return Err(Box::new(ValidationError {
-                problem: "the `property_flags` of the memory type of \
-                    `allocation.device_memory()` contains `MemoryPropertyFlags::PROTECTED`"
+                problem: "`self.flags()` does not contain `BufferCreateFlags::PROTECTED`, but the \
+                    `property_flags` of the memory type of `allocation.device_memory()` contains \
+                    `MemoryPropertyFlags::PROTECTED`"
                     .into(),
                 vuids: &["VUID-VkBindBufferMemoryInfo-None-01899"],
                 ..Default::default()
@@ -616,6 +642,10 @@ impl RawBuffer {
 impl Drop for RawBuffer {
     #[inline]
     fn drop(&mut self) {
+        if let Some(resources) = self.device.resources() {
+            resources.remove_buffer(self);
+        }
+
         if self.needs_destruction {
             unsafe {
                 let fns = self.device.fns();
@@ -737,6 +767,19 @@ impl BufferCreateInfo {
             }));
         }
 
+        if flags.intersects(BufferCreateFlags::PROTECTED)
+            && !device.enabled_features().protected_memory
+        {
+            return Err(Box::new(ValidationError {
+                context: "flags".into(),
+                problem: "contains `BufferCreateFlags::PROTECTED`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "protected_memory",
+                )])]),
+                vuids: &["VUID-VkBufferCreateInfo-flags-01887"],
+            }));
+        }
+
         /* Enable when sparse binding is properly handled
         if let Some(sparse_level) = sparse {
             if !device.enabled_features().sparse_binding {
@@ -44,7 +44,8 @@ use crate::{
     format::{Format, FormatFeatures},
     macros::impl_id_counter,
     memory::{is_aligned, DeviceAlignment},
-    DeviceSize, Validated, ValidationError, Version, VulkanError, VulkanObject,
+    DeviceSize, Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, Version,
+    VulkanError, VulkanObject,
 };
 use std::{mem::MaybeUninit, num::NonZeroU64, ops::Range, ptr, sync::Arc};
 
@@ -59,6 +60,7 @@ pub struct BufferView {
     format: Format,
     format_features: FormatFeatures,
     range: Range<DeviceSize>,
+    usage: BufferUsage,
 }
 
 impl BufferView {
@@ -84,11 +86,30 @@ impl BufferView {
             .validate(device)
             .map_err(|err| err.add_context("create_info"))?;
 
-        let &BufferViewCreateInfo { format, _ne: _ } = create_info;
+        let &BufferViewCreateInfo {
+            format,
+            usage,
+            _ne: _,
+        } = create_info;
 
         let buffer = subbuffer.buffer();
         let properties = device.physical_device().properties();
 
+        if !buffer.usage().contains(usage) {
+            return Err(Box::new(ValidationError {
+                problem: "`create_info.usage` is not a subset of `subbuffer.buffer().usage()`"
+                    .into(),
+                vuids: &["VUID-VkBufferViewCreateInfo-pNext-08781"],
+                ..Default::default()
+            }));
+        }
+
+        let usage = if usage.is_empty() {
+            buffer.usage()
+        } else {
+            usage
+        };
+
         let format_features = unsafe {
             device
                 .physical_device()
@@ -96,9 +117,7 @@ impl BufferView {
                 .buffer_features
         };
 
-        if !buffer
-            .usage()
-            .intersects(BufferUsage::UNIFORM_TEXEL_BUFFER | BufferUsage::STORAGE_TEXEL_BUFFER)
+        if !usage.intersects(BufferUsage::UNIFORM_TEXEL_BUFFER | BufferUsage::STORAGE_TEXEL_BUFFER)
         {
             return Err(Box::new(ValidationError {
                 context: "subbuffer".into(),
@@ -110,12 +129,12 @@ impl BufferView {
             }));
         }
 
-        if buffer.usage().intersects(BufferUsage::UNIFORM_TEXEL_BUFFER)
+        if usage.intersects(BufferUsage::UNIFORM_TEXEL_BUFFER)
             && !format_features.intersects(FormatFeatures::UNIFORM_TEXEL_BUFFER)
         {
             return Err(Box::new(ValidationError {
-                problem: "`subbuffer` was created with the `BufferUsage::UNIFORM_TEXEL_BUFFER` \
-                    usage, but the format features of `create_info.format` do not include \
+                problem: "the usage of the view contains `BufferUsage::UNIFORM_TEXEL_BUFFER`, \
+                    but the format features of `create_info.format` do not include \
                     `FormatFeatures::UNIFORM_TEXEL_BUFFER`"
                     .into(),
                 vuids: &["VUID-VkBufferViewCreateInfo-buffer-00933"],
@@ -123,12 +142,12 @@ impl BufferView {
             }));
         }
 
-        if buffer.usage().intersects(BufferUsage::STORAGE_TEXEL_BUFFER)
+        if usage.intersects(BufferUsage::STORAGE_TEXEL_BUFFER)
             && !format_features.intersects(FormatFeatures::STORAGE_TEXEL_BUFFER)
         {
             return Err(Box::new(ValidationError {
-                problem: "`subbuffer` was created with the `BufferUsage::STORAGE_TEXEL_BUFFER` \
-                    usage, but the format features of `create_info.format` do not include \
+                problem: "the usage of the view contains `BufferUsage::STORAGE_TEXEL_BUFFER`, \
+                    but the format features of `create_info.format` do not include \
                     `FormatFeatures::STORAGE_TEXEL_BUFFER`"
                     .into(),
                 vuids: &["VUID-VkBufferViewCreateInfo-buffer-00934"],
@@ -171,7 +190,7 @@ impl BufferView {
             })
             .unwrap();
 
-            if buffer.usage().intersects(BufferUsage::STORAGE_TEXEL_BUFFER) {
+            if usage.intersects(BufferUsage::STORAGE_TEXEL_BUFFER) {
                 if properties
                     .storage_texel_buffer_offset_single_texel_alignment
                     .unwrap()
@@ -184,8 +203,8 @@ impl BufferView {
                             .min(element_size),
                     ) {
                         return Err(Box::new(ValidationError {
-                            problem: "`subbuffer` was created with the \
-                                `BufferUsage::STORAGE_TEXEL_BUFFER` usage, and the \
+                            problem: "the usage of the view contains \
+                                `BufferUsage::STORAGE_TEXEL_BUFFER`, and the \
                                 `storage_texel_buffer_offset_single_texel_alignment` \
                                 property is `true`, but \
                                 `subbuffer.offset()` is not a multiple of the \
@@ -204,8 +223,8 @@ impl BufferView {
                             .unwrap(),
                     ) {
                         return Err(Box::new(ValidationError {
-                            problem: "`subbuffer` was created with the \
-                                `BufferUsage::STORAGE_TEXEL_BUFFER` usage, and the \
+                            problem: "the usage of the view contains \
+                                `BufferUsage::STORAGE_TEXEL_BUFFER`, and the \
                                 `storage_texel_buffer_offset_single_texel_alignment` \
                                 property is `false`, but \
                                 `subbuffer.offset()` is not a multiple of the \
@@ -218,7 +237,7 @@ impl BufferView {
                 }
             }
 
-            if buffer.usage().intersects(BufferUsage::UNIFORM_TEXEL_BUFFER) {
+            if usage.intersects(BufferUsage::UNIFORM_TEXEL_BUFFER) {
                 if properties
                     .uniform_texel_buffer_offset_single_texel_alignment
                     .unwrap()
@@ -231,8 +250,8 @@ impl BufferView {
                             .min(element_size),
                     ) {
                         return Err(Box::new(ValidationError {
-                            problem: "`subbuffer` was created with the \
-                                `BufferUsage::UNIFORM_TEXEL_BUFFER` usage, and the \
+                            problem: "the usage of the view contains \
+                                `BufferUsage::UNIFORM_TEXEL_BUFFER`, and the \
                                 `uniform_texel_buffer_offset_single_texel_alignment` \
                                 property is `false`, but \
                                 `subbuffer.offset()` is not a multiple of the \
@@ -251,8 +270,8 @@ impl BufferView {
                             .unwrap(),
                     ) {
                         return Err(Box::new(ValidationError {
-                            problem: "`subbuffer` was created with the \
-                                `BufferUsage::UNIFORM_TEXEL_BUFFER` usage, and the \
+                            problem: "the usage of the view contains \
+                                `BufferUsage::UNIFORM_TEXEL_BUFFER`, and the \
                                 `uniform_texel_buffer_offset_single_texel_alignment` \
                                 property is `false`, but \
                                 `subbuffer.offset()` is not a multiple of the \
@@ -287,11 +306,15 @@ impl BufferView {
         subbuffer: Subbuffer<impl ?Sized>,
         create_info: BufferViewCreateInfo,
     ) -> Result<Arc<BufferView>, VulkanError> {
-        let &BufferViewCreateInfo { format, _ne: _ } = &create_info;
+        let &BufferViewCreateInfo {
+            format,
+            usage,
+            _ne: _,
+        } = &create_info;
 
         let device = subbuffer.device();
 
-        let create_info_vk = ash::vk::BufferViewCreateInfo {
+        let mut create_info_vk = ash::vk::BufferViewCreateInfo {
             flags: ash::vk::BufferViewCreateFlags::empty(),
             buffer: subbuffer.buffer().handle(),
             format: format.into(),
@@ -299,6 +322,18 @@ impl BufferView {
             range: subbuffer.size(),
             ..Default::default()
         };
+        let usage_create_info_vk;
+
+        if !usage.is_empty() {
+            let next = usage_create_info_vk.insert(ash::vk::BufferUsageFlags2CreateInfoKHR {
+                usage: ash::vk::BufferUsageFlags2KHR::from_raw(
+                    ash::vk::BufferUsageFlags::from(usage).as_raw().into(),
+                ),
+                ..Default::default()
+            });
+
+            create_info_vk.p_next = <*const _>::cast(next);
+        }
 
         let handle = unsafe {
             let fns = device.fns();
@@ -328,8 +363,17 @@ impl BufferView {
         handle: ash::vk::BufferView,
         create_info: BufferViewCreateInfo,
     ) -> Arc<BufferView> {
-        let &BufferViewCreateInfo { format, _ne: _ } = &create_info;
+        let &BufferViewCreateInfo {
+            format,
+            usage,
+            _ne: _,
+        } = &create_info;
         let size = subbuffer.size();
+        let usage = if usage.is_empty() {
+            subbuffer.buffer().usage()
+        } else {
+            usage
+        };
         let format_features = unsafe {
             subbuffer
                 .device()
@@ -345,6 +389,7 @@ impl BufferView {
             format,
             format_features,
             range: 0..size,
+            usage,
         })
     }
 
@@ -371,6 +416,15 @@ impl BufferView {
     pub fn range(&self) -> Range<DeviceSize> {
         self.range.clone()
     }
+
+    /// Returns the usage of this view.
+    ///
+    /// This is [`BufferViewCreateInfo::usage`] if it was not empty, and the usage of the buffer
+    /// otherwise.
+    #[inline]
+    pub fn usage(&self) -> BufferUsage {
+        self.usage
+    }
 }
 
 impl Drop for BufferView {
@@ -413,6 +467,20 @@ pub struct BufferViewCreateInfo {
     /// The default value is `Format::UNDEFINED`.
     pub format: Format,
 
+    /// The usages of the buffer that the view can be used for.
+    ///
+    /// If this is empty, the view has all the usages of the buffer. Otherwise, it must be a
+    /// subset of the usage of the buffer, and can only contain
+    /// [`BufferUsage::UNIFORM_TEXEL_BUFFER`] and [`BufferUsage::STORAGE_TEXEL_BUFFER`]. This
+    /// allows creating a view with a format that only supports one of the two usages, from a
+    /// buffer that has both. If not empty, the [`maintenance5`] feature must be enabled on the
+    /// device.
+    ///
+    /// The default value is [`BufferUsage::empty()`].
+    ///
+    /// [`maintenance5`]: crate::device::DeviceFeatures::maintenance5
+    pub usage: BufferUsage,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -421,6 +489,7 @@ impl Default for BufferViewCreateInfo {
     fn default() -> Self {
         Self {
             format: Format::UNDEFINED,
+            usage: BufferUsage::empty(),
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -428,13 +497,48 @@ impl Default for BufferViewCreateInfo {
 
 impl BufferViewCreateInfo {
     pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
-        let Self { format, _ne: _ } = self;
+        let &Self {
+            format,
+            usage,
+            _ne: _,
+        } = self;
 
         format.validate_device(device).map_err(|err| {
             err.add_context("format")
                 .set_vuids(&["VUID-VkBufferViewCreateInfo-format-parameter"])
         })?;
 
+        if !usage.is_empty() {
+            if !device.enabled_features().maintenance5 {
+                return Err(Box::new(ValidationError {
+                    context: "usage".into(),
+                    problem: "is not empty".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "maintenance5",
+                    )])]),
+                    ..Default::default()
+                }));
+            }
+
+            usage.validate_device(device).map_err(|err| {
+                err.add_context("usage")
+                    .set_vuids(&["VUID-VkBufferUsageFlags2CreateInfoKHR-usage-parameter"])
+            })?;
+
+            if !(BufferUsage::UNIFORM_TEXEL_BUFFER | BufferUsage::STORAGE_TEXEL_BUFFER)
+                .contains(usage)
+            {
+                return Err(Box::new(ValidationError {
+                    context: "usage".into(),
+                    problem: "contains flags other than `BufferUsage::UNIFORM_TEXEL_BUFFER` and \
+                        `BufferUsage::STORAGE_TEXEL_BUFFER`"
+                        .into(),
+                    vuids: &["VUID-VkBufferViewCreateInfo-pNext-08780"],
+                    ..Default::default()
+                }));
+            }
+        }
+
         Ok(())
     }
 }
@@ -585,4 +689,45 @@ mod tests {
             _ => panic!(),
         }
     }
+
+    #[test]
+    fn restricted_usage() {
+        let (device, _) = gfx_dev_and_queue!(maintenance5; khr_maintenance5, khr_dynamic_rendering);
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device));
+
+        let buffer = Buffer::new_slice::<[u8; 4]>(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::UNIFORM_TEXEL_BUFFER
+                    | BufferUsage::STORAGE_TEXEL_BUFFER
+                    | BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+            128,
+        )
+        .unwrap();
+
+        let view = BufferView::new(
+            buffer.clone(),
+            BufferViewCreateInfo {
+                format: Format::R8G8B8A8_UNORM,
+                usage: BufferUsage::UNIFORM_TEXEL_BUFFER,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(view.usage(), BufferUsage::UNIFORM_TEXEL_BUFFER);
+
+        // Only the texel buffer usages can be given to a view.
+        assert!(BufferView::new(
+            buffer,
+            BufferViewCreateInfo {
+                format: Format::R8G8B8A8_UNORM,
+                usage: BufferUsage::UNIFORM_TEXEL_BUFFER | BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+        )
+        .is_err());
+    }
 }
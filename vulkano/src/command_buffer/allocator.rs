@@ -6,29 +6,32 @@
 
 use super::{
     pool::{
-        CommandBufferAllocateInfo, CommandPool, CommandPoolAlloc, CommandPoolCreateInfo,
-        CommandPoolResetFlags,
+        CommandBufferAllocateInfo, CommandPool, CommandPoolAlloc, CommandPoolCreateFlags,
+        CommandPoolCreateInfo, CommandPoolResetFlags,
     },
     CommandBufferLevel,
 };
 use crate::{
     device::{Device, DeviceOwned},
     instance::InstanceOwnedDebugWrapper,
-    Validated, ValidationError, VulkanError,
+    Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, Version, VulkanError,
 };
 use crossbeam_queue::ArrayQueue;
 use smallvec::SmallVec;
 use std::{
-    cell::UnsafeCell,
+    cell::{Cell, UnsafeCell},
     error::Error,
     fmt::{Debug, Display, Error as FmtError, Formatter},
-    mem, ptr,
-    sync::{Arc, Weak},
+    mem,
+    num::NonZeroU32,
+    ptr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Weak,
+    },
 };
 use thread_local::ThreadLocal;
 
-const MAX_POOLS: usize = 32;
-
 /// Types that manage the memory of command buffers.
 ///
 /// # Safety
@@ -173,29 +176,94 @@ impl AllocationHandle {
 ///
 /// This allocator only needs to lock when a thread first allocates or when a thread that
 /// previously allocated exits. In all other cases, allocation is lock-free.
+///
+/// Because the pools are local to each thread, worker threads recording command buffers in
+/// parallel never wait on each other. At the end of a frame, each thread can reset all of its
+/// pools at once using [`try_reset_pools`], which recycles every command buffer allocated from
+/// them without freeing them individually.
+///
+/// How much memory the pools keep can be controlled with the [`reset_flags`], [`trim_interval`]
+/// and [`max_reserved_pools`] parameters of the create info. Drivers differ in whether they give
+/// memory back when a pool is reset, so for long-running applications it can be worth trimming the
+/// pools periodically. The [`statistics`] method can be used to see how many pools exist and how
+/// often they are reset and trimmed.
+///
+/// [`try_reset_pools`]: Self::try_reset_pools
+/// [`reset_flags`]: StandardCommandBufferAllocatorCreateInfo::reset_flags
+/// [`trim_interval`]: StandardCommandBufferAllocatorCreateInfo::trim_interval
+/// [`max_reserved_pools`]: StandardCommandBufferAllocatorCreateInfo::max_reserved_pools
+/// [`statistics`]: Self::statistics
 #[derive(Debug)]
 pub struct StandardCommandBufferAllocator {
     device: InstanceOwnedDebugWrapper<Arc<Device>>,
     // Each queue family index points directly to its entry.
     pools: ThreadLocal<SmallVec<[UnsafeCell<Option<Entry>>; 8]>>,
     buffer_count: [usize; 2],
+    pool_flags: CommandPoolCreateFlags,
+    reset_policy: ResetPolicy,
+    max_reserved_pools: usize,
+    counters: Arc<PoolCounters>,
 }
 
 impl StandardCommandBufferAllocator {
     /// Creates a new `StandardCommandBufferAllocator`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.max_reserved_pools` is zero.
     #[inline]
     pub fn new(device: Arc<Device>, create_info: StandardCommandBufferAllocatorCreateInfo) -> Self {
+        let StandardCommandBufferAllocatorCreateInfo {
+            primary_buffer_count,
+            secondary_buffer_count,
+            protected,
+            reset_flags,
+            trim_interval,
+            max_reserved_pools,
+            _ne: _,
+        } = create_info;
+
+        assert!(
+            max_reserved_pools != 0,
+            "`max_reserved_pools` must be greater than zero",
+        );
+
         let mut buffer_count = [0, 0];
-        buffer_count[CommandBufferLevel::Primary as usize] = create_info.primary_buffer_count;
-        buffer_count[CommandBufferLevel::Secondary as usize] = create_info.secondary_buffer_count;
+        buffer_count[CommandBufferLevel::Primary as usize] = primary_buffer_count;
+        buffer_count[CommandBufferLevel::Secondary as usize] = secondary_buffer_count;
+
+        let pool_flags = if protected {
+            CommandPoolCreateFlags::PROTECTED
+        } else {
+            CommandPoolCreateFlags::empty()
+        };
+
+        // Trimming is only available with `khr_maintenance1`, so it's skipped without it.
+        let trim_interval = trim_interval.filter(|_| {
+            device.api_version() >= Version::V1_1 || device.enabled_extensions().khr_maintenance1
+        });
 
         StandardCommandBufferAllocator {
             device: InstanceOwnedDebugWrapper(device),
             pools: ThreadLocal::new(),
             buffer_count,
+            pool_flags,
+            reset_policy: ResetPolicy {
+                flags: reset_flags,
+                trim_interval,
+            },
+            max_reserved_pools,
+            counters: Arc::new(PoolCounters::default()),
         }
     }
 
+    /// Returns statistics about the command pools of the allocator, across all threads and queue
+    /// families.
+    #[inline]
+    pub fn statistics(&self) -> StandardCommandBufferAllocatorStatistics {
+        self.counters.statistics()
+    }
+
     /// Tries to reset the [`CommandPool`] that's currently in use for the given queue family index
     /// on the current thread.
     ///
@@ -217,12 +285,50 @@ impl StandardCommandBufferAllocator {
         flags: CommandPoolResetFlags,
     ) -> Result<(), Validated<ResetCommandPoolError>> {
         if let Some(entry) = unsafe { &mut *self.entry(queue_family_index) }.as_mut() {
-            entry.try_reset_pool(flags)
+            entry.try_reset_pool(flags, &self.reset_policy)
         } else {
             Ok(())
         }
     }
 
+    /// Tries to reset the [`CommandPool`]s that are currently in use on the current thread, for
+    /// every queue family index at once.
+    ///
+    /// This is intended to be called by each worker thread once per frame, after all command
+    /// buffers it recorded for the previous frame have been dropped. Resetting a pool is O(1) with
+    /// regards to the number of command buffers allocated from it, and since the pools are local
+    /// to the thread, no other threads are blocked while doing so.
+    ///
+    /// Pools that are still in use are skipped, and [`ResetCommandPoolError::InUse`] is returned
+    /// after the remaining pools have been reset. Entries that weren't initialized yet or were
+    /// [cleared] are ignored.
+    ///
+    /// [cleared]: Self::clear
+    pub fn try_reset_pools(
+        &self,
+        flags: CommandPoolResetFlags,
+    ) -> Result<(), Validated<ResetCommandPoolError>> {
+        let Some(pools) = self.pools.get() else {
+            return Ok(());
+        };
+
+        let mut result = Ok(());
+
+        for entry in pools {
+            if let Some(entry) = unsafe { &mut *entry.get() }.as_mut() {
+                match entry.try_reset_pool(flags, &self.reset_policy) {
+                    Ok(()) => {}
+                    Err(Validated::Error(ResetCommandPoolError::InUse)) => {
+                        result = Err(ResetCommandPoolError::InUse.into());
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        result
+    }
+
     /// Clears the entry for the given queue family index and the current thread. This does not
     /// mean that the pools are dropped immediately. A pool is kept alive for as long as command
     /// buffers allocated from it exist.
@@ -271,20 +377,44 @@ unsafe impl CommandBufferAllocator for StandardCommandBufferAllocator {
             }))?;
         }
 
+        if self
+            .pool_flags
+            .intersects(CommandPoolCreateFlags::PROTECTED)
+            && !self.device.enabled_features().protected_memory
+        {
+            Err(Box::new(ValidationError {
+                problem: "the allocator was created with `protected` set to `true`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "protected_memory",
+                )])]),
+                vuids: &["VUID-VkCommandPoolCreateInfo-flags-02860"],
+                ..Default::default()
+            }))?;
+        }
+
         let entry = unsafe { &mut *self.entry(queue_family_index) };
 
         if entry.is_none() {
             *entry = Some(Entry::new(
                 self.device.clone(),
                 queue_family_index,
+                self.pool_flags,
                 &self.buffer_count,
-                Arc::new(ArrayQueue::new(MAX_POOLS)),
+                Arc::new(ArrayQueue::new(self.max_reserved_pools)),
+                &self.counters,
             )?);
         }
 
         let entry = entry.as_mut().unwrap();
 
-        Ok(entry.allocate(queue_family_index, level, &self.buffer_count)?)
+        Ok(entry.allocate(
+            queue_family_index,
+            self.pool_flags,
+            level,
+            &self.buffer_count,
+            &self.reset_policy,
+            &self.counters,
+        )?)
     }
 
     #[inline]
@@ -319,8 +449,8 @@ unsafe impl CommandBufferAllocator for StandardCommandBufferAllocator {
             if let Some(reserve) = pool.pool_reserve.upgrade() {
                 // If there is not enough space in the reserve, we destroy the pool. The only way
                 // this can happen is if something is resource hogging, forcing new pools to be
-                // created such that the number exceeds `MAX_POOLS`, and then drops them all at
-                // once.
+                // created such that the number exceeds `max_reserved_pools`, and then drops them
+                // all at once.
                 let _ = reserve.push(pool);
             }
         }
@@ -369,11 +499,20 @@ impl Entry {
     fn new(
         device: Arc<Device>,
         queue_family_index: u32,
+        pool_flags: CommandPoolCreateFlags,
         buffer_count: &[usize; 2],
         pool_reserve: Arc<ArrayQueue<Arc<Pool>>>,
+        counters: &Arc<PoolCounters>,
     ) -> Result<Self, VulkanError> {
         Ok(Entry {
-            pool: Pool::new(device, queue_family_index, buffer_count, &pool_reserve)?,
+            pool: Pool::new(
+                device,
+                queue_family_index,
+                pool_flags,
+                buffer_count,
+                &pool_reserve,
+                counters,
+            )?,
             allocations: [0; 2],
             pool_reserve,
         })
@@ -382,8 +521,11 @@ impl Entry {
     fn allocate(
         &mut self,
         queue_family_index: u32,
+        pool_flags: CommandPoolCreateFlags,
         level: CommandBufferLevel,
         buffer_count: &[usize; 2],
+        reset_policy: &ResetPolicy,
+        counters: &Arc<PoolCounters>,
     ) -> Result<CommandBufferAlloc, VulkanError> {
         if self.allocations[level as usize] >= buffer_count[level as usize] {
             // This can happen if there's only ever one allocation alive at any point in time. In
@@ -400,11 +542,7 @@ impl Entry {
             if Arc::strong_count(&self.pool) == 1 {
                 // SAFETY: We checked that the pool has a single strong reference above, meaning
                 // that all the allocations we gave out must have been deallocated.
-                unsafe {
-                    self.pool
-                        .inner
-                        .reset_unchecked(CommandPoolResetFlags::empty())
-                }?;
+                unsafe { self.pool.reset(reset_policy.flags, reset_policy) }?;
 
                 self.allocations = [0; 2];
             } else {
@@ -412,7 +550,7 @@ impl Entry {
                     // SAFETY: We checked that the pool has a single strong reference when
                     // deallocating, meaning that all the allocations we gave out must have been
                     // deallocated.
-                    unsafe { pool.inner.reset_unchecked(CommandPoolResetFlags::empty()) }?;
+                    unsafe { pool.reset(reset_policy.flags, reset_policy) }?;
 
                     self.pool = pool;
                     self.allocations = [0; 2];
@@ -420,8 +558,10 @@ impl Entry {
                     *self = Entry::new(
                         self.pool.inner.device().clone(),
                         queue_family_index,
+                        pool_flags,
                         buffer_count,
                         self.pool_reserve.clone(),
+                        counters,
                     )?;
                 }
             }
@@ -448,13 +588,17 @@ impl Entry {
     fn try_reset_pool(
         &mut self,
         flags: CommandPoolResetFlags,
+        reset_policy: &ResetPolicy,
     ) -> Result<(), Validated<ResetCommandPoolError>> {
         if let Some(pool) = Arc::get_mut(&mut self.pool) {
-            unsafe { pool.inner.reset(flags) }.map_err(|err| match err {
-                Validated::Error(err) => Validated::Error(ResetCommandPoolError::VulkanError(err)),
-                Validated::ValidationError(err) => err.into(),
+            flags.validate_device(pool.inner.device()).map_err(|err| {
+                err.add_context("flags")
+                    .set_vuids(&["VUID-vkResetCommandPool-flags-parameter"])
             })?;
 
+            unsafe { pool.reset(flags, reset_policy) }
+                .map_err(ResetCommandPoolError::VulkanError)?;
+
             self.allocations = [0; 2];
 
             Ok(())
@@ -472,18 +616,24 @@ struct Pool {
     buffer_reserve: [Option<ArrayQueue<CommandPoolAlloc>>; 2],
     // Where to return this pool once there are no more current allocations.
     pool_reserve: Weak<ArrayQueue<Arc<Self>>>,
+    // How many times the pool has been reset since it was last trimmed.
+    resets_since_trim: Cell<u32>,
+    counters: Arc<PoolCounters>,
 }
 
 impl Pool {
     fn new(
         device: Arc<Device>,
         queue_family_index: u32,
+        flags: CommandPoolCreateFlags,
         buffer_counts: &[usize; 2],
         pool_reserve: &Arc<ArrayQueue<Arc<Self>>>,
+        counters: &Arc<PoolCounters>,
     ) -> Result<Arc<Self>, VulkanError> {
         let inner = CommandPool::new(
             device,
             CommandPoolCreateInfo {
+                flags,
                 queue_family_index,
                 ..Default::default()
             },
@@ -511,12 +661,80 @@ impl Pool {
             buffer_reserve[level as usize] = Some(pool);
         }
 
+        counters.pools_created.fetch_add(1, Ordering::Relaxed);
+
         Ok(Arc::new(Pool {
             inner: Arc::new(inner),
             buffer_reserve,
             pool_reserve: Arc::downgrade(pool_reserve),
+            resets_since_trim: Cell::new(0),
+            counters: counters.clone(),
         }))
     }
+
+    /// Resets the pool with `flags`, and trims it if `reset_policy` says it's due.
+    ///
+    /// # Safety
+    ///
+    /// - All command buffers allocated from the pool must have been returned to it.
+    unsafe fn reset(
+        &self,
+        flags: CommandPoolResetFlags,
+        reset_policy: &ResetPolicy,
+    ) -> Result<(), VulkanError> {
+        unsafe { self.inner.reset_unchecked(flags) }?;
+        self.counters.pool_resets.fetch_add(1, Ordering::Relaxed);
+
+        if let Some(trim_interval) = reset_policy.trim_interval {
+            let resets = self.resets_since_trim.get() + 1;
+
+            if resets >= trim_interval.get() {
+                unsafe { self.inner.trim_unchecked() };
+                self.counters.pool_trims.fetch_add(1, Ordering::Relaxed);
+                self.resets_since_trim.set(0);
+            } else {
+                self.resets_since_trim.set(resets);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        self.counters
+            .pools_destroyed
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ResetPolicy {
+    flags: CommandPoolResetFlags,
+    trim_interval: Option<NonZeroU32>,
+}
+
+#[derive(Debug, Default)]
+struct PoolCounters {
+    pools_created: AtomicU64,
+    pools_destroyed: AtomicU64,
+    pool_resets: AtomicU64,
+    pool_trims: AtomicU64,
+}
+
+impl PoolCounters {
+    fn statistics(&self) -> StandardCommandBufferAllocatorStatistics {
+        let pools_created = self.pools_created.load(Ordering::Relaxed);
+        let pools_destroyed = self.pools_destroyed.load(Ordering::Relaxed);
+
+        StandardCommandBufferAllocatorStatistics {
+            pool_count: pools_created.saturating_sub(pools_destroyed),
+            pools_created,
+            pool_resets: self.pool_resets.load(Ordering::Relaxed),
+            pool_trims: self.pool_trims.load(Ordering::Relaxed),
+        }
+    }
 }
 
 /// Parameters to create a new [`StandardCommandBufferAllocator`].
@@ -542,6 +760,45 @@ pub struct StandardCommandBufferAllocatorCreateInfo {
     /// The default value is `0`.
     pub secondary_buffer_count: usize,
 
+    /// Whether the command pools are created with the [`CommandPoolCreateFlags::PROTECTED`] flag,
+    /// so that all command buffers allocated by the allocator are protected.
+    ///
+    /// If set to `true`, the [`protected_memory`] feature must be enabled on the device.
+    ///
+    /// The default value is `false`.
+    ///
+    /// [`protected_memory`]: crate::device::DeviceFeatures::protected_memory
+    pub protected: bool,
+
+    /// The flags that a pool is reset with when the allocator recycles it.
+    ///
+    /// With [`CommandPoolResetFlags::RELEASE_RESOURCES`], the memory of the command buffers is
+    /// given back to the system each time, instead of being kept for the next command buffers
+    /// that are recorded. This keeps memory usage low, at the cost of allocating the memory again
+    /// when recording.
+    ///
+    /// The default value is empty.
+    pub reset_flags: CommandPoolResetFlags,
+
+    /// If `Some`, each pool is trimmed after it has been reset this many times, giving unused
+    /// memory back to the system.
+    ///
+    /// Trimming requires the device API version to be at least 1.1, or the [`khr_maintenance1`]
+    /// extension to be enabled. Otherwise, pools are never trimmed.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`khr_maintenance1`]: crate::device::DeviceExtensions::khr_maintenance1
+    pub trim_interval: Option<NonZeroU32>,
+
+    /// How many unused pools are kept around for reuse, per thread and queue family. When a pool
+    /// becomes unused and this many pools are already kept, the pool is destroyed.
+    ///
+    /// This must be greater than zero.
+    ///
+    /// The default value is `32`.
+    pub max_reserved_pools: usize,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -551,11 +808,32 @@ impl Default for StandardCommandBufferAllocatorCreateInfo {
         StandardCommandBufferAllocatorCreateInfo {
             primary_buffer_count: 32,
             secondary_buffer_count: 0,
+            protected: false,
+            reset_flags: CommandPoolResetFlags::empty(),
+            trim_interval: None,
+            max_reserved_pools: 32,
             _ne: crate::NonExhaustive(()),
         }
     }
 }
 
+/// Statistics about the command pools of a [`StandardCommandBufferAllocator`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StandardCommandBufferAllocatorStatistics {
+    /// The number of pools that currently exist, including the ones that are kept for reuse.
+    pub pool_count: u64,
+
+    /// The total number of pools that have been created.
+    pub pools_created: u64,
+
+    /// The total number of times that a pool has been reset.
+    pub pool_resets: u64,
+
+    /// The total number of times that a pool has been trimmed.
+    pub pool_trims: u64,
+}
+
 /// Error that can be returned when resetting a [`CommandPool`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ResetCommandPoolError {
@@ -1,8 +1,9 @@
 use super::{
-    CommandBuffer, CommandInfo, RenderPassCommand, Resource, ResourceUseRef2, SubmitState,
+    CommandBuffer, CommandBufferStatistics, CommandInfo, OwnershipTransfer, RenderPassCommand,
+    Resource, ResourceUseRef2, SubmitState,
 };
 use crate::{
-    buffer::{Buffer, IndexBuffer, Subbuffer},
+    buffer::{Buffer, BufferMemory, IndexBuffer, Subbuffer},
     command_buffer::{
         allocator::CommandBufferAllocator,
         sys::{CommandBufferBeginInfo, RawRecordingCommandBuffer},
@@ -14,14 +15,20 @@ use crate::{
     },
     descriptor_set::{DescriptorSetResources, DescriptorSetWithOffsets},
     device::{Device, DeviceOwned},
-    image::{view::ImageView, Image, ImageAspects, ImageLayout, ImageSubresourceRange},
+    image::{
+        view::ImageView, Image, ImageAspects, ImageLayout, ImageMemory, ImageSubresourceRange,
+    },
+    memory::{DeviceMemory, ResourceMemory},
     pipeline::{
         graphics::{
             color_blend::LogicOp,
             conservative_rasterization::ConservativeRasterizationMode,
             depth_stencil::{CompareOp, StencilOps},
+            discard_rectangle::DiscardRectangleMode,
             input_assembly::PrimitiveTopology,
-            rasterization::{CullMode, DepthBiasState, FrontFace, LineStipple},
+            rasterization::{
+                CullMode, DepthBiasState, FrontFace, LineRasterizationMode, LineStipple,
+            },
             subpass::PipelineRenderingCreateInfo,
             vertex_input::VertexInputState,
             viewport::{Scissor, Viewport},
@@ -32,9 +39,10 @@ use crate::{
     range_map::RangeMap,
     range_set::RangeSet,
     render_pass::{Framebuffer, Subpass},
+    swapchain::Swapchain,
     sync::{
         AccessFlags, BufferMemoryBarrier, DependencyFlags, DependencyInfo, ImageMemoryBarrier,
-        PipelineStageAccessFlags, PipelineStages,
+        MemoryBarrier, PipelineStageAccessFlags, PipelineStages,
     },
     DeviceSize, Validated, ValidationError, VulkanError,
 };
@@ -157,6 +165,14 @@ impl RecordingCommandBuffer {
             }));
         }
 
+        if self.builder_state.conditional_rendering.is_some() {
+            return Err(Box::new(ValidationError {
+                problem: "conditional rendering is still active".into(),
+                vuids: &["VUID-vkEndCommandBuffer-None-01978"],
+                ..Default::default()
+            }));
+        }
+
         // TODO:
         // VUID-vkEndCommandBuffer-commandBuffer-01815
 
@@ -168,6 +184,7 @@ impl RecordingCommandBuffer {
         let mut auto_sync_state = AutoSyncState::new(
             self.device().clone(),
             self.inner.level(),
+            self.inner.usage(),
             self.inner
                 .inheritance_info()
                 .as_ref()
@@ -192,11 +209,14 @@ impl RecordingCommandBuffer {
 
         let (mut barriers, resources_usage, secondary_resources_usage) = auto_sync_state.build();
         let final_barrier_index = self.commands.len();
+        let mut statistics = self.statistics();
 
         // Record all the commands and barriers to the inner command buffer.
         for (command_index, (_, record_func)) in self.commands.iter().enumerate() {
             if let Some(barriers) = barriers.remove(&command_index) {
                 for dependency_info in barriers {
+                    statistics.add_inserted_barrier(&dependency_info);
+
                     unsafe {
                         #[cfg(debug_assertions)]
                         self.inner
@@ -215,6 +235,8 @@ impl RecordingCommandBuffer {
         // Record final barriers
         if let Some(final_barriers) = barriers.remove(&final_barrier_index) {
             for dependency_info in final_barriers {
+                statistics.add_inserted_barrier(&dependency_info);
+
                 unsafe {
                     #[cfg(debug_assertions)]
                     self.inner
@@ -250,9 +272,25 @@ impl RecordingCommandBuffer {
             secondary_resources_usage,
             state: Mutex::new(Default::default()),
             submit_state,
+            statistics,
         }))
     }
 
+    /// Returns statistics about the commands that have been recorded so far.
+    ///
+    /// The pipeline barriers that will be inserted automatically are not known until recording
+    /// has ended, so they are not included. Use [`CommandBuffer::statistics`] on the finished
+    /// command buffer to get them.
+    pub fn statistics(&self) -> CommandBufferStatistics {
+        let mut statistics = CommandBufferStatistics::default();
+
+        for (command_info, _) in &self.commands {
+            statistics.add_command(command_info.name);
+        }
+
+        statistics
+    }
+
     /// Returns the level of the command buffer.
     #[inline]
     pub fn level(&self) -> CommandBufferLevel {
@@ -320,6 +358,7 @@ unsafe impl DeviceOwned for RecordingCommandBuffer {
 struct AutoSyncState {
     device: Arc<Device>,
     level: CommandBufferLevel,
+    usage: CommandBufferUsage,
 
     command_index: usize,
     barriers: HashMap<usize, Vec<DependencyInfo>>,
@@ -335,11 +374,13 @@ impl AutoSyncState {
     fn new(
         device: Arc<Device>,
         level: CommandBufferLevel,
+        usage: CommandBufferUsage,
         has_inherited_render_pass: bool,
     ) -> Self {
         Self {
             device,
             level,
+            usage,
 
             command_index: 0,
             pending_barrier: DependencyInfo {
@@ -401,12 +442,38 @@ impl AutoSyncState {
                 }
             }
 
+            // A `SimultaneousUse` command buffer can be submitted again while a previous
+            // submission is still pending. Make its writes available to everything that is
+            // submitted after it, so that the submissions don't race on the same queue.
+            if self.usage == CommandBufferUsage::SimultaneousUse
+                && (self
+                    .buffers
+                    .values()
+                    .any(|range_map| range_map.iter().any(|(_, state)| state.is_written))
+                    || self
+                        .images
+                        .values()
+                        .any(|range_map| range_map.iter().any(|(_, state)| state.is_written)))
+            {
+                final_barrier.memory_barriers.push(MemoryBarrier {
+                    src_stages: PipelineStages::ALL_COMMANDS,
+                    src_access: AccessFlags::MEMORY_WRITE,
+                    dst_stages: PipelineStages::ALL_COMMANDS,
+                    dst_access: AccessFlags::MEMORY_READ | AccessFlags::MEMORY_WRITE,
+                    ..Default::default()
+                });
+            }
+
             self.barriers
                 .entry(self.command_index)
                 .or_default()
                 .push(final_barrier);
         }
 
+        for barriers in self.barriers.values_mut() {
+            merge_barriers(barriers);
+        }
+
         let mut resources_usage = CommandBufferResourcesUsage {
             buffers: self
                 .buffers
@@ -519,7 +586,7 @@ impl AutoSyncState {
         } = command_info;
 
         for (use_ref, resource) in used_resources {
-            match *resource {
+            let previous_use_ref = match *resource {
                 Resource::Buffer {
                     ref buffer,
                     ref range,
@@ -528,22 +595,12 @@ impl AutoSyncState {
                     debug_assert!(range.start <= range.end);
                     debug_assert!(range.end <= buffer.size());
 
-                    if let Some(previous_use_ref) = self.find_buffer_conflict(
+                    self.find_buffer_conflict(
                         self.command_index,
                         buffer,
                         range.clone(),
                         memory_access,
-                    ) {
-                        return Err(UnsolvableResourceConflict {
-                            current_use_ref: ResourceUseRef {
-                                command_index: self.command_index,
-                                command_name,
-                                resource_in_command: use_ref.resource_in_command,
-                                secondary_use_ref: use_ref.secondary_use_ref,
-                            },
-                            previous_use_ref,
-                        });
-                    }
+                    )
                 }
                 Resource::Image {
                     ref image,
@@ -562,28 +619,77 @@ impl AutoSyncState {
                     );
                     debug_assert!(subresource_range.array_layers.end <= image.array_layers());
 
-                    debug_assert!(memory_access.contains_write() || start_layout == end_layout);
                     debug_assert!(end_layout != ImageLayout::Undefined);
                     debug_assert!(end_layout != ImageLayout::Preinitialized);
 
-                    if let Some(previous_use) = self.find_image_conflict(
+                    self.find_image_conflict(
                         image,
                         subresource_range.clone(),
                         memory_access,
                         start_layout,
                         end_layout,
-                    ) {
-                        return Err(UnsolvableResourceConflict {
-                            current_use_ref: ResourceUseRef {
-                                command_index: self.command_index,
-                                command_name,
-                                resource_in_command: use_ref.resource_in_command,
-                                secondary_use_ref: use_ref.secondary_use_ref,
-                            },
-                            previous_use_ref: previous_use,
-                        });
+                    )
+                }
+                Resource::BufferOwnershipTransfer {
+                    ref buffer,
+                    ref range,
+                    memory_access,
+                    transfer,
+                } => {
+                    debug_assert!(range.start <= range.end);
+                    debug_assert!(range.end <= buffer.size());
+                    debug_assert_eq!(self.level, CommandBufferLevel::Primary);
+
+                    match transfer {
+                        OwnershipTransfer::Release => self.find_buffer_conflict(
+                            self.command_index,
+                            buffer,
+                            range.clone(),
+                            memory_access,
+                        ),
+                        // The buffer isn't owned by the queue family before the acquire, so it
+                        // can't have been used.
+                        OwnershipTransfer::Acquire => self.find_buffer_use(buffer, range.clone()),
+                    }
+                }
+                Resource::ImageOwnershipTransfer {
+                    ref image,
+                    ref subresource_range,
+                    memory_access,
+                    old_layout,
+                    new_layout: _,
+                    transfer,
+                } => {
+                    debug_assert!(image.format().aspects().contains(subresource_range.aspects));
+                    debug_assert_eq!(self.level, CommandBufferLevel::Primary);
+
+                    match transfer {
+                        OwnershipTransfer::Release => self.find_image_conflict(
+                            image,
+                            subresource_range.clone(),
+                            memory_access,
+                            old_layout,
+                            old_layout,
+                        ),
+                        // The image isn't owned by the queue family before the acquire, so it
+                        // can't have been used.
+                        OwnershipTransfer::Acquire => {
+                            self.find_image_use(image, subresource_range.clone())
+                        }
                     }
                 }
+            };
+
+            if let Some(previous_use_ref) = previous_use_ref {
+                return Err(UnsolvableResourceConflict {
+                    current_use_ref: ResourceUseRef {
+                        command_index: self.command_index,
+                        command_name,
+                        resource_in_command: use_ref.resource_in_command,
+                        secondary_use_ref: use_ref.secondary_use_ref,
+                    },
+                    previous_use_ref,
+                });
             }
         }
 
@@ -615,6 +721,11 @@ impl AutoSyncState {
                 .iter()
                 .all(|resource_use| resource_use.command_index <= command_index));
 
+            // The buffer is no longer owned by the queue family after it's released.
+            if state.is_released {
+                return state.resource_uses.last().copied();
+            }
+
             if memory_access.contains_write() || state.memory_access.contains_write() {
                 // If there is a resource use at a position beyond where we can insert a
                 // barrier, then there is an unsolvable conflict.
@@ -637,7 +748,7 @@ impl AutoSyncState {
         subresource_range: ImageSubresourceRange,
         memory_access: PipelineStageAccessFlags,
         start_layout: ImageLayout,
-        _end_layout: ImageLayout,
+        end_layout: ImageLayout,
     ) -> Option<ResourceUseRef> {
         // Barriers work differently in render passes, so if we're in one, we can only insert a
         // barrier before the start of the render pass.
@@ -656,6 +767,11 @@ impl AutoSyncState {
                     .iter()
                     .all(|resource_use| resource_use.command_index <= self.command_index));
 
+                // The image is no longer owned by the queue family after it's released.
+                if state.is_released {
+                    return state.resource_uses.last().copied();
+                }
+
                 // If the command expects the image to be undefined, then we can't
                 // transition it, so use the current layout for both old and new layout.
                 let start_layout = if start_layout == ImageLayout::Undefined {
@@ -667,6 +783,7 @@ impl AutoSyncState {
                 if memory_access.contains_write()
                     || state.memory_access.contains_write()
                     || state.current_layout != start_layout
+                    || start_layout != end_layout
                 {
                     // If there is a resource use at a position beyond where we can insert a
                     // barrier, then there is an unsolvable conflict.
@@ -682,6 +799,37 @@ impl AutoSyncState {
         None
     }
 
+    /// Returns the first use of `range` of `buffer` in the command buffer, if any.
+    fn find_buffer_use(
+        &self,
+        buffer: &Subbuffer<[u8]>,
+        mut range: Range<DeviceSize>,
+    ) -> Option<ResourceUseRef> {
+        range.start += buffer.offset();
+        range.end += buffer.offset();
+
+        let range_map = self.buffers.get(buffer.buffer())?;
+
+        range_map
+            .range(&range)
+            .find_map(|(_range, state)| state.resource_uses.first().copied())
+    }
+
+    /// Returns the first use of `subresource_range` of `image` in the command buffer, if any.
+    fn find_image_use(
+        &self,
+        image: &Image,
+        subresource_range: ImageSubresourceRange,
+    ) -> Option<ResourceUseRef> {
+        let range_map = self.images.get(image)?;
+
+        image.iter_ranges(subresource_range).find_map(|range| {
+            range_map
+                .range(&range)
+                .find_map(|(_range, state)| state.resource_uses.first().copied())
+        })
+    }
+
     /// Adds a command to be processed by the builder.
     ///
     /// The `resources` argument should contain each buffer or image used by the command.
@@ -701,23 +849,20 @@ impl AutoSyncState {
         } = command_info;
 
         for (use_ref, resource) in used_resources {
+            let use_ref = ResourceUseRef {
+                command_index: self.command_index,
+                command_name,
+                resource_in_command: use_ref.resource_in_command,
+                secondary_use_ref: use_ref.secondary_use_ref,
+            };
+
             match *resource {
                 Resource::Buffer {
                     ref buffer,
                     ref range,
                     memory_access,
                 } => {
-                    self.add_buffer(
-                        ResourceUseRef {
-                            command_index: self.command_index,
-                            command_name,
-                            resource_in_command: use_ref.resource_in_command,
-                            secondary_use_ref: use_ref.secondary_use_ref,
-                        },
-                        buffer.clone(),
-                        range.clone(),
-                        memory_access,
-                    );
+                    self.add_buffer(use_ref, buffer.clone(), range.clone(), memory_access);
                 }
                 Resource::Image {
                     ref image,
@@ -727,12 +872,7 @@ impl AutoSyncState {
                     end_layout,
                 } => {
                     self.add_image(
-                        ResourceUseRef {
-                            command_index: self.command_index,
-                            command_name,
-                            resource_in_command: use_ref.resource_in_command,
-                            secondary_use_ref: use_ref.secondary_use_ref,
-                        },
+                        use_ref,
                         image.clone(),
                         subresource_range.clone(),
                         memory_access,
@@ -740,6 +880,55 @@ impl AutoSyncState {
                         end_layout,
                     );
                 }
+                Resource::BufferOwnershipTransfer {
+                    ref buffer,
+                    ref range,
+                    memory_access,
+                    transfer: OwnershipTransfer::Release,
+                } => {
+                    self.release_buffer(use_ref, buffer.clone(), range.clone(), memory_access);
+                }
+                Resource::BufferOwnershipTransfer {
+                    ref buffer,
+                    ref range,
+                    memory_access,
+                    transfer: OwnershipTransfer::Acquire,
+                } => {
+                    self.acquire_buffer(use_ref, buffer.clone(), range.clone(), memory_access);
+                }
+                Resource::ImageOwnershipTransfer {
+                    ref image,
+                    ref subresource_range,
+                    memory_access,
+                    old_layout,
+                    new_layout,
+                    transfer: OwnershipTransfer::Release,
+                } => {
+                    self.release_image(
+                        use_ref,
+                        image.clone(),
+                        subresource_range.clone(),
+                        memory_access,
+                        old_layout,
+                        new_layout,
+                    );
+                }
+                Resource::ImageOwnershipTransfer {
+                    ref image,
+                    ref subresource_range,
+                    memory_access,
+                    old_layout: _,
+                    new_layout,
+                    transfer: OwnershipTransfer::Acquire,
+                } => {
+                    self.acquire_image(
+                        use_ref,
+                        image.clone(),
+                        subresource_range.clone(),
+                        memory_access,
+                        new_layout,
+                    );
+                }
             }
         }
     }
@@ -768,21 +957,7 @@ impl AutoSyncState {
         range.start += buffer.offset();
         range.end += buffer.offset();
 
-        let range_map = self
-            .buffers
-            .entry(buffer.buffer().clone())
-            .or_insert_with(|| {
-                [(
-                    0..buffer.buffer().size(),
-                    BufferState {
-                        resource_uses: Vec::new(),
-                        memory_access: PipelineStageAccessFlags::empty(),
-                        is_written: false,
-                    },
-                )]
-                .into_iter()
-                .collect()
-            });
+        let range_map = buffer_range_map(&mut self.buffers, buffer.buffer());
         range_map.split_at(&range.start);
         range_map.split_at(&range.end);
 
@@ -892,62 +1067,9 @@ impl AutoSyncState {
         let last_allowed_barrier_index =
             self.latest_render_pass_enter.unwrap_or(self.command_index);
 
-        // VUID-VkImageMemoryBarrier2-image-03320
-        if !self
-            .device
-            .enabled_features()
-            .separate_depth_stencil_layouts
-            && image
-                .format()
-                .aspects()
-                .contains(ImageAspects::DEPTH | ImageAspects::STENCIL)
-        {
-            subresource_range.aspects = ImageAspects::DEPTH | ImageAspects::STENCIL;
-        }
-
-        let range_map = self.images.entry(image.clone()).or_insert_with(|| {
-            [(
-                0..image.range_size(),
-                match self.level {
-                    CommandBufferLevel::Primary => {
-                        // In a primary command buffer, the initial layout is determined
-                        // by the image.
-                        let initial_layout = if !image.is_layout_initialized() {
-                            unsafe {
-                                image.layout_initialized();
-                            }
+        self.adjust_aspects(&image, &mut subresource_range);
 
-                            image.initial_layout()
-                        } else {
-                            image.initial_layout_requirement()
-                        };
-
-                        ImageState {
-                            resource_uses: Vec::new(),
-                            memory_access: PipelineStageAccessFlags::empty(),
-                            is_written: false,
-                            initial_layout,
-                            current_layout: initial_layout,
-                            final_layout: image.final_layout_requirement(),
-                        }
-                    }
-                    CommandBufferLevel::Secondary => {
-                        // In a secondary command buffer, the initial layout is the layout
-                        // of the first use.
-                        ImageState {
-                            resource_uses: Vec::new(),
-                            memory_access: PipelineStageAccessFlags::empty(),
-                            is_written: false,
-                            initial_layout: ImageLayout::Undefined,
-                            current_layout: ImageLayout::Undefined,
-                            final_layout: ImageLayout::Undefined,
-                        }
-                    }
-                },
-            )]
-            .into_iter()
-            .collect()
-        });
+        let range_map = image_range_map(&mut self.images, self.level, &image);
 
         for range in image.iter_ranges(subresource_range) {
             range_map.split_at(&range.start);
@@ -961,7 +1083,7 @@ impl AutoSyncState {
 
                     state.resource_uses.push(use_ref);
                     state.memory_access = memory_access;
-                    state.is_written = memory_access.contains_write();
+                    state.is_written = memory_access.contains_write() || start_layout != end_layout;
                     state.current_layout = end_layout;
 
                     match self.level {
@@ -1055,10 +1177,13 @@ impl AutoSyncState {
                         start_layout
                     };
 
-                    // Find out if we have a collision with the pending commands.
+                    // Find out if we have a collision with the pending commands. A command that
+                    // transitions the layout itself, such as a pipeline barrier, needs exclusive
+                    // access like a write.
                     if memory_access.contains_write()
                         || state.memory_access.contains_write()
                         || state.current_layout != start_layout
+                        || start_layout != end_layout
                     {
                         // Collision found between `latest_command_id` and `collision_cmd_id`.
 
@@ -1115,6 +1240,485 @@ impl AutoSyncState {
             }
         }
     }
+
+    /// Records the release half of a queue family ownership transfer of a buffer range.
+    ///
+    /// The release barrier is synchronized with earlier uses like any other access, but the
+    /// range can't be used afterwards, so no barriers are ever inserted after it.
+    fn release_buffer(
+        &mut self,
+        use_ref: ResourceUseRef,
+        buffer: Subbuffer<[u8]>,
+        mut range: Range<DeviceSize>,
+        memory_access: PipelineStageAccessFlags,
+    ) {
+        self.add_buffer(use_ref, buffer.clone(), range.clone(), memory_access);
+
+        range.start += buffer.offset();
+        range.end += buffer.offset();
+
+        let range_map = buffer_range_map(&mut self.buffers, buffer.buffer());
+
+        for (_range, state) in range_map.range_mut(&range) {
+            state.is_released = true;
+        }
+    }
+
+    /// Records the acquire half of a queue family ownership transfer of a buffer range.
+    ///
+    /// The acquire barrier must be the first use of the range, and it already makes the
+    /// transferred contents available, so no barrier is inserted before it.
+    fn acquire_buffer(
+        &mut self,
+        use_ref: ResourceUseRef,
+        buffer: Subbuffer<[u8]>,
+        mut range: Range<DeviceSize>,
+        memory_access: PipelineStageAccessFlags,
+    ) {
+        self.secondary_resources_usage
+            .buffers
+            .push(SecondaryCommandBufferBufferUsage {
+                use_ref,
+                buffer: buffer.clone(),
+                range: range.clone(),
+                memory_access,
+            });
+
+        range.start += buffer.offset();
+        range.end += buffer.offset();
+
+        let range_map = buffer_range_map(&mut self.buffers, buffer.buffer());
+        range_map.split_at(&range.start);
+        range_map.split_at(&range.end);
+
+        for (_range, state) in range_map.range_mut(&range) {
+            debug_assert!(state.resource_uses.is_empty());
+
+            state.resource_uses.push(use_ref);
+            state.memory_access = memory_access;
+            state.is_written = true;
+        }
+    }
+
+    /// Records the release half of a queue family ownership transfer of an image range.
+    ///
+    /// The image range is brought into `old_layout` before the release barrier like for any
+    /// other access. The release barrier itself transitions it to `new_layout`, which is the
+    /// layout that the range has at the end of the command buffer, and no barriers are ever
+    /// inserted after it.
+    fn release_image(
+        &mut self,
+        use_ref: ResourceUseRef,
+        image: Arc<Image>,
+        mut subresource_range: ImageSubresourceRange,
+        memory_access: PipelineStageAccessFlags,
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+    ) {
+        self.add_image(
+            use_ref,
+            image.clone(),
+            subresource_range.clone(),
+            memory_access,
+            old_layout,
+            old_layout,
+        );
+
+        self.adjust_aspects(&image, &mut subresource_range);
+
+        let range_map = image_range_map(&mut self.images, self.level, &image);
+
+        for range in image.iter_ranges(subresource_range) {
+            for (_range, state) in range_map.range_mut(&range) {
+                state.current_layout = new_layout;
+                state.final_layout = new_layout;
+                state.is_written = true;
+                state.is_released = true;
+            }
+        }
+    }
+
+    /// Records the acquire half of a queue family ownership transfer of an image range.
+    ///
+    /// The acquire barrier must be the first use of the range. The image range is expected to
+    /// be in `new_layout` when the command buffer is executed, as the matching release barrier
+    /// already transitioned it, so no barrier is inserted before the acquire.
+    fn acquire_image(
+        &mut self,
+        use_ref: ResourceUseRef,
+        image: Arc<Image>,
+        mut subresource_range: ImageSubresourceRange,
+        memory_access: PipelineStageAccessFlags,
+        new_layout: ImageLayout,
+    ) {
+        self.secondary_resources_usage
+            .images
+            .push(SecondaryCommandBufferImageUsage {
+                use_ref,
+                image: image.clone(),
+                subresource_range: subresource_range.clone(),
+                memory_access,
+                start_layout: new_layout,
+                end_layout: new_layout,
+            });
+
+        self.adjust_aspects(&image, &mut subresource_range);
+
+        let range_map = image_range_map(&mut self.images, self.level, &image);
+
+        for range in image.iter_ranges(subresource_range) {
+            range_map.split_at(&range.start);
+            range_map.split_at(&range.end);
+
+            for (_range, state) in range_map.range_mut(&range) {
+                debug_assert!(state.resource_uses.is_empty());
+
+                state.resource_uses.push(use_ref);
+                state.memory_access = memory_access;
+                state.is_written = true;
+                state.initial_layout = new_layout;
+                state.current_layout = new_layout;
+            }
+        }
+    }
+
+    fn adjust_aspects(&self, image: &Image, subresource_range: &mut ImageSubresourceRange) {
+        // VUID-VkImageMemoryBarrier2-image-03320
+        if !self
+            .device
+            .enabled_features()
+            .separate_depth_stencil_layouts
+            && image
+                .format()
+                .aspects()
+                .contains(ImageAspects::DEPTH | ImageAspects::STENCIL)
+        {
+            subresource_range.aspects = ImageAspects::DEPTH | ImageAspects::STENCIL;
+        }
+    }
+}
+
+/// Merges the barriers that are recorded before the same command into as few pipeline barrier
+/// commands as possible, and reduces the number of individual barriers within them.
+///
+/// Consecutive barriers are merged into one if they don't touch the same resource or resources
+/// that may alias each other in memory, as the order between them then doesn't matter. Within a
+/// barrier, buffer barriers and image barriers with identical parameters are coalesced when they
+/// cover adjacent ranges of the same resource. Every barrier keeps covering exactly the resources
+/// that it covered before, and stage and access masks are never widened.
+pub(super) fn merge_barriers(barriers: &mut Vec<DependencyInfo>) {
+    let mut merged: Vec<DependencyInfo> = Vec::with_capacity(barriers.len());
+
+    for dependency_info in barriers.drain(..) {
+        if dependency_info.is_empty() {
+            continue;
+        }
+
+        match merged.last_mut() {
+            Some(last) if can_merge_dependency_infos(last, &dependency_info) => {
+                let DependencyInfo {
+                    dependency_flags: _,
+                    memory_barriers,
+                    buffer_memory_barriers,
+                    image_memory_barriers,
+                    _ne: _,
+                } = dependency_info;
+
+                last.memory_barriers.extend(memory_barriers);
+                last.buffer_memory_barriers.extend(buffer_memory_barriers);
+                last.image_memory_barriers.extend(image_memory_barriers);
+            }
+            _ => merged.push(dependency_info),
+        }
+    }
+
+    for dependency_info in &mut merged {
+        coalesce_buffer_barriers(dependency_info);
+        coalesce_image_barriers(dependency_info);
+    }
+
+    *barriers = merged;
+}
+
+fn can_merge_dependency_infos(first: &DependencyInfo, second: &DependencyInfo) -> bool {
+    // Global barriers affect every resource, so they must stay ordered relative to the others.
+    if first.dependency_flags != second.dependency_flags
+        || !first.memory_barriers.is_empty()
+        || !second.memory_barriers.is_empty()
+    {
+        return false;
+    }
+
+    let buffers_overlap = second.buffer_memory_barriers.iter().any(|second| {
+        first.buffer_memory_barriers.iter().any(|first| {
+            first.buffer == second.buffer
+                && first.range.start < second.range.end
+                && second.range.start < first.range.end
+        })
+    });
+
+    let images_overlap = second.image_memory_barriers.iter().any(|second| {
+        first.image_memory_barriers.iter().any(|first| {
+            let first_range = &first.subresource_range;
+            let second_range = &second.subresource_range;
+
+            first.image == second.image
+                && first_range.aspects.intersects(second_range.aspects)
+                && first_range.mip_levels.start < second_range.mip_levels.end
+                && second_range.mip_levels.start < first_range.mip_levels.end
+                && first_range.array_layers.start < second_range.array_layers.end
+                && second_range.array_layers.start < first_range.array_layers.end
+        })
+    });
+
+    if buffers_overlap || images_overlap {
+        return false;
+    }
+
+    // Different resources can still be bound to the same memory, in which case reordering their
+    // barriers could reorder layout transitions or accesses of that memory.
+    let first_memory = barriers_bound_memory(first);
+    let second_memory = barriers_bound_memory(second);
+
+    !second_memory.iter().any(|(second_resource, second)| {
+        first_memory.iter().any(|(first_resource, first)| {
+            first_resource != second_resource && first.may_alias(second)
+        })
+    })
+}
+
+// Returns the memory that each resource of the barriers is bound to, along with the address of the
+// resource to tell the resources apart.
+fn barriers_bound_memory(
+    dependency_info: &DependencyInfo,
+) -> SmallVec<[(*const (), BoundMemory<'_>); 8]> {
+    let buffers = dependency_info
+        .buffer_memory_barriers
+        .iter()
+        .map(|barrier| {
+            let buffer = &barrier.buffer;
+
+            (Arc::as_ptr(buffer).cast(), BoundMemory::buffer(buffer))
+        });
+    let images = dependency_info.image_memory_barriers.iter().map(|barrier| {
+        let image = &barrier.image;
+
+        (Arc::as_ptr(image).cast(), BoundMemory::image(image))
+    });
+
+    buffers.chain(images).collect()
+}
+
+// The memory that a buffer or image is bound to.
+enum BoundMemory<'a> {
+    DeviceMemory(SmallVec<[(&'a Arc<DeviceMemory>, Range<DeviceSize>); 4]>),
+    Swapchain(&'a Arc<Swapchain>, u32),
+    // Sparse and external memory can't be inspected, so it may alias anything.
+    Unknown,
+}
+
+impl<'a> BoundMemory<'a> {
+    fn buffer(buffer: &'a Buffer) -> Self {
+        match buffer.memory() {
+            BufferMemory::Normal(memory) => {
+                BoundMemory::DeviceMemory([Self::resource_memory(memory)].into_iter().collect())
+            }
+            BufferMemory::Sparse | BufferMemory::External => BoundMemory::Unknown,
+        }
+    }
+
+    fn image(image: &'a Image) -> Self {
+        match image.memory() {
+            ImageMemory::Normal(memory) => {
+                BoundMemory::DeviceMemory(memory.iter().map(Self::resource_memory).collect())
+            }
+            &ImageMemory::Swapchain {
+                ref swapchain,
+                image_index,
+            } => BoundMemory::Swapchain(swapchain, image_index),
+            ImageMemory::Sparse(_) | ImageMemory::External => BoundMemory::Unknown,
+        }
+    }
+
+    fn resource_memory(memory: &'a ResourceMemory) -> (&'a Arc<DeviceMemory>, Range<DeviceSize>) {
+        (
+            memory.device_memory(),
+            memory.offset()..memory.offset() + memory.size(),
+        )
+    }
+
+    fn may_alias(&self, other: &Self) -> bool {
+        match (self, other) {
+            (BoundMemory::DeviceMemory(ranges), BoundMemory::DeviceMemory(other_ranges)) => {
+                ranges.iter().any(|(memory, range)| {
+                    other_ranges.iter().any(|(other_memory, other_range)| {
+                        Arc::ptr_eq(memory, other_memory)
+                            && range.start < other_range.end
+                            && other_range.start < range.end
+                    })
+                })
+            }
+            (
+                &BoundMemory::Swapchain(swapchain, image_index),
+                &BoundMemory::Swapchain(other_swapchain, other_image_index),
+            ) => Arc::ptr_eq(swapchain, other_swapchain) && image_index == other_image_index,
+            (BoundMemory::DeviceMemory(_), BoundMemory::Swapchain(..))
+            | (BoundMemory::Swapchain(..), BoundMemory::DeviceMemory(_)) => false,
+            (BoundMemory::Unknown, _) | (_, BoundMemory::Unknown) => true,
+        }
+    }
+}
+
+fn coalesce_buffer_barriers(dependency_info: &mut DependencyInfo) {
+    let mut buffer_memory_barriers: SmallVec<[BufferMemoryBarrier; 8]> = SmallVec::new();
+
+    // Join barriers of adjacent ranges of the same buffer.
+    for barrier in dependency_info.buffer_memory_barriers.drain(..) {
+        let adjacent = buffer_memory_barriers.iter_mut().find(|other| {
+            other.buffer == barrier.buffer
+                && other.queue_family_ownership_transfer.is_none()
+                && barrier.queue_family_ownership_transfer.is_none()
+                && other.src_stages == barrier.src_stages
+                && other.src_access == barrier.src_access
+                && other.dst_stages == barrier.dst_stages
+                && other.dst_access == barrier.dst_access
+                && (other.range.end == barrier.range.start
+                    || barrier.range.end == other.range.start)
+        });
+
+        match adjacent {
+            Some(other) => {
+                other.range = other.range.start.min(barrier.range.start)
+                    ..other.range.end.max(barrier.range.end);
+            }
+            None => buffer_memory_barriers.push(barrier),
+        }
+    }
+
+    dependency_info.buffer_memory_barriers = buffer_memory_barriers;
+}
+
+fn coalesce_image_barriers(dependency_info: &mut DependencyInfo) {
+    let mut image_memory_barriers: SmallVec<[ImageMemoryBarrier; 8]> = SmallVec::new();
+
+    // Join barriers of adjacent mip levels or array layers of the same image.
+    for barrier in dependency_info.image_memory_barriers.drain(..) {
+        let adjacent = image_memory_barriers.iter_mut().find(|other| {
+            let other_range = &other.subresource_range;
+            let range = &barrier.subresource_range;
+
+            other.image == barrier.image
+                && other.queue_family_ownership_transfer.is_none()
+                && barrier.queue_family_ownership_transfer.is_none()
+                && other.src_stages == barrier.src_stages
+                && other.src_access == barrier.src_access
+                && other.dst_stages == barrier.dst_stages
+                && other.dst_access == barrier.dst_access
+                && other.old_layout == barrier.old_layout
+                && other.new_layout == barrier.new_layout
+                && other_range.aspects == range.aspects
+                && ((other_range.array_layers == range.array_layers
+                    && (other_range.mip_levels.end == range.mip_levels.start
+                        || range.mip_levels.end == other_range.mip_levels.start))
+                    || (other_range.mip_levels == range.mip_levels
+                        && (other_range.array_layers.end == range.array_layers.start
+                            || range.array_layers.end == other_range.array_layers.start)))
+        });
+
+        match adjacent {
+            Some(other) => {
+                let other_range = &mut other.subresource_range;
+                let range = barrier.subresource_range;
+
+                other_range.mip_levels = other_range.mip_levels.start.min(range.mip_levels.start)
+                    ..other_range.mip_levels.end.max(range.mip_levels.end);
+                other_range.array_layers =
+                    other_range.array_layers.start.min(range.array_layers.start)
+                        ..other_range.array_layers.end.max(range.array_layers.end);
+            }
+            None => image_memory_barriers.push(barrier),
+        }
+    }
+
+    dependency_info.image_memory_barriers = image_memory_barriers;
+}
+
+fn buffer_range_map<'a>(
+    buffers: &'a mut HashMap<Arc<Buffer>, RangeMap<DeviceSize, BufferState>>,
+    buffer: &Arc<Buffer>,
+) -> &'a mut RangeMap<DeviceSize, BufferState> {
+    buffers.entry(buffer.clone()).or_insert_with(|| {
+        [(
+            0..buffer.size(),
+            BufferState {
+                resource_uses: Vec::new(),
+                memory_access: PipelineStageAccessFlags::empty(),
+                is_written: false,
+                is_released: false,
+            },
+        )]
+        .into_iter()
+        .collect()
+    })
+}
+
+fn image_range_map<'a>(
+    images: &'a mut HashMap<Arc<Image>, RangeMap<DeviceSize, ImageState>>,
+    level: CommandBufferLevel,
+    image: &Arc<Image>,
+) -> &'a mut RangeMap<DeviceSize, ImageState> {
+    images.entry(image.clone()).or_insert_with(|| {
+        let new_state = |initial_layout, final_layout| ImageState {
+            resource_uses: Vec::new(),
+            memory_access: PipelineStageAccessFlags::empty(),
+            is_written: false,
+            is_released: false,
+            initial_layout,
+            current_layout: initial_layout,
+            final_layout,
+        };
+
+        match level {
+            CommandBufferLevel::Primary => {
+                // In a primary command buffer, the initial layout is determined
+                // by the image.
+                if !image.is_layout_initialized() {
+                    unsafe {
+                        image.layout_initialized();
+                    }
+
+                    [(
+                        0..image.range_size(),
+                        new_state(image.initial_layout(), image.final_layout_requirement()),
+                    )]
+                    .into_iter()
+                    .collect()
+                } else {
+                    // The image is normally in its initial layout requirement here, unless
+                    // a transition was declared with `Image::set_current_layout`. If the
+                    // layout changes again before this command buffer is submitted, this is
+                    // caught when the command buffer is submitted.
+                    image
+                        .state()
+                        .layouts(0..image.range_size())
+                        .map(|(range, layout)| {
+                            (range, new_state(layout, image.final_layout_requirement()))
+                        })
+                        .collect()
+                }
+            }
+            CommandBufferLevel::Secondary => {
+                // In a secondary command buffer, the initial layout is the layout
+                // of the first use.
+                [(
+                    0..image.range_size(),
+                    new_state(ImageLayout::Undefined, ImageLayout::Undefined),
+                )]
+                .into_iter()
+                .collect()
+            }
+        }
+    })
 }
 
 /// Error returned if the builder detects that there's an unsolvable conflict.
@@ -1136,6 +1740,10 @@ struct BufferState {
     // True if the resource was written to at any point during the command buffer.
     // Also true if an image layout transition or queue transfer has been performed.
     is_written: bool,
+
+    // True if ownership of the resource was released to another queue family. The resource
+    // can't be used again in this command buffer.
+    is_released: bool,
 }
 
 // State of a resource during the building of the command buffer.
@@ -1151,6 +1759,10 @@ struct ImageState {
     // Also true if an image layout transition or queue transfer has been performed.
     is_written: bool,
 
+    // True if ownership of the resource was released to another queue family. The resource
+    // can't be used again in this command buffer.
+    is_released: bool,
+
     // The layout that the image range must have when this command buffer is executed.
     // Can be `Undefined` if we don't care.
     initial_layout: ImageLayout,
@@ -1190,6 +1802,8 @@ pub(in crate::command_buffer) struct CommandBufferBuilderState {
     pub(in crate::command_buffer) depth_test_enable: Option<bool>,
     pub(in crate::command_buffer) depth_write_enable: Option<bool>,
     pub(in crate::command_buffer) discard_rectangle: HashMap<u32, Scissor>,
+    pub(in crate::command_buffer) discard_rectangle_enable: Option<bool>,
+    pub(in crate::command_buffer) discard_rectangle_mode: Option<DiscardRectangleMode>,
     pub(in crate::command_buffer) front_face: Option<FrontFace>,
     pub(in crate::command_buffer) line_stipple: Option<LineStipple>,
     pub(in crate::command_buffer) line_width: Option<f32>,
@@ -1211,15 +1825,24 @@ pub(in crate::command_buffer) struct CommandBufferBuilderState {
     pub(in crate::command_buffer) conservative_rasterization_mode:
         Option<ConservativeRasterizationMode>,
     pub(in crate::command_buffer) extra_primitive_overestimation_size: Option<f32>,
+    pub(in crate::command_buffer) line_rasterization_mode: Option<LineRasterizationMode>,
+    pub(in crate::command_buffer) line_stipple_enable: Option<bool>,
 
     // Active queries
     pub(in crate::command_buffer) queries: HashMap<QueryType, QueryState>,
+
+    // Active conditional rendering
+    pub(in crate::command_buffer) conditional_rendering: Option<ConditionalRenderingState>,
+
+    // Events that were set in this command buffer, with the dependency info they were set with
+    pub(in crate::command_buffer) events: HashMap<ash::vk::Event, DependencyInfo>,
 }
 
 impl CommandBufferBuilderState {
     pub(in crate::command_buffer) fn reset_non_render_pass_states(&mut self) {
         *self = Self {
             render_pass: take(&mut self.render_pass),
+            conditional_rendering: take(&mut self.conditional_rendering),
             ..Default::default()
         }
     }
@@ -1241,6 +1864,8 @@ impl CommandBufferBuilderState {
                 DynamicState::DepthTestEnable => self.depth_test_enable = None,
                 DynamicState::DepthWriteEnable => self.depth_write_enable = None,
                 DynamicState::DiscardRectangle => self.discard_rectangle.clear(),
+                DynamicState::DiscardRectangleEnable => self.discard_rectangle_enable = None,
+                DynamicState::DiscardRectangleMode => self.discard_rectangle_mode = None,
                 // DynamicState::ExclusiveScissor => todo!(),
                 // DynamicState::FragmentShadingRate => todo!(),
                 DynamicState::FrontFace => self.front_face = None,
@@ -1284,23 +1909,24 @@ impl CommandBufferBuilderState {
                 }
                 DynamicState::ExtraPrimitiveOverestimationSize => {
                     self.extra_primitive_overestimation_size = None
-                } /* DynamicState::DepthClipEnable => todo!(),
-                   * DynamicState::SampleLocationsEnable => todo!(),
-                   * DynamicState::ColorBlendAdvanced => todo!(),
-                   * DynamicState::ProvokingVertexMode => todo!(),
-                   * DynamicState::LineRasterizationMode => todo!(),
-                   * DynamicState::LineStippleEnable => todo!(),
-                   * DynamicState::DepthClipNegativeOneToOne => todo!(),
-                   * DynamicState::ViewportWScalingEnable => todo!(),
-                   * DynamicState::ViewportSwizzle => todo!(),
-                   * DynamicState::CoverageToColorEnable => todo!(),
-                   * DynamicState::CoverageToColorLocation => todo!(),
-                   * DynamicState::CoverageModulationMode => todo!(),
-                   * DynamicState::CoverageModulationTableEnable => todo!(),
-                   * DynamicState::CoverageModulationTable => todo!(),
-                   * DynamicState::ShadingRateImageEnable => todo!(),
-                   * DynamicState::RepresentativeFragmentTestEnable => todo!(),
-                   * DynamicState::CoverageReductionMode => todo!(), */
+                }
+                DynamicState::LineRasterizationMode => self.line_rasterization_mode = None,
+                DynamicState::LineStippleEnable => self.line_stipple_enable = None,
+                /* DynamicState::DepthClipEnable => todo!(),
+                 * DynamicState::SampleLocationsEnable => todo!(),
+                 * DynamicState::ColorBlendAdvanced => todo!(),
+                 * DynamicState::ProvokingVertexMode => todo!(),
+                 * DynamicState::DepthClipNegativeOneToOne => todo!(),
+                 * DynamicState::ViewportWScalingEnable => todo!(),
+                 * DynamicState::ViewportSwizzle => todo!(),
+                 * DynamicState::CoverageToColorEnable => todo!(),
+                 * DynamicState::CoverageToColorLocation => todo!(),
+                 * DynamicState::CoverageModulationMode => todo!(),
+                 * DynamicState::CoverageModulationTableEnable => todo!(),
+                 * DynamicState::CoverageModulationTable => todo!(),
+                 * DynamicState::ShadingRateImageEnable => todo!(),
+                 * DynamicState::RepresentativeFragmentTestEnable => todo!(),
+                 * DynamicState::CoverageReductionMode => todo!(), */
             }
         }
     }
@@ -1649,6 +2275,12 @@ pub(in crate::command_buffer) struct StencilOpStateDynamic {
     pub(in crate::command_buffer) back: Option<StencilOps>,
 }
 
+pub(in crate::command_buffer) struct ConditionalRenderingState {
+    // The index of the subpass that conditional rendering was made active in, or `None` if it was
+    // made active outside a render pass instance.
+    pub(in crate::command_buffer) subpass_index: Option<u32>,
+}
+
 pub(in crate::command_buffer) struct QueryState {
     pub(in crate::command_buffer) query_pool: Arc<QueryPool>,
     pub(in crate::command_buffer) query: u32,
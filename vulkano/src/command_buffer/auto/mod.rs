@@ -56,11 +56,16 @@
 //! command. If so, it will try to merge this barrier with the prototype and add the command to the
 //! queue. If not possible, the queue will be entirely flushed and the command added to a fresh new
 //! queue with a fresh new barrier prototype.
+//!
+//! When the command buffer is built, the barriers that end up before the same command are merged
+//! into a single pipeline barrier where possible, which isn't done for barriers of resources that
+//! overlap or may alias in memory. Barriers of adjacent ranges of the same resource are joined.
+//! The resources, stages and accesses of a barrier are never widened in the process.
 
 pub use self::builder::*;
 pub(in crate::command_buffer) use self::builder::{
-    BeginRenderPassState, BeginRenderingState, QueryState, RenderPassState,
-    RenderPassStateAttachments, RenderPassStateType, SetOrPush,
+    BeginRenderPassState, BeginRenderingState, ConditionalRenderingState, QueryState,
+    RenderPassState, RenderPassStateAttachments, RenderPassStateType, SetOrPush,
 };
 use super::{
     sys::{RawCommandBuffer, RawRecordingCommandBuffer},
@@ -72,7 +77,7 @@ use crate::{
     buffer::Subbuffer,
     device::{Device, DeviceOwned},
     image::{Image, ImageLayout, ImageSubresourceRange},
-    sync::PipelineStageAccessFlags,
+    sync::{DependencyInfo, PipelineStageAccessFlags},
     DeviceSize, ValidationError, VulkanObject,
 };
 use parking_lot::{Mutex, MutexGuard};
@@ -95,6 +100,7 @@ pub struct CommandBuffer {
     secondary_resources_usage: SecondaryCommandBufferResourcesUsage,
     state: Mutex<CommandBufferState>,
     submit_state: SubmitState,
+    statistics: CommandBufferStatistics,
 }
 
 unsafe impl VulkanObject for CommandBuffer {
@@ -146,12 +152,33 @@ impl CommandBuffer {
         self.inner.usage()
     }
 
+    /// Returns whether this command buffer is protected.
+    #[inline]
+    pub fn is_protected(&self) -> bool {
+        self.inner.is_protected()
+    }
+
     /// Returns the inheritance info of the command buffer, if it is a secondary command buffer.
     #[inline]
     pub fn inheritance_info(&self) -> Option<&CommandBufferInheritanceInfo> {
         self.inner.inheritance_info()
     }
 
+    /// Returns the nesting level of the command buffer.
+    ///
+    /// See [`RawCommandBuffer::nesting_level`].
+    #[inline]
+    pub fn nesting_level(&self) -> u32 {
+        self.inner.nesting_level()
+    }
+
+    /// Returns statistics about the commands that were recorded into the command buffer,
+    /// including the pipeline barriers that were inserted automatically.
+    #[inline]
+    pub fn statistics(&self) -> CommandBufferStatistics {
+        self.statistics
+    }
+
     pub(crate) fn state(&self) -> MutexGuard<'_, CommandBufferState> {
         debug_assert_eq!(self.level(), CommandBufferLevel::Primary);
 
@@ -233,6 +260,76 @@ impl CommandBuffer {
     }
 }
 
+/// Statistics about the commands that were recorded into a command buffer.
+///
+/// These can be used to find out where the cost of recording and executing a command buffer
+/// comes from, for example to show them in a profiling overlay.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CommandBufferStatistics {
+    /// The number of commands that were recorded, not including the pipeline barriers that were
+    /// inserted automatically.
+    pub commands: u32,
+
+    /// The number of draw commands, including indirect and mesh shading draws.
+    pub draws: u32,
+
+    /// The number of dispatch commands, including indirect dispatches.
+    pub dispatches: u32,
+
+    /// The number of commands that bind a pipeline.
+    pub pipeline_binds: u32,
+
+    /// The number of commands that bind or push descriptor sets.
+    pub descriptor_set_binds: u32,
+
+    /// The number of render pass instances that were begun, with either a render pass object or
+    /// dynamic rendering.
+    pub render_passes: u32,
+
+    /// The number of pipeline barrier commands that were inserted automatically to synchronize
+    /// the recorded commands. This is only known once recording has ended.
+    pub inserted_pipeline_barriers: u32,
+
+    /// The total number of memory, buffer memory and image memory barriers in the pipeline
+    /// barrier commands that were inserted automatically. This is only known once recording has
+    /// ended.
+    pub inserted_barriers: u32,
+}
+
+impl CommandBufferStatistics {
+    pub(in crate::command_buffer) fn add_command(&mut self, name: &str) {
+        self.commands += 1;
+
+        match name {
+            "draw"
+            | "draw_indexed"
+            | "draw_indexed_indirect"
+            | "draw_indexed_indirect_count"
+            | "draw_indirect"
+            | "draw_indirect_count"
+            | "draw_mesh_tasks"
+            | "draw_mesh_tasks_indirect"
+            | "draw_mesh_tasks_indirect_count" => self.draws += 1,
+            "dispatch" => self.dispatches += 1,
+            "bind_pipeline_compute" | "bind_pipeline_graphics" => self.pipeline_binds += 1,
+            "bind_descriptor_sets" | "push_descriptor_set" => self.descriptor_set_binds += 1,
+            "begin_render_pass" | "begin_rendering" => self.render_passes += 1,
+            _ => (),
+        }
+    }
+
+    pub(in crate::command_buffer) fn add_inserted_barrier(
+        &mut self,
+        dependency_info: &DependencyInfo,
+    ) {
+        self.inserted_pipeline_barriers += 1;
+        self.inserted_barriers += (dependency_info.memory_barriers.len()
+            + dependency_info.buffer_memory_barriers.len()
+            + dependency_info.image_memory_barriers.len()) as u32;
+    }
+}
+
 // Whether the command buffer can be submitted.
 #[derive(Debug)]
 enum SubmitState {
@@ -285,6 +382,32 @@ pub(super) enum Resource {
         start_layout: ImageLayout,
         end_layout: ImageLayout,
     },
+    /// The release or acquire half of a queue family ownership transfer of a buffer range.
+    BufferOwnershipTransfer {
+        buffer: Subbuffer<[u8]>,
+        range: Range<DeviceSize>,
+        memory_access: PipelineStageAccessFlags,
+        transfer: OwnershipTransfer,
+    },
+    /// The release or acquire half of a queue family ownership transfer of an image range, which
+    /// transitions the image from `old_layout` to `new_layout`.
+    ImageOwnershipTransfer {
+        image: Arc<Image>,
+        subresource_range: ImageSubresourceRange,
+        memory_access: PipelineStageAccessFlags,
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+        transfer: OwnershipTransfer,
+    },
+}
+
+/// Which half of a queue family ownership transfer a command performs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum OwnershipTransfer {
+    /// The resource is released to another queue family. It can't be used afterwards.
+    Release,
+    /// The resource is acquired from another queue family. It can't have been used before.
+    Acquire,
 }
 
 struct CommandInfo {
@@ -309,11 +432,12 @@ impl Debug for CommandInfo {
 #[cfg(test)]
 mod tests {
     use crate::{
-        buffer::{Buffer, BufferCreateInfo, BufferUsage},
+        buffer::{sys::RawBuffer, Buffer, BufferCreateInfo, BufferUsage},
         command_buffer::{
             allocator::{StandardCommandBufferAllocator, StandardCommandBufferAllocatorCreateInfo},
-            BufferCopy, CommandBufferBeginInfo, CommandBufferLevel, CommandBufferUsage,
-            CopyBufferInfoTyped, RecordingCommandBuffer,
+            BufferCopy, ClearColorImageInfo, CommandBufferBeginInfo, CommandBufferLevel,
+            CommandBufferUsage, ConditionalRenderingBeginInfo, CopyBufferInfoTyped,
+            RecordingCommandBuffer, RenderPassBeginInfo,
         },
         descriptor_set::{
             allocator::StandardDescriptorSetAllocator,
@@ -323,12 +447,32 @@ mod tests {
             },
             DescriptorSet, WriteDescriptorSet,
         },
-        device::{Device, DeviceCreateInfo, QueueCreateInfo},
-        image::sampler::{Sampler, SamplerCreateInfo},
-        memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator},
-        pipeline::{layout::PipelineLayoutCreateInfo, PipelineBindPoint, PipelineLayout},
+        device::{Device, DeviceCreateInfo, DeviceOwned, QueueCreateInfo},
+        format::Format,
+        image::{
+            sampler::{Sampler, SamplerCreateInfo},
+            Image, ImageCreateInfo, ImageLayout, ImageUsage,
+        },
+        memory::{
+            allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator},
+            DeviceMemory, MemoryAllocateInfo, ResourceMemory,
+        },
+        pipeline::{
+            graphics::vertex_input::{
+                VertexInputAttributeDescription, VertexInputBindingDescription, VertexInputState,
+            },
+            layout::{PipelineLayoutCreateInfo, PushConstantRange},
+            PipelineBindPoint, PipelineLayout,
+        },
+        render_pass::{
+            Framebuffer, FramebufferCreateInfo, RenderPass, RenderPassCreateInfo,
+            SubpassDescription,
+        },
         shader::ShaderStages,
-        sync::GpuFuture,
+        sync::{
+            event::Event, AccessFlags, BufferMemoryBarrier, DependencyInfo, GpuFuture,
+            ImageMemoryBarrier, MemoryBarrier, PipelineStages, QueueFamilyOwnershipTransfer,
+        },
     };
     use std::sync::Arc;
 
@@ -763,6 +907,163 @@ mod tests {
         }
     }
 
+    #[test]
+    fn simultaneous_use_resubmit() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device));
+        let buffer = Buffer::from_data(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                    | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+                ..Default::default()
+            },
+            0u32,
+        )
+        .unwrap();
+
+        let mut cbb = RecordingCommandBuffer::new(
+            cb_allocator,
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::SimultaneousUse,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        cbb.fill_buffer(buffer.clone().into_slice(), 42).unwrap();
+        let cb = cbb.end().unwrap();
+
+        // The second submission is made while the first one is still pending, and writes to the
+        // same buffer.
+        let first = cb
+            .clone()
+            .execute(queue.clone())
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap();
+        let second = cb
+            .execute(queue)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap();
+
+        first.wait(None).unwrap();
+        second.wait(None).unwrap();
+        drop((first, second));
+
+        assert_eq!(*buffer.read().unwrap(), 42);
+    }
+
+    #[test]
+    fn nested_command_buffer() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let new_secondary = || {
+            RecordingCommandBuffer::new(
+                cb_allocator.clone(),
+                queue.queue_family_index(),
+                CommandBufferLevel::Secondary,
+                CommandBufferBeginInfo {
+                    usage: CommandBufferUsage::MultipleSubmit,
+                    inheritance_info: Some(Default::default()),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let inner = new_secondary().end().unwrap();
+        assert_eq!(inner.nesting_level(), 0);
+
+        let mut outer = new_secondary();
+
+        if device.enabled_features().nested_command_buffer {
+            outer.execute_commands(inner).unwrap();
+            assert_eq!(outer.end().unwrap().nesting_level(), 1);
+        } else {
+            // Executing a secondary command buffer in another one requires the feature.
+            assert!(outer.execute_commands(inner).is_err());
+        }
+    }
+
+    #[test]
+    fn execute_commands_parallel() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let mut cbb = RecordingCommandBuffer::new(
+            cb_allocator.clone(),
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // Outside of a render pass, nothing is inherited.
+        assert!(cbb.secondary_inheritance_info().render_pass.is_none());
+
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device));
+        let buffers: Vec<_> = (0..4)
+            .map(|_| {
+                Buffer::from_data(
+                    memory_allocator.clone(),
+                    BufferCreateInfo {
+                        usage: BufferUsage::TRANSFER_DST,
+                        ..Default::default()
+                    },
+                    AllocationCreateInfo {
+                        memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                            | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                        ..Default::default()
+                    },
+                    0u32,
+                )
+                .unwrap()
+            })
+            .collect();
+
+        cbb.execute_commands_parallel(cb_allocator, buffers.len(), |index, builder| {
+            builder.fill_buffer(buffers[index].clone().into_slice(), 42)?;
+
+            Ok(())
+        })
+        .unwrap();
+
+        cbb.end()
+            .unwrap()
+            .execute(queue)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        for buffer in buffers {
+            assert_eq!(*buffer.read().unwrap(), 42);
+        }
+    }
+
     #[test]
     fn vertex_buffer_binding() {
         unsafe {
@@ -806,6 +1107,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn set_vertex_input() {
+        let (device, queue) =
+            gfx_dev_and_queue!(vertex_input_dynamic_state; ext_vertex_input_dynamic_state);
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device,
+            Default::default(),
+        ));
+        let mut cbb = RecordingCommandBuffer::new(
+            cb_allocator,
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let binding = VertexInputBindingDescription {
+            stride: 16,
+            ..Default::default()
+        };
+        let attribute = VertexInputAttributeDescription {
+            binding: 0,
+            format: Format::R32G32B32A32_SFLOAT,
+            offset: 0,
+            ..Default::default()
+        };
+
+        // The attribute refers to a binding that doesn't exist.
+        assert!(cbb
+            .set_vertex_input(VertexInputState::new().attribute(0, attribute))
+            .is_err());
+        assert!(cbb.builder_state.vertex_input.is_none());
+
+        cbb.set_vertex_input(
+            VertexInputState::new()
+                .binding(0, binding)
+                .attribute(0, attribute),
+        )
+        .unwrap();
+
+        let vertex_input = cbb.builder_state.vertex_input.as_ref().unwrap();
+        assert_eq!(vertex_input.bindings[&0].stride, 16);
+        assert_eq!(
+            vertex_input.attributes[&0].format,
+            Format::R32G32B32A32_SFLOAT
+        );
+    }
+
     #[test]
     fn descriptor_set_binding() {
         unsafe {
@@ -955,4 +1308,675 @@ mod tests {
                 .map_or(false, |state| state.descriptor_sets.contains_key(&1)));
         }
     }
+
+    #[test]
+    fn pipeline_barrier_layout_transition() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+        let image = Image::new(
+            memory_allocator,
+            ImageCreateInfo {
+                format: Format::R8G8B8A8_UNORM,
+                extent: [32, 32, 1],
+                usage: ImageUsage::TRANSFER_DST | ImageUsage::SAMPLED,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .unwrap();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device,
+            Default::default(),
+        ));
+        let mut cbb = RecordingCommandBuffer::new(
+            cb_allocator,
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let barrier = ImageMemoryBarrier {
+            dst_stages: PipelineStages::ALL_TRANSFER,
+            dst_access: AccessFlags::TRANSFER_WRITE,
+            old_layout: ImageLayout::Undefined,
+            new_layout: ImageLayout::TransferDstOptimal,
+            subresource_range: image.subresource_range(),
+            ..ImageMemoryBarrier::image(image.clone())
+        };
+
+        // A queue family ownership transfer must be to or from the queue family of the command
+        // buffer.
+        let queue_family_count = queue
+            .device()
+            .physical_device()
+            .queue_family_properties()
+            .len();
+        let mut other_queue_family_indices =
+            (0..queue_family_count as u32).filter(|&index| index != queue.queue_family_index());
+
+        if let (Some(src_index), Some(dst_index)) = (
+            other_queue_family_indices.next(),
+            other_queue_family_indices.next(),
+        ) {
+            let err = cbb
+                .pipeline_barrier(DependencyInfo {
+                    image_memory_barriers: [ImageMemoryBarrier {
+                        queue_family_ownership_transfer: Some(
+                            QueueFamilyOwnershipTransfer::ExclusiveBetweenLocal {
+                                src_index,
+                                dst_index,
+                            },
+                        ),
+                        ..barrier.clone()
+                    }]
+                    .into(),
+                    ..Default::default()
+                })
+                .unwrap_err();
+            assert_eq!(err.context, "dependency_info.image_memory_barriers[0]");
+            assert!(err.problem.contains("queue family of the command buffer"));
+        }
+
+        cbb.pipeline_barrier(DependencyInfo {
+            image_memory_barriers: [barrier].into(),
+            ..Default::default()
+        })
+        .unwrap()
+        .clear_color_image(ClearColorImageInfo::image(image))
+        .unwrap();
+
+        cbb.end().unwrap();
+    }
+
+    #[test]
+    fn pipeline_barrier_ownership_transfer() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        if device.physical_device().queue_family_properties().len() < 2 {
+            return;
+        }
+
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+        let image = Image::new(
+            memory_allocator,
+            ImageCreateInfo {
+                format: Format::R8G8B8A8_UNORM,
+                extent: [32, 32, 1],
+                usage: ImageUsage::TRANSFER_DST | ImageUsage::SAMPLED,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .unwrap();
+
+        let queue_family_index = queue.queue_family_index();
+        let other_queue_family_index = if queue_family_index == 0 { 1 } else { 0 };
+
+        let (release, acquire) = ImageMemoryBarrier {
+            src_stages: PipelineStages::ALL_TRANSFER,
+            src_access: AccessFlags::TRANSFER_WRITE,
+            dst_stages: PipelineStages::ALL_COMMANDS,
+            dst_access: AccessFlags::MEMORY_READ,
+            old_layout: ImageLayout::TransferDstOptimal,
+            new_layout: ImageLayout::ShaderReadOnlyOptimal,
+            queue_family_ownership_transfer: Some(
+                QueueFamilyOwnershipTransfer::ExclusiveBetweenLocal {
+                    src_index: queue_family_index,
+                    dst_index: other_queue_family_index,
+                },
+            ),
+            subresource_range: image.subresource_range(),
+            ..ImageMemoryBarrier::image(image.clone())
+        }
+        .release_acquire();
+
+        assert_eq!(release.dst_stages, PipelineStages::empty());
+        assert_eq!(release.dst_access, AccessFlags::empty());
+        assert_eq!(acquire.src_stages, PipelineStages::empty());
+        assert_eq!(acquire.src_access, AccessFlags::empty());
+        assert_eq!(acquire.new_layout, ImageLayout::ShaderReadOnlyOptimal);
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device,
+            Default::default(),
+        ));
+        let mut cbb = RecordingCommandBuffer::new(
+            cb_allocator.clone(),
+            queue_family_index,
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // The release barrier is recorded after the image is written on this queue family.
+        cbb.clear_color_image(ClearColorImageInfo::image(image.clone()))
+            .unwrap()
+            .pipeline_barrier(DependencyInfo {
+                image_memory_barriers: [release.clone()].into(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // One barrier before the first use, and one between the clear and the release. The
+        // release transitions the image to its final layout, so there is no barrier after it.
+        let cb = cbb.end().unwrap();
+        assert_eq!(cb.statistics().inserted_pipeline_barriers, 2);
+
+        let image_usage = &cb.resources_usage().images[0];
+        assert!(image_usage
+            .ranges
+            .iter()
+            .all(|(_range, usage)| usage.final_layout == ImageLayout::ShaderReadOnlyOptimal));
+
+        // Using the image after it's released is a conflict that can't be solved.
+        let mut invalid_cbb = RecordingCommandBuffer::new(
+            cb_allocator.clone(),
+            queue_family_index,
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        invalid_cbb
+            .pipeline_barrier(DependencyInfo {
+                image_memory_barriers: [release].into(),
+                ..Default::default()
+            })
+            .unwrap();
+        invalid_cbb
+            .clear_color_image(ClearColorImageInfo::image(image.clone()))
+            .unwrap();
+        assert!(invalid_cbb.end().is_err());
+
+        let mut cbb = RecordingCommandBuffer::new(
+            cb_allocator,
+            other_queue_family_index,
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        cbb.pipeline_barrier(DependencyInfo {
+            image_memory_barriers: [acquire].into(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        // The acquire expects the layout that the release transitioned the image to, and no
+        // barrier is inserted before it. The only barrier transitions the image back to its
+        // layout requirement at the end.
+        let cb = cbb.end().unwrap();
+        assert_eq!(cb.statistics().inserted_pipeline_barriers, 1);
+
+        let image_usage = &cb.resources_usage().images[0];
+        assert!(image_usage.ranges.iter().all(|(_range, usage)| {
+            usage.expected_layout == ImageLayout::ShaderReadOnlyOptimal
+                && usage.first_use.unwrap().command_name == "pipeline_barrier"
+        }));
+    }
+
+    #[test]
+    fn wait_events_dependency_info() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let mut cbb = RecordingCommandBuffer::new(
+            cb_allocator,
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let event = Arc::new(Event::new(device, Default::default()).unwrap());
+        let dependency_info = DependencyInfo {
+            memory_barriers: [MemoryBarrier {
+                src_stages: PipelineStages::ALL_COMMANDS,
+                src_access: AccessFlags::MEMORY_WRITE,
+                dst_stages: PipelineStages::ALL_COMMANDS,
+                dst_access: AccessFlags::MEMORY_READ,
+                ..Default::default()
+            }]
+            .into(),
+            ..Default::default()
+        };
+
+        cbb.set_event(event.clone(), dependency_info.clone())
+            .unwrap();
+
+        // The dependency info must match the one that the event was set with.
+        assert!(cbb
+            .wait_events([(event.clone(), DependencyInfo::default())].into())
+            .is_err());
+
+        cbb.wait_events([(event, dependency_info)].into()).unwrap();
+    }
+
+    #[test]
+    fn statistics() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+        let buffers: Vec<_> = (0..2)
+            .map(|_| {
+                Buffer::new_slice::<u32>(
+                    memory_allocator.clone(),
+                    BufferCreateInfo {
+                        usage: BufferUsage::TRANSFER_SRC | BufferUsage::TRANSFER_DST,
+                        ..Default::default()
+                    },
+                    AllocationCreateInfo::default(),
+                    16,
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device,
+            Default::default(),
+        ));
+        let mut builder = RecordingCommandBuffer::new(
+            cb_allocator,
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        builder
+            .fill_buffer(buffers[0].clone(), 42)
+            .unwrap()
+            .copy_buffer(CopyBufferInfoTyped::buffers(
+                buffers[0].clone(),
+                buffers[1].clone(),
+            ))
+            .unwrap();
+
+        let statistics = builder.statistics();
+        assert_eq!(statistics.commands, 2);
+        assert_eq!(statistics.draws, 0);
+        assert_eq!(statistics.inserted_pipeline_barriers, 0);
+
+        // The copy reads what the fill wrote, so a barrier is inserted between them.
+        let statistics = builder.end().unwrap().statistics();
+        assert_eq!(statistics.commands, 2);
+        assert_eq!(statistics.inserted_pipeline_barriers, 1);
+        assert_eq!(statistics.inserted_barriers, 1);
+    }
+
+    #[test]
+    fn merge_barriers() {
+        let (device, _queue) = gfx_dev_and_queue!();
+
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device));
+        let buffers: Vec<_> = (0..3)
+            .map(|_| {
+                Buffer::new_slice::<u32>(
+                    memory_allocator.clone(),
+                    BufferCreateInfo {
+                        usage: BufferUsage::TRANSFER_DST,
+                        ..Default::default()
+                    },
+                    AllocationCreateInfo::default(),
+                    16,
+                )
+                .unwrap()
+                .buffer()
+                .clone()
+            })
+            .collect();
+
+        let barrier = |buffer: &Arc<Buffer>, range| BufferMemoryBarrier {
+            src_stages: PipelineStages::ALL_TRANSFER,
+            src_access: AccessFlags::TRANSFER_WRITE,
+            dst_stages: PipelineStages::ALL_TRANSFER,
+            dst_access: AccessFlags::TRANSFER_READ,
+            range,
+            ..BufferMemoryBarrier::buffer(buffer.clone())
+        };
+
+        // Barriers on different buffers are merged into one pipeline barrier, but each buffer keeps
+        // its own barrier.
+        let mut barriers = vec![
+            DependencyInfo {
+                buffer_memory_barriers: [barrier(&buffers[0], 0..64)].into(),
+                ..Default::default()
+            },
+            DependencyInfo::default(),
+            DependencyInfo {
+                buffer_memory_barriers: [barrier(&buffers[1], 0..64)].into(),
+                ..Default::default()
+            },
+        ];
+        super::builder::merge_barriers(&mut barriers);
+
+        assert_eq!(barriers.len(), 1);
+        assert!(barriers[0].memory_barriers.is_empty());
+        assert_eq!(barriers[0].buffer_memory_barriers.len(), 2);
+
+        // Adjacent ranges of the same buffer are coalesced, while overlapping ranges keep their
+        // barriers ordered.
+        let mut barriers = vec![
+            DependencyInfo {
+                buffer_memory_barriers: [barrier(&buffers[2], 0..32), barrier(&buffers[2], 32..64)]
+                    .into(),
+                ..Default::default()
+            },
+            DependencyInfo {
+                buffer_memory_barriers: [barrier(&buffers[2], 16..48)].into(),
+                ..Default::default()
+            },
+        ];
+        super::builder::merge_barriers(&mut barriers);
+
+        assert_eq!(barriers.len(), 2);
+        assert_eq!(barriers[0].buffer_memory_barriers.len(), 1);
+        assert_eq!(barriers[0].buffer_memory_barriers[0].range, 0..64);
+    }
+
+    #[test]
+    fn merge_barriers_aliased() {
+        let (device, _queue) = gfx_dev_and_queue!();
+
+        let raw_buffers: Vec<_> = (0..2)
+            .map(|_| {
+                RawBuffer::new(
+                    device.clone(),
+                    BufferCreateInfo {
+                        size: 64,
+                        usage: BufferUsage::TRANSFER_DST,
+                        ..Default::default()
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+        let memory_requirements = raw_buffers[0].memory_requirements();
+        let memory = Arc::new(
+            DeviceMemory::allocate(
+                device,
+                MemoryAllocateInfo {
+                    allocation_size: memory_requirements.layout.size(),
+                    memory_type_index: memory_requirements.memory_type_bits.trailing_zeros(),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+
+        // Both buffers are bound to the start of the same memory.
+        let buffers: Vec<_> = raw_buffers
+            .into_iter()
+            .map(|raw_buffer| unsafe {
+                let resource_memory = ResourceMemory::new_dedicated_unchecked(memory.clone());
+
+                Arc::new(raw_buffer.bind_memory(resource_memory).unwrap())
+            })
+            .collect();
+
+        let barrier = |buffer: &Arc<Buffer>| BufferMemoryBarrier {
+            src_stages: PipelineStages::ALL_TRANSFER,
+            src_access: AccessFlags::TRANSFER_WRITE,
+            dst_stages: PipelineStages::ALL_TRANSFER,
+            dst_access: AccessFlags::TRANSFER_READ,
+            range: 0..64,
+            ..BufferMemoryBarrier::buffer(buffer.clone())
+        };
+
+        // The buffers are different, but they alias, so the barriers must stay ordered.
+        let mut barriers = vec![
+            DependencyInfo {
+                buffer_memory_barriers: [barrier(&buffers[0])].into(),
+                ..Default::default()
+            },
+            DependencyInfo {
+                buffer_memory_barriers: [barrier(&buffers[1])].into(),
+                ..Default::default()
+            },
+        ];
+        super::builder::merge_barriers(&mut barriers);
+
+        assert_eq!(barriers.len(), 2);
+        assert_eq!(barriers[0].buffer_memory_barriers.len(), 1);
+        assert_eq!(barriers[1].buffer_memory_barriers.len(), 1);
+    }
+
+    #[test]
+    fn descriptor_set_binding2() {
+        let (device, queue) = gfx_dev_and_queue!(maintenance6; khr_maintenance6);
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let mut cbb = RecordingCommandBuffer::new(
+            cb_allocator,
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let set_layout = DescriptorSetLayout::new(
+            device.clone(),
+            DescriptorSetLayoutCreateInfo {
+                bindings: [(
+                    0,
+                    DescriptorSetLayoutBinding {
+                        stages: ShaderStages::FRAGMENT | ShaderStages::COMPUTE,
+                        ..DescriptorSetLayoutBinding::descriptor_type(DescriptorType::Sampler)
+                    },
+                )]
+                .into(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let pipeline_layout = PipelineLayout::new(
+            device.clone(),
+            PipelineLayoutCreateInfo {
+                set_layouts: [set_layout.clone()].into(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let ds_allocator = Arc::new(StandardDescriptorSetAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let set = DescriptorSet::new(
+            ds_allocator,
+            set_layout,
+            [WriteDescriptorSet::sampler(
+                0,
+                Sampler::new(device, SamplerCreateInfo::simple_repeat_linear()).unwrap(),
+            )],
+            [],
+        )
+        .unwrap();
+
+        assert!(cbb
+            .bind_descriptor_sets2(
+                ShaderStages::empty(),
+                pipeline_layout.clone(),
+                0,
+                set.clone(),
+            )
+            .is_err());
+
+        cbb.bind_descriptor_sets2(
+            ShaderStages::FRAGMENT | ShaderStages::COMPUTE,
+            pipeline_layout,
+            0,
+            set,
+        )
+        .unwrap();
+
+        for pipeline_bind_point in [PipelineBindPoint::Compute, PipelineBindPoint::Graphics] {
+            assert!(cbb
+                .builder_state
+                .descriptor_sets
+                .get(&pipeline_bind_point)
+                .map_or(false, |state| state.descriptor_sets.contains_key(&0)));
+        }
+    }
+
+    #[test]
+    fn push_constants2_stages() {
+        let (device, queue) = gfx_dev_and_queue!(maintenance6; khr_maintenance6);
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let mut cbb = RecordingCommandBuffer::new(
+            cb_allocator,
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let pipeline_layout = PipelineLayout::new(
+            device,
+            PipelineLayoutCreateInfo {
+                push_constant_ranges: vec![
+                    PushConstantRange {
+                        stages: ShaderStages::VERTEX,
+                        offset: 0,
+                        size: 8,
+                    },
+                    PushConstantRange {
+                        stages: ShaderStages::FRAGMENT,
+                        offset: 4,
+                        size: 8,
+                    },
+                ],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // Bytes 8..12 are not accessible from the vertex stage.
+        assert!(cbb
+            .push_constants2(
+                ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                pipeline_layout.clone(),
+                4,
+                [0u32; 2],
+            )
+            .is_err());
+
+        // The fragment range overlaps bytes 4..8, so its stage must be included.
+        assert!(cbb
+            .push_constants2(ShaderStages::VERTEX, pipeline_layout.clone(), 0, [0u32; 2])
+            .is_err());
+
+        cbb.push_constants2(
+            ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+            pipeline_layout.clone(),
+            4,
+            0u32,
+        )
+        .unwrap();
+        cbb.push_constants2(ShaderStages::FRAGMENT, pipeline_layout, 8, 0u32)
+            .unwrap();
+
+        assert!(cbb.builder_state.push_constants.contains(4..12));
+        assert!(!cbb.builder_state.push_constants.contains(12..16));
+    }
+
+    #[test]
+    fn conditional_rendering_subpass() {
+        let (device, queue) = gfx_dev_and_queue!(conditional_rendering; ext_conditional_rendering);
+
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+        let buffer = Buffer::from_data(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::CONDITIONAL_RENDERING,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+            1u32,
+        )
+        .unwrap();
+
+        let render_pass = RenderPass::new(
+            device.clone(),
+            RenderPassCreateInfo {
+                subpasses: vec![SubpassDescription::default(), SubpassDescription::default()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let framebuffer = Framebuffer::new(
+            render_pass,
+            FramebufferCreateInfo {
+                extent: [1, 1],
+                layers: 1,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device,
+            Default::default(),
+        ));
+        let mut cbb = RecordingCommandBuffer::new(
+            cb_allocator,
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        cbb.begin_render_pass(
+            RenderPassBeginInfo::framebuffer(framebuffer),
+            Default::default(),
+        )
+        .unwrap()
+        .begin_conditional_rendering(ConditionalRenderingBeginInfo::buffer(buffer))
+        .unwrap()
+        .next_subpass(Default::default(), Default::default())
+        .unwrap();
+
+        // Conditional rendering was made active in the first subpass, so it can't be ended in
+        // the second one.
+        assert!(cbb.end_conditional_rendering().is_err());
+    }
 }
@@ -5,7 +5,8 @@ use crate::{
         AccelerationStructureGeometries, AccelerationStructureGeometryAabbsData,
         AccelerationStructureGeometryInstancesData, AccelerationStructureGeometryInstancesDataType,
         AccelerationStructureGeometryTrianglesData, AccelerationStructureInstance,
-        AccelerationStructureType, BuildAccelerationStructureMode, CopyAccelerationStructureInfo,
+        AccelerationStructureMotionInstance, AccelerationStructureType,
+        BuildAccelerationStructureMode, CopyAccelerationStructureInfo,
         CopyAccelerationStructureToMemoryInfo, CopyMemoryToAccelerationStructureInfo,
         TransformMatrix,
     },
@@ -81,7 +82,9 @@ impl RecordingCommandBuffer {
         info: AccelerationStructureBuildGeometryInfo,
         build_range_infos: SmallVec<[AccelerationStructureBuildRangeInfo; 8]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_build_acceleration_structure(&info, &build_range_infos)?;
+        if self.device().validation_enabled() {
+            self.validate_build_acceleration_structure(&info, &build_range_infos)?;
+        }
 
         Ok(self.build_acceleration_structure_unchecked(info, build_range_infos))
     }
@@ -191,12 +194,14 @@ impl RecordingCommandBuffer {
         stride: u32,
         max_primitive_counts: SmallVec<[u32; 8]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_build_acceleration_structure_indirect(
-            &info,
-            &indirect_buffer,
-            stride,
-            &max_primitive_counts,
-        )?;
+        if self.device().validation_enabled() {
+            self.validate_build_acceleration_structure_indirect(
+                &info,
+                &indirect_buffer,
+                stride,
+                &max_primitive_counts,
+            )?;
+        }
 
         Ok(self.build_acceleration_structure_indirect_unchecked(
             info,
@@ -275,7 +280,9 @@ impl RecordingCommandBuffer {
         &mut self,
         info: CopyAccelerationStructureInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_copy_acceleration_structure(&info)?;
+        if self.device().validation_enabled() {
+            self.validate_copy_acceleration_structure(&info)?;
+        }
 
         Ok(self.copy_acceleration_structure_unchecked(info))
     }
@@ -359,7 +366,9 @@ impl RecordingCommandBuffer {
         &mut self,
         info: CopyAccelerationStructureToMemoryInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_copy_acceleration_structure_to_memory(&info)?;
+        if self.device().validation_enabled() {
+            self.validate_copy_acceleration_structure_to_memory(&info)?;
+        }
 
         Ok(self.copy_acceleration_structure_to_memory_unchecked(info))
     }
@@ -446,7 +455,9 @@ impl RecordingCommandBuffer {
         &mut self,
         info: CopyMemoryToAccelerationStructureInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_copy_memory_to_acceleration_structure(&info)?;
+        if self.device().validation_enabled() {
+            self.validate_copy_memory_to_acceleration_structure(&info)?;
+        }
 
         Ok(self.copy_memory_to_acceleration_structure_unchecked(info))
     }
@@ -538,11 +549,13 @@ impl RecordingCommandBuffer {
         query_pool: Arc<QueryPool>,
         first_query: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_write_acceleration_structures_properties(
-            &acceleration_structures,
-            &query_pool,
-            first_query,
-        )?;
+        if self.device().validation_enabled() {
+            self.validate_write_acceleration_structures_properties(
+                &acceleration_structures,
+                &query_pool,
+                first_query,
+            )?;
+        }
 
         Ok(self.write_acceleration_structures_properties_unchecked(
             acceleration_structures,
@@ -642,6 +655,8 @@ fn add_build_geometry_resources(
                         max_vertex: _,
                         ref index_data,
                         ref transform_data,
+                        ref motion_vertex_data,
+                        ref opacity_micromap,
                         _ne,
                     } = triangles_data;
 
@@ -682,6 +697,44 @@ fn add_build_geometry_resources(
                             },
                         )
                     }))
+                    .chain(motion_vertex_data.as_ref().map(|motion_vertex_data| {
+                        (
+                            ResourceInCommand::GeometryTrianglesMotionVertexData { index }.into(),
+                            Resource::Buffer {
+                                buffer: motion_vertex_data.clone(),
+                                range: 0..motion_vertex_data.size(), // TODO:
+                                memory_access: PipelineStageAccessFlags::AccelerationStructureBuild_ShaderSampledRead
+                                    | PipelineStageAccessFlags::AccelerationStructureBuild_ShaderStorageRead,
+                            },
+                        )
+                    }))
+                    .chain(opacity_micromap.iter().flat_map(move |opacity_micromap| {
+                        let micromap_buffer = opacity_micromap.micromap.buffer();
+
+                        [
+                            (
+                                ResourceInCommand::GeometryTrianglesOpacityMicromap { index }.into(),
+                                Resource::Buffer {
+                                    buffer: micromap_buffer.clone(),
+                                    range: 0..micromap_buffer.size(),
+                                    memory_access: PipelineStageAccessFlags::AccelerationStructureBuild_MicromapRead,
+                                },
+                            ),
+                        ].into_iter()
+                        .chain(opacity_micromap.index_data.as_ref().map(|index_data| {
+                            let index_data_bytes = index_data.as_bytes();
+
+                            (
+                                ResourceInCommand::GeometryTrianglesOpacityMicromapIndexData { index }.into(),
+                                Resource::Buffer {
+                                    buffer: index_data_bytes.clone(),
+                                    range: 0..index_data_bytes.size(), // TODO:
+                                    memory_access: PipelineStageAccessFlags::AccelerationStructureBuild_ShaderSampledRead
+                                        | PipelineStageAccessFlags::AccelerationStructureBuild_ShaderStorageRead,
+                                },
+                            )
+                        }))
+                    }))
                 })
             );
         }
@@ -720,6 +773,10 @@ fn add_build_geometry_resources(
                     let data = data.as_ref().unwrap();
                     data.as_bytes()
                 }
+                AccelerationStructureGeometryInstancesDataType::MotionValues(data) => {
+                    let data = data.as_ref().unwrap();
+                    data.as_bytes()
+                }
                 AccelerationStructureGeometryInstancesDataType::Pointers(data) => {
                     let data = data.as_ref().unwrap();
                     data.as_bytes()
@@ -976,6 +1033,8 @@ impl RawRecordingCommandBuffer {
                         max_vertex: _,
                         ref index_data,
                         ref transform_data,
+                        ref motion_vertex_data,
+                        opacity_micromap: _,
                         _ne,
                     } = triangles_data;
 
@@ -989,6 +1048,39 @@ impl RawRecordingCommandBuffer {
                         })
                     })?;
 
+                    if let Some(motion_vertex_data) = motion_vertex_data {
+                        if !motion_vertex_data
+                            .buffer()
+                            .usage()
+                            .intersects(BufferUsage::ACCELERATION_STRUCTURE_BUILD_INPUT_READ_ONLY)
+                        {
+                            return Err(Box::new(ValidationError {
+                                context: format!(
+                                    "info.geometries[{}].motion_vertex_data",
+                                    geometry_index
+                                )
+                                .into(),
+                                problem: "the buffer was not created with the \
+                                    `BufferUsage::ACCELERATION_STRUCTURE_BUILD_INPUT_READ_ONLY` \
+                                    usage"
+                                    .into(),
+                                ..Default::default()
+                            }));
+                        }
+
+                        if motion_vertex_data.size() < vertex_data.size() {
+                            return Err(Box::new(ValidationError {
+                                context: format!(
+                                    "info.geometries[{}].motion_vertex_data",
+                                    geometry_index
+                                )
+                                .into(),
+                                problem: "is smaller than `vertex_data`".into(),
+                                ..Default::default()
+                            }));
+                        }
+                    }
+
                     let &AccelerationStructureBuildRangeInfo {
                         primitive_count,
                         primitive_offset,
@@ -1401,6 +1493,50 @@ impl RawRecordingCommandBuffer {
 
                         data.buffer()
                     }
+                    AccelerationStructureGeometryInstancesDataType::MotionValues(data) => {
+                        let data = data.as_ref().ok_or_else(|| {
+                            Box::new(ValidationError {
+                                context: "info.geometries.data".into(),
+                                problem: "is `None`".into(),
+                                // vuids?
+                                ..Default::default()
+                            })
+                        })?;
+
+                        if data.device_address().unwrap().get() % 16 != 0 {
+                            return Err(Box::new(ValidationError {
+                                context: "info.geometries.data".into(),
+                                problem: "is `AccelerationStructureGeometryInstancesDataType::\
+                                    MotionValues`, and the buffer's device address is not a \
+                                    multiple of 16"
+                                    .into(),
+                                vuids: &["VUID-vkCmdBuildAccelerationStructuresKHR-pInfos-03715"],
+                                ..Default::default()
+                            }));
+                        }
+
+                        if primitive_offset as DeviceSize
+                            + primitive_count as DeviceSize
+                                * size_of::<AccelerationStructureMotionInstance>() as DeviceSize
+                            > data.size()
+                        {
+                            return Err(Box::new(ValidationError {
+                                problem: "`infos.geometries` is \
+                                    `AccelerationStructureGeometries::Instances`, \
+                                    `infos.geometries.data` is \
+                                    `AccelerationStructureGeometryInstancesDataType::\
+                                    MotionValues`, and \
+                                    `build_range_infos[0].primitive_offset` + \
+                                    `build_range_infos[0].primitive_count` * \
+                                    `size_of::<AccelerationStructureMotionInstance>()` is greater \
+                                    than the size of `infos.geometries.data`"
+                                    .into(),
+                                ..Default::default()
+                            }));
+                        }
+
+                        data.buffer()
+                    }
                     AccelerationStructureGeometryInstancesDataType::Pointers(data) => {
                         let data = data.as_ref().ok_or_else(|| {
                             Box::new(ValidationError {
@@ -1539,7 +1675,7 @@ impl RawRecordingCommandBuffer {
         info: &AccelerationStructureBuildGeometryInfo,
         build_range_infos: &[AccelerationStructureBuildRangeInfo],
     ) -> &mut Self {
-        let (mut info_vk, geometries_vk) = info.to_vulkan();
+        let (mut info_vk, geometries_vk, _extensions_vk) = info.to_vulkan();
         info_vk = ash::vk::AccelerationStructureBuildGeometryInfoKHR {
             geometry_count: geometries_vk.len() as u32,
             p_geometries: geometries_vk.as_ptr(),
@@ -1779,6 +1915,8 @@ impl RawRecordingCommandBuffer {
                         max_vertex: _,
                         ref index_data,
                         ref transform_data,
+                        ref motion_vertex_data,
+                        opacity_micromap: _,
                         _ne,
                     } = triangles_data;
 
@@ -1792,6 +1930,39 @@ impl RawRecordingCommandBuffer {
                         })
                     })?;
 
+                    if let Some(motion_vertex_data) = motion_vertex_data {
+                        if !motion_vertex_data
+                            .buffer()
+                            .usage()
+                            .intersects(BufferUsage::ACCELERATION_STRUCTURE_BUILD_INPUT_READ_ONLY)
+                        {
+                            return Err(Box::new(ValidationError {
+                                context: format!(
+                                    "info.geometries[{}].motion_vertex_data",
+                                    geometry_index
+                                )
+                                .into(),
+                                problem: "the buffer was not created with the \
+                                    `BufferUsage::ACCELERATION_STRUCTURE_BUILD_INPUT_READ_ONLY` \
+                                    usage"
+                                    .into(),
+                                ..Default::default()
+                            }));
+                        }
+
+                        if motion_vertex_data.size() < vertex_data.size() {
+                            return Err(Box::new(ValidationError {
+                                context: format!(
+                                    "info.geometries[{}].motion_vertex_data",
+                                    geometry_index
+                                )
+                                .into(),
+                                problem: "is smaller than `vertex_data`".into(),
+                                ..Default::default()
+                            }));
+                        }
+                    }
+
                     // VUID-VkAccelerationStructureBuildGeometryInfoKHR-type-03795
                     // unsafe
 
@@ -2012,6 +2183,32 @@ impl RawRecordingCommandBuffer {
 
                         data.buffer()
                     }
+                    AccelerationStructureGeometryInstancesDataType::MotionValues(data) => {
+                        let data = data.as_ref().ok_or_else(|| {
+                            Box::new(ValidationError {
+                                context: "info.geometries.data".into(),
+                                problem: "is `None`".into(),
+                                // vuids?
+                                ..Default::default()
+                            })
+                        })?;
+
+                        if data.device_address().unwrap().get() % 16 != 0 {
+                            return Err(Box::new(ValidationError {
+                                context: "info.geometries.data".into(),
+                                problem: "is `AccelerationStructureGeometryInstancesDataType::\
+                                    MotionValues` and the buffer's device address is not a \
+                                    multiple of 16"
+                                    .into(),
+                                vuids: &[
+                                    "VUID-vkCmdBuildAccelerationStructuresIndirectKHR-pInfos-03715",
+                                ],
+                                ..Default::default()
+                            }));
+                        }
+
+                        data.buffer()
+                    }
                     AccelerationStructureGeometryInstancesDataType::Pointers(data) => {
                         let data = data.as_ref().ok_or_else(|| {
                             Box::new(ValidationError {
@@ -2182,7 +2379,7 @@ impl RawRecordingCommandBuffer {
         stride: u32,
         max_primitive_counts: &[u32],
     ) -> &mut Self {
-        let (mut info_vk, geometries_vk) = info.to_vulkan();
+        let (mut info_vk, geometries_vk, _extensions_vk) = info.to_vulkan();
         info_vk = ash::vk::AccelerationStructureBuildGeometryInfoKHR {
             geometry_count: geometries_vk.len() as u32,
             p_geometries: geometries_vk.as_ptr(),
@@ -7,12 +7,13 @@ use crate::{
         DescriptorSetWithOffsets, DescriptorSetsCollection, DescriptorWriteInfo,
         WriteDescriptorSet,
     },
-    device::{DeviceOwned, QueueFlags},
+    device::{Device, DeviceOwned, QueueFlags},
     memory::is_aligned,
     pipeline::{
-        graphics::vertex_input::VertexBuffersCollection, ComputePipeline, GraphicsPipeline,
-        PipelineBindPoint, PipelineLayout,
+        graphics::vertex_input::VertexBuffersCollection, layout::PushConstantRange,
+        ComputePipeline, GraphicsPipeline, Pipeline, PipelineBindPoint, PipelineLayout,
     },
+    shader::ShaderStages,
     DeviceSize, Requires, RequiresAllOf, RequiresOneOf, ValidationError, Version, VulkanObject,
 };
 use smallvec::SmallVec;
@@ -31,12 +32,14 @@ impl RecordingCommandBuffer {
         descriptor_sets: impl DescriptorSetsCollection,
     ) -> Result<&mut Self, Box<ValidationError>> {
         let descriptor_sets = descriptor_sets.into_vec();
-        self.validate_bind_descriptor_sets(
-            pipeline_bind_point,
-            &pipeline_layout,
-            first_set,
-            &descriptor_sets,
-        )?;
+        if self.device().validation_enabled() {
+            self.validate_bind_descriptor_sets(
+                pipeline_bind_point,
+                &pipeline_layout,
+                first_set,
+                &descriptor_sets,
+            )?;
+        }
 
         unsafe {
             Ok(self.bind_descriptor_sets_unchecked(
@@ -108,13 +111,113 @@ impl RecordingCommandBuffer {
         self
     }
 
+    /// Binds descriptor sets for future dispatch or draw calls, to the pipeline bind points of
+    /// all the shader stages in `stage_flags`.
+    ///
+    /// This has the same effect as calling [`bind_descriptor_sets`] for each of the pipeline bind
+    /// points, but records a single command. The [`maintenance6`] feature must be enabled on the
+    /// device.
+    ///
+    /// [`bind_descriptor_sets`]: Self::bind_descriptor_sets
+    /// [`maintenance6`]: crate::device::DeviceFeatures::maintenance6
+    pub fn bind_descriptor_sets2(
+        &mut self,
+        stage_flags: ShaderStages,
+        pipeline_layout: Arc<PipelineLayout>,
+        first_set: u32,
+        descriptor_sets: impl DescriptorSetsCollection,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        let descriptor_sets = descriptor_sets.into_vec();
+        if self.device().validation_enabled() {
+            self.validate_bind_descriptor_sets2(
+                stage_flags,
+                &pipeline_layout,
+                first_set,
+                &descriptor_sets,
+            )?;
+        }
+
+        unsafe {
+            Ok(self.bind_descriptor_sets2_unchecked(
+                stage_flags,
+                pipeline_layout,
+                first_set,
+                descriptor_sets,
+            ))
+        }
+    }
+
+    fn validate_bind_descriptor_sets2(
+        &self,
+        stage_flags: ShaderStages,
+        pipeline_layout: &PipelineLayout,
+        first_set: u32,
+        descriptor_sets: &[DescriptorSetWithOffsets],
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_bind_descriptor_sets2(
+            stage_flags,
+            pipeline_layout,
+            first_set,
+            descriptor_sets,
+        )?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn bind_descriptor_sets2_unchecked(
+        &mut self,
+        stage_flags: ShaderStages,
+        pipeline_layout: Arc<PipelineLayout>,
+        first_set: u32,
+        descriptor_sets: impl DescriptorSetsCollection,
+    ) -> &mut Self {
+        let descriptor_sets = descriptor_sets.into_vec();
+
+        if descriptor_sets.is_empty() {
+            return self;
+        }
+
+        for pipeline_bind_point in pipeline_bind_points(stage_flags) {
+            let state = self.builder_state.invalidate_descriptor_sets(
+                pipeline_bind_point,
+                pipeline_layout.clone(),
+                first_set,
+                descriptor_sets.len() as u32,
+            );
+
+            for (set_num, set) in descriptor_sets.iter().enumerate() {
+                state
+                    .descriptor_sets
+                    .insert(first_set + set_num as u32, SetOrPush::Set(set.clone()));
+            }
+        }
+
+        self.add_command(
+            "bind_descriptor_sets2",
+            Default::default(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.bind_descriptor_sets2_unchecked(
+                    stage_flags,
+                    &pipeline_layout,
+                    first_set,
+                    &descriptor_sets,
+                );
+            },
+        );
+
+        self
+    }
+
     /// Binds an index buffer for future indexed draw calls.
     pub fn bind_index_buffer(
         &mut self,
         index_buffer: impl Into<IndexBuffer>,
     ) -> Result<&mut Self, Box<ValidationError>> {
         let index_buffer = index_buffer.into();
-        self.validate_bind_index_buffer(&index_buffer)?;
+        if self.device().validation_enabled() {
+            self.validate_bind_index_buffer(&index_buffer)?;
+        }
 
         unsafe { Ok(self.bind_index_buffer_unchecked(index_buffer)) }
     }
@@ -151,7 +254,9 @@ impl RecordingCommandBuffer {
         &mut self,
         pipeline: Arc<ComputePipeline>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_bind_pipeline_compute(&pipeline)?;
+        if self.device().validation_enabled() {
+            self.validate_bind_pipeline_compute(&pipeline)?;
+        }
 
         unsafe { Ok(self.bind_pipeline_compute_unchecked(pipeline)) }
     }
@@ -187,7 +292,9 @@ impl RecordingCommandBuffer {
         &mut self,
         pipeline: Arc<GraphicsPipeline>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_bind_pipeline_graphics(&pipeline)?;
+        if self.device().validation_enabled() {
+            self.validate_bind_pipeline_graphics(&pipeline)?;
+        }
 
         unsafe { Ok(self.bind_pipeline_graphics_unchecked(pipeline)) }
     }
@@ -198,6 +305,32 @@ impl RecordingCommandBuffer {
     ) -> Result<(), Box<ValidationError>> {
         self.inner.validate_bind_pipeline_graphics(pipeline)?;
 
+        if self.builder_state.render_pass.is_some()
+            && !self
+                .device()
+                .physical_device()
+                .properties()
+                .provoking_vertex_mode_per_pipeline
+                .unwrap_or(false)
+        {
+            if let Some(current_pipeline) = self.builder_state.pipeline_graphics.as_ref() {
+                if pipeline.rasterization_state().provoking_vertex_mode
+                    != current_pipeline.rasterization_state().provoking_vertex_mode
+                {
+                    return Err(Box::new(ValidationError {
+                        problem: "a render pass instance is active, the \
+                            `provoking_vertex_mode_per_pipeline` device property is \
+                            `false`, and `pipeline.rasterization_state().provoking_vertex_mode` \
+                            does not equal the provoking vertex mode of the currently bound \
+                            graphics pipeline"
+                            .into(),
+                        vuids: &["VUID-vkCmdBindPipeline-pipelineBindPoint-04881"],
+                        ..Default::default()
+                    }));
+                }
+            }
+        }
+
         // VUID-vkCmdBindPipeline-pipeline-00781
         // TODO:
 
@@ -232,7 +365,9 @@ impl RecordingCommandBuffer {
         vertex_buffers: impl VertexBuffersCollection,
     ) -> Result<&mut Self, Box<ValidationError>> {
         let vertex_buffers = vertex_buffers.into_vec();
-        self.validate_bind_vertex_buffers(first_binding, &vertex_buffers)?;
+        if self.device().validation_enabled() {
+            self.validate_bind_vertex_buffers(first_binding, &vertex_buffers)?;
+        }
 
         unsafe { Ok(self.bind_vertex_buffers_unchecked(first_binding, vertex_buffers)) }
     }
@@ -289,7 +424,9 @@ impl RecordingCommandBuffer {
             return Ok(self);
         }
 
-        self.validate_push_constants(&pipeline_layout, offset, &push_constants)?;
+        if self.device().validation_enabled() {
+            self.validate_push_constants(&pipeline_layout, offset, &push_constants)?;
+        }
 
         unsafe { Ok(self.push_constants_unchecked(pipeline_layout, offset, push_constants)) }
     }
@@ -337,6 +474,195 @@ impl RecordingCommandBuffer {
         self
     }
 
+    /// Sets push constants for future dispatch or draw calls, for the shader stages in
+    /// `stage_flags`.
+    ///
+    /// Unlike [`push_constants`], which splits `push_constants` across the push constant ranges
+    /// of `pipeline_layout`, this records a single command with the given stages. Each byte of
+    /// `push_constants` must be in a push constant range of `pipeline_layout` for all of
+    /// `stage_flags`, and `stage_flags` must contain the stages of every range that overlaps
+    /// `push_constants`. The [`maintenance6`] feature must be enabled on the device.
+    ///
+    /// [`push_constants`]: Self::push_constants
+    /// [`maintenance6`]: crate::device::DeviceFeatures::maintenance6
+    pub fn push_constants2<Pc>(
+        &mut self,
+        stage_flags: ShaderStages,
+        pipeline_layout: Arc<PipelineLayout>,
+        offset: u32,
+        push_constants: Pc,
+    ) -> Result<&mut Self, Box<ValidationError>>
+    where
+        Pc: BufferContents,
+    {
+        let size = size_of::<Pc>() as u32;
+
+        if size == 0 {
+            return Ok(self);
+        }
+
+        if self.device().validation_enabled() {
+            self.validate_push_constants2(stage_flags, &pipeline_layout, offset, &push_constants)?;
+        }
+
+        unsafe {
+            Ok(
+                self.push_constants2_unchecked(
+                    stage_flags,
+                    pipeline_layout,
+                    offset,
+                    push_constants,
+                ),
+            )
+        }
+    }
+
+    fn validate_push_constants2<Pc: BufferContents>(
+        &self,
+        stage_flags: ShaderStages,
+        pipeline_layout: &PipelineLayout,
+        offset: u32,
+        push_constants: &Pc,
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_push_constants2(
+            stage_flags,
+            pipeline_layout,
+            offset,
+            push_constants,
+        )?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn push_constants2_unchecked<Pc>(
+        &mut self,
+        stage_flags: ShaderStages,
+        pipeline_layout: Arc<PipelineLayout>,
+        offset: u32,
+        push_constants: Pc,
+    ) -> &mut Self
+    where
+        Pc: BufferContents,
+    {
+        // See the comment in `push_constants_unchecked`.
+        self.builder_state
+            .push_constants
+            .insert(offset..offset + size_of::<Pc>() as u32);
+        self.builder_state.push_constants_pipeline_layout = Some(pipeline_layout.clone());
+
+        self.add_command(
+            "push_constants2",
+            Default::default(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.push_constants2_unchecked(
+                    stage_flags,
+                    &pipeline_layout,
+                    offset,
+                    &push_constants,
+                );
+            },
+        );
+
+        self
+    }
+
+    /// Sets push constants for `range`, using the layout of the pipeline that is currently bound
+    /// to `pipeline_bind_point`.
+    ///
+    /// Unlike [`push_constants`](Self::push_constants), which writes to any offset that it's
+    /// given, this checks that `push_constants` has the size of `range`, and that the push
+    /// constant ranges of the bound pipeline's layout give all of `range.stages` access to
+    /// `range`. The `shader!` macro of `vulkano-shaders` generates the range of every entry point
+    /// that uses push constants, along with a method that calls this one with the push constant
+    /// struct of the entry point.
+    pub fn push_constants_for_range<Pc>(
+        &mut self,
+        pipeline_bind_point: PipelineBindPoint,
+        range: PushConstantRange,
+        push_constants: Pc,
+    ) -> Result<&mut Self, Box<ValidationError>>
+    where
+        Pc: BufferContents,
+    {
+        let pipeline_layout = match pipeline_bind_point {
+            PipelineBindPoint::Compute => self
+                .builder_state
+                .pipeline_compute
+                .as_ref()
+                .map(|pipeline| pipeline.layout().clone()),
+            PipelineBindPoint::Graphics => self
+                .builder_state
+                .pipeline_graphics
+                .as_ref()
+                .map(|pipeline| pipeline.layout().clone()),
+            _ => None,
+        };
+
+        let pipeline_layout = match pipeline_layout {
+            Some(pipeline_layout) => pipeline_layout,
+            None => {
+                return Err(Box::new(ValidationError {
+                    context: "pipeline_bind_point".into(),
+                    problem: "no compute or graphics pipeline is currently bound to this \
+                        pipeline bind point"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        };
+
+        if self.device().validation_enabled() {
+            self.validate_push_constants_for_range(&pipeline_layout, &range, &push_constants)?;
+        }
+
+        unsafe { Ok(self.push_constants_unchecked(pipeline_layout, range.offset, push_constants)) }
+    }
+
+    fn validate_push_constants_for_range<Pc: BufferContents>(
+        &self,
+        pipeline_layout: &PipelineLayout,
+        range: &PushConstantRange,
+        push_constants: &Pc,
+    ) -> Result<(), Box<ValidationError>> {
+        if size_of::<Pc>() != range.size as usize {
+            return Err(Box::new(ValidationError {
+                problem: "the size of `push_constants` does not equal `range.size`".into(),
+                ..Default::default()
+            }));
+        }
+
+        let end = range.offset + range.size;
+        let mut current_offset = range.offset;
+
+        for layout_range in pipeline_layout
+            .push_constant_ranges_disjoint()
+            .iter()
+            .skip_while(|layout_range| layout_range.offset + layout_range.size <= range.offset)
+        {
+            if layout_range.offset > current_offset || !layout_range.stages.contains(range.stages) {
+                break;
+            }
+
+            current_offset = layout_range.offset + layout_range.size;
+
+            if current_offset >= end {
+                break;
+            }
+        }
+
+        if current_offset < end {
+            return Err(Box::new(ValidationError {
+                problem: "the push constant ranges of the layout of the bound pipeline do not \
+                    give all of `range.stages` access to all bytes of `range`"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        self.validate_push_constants(pipeline_layout, range.offset, push_constants)
+    }
+
     /// Pushes descriptor data directly into the command buffer for future dispatch or draw calls.
     pub fn push_descriptor_set(
         &mut self,
@@ -345,12 +671,14 @@ impl RecordingCommandBuffer {
         set_num: u32,
         descriptor_writes: SmallVec<[WriteDescriptorSet; 8]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_push_descriptor_set(
-            pipeline_bind_point,
-            &pipeline_layout,
-            set_num,
-            &descriptor_writes,
-        )?;
+        if self.device().validation_enabled() {
+            self.validate_push_descriptor_set(
+                pipeline_bind_point,
+                &pipeline_layout,
+                set_num,
+                &descriptor_writes,
+            )?;
+        }
 
         unsafe {
             Ok(self.push_descriptor_set_unchecked(
@@ -700,6 +1028,105 @@ impl RawRecordingCommandBuffer {
         self
     }
 
+    #[inline]
+    pub unsafe fn bind_descriptor_sets2(
+        &mut self,
+        stage_flags: ShaderStages,
+        pipeline_layout: &PipelineLayout,
+        first_set: u32,
+        descriptor_sets: &[DescriptorSetWithOffsets],
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        self.validate_bind_descriptor_sets2(
+            stage_flags,
+            pipeline_layout,
+            first_set,
+            descriptor_sets,
+        )?;
+
+        Ok(self.bind_descriptor_sets2_unchecked(
+            stage_flags,
+            pipeline_layout,
+            first_set,
+            descriptor_sets,
+        ))
+    }
+
+    fn validate_bind_descriptor_sets2(
+        &self,
+        stage_flags: ShaderStages,
+        pipeline_layout: &PipelineLayout,
+        first_set: u32,
+        descriptor_sets: &[DescriptorSetWithOffsets],
+    ) -> Result<(), Box<ValidationError>> {
+        if !self.device().enabled_features().maintenance6 {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "maintenance6",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        validate_stage_flags(self.device(), stage_flags).map_err(|err| {
+            err.add_context("stage_flags").set_vuids(&[
+                "VUID-VkBindDescriptorSetsInfoKHR-stageFlags-parameter",
+                "VUID-VkBindDescriptorSetsInfoKHR-stageFlags-requiredbitmask",
+            ])
+        })?;
+
+        for pipeline_bind_point in pipeline_bind_points(stage_flags) {
+            self.validate_bind_descriptor_sets(
+                pipeline_bind_point,
+                pipeline_layout,
+                first_set,
+                descriptor_sets,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn bind_descriptor_sets2_unchecked(
+        &mut self,
+        stage_flags: ShaderStages,
+        pipeline_layout: &PipelineLayout,
+        first_set: u32,
+        descriptor_sets: &[DescriptorSetWithOffsets],
+    ) -> &mut Self {
+        if descriptor_sets.is_empty() {
+            return self;
+        }
+
+        let descriptor_sets_vk: SmallVec<[_; 12]> = descriptor_sets
+            .iter()
+            .map(|x| x.as_ref().0.handle())
+            .collect();
+        let dynamic_offsets_vk: SmallVec<[_; 32]> = descriptor_sets
+            .iter()
+            .flat_map(|x| x.as_ref().1.iter().copied())
+            .collect();
+
+        let bind_descriptor_sets_info_vk = ash::vk::BindDescriptorSetsInfoKHR {
+            stage_flags: stage_flags.into(),
+            layout: pipeline_layout.handle(),
+            first_set,
+            descriptor_set_count: descriptor_sets_vk.len() as u32,
+            p_descriptor_sets: descriptor_sets_vk.as_ptr(),
+            dynamic_offset_count: dynamic_offsets_vk.len() as u32,
+            p_dynamic_offsets: dynamic_offsets_vk.as_ptr(),
+            ..Default::default()
+        };
+
+        let fns = self.device().fns();
+        (fns.khr_maintenance6.cmd_bind_descriptor_sets2_khr)(
+            self.handle(),
+            &bind_descriptor_sets_info_vk,
+        );
+
+        self
+    }
+
     #[inline]
     pub unsafe fn bind_index_buffer(
         &mut self,
@@ -770,12 +1197,25 @@ impl RawRecordingCommandBuffer {
         let index_buffer_bytes = index_buffer.as_bytes();
 
         let fns = self.device().fns();
-        (fns.v1_0.cmd_bind_index_buffer)(
-            self.handle(),
-            index_buffer_bytes.buffer().handle(),
-            index_buffer_bytes.offset(),
-            index_buffer.index_type().into(),
-        );
+
+        if self.device().enabled_features().maintenance5 {
+            // Binding the size as well lets the implementation bounds-check the index fetches
+            // against the subbuffer, rather than against the whole buffer.
+            (fns.khr_maintenance5.cmd_bind_index_buffer2_khr)(
+                self.handle(),
+                index_buffer_bytes.buffer().handle(),
+                index_buffer_bytes.offset(),
+                index_buffer_bytes.size(),
+                index_buffer.index_type().into(),
+            );
+        } else {
+            (fns.v1_0.cmd_bind_index_buffer)(
+                self.handle(),
+                index_buffer_bytes.buffer().handle(),
+                index_buffer_bytes.offset(),
+                index_buffer.index_type().into(),
+            );
+        }
 
         self
     }
@@ -1170,6 +1610,168 @@ impl RawRecordingCommandBuffer {
         self
     }
 
+    #[inline]
+    pub unsafe fn push_constants2<Pc>(
+        &mut self,
+        stage_flags: ShaderStages,
+        pipeline_layout: &PipelineLayout,
+        offset: u32,
+        push_constants: &Pc,
+    ) -> Result<&mut Self, Box<ValidationError>>
+    where
+        Pc: BufferContents,
+    {
+        self.validate_push_constants2(stage_flags, pipeline_layout, offset, push_constants)?;
+
+        Ok(self.push_constants2_unchecked(stage_flags, pipeline_layout, offset, push_constants))
+    }
+
+    fn validate_push_constants2<Pc: BufferContents>(
+        &self,
+        stage_flags: ShaderStages,
+        pipeline_layout: &PipelineLayout,
+        offset: u32,
+        _push_constants: &Pc,
+    ) -> Result<(), Box<ValidationError>> {
+        if !self.device().enabled_features().maintenance6 {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "maintenance6",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        validate_stage_flags(self.device(), stage_flags).map_err(|err| {
+            err.add_context("stage_flags").set_vuids(&[
+                "VUID-VkPushConstantsInfoKHR-stageFlags-parameter",
+                "VUID-VkPushConstantsInfoKHR-stageFlags-requiredbitmask",
+            ])
+        })?;
+
+        let size = size_of::<Pc>();
+
+        if offset % 4 != 0 {
+            return Err(Box::new(ValidationError {
+                context: "offset".into(),
+                problem: "is not a multiple of 4".into(),
+                vuids: &["VUID-VkPushConstantsInfoKHR-offset-00368"],
+                ..Default::default()
+            }));
+        }
+
+        if size % 4 != 0 {
+            return Err(Box::new(ValidationError {
+                context: "push_constants".into(),
+                problem: "the size is not a multiple of 4".into(),
+                vuids: &["VUID-VkPushConstantsInfoKHR-size-00369"],
+                ..Default::default()
+            }));
+        }
+
+        let properties = self.device().physical_device().properties();
+
+        if offset >= properties.max_push_constants_size {
+            return Err(Box::new(ValidationError {
+                context: "offset".into(),
+                problem: "is not less than the `max_push_constants_size` limit".into(),
+                vuids: &["VUID-VkPushConstantsInfoKHR-offset-00370"],
+                ..Default::default()
+            }));
+        }
+
+        if offset as usize + size > properties.max_push_constants_size as usize {
+            return Err(Box::new(ValidationError {
+                problem: "`offset` + the size of `push_constants` is not less than or \
+                    equal to the `max_push_constants_size` limit"
+                    .into(),
+                vuids: &["VUID-VkPushConstantsInfoKHR-size-00371"],
+                ..Default::default()
+            }));
+        }
+
+        let end = offset + size as u32;
+        let mut current_offset = offset;
+
+        // The stages of each disjoint range are the union of the stages of the ranges that
+        // overlap it.
+        for range in pipeline_layout
+            .push_constant_ranges_disjoint()
+            .iter()
+            .skip_while(|range| range.offset + range.size <= offset)
+        {
+            if range.offset > current_offset || !range.stages.contains(stage_flags) {
+                break;
+            }
+
+            current_offset = range.offset + range.size;
+
+            if current_offset >= end {
+                break;
+            }
+        }
+
+        if current_offset < end {
+            return Err(Box::new(ValidationError {
+                problem: "one or more bytes of `push_constants` are not within a push constant \
+                    range of `pipeline_layout` for all of `stage_flags`"
+                    .into(),
+                vuids: &["VUID-VkPushConstantsInfoKHR-offset-01795"],
+                ..Default::default()
+            }));
+        }
+
+        for range in pipeline_layout.push_constant_ranges() {
+            if range.offset < end
+                && offset < range.offset + range.size
+                && !stage_flags.contains(range.stages)
+            {
+                return Err(Box::new(ValidationError {
+                    problem: "a push constant range of `pipeline_layout` overlaps \
+                        `push_constants`, but `stage_flags` does not contain all of the stages \
+                        of the range"
+                        .into(),
+                    vuids: &["VUID-VkPushConstantsInfoKHR-offset-01796"],
+                    ..Default::default()
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn push_constants2_unchecked<Pc>(
+        &mut self,
+        stage_flags: ShaderStages,
+        pipeline_layout: &PipelineLayout,
+        offset: u32,
+        push_constants: &Pc,
+    ) -> &mut Self
+    where
+        Pc: BufferContents,
+    {
+        let size = u32::try_from(size_of::<Pc>()).unwrap();
+
+        if size == 0 {
+            return self;
+        }
+
+        let push_constants_info_vk = ash::vk::PushConstantsInfoKHR {
+            layout: pipeline_layout.handle(),
+            stage_flags: stage_flags.into(),
+            offset,
+            size,
+            p_values: <*const _>::cast::<c_void>(push_constants),
+            ..Default::default()
+        };
+
+        let fns = self.device().fns();
+        (fns.khr_maintenance6.cmd_push_constants2_khr)(self.handle(), &push_constants_info_vk);
+
+        self
+    }
+
     #[inline]
     pub unsafe fn push_descriptor_set(
         &mut self,
@@ -1309,8 +1911,8 @@ impl RawRecordingCommandBuffer {
 
         let set_layout = &pipeline_layout.set_layouts()[set_num as usize];
 
-        struct PerDescriptorWrite {
-            write_info: DescriptorWriteInfo,
+        struct PerDescriptorWrite<'a> {
+            write_info: DescriptorWriteInfo<'a>,
             acceleration_structures: ash::vk::WriteDescriptorSetAccelerationStructureKHR<'static>,
             inline_uniform_block: ash::vk::WriteDescriptorSetInlineUniformBlock<'static>,
         }
@@ -1380,3 +1982,46 @@ impl RawRecordingCommandBuffer {
         self
     }
 }
+
+/// Returns the pipeline bind points that the shader stages in `stage_flags` belong to.
+fn pipeline_bind_points(stage_flags: ShaderStages) -> impl Iterator<Item = PipelineBindPoint> {
+    let graphics_stages = ShaderStages::all_graphics() | ShaderStages::TASK | ShaderStages::MESH;
+
+    [
+        (PipelineBindPoint::Compute, ShaderStages::COMPUTE),
+        (PipelineBindPoint::Graphics, graphics_stages),
+    ]
+    .into_iter()
+    .filter(move |&(_, stages)| stage_flags.intersects(stages))
+    .map(|(pipeline_bind_point, _)| pipeline_bind_point)
+}
+
+fn validate_stage_flags(
+    device: &Device,
+    stage_flags: ShaderStages,
+) -> Result<(), Box<ValidationError>> {
+    stage_flags.validate_device(device)?;
+
+    if stage_flags.is_empty() {
+        return Err(Box::new(ValidationError {
+            problem: "is empty".into(),
+            ..Default::default()
+        }));
+    }
+
+    let supported_stages = ShaderStages::all_graphics()
+        | ShaderStages::TASK
+        | ShaderStages::MESH
+        | ShaderStages::COMPUTE;
+
+    if !supported_stages.contains(stage_flags) {
+        return Err(Box::new(ValidationError {
+            problem: "contains stages that don't belong to the compute or graphics pipeline bind \
+                point"
+                .into(),
+            ..Default::default()
+        }));
+    }
+
+    Ok(())
+}
@@ -20,7 +20,9 @@ impl RecordingCommandBuffer {
         &mut self,
         clear_info: ClearColorImageInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_clear_color_image(&clear_info)?;
+        if self.device().validation_enabled() {
+            self.validate_clear_color_image(&clear_info)?;
+        }
 
         unsafe { Ok(self.clear_color_image_unchecked(clear_info)) }
     }
@@ -86,7 +88,9 @@ impl RecordingCommandBuffer {
         &mut self,
         clear_info: ClearDepthStencilImageInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_clear_depth_stencil_image(&clear_info)?;
+        if self.device().validation_enabled() {
+            self.validate_clear_depth_stencil_image(&clear_info)?;
+        }
 
         unsafe { Ok(self.clear_depth_stencil_image_unchecked(clear_info)) }
     }
@@ -156,7 +160,9 @@ impl RecordingCommandBuffer {
         dst_buffer: Subbuffer<[u32]>,
         data: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_fill_buffer(&dst_buffer, data)?;
+        if self.device().validation_enabled() {
+            self.validate_fill_buffer(&dst_buffer, data)?;
+        }
 
         unsafe { Ok(self.fill_buffer_unchecked(dst_buffer, data)) }
     }
@@ -215,10 +221,12 @@ impl RecordingCommandBuffer {
         D: BufferContents + ?Sized,
         Dd: SafeDeref<Target = D> + Send + Sync + 'static,
     {
-        self.validate_update_buffer(
-            dst_buffer.as_bytes(),
-            size_of_val(data.deref()) as DeviceSize,
-        )?;
+        if self.device().validation_enabled() {
+            self.validate_update_buffer(
+                dst_buffer.as_bytes(),
+                size_of_val(data.deref()) as DeviceSize,
+            )?;
+        }
 
         unsafe { Ok(self.update_buffer_unchecked(dst_buffer, data)) }
     }
@@ -0,0 +1,367 @@
+use crate::{
+    buffer::{BufferUsage, Subbuffer},
+    command_buffer::{
+        auto::{ConditionalRenderingState, RenderPassStateType, Resource},
+        sys::RawRecordingCommandBuffer,
+        RecordingCommandBuffer, ResourceInCommand,
+    },
+    device::{Device, DeviceOwned, QueueFlags},
+    macros::vulkan_bitflags,
+    sync::PipelineStageAccessFlags,
+    DeviceSize, Requires, RequiresAllOf, RequiresOneOf, ValidationError, VulkanObject,
+};
+use std::mem::size_of;
+
+/// # Commands related to conditional rendering.
+impl RecordingCommandBuffer {
+    /// Begins conditional rendering.
+    ///
+    /// While conditional rendering is active, draw, dispatch and clear attachment commands are
+    /// discarded if the 32-bit value in `conditional_rendering_begin_info.buffer` is zero, or
+    /// non-zero if [`ConditionalRenderingFlags::INVERTED`] is set. The value is read by the
+    /// device when the commands execute, so it can be written by previous commands in the same
+    /// submission.
+    ///
+    /// Conditional rendering is active until
+    /// [`end_conditional_rendering`](Self::end_conditional_rendering) is called.
+    ///
+    /// The [`conditional_rendering`] feature must be enabled on the device.
+    ///
+    /// [`conditional_rendering`]: crate::device::DeviceFeatures::conditional_rendering
+    pub fn begin_conditional_rendering(
+        &mut self,
+        conditional_rendering_begin_info: ConditionalRenderingBeginInfo,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_begin_conditional_rendering(&conditional_rendering_begin_info)?;
+        }
+
+        unsafe { Ok(self.begin_conditional_rendering_unchecked(conditional_rendering_begin_info)) }
+    }
+
+    fn validate_begin_conditional_rendering(
+        &self,
+        conditional_rendering_begin_info: &ConditionalRenderingBeginInfo,
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner
+            .validate_begin_conditional_rendering(conditional_rendering_begin_info)?;
+
+        if self.builder_state.conditional_rendering.is_some() {
+            return Err(Box::new(ValidationError {
+                problem: "conditional rendering is already active".into(),
+                vuids: &["VUID-vkCmdBeginConditionalRenderingEXT-None-01980"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn begin_conditional_rendering_unchecked(
+        &mut self,
+        conditional_rendering_begin_info: ConditionalRenderingBeginInfo,
+    ) -> &mut Self {
+        self.builder_state.conditional_rendering = Some(ConditionalRenderingState {
+            subpass_index: self.current_subpass_index(),
+        });
+
+        let buffer = &conditional_rendering_begin_info.buffer;
+
+        self.add_command(
+            "begin_conditional_rendering",
+            [(
+                ResourceInCommand::ConditionalRenderingBuffer.into(),
+                Resource::Buffer {
+                    buffer: buffer.as_bytes().clone(),
+                    range: 0..buffer.size(),
+                    memory_access:
+                        PipelineStageAccessFlags::ConditionalRendering_ConditionalRenderingRead,
+                },
+            )]
+            .into_iter()
+            .collect(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.begin_conditional_rendering_unchecked(&conditional_rendering_begin_info);
+            },
+        );
+
+        self
+    }
+
+    /// Ends conditional rendering.
+    pub fn end_conditional_rendering(&mut self) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_end_conditional_rendering()?;
+        }
+
+        unsafe { Ok(self.end_conditional_rendering_unchecked()) }
+    }
+
+    fn validate_end_conditional_rendering(&self) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_end_conditional_rendering()?;
+
+        let state = self
+            .builder_state
+            .conditional_rendering
+            .as_ref()
+            .ok_or_else(|| {
+                Box::new(ValidationError {
+                    problem: "conditional rendering is not active".into(),
+                    vuids: &["VUID-vkCmdEndConditionalRenderingEXT-None-01985"],
+                    ..Default::default()
+                })
+            })?;
+
+        match (state.subpass_index, self.current_subpass_index()) {
+            (Some(begin_index), current_index) if current_index != Some(begin_index) => {
+                return Err(Box::new(ValidationError {
+                    problem: "conditional rendering was made active inside a subpass, but \
+                        the subpass has ended"
+                        .into(),
+                    vuids: &["VUID-vkCmdEndConditionalRenderingEXT-None-01987"],
+                    ..Default::default()
+                }));
+            }
+            (None, Some(_)) => {
+                return Err(Box::new(ValidationError {
+                    problem: "conditional rendering was made active outside a render pass \
+                        instance, but a render pass instance is currently active"
+                        .into(),
+                    vuids: &["VUID-vkCmdEndConditionalRenderingEXT-None-01986"],
+                    ..Default::default()
+                }));
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    // Returns the index of the current subpass, or `None` if no render pass instance is active.
+    // A render pass instance begun with `begin_rendering` has a single subpass.
+    fn current_subpass_index(&self) -> Option<u32> {
+        self.builder_state
+            .render_pass
+            .as_ref()
+            .map(|render_pass_state| match &render_pass_state.render_pass {
+                RenderPassStateType::BeginRenderPass(state) => state.subpass.index(),
+                RenderPassStateType::BeginRendering(_) => 0,
+            })
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn end_conditional_rendering_unchecked(&mut self) -> &mut Self {
+        self.builder_state.conditional_rendering = None;
+
+        self.add_command(
+            "end_conditional_rendering",
+            Default::default(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.end_conditional_rendering_unchecked();
+            },
+        );
+
+        self
+    }
+}
+
+impl RawRecordingCommandBuffer {
+    #[inline]
+    pub unsafe fn begin_conditional_rendering(
+        &mut self,
+        conditional_rendering_begin_info: &ConditionalRenderingBeginInfo,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        self.validate_begin_conditional_rendering(conditional_rendering_begin_info)?;
+
+        Ok(self.begin_conditional_rendering_unchecked(conditional_rendering_begin_info))
+    }
+
+    fn validate_begin_conditional_rendering(
+        &self,
+        conditional_rendering_begin_info: &ConditionalRenderingBeginInfo,
+    ) -> Result<(), Box<ValidationError>> {
+        let device = self.device();
+
+        if !device.enabled_features().conditional_rendering {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "conditional_rendering",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        if !self
+            .queue_family_properties()
+            .queue_flags
+            .intersects(QueueFlags::GRAPHICS | QueueFlags::COMPUTE)
+        {
+            return Err(Box::new(ValidationError {
+                problem: "the queue family of the command buffer does not support \
+                    graphics or compute operations"
+                    .into(),
+                vuids: &["VUID-vkCmdBeginConditionalRenderingEXT-commandBuffer-cmdpool"],
+                ..Default::default()
+            }));
+        }
+
+        conditional_rendering_begin_info
+            .validate(device)
+            .map_err(|err| err.add_context("conditional_rendering_begin_info"))?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn begin_conditional_rendering_unchecked(
+        &mut self,
+        conditional_rendering_begin_info: &ConditionalRenderingBeginInfo,
+    ) -> &mut Self {
+        let &ConditionalRenderingBeginInfo {
+            ref buffer,
+            flags,
+            _ne: _,
+        } = conditional_rendering_begin_info;
+
+        let conditional_rendering_begin_info_vk = ash::vk::ConditionalRenderingBeginInfoEXT {
+            buffer: buffer.buffer().handle(),
+            offset: buffer.offset(),
+            flags: flags.into(),
+            ..Default::default()
+        };
+
+        let fns = self.device().fns();
+        (fns.ext_conditional_rendering
+            .cmd_begin_conditional_rendering_ext)(
+            self.handle(),
+            &conditional_rendering_begin_info_vk,
+        );
+
+        self
+    }
+
+    #[inline]
+    pub unsafe fn end_conditional_rendering(&mut self) -> Result<&mut Self, Box<ValidationError>> {
+        self.validate_end_conditional_rendering()?;
+
+        Ok(self.end_conditional_rendering_unchecked())
+    }
+
+    fn validate_end_conditional_rendering(&self) -> Result<(), Box<ValidationError>> {
+        if !self.device().enabled_features().conditional_rendering {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "conditional_rendering",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        if !self
+            .queue_family_properties()
+            .queue_flags
+            .intersects(QueueFlags::GRAPHICS | QueueFlags::COMPUTE)
+        {
+            return Err(Box::new(ValidationError {
+                problem: "the queue family of the command buffer does not support \
+                    graphics or compute operations"
+                    .into(),
+                vuids: &["VUID-vkCmdEndConditionalRenderingEXT-commandBuffer-cmdpool"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn end_conditional_rendering_unchecked(&mut self) -> &mut Self {
+        let fns = self.device().fns();
+        (fns.ext_conditional_rendering
+            .cmd_end_conditional_rendering_ext)(self.handle());
+
+        self
+    }
+}
+
+/// Parameters to begin conditional rendering.
+#[derive(Clone, Debug)]
+pub struct ConditionalRenderingBeginInfo {
+    /// The buffer containing the 32-bit predicate value.
+    ///
+    /// There is no default value.
+    pub buffer: Subbuffer<u32>,
+
+    /// Additional properties of conditional rendering.
+    ///
+    /// The default value is empty.
+    pub flags: ConditionalRenderingFlags,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl ConditionalRenderingBeginInfo {
+    /// Returns a `ConditionalRenderingBeginInfo` with the specified `buffer`.
+    #[inline]
+    pub fn buffer(buffer: Subbuffer<u32>) -> Self {
+        Self {
+            buffer,
+            flags: ConditionalRenderingFlags::empty(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+
+    pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
+        let &Self {
+            ref buffer,
+            flags,
+            _ne: _,
+        } = self;
+
+        // VUID-VkConditionalRenderingBeginInfoEXT-buffer-parameter
+        assert_eq!(device, buffer.device().as_ref());
+
+        flags.validate_device(device).map_err(|err| {
+            err.add_context("flags")
+                .set_vuids(&["VUID-VkConditionalRenderingBeginInfoEXT-flags-parameter"])
+        })?;
+
+        if !buffer
+            .buffer()
+            .usage()
+            .intersects(BufferUsage::CONDITIONAL_RENDERING)
+        {
+            return Err(Box::new(ValidationError {
+                context: "buffer.buffer().usage()".into(),
+                problem: "does not contain `BufferUsage::CONDITIONAL_RENDERING`".into(),
+                vuids: &["VUID-VkConditionalRenderingBeginInfoEXT-buffer-01982"],
+                ..Default::default()
+            }));
+        }
+
+        if buffer.offset() % size_of::<u32>() as DeviceSize != 0 {
+            return Err(Box::new(ValidationError {
+                context: "buffer".into(),
+                problem: "the offset is not a multiple of 4".into(),
+                vuids: &["VUID-VkConditionalRenderingBeginInfoEXT-offset-01984"],
+                ..Default::default()
+            }));
+        }
+
+        // VUID-VkConditionalRenderingBeginInfoEXT-offset-01983
+        // Ensured by the `Subbuffer` type.
+
+        Ok(())
+    }
+}
+
+vulkan_bitflags! {
+    #[non_exhaustive]
+
+    /// Flags specifying additional properties of conditional rendering.
+    ConditionalRenderingFlags = ConditionalRenderingFlagsEXT(u32);
+
+    /// Commands are discarded if the predicate value is non-zero, instead of if it is zero.
+    INVERTED = INVERTED,
+}
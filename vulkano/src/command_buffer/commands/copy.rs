@@ -31,7 +31,9 @@ impl RecordingCommandBuffer {
         copy_buffer_info: impl Into<CopyBufferInfo>,
     ) -> Result<&mut Self, Box<ValidationError>> {
         let copy_buffer_info = copy_buffer_info.into();
-        self.validate_copy_buffer(&copy_buffer_info)?;
+        if self.device().validation_enabled() {
+            self.validate_copy_buffer(&copy_buffer_info)?;
+        }
 
         unsafe { Ok(self.copy_buffer_unchecked(copy_buffer_info)) }
     }
@@ -129,7 +131,9 @@ impl RecordingCommandBuffer {
         &mut self,
         copy_image_info: CopyImageInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_copy_image(&copy_image_info)?;
+        if self.device().validation_enabled() {
+            self.validate_copy_image(&copy_image_info)?;
+        }
 
         unsafe { Ok(self.copy_image_unchecked(copy_image_info)) }
     }
@@ -213,7 +217,9 @@ impl RecordingCommandBuffer {
         &mut self,
         copy_buffer_to_image_info: CopyBufferToImageInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_copy_buffer_to_image(&copy_buffer_to_image_info)?;
+        if self.device().validation_enabled() {
+            self.validate_copy_buffer_to_image(&copy_buffer_to_image_info)?;
+        }
 
         unsafe { Ok(self.copy_buffer_to_image_unchecked(copy_buffer_to_image_info)) }
     }
@@ -300,7 +306,9 @@ impl RecordingCommandBuffer {
         &mut self,
         copy_image_to_buffer_info: CopyImageToBufferInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_copy_image_to_buffer(&copy_image_to_buffer_info)?;
+        if self.device().validation_enabled() {
+            self.validate_copy_image_to_buffer(&copy_image_to_buffer_info)?;
+        }
 
         unsafe { Ok(self.copy_image_to_buffer_unchecked(copy_image_to_buffer_info)) }
     }
@@ -416,7 +424,9 @@ impl RecordingCommandBuffer {
         &mut self,
         blit_image_info: BlitImageInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_blit_image(&blit_image_info)?;
+        if self.device().validation_enabled() {
+            self.validate_blit_image(&blit_image_info)?;
+        }
 
         unsafe { Ok(self.blit_image_unchecked(blit_image_info)) }
     }
@@ -504,7 +514,9 @@ impl RecordingCommandBuffer {
         &mut self,
         resolve_image_info: ResolveImageInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_resolve_image(&resolve_image_info)?;
+        if self.device().validation_enabled() {
+            self.validate_resolve_image(&resolve_image_info)?;
+        }
 
         unsafe { Ok(self.resolve_image_unchecked(resolve_image_info)) }
     }
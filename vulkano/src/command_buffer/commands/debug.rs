@@ -1,14 +1,19 @@
 use crate::{
-    command_buffer::{sys::RawRecordingCommandBuffer, RecordingCommandBuffer},
+    buffer::{BufferUsage, Subbuffer},
+    command_buffer::{
+        auto::Resource, sys::RawRecordingCommandBuffer, RecordingCommandBuffer, ResourceInCommand,
+    },
     device::{DeviceOwned, QueueFlags},
     instance::debug::DebugUtilsLabel,
-    Requires, RequiresAllOf, RequiresOneOf, ValidationError, VulkanObject,
+    sync::{PipelineStage, PipelineStageAccessFlags, PipelineStages},
+    DeviceSize, Requires, RequiresAllOf, RequiresOneOf, ValidationError, VulkanObject,
 };
-use std::ffi::CString;
+use std::{ffi::CString, mem::size_of, panic::Location};
 
 /// # Commands for debugging.
 ///
-/// These commands all require the [`ext_debug_utils`] extension to be enabled on the instance.
+/// The debug label commands require the [`ext_debug_utils`] extension to be enabled on the
+/// instance.
 ///
 /// [`ext_debug_utils`]: crate::instance::InstanceExtensions::ext_debug_utils
 impl RecordingCommandBuffer {
@@ -17,7 +22,9 @@ impl RecordingCommandBuffer {
         &mut self,
         label_info: DebugUtilsLabel,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_begin_debug_utils_label(&label_info)?;
+        if self.device().validation_enabled() {
+            self.validate_begin_debug_utils_label(&label_info)?;
+        }
 
         unsafe { Ok(self.begin_debug_utils_label_unchecked(label_info)) }
     }
@@ -55,7 +62,9 @@ impl RecordingCommandBuffer {
     ///   region begun with `begin_debug_utils_label` in the queue, either within this command
     ///   buffer or a previously submitted one.
     pub unsafe fn end_debug_utils_label(&mut self) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_end_debug_utils_label()?;
+        if self.device().validation_enabled() {
+            self.validate_end_debug_utils_label()?;
+        }
 
         Ok(self.end_debug_utils_label_unchecked())
     }
@@ -83,12 +92,35 @@ impl RecordingCommandBuffer {
         self
     }
 
+    /// Opens a debug label region named after `kind` and the location in the user's code, if
+    /// [`DeviceCreateInfo::auto_debug_names`] is enabled.
+    ///
+    /// [`DeviceCreateInfo::auto_debug_names`]: crate::device::DeviceCreateInfo::auto_debug_names
+    #[track_caller]
+    pub(in crate::command_buffer) unsafe fn begin_auto_debug_utils_label(&mut self, kind: &str) {
+        if self.device().auto_debug_names() {
+            self.begin_debug_utils_label_unchecked(DebugUtilsLabel {
+                label_name: format!("{} ({})", kind, Location::caller()),
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Closes a debug label region opened with `begin_auto_debug_utils_label`.
+    pub(in crate::command_buffer) unsafe fn end_auto_debug_utils_label(&mut self) {
+        if self.device().auto_debug_names() {
+            self.end_debug_utils_label_unchecked();
+        }
+    }
+
     /// Inserts a command buffer debug label.
     pub fn insert_debug_utils_label(
         &mut self,
         label_info: DebugUtilsLabel,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_insert_debug_utils_label(&label_info)?;
+        if self.device().validation_enabled() {
+            self.validate_insert_debug_utils_label(&label_info)?;
+        }
 
         unsafe { Ok(self.insert_debug_utils_label_unchecked(label_info)) }
     }
@@ -117,6 +149,108 @@ impl RecordingCommandBuffer {
 
         self
     }
+
+    /// Sets a checkpoint with the given name.
+    ///
+    /// When the device is lost, [`Queue::checkpoint_data`] can be used to find out which
+    /// checkpoints were the last ones to be reached before the device was lost, which narrows
+    /// down the commands that caused it.
+    ///
+    /// The [`nv_device_diagnostic_checkpoints`] extension must be enabled on the device.
+    ///
+    /// [`Queue::checkpoint_data`]: crate::device::Queue::checkpoint_data
+    /// [`nv_device_diagnostic_checkpoints`]: crate::device::DeviceExtensions::nv_device_diagnostic_checkpoints
+    pub fn set_checkpoint(&mut self, name: &str) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_set_checkpoint(name)?;
+        }
+
+        unsafe { Ok(self.set_checkpoint_unchecked(name)) }
+    }
+
+    fn validate_set_checkpoint(&self, name: &str) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_set_checkpoint(name)?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_checkpoint_unchecked(&mut self, name: &str) -> &mut Self {
+        let name = name.to_owned();
+
+        self.add_command(
+            "set_checkpoint",
+            Default::default(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.set_checkpoint_unchecked(&name);
+            },
+        );
+
+        self
+    }
+
+    /// Writes `marker` to `dst_buffer` once all the previously submitted commands have
+    /// completed `stage`.
+    ///
+    /// If `dst_buffer` is in host-visible memory, markers that were written before the device was
+    /// lost can usually still be read afterwards. By writing increasing markers at different
+    /// stages and reading them back after the device was lost, the commands that caused it can be
+    /// narrowed down.
+    ///
+    /// The [`amd_buffer_marker`] extension must be enabled on the device.
+    ///
+    /// [`amd_buffer_marker`]: crate::device::DeviceExtensions::amd_buffer_marker
+    pub fn write_buffer_marker(
+        &mut self,
+        stage: PipelineStage,
+        dst_buffer: Subbuffer<u32>,
+        marker: u32,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_write_buffer_marker(stage, &dst_buffer, marker)?;
+        }
+
+        unsafe { Ok(self.write_buffer_marker_unchecked(stage, dst_buffer, marker)) }
+    }
+
+    fn validate_write_buffer_marker(
+        &self,
+        stage: PipelineStage,
+        dst_buffer: &Subbuffer<u32>,
+        marker: u32,
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner
+            .validate_write_buffer_marker(stage, dst_buffer, marker)?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn write_buffer_marker_unchecked(
+        &mut self,
+        stage: PipelineStage,
+        dst_buffer: Subbuffer<u32>,
+        marker: u32,
+    ) -> &mut Self {
+        self.add_command(
+            "write_buffer_marker",
+            [(
+                ResourceInCommand::Destination.into(),
+                Resource::Buffer {
+                    buffer: dst_buffer.as_bytes().clone(),
+                    range: 0..dst_buffer.size(),
+                    memory_access: PipelineStageAccessFlags::Copy_TransferWrite,
+                },
+            )]
+            .into_iter()
+            .collect(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.write_buffer_marker_unchecked(stage, &dst_buffer, marker);
+            },
+        );
+
+        self
+    }
 }
 
 impl RawRecordingCommandBuffer {
@@ -304,4 +438,166 @@ impl RawRecordingCommandBuffer {
 
         self
     }
+
+    #[inline]
+    pub unsafe fn set_checkpoint(&mut self, name: &str) -> Result<&mut Self, Box<ValidationError>> {
+        self.validate_set_checkpoint(name)?;
+
+        Ok(self.set_checkpoint_unchecked(name))
+    }
+
+    fn validate_set_checkpoint(&self, _name: &str) -> Result<(), Box<ValidationError>> {
+        if !self
+            .device()
+            .enabled_extensions()
+            .nv_device_diagnostic_checkpoints
+        {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "nv_device_diagnostic_checkpoints",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        if !self
+            .queue_family_properties()
+            .queue_flags
+            .intersects(QueueFlags::TRANSFER | QueueFlags::GRAPHICS | QueueFlags::COMPUTE)
+        {
+            return Err(Box::new(ValidationError {
+                problem: "the queue family of the command buffer does not support \
+                    transfer, graphics or compute operations"
+                    .into(),
+                vuids: &["VUID-vkCmdSetCheckpointNV-commandBuffer-cmdpool"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_checkpoint_unchecked(&mut self, name: &str) -> &mut Self {
+        let marker = self.device().checkpoint_names().marker(name);
+
+        let fns = self.device().fns();
+        (fns.nv_device_diagnostic_checkpoints.cmd_set_checkpoint_nv)(self.handle(), marker);
+
+        self
+    }
+
+    #[inline]
+    pub unsafe fn write_buffer_marker(
+        &mut self,
+        stage: PipelineStage,
+        dst_buffer: &Subbuffer<u32>,
+        marker: u32,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        self.validate_write_buffer_marker(stage, dst_buffer, marker)?;
+
+        Ok(self.write_buffer_marker_unchecked(stage, dst_buffer, marker))
+    }
+
+    fn validate_write_buffer_marker(
+        &self,
+        stage: PipelineStage,
+        dst_buffer: &Subbuffer<u32>,
+        _marker: u32,
+    ) -> Result<(), Box<ValidationError>> {
+        let device = self.device();
+
+        if !device.enabled_extensions().amd_buffer_marker {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "amd_buffer_marker",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        let queue_family_properties = self.queue_family_properties();
+
+        if !queue_family_properties
+            .queue_flags
+            .intersects(QueueFlags::TRANSFER | QueueFlags::GRAPHICS | QueueFlags::COMPUTE)
+        {
+            return Err(Box::new(ValidationError {
+                problem: "the queue family of the command buffer does not support \
+                    transfer, graphics or compute operations"
+                    .into(),
+                vuids: &["VUID-vkCmdWriteBufferMarkerAMD-commandBuffer-cmdpool"],
+                ..Default::default()
+            }));
+        }
+
+        stage.validate_device(device).map_err(|err| {
+            err.add_context("stage")
+                .set_vuids(&["VUID-vkCmdWriteBufferMarkerAMD-pipelineStage-parameter"])
+        })?;
+
+        if PipelineStages::from(stage).contains_flags2() {
+            return Err(Box::new(ValidationError {
+                context: "stage".into(),
+                problem: "is a stage flag from `VkPipelineStageFlagBits2`".into(),
+                vuids: &["VUID-vkCmdWriteBufferMarkerAMD-pipelineStage-parameter"],
+                ..Default::default()
+            }));
+        }
+
+        if !PipelineStages::from(queue_family_properties.queue_flags).contains_enum(stage) {
+            return Err(Box::new(ValidationError {
+                context: "stage".into(),
+                problem: "is not supported by the queue family of the command buffer".into(),
+                vuids: &["VUID-vkCmdWriteBufferMarkerAMD-pipelineStage-04074"],
+                ..Default::default()
+            }));
+        }
+
+        // VUID-vkCmdWriteBufferMarkerAMD-commonparent
+        assert_eq!(device, dst_buffer.device());
+
+        if dst_buffer.offset() % size_of::<u32>() as DeviceSize != 0 {
+            return Err(Box::new(ValidationError {
+                context: "dst_buffer".into(),
+                problem: "the offset is not a multiple of 4".into(),
+                vuids: &["VUID-vkCmdWriteBufferMarkerAMD-dstOffset-01800"],
+                ..Default::default()
+            }));
+        }
+
+        if !dst_buffer
+            .buffer()
+            .usage()
+            .intersects(BufferUsage::TRANSFER_DST)
+        {
+            return Err(Box::new(ValidationError {
+                context: "dst_buffer.buffer().usage()".into(),
+                problem: "does not contain `BufferUsage::TRANSFER_DST`".into(),
+                vuids: &["VUID-vkCmdWriteBufferMarkerAMD-dstBuffer-01799"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn write_buffer_marker_unchecked(
+        &mut self,
+        stage: PipelineStage,
+        dst_buffer: &Subbuffer<u32>,
+        marker: u32,
+    ) -> &mut Self {
+        let fns = self.device().fns();
+        (fns.amd_buffer_marker.cmd_write_buffer_marker_amd)(
+            self.handle(),
+            stage.into(),
+            dst_buffer.buffer().handle(),
+            dst_buffer.offset(),
+            marker,
+        );
+
+        self
+    }
 }
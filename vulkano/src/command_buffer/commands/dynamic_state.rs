@@ -6,8 +6,11 @@ use crate::{
             color_blend::LogicOp,
             conservative_rasterization::ConservativeRasterizationMode,
             depth_stencil::{CompareOp, StencilFaces, StencilOp, StencilOps},
+            discard_rectangle::DiscardRectangleMode,
             input_assembly::PrimitiveTopology,
-            rasterization::{CullMode, DepthBiasState, FrontFace, LineStipple},
+            rasterization::{
+                CullMode, DepthBiasState, FrontFace, LineRasterizationMode, LineStipple,
+            },
             vertex_input::{
                 VertexInputAttributeDescription, VertexInputBindingDescription, VertexInputRate,
                 VertexInputState,
@@ -53,7 +56,9 @@ impl RecordingCommandBuffer {
         &mut self,
         constants: [f32; 4],
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_blend_constants(constants)?;
+        if self.device().validation_enabled() {
+            self.validate_set_blend_constants(constants)?;
+        }
 
         unsafe { Ok(self.set_blend_constants_unchecked(constants)) }
     }
@@ -85,11 +90,17 @@ impl RecordingCommandBuffer {
 
     /// Sets whether dynamic color writes should be enabled for each attachment in the
     /// framebuffer.
+    ///
+    /// At draw time, `enables` must contain at least as many values as there are attachments in
+    /// the color blend state of the bound graphics pipeline. Extra values are ignored, so the same
+    /// state can be shared between pipelines with different numbers of attachments.
     pub fn set_color_write_enable(
         &mut self,
         enables: SmallVec<[bool; 4]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_color_write_enable(&enables)?;
+        if self.device().validation_enabled() {
+            self.validate_set_color_write_enable(&enables)?;
+        }
 
         unsafe { Ok(self.set_color_write_enable_unchecked(enables)) }
     }
@@ -102,24 +113,6 @@ impl RecordingCommandBuffer {
 
         self.validate_graphics_pipeline_fixed_state(DynamicState::ColorWriteEnable)?;
 
-        if let Some(color_blend_state) = self
-            .builder_state
-            .pipeline_graphics
-            .as_ref()
-            .and_then(|pipeline| pipeline.color_blend_state())
-        {
-            // Indirectly checked
-            if enables.len() != color_blend_state.attachments.len() {
-                return Err(Box::new(ValidationError {
-                    problem: "the length of `enables` does not match the number of \
-                        color attachments in the subpass of the currently bound graphics pipeline"
-                        .into(),
-                    vuids: &["VUID-vkCmdSetColorWriteEnableEXT-attachmentCount-06656"],
-                    ..Default::default()
-                }));
-            }
-        }
-
         Ok(())
     }
 
@@ -145,7 +138,9 @@ impl RecordingCommandBuffer {
         &mut self,
         cull_mode: CullMode,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_cull_mode(cull_mode)?;
+        if self.device().validation_enabled() {
+            self.validate_set_cull_mode(cull_mode)?;
+        }
 
         unsafe { Ok(self.set_cull_mode_unchecked(cull_mode)) }
     }
@@ -179,7 +174,9 @@ impl RecordingCommandBuffer {
         clamp: f32,
         slope_factor: f32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_depth_bias(constant_factor, clamp, slope_factor)?;
+        if self.device().validation_enabled() {
+            self.validate_set_depth_bias(constant_factor, clamp, slope_factor)?;
+        }
 
         unsafe { Ok(self.set_depth_bias_unchecked(constant_factor, clamp, slope_factor)) }
     }
@@ -226,7 +223,9 @@ impl RecordingCommandBuffer {
         &mut self,
         enable: bool,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_depth_bias_enable(enable)?;
+        if self.device().validation_enabled() {
+            self.validate_set_depth_bias_enable(enable)?;
+        }
 
         unsafe { Ok(self.set_depth_bias_enable_unchecked(enable)) }
     }
@@ -258,7 +257,9 @@ impl RecordingCommandBuffer {
         &mut self,
         bounds: RangeInclusive<f32>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_depth_bounds(bounds.clone())?;
+        if self.device().validation_enabled() {
+            self.validate_set_depth_bounds(bounds.clone())?;
+        }
 
         unsafe { Ok(self.set_depth_bounds_unchecked(bounds)) }
     }
@@ -293,7 +294,9 @@ impl RecordingCommandBuffer {
         &mut self,
         enable: bool,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_depth_bounds_test_enable(enable)?;
+        if self.device().validation_enabled() {
+            self.validate_set_depth_bounds_test_enable(enable)?;
+        }
 
         unsafe { Ok(self.set_depth_bounds_test_enable_unchecked(enable)) }
     }
@@ -328,7 +331,9 @@ impl RecordingCommandBuffer {
         &mut self,
         compare_op: CompareOp,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_depth_compare_op(compare_op)?;
+        if self.device().validation_enabled() {
+            self.validate_set_depth_compare_op(compare_op)?;
+        }
 
         unsafe { Ok(self.set_depth_compare_op_unchecked(compare_op)) }
     }
@@ -363,7 +368,9 @@ impl RecordingCommandBuffer {
         &mut self,
         enable: bool,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_depth_test_enable(enable)?;
+        if self.device().validation_enabled() {
+            self.validate_set_depth_test_enable(enable)?;
+        }
 
         unsafe { Ok(self.set_depth_test_enable_unchecked(enable)) }
     }
@@ -395,7 +402,9 @@ impl RecordingCommandBuffer {
         &mut self,
         enable: bool,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_depth_write_enable(enable)?;
+        if self.device().validation_enabled() {
+            self.validate_set_depth_write_enable(enable)?;
+        }
 
         unsafe { Ok(self.set_depth_write_enable_unchecked(enable)) }
     }
@@ -428,7 +437,9 @@ impl RecordingCommandBuffer {
         first_rectangle: u32,
         rectangles: SmallVec<[Scissor; 2]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_discard_rectangle(first_rectangle, &rectangles)?;
+        if self.device().validation_enabled() {
+            self.validate_set_discard_rectangle(first_rectangle, &rectangles)?;
+        }
 
         unsafe { Ok(self.set_discard_rectangle_unchecked(first_rectangle, rectangles)) }
     }
@@ -468,9 +479,88 @@ impl RecordingCommandBuffer {
         self
     }
 
+    /// Sets whether the discard rectangle test is enabled for future draw calls.
+    pub fn set_discard_rectangle_enable(
+        &mut self,
+        enable: bool,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_set_discard_rectangle_enable(enable)?;
+        }
+
+        unsafe { Ok(self.set_discard_rectangle_enable_unchecked(enable)) }
+    }
+
+    fn validate_set_discard_rectangle_enable(
+        &self,
+        enable: bool,
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_set_discard_rectangle_enable(enable)?;
+
+        self.validate_graphics_pipeline_fixed_state(DynamicState::DiscardRectangleEnable)?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_discard_rectangle_enable_unchecked(&mut self, enable: bool) -> &mut Self {
+        self.builder_state.discard_rectangle_enable = Some(enable);
+        self.add_command(
+            "set_discard_rectangle_enable",
+            Default::default(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.set_discard_rectangle_enable_unchecked(enable);
+            },
+        );
+
+        self
+    }
+
+    /// Sets the dynamic discard rectangle mode for future draw calls.
+    pub fn set_discard_rectangle_mode(
+        &mut self,
+        mode: DiscardRectangleMode,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_set_discard_rectangle_mode(mode)?;
+        }
+
+        unsafe { Ok(self.set_discard_rectangle_mode_unchecked(mode)) }
+    }
+
+    fn validate_set_discard_rectangle_mode(
+        &self,
+        mode: DiscardRectangleMode,
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_set_discard_rectangle_mode(mode)?;
+
+        self.validate_graphics_pipeline_fixed_state(DynamicState::DiscardRectangleMode)?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_discard_rectangle_mode_unchecked(
+        &mut self,
+        mode: DiscardRectangleMode,
+    ) -> &mut Self {
+        self.builder_state.discard_rectangle_mode = Some(mode);
+        self.add_command(
+            "set_discard_rectangle_mode",
+            Default::default(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.set_discard_rectangle_mode_unchecked(mode);
+            },
+        );
+
+        self
+    }
+
     /// Sets the dynamic front face for future draw calls.
     pub fn set_front_face(&mut self, face: FrontFace) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_front_face(face)?;
+        if self.device().validation_enabled() {
+            self.validate_set_front_face(face)?;
+        }
 
         unsafe { Ok(self.set_front_face_unchecked(face)) }
     }
@@ -503,7 +593,9 @@ impl RecordingCommandBuffer {
         factor: u32,
         pattern: u16,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_line_stipple(factor, pattern)?;
+        if self.device().validation_enabled() {
+            self.validate_set_line_stipple(factor, pattern)?;
+        }
 
         unsafe { Ok(self.set_line_stipple_unchecked(factor, pattern)) }
     }
@@ -536,7 +628,9 @@ impl RecordingCommandBuffer {
 
     /// Sets the dynamic line width for future draw calls.
     pub fn set_line_width(&mut self, line_width: f32) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_line_width(line_width)?;
+        if self.device().validation_enabled() {
+            self.validate_set_line_width(line_width)?;
+        }
 
         unsafe { Ok(self.set_line_width_unchecked(line_width)) }
     }
@@ -565,7 +659,9 @@ impl RecordingCommandBuffer {
 
     /// Sets the dynamic logic op for future draw calls.
     pub fn set_logic_op(&mut self, logic_op: LogicOp) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_logic_op(logic_op)?;
+        if self.device().validation_enabled() {
+            self.validate_set_logic_op(logic_op)?;
+        }
 
         unsafe { Ok(self.set_logic_op_unchecked(logic_op)) }
     }
@@ -597,7 +693,9 @@ impl RecordingCommandBuffer {
         &mut self,
         num: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_patch_control_points(num)?;
+        if self.device().validation_enabled() {
+            self.validate_set_patch_control_points(num)?;
+        }
 
         unsafe { Ok(self.set_patch_control_points_unchecked(num)) }
     }
@@ -629,7 +727,9 @@ impl RecordingCommandBuffer {
         &mut self,
         enable: bool,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_primitive_restart_enable(enable)?;
+        if self.device().validation_enabled() {
+            self.validate_set_primitive_restart_enable(enable)?;
+        }
 
         unsafe { Ok(self.set_primitive_restart_enable_unchecked(enable)) }
     }
@@ -664,7 +764,9 @@ impl RecordingCommandBuffer {
         &mut self,
         topology: PrimitiveTopology,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_primitive_topology(topology)?;
+        if self.device().validation_enabled() {
+            self.validate_set_primitive_topology(topology)?;
+        }
 
         unsafe { Ok(self.set_primitive_topology_unchecked(topology)) }
     }
@@ -702,7 +804,9 @@ impl RecordingCommandBuffer {
         &mut self,
         enable: bool,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_rasterizer_discard_enable(enable)?;
+        if self.device().validation_enabled() {
+            self.validate_set_rasterizer_discard_enable(enable)?;
+        }
 
         unsafe { Ok(self.set_rasterizer_discard_enable_unchecked(enable)) }
     }
@@ -738,7 +842,9 @@ impl RecordingCommandBuffer {
         first_scissor: u32,
         scissors: SmallVec<[Scissor; 2]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_scissor(first_scissor, &scissors)?;
+        if self.device().validation_enabled() {
+            self.validate_set_scissor(first_scissor, &scissors)?;
+        }
 
         unsafe { Ok(self.set_scissor_unchecked(first_scissor, scissors)) }
     }
@@ -784,7 +890,9 @@ impl RecordingCommandBuffer {
         &mut self,
         scissors: SmallVec<[Scissor; 2]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_scissor_with_count(&scissors)?;
+        if self.device().validation_enabled() {
+            self.validate_set_scissor_with_count(&scissors)?;
+        }
 
         unsafe { Ok(self.set_scissor_with_count_unchecked(scissors)) }
     }
@@ -823,7 +931,9 @@ impl RecordingCommandBuffer {
         faces: StencilFaces,
         compare_mask: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_stencil_compare_mask(faces, compare_mask)?;
+        if self.device().validation_enabled() {
+            self.validate_set_stencil_compare_mask(faces, compare_mask)?;
+        }
 
         unsafe { Ok(self.set_stencil_compare_mask_unchecked(faces, compare_mask)) }
     }
@@ -877,7 +987,9 @@ impl RecordingCommandBuffer {
         depth_fail_op: StencilOp,
         compare_op: CompareOp,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_stencil_op(faces, fail_op, pass_op, depth_fail_op, compare_op)?;
+        if self.device().validation_enabled() {
+            self.validate_set_stencil_op(faces, fail_op, pass_op, depth_fail_op, compare_op)?;
+        }
 
         unsafe {
             Ok(self.set_stencil_op_unchecked(faces, fail_op, pass_op, depth_fail_op, compare_op))
@@ -946,7 +1058,9 @@ impl RecordingCommandBuffer {
         faces: StencilFaces,
         reference: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_stencil_reference(faces, reference)?;
+        if self.device().validation_enabled() {
+            self.validate_set_stencil_reference(faces, reference)?;
+        }
 
         unsafe { Ok(self.set_stencil_reference_unchecked(faces, reference)) }
     }
@@ -996,7 +1110,9 @@ impl RecordingCommandBuffer {
         &mut self,
         enable: bool,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_stencil_test_enable(enable)?;
+        if self.device().validation_enabled() {
+            self.validate_set_stencil_test_enable(enable)?;
+        }
 
         unsafe { Ok(self.set_stencil_test_enable_unchecked(enable)) }
     }
@@ -1029,7 +1145,9 @@ impl RecordingCommandBuffer {
         faces: StencilFaces,
         write_mask: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_stencil_write_mask(faces, write_mask)?;
+        if self.device().validation_enabled() {
+            self.validate_set_stencil_write_mask(faces, write_mask)?;
+        }
 
         unsafe { Ok(self.set_stencil_write_mask_unchecked(faces, write_mask)) }
     }
@@ -1080,7 +1198,9 @@ impl RecordingCommandBuffer {
         &mut self,
         vertex_input_state: VertexInputState,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_vertex_input(&vertex_input_state)?;
+        if self.device().validation_enabled() {
+            self.validate_set_vertex_input(&vertex_input_state)?;
+        }
 
         unsafe { Ok(self.set_vertex_input_unchecked(vertex_input_state)) }
     }
@@ -1120,7 +1240,9 @@ impl RecordingCommandBuffer {
         first_viewport: u32,
         viewports: SmallVec<[Viewport; 2]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_viewport(first_viewport, &viewports)?;
+        if self.device().validation_enabled() {
+            self.validate_set_viewport(first_viewport, &viewports)?;
+        }
 
         unsafe { Ok(self.set_viewport_unchecked(first_viewport, viewports)) }
     }
@@ -1165,7 +1287,9 @@ impl RecordingCommandBuffer {
         &mut self,
         viewports: SmallVec<[Viewport; 2]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_viewport_with_count(&viewports)?;
+        if self.device().validation_enabled() {
+            self.validate_set_viewport_with_count(&viewports)?;
+        }
 
         unsafe { Ok(self.set_viewport_with_count_unchecked(viewports)) }
     }
@@ -1204,7 +1328,9 @@ impl RecordingCommandBuffer {
         &mut self,
         conservative_rasterization_mode: ConservativeRasterizationMode,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_conservative_rasterization_mode()?;
+        if self.device().validation_enabled() {
+            self.validate_set_conservative_rasterization_mode()?;
+        }
 
         unsafe {
             Ok(self.set_conservative_rasterization_mode_unchecked(conservative_rasterization_mode))
@@ -1243,7 +1369,9 @@ impl RecordingCommandBuffer {
         &mut self,
         extra_primitive_overestimation_size: f32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_set_extra_primitive_overestimation_size()?;
+        if self.device().validation_enabled() {
+            self.validate_set_extra_primitive_overestimation_size()?;
+        }
 
         unsafe {
             Ok(self.set_extra_primitive_overestimation_size_unchecked(
@@ -1282,6 +1410,85 @@ impl RecordingCommandBuffer {
 
         self
     }
+
+    /// Sets the dynamic line rasterization mode for future draw calls.
+    #[inline]
+    pub fn set_line_rasterization_mode(
+        &mut self,
+        line_rasterization_mode: LineRasterizationMode,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_set_line_rasterization_mode(line_rasterization_mode)?;
+        }
+
+        unsafe { Ok(self.set_line_rasterization_mode_unchecked(line_rasterization_mode)) }
+    }
+
+    fn validate_set_line_rasterization_mode(
+        &self,
+        line_rasterization_mode: LineRasterizationMode,
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner
+            .validate_set_line_rasterization_mode(line_rasterization_mode)?;
+
+        self.validate_graphics_pipeline_fixed_state(DynamicState::LineRasterizationMode)?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_line_rasterization_mode_unchecked(
+        &mut self,
+        line_rasterization_mode: LineRasterizationMode,
+    ) -> &mut Self {
+        self.builder_state.line_rasterization_mode = Some(line_rasterization_mode);
+
+        self.add_command(
+            "set_line_rasterization_mode",
+            Default::default(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.set_line_rasterization_mode_unchecked(line_rasterization_mode);
+            },
+        );
+
+        self
+    }
+
+    /// Sets whether dynamic line stippling is enabled for future draw calls.
+    #[inline]
+    pub fn set_line_stipple_enable(
+        &mut self,
+        enable: bool,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_set_line_stipple_enable(enable)?;
+        }
+
+        unsafe { Ok(self.set_line_stipple_enable_unchecked(enable)) }
+    }
+
+    fn validate_set_line_stipple_enable(&self, enable: bool) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_set_line_stipple_enable(enable)?;
+
+        self.validate_graphics_pipeline_fixed_state(DynamicState::LineStippleEnable)?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_line_stipple_enable_unchecked(&mut self, enable: bool) -> &mut Self {
+        self.builder_state.line_stipple_enable = Some(enable);
+
+        self.add_command(
+            "set_line_stipple_enable",
+            Default::default(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.set_line_stipple_enable_unchecked(enable);
+            },
+        );
+
+        self
+    }
 }
 
 impl RawRecordingCommandBuffer {
@@ -1336,12 +1543,12 @@ impl RawRecordingCommandBuffer {
 
     fn validate_set_color_write_enable(
         &self,
-        _enables: &[bool],
+        enables: &[bool],
     ) -> Result<(), Box<ValidationError>> {
         if !self.device().enabled_features().color_write_enable {
             return Err(Box::new(ValidationError {
-                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
-                    "ext_color_write_enable",
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "color_write_enable",
                 )])]),
                 vuids: &["VUID-vkCmdSetColorWriteEnableEXT-None-04803"],
                 ..Default::default()
@@ -1362,6 +1569,26 @@ impl RawRecordingCommandBuffer {
             }));
         }
 
+        if enables.is_empty() {
+            return Err(Box::new(ValidationError {
+                context: "enables".into(),
+                problem: "is empty".into(),
+                vuids: &["VUID-vkCmdSetColorWriteEnableEXT-attachmentCount-arraylength"],
+                ..Default::default()
+            }));
+        }
+
+        let properties = self.device().physical_device().properties();
+
+        if enables.len() as u32 > properties.max_color_attachments {
+            return Err(Box::new(ValidationError {
+                context: "enables".into(),
+                problem: "the length exceeds the `max_color_attachments` limit".into(),
+                vuids: &["VUID-vkCmdSetColorWriteEnableEXT-attachmentCount-06656"],
+                ..Default::default()
+            }));
+        }
+
         Ok(())
     }
 
@@ -1878,7 +2105,7 @@ impl RawRecordingCommandBuffer {
         first_rectangle: u32,
         rectangles: &[Scissor],
     ) -> Result<(), Box<ValidationError>> {
-        if self.device().enabled_extensions().ext_discard_rectangles {
+        if !self.device().enabled_extensions().ext_discard_rectangles {
             return Err(Box::new(ValidationError {
                 requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
                     "ext_discard_rectangles",
@@ -1896,7 +2123,7 @@ impl RawRecordingCommandBuffer {
                 problem: "the queue family of the command buffer does not support \
                     graphics operations"
                     .into(),
-                vuids: &["VUID-vkCmdSetDiscardRectangle-commandBuffer-cmdpool"],
+                vuids: &["VUID-vkCmdSetDiscardRectangleEXT-commandBuffer-cmdpool"],
                 ..Default::default()
             }));
         }
@@ -1913,6 +2140,22 @@ impl RawRecordingCommandBuffer {
             }));
         }
 
+        for (index, rectangle) in rectangles.iter().enumerate() {
+            if rectangle.offset[0] as u64 + rectangle.extent[0] as u64 > i32::MAX as u64
+                || rectangle.offset[1] as u64 + rectangle.extent[1] as u64 > i32::MAX as u64
+            {
+                return Err(Box::new(ValidationError {
+                    context: format!("rectangles[{}]", index).into(),
+                    problem: "`offset + extent` is greater than `i32::MAX`".into(),
+                    vuids: &[
+                        "VUID-vkCmdSetDiscardRectangleEXT-offset-00588",
+                        "VUID-vkCmdSetDiscardRectangleEXT-offset-00589",
+                    ],
+                    ..Default::default()
+                }));
+            }
+        }
+
         Ok(())
     }
 
@@ -1941,6 +2184,114 @@ impl RawRecordingCommandBuffer {
         self
     }
 
+    #[inline]
+    pub unsafe fn set_discard_rectangle_enable(
+        &mut self,
+        enable: bool,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        self.validate_set_discard_rectangle_enable(enable)?;
+
+        Ok(self.set_discard_rectangle_enable_unchecked(enable))
+    }
+
+    fn validate_set_discard_rectangle_enable(
+        &self,
+        _enable: bool,
+    ) -> Result<(), Box<ValidationError>> {
+        if !self.device().enabled_extensions().ext_discard_rectangles {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "ext_discard_rectangles",
+                )])]),
+                vuids: &["VUID-vkCmdSetDiscardRectangleEnableEXT-specVersion-07851"],
+                ..Default::default()
+            }));
+        }
+
+        if !self
+            .queue_family_properties()
+            .queue_flags
+            .intersects(QueueFlags::GRAPHICS)
+        {
+            return Err(Box::new(ValidationError {
+                problem: "the queue family of the command buffer does not support \
+                    graphics operations"
+                    .into(),
+                vuids: &["VUID-vkCmdSetDiscardRectangleEnableEXT-commandBuffer-cmdpool"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_discard_rectangle_enable_unchecked(&mut self, enable: bool) -> &mut Self {
+        let fns = self.device().fns();
+        (fns.ext_discard_rectangles
+            .cmd_set_discard_rectangle_enable_ext)(self.handle(), enable.into());
+
+        self
+    }
+
+    #[inline]
+    pub unsafe fn set_discard_rectangle_mode(
+        &mut self,
+        mode: DiscardRectangleMode,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        self.validate_set_discard_rectangle_mode(mode)?;
+
+        Ok(self.set_discard_rectangle_mode_unchecked(mode))
+    }
+
+    fn validate_set_discard_rectangle_mode(
+        &self,
+        mode: DiscardRectangleMode,
+    ) -> Result<(), Box<ValidationError>> {
+        if !self.device().enabled_extensions().ext_discard_rectangles {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "ext_discard_rectangles",
+                )])]),
+                vuids: &["VUID-vkCmdSetDiscardRectangleModeEXT-specVersion-07852"],
+                ..Default::default()
+            }));
+        }
+
+        if !self
+            .queue_family_properties()
+            .queue_flags
+            .intersects(QueueFlags::GRAPHICS)
+        {
+            return Err(Box::new(ValidationError {
+                problem: "the queue family of the command buffer does not support \
+                    graphics operations"
+                    .into(),
+                vuids: &["VUID-vkCmdSetDiscardRectangleModeEXT-commandBuffer-cmdpool"],
+                ..Default::default()
+            }));
+        }
+
+        mode.validate_device(self.device()).map_err(|err| {
+            err.add_context("mode")
+                .set_vuids(&["VUID-vkCmdSetDiscardRectangleModeEXT-discardRectangleMode-parameter"])
+        })?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_discard_rectangle_mode_unchecked(
+        &mut self,
+        mode: DiscardRectangleMode,
+    ) -> &mut Self {
+        let fns = self.device().fns();
+        (fns.ext_discard_rectangles
+            .cmd_set_discard_rectangle_mode_ext)(self.handle(), mode.into());
+
+        self
+    }
+
     #[inline]
     pub unsafe fn set_front_face(
         &mut self,
@@ -3412,4 +3763,179 @@ impl RawRecordingCommandBuffer {
 
         self
     }
+
+    #[inline]
+    pub unsafe fn set_line_rasterization_mode(
+        &mut self,
+        line_rasterization_mode: LineRasterizationMode,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        self.validate_set_line_rasterization_mode(line_rasterization_mode)?;
+
+        Ok(self.set_line_rasterization_mode_unchecked(line_rasterization_mode))
+    }
+
+    fn validate_set_line_rasterization_mode(
+        &self,
+        line_rasterization_mode: LineRasterizationMode,
+    ) -> Result<(), Box<ValidationError>> {
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_line_rasterization_mode
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[
+                    RequiresAllOf(&[Requires::DeviceFeature(
+                        "extended_dynamic_state3_line_rasterization_mode",
+                    )]),
+                    RequiresAllOf(&[Requires::DeviceFeature("shader_object")]),
+                ]),
+                vuids: &["VUID-vkCmdSetLineRasterizationModeEXT-None-09423"],
+                ..Default::default()
+            }));
+        }
+
+        if !self
+            .queue_family_properties()
+            .queue_flags
+            .intersects(QueueFlags::GRAPHICS)
+        {
+            return Err(Box::new(ValidationError {
+                problem: "the queue family of the command buffer does not support \
+                    graphics operations"
+                    .into(),
+                vuids: &["VUID-vkCmdSetLineRasterizationModeEXT-commandBuffer-cmdpool"],
+                ..Default::default()
+            }));
+        }
+
+        line_rasterization_mode
+            .validate_device(self.device())
+            .map_err(|err| {
+                err.add_context("line_rasterization_mode").set_vuids(&[
+                    "VUID-vkCmdSetLineRasterizationModeEXT-lineRasterizationMode-parameter",
+                ])
+            })?;
+
+        match line_rasterization_mode {
+            LineRasterizationMode::Default => (),
+            LineRasterizationMode::Rectangular => {
+                if !self.device().enabled_features().rectangular_lines {
+                    return Err(Box::new(ValidationError {
+                        context: "line_rasterization_mode".into(),
+                        problem: "is `LineRasterizationMode::Rectangular`".into(),
+                        requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                            Requires::DeviceFeature("rectangular_lines"),
+                        ])]),
+                        vuids: &[
+                            "VUID-vkCmdSetLineRasterizationModeEXT-lineRasterizationMode-07418",
+                        ],
+                        ..Default::default()
+                    }));
+                }
+            }
+            LineRasterizationMode::Bresenham => {
+                if !self.device().enabled_features().bresenham_lines {
+                    return Err(Box::new(ValidationError {
+                        context: "line_rasterization_mode".into(),
+                        problem: "is `LineRasterizationMode::Bresenham`".into(),
+                        requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                            Requires::DeviceFeature("bresenham_lines"),
+                        ])]),
+                        vuids: &[
+                            "VUID-vkCmdSetLineRasterizationModeEXT-lineRasterizationMode-07419",
+                        ],
+                        ..Default::default()
+                    }));
+                }
+            }
+            LineRasterizationMode::RectangularSmooth => {
+                if !self.device().enabled_features().smooth_lines {
+                    return Err(Box::new(ValidationError {
+                        context: "line_rasterization_mode".into(),
+                        problem: "is `LineRasterizationMode::RectangularSmooth`".into(),
+                        requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                            Requires::DeviceFeature("smooth_lines"),
+                        ])]),
+                        vuids: &[
+                            "VUID-vkCmdSetLineRasterizationModeEXT-lineRasterizationMode-07420",
+                        ],
+                        ..Default::default()
+                    }));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_line_rasterization_mode_unchecked(
+        &mut self,
+        line_rasterization_mode: LineRasterizationMode,
+    ) -> &mut Self {
+        let fns = self.device().fns();
+        (fns.ext_extended_dynamic_state3
+            .cmd_set_line_rasterization_mode_ext)(
+            self.handle(), line_rasterization_mode.into()
+        );
+
+        self
+    }
+
+    #[inline]
+    pub unsafe fn set_line_stipple_enable(
+        &mut self,
+        enable: bool,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        self.validate_set_line_stipple_enable(enable)?;
+
+        Ok(self.set_line_stipple_enable_unchecked(enable))
+    }
+
+    fn validate_set_line_stipple_enable(&self, _enable: bool) -> Result<(), Box<ValidationError>> {
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_line_stipple_enable
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[
+                    RequiresAllOf(&[Requires::DeviceFeature(
+                        "extended_dynamic_state3_line_stipple_enable",
+                    )]),
+                    RequiresAllOf(&[Requires::DeviceFeature("shader_object")]),
+                ]),
+                vuids: &["VUID-vkCmdSetLineStippleEnableEXT-None-09423"],
+                ..Default::default()
+            }));
+        }
+
+        if !self
+            .queue_family_properties()
+            .queue_flags
+            .intersects(QueueFlags::GRAPHICS)
+        {
+            return Err(Box::new(ValidationError {
+                problem: "the queue family of the command buffer does not support \
+                    graphics operations"
+                    .into(),
+                vuids: &["VUID-vkCmdSetLineStippleEnableEXT-commandBuffer-cmdpool"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_line_stipple_enable_unchecked(&mut self, enable: bool) -> &mut Self {
+        let fns = self.device().fns();
+        (fns.ext_extended_dynamic_state3
+            .cmd_set_line_stipple_enable_ext)(self.handle(), enable.into());
+
+        self
+    }
 }
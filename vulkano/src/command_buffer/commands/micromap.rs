@@ -0,0 +1,284 @@
+use crate::{
+    acceleration_structure::AccelerationStructureBuildType,
+    buffer::BufferUsage,
+    command_buffer::{
+        auto::Resource, sys::RawRecordingCommandBuffer, RecordingCommandBuffer, ResourceInCommand,
+    },
+    device::{DeviceOwned, QueueFlags},
+    micromap::MicromapBuildInfo,
+    sync::PipelineStageAccessFlags,
+    ValidationError, VulkanObject,
+};
+
+/// # Commands to do operations on micromaps.
+impl RecordingCommandBuffer {
+    /// Builds a micromap.
+    ///
+    /// # Safety
+    ///
+    /// - For each element of `info.triangle_array`, the data that it refers to must be within
+    ///   the bounds of `info.data`.
+    /// - The elements of `info.triangle_array` must match `info.usage_counts`, meaning that for
+    ///   each combination of format and subdivision level, the number of triangles that use it
+    ///   must equal the corresponding `count`.
+    #[inline]
+    pub unsafe fn build_micromap(
+        &mut self,
+        info: MicromapBuildInfo,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_build_micromap(&info)?;
+        }
+
+        Ok(self.build_micromap_unchecked(info))
+    }
+
+    fn validate_build_micromap(
+        &self,
+        info: &MicromapBuildInfo,
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_build_micromap(info)?;
+
+        if self.builder_state.render_pass.is_some() {
+            return Err(Box::new(ValidationError {
+                context: "self".into(),
+                problem: "a render pass instance is active".into(),
+                vuids: &["VUID-vkCmdBuildMicromapsEXT-renderpass"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn build_micromap_unchecked(&mut self, info: MicromapBuildInfo) -> &mut Self {
+        let &MicromapBuildInfo {
+            ty: _,
+            flags: _,
+            ref dst_micromap,
+            usage_counts: _,
+            ref data,
+            ref scratch_data,
+            ref triangle_array,
+            _ne: _,
+        } = &info;
+
+        let dst_buffer = dst_micromap.as_ref().unwrap().buffer();
+        let data = data.as_ref().unwrap();
+        let scratch_data = scratch_data.as_ref().unwrap();
+        let triangle_array = triangle_array.as_ref().unwrap().as_bytes();
+
+        self.add_command(
+            "build_micromap",
+            [
+                (
+                    ResourceInCommand::MicromapData.into(),
+                    Resource::Buffer {
+                        buffer: data.clone(),
+                        range: 0..data.size(), // TODO:
+                        memory_access: PipelineStageAccessFlags::MicromapBuild_MicromapRead,
+                    },
+                ),
+                (
+                    ResourceInCommand::MicromapTriangleArray.into(),
+                    Resource::Buffer {
+                        buffer: triangle_array.clone(),
+                        range: 0..triangle_array.size(),
+                        memory_access: PipelineStageAccessFlags::MicromapBuild_MicromapRead,
+                    },
+                ),
+                (
+                    ResourceInCommand::ScratchData.into(),
+                    Resource::Buffer {
+                        buffer: scratch_data.clone(),
+                        range: 0..scratch_data.size(), // TODO:
+                        memory_access: PipelineStageAccessFlags::MicromapBuild_MicromapRead
+                            | PipelineStageAccessFlags::MicromapBuild_MicromapWrite,
+                    },
+                ),
+                (
+                    ResourceInCommand::Destination.into(),
+                    Resource::Buffer {
+                        buffer: dst_buffer.clone(),
+                        range: 0..dst_buffer.size(), // TODO:
+                        memory_access: PipelineStageAccessFlags::MicromapBuild_MicromapWrite,
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.build_micromap_unchecked(&info);
+            },
+        );
+
+        self
+    }
+}
+
+impl RawRecordingCommandBuffer {
+    #[inline]
+    pub unsafe fn build_micromap(
+        &mut self,
+        info: &MicromapBuildInfo,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        self.validate_build_micromap(info)?;
+
+        Ok(self.build_micromap_unchecked(info))
+    }
+
+    fn validate_build_micromap(
+        &self,
+        info: &MicromapBuildInfo,
+    ) -> Result<(), Box<ValidationError>> {
+        if !self
+            .queue_family_properties()
+            .queue_flags
+            .intersects(QueueFlags::COMPUTE)
+        {
+            return Err(Box::new(ValidationError {
+                context: "self".into(),
+                problem: "queue family does not support compute operations".into(),
+                vuids: &["VUID-vkCmdBuildMicromapsEXT-commandBuffer-cmdpool"],
+                ..Default::default()
+            }));
+        }
+
+        // VUID-vkCmdBuildMicromapsEXT-pInfos-parameter
+        info.validate(self.device())
+            .map_err(|err| err.add_context("info"))?;
+
+        let &MicromapBuildInfo {
+            ty: _,
+            flags: _,
+            ref dst_micromap,
+            usage_counts: _,
+            ref data,
+            ref scratch_data,
+            ref triangle_array,
+            _ne: _,
+        } = info;
+
+        let dst_micromap = dst_micromap.as_ref().ok_or_else(|| {
+            Box::new(ValidationError {
+                context: "info.dst_micromap".into(),
+                problem: "is `None`".into(),
+                ..Default::default()
+            })
+        })?;
+        let data = data.as_ref().ok_or_else(|| {
+            Box::new(ValidationError {
+                context: "info.data".into(),
+                problem: "is `None`".into(),
+                ..Default::default()
+            })
+        })?;
+        let scratch_data = scratch_data.as_ref().ok_or_else(|| {
+            Box::new(ValidationError {
+                context: "info.scratch_data".into(),
+                problem: "is `None`".into(),
+                ..Default::default()
+            })
+        })?;
+        let triangle_array = triangle_array.as_ref().ok_or_else(|| {
+            Box::new(ValidationError {
+                context: "info.triangle_array".into(),
+                problem: "is `None`".into(),
+                ..Default::default()
+            })
+        })?;
+
+        if !data
+            .buffer()
+            .usage()
+            .intersects(BufferUsage::MICROMAP_BUILD_INPUT_READ_ONLY)
+        {
+            return Err(Box::new(ValidationError {
+                context: "info.data".into(),
+                problem: "the buffer was not created with the \
+                    `BufferUsage::MICROMAP_BUILD_INPUT_READ_ONLY` usage"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        if data.device_address().unwrap().get() % 256 != 0 {
+            return Err(Box::new(ValidationError {
+                context: "info.data".into(),
+                problem: "the device address of the buffer is not a multiple of 256".into(),
+                ..Default::default()
+            }));
+        }
+
+        if !triangle_array
+            .buffer()
+            .usage()
+            .intersects(BufferUsage::MICROMAP_BUILD_INPUT_READ_ONLY)
+        {
+            return Err(Box::new(ValidationError {
+                context: "info.triangle_array".into(),
+                problem: "the buffer was not created with the \
+                    `BufferUsage::MICROMAP_BUILD_INPUT_READ_ONLY` usage"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        if triangle_array.device_address().unwrap().get() % 4 != 0 {
+            return Err(Box::new(ValidationError {
+                context: "info.triangle_array".into(),
+                problem: "the device address of the buffer is not a multiple of 4".into(),
+                ..Default::default()
+            }));
+        }
+
+        if !scratch_data
+            .buffer()
+            .usage()
+            .intersects(BufferUsage::STORAGE_BUFFER)
+        {
+            return Err(Box::new(ValidationError {
+                context: "info.scratch_data".into(),
+                problem: "the buffer was not created with the `BufferUsage::STORAGE_BUFFER` usage"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        let build_sizes_info = unsafe {
+            self.device()
+                .micromap_build_sizes_unchecked(AccelerationStructureBuildType::Device, info)
+        };
+
+        if dst_micromap.size() < build_sizes_info.micromap_size {
+            return Err(Box::new(ValidationError {
+                context: "info.dst_micromap".into(),
+                problem: "size is too small to hold the resulting micromap data".into(),
+                ..Default::default()
+            }));
+        }
+
+        if scratch_data.size() < build_sizes_info.build_scratch_size {
+            return Err(Box::new(ValidationError {
+                context: "info.scratch_data".into(),
+                problem: "size is too small for the build operation".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn build_micromap_unchecked(&mut self, info: &MicromapBuildInfo) -> &mut Self {
+        let (mut info_vk, usage_counts_vk) = info.to_vulkan();
+        info_vk.usage_counts_count = usage_counts_vk.len() as u32;
+        info_vk.p_usage_counts = usage_counts_vk.as_ptr();
+
+        let fns = self.device().fns();
+        (fns.ext_opacity_micromap.cmd_build_micromaps_ext)(self.handle(), 1, &info_vk);
+
+        self
+    }
+}
@@ -1,9 +1,11 @@
 pub(super) mod acceleration_structure;
 pub(super) mod bind_push;
 pub(super) mod clear;
+pub(super) mod conditional_rendering;
 pub(super) mod copy;
 pub(super) mod debug;
 pub(super) mod dynamic_state;
+pub(super) mod micromap;
 pub(super) mod pipeline;
 pub(super) mod query;
 pub(super) mod render_pass;
@@ -15,10 +15,13 @@ use crate::{
     },
     device::{DeviceOwned, QueueFlags},
     format::{FormatFeatures, NumericType},
-    image::{sampler::Sampler, view::ImageView, ImageAspects, ImageLayout, SampleCount},
+    image::{
+        sampler::Sampler, view::ImageView, ImageAspects, ImageCreateFlags, ImageLayout, SampleCount,
+    },
     pipeline::{
         graphics::{
             input_assembly::PrimitiveTopology,
+            rasterization::CullMode,
             subpass::PipelineSubpassType,
             vertex_input::{RequiredVertexInputsVUIDs, VertexInputRate},
         },
@@ -66,7 +69,9 @@ impl RecordingCommandBuffer {
         &mut self,
         group_counts: [u32; 3],
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_dispatch(group_counts)?;
+        if self.device().validation_enabled() {
+            self.validate_dispatch(group_counts)?;
+        }
 
         unsafe { Ok(self.dispatch_unchecked(group_counts)) }
     }
@@ -141,7 +146,9 @@ impl RecordingCommandBuffer {
         &mut self,
         indirect_buffer: Subbuffer<[DispatchIndirectCommand]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_dispatch_indirect(indirect_buffer.as_bytes())?;
+        if self.device().validation_enabled() {
+            self.validate_dispatch_indirect(indirect_buffer.as_bytes())?;
+        }
 
         unsafe { Ok(self.dispatch_indirect_unchecked(indirect_buffer)) }
     }
@@ -229,7 +236,9 @@ impl RecordingCommandBuffer {
         first_vertex: u32,
         first_instance: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_draw(vertex_count, instance_count, first_vertex, first_instance)?;
+        if self.device().validation_enabled() {
+            self.validate_draw(vertex_count, instance_count, first_vertex, first_instance)?;
+        }
 
         unsafe {
             Ok(self.draw_unchecked(vertex_count, instance_count, first_vertex, first_instance))
@@ -420,7 +429,9 @@ impl RecordingCommandBuffer {
     ) -> Result<&mut Self, Box<ValidationError>> {
         let draw_count = indirect_buffer.len() as u32;
         let stride = size_of::<DrawIndirectCommand>() as u32;
-        self.validate_draw_indirect(indirect_buffer.as_bytes(), draw_count, stride)?;
+        if self.device().validation_enabled() {
+            self.validate_draw_indirect(indirect_buffer.as_bytes(), draw_count, stride)?;
+        }
 
         unsafe { Ok(self.draw_indirect_unchecked(indirect_buffer, draw_count, stride)) }
     }
@@ -531,12 +542,14 @@ impl RecordingCommandBuffer {
         max_draw_count: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
         let stride = size_of::<DrawIndirectCommand>() as u32;
-        self.validate_draw_indirect_count(
-            indirect_buffer.as_bytes(),
-            count_buffer.as_bytes(),
-            max_draw_count,
-            stride,
-        )?;
+        if self.device().validation_enabled() {
+            self.validate_draw_indirect_count(
+                indirect_buffer.as_bytes(),
+                count_buffer.as_bytes(),
+                max_draw_count,
+                stride,
+            )?;
+        }
 
         unsafe {
             Ok(self.draw_indirect_count_unchecked(
@@ -673,13 +686,15 @@ impl RecordingCommandBuffer {
         vertex_offset: i32,
         first_instance: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_draw_indexed(
-            index_count,
-            instance_count,
-            first_index,
-            vertex_offset,
-            first_instance,
-        )?;
+        if self.device().validation_enabled() {
+            self.validate_draw_indexed(
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            )?;
+        }
 
         unsafe {
             Ok(self.draw_indexed_unchecked(
@@ -907,7 +922,9 @@ impl RecordingCommandBuffer {
     ) -> Result<&mut Self, Box<ValidationError>> {
         let draw_count = indirect_buffer.len() as u32;
         let stride = size_of::<DrawIndexedIndirectCommand>() as u32;
-        self.validate_draw_indexed_indirect(indirect_buffer.as_bytes(), draw_count, stride)?;
+        if self.device().validation_enabled() {
+            self.validate_draw_indexed_indirect(indirect_buffer.as_bytes(), draw_count, stride)?;
+        }
 
         unsafe { Ok(self.draw_indexed_indirect_unchecked(indirect_buffer, draw_count, stride)) }
     }
@@ -1033,12 +1050,14 @@ impl RecordingCommandBuffer {
         max_draw_count: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
         let stride = size_of::<DrawIndexedIndirectCommand>() as u32;
-        self.validate_draw_indexed_indirect_count(
-            indirect_buffer.as_bytes(),
-            count_buffer.as_bytes(),
-            max_draw_count,
-            stride,
-        )?;
+        if self.device().validation_enabled() {
+            self.validate_draw_indexed_indirect_count(
+                indirect_buffer.as_bytes(),
+                count_buffer.as_bytes(),
+                max_draw_count,
+                stride,
+            )?;
+        }
 
         unsafe {
             Ok(self.draw_indexed_indirect_count_unchecked(
@@ -1161,7 +1180,9 @@ impl RecordingCommandBuffer {
         &mut self,
         group_counts: [u32; 3],
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_draw_mesh_tasks(group_counts)?;
+        if self.device().validation_enabled() {
+            self.validate_draw_mesh_tasks(group_counts)?;
+        }
 
         unsafe { Ok(self.draw_mesh_tasks_unchecked(group_counts)) }
     }
@@ -1359,7 +1380,9 @@ impl RecordingCommandBuffer {
     ) -> Result<&mut Self, Box<ValidationError>> {
         let draw_count = indirect_buffer.len() as u32;
         let stride = size_of::<DrawMeshTasksIndirectCommand>() as u32;
-        self.validate_draw_mesh_tasks_indirect(indirect_buffer.as_bytes(), draw_count, stride)?;
+        if self.device().validation_enabled() {
+            self.validate_draw_mesh_tasks_indirect(indirect_buffer.as_bytes(), draw_count, stride)?;
+        }
 
         unsafe { Ok(self.draw_mesh_tasks_indirect_unchecked(indirect_buffer, draw_count, stride)) }
     }
@@ -1478,12 +1501,14 @@ impl RecordingCommandBuffer {
         max_draw_count: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
         let stride = size_of::<DrawMeshTasksIndirectCommand>() as u32;
-        self.validate_draw_mesh_tasks_indirect_count(
-            indirect_buffer.as_bytes(),
-            count_buffer.as_bytes(),
-            max_draw_count,
-            stride,
-        )?;
+        if self.device().validation_enabled() {
+            self.validate_draw_mesh_tasks_indirect_count(
+                indirect_buffer.as_bytes(),
+                count_buffer.as_bytes(),
+                max_draw_count,
+                stride,
+            )?;
+        }
 
         unsafe {
             Ok(self.draw_mesh_tasks_indirect_count_unchecked(
@@ -1603,6 +1628,7 @@ impl RecordingCommandBuffer {
             binding_num: u32,
             binding_reqs: &DescriptorBindingRequirements,
             elements: &[Option<T>],
+            allow_null: bool,
             mut extra_check: impl FnMut(u32, u32, u32, &T) -> Result<(), Box<ValidationError>>,
         ) -> Result<(), Box<ValidationError>> {
             let elements_to_check = if let Some(descriptor_count) = binding_reqs.descriptor_count {
@@ -1634,6 +1660,8 @@ impl RecordingCommandBuffer {
 
                 let element = match element {
                     Some(x) => x,
+                    // Null descriptors are tracked the same way as unwritten descriptors.
+                    None if allow_null => continue,
                     None => {
                         return Err(Box::new(ValidationError {
                             problem: format!(
@@ -1699,6 +1727,30 @@ impl RecordingCommandBuffer {
 
             let check_buffer_view =
                 |set_num: u32, binding_num: u32, index: u32, buffer_view: &Arc<BufferView>| {
+                    if let Some(shader_scalar_width) = binding_reqs.image_scalar_width {
+                        let view_has_64bit_components =
+                            buffer_view.format().components().contains(&64);
+
+                        if view_has_64bit_components != (shader_scalar_width == 64) {
+                            return Err(Box::new(ValidationError {
+                                problem: format!(
+                                    "the currently bound pipeline accesses the buffer view \
+                                    bound to descriptor set {set_num}, binding {binding_num}, \
+                                    descriptor index {index}, and the pipeline requires a \
+                                    buffer view whose format has {shader_scalar_width}-bit \
+                                    components, but the format of the buffer view does not"
+                                )
+                                .into(),
+                                vuids: if view_has_64bit_components {
+                                    vuids!(vuid_type, "SampledType-04472")
+                                } else {
+                                    vuids!(vuid_type, "SampledType-04473")
+                                },
+                                ..Default::default()
+                            }));
+                        }
+                    }
+
                     for desc_reqs in binding_reqs
                         .descriptors
                         .get(&Some(index))
@@ -1778,6 +1830,31 @@ impl RecordingCommandBuffer {
                             }));
                         }
 
+                        if desc_reqs.storage_image_atomic
+                            && binding_reqs.image_scalar_width == Some(64)
+                            && image_view
+                                .image()
+                                .flags()
+                                .intersects(ImageCreateFlags::SPARSE_RESIDENCY)
+                            && !self.device().enabled_features().sparse_image_int64_atomics
+                        {
+                            return Err(Box::new(ValidationError {
+                                problem: format!(
+                                    "the currently bound pipeline performs 64-bit atomic \
+                                    operations on the image view bound to descriptor set \
+                                    {set_num}, binding {binding_num}, descriptor index {index}, \
+                                    and the image of the image view was created with \
+                                    `ImageCreateFlags::SPARSE_RESIDENCY`"
+                                )
+                                .into(),
+                                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                                    Requires::DeviceFeature("sparse_image_int64_atomics"),
+                                ])]),
+                                vuids: vuids!(vuid_type, "sparseImageInt64Atomics-04474"),
+                                ..Default::default()
+                            }));
+                        }
+
                         if layout_binding.descriptor_type == DescriptorType::StorageImage {
                             if binding_reqs.image_format.is_none()
                                 && !desc_reqs.memory_write.is_empty()
@@ -1902,6 +1979,30 @@ impl RecordingCommandBuffer {
                     //   of Numeric Format table.
                     // - If the signedness of any read or sample operation does not match the
                     //   signedness of the image’s format.
+                    if let Some(shader_scalar_width) = binding_reqs.image_scalar_width {
+                        let view_has_64bit_components =
+                            image_view.format().components().contains(&64);
+
+                        if view_has_64bit_components != (shader_scalar_width == 64) {
+                            return Err(Box::new(ValidationError {
+                                problem: format!(
+                                    "the currently bound pipeline accesses the image view \
+                                    bound to descriptor set {set_num}, binding {binding_num}, \
+                                    descriptor index {index}, and the pipeline requires an \
+                                    image view whose format has {shader_scalar_width}-bit \
+                                    components, but the format of the image view does not"
+                                )
+                                .into(),
+                                vuids: if view_has_64bit_components {
+                                    vuids!(vuid_type, "SampledType-04470")
+                                } else {
+                                    vuids!(vuid_type, "SampledType-04471")
+                                },
+                                ..Default::default()
+                            }));
+                        }
+                    }
+
                     if let Some(shader_numeric_type) = binding_reqs.image_scalar_type {
                         let aspects = image_view.subresource_range().aspects;
                         let view_numeric_type = NumericType::from(
@@ -2146,6 +2247,10 @@ impl RecordingCommandBuffer {
                 Ok(())
             };
 
+            // Elements that don't have a resource may have been written as null descriptors.
+            let allow_null = self.device().enabled_features().null_descriptor
+                && layout_binding.descriptor_type != DescriptorType::InputAttachment;
+
             let flags_skip_binding_validation =
                 DescriptorBindingFlags::UPDATE_AFTER_BIND | DescriptorBindingFlags::PARTIALLY_BOUND;
             let requires_binding_validation =
@@ -2177,6 +2282,7 @@ impl RecordingCommandBuffer {
                             binding_num,
                             binding_reqs,
                             elements,
+                            false,
                             check_none,
                         )?;
                     }
@@ -2187,6 +2293,7 @@ impl RecordingCommandBuffer {
                             binding_num,
                             binding_reqs,
                             elements,
+                            allow_null,
                             check_buffer,
                         )?;
                     }
@@ -2197,6 +2304,7 @@ impl RecordingCommandBuffer {
                             binding_num,
                             binding_reqs,
                             elements,
+                            allow_null,
                             check_buffer_view,
                         )?;
                     }
@@ -2207,6 +2315,7 @@ impl RecordingCommandBuffer {
                             binding_num,
                             binding_reqs,
                             elements,
+                            allow_null,
                             check_image_view,
                         )?;
                     }
@@ -2217,6 +2326,7 @@ impl RecordingCommandBuffer {
                             binding_num,
                             binding_reqs,
                             elements,
+                            false,
                             check_image_view_sampler,
                         )?;
                     }
@@ -2227,6 +2337,7 @@ impl RecordingCommandBuffer {
                             binding_num,
                             binding_reqs,
                             elements,
+                            false,
                             check_sampler,
                         )?;
                     }
@@ -2244,6 +2355,7 @@ impl RecordingCommandBuffer {
                             binding_num,
                             binding_reqs,
                             elements,
+                            allow_null,
                             check_acceleration_structure,
                         )?;
                     }
@@ -2632,6 +2744,38 @@ impl RecordingCommandBuffer {
                         }
                     }
                 }
+                DynamicState::DiscardRectangleEnable => {
+                    if self.builder_state.discard_rectangle_enable.is_none() {
+                        return Err(Box::new(ValidationError {
+                            problem: format!(
+                                "the currently bound graphics pipeline requires the \
+                                `DynamicState::{:?}` dynamic state, but \
+                                this state was either not set, or it was overwritten by a \
+                                more recent `bind_pipeline_graphics` command",
+                                dynamic_state
+                            )
+                            .into(),
+                            vuids: vuids!(vuid_type, "None-07880"),
+                            ..Default::default()
+                        }));
+                    }
+                }
+                DynamicState::DiscardRectangleMode => {
+                    if self.builder_state.discard_rectangle_mode.is_none() {
+                        return Err(Box::new(ValidationError {
+                            problem: format!(
+                                "the currently bound graphics pipeline requires the \
+                                `DynamicState::{:?}` dynamic state, but \
+                                this state was either not set, or it was overwritten by a \
+                                more recent `bind_pipeline_graphics` command",
+                                dynamic_state
+                            )
+                            .into(),
+                            vuids: vuids!(vuid_type, "None-07881"),
+                            ..Default::default()
+                        }));
+                    }
+                }
                 // DynamicState::ExclusiveScissor => todo!(),
                 // DynamicState::FragmentShadingRate => todo!(),
                 DynamicState::FrontFace => {
@@ -3049,6 +3193,33 @@ impl RecordingCommandBuffer {
                             ..Default::default()
                         }));
                     }
+
+                    if device.enabled_extensions().khr_portability_subset
+                        && !device.enabled_features().separate_stencil_mask_ref
+                        && state.front != state.back
+                    {
+                        let cull_mode =
+                            if pipeline.dynamic_state().contains(&DynamicState::CullMode) {
+                                self.builder_state.cull_mode
+                            } else {
+                                Some(pipeline.rasterization_state().cull_mode)
+                            };
+
+                        if cull_mode == Some(CullMode::None) {
+                            return Err(Box::new(ValidationError {
+                                problem: "the currently bound graphics pipeline requires the \
+                                    `DynamicState::StencilReference` dynamic state, this device \
+                                    is a portability subset device, the current cull mode is \
+                                    `CullMode::None`, and the current front and back stencil \
+                                    references are not equal"
+                                    .into(),
+                                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                                    Requires::DeviceFeature("separate_stencil_mask_ref"),
+                                ])]),
+                                ..Default::default()
+                            }));
+                        }
+                    }
                 }
                 DynamicState::StencilTestEnable => {
                     if self.builder_state.stencil_test_enable.is_none() {
@@ -3262,6 +3433,73 @@ impl RecordingCommandBuffer {
                         }));
                     }
                 }
+                DynamicState::LineRasterizationMode => {
+                    if self.builder_state.line_rasterization_mode.is_none() {
+                        return Err(Box::new(ValidationError {
+                            problem: format!(
+                                "the currently bound graphics pipeline requires the \
+                                `DynamicState::{:?}` dynamic state, but \
+                                this state was either not set, or it was overwritten by a \
+                                more recent `bind_pipeline_graphics` command",
+                                dynamic_state
+                            )
+                            .into(),
+                            vuids: vuids!(vuid_type, "None-07637"),
+                            ..Default::default()
+                        }));
+                    }
+                }
+                DynamicState::LineStippleEnable => {
+                    if self.builder_state.line_stipple_enable.is_none() {
+                        return Err(Box::new(ValidationError {
+                            problem: format!(
+                                "the currently bound graphics pipeline requires the \
+                                `DynamicState::{:?}` dynamic state, but \
+                                this state was either not set, or it was overwritten by a \
+                                more recent `bind_pipeline_graphics` command",
+                                dynamic_state
+                            )
+                            .into(),
+                            vuids: vuids!(vuid_type, "None-07638"),
+                            ..Default::default()
+                        }));
+                    }
+                }
+            }
+        }
+
+        if self
+            .builder_state
+            .queries
+            .contains_key(&QueryType::PrimitivesGenerated)
+            && !device
+                .enabled_features()
+                .primitives_generated_query_with_rasterizer_discard
+        {
+            let rasterizer_discard_enable = if pipeline
+                .dynamic_state()
+                .contains(&DynamicState::RasterizerDiscardEnable)
+            {
+                self.builder_state
+                    .rasterizer_discard_enable
+                    .unwrap_or_default()
+            } else {
+                pipeline.rasterization_state().rasterizer_discard_enable
+            };
+
+            if rasterizer_discard_enable {
+                return Err(Box::new(ValidationError {
+                    problem: "a `PrimitivesGenerated` query is currently active, and \
+                        rasterizer discard is enabled"
+                        .into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "primitives_generated_query_with_rasterizer_discard",
+                    )])]),
+                    vuids: vuids!(
+                        vuid_type,
+                        "primitivesGeneratedQueryWithRasterizerDiscard-06708"
+                    ),
+                }));
             }
         }
 
@@ -28,7 +28,9 @@ impl RecordingCommandBuffer {
         query: u32,
         flags: QueryControlFlags,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_begin_query(&query_pool, query, flags)?;
+        if self.device().validation_enabled() {
+            self.validate_begin_query(&query_pool, query, flags)?;
+        }
 
         Ok(self.begin_query_unchecked(query_pool, query, flags))
     }
@@ -111,7 +113,9 @@ impl RecordingCommandBuffer {
         query_pool: Arc<QueryPool>,
         query: u32,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_end_query(&query_pool, query)?;
+        if self.device().validation_enabled() {
+            self.validate_end_query(&query_pool, query)?;
+        }
 
         unsafe { Ok(self.end_query_unchecked(query_pool, query)) }
     }
@@ -188,7 +192,9 @@ impl RecordingCommandBuffer {
         query: u32,
         stage: PipelineStage,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_write_timestamp(&query_pool, query, stage)?;
+        if self.device().validation_enabled() {
+            self.validate_write_timestamp(&query_pool, query, stage)?;
+        }
 
         Ok(self.write_timestamp_unchecked(query_pool, query, stage))
     }
@@ -263,7 +269,14 @@ impl RecordingCommandBuffer {
     where
         T: QueryResultElement,
     {
-        self.validate_copy_query_pool_results(&query_pool, queries.clone(), &destination, flags)?;
+        if self.device().validation_enabled() {
+            self.validate_copy_query_pool_results(
+                &query_pool,
+                queries.clone(),
+                &destination,
+                flags,
+            )?;
+        }
 
         unsafe {
             Ok(self.copy_query_pool_results_unchecked(query_pool, queries, destination, flags))
@@ -343,7 +356,9 @@ impl RecordingCommandBuffer {
         query_pool: Arc<QueryPool>,
         queries: Range<u32>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_reset_query_pool(&query_pool, queries.clone())?;
+        if self.device().validation_enabled() {
+            self.validate_reset_query_pool(&query_pool, queries.clone())?;
+        }
 
         Ok(self.reset_query_pool_unchecked(query_pool, queries))
     }
@@ -517,6 +532,33 @@ impl RawRecordingCommandBuffer {
                     }));
                 }
             }
+            QueryType::PrimitivesGenerated => {
+                if !device.enabled_features().primitives_generated_query {
+                    return Err(Box::new(ValidationError {
+                        context: "query_pool.query_type()".into(),
+                        problem: "is `QueryType::PrimitivesGenerated`".into(),
+                        requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                            Requires::DeviceFeature("primitives_generated_query"),
+                        ])]),
+                        vuids: &["VUID-vkCmdBeginQuery-queryType-06688"],
+                    }));
+                }
+
+                if !queue_family_properties
+                    .queue_flags
+                    .intersects(QueueFlags::GRAPHICS)
+                {
+                    return Err(Box::new(ValidationError {
+                        problem: "`query_pool.query_type()` is \
+                            `QueryType::PrimitivesGenerated`, but \
+                            the queue family of the command buffer does not support \
+                            graphics operations"
+                            .into(),
+                        vuids: &["VUID-vkCmdBeginQuery-queryType-06687"],
+                        ..Default::default()
+                    }));
+                }
+            }
             QueryType::Timestamp
             | QueryType::AccelerationStructureCompactedSize
             | QueryType::AccelerationStructureSerializationSize
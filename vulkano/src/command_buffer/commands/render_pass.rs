@@ -31,12 +31,15 @@ impl RecordingCommandBuffer {
     ///
     /// `contents` specifies what kinds of commands will be recorded in the render pass, either
     /// draw commands or executions of secondary command buffers.
+    #[track_caller]
     pub fn begin_render_pass(
         &mut self,
         render_pass_begin_info: RenderPassBeginInfo,
         subpass_begin_info: SubpassBeginInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_begin_render_pass(&render_pass_begin_info, &subpass_begin_info)?;
+        if self.device().validation_enabled() {
+            self.validate_begin_render_pass(&render_pass_begin_info, &subpass_begin_info)?;
+        }
 
         unsafe { Ok(self.begin_render_pass_unchecked(render_pass_begin_info, subpass_begin_info)) }
     }
@@ -76,6 +79,7 @@ impl RecordingCommandBuffer {
     }
 
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[track_caller]
     pub unsafe fn begin_render_pass_unchecked(
         &mut self,
         render_pass_begin_info: RenderPassBeginInfo,
@@ -109,6 +113,8 @@ impl RecordingCommandBuffer {
             .into(),
         });
 
+        self.begin_auto_debug_utils_label("Render pass");
+
         self.add_render_pass_begin(
             "begin_render_pass",
             render_pass
@@ -152,7 +158,9 @@ impl RecordingCommandBuffer {
         subpass_end_info: SubpassEndInfo,
         subpass_begin_info: SubpassBeginInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_next_subpass(&subpass_end_info, &subpass_begin_info)?;
+        if self.device().validation_enabled() {
+            self.validate_next_subpass(&subpass_end_info, &subpass_begin_info)?;
+        }
 
         unsafe { Ok(self.next_subpass_unchecked(subpass_end_info, subpass_begin_info)) }
     }
@@ -255,7 +263,9 @@ impl RecordingCommandBuffer {
         &mut self,
         subpass_end_info: SubpassEndInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_end_render_pass(&subpass_end_info)?;
+        if self.device().validation_enabled() {
+            self.validate_end_render_pass(&subpass_end_info)?;
+        }
 
         unsafe { Ok(self.end_render_pass_unchecked(subpass_end_info)) }
     }
@@ -326,6 +336,8 @@ impl RecordingCommandBuffer {
             },
         );
 
+        self.end_auto_debug_utils_label();
+
         self
     }
 }
@@ -338,12 +350,15 @@ impl RecordingCommandBuffer {
     /// You must call this or `begin_render_pass` before you can record draw commands.
     ///
     /// [`dynamic_rendering`]: crate::device::DeviceFeatures::dynamic_rendering
+    #[track_caller]
     pub fn begin_rendering(
         &mut self,
         mut rendering_info: RenderingInfo,
     ) -> Result<&mut Self, Box<ValidationError>> {
         rendering_info.set_auto_extent_layers();
-        self.validate_begin_rendering(&rendering_info)?;
+        if self.device().validation_enabled() {
+            self.validate_begin_rendering(&rendering_info)?;
+        }
 
         unsafe { Ok(self.begin_rendering_unchecked(rendering_info)) }
     }
@@ -366,6 +381,7 @@ impl RecordingCommandBuffer {
     }
 
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[track_caller]
     pub unsafe fn begin_rendering_unchecked(
         &mut self,
         mut rendering_info: RenderingInfo,
@@ -400,6 +416,8 @@ impl RecordingCommandBuffer {
             .into(),
         });
 
+        self.begin_auto_debug_utils_label("Rendering");
+
         self.add_render_pass_begin(
             "begin_rendering",
             (color_attachments
@@ -573,7 +591,9 @@ impl RecordingCommandBuffer {
 
     /// Ends the render pass previously begun with `begin_rendering`.
     pub fn end_rendering(&mut self) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_end_rendering()?;
+        if self.device().validation_enabled() {
+            self.validate_end_rendering()?;
+        }
 
         unsafe { Ok(self.end_rendering_unchecked()) }
     }
@@ -635,6 +655,8 @@ impl RecordingCommandBuffer {
             },
         );
 
+        self.end_auto_debug_utils_label();
+
         self
     }
 
@@ -656,7 +678,9 @@ impl RecordingCommandBuffer {
         attachments: SmallVec<[ClearAttachment; 4]>,
         rects: SmallVec<[ClearRect; 4]>,
     ) -> Result<&mut Self, Box<ValidationError>> {
-        self.validate_clear_attachments(&attachments, &rects)?;
+        if self.device().validation_enabled() {
+            self.validate_clear_attachments(&attachments, &rects)?;
+        }
 
         unsafe { Ok(self.clear_attachments_unchecked(attachments, rects)) }
     }
@@ -2319,6 +2343,45 @@ impl RenderingInfo {
             }));
         }
 
+        if view_mask != 0 {
+            let attachments = (color_attachments.iter().enumerate())
+                .filter_map(|(index, attachment_info)| {
+                    attachment_info.as_ref().map(|attachment_info| {
+                        (format!("color_attachments[{}]", index), attachment_info)
+                    })
+                })
+                .chain(
+                    depth_attachment
+                        .iter()
+                        .map(|attachment_info| ("depth_attachment".to_owned(), attachment_info)),
+                )
+                .chain(
+                    stencil_attachment
+                        .iter()
+                        .map(|attachment_info| ("stencil_attachment".to_owned(), attachment_info)),
+                );
+
+            for (context, attachment_info) in attachments {
+                let image_view_array_layers = attachment_info
+                    .image_view
+                    .subresource_range()
+                    .array_layers
+                    .len() as u32;
+
+                if image_view_array_layers < highest_view_index {
+                    return Err(Box::new(ValidationError {
+                        problem: format!(
+                            "`view_mask` is not 0, but the layer count of \
+                            `{}.image_view` is less than the number of views used by `view_mask`",
+                            context,
+                        )
+                        .into(),
+                        ..Default::default()
+                    }));
+                }
+            }
+        }
+
         let mut samples = None;
 
         if color_attachments.len() > properties.max_color_attachments as usize {
@@ -2719,7 +2782,7 @@ impl RenderingInfo {
                     return Err(Box::new(ValidationError {
                         context: "stencil_attachment.resolve_info.image_layout".into(),
                         problem: "is `ImageLayout::ColorAttachmentOptimal` or \
-                            `ImageLayout::DepthReadOnlyStencilAttachmentOptimal`"
+                            `ImageLayout::DepthAttachmentStencilReadOnlyOptimal`"
                             .into(),
                         vuids: &[
                             "VUID-VkRenderingInfo-pStencilAttachment-06095",
@@ -1,19 +1,35 @@
 use crate::{
     command_buffer::{
+        allocator::CommandBufferAllocator,
         auto::{RenderPassStateType, Resource, ResourceUseRef2},
-        sys::{RawCommandBuffer, RawRecordingCommandBuffer},
-        CommandBuffer, CommandBufferInheritanceRenderPassType, CommandBufferLevel,
-        RecordingCommandBuffer, ResourceInCommand, SecondaryCommandBufferBufferUsage,
-        SecondaryCommandBufferImageUsage, SecondaryCommandBufferResourcesUsage, SubpassContents,
+        sys::{CommandBufferBeginInfo, RawCommandBuffer, RawRecordingCommandBuffer},
+        CommandBuffer, CommandBufferInheritanceInfo, CommandBufferInheritanceRenderPassInfo,
+        CommandBufferInheritanceRenderPassType, CommandBufferInheritanceRenderingInfo,
+        CommandBufferLevel, CommandBufferUsage, RecordingCommandBuffer, ResourceInCommand,
+        SecondaryCommandBufferBufferUsage, SecondaryCommandBufferImageUsage,
+        SecondaryCommandBufferResourcesUsage, SubpassContents,
     },
     device::{DeviceOwned, QueueFlags},
-    query::QueryType,
-    Requires, RequiresAllOf, RequiresOneOf, SafeDeref, ValidationError, VulkanObject,
+    image::SampleCount,
+    pipeline::graphics::viewport::{Scissor, Viewport},
+    query::{QueryPipelineStatisticFlags, QueryType},
+    Requires, RequiresAllOf, RequiresOneOf, SafeDeref, Validated, ValidationError, VulkanError,
+    VulkanObject,
 };
+use ahash::HashMap;
 use smallvec::{smallvec, SmallVec};
-use std::{cmp::min, iter, sync::Arc};
+use std::{
+    cmp::{max, min},
+    iter,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
 
-/// # Commands to execute a secondary command buffer inside a primary command buffer.
+/// # Commands to execute a secondary command buffer inside another command buffer.
 ///
 /// These commands can be called on any queue that can execute the commands recorded in the
 /// secondary command buffer.
@@ -23,12 +39,23 @@ impl RecordingCommandBuffer {
     /// If the `flags` that `command_buffer` was created with are more restrictive than those of
     /// `self`, then `self` will be restricted to match. E.g. executing a secondary command buffer
     /// with `Flags::OneTimeSubmit` will set `self`'s flags to `Flags::OneTimeSubmit` also.
+    ///
+    /// If `self` is itself a secondary command buffer, then the
+    /// [`nested_command_buffer`](crate::device::DeviceFeatures::nested_command_buffer) feature
+    /// must be enabled on the device, and the nesting level of `command_buffer` must be less than
+    /// the [`max_command_buffer_nesting_level`] limit. If `self` inherits a render pass instance,
+    /// then the [`nested_command_buffer_rendering`] feature must also be enabled.
+    ///
+    /// [`nested_command_buffer_rendering`]: crate::device::DeviceFeatures::nested_command_buffer_rendering
+    /// [`max_command_buffer_nesting_level`]: crate::device::DeviceProperties::max_command_buffer_nesting_level
     pub fn execute_commands(
         &mut self,
         command_buffer: Arc<CommandBuffer>,
     ) -> Result<&mut Self, Box<ValidationError>> {
         let command_buffer = DropUnlockCommandBuffer::new(command_buffer)?;
-        self.validate_execute_commands(iter::once(&**command_buffer))?;
+        if self.device().validation_enabled() {
+            self.validate_execute_commands(iter::once(&**command_buffer))?;
+        }
 
         unsafe { Ok(self.execute_commands_locked(smallvec![command_buffer])) }
     }
@@ -48,11 +75,163 @@ impl RecordingCommandBuffer {
             .map(DropUnlockCommandBuffer::new)
             .collect::<Result<_, _>>()?;
 
-        self.validate_execute_commands(command_buffers.iter().map(|cb| &***cb))?;
+        if self.device().validation_enabled() {
+            self.validate_execute_commands(command_buffers.iter().map(|cb| &***cb))?;
+        }
 
         unsafe { Ok(self.execute_commands_locked(command_buffers)) }
     }
 
+    /// Returns the inheritance info that a secondary command buffer must be created with, to be
+    /// executed at the current point of this command buffer.
+    ///
+    /// The render pass instance or dynamic rendering instance that is currently active is
+    /// inherited, as well as the occlusion and pipeline statistics queries that are currently
+    /// active.
+    pub fn secondary_inheritance_info(&self) -> CommandBufferInheritanceInfo {
+        let render_pass = self
+            .builder_state
+            .render_pass
+            .as_ref()
+            .map(|render_pass_state| match &render_pass_state.render_pass {
+                RenderPassStateType::BeginRenderPass(state) => {
+                    CommandBufferInheritanceRenderPassInfo {
+                        framebuffer: state.framebuffer.clone(),
+                        ..CommandBufferInheritanceRenderPassInfo::subpass(state.subpass.clone())
+                    }
+                    .into()
+                }
+                RenderPassStateType::BeginRendering(_) => {
+                    let rendering_info = &render_pass_state.rendering_info;
+                    let rasterization_samples = render_pass_state
+                        .attachments
+                        .as_ref()
+                        .and_then(|attachments| {
+                            (attachments.color_attachments.iter().flatten())
+                                .chain(&attachments.depth_attachment)
+                                .chain(&attachments.stencil_attachment)
+                                .next()
+                        })
+                        .map_or(SampleCount::Sample1, |attachment| {
+                            attachment.image_view.image().samples()
+                        });
+
+                    CommandBufferInheritanceRenderingInfo {
+                        view_mask: rendering_info.view_mask,
+                        color_attachment_formats: rendering_info.color_attachment_formats.clone(),
+                        depth_attachment_format: rendering_info.depth_attachment_format,
+                        stencil_attachment_format: rendering_info.stencil_attachment_format,
+                        rasterization_samples,
+                        ..Default::default()
+                    }
+                    .into()
+                }
+            });
+
+        let mut occlusion_query = None;
+        let mut pipeline_statistics = QueryPipelineStatisticFlags::empty();
+
+        for state in self.builder_state.queries.values() {
+            match state.query_pool.query_type() {
+                QueryType::Occlusion => occlusion_query = Some(state.flags),
+                QueryType::PipelineStatistics => {
+                    pipeline_statistics = state.query_pool.pipeline_statistics();
+                }
+                _ => (),
+            }
+        }
+
+        CommandBufferInheritanceInfo {
+            render_pass,
+            occlusion_query,
+            pipeline_statistics,
+            ..Default::default()
+        }
+    }
+
+    /// Records secondary command buffers on multiple threads, and then executes them in order.
+    ///
+    /// `record` is called once for every index in `0..count`, spread over as many scoped threads
+    /// as there is available parallelism. Each call receives a new secondary command buffer, which
+    /// is created with [`secondary_inheritance_info`] and the same usage as `self`. Because
+    /// dynamic state is not inherited, the viewports and scissors that are currently set on
+    /// `self` are set on the secondary command buffer before `record` is called.
+    ///
+    /// Once all of them are recorded, the secondary command buffers are executed in index order,
+    /// as if by [`execute_commands_from_vec`]. Like with that function, the secondary command
+    /// buffers must not have resource conflicts with each other.
+    ///
+    /// [`secondary_inheritance_info`]: Self::secondary_inheritance_info
+    /// [`execute_commands_from_vec`]: Self::execute_commands_from_vec
+    pub fn execute_commands_parallel<R>(
+        &mut self,
+        allocator: Arc<dyn CommandBufferAllocator>,
+        count: usize,
+        record: R,
+    ) -> Result<&mut Self, Validated<VulkanError>>
+    where
+        R: Fn(usize, &mut RecordingCommandBuffer) -> Result<(), Validated<VulkanError>> + Sync,
+    {
+        let queue_family_index = self.inner.queue_family_index();
+        let begin_info = CommandBufferBeginInfo {
+            usage: self.inner.usage(),
+            inheritance_info: Some(self.secondary_inheritance_info()),
+            ..Default::default()
+        };
+        let dynamic_state = InheritedDynamicState {
+            viewport: self.builder_state.viewport.clone(),
+            viewport_with_count: self.builder_state.viewport_with_count.clone(),
+            scissor: self.builder_state.scissor.clone(),
+            scissor_with_count: self.builder_state.scissor_with_count.clone(),
+        };
+
+        let (allocator, record, begin_info, dynamic_state) =
+            (&allocator, &record, &begin_info, &dynamic_state);
+        let next_index = &AtomicUsize::new(0);
+        let thread_count = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(count);
+        let mut command_buffers: Vec<Option<Arc<CommandBuffer>>> = vec![None; count];
+
+        thread::scope(|scope| {
+            let threads: Vec<_> = (0..thread_count)
+                .map(|_| {
+                    scope.spawn(move || {
+                        let mut recorded = Vec::new();
+
+                        loop {
+                            let index = next_index.fetch_add(1, Ordering::Relaxed);
+
+                            if index >= count {
+                                break Ok::<_, Validated<VulkanError>>(recorded);
+                            }
+
+                            let mut builder = RecordingCommandBuffer::new(
+                                allocator.clone(),
+                                queue_family_index,
+                                CommandBufferLevel::Secondary,
+                                begin_info.clone(),
+                            )?;
+                            dynamic_state.set(&mut builder);
+                            record(index, &mut builder)?;
+                            recorded.push((index, builder.end()?));
+                        }
+                    })
+                })
+                .collect();
+
+            for thread in threads {
+                for (index, command_buffer) in thread.join().unwrap()? {
+                    command_buffers[index] = Some(command_buffer);
+                }
+            }
+
+            Ok::<_, Validated<VulkanError>>(())
+        })?;
+
+        Ok(self.execute_commands_from_vec(command_buffers.into_iter().flatten().collect())?)
+    }
+
     fn validate_execute_commands<'a>(
         &self,
         command_buffers: impl Iterator<Item = &'a CommandBuffer> + Clone,
@@ -61,7 +240,11 @@ impl RecordingCommandBuffer {
             .validate_execute_commands(command_buffers.clone().map(|cb| cb.inner()))?;
 
         if let Some(render_pass_state) = &self.builder_state.render_pass {
-            if render_pass_state.contents != SubpassContents::SecondaryCommandBuffers {
+            // In a secondary command buffer, the render pass instance is inherited, and the
+            // contents are determined by the primary command buffer that executes it.
+            if self.level() == CommandBufferLevel::Primary
+                && render_pass_state.contents != SubpassContents::SecondaryCommandBuffers
+            {
                 return Err(Box::new(ValidationError {
                     problem: "a render pass instance is active, but its current subpass contents \
                         is `SubpassContents::SecondaryCommandBuffers`"
@@ -146,8 +329,10 @@ impl RecordingCommandBuffer {
                             }));
                         }
 
-                        if let Some(framebuffer) = &inheritance_info.framebuffer {
-                            if framebuffer != state.framebuffer.as_ref().unwrap() {
+                        if let (Some(framebuffer), Some(current_framebuffer)) =
+                            (&inheritance_info.framebuffer, &state.framebuffer)
+                        {
+                            if framebuffer != current_framebuffer {
                                 return Err(Box::new(ValidationError {
                                     context: format!(
                                         "command_buffers[{}].inheritance_info().render_pass\
@@ -168,10 +353,11 @@ impl RecordingCommandBuffer {
                         RenderPassStateType::BeginRendering(_),
                         CommandBufferInheritanceRenderPassType::BeginRendering(inheritance_info),
                     ) => {
-                        let attachments = render_pass_state.attachments.as_ref().unwrap();
-
                         if inheritance_info.color_attachment_formats.len()
-                            != attachments.color_attachments.len()
+                            != render_pass_state
+                                .rendering_info
+                                .color_attachment_formats
+                                .len()
                         {
                             return Err(Box::new(ValidationError {
                                 context: format!(
@@ -188,116 +374,177 @@ impl RecordingCommandBuffer {
                             }));
                         }
 
-                        for (color_attachment_index, image_view, inherited_format) in attachments
-                            .color_attachments
-                            .iter()
-                            .zip(inheritance_info.color_attachment_formats.iter().copied())
-                            .enumerate()
-                            .filter_map(|(i, (a, f))| {
-                                a.as_ref().map(|a| (i as u32, &a.image_view, f))
-                            })
-                        {
-                            let required_format = image_view.format();
+                        if let Some(attachments) = &render_pass_state.attachments {
+                            for (color_attachment_index, image_view, inherited_format) in
+                                attachments
+                                    .color_attachments
+                                    .iter()
+                                    .zip(inheritance_info.color_attachment_formats.iter().copied())
+                                    .enumerate()
+                                    .filter_map(|(i, (a, f))| {
+                                        a.as_ref().map(|a| (i as u32, &a.image_view, f))
+                                    })
+                            {
+                                let required_format = image_view.format();
 
-                            if Some(required_format) != inherited_format {
-                                return Err(Box::new(ValidationError {
-                                    context: format!(
-                                        "command_buffers[{}].inheritance_info().render_pass\
-                                        .color_attachment_formats[{}]",
-                                        command_buffer_index, color_attachment_index
-                                    )
-                                    .into(),
-                                    problem: "is not equal to the format of the \
-                                        corresponding color attachment in the current subpass \
-                                        instance"
+                                if Some(required_format) != inherited_format {
+                                    return Err(Box::new(ValidationError {
+                                        context: format!(
+                                            "command_buffers[{}].inheritance_info().render_pass\
+                                            .color_attachment_formats[{}]",
+                                            command_buffer_index, color_attachment_index
+                                        )
                                         .into(),
-                                    vuids: &["VUID-vkCmdExecuteCommands-imageView-06028"],
-                                    ..Default::default()
-                                }));
+                                        problem: "is not equal to the format of the \
+                                            corresponding color attachment in the current subpass \
+                                            instance"
+                                            .into(),
+                                        vuids: &["VUID-vkCmdExecuteCommands-imageView-06028"],
+                                        ..Default::default()
+                                    }));
+                                }
+
+                                if image_view.image().samples()
+                                    != inheritance_info.rasterization_samples
+                                {
+                                    return Err(Box::new(ValidationError {
+                                        context: format!(
+                                            "command_buffers[{}].inheritance_info().render_pass\
+                                            .rasterization_samples",
+                                            command_buffer_index,
+                                        )
+                                        .into(),
+                                        problem: "is not equal to the number of samples of the \
+                                            attachments in the current subpass instance"
+                                            .into(),
+                                        vuids: &["VUID-vkCmdExecuteCommands-pNext-06035"],
+                                        ..Default::default()
+                                    }));
+                                }
                             }
 
-                            if image_view.image().samples()
-                                != inheritance_info.rasterization_samples
+                            if let Some((image_view, format)) = attachments
+                                .depth_attachment
+                                .as_ref()
+                                .map(|a| (&a.image_view, inheritance_info.depth_attachment_format))
                             {
-                                return Err(Box::new(ValidationError {
-                                    context: format!(
-                                        "command_buffers[{}].inheritance_info().render_pass\
-                                        .rasterization_samples",
-                                        command_buffer_index,
-                                    )
-                                    .into(),
-                                    problem: "is not equal to the number of samples of the \
-                                        attachments in the current subpass instance"
+                                if Some(image_view.format()) != format {
+                                    return Err(Box::new(ValidationError {
+                                        context: format!(
+                                            "command_buffers[{}].inheritance_info().render_pass\
+                                            .depth_attachment_format",
+                                            command_buffer_index
+                                        )
                                         .into(),
-                                    vuids: &["VUID-vkCmdExecuteCommands-pNext-06035"],
-                                    ..Default::default()
-                                }));
-                            }
-                        }
+                                        problem: "is not equal to the format of the \
+                                            depth attachment in the current subpass instance"
+                                            .into(),
+                                        vuids: &[
+                                            "VUID-vkCmdExecuteCommands-pDepthAttachment-06029",
+                                        ],
+                                        ..Default::default()
+                                    }));
+                                }
 
-                        if let Some((image_view, format)) = attachments
-                            .depth_attachment
-                            .as_ref()
-                            .map(|a| (&a.image_view, inheritance_info.depth_attachment_format))
-                        {
-                            if Some(image_view.format()) != format {
-                                return Err(Box::new(ValidationError {
-                                    context: format!(
-                                        "command_buffers[{}].inheritance_info().render_pass\
-                                        .depth_attachment_format",
-                                        command_buffer_index
-                                    )
-                                    .into(),
-                                    problem: "is not equal to the format of the \
-                                        depth attachment in the current subpass instance"
+                                if image_view.image().samples()
+                                    != inheritance_info.rasterization_samples
+                                {
+                                    return Err(Box::new(ValidationError {
+                                        context: format!(
+                                            "command_buffers[{}].inheritance_info().render_pass\
+                                            .rasterization_samples",
+                                            command_buffer_index,
+                                        )
                                         .into(),
-                                    vuids: &["VUID-vkCmdExecuteCommands-pDepthAttachment-06029"],
-                                    ..Default::default()
-                                }));
+                                        problem: "is not equal to the number of samples of the \
+                                            attachments in the current subpass instance"
+                                            .into(),
+                                        vuids: &["VUID-vkCmdExecuteCommands-pNext-06036"],
+                                        ..Default::default()
+                                    }));
+                                }
                             }
 
-                            if image_view.image().samples()
-                                != inheritance_info.rasterization_samples
+                            if let Some((image_view, format)) =
+                                attachments.stencil_attachment.as_ref().map(|a| {
+                                    (&a.image_view, inheritance_info.stencil_attachment_format)
+                                })
                             {
-                                return Err(Box::new(ValidationError {
-                                    context: format!(
-                                        "command_buffers[{}].inheritance_info().render_pass\
-                                        .rasterization_samples",
-                                        command_buffer_index,
-                                    )
-                                    .into(),
-                                    problem: "is not equal to the number of samples of the \
-                                        attachments in the current subpass instance"
+                                if Some(image_view.format()) != format {
+                                    return Err(Box::new(ValidationError {
+                                        context: format!(
+                                            "command_buffers[{}].inheritance_info().render_pass\
+                                            .stencil_attachment_format",
+                                            command_buffer_index
+                                        )
                                         .into(),
-                                    vuids: &["VUID-vkCmdExecuteCommands-pNext-06036"],
-                                    ..Default::default()
-                                }));
-                            }
-                        }
+                                        problem: "is not equal to the format of the \
+                                            stencil attachment in the current subpass instance"
+                                            .into(),
+                                        vuids: &[
+                                            "VUID-vkCmdExecuteCommands-pStencilAttachment-06030",
+                                        ],
+                                        ..Default::default()
+                                    }));
+                                }
 
-                        if let Some((image_view, format)) = attachments
-                            .stencil_attachment
-                            .as_ref()
-                            .map(|a| (&a.image_view, inheritance_info.stencil_attachment_format))
+                                if image_view.image().samples()
+                                    != inheritance_info.rasterization_samples
+                                {
+                                    return Err(Box::new(ValidationError {
+                                        context: format!(
+                                            "command_buffers[{}].inheritance_info().render_pass\
+                                            .rasterization_samples",
+                                            command_buffer_index,
+                                        )
+                                        .into(),
+                                        problem: "is not equal to the number of samples of the \
+                                            attachments in the current subpass instance"
+                                            .into(),
+                                        vuids: &["VUID-vkCmdExecuteCommands-pNext-06037"],
+                                        ..Default::default()
+                                    }));
+                                }
+                            }
+                        } else if let Some(
+                            CommandBufferInheritanceRenderPassType::BeginRendering(
+                                current_inheritance_info,
+                            ),
+                        ) = self
+                            .inner
+                            .inheritance_info()
+                            .and_then(|info| info.render_pass.as_ref())
                         {
-                            if Some(image_view.format()) != format {
+                            // The render pass instance is inherited by this secondary command
+                            // buffer, so the attachments are not known. Compare against the
+                            // inherited formats instead.
+                            if inheritance_info.color_attachment_formats
+                                != current_inheritance_info.color_attachment_formats
+                                || inheritance_info.depth_attachment_format
+                                    != current_inheritance_info.depth_attachment_format
+                                || inheritance_info.stencil_attachment_format
+                                    != current_inheritance_info.stencil_attachment_format
+                            {
                                 return Err(Box::new(ValidationError {
                                     context: format!(
-                                        "command_buffers[{}].inheritance_info().render_pass\
-                                        .stencil_attachment_format",
+                                        "command_buffers[{}].inheritance_info().render_pass",
                                         command_buffer_index
                                     )
                                     .into(),
-                                    problem: "is not equal to the format of the \
-                                        stencil attachment in the current subpass instance"
+                                    problem: "the attachment formats are not equal to the \
+                                        attachment formats inherited by this command buffer"
                                         .into(),
-                                    vuids: &["VUID-vkCmdExecuteCommands-pStencilAttachment-06030"],
+                                    vuids: &[
+                                        "VUID-vkCmdExecuteCommands-imageView-06028",
+                                        "VUID-vkCmdExecuteCommands-pDepthAttachment-06029",
+                                        "VUID-vkCmdExecuteCommands-pStencilAttachment-06030",
+                                    ],
                                     ..Default::default()
                                 }));
                             }
 
-                            if image_view.image().samples()
-                                != inheritance_info.rasterization_samples
+                            if inheritance_info.rasterization_samples
+                                != current_inheritance_info.rasterization_samples
                             {
                                 return Err(Box::new(ValidationError {
                                     context: format!(
@@ -306,10 +553,10 @@ impl RecordingCommandBuffer {
                                         command_buffer_index,
                                     )
                                     .into(),
-                                    problem: "is not equal to the number of samples of the \
-                                        attachments in the current subpass instance"
+                                    problem: "is not equal to the number of samples inherited by \
+                                        this command buffer"
                                         .into(),
-                                    vuids: &["VUID-vkCmdExecuteCommands-pNext-06037"],
+                                    vuids: &["VUID-vkCmdExecuteCommands-pNext-06035"],
                                     ..Default::default()
                                 }));
                             }
@@ -546,6 +793,38 @@ impl RecordingCommandBuffer {
     }
 }
 
+/// The dynamic state of a primary command buffer that is set on the secondary command buffers
+/// recorded by `execute_commands_parallel`.
+struct InheritedDynamicState {
+    viewport: HashMap<u32, Viewport>,
+    viewport_with_count: Option<SmallVec<[Viewport; 2]>>,
+    scissor: HashMap<u32, Scissor>,
+    scissor_with_count: Option<SmallVec<[Scissor; 2]>>,
+}
+
+impl InheritedDynamicState {
+    fn set(&self, builder: &mut RecordingCommandBuffer) {
+        // The state was already validated when it was set on the primary command buffer.
+        unsafe {
+            for (&index, viewport) in &self.viewport {
+                builder.set_viewport_unchecked(index, smallvec![viewport.clone()]);
+            }
+
+            if let Some(viewports) = &self.viewport_with_count {
+                builder.set_viewport_with_count_unchecked(viewports.clone());
+            }
+
+            for (&index, &scissor) in &self.scissor {
+                builder.set_scissor_unchecked(index, smallvec![scissor]);
+            }
+
+            if let Some(scissors) = &self.scissor_with_count {
+                builder.set_scissor_with_count_unchecked(scissors.clone());
+            }
+        }
+    }
+}
+
 impl RawRecordingCommandBuffer {
     #[inline]
     pub unsafe fn execute_commands(
@@ -561,12 +840,37 @@ impl RawRecordingCommandBuffer {
         &self,
         command_buffers: impl Iterator<Item = &'a RawCommandBuffer>,
     ) -> Result<(), Box<ValidationError>> {
-        if self.level() != CommandBufferLevel::Primary {
-            return Err(Box::new(ValidationError {
-                problem: "this command buffer is not a primary command buffer".into(),
-                vuids: &["VUID-vkCmdExecuteCommands-bufferlevel"],
-                ..Default::default()
-            }));
+        let device = self.device();
+        let is_secondary = self.level() == CommandBufferLevel::Secondary;
+
+        if is_secondary {
+            if !device.enabled_features().nested_command_buffer {
+                return Err(Box::new(ValidationError {
+                    problem: "this command buffer is a secondary command buffer".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "nested_command_buffer",
+                    )])]),
+                    vuids: &["VUID-vkCmdExecuteCommands-nestedCommandBuffer-09375"],
+                    ..Default::default()
+                }));
+            }
+
+            if self
+                .inheritance_info()
+                .map_or(false, |info| info.render_pass.is_some())
+                && !device.enabled_features().nested_command_buffer_rendering
+            {
+                return Err(Box::new(ValidationError {
+                    problem: "this command buffer is a secondary command buffer, and \
+                        `inheritance_info.render_pass` is `Some`"
+                        .into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "nested_command_buffer_rendering",
+                    )])]),
+                    vuids: &["VUID-vkCmdExecuteCommands-nestedCommandBufferRendering-09377"],
+                    ..Default::default()
+                }));
+            }
         }
 
         if !self
@@ -596,6 +900,49 @@ impl RawRecordingCommandBuffer {
                 }));
             }
 
+            if is_secondary {
+                if command_buffer.usage() == CommandBufferUsage::SimultaneousUse
+                    && !device
+                        .enabled_features()
+                        .nested_command_buffer_simultaneous_use
+                {
+                    return Err(Box::new(ValidationError {
+                        context: format!("command_buffers[{}].usage()", command_buffer_index)
+                            .into(),
+                        problem: "is `CommandBufferUsage::SimultaneousUse`, and this command \
+                            buffer is a secondary command buffer"
+                            .into(),
+                        requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                            Requires::DeviceFeature("nested_command_buffer_simultaneous_use"),
+                        ])]),
+                        vuids: &[
+                            "VUID-vkCmdExecuteCommands-nestedCommandBufferSimultaneousUse-09378",
+                        ],
+                        ..Default::default()
+                    }));
+                }
+
+                let max_command_buffer_nesting_level = device
+                    .physical_device()
+                    .properties()
+                    .max_command_buffer_nesting_level
+                    .unwrap_or(0);
+
+                if command_buffer.nesting_level() >= max_command_buffer_nesting_level {
+                    return Err(Box::new(ValidationError {
+                        context: format!(
+                            "command_buffers[{}].nesting_level()",
+                            command_buffer_index
+                        )
+                        .into(),
+                        problem: "is not less than the `max_command_buffer_nesting_level` limit"
+                            .into(),
+                        vuids: &["VUID-vkCmdExecuteCommands-nestedCommandBuffer-09376"],
+                        ..Default::default()
+                    }));
+                }
+            }
+
             // TODO:
             // VUID-vkCmdExecuteCommands-pCommandBuffers-00094
         }
@@ -637,6 +984,10 @@ impl RawRecordingCommandBuffer {
             .iter()
             .map(|cb| cb.usage())
             .fold(self.usage, min);
+        self.nesting_level = command_buffers
+            .iter()
+            .map(|cb| cb.nesting_level() + 1)
+            .fold(self.nesting_level, max);
 
         self
     }
@@ -665,6 +1016,10 @@ impl RawRecordingCommandBuffer {
             .iter()
             .map(|cb| cb.usage())
             .fold(self.usage, min);
+        self.nesting_level = command_buffers
+            .iter()
+            .map(|cb| cb.nesting_level() + 1)
+            .fold(self.nesting_level, max);
 
         self
     }
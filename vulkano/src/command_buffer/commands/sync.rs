@@ -1,15 +1,620 @@
 use crate::{
-    command_buffer::sys::RawRecordingCommandBuffer,
-    device::{DeviceOwned, QueueFlags},
+    buffer::Subbuffer,
+    command_buffer::{
+        auto::{OwnershipTransfer, RenderPassStateType, Resource, ResourceUseRef2},
+        sys::RawRecordingCommandBuffer,
+        CommandBufferLevel, RecordingCommandBuffer, ResourceInCommand,
+    },
+    device::{Device, DeviceOwned, QueueFlags},
+    image::ImageLayout,
     sync::{
-        event::Event, BufferMemoryBarrier, DependencyFlags, DependencyInfo, ImageMemoryBarrier,
-        MemoryBarrier, PipelineStages,
+        event::Event, AccessFlags, BufferMemoryBarrier, DependencyFlags, DependencyInfo,
+        ImageMemoryBarrier, MemoryBarrier, PipelineStageAccessFlags, PipelineStages,
+        QueueFamilyOwnershipTransfer,
     },
     Requires, RequiresAllOf, RequiresOneOf, ValidationError, Version, VulkanObject,
 };
 use smallvec::SmallVec;
 use std::{ptr, sync::Arc};
 
+/// # Commands to synchronize resource accesses.
+///
+/// The command buffer already inserts the barriers that are needed between the commands that are
+/// recorded into it, so these commands are only needed for dependencies that the automatic
+/// synchronization doesn't know about, or to overlap independent work with a split barrier. The
+/// buffers and images in the barriers are tracked like resources used by any other command: the
+/// command buffer makes sure that previous accesses to them are finished before the barriers take
+/// effect, and an image memory barrier with a layout transition changes the layout that the
+/// following commands expect the image to be in.
+///
+/// Queue family ownership transfers can only be recorded with [`pipeline_barrier`], and these
+/// commands can't be recorded inside a render pass instance, with the exception of memory
+/// barriers between framebuffer-space stages inside a render pass instance that was begun with
+/// [`begin_rendering`], when one of the `shader_tile_image_*_read_access` features is enabled.
+/// These make the attachment writes of previous draws visible to tile image reads in fragment
+/// shaders.
+///
+/// [`pipeline_barrier`]: Self::pipeline_barrier
+/// [`begin_rendering`]: Self::begin_rendering
+impl RecordingCommandBuffer {
+    /// Records a pipeline barrier.
+    ///
+    /// Buffer and image memory barriers can be the release or acquire half of a queue family
+    /// ownership transfer to or from the queue family of the command buffer, as created by
+    /// [`BufferMemoryBarrier::release_acquire`] and [`ImageMemoryBarrier::release_acquire`]. This
+    /// is only possible in a primary command buffer. A release barrier is synchronized with the
+    /// previous commands through its source scope, and must be the last use of the resource in
+    /// the command buffer. An acquire barrier must be the first use of the resource, and its
+    /// destination scope synchronizes it with the following commands. No barriers are inserted
+    /// after a release or before an acquire, and the layout transition of an image is assumed to
+    /// happen in the release, so an acquire expects the image to already be in `new_layout`.
+    pub fn pipeline_barrier(
+        &mut self,
+        dependency_info: DependencyInfo,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_pipeline_barrier(&dependency_info)?;
+        }
+
+        unsafe { Ok(self.pipeline_barrier_unchecked(dependency_info)) }
+    }
+
+    fn validate_pipeline_barrier(
+        &self,
+        dependency_info: &DependencyInfo,
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner
+            .validate_pipeline_barrier(dependency_info)
+            .map_err(|err| err.add_context("dependency_info"))?;
+
+        if let Some(render_pass_state) = &self.builder_state.render_pass {
+            match &render_pass_state.render_pass {
+                RenderPassStateType::BeginRenderPass(_) => {
+                    return Err(Box::new(ValidationError {
+                        problem: "a render pass instance is active".into(),
+                        ..Default::default()
+                    }));
+                }
+                RenderPassStateType::BeginRendering(_) => {
+                    validate_dynamic_rendering_dependency_info(self.device(), dependency_info)?;
+                }
+            }
+        }
+
+        validate_tracked_dependency_info(dependency_info, Some(self.inner.queue_family_index()))
+            .map_err(|err| err.add_context("dependency_info"))?;
+
+        if self.level() == CommandBufferLevel::Secondary
+            && (dependency_info
+                .buffer_memory_barriers
+                .iter()
+                .any(|barrier| barrier.queue_family_ownership_transfer.is_some())
+                || dependency_info
+                    .image_memory_barriers
+                    .iter()
+                    .any(|barrier| barrier.queue_family_ownership_transfer.is_some()))
+        {
+            return Err(Box::new(ValidationError {
+                context: "dependency_info".into(),
+                problem: "the command buffer is a secondary command buffer, and \
+                    `queue_family_ownership_transfer` is `Some` for one of the barriers"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn pipeline_barrier_unchecked(
+        &mut self,
+        dependency_info: DependencyInfo,
+    ) -> &mut Self {
+        let resources = self.dependency_info_resources([&dependency_info]);
+
+        self.add_command(
+            "pipeline_barrier",
+            resources,
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.pipeline_barrier_unchecked(&dependency_info);
+            },
+        );
+
+        self
+    }
+
+    /// Sets an event from the device, once the source scope of `dependency_info` has completed.
+    ///
+    /// The event must later be waited on with the same dependency info, which is checked by
+    /// [`wait_events`] if it is called on the same command buffer. The barriers take effect when
+    /// the event is waited on, so the resources in `dependency_info` are not tracked by this
+    /// command.
+    ///
+    /// [`wait_events`]: Self::wait_events
+    pub fn set_event(
+        &mut self,
+        event: Arc<Event>,
+        dependency_info: DependencyInfo,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_set_event(&event, &dependency_info)?;
+        }
+
+        unsafe { Ok(self.set_event_unchecked(event, dependency_info)) }
+    }
+
+    fn validate_set_event(
+        &self,
+        event: &Event,
+        dependency_info: &DependencyInfo,
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_set_event(event, dependency_info)?;
+
+        if self.builder_state.render_pass.is_some() {
+            return Err(Box::new(ValidationError {
+                problem: "a render pass instance is active".into(),
+                vuids: &["VUID-vkCmdSetEvent2-renderpass"],
+                ..Default::default()
+            }));
+        }
+
+        validate_tracked_dependency_info(dependency_info, None)
+            .map_err(|err| err.add_context("dependency_info"))?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_event_unchecked(
+        &mut self,
+        event: Arc<Event>,
+        dependency_info: DependencyInfo,
+    ) -> &mut Self {
+        self.builder_state
+            .events
+            .insert(event.handle(), dependency_info.clone());
+
+        self.add_command(
+            "set_event",
+            Default::default(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.set_event_unchecked(&event, &dependency_info);
+            },
+        );
+
+        self
+    }
+
+    /// Waits for events to be set, and then applies the barriers in the dependency info of each
+    /// event.
+    ///
+    /// The dependency info of each event must be the same as the one that the event was set with.
+    /// For events that were set on this command buffer, this is checked.
+    pub fn wait_events(
+        &mut self,
+        events: SmallVec<[(Arc<Event>, DependencyInfo); 4]>,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_wait_events(&events)?;
+        }
+
+        unsafe { Ok(self.wait_events_unchecked(events)) }
+    }
+
+    fn validate_wait_events(
+        &self,
+        events: &[(Arc<Event>, DependencyInfo)],
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_wait_events(events)?;
+
+        if self.builder_state.render_pass.is_some() {
+            return Err(Box::new(ValidationError {
+                problem: "a render pass instance is active".into(),
+                ..Default::default()
+            }));
+        }
+
+        for (event_index, (event, dependency_info)) in events.iter().enumerate() {
+            validate_tracked_dependency_info(dependency_info, None)
+                .map_err(|err| err.add_context(format!("events[{}].1", event_index)))?;
+
+            if let Some(set_dependency_info) = self.builder_state.events.get(&event.handle()) {
+                if !dependency_info_eq(dependency_info, set_dependency_info) {
+                    return Err(Box::new(ValidationError {
+                        context: format!("events[{}].1", event_index).into(),
+                        problem: "is not the same as the dependency info that the event was set \
+                            with in this command buffer"
+                            .into(),
+                        ..Default::default()
+                    }));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn wait_events_unchecked(
+        &mut self,
+        events: SmallVec<[(Arc<Event>, DependencyInfo); 4]>,
+    ) -> &mut Self {
+        let resources = self
+            .dependency_info_resources(events.iter().map(|(_, dependency_info)| dependency_info));
+
+        self.add_command(
+            "wait_events",
+            resources,
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.wait_events_unchecked(&events);
+            },
+        );
+
+        self
+    }
+
+    /// Resets an event from the device, once `stages` of the previous commands have completed.
+    pub fn reset_event(
+        &mut self,
+        event: Arc<Event>,
+        stages: PipelineStages,
+    ) -> Result<&mut Self, Box<ValidationError>> {
+        if self.device().validation_enabled() {
+            self.validate_reset_event(&event, stages)?;
+        }
+
+        unsafe { Ok(self.reset_event_unchecked(event, stages)) }
+    }
+
+    fn validate_reset_event(
+        &self,
+        event: &Event,
+        stages: PipelineStages,
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner.validate_reset_event(event, stages)?;
+
+        if self.builder_state.render_pass.is_some() {
+            return Err(Box::new(ValidationError {
+                problem: "a render pass instance is active".into(),
+                vuids: &["VUID-vkCmdResetEvent2-renderpass"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn reset_event_unchecked(
+        &mut self,
+        event: Arc<Event>,
+        stages: PipelineStages,
+    ) -> &mut Self {
+        self.builder_state.events.remove(&event.handle());
+
+        self.add_command(
+            "reset_event",
+            Default::default(),
+            move |out: &mut RawRecordingCommandBuffer| {
+                out.reset_event_unchecked(&event, stages);
+            },
+        );
+
+        self
+    }
+
+    /// Returns the resources that are accessed by the barriers in `dependency_infos`, which are
+    /// treated as accesses by the destination scope of each barrier, or by the source scope for
+    /// the release half of a queue family ownership transfer.
+    fn dependency_info_resources<'a>(
+        &self,
+        dependency_infos: impl IntoIterator<Item = &'a DependencyInfo>,
+    ) -> Vec<(ResourceUseRef2, Resource)> {
+        let queue_family_index = self.inner.queue_family_index();
+        let queue_flags = self.inner.queue_family_properties().queue_flags;
+        let memory_access = |dst_stages: PipelineStages, dst_access: AccessFlags| {
+            PipelineStageAccessFlags::from_stages_access(
+                dst_stages.expand(queue_flags),
+                dst_access.expand(),
+            )
+        };
+
+        let mut resources = Vec::new();
+        let mut buffer_index = 0;
+        let mut image_index = 0;
+
+        for dependency_info in dependency_infos {
+            for barrier in &dependency_info.buffer_memory_barriers {
+                let buffer = Subbuffer::from(barrier.buffer.clone());
+                let range = barrier.range.clone();
+                let resource = match barrier.queue_family_ownership_transfer {
+                    Some(transfer) if is_release(transfer, queue_family_index) => {
+                        Resource::BufferOwnershipTransfer {
+                            buffer,
+                            range,
+                            memory_access: memory_access(barrier.src_stages, barrier.src_access),
+                            transfer: OwnershipTransfer::Release,
+                        }
+                    }
+                    Some(_) => Resource::BufferOwnershipTransfer {
+                        buffer,
+                        range,
+                        memory_access: memory_access(barrier.dst_stages, barrier.dst_access),
+                        transfer: OwnershipTransfer::Acquire,
+                    },
+                    None => Resource::Buffer {
+                        buffer,
+                        range,
+                        memory_access: memory_access(barrier.dst_stages, barrier.dst_access),
+                    },
+                };
+
+                resources.push((
+                    ResourceInCommand::BufferMemoryBarrier {
+                        index: buffer_index,
+                    }
+                    .into(),
+                    resource,
+                ));
+                buffer_index += 1;
+            }
+
+            for barrier in &dependency_info.image_memory_barriers {
+                let image = barrier.image.clone();
+                let subresource_range = barrier.subresource_range.clone();
+                let resource = match barrier.queue_family_ownership_transfer {
+                    Some(transfer) if is_release(transfer, queue_family_index) => {
+                        Resource::ImageOwnershipTransfer {
+                            image,
+                            subresource_range,
+                            memory_access: memory_access(barrier.src_stages, barrier.src_access),
+                            old_layout: barrier.old_layout,
+                            new_layout: barrier.new_layout,
+                            transfer: OwnershipTransfer::Release,
+                        }
+                    }
+                    Some(_) => Resource::ImageOwnershipTransfer {
+                        image,
+                        subresource_range,
+                        memory_access: memory_access(barrier.dst_stages, barrier.dst_access),
+                        old_layout: barrier.old_layout,
+                        new_layout: barrier.new_layout,
+                        transfer: OwnershipTransfer::Acquire,
+                    },
+                    None => Resource::Image {
+                        image,
+                        subresource_range,
+                        memory_access: memory_access(barrier.dst_stages, barrier.dst_access),
+                        start_layout: barrier.old_layout,
+                        end_layout: barrier.new_layout,
+                    },
+                };
+
+                resources.push((
+                    ResourceInCommand::ImageMemoryBarrier { index: image_index }.into(),
+                    resource,
+                ));
+                image_index += 1;
+            }
+        }
+
+        resources
+    }
+}
+
+/// Checks that the barriers in `dependency_info` can be tracked by `RecordingCommandBuffer`.
+///
+/// Queue family ownership transfers are only allowed if `queue_family_index` is `Some`, and they
+/// must then be to or from that queue family.
+fn validate_tracked_dependency_info(
+    dependency_info: &DependencyInfo,
+    queue_family_index: Option<u32>,
+) -> Result<(), Box<ValidationError>> {
+    let validate_transfer = |transfer: &Option<QueueFamilyOwnershipTransfer>| {
+        let Some(transfer) = *transfer else {
+            return Ok(());
+        };
+
+        let Some(queue_family_index) = queue_family_index else {
+            return Err("`queue_family_ownership_transfer` is `Some`");
+        };
+
+        if let QueueFamilyOwnershipTransfer::ExclusiveBetweenLocal {
+            src_index,
+            dst_index,
+        } = transfer
+        {
+            if src_index == dst_index
+                || src_index != queue_family_index && dst_index != queue_family_index
+            {
+                return Err(
+                    "`queue_family_ownership_transfer` is not a transfer to or from \
+                    the queue family of the command buffer",
+                );
+            }
+        }
+
+        Ok(())
+    };
+
+    for (barrier_index, barrier) in dependency_info.buffer_memory_barriers.iter().enumerate() {
+        validate_transfer(&barrier.queue_family_ownership_transfer).map_err(|problem| {
+            Box::new(ValidationError {
+                context: format!("buffer_memory_barriers[{}]", barrier_index).into(),
+                problem: problem.into(),
+                ..Default::default()
+            })
+        })?;
+    }
+
+    for (barrier_index, barrier) in dependency_info.image_memory_barriers.iter().enumerate() {
+        validate_transfer(&barrier.queue_family_ownership_transfer).map_err(|problem| {
+            Box::new(ValidationError {
+                context: format!("image_memory_barriers[{}]", barrier_index).into(),
+                problem: problem.into(),
+                ..Default::default()
+            })
+        })?;
+
+        if matches!(
+            barrier.new_layout,
+            ImageLayout::Undefined | ImageLayout::Preinitialized
+        ) {
+            return Err(Box::new(ValidationError {
+                context: format!("image_memory_barriers[{}]", barrier_index).into(),
+                problem: "`new_layout` is `ImageLayout::Undefined` or \
+                    `ImageLayout::Preinitialized`"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `dependency_info` can be used for a pipeline barrier inside a render pass instance
+/// that was begun with `begin_rendering`.
+///
+/// Such barriers are only used to make the writes to the attachments visible to tile image reads
+/// in later fragment shader invocations.
+fn validate_dynamic_rendering_dependency_info(
+    device: &Device,
+    dependency_info: &DependencyInfo,
+) -> Result<(), Box<ValidationError>> {
+    let enabled_features = device.enabled_features();
+
+    if !(enabled_features.shader_tile_image_color_read_access
+        || enabled_features.shader_tile_image_depth_read_access
+        || enabled_features.shader_tile_image_stencil_read_access)
+    {
+        return Err(Box::new(ValidationError {
+            problem: "a render pass instance is active".into(),
+            requires_one_of: RequiresOneOf(&[
+                RequiresAllOf(&[Requires::DeviceFeature(
+                    "shader_tile_image_color_read_access",
+                )]),
+                RequiresAllOf(&[Requires::DeviceFeature(
+                    "shader_tile_image_depth_read_access",
+                )]),
+                RequiresAllOf(&[Requires::DeviceFeature(
+                    "shader_tile_image_stencil_read_access",
+                )]),
+            ]),
+            vuids: &["VUID-vkCmdPipelineBarrier2-None-09553"],
+            ..Default::default()
+        }));
+    }
+
+    if !(dependency_info.buffer_memory_barriers.is_empty()
+        && dependency_info.image_memory_barriers.is_empty())
+    {
+        return Err(Box::new(ValidationError {
+            problem: "a render pass instance is active, and \
+                `dependency_info.buffer_memory_barriers` or \
+                `dependency_info.image_memory_barriers` is not empty"
+                .into(),
+            vuids: &["VUID-vkCmdPipelineBarrier2-None-09554"],
+            ..Default::default()
+        }));
+    }
+
+    if !dependency_info
+        .dependency_flags
+        .intersects(DependencyFlags::BY_REGION)
+    {
+        return Err(Box::new(ValidationError {
+            problem: "a render pass instance is active, and \
+                `dependency_info.dependency_flags` does not contain `DependencyFlags::BY_REGION`"
+                .into(),
+            vuids: &["VUID-vkCmdPipelineBarrier2-dependencyFlags-07891"],
+            ..Default::default()
+        }));
+    }
+
+    let framebuffer_stages = PipelineStages::EARLY_FRAGMENT_TESTS
+        | PipelineStages::FRAGMENT_SHADER
+        | PipelineStages::LATE_FRAGMENT_TESTS
+        | PipelineStages::COLOR_ATTACHMENT_OUTPUT;
+
+    for (barrier_index, barrier) in dependency_info.memory_barriers.iter().enumerate() {
+        if !(barrier.src_stages - framebuffer_stages).is_empty()
+            || !(barrier.dst_stages - framebuffer_stages).is_empty()
+        {
+            return Err(Box::new(ValidationError {
+                context: format!("dependency_info.memory_barriers[{}]", barrier_index).into(),
+                problem: "a render pass instance is active, and `src_stages` or `dst_stages` \
+                    contains stages other than framebuffer-space stages"
+                    .into(),
+                vuids: &["VUID-vkCmdPipelineBarrier2-srcStageMask-09556"],
+                ..Default::default()
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns whether `transfer` is the release half of a queue family ownership transfer, when
+/// recorded in a command buffer for `queue_family_index`.
+fn is_release(transfer: QueueFamilyOwnershipTransfer, queue_family_index: u32) -> bool {
+    match transfer {
+        QueueFamilyOwnershipTransfer::ExclusiveBetweenLocal { src_index, .. } => {
+            src_index == queue_family_index
+        }
+        QueueFamilyOwnershipTransfer::ExclusiveToExternal { .. }
+        | QueueFamilyOwnershipTransfer::ExclusiveToForeign { .. }
+        | QueueFamilyOwnershipTransfer::ConcurrentToExternal
+        | QueueFamilyOwnershipTransfer::ConcurrentToForeign => true,
+        QueueFamilyOwnershipTransfer::ExclusiveFromExternal { .. }
+        | QueueFamilyOwnershipTransfer::ExclusiveFromForeign { .. }
+        | QueueFamilyOwnershipTransfer::ConcurrentFromExternal
+        | QueueFamilyOwnershipTransfer::ConcurrentFromForeign => false,
+    }
+}
+
+/// Returns whether two dependency infos describe the same barriers.
+fn dependency_info_eq(a: &DependencyInfo, b: &DependencyInfo) -> bool {
+    a.dependency_flags == b.dependency_flags
+        && a.memory_barriers.len() == b.memory_barriers.len()
+        && a.memory_barriers
+            .iter()
+            .zip(&b.memory_barriers)
+            .all(|(a, b)| {
+                a.src_stages == b.src_stages
+                    && a.src_access == b.src_access
+                    && a.dst_stages == b.dst_stages
+                    && a.dst_access == b.dst_access
+            })
+        && a.buffer_memory_barriers.len() == b.buffer_memory_barriers.len()
+        && a.buffer_memory_barriers
+            .iter()
+            .zip(&b.buffer_memory_barriers)
+            .all(|(a, b)| {
+                a.src_stages == b.src_stages
+                    && a.src_access == b.src_access
+                    && a.dst_stages == b.dst_stages
+                    && a.dst_access == b.dst_access
+                    && a.buffer == b.buffer
+                    && a.range == b.range
+            })
+        && a.image_memory_barriers.len() == b.image_memory_barriers.len()
+        && a.image_memory_barriers
+            .iter()
+            .zip(&b.image_memory_barriers)
+            .all(|(a, b)| {
+                a.src_stages == b.src_stages
+                    && a.src_access == b.src_access
+                    && a.dst_stages == b.dst_stages
+                    && a.dst_access == b.dst_access
+                    && a.old_layout == b.old_layout
+                    && a.new_layout == b.new_layout
+                    && a.image == b.image
+                    && a.subresource_range == b.subresource_range
+            })
+}
+
 impl RawRecordingCommandBuffer {
     #[inline]
     pub unsafe fn pipeline_barrier(
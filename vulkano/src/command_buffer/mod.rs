@@ -96,20 +96,20 @@
 
 #[allow(unused_imports)] // everything is exported for future-proofing
 pub use self::commands::{
-    acceleration_structure::*, clear::*, copy::*, debug::*, dynamic_state::*, pipeline::*,
-    query::*, render_pass::*, secondary::*, sync::*,
+    acceleration_structure::*, clear::*, conditional_rendering::*, copy::*, debug::*,
+    dynamic_state::*, pipeline::*, query::*, render_pass::*, secondary::*, sync::*,
 };
 pub use self::{
-    auto::{CommandBuffer, RecordingCommandBuffer},
+    auto::{CommandBuffer, CommandBufferStatistics, RecordingCommandBuffer},
     sys::CommandBufferBeginInfo,
     traits::{CommandBufferExecError, CommandBufferExecFuture},
 };
 use crate::{
     buffer::{Buffer, Subbuffer},
-    device::{Device, DeviceOwned},
+    device::{Device, DeviceOwned, FrameBoundary, Queue, Win32KeyedMutexAcquireReleaseInfo},
     format::{Format, FormatFeatures},
     image::{Image, ImageAspects, ImageLayout, ImageSubresourceRange, SampleCount},
-    macros::vulkan_enum,
+    macros::{vulkan_bitflags, vulkan_enum},
     query::{QueryControlFlags, QueryPipelineStatisticFlags},
     range_map::RangeMap,
     render_pass::{Framebuffer, Subpass},
@@ -117,7 +117,7 @@ use crate::{
         semaphore::{Semaphore, SemaphoreType},
         PipelineStageAccessFlags, PipelineStages,
     },
-    DeviceSize, Requires, RequiresAllOf, RequiresOneOf, ValidationError,
+    DeviceSize, Requires, RequiresAllOf, RequiresOneOf, ValidationError, VulkanObject,
 };
 #[cfg(doc)]
 use crate::{
@@ -126,6 +126,7 @@ use crate::{
 };
 use ahash::HashMap;
 use bytemuck::{Pod, Zeroable};
+use smallvec::SmallVec;
 use std::{ops::Range, sync::Arc};
 
 pub mod allocator;
@@ -759,6 +760,13 @@ pub enum CommandBufferUsage {
     /// The command buffer can be executed multiple times in parallel on different queues.
     /// If it's a secondary command buffer, it can be recorded to multiple primary command buffers
     /// at once.
+    ///
+    /// A primary command buffer with this usage can be submitted again to the same queue while an
+    /// earlier submission is still pending, for example to execute the same pre-recorded commands
+    /// every frame. Resources that it writes to are then not considered to be in use by its own
+    /// earlier submissions, because a memory barrier is recorded at the end of the command buffer.
+    /// Submitting it to a different queue while it is pending still requires the resources that
+    /// it writes to be free.
     SimultaneousUse = ash::vk::CommandBufferUsageFlags::SIMULTANEOUS_USE.as_raw(),
 }
 
@@ -772,6 +780,11 @@ impl From<CommandBufferUsage> for ash::vk::CommandBufferUsageFlags {
 /// Parameters to submit command buffers to a queue.
 #[derive(Clone, Debug)]
 pub struct SubmitInfo {
+    /// Additional properties of the submission.
+    ///
+    /// The default value is empty.
+    pub flags: SubmitFlags,
+
     /// The semaphores to wait for before beginning the execution of this batch of
     /// command buffer operations.
     ///
@@ -789,6 +802,27 @@ pub struct SubmitInfo {
     /// The default value is empty.
     pub signal_semaphores: Vec<SemaphoreSubmitInfo>,
 
+    /// Information about the frame that this batch belongs to, for capture tools and profilers.
+    ///
+    /// If this is `Some`, the [`ext_frame_boundary`] extension and the [`frame_boundary`]
+    /// feature must be enabled on the device.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`ext_frame_boundary`]: crate::device::DeviceExtensions::ext_frame_boundary
+    /// [`frame_boundary`]: DeviceFeatures::frame_boundary
+    pub frame_boundary: Option<FrameBoundary>,
+
+    /// Keyed mutexes of imported Direct3D resources to acquire before executing the command
+    /// buffers, and to release afterwards.
+    ///
+    /// If this is `Some`, the [`khr_win32_keyed_mutex`] extension must be enabled on the device.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`khr_win32_keyed_mutex`]: crate::device::DeviceExtensions::khr_win32_keyed_mutex
+    pub win32_keyed_mutex: Option<Win32KeyedMutexAcquireReleaseInfo>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -796,9 +830,12 @@ impl Default for SubmitInfo {
     #[inline]
     fn default() -> Self {
         Self {
+            flags: SubmitFlags::empty(),
             wait_semaphores: Vec::new(),
             command_buffers: Vec::new(),
             signal_semaphores: Vec::new(),
+            frame_boundary: None,
+            win32_keyed_mutex: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -807,12 +844,31 @@ impl Default for SubmitInfo {
 impl SubmitInfo {
     pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
         let &Self {
+            flags,
             ref wait_semaphores,
             ref command_buffers,
             ref signal_semaphores,
+            ref frame_boundary,
+            ref win32_keyed_mutex,
             _ne: _,
         } = self;
 
+        flags.validate_device(device).map_err(|err| {
+            err.add_context("flags")
+                .set_vuids(&["VUID-VkSubmitInfo2-flags-parameter"])
+        })?;
+
+        if flags.intersects(SubmitFlags::PROTECTED) && !device.enabled_features().protected_memory {
+            return Err(Box::new(ValidationError {
+                context: "flags".into(),
+                problem: "contains `SubmitFlags::PROTECTED`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "protected_memory",
+                )])]),
+                vuids: &["VUID-VkSubmitInfo2-flags-03885"],
+            }));
+        }
+
         for (index, semaphore_submit_info) in wait_semaphores.iter().enumerate() {
             semaphore_submit_info
                 .validate(device)
@@ -823,6 +879,35 @@ impl SubmitInfo {
             command_buffer_submit_info
                 .validate(device)
                 .map_err(|err| err.add_context(format!("command_buffers[{}]", index)))?;
+
+            let is_protected = command_buffer_submit_info.command_buffer.is_protected();
+
+            if flags.intersects(SubmitFlags::PROTECTED) {
+                if !is_protected {
+                    return Err(Box::new(ValidationError {
+                        problem: format!(
+                            "`flags` contains `SubmitFlags::PROTECTED`, but \
+                            `command_buffers[{}].command_buffer` is not a protected command \
+                            buffer",
+                            index
+                        )
+                        .into(),
+                        vuids: &["VUID-VkSubmitInfo2-flags-03886"],
+                        ..Default::default()
+                    }));
+                }
+            } else if is_protected {
+                return Err(Box::new(ValidationError {
+                    problem: format!(
+                        "`flags` does not contain `SubmitFlags::PROTECTED`, but \
+                        `command_buffers[{}].command_buffer` is a protected command buffer",
+                        index
+                    )
+                    .into(),
+                    vuids: &["VUID-VkSubmitInfo2-flags-03887"],
+                    ..Default::default()
+                }));
+            }
         }
 
         for (index, semaphore_submit_info) in signal_semaphores.iter().enumerate() {
@@ -850,6 +935,18 @@ impl SubmitInfo {
             }
         }
 
+        if let Some(frame_boundary) = frame_boundary {
+            frame_boundary
+                .validate(device)
+                .map_err(|err| err.add_context("frame_boundary"))?;
+        }
+
+        if let Some(win32_keyed_mutex) = win32_keyed_mutex {
+            win32_keyed_mutex
+                .validate(device)
+                .map_err(|err| err.add_context("win32_keyed_mutex"))?;
+        }
+
         // unsafe
         // VUID-VkSubmitInfo2-semaphore-03882
         // VUID-VkSubmitInfo2-semaphore-03883
@@ -859,6 +956,22 @@ impl SubmitInfo {
     }
 }
 
+vulkan_bitflags! {
+    #[non_exhaustive]
+
+    /// Flags specifying additional properties of a queue submission.
+    SubmitFlags = SubmitFlags(u32);
+
+    /// The submission is protected. All of its command buffers must be protected, and the queue
+    /// must have been created with the [`QueueCreateFlags::PROTECTED`] flag.
+    ///
+    /// The [`protected_memory`] feature must be enabled on the device.
+    ///
+    /// [`QueueCreateFlags::PROTECTED`]: crate::device::QueueCreateFlags::PROTECTED
+    /// [`protected_memory`]: DeviceFeatures::protected_memory
+    PROTECTED = PROTECTED,
+}
+
 /// Parameters for a command buffer in a queue submit operation.
 #[derive(Clone, Debug)]
 pub struct CommandBufferSubmitInfo {
@@ -1151,6 +1264,8 @@ impl SemaphoreSubmitInfo {
 pub struct CommandBufferState {
     has_been_submitted: bool,
     pending_submits: u32,
+    // The queues that the pending submissions were made to, and how many there are for each.
+    pending_queues: SmallVec<[(ash::vk::Queue, u32); 1]>,
 }
 
 impl CommandBufferState {
@@ -1162,13 +1277,43 @@ impl CommandBufferState {
         self.pending_submits != 0
     }
 
-    pub(crate) unsafe fn add_queue_submit(&mut self) {
+    /// Returns the number of pending submissions, if they were all made to `queue`.
+    pub(crate) fn pending_submits_only_on(&self, queue: &Queue) -> Option<u32> {
+        match self.pending_queues.as_slice() {
+            [] => Some(0),
+            &[(handle, count)] if handle == queue.handle() => Some(count),
+            _ => None,
+        }
+    }
+
+    pub(crate) unsafe fn add_queue_submit(&mut self, queue: &Queue) {
         self.has_been_submitted = true;
         self.pending_submits += 1;
+
+        match self
+            .pending_queues
+            .iter_mut()
+            .find(|(handle, _)| *handle == queue.handle())
+        {
+            Some((_, count)) => *count += 1,
+            None => self.pending_queues.push((queue.handle(), 1)),
+        }
     }
 
-    pub(crate) unsafe fn set_submit_finished(&mut self) {
+    pub(crate) unsafe fn set_submit_finished(&mut self, queue: &Queue) {
         self.pending_submits -= 1;
+
+        if let Some(index) = self
+            .pending_queues
+            .iter()
+            .position(|&(handle, _)| handle == queue.handle())
+        {
+            self.pending_queues[index].1 -= 1;
+
+            if self.pending_queues[index].1 == 0 {
+                self.pending_queues.swap_remove(index);
+            }
+        }
     }
 }
 
@@ -1246,8 +1391,10 @@ impl From<ResourceUseRef> for SecondaryResourceUseRef {
 #[non_exhaustive]
 pub enum ResourceInCommand {
     AccelerationStructure { index: u32 },
+    BufferMemoryBarrier { index: u32 },
     ColorAttachment { index: u32 },
     ColorResolveAttachment { index: u32 },
+    ConditionalRenderingBuffer,
     DepthStencilAttachment,
     DepthStencilResolveAttachment,
     DescriptorSet { set: u32, binding: u32, index: u32 },
@@ -1257,10 +1404,15 @@ pub enum ResourceInCommand {
     GeometryInstancesData,
     GeometryTrianglesTransformData { index: u32 },
     GeometryTrianglesIndexData { index: u32 },
+    GeometryTrianglesMotionVertexData { index: u32 },
+    GeometryTrianglesOpacityMicromap { index: u32 },
+    GeometryTrianglesOpacityMicromapIndexData { index: u32 },
     GeometryTrianglesVertexData { index: u32 },
     ImageMemoryBarrier { index: u32 },
     IndexBuffer,
     IndirectBuffer,
+    MicromapData,
+    MicromapTriangleArray,
     ScratchData,
     SecondaryCommandBuffer { index: u32 },
     Source,
@@ -382,6 +382,19 @@ impl CommandPoolCreateInfo {
                 .set_vuids(&["VUID-VkCommandPoolCreateInfo-flags-parameter"])
         })?;
 
+        if flags.intersects(CommandPoolCreateFlags::PROTECTED)
+            && !device.enabled_features().protected_memory
+        {
+            return Err(Box::new(ValidationError {
+                context: "flags".into(),
+                problem: "contains `CommandPoolCreateFlags::PROTECTED`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "protected_memory",
+                )])]),
+                vuids: &["VUID-VkCommandPoolCreateInfo-flags-02860"],
+            }));
+        }
+
         if queue_family_index >= device.physical_device().queue_family_properties().len() as u32 {
             return Err(Box::new(ValidationError {
                 context: "queue_family_index".into(),
@@ -409,12 +422,16 @@ vulkan_bitflags! {
     /// Command buffers allocated from this pool can be reset individually.
     RESET_COMMAND_BUFFER = RESET_COMMAND_BUFFER,
 
-    /* TODO: enable
-    // TODO: document
+    /// Command buffers allocated from this pool are protected, and can only be submitted to
+    /// protected-capable queues as part of a protected submission.
+    ///
+    /// The [`protected_memory`] feature must be enabled on the device.
+    ///
+    /// [`protected_memory`]: crate::device::DeviceFeatures::protected_memory
     PROTECTED = PROTECTED
     RequiresOneOf([
         RequiresAllOf([APIVersion(V1_1)])
-    ]), */
+    ]),
 }
 
 vulkan_bitflags! {
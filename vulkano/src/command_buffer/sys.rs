@@ -1,5 +1,6 @@
 use super::{
     allocator::{CommandBufferAlloc, CommandBufferAllocator},
+    pool::CommandPoolCreateFlags,
     CommandBufferInheritanceInfo, CommandBufferLevel, CommandBufferUsage,
 };
 use crate::{
@@ -31,6 +32,7 @@ pub struct RawRecordingCommandBuffer {
     // Must be `None` in a primary command buffer and `Some` in a secondary command buffer.
     inheritance_info: Option<CommandBufferInheritanceInfo>,
     pub(super) usage: CommandBufferUsage,
+    pub(super) nesting_level: u32,
 }
 
 impl RawRecordingCommandBuffer {
@@ -198,6 +200,7 @@ impl RawRecordingCommandBuffer {
             inheritance_info,
             queue_family_index,
             usage,
+            nesting_level: 0,
         })
     }
 
@@ -230,12 +233,31 @@ impl RawRecordingCommandBuffer {
         self.usage
     }
 
+    /// Returns whether the command buffer is protected, meaning that it was allocated from a
+    /// command pool with the [`CommandPoolCreateFlags::PROTECTED`] flag.
+    #[inline]
+    pub fn is_protected(&self) -> bool {
+        self.allocation
+            .pool
+            .flags()
+            .intersects(CommandPoolCreateFlags::PROTECTED)
+    }
+
     /// Returns the inheritance info of the command buffer, if it is a secondary command buffer.
     #[inline]
     pub fn inheritance_info(&self) -> Option<&CommandBufferInheritanceInfo> {
         self.inheritance_info.as_ref()
     }
 
+    /// Returns the nesting level of the command buffer.
+    ///
+    /// This is 0 for command buffers that have not executed any secondary command buffers, and one
+    /// more than the highest nesting level of the executed secondary command buffers otherwise.
+    #[inline]
+    pub fn nesting_level(&self) -> u32 {
+        self.nesting_level
+    }
+
     pub(in crate::command_buffer) fn queue_family_properties(&self) -> &QueueFamilyProperties {
         &self.device().physical_device().queue_family_properties()[self.queue_family_index as usize]
     }
@@ -359,11 +381,27 @@ impl RawCommandBuffer {
         self.inner.usage
     }
 
+    /// Returns whether the command buffer is protected, meaning that it was allocated from a
+    /// command pool with the [`CommandPoolCreateFlags::PROTECTED`] flag.
+    #[inline]
+    pub fn is_protected(&self) -> bool {
+        self.inner.is_protected()
+    }
+
     /// Returns the inheritance info of the command buffer, if it is a secondary command buffer.
     #[inline]
     pub fn inheritance_info(&self) -> Option<&CommandBufferInheritanceInfo> {
         self.inner.inheritance_info.as_ref()
     }
+
+    /// Returns the nesting level of the command buffer.
+    ///
+    /// This is 0 for command buffers that have not executed any secondary command buffers, and one
+    /// more than the highest nesting level of the executed secondary command buffers otherwise.
+    #[inline]
+    pub fn nesting_level(&self) -> u32 {
+        self.inner.nesting_level
+    }
 }
 
 unsafe impl VulkanObject for RawCommandBuffer {
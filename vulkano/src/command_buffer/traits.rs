@@ -6,14 +6,16 @@ use crate::{
     swapchain::Swapchain,
     sync::{
         future::{
-            now, queue_submit, AccessCheckError, AccessError, GpuFuture, NowFuture,
-            SubmitAnyBuilder,
+            now, queue_submit, submit_signal_semaphore, AccessCheckError, AccessError, GpuFuture,
+            NowFuture, SubmitAnyBuilder,
         },
+        semaphore::Semaphore,
         PipelineStages,
     },
     DeviceSize, Validated, VulkanError, VulkanObject,
 };
 use parking_lot::Mutex;
+use smallvec::smallvec;
 use std::{
     borrow::Cow,
     error::Error,
@@ -84,6 +86,13 @@ impl CommandBuffer {
     /// `std::mem::forget` on that object and "unlock" these resources. For more information about
     /// this problem, search the web for "rust thread scoped leakpocalypse".
     ///
+    /// If `future` is executed on a different queue than `queue`, then a semaphore is
+    /// automatically signaled after `future` and waited on before executing the command buffer,
+    /// as if [`then_signal_semaphore`] had been called on `future`. For example, work on a compute
+    /// queue can gate a submission on a graphics queue this way.
+    ///
+    /// [`then_signal_semaphore`]: GpuFuture::then_signal_semaphore
+    ///
     /// # Panics
     ///
     /// - Panics if the device of the command buffer is not the same as the device of the future.
@@ -98,12 +107,13 @@ impl CommandBuffer {
     {
         assert_eq!(self.device().handle(), future.device().handle());
 
-        if !future.queue_change_allowed() {
-            assert_eq!(future.queue().unwrap(), queue);
-        }
+        let queue_change =
+            !future.queue_change_allowed() && future.queue().as_ref() != Some(&queue);
 
         Ok(CommandBufferExecFuture {
             previous: future,
+            queue_change,
+            wait_semaphore: Mutex::new(None),
             command_buffer: self,
             queue,
             submitted: Mutex::new(false),
@@ -121,6 +131,10 @@ where
     F: GpuFuture,
 {
     previous: F,
+    // True if `previous` is executed on a different queue, and must signal `wait_semaphore`.
+    queue_change: bool,
+    // The semaphore that is signaled after `previous`, once it has been submitted.
+    wait_semaphore: Mutex<Option<Arc<Semaphore>>>,
     command_buffer: Arc<CommandBuffer>,
     queue: Arc<Queue>,
     // True if the command buffer has already been submitted.
@@ -137,19 +151,37 @@ where
     // Implementation of `build_submission`. Doesn't check whenever the future was already flushed.
     // You must make sure to not submit same command buffer multiple times.
     unsafe fn build_submission_impl(&self) -> Result<SubmitAnyBuilder, Validated<VulkanError>> {
-        Ok(match self.previous.build_submission()? {
+        let previous_submission = if self.queue_change {
+            let mut wait_semaphore = self.wait_semaphore.lock();
+
+            let semaphore = match &*wait_semaphore {
+                Some(semaphore) => semaphore.clone(),
+                None => {
+                    let semaphore = Arc::new(Semaphore::from_pool(self.queue.device().clone())?);
+                    submit_signal_semaphore(&self.previous, &semaphore)?;
+
+                    wait_semaphore.insert(semaphore).clone()
+                }
+            };
+
+            SubmitAnyBuilder::SemaphoresWait(smallvec![semaphore])
+        } else {
+            self.previous.build_submission()?
+        };
+
+        Ok(match previous_submission {
             SubmitAnyBuilder::Empty => SubmitAnyBuilder::CommandBuffer(
-                SubmitInfo {
+                smallvec![SubmitInfo {
                     command_buffers: vec![CommandBufferSubmitInfo::new(
                         self.command_buffer.clone(),
                     )],
                     ..Default::default()
-                },
+                }],
                 None,
             ),
             SubmitAnyBuilder::SemaphoresWait(semaphores) => {
                 SubmitAnyBuilder::CommandBuffer(
-                    SubmitInfo {
+                    smallvec![SubmitInfo {
                         wait_semaphores: semaphores
                             .into_iter()
                             .map(|semaphore| {
@@ -164,16 +196,30 @@ where
                             self.command_buffer.clone(),
                         )],
                         ..Default::default()
-                    },
+                    }],
                     None,
                 )
             }
-            SubmitAnyBuilder::CommandBuffer(mut submit_info, fence) => {
-                // FIXME: add pipeline barrier
-                submit_info
-                    .command_buffers
-                    .push(CommandBufferSubmitInfo::new(self.command_buffer.clone()));
-                SubmitAnyBuilder::CommandBuffer(submit_info, fence)
+            SubmitAnyBuilder::CommandBuffer(mut submit_infos, fence) => {
+                let submit_info = submit_infos.last_mut().unwrap();
+
+                if submit_info.signal_semaphores.is_empty() {
+                    // FIXME: add pipeline barrier
+                    submit_info
+                        .command_buffers
+                        .push(CommandBufferSubmitInfo::new(self.command_buffer.clone()));
+                } else {
+                    // The semaphores of the last batch must only be signaled once its own command
+                    // buffers have completed, so start a new batch in the same submission.
+                    submit_infos.push(SubmitInfo {
+                        command_buffers: vec![CommandBufferSubmitInfo::new(
+                            self.command_buffer.clone(),
+                        )],
+                        ..Default::default()
+                    });
+                }
+
+                SubmitAnyBuilder::CommandBuffer(submit_infos, fence)
             }
             SubmitAnyBuilder::QueuePresent(_) | SubmitAnyBuilder::BindSparse(_, _) => {
                 unimplemented!() // TODO:
@@ -211,8 +257,8 @@ where
 
             match self.build_submission_impl()? {
                 SubmitAnyBuilder::Empty => {}
-                SubmitAnyBuilder::CommandBuffer(submit_info, fence) => {
-                    queue_submit(&self.queue, submit_info, fence, &self.previous).unwrap();
+                SubmitAnyBuilder::CommandBuffer(submit_infos, fence) => {
+                    queue_submit(&self.queue, submit_infos, fence, &self.previous).unwrap();
                 }
                 _ => unreachable!(),
             };
@@ -251,7 +297,7 @@ where
                 }
             }
 
-            self.command_buffer.state().set_submit_finished();
+            self.command_buffer.state().set_submit_finished(&self.queue);
         }
 
         self.previous.signal_finished();
@@ -5,14 +5,49 @@
 //! immediately, but is put on hold. You must then call [`join`] repeatedly on one or more threads
 //! to make the operation progress, until it is complete.
 //!
+//! Operations that can currently be deferred are the host acceleration structure commands, such
+//! as [`Device::build_acceleration_structure`] and [`Device::copy_acceleration_structure`]. Any
+//! data that the operation needs is kept alive by the `DeferredOperation` until the operation has
+//! completed.
+//!
+//! # Examples
+//!
+//! Joining an operation from multiple threads, so that the work is spread out over them:
+//!
+//! ```
+//! # use std::{sync::Arc, thread};
+//! # use vulkano::deferred::{DeferredOperation, DeferredOperationJoinStatus};
+//! # fn example(operation: Arc<DeferredOperation>) {
+//! let thread_count = operation
+//!     .max_concurrency()
+//!     .unwrap_or(1)
+//!     .min(thread::available_parallelism().map_or(1, |count| count.get() as u32));
+//!
+//! thread::scope(|scope| {
+//!     for _ in 0..thread_count {
+//!         scope.spawn(|| {
+//!             while operation.join().unwrap() == DeferredOperationJoinStatus::ThreadIdle {}
+//!         });
+//!     }
+//! });
+//!
+//! // Poll for the result of the operation.
+//! let result = operation.wait().unwrap();
+//! # let _ = result;
+//! # }
+//! ```
+//!
 //! [`join`]: DeferredOperation::join
+//! [`Device::build_acceleration_structure`]: crate::device::Device::build_acceleration_structure
+//! [`Device::copy_acceleration_structure`]: crate::device::Device::copy_acceleration_structure
 
 use crate::{
     device::{Device, DeviceOwned},
     instance::InstanceOwnedDebugWrapper,
     Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, VulkanError, VulkanObject,
 };
-use std::{mem::MaybeUninit, ptr, sync::Arc};
+use parking_lot::Mutex;
+use std::{any::Any, mem::MaybeUninit, ptr, sync::Arc};
 
 /// An operation on the host that has been deferred.
 ///
@@ -22,6 +57,8 @@ use std::{mem::MaybeUninit, ptr, sync::Arc};
 pub struct DeferredOperation {
     device: InstanceOwnedDebugWrapper<Arc<Device>>,
     handle: ash::vk::DeferredOperationKHR,
+    // Data that the operation reads from, which must stay alive until the operation completes.
+    keep_alive: Mutex<Option<Box<dyn Any + Send + Sync>>>,
 }
 
 impl DeferredOperation {
@@ -80,6 +117,7 @@ impl DeferredOperation {
         Arc::new(Self {
             device: InstanceOwnedDebugWrapper(device),
             handle,
+            keep_alive: Mutex::new(None),
         })
     }
 
@@ -144,6 +182,16 @@ impl DeferredOperation {
         }
     }
 
+    /// Keeps `objects` alive until the operation has completed, replacing the objects of a
+    /// previous operation.
+    ///
+    /// # Safety
+    ///
+    /// - The previous operation that was deferred on `self`, if any, must have completed.
+    pub(crate) unsafe fn set_keep_alive(&self, objects: Box<dyn Any + Send + Sync>) {
+        *self.keep_alive.lock() = Some(objects);
+    }
+
     /// The maximum number of threads that could usefully execute the operation at this point in
     /// its execution, or zero if the operation is complete.
     ///
@@ -21,6 +21,7 @@ use crate::{
     instance::InstanceOwnedDebugWrapper,
     Validated, VulkanError,
 };
+use ahash::HashMap;
 use crossbeam_queue::ArrayQueue;
 use std::{
     cell::UnsafeCell,
@@ -28,12 +29,13 @@ use std::{
     mem,
     num::NonZeroU64,
     ptr,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
 };
 use thread_local::ThreadLocal;
 
-const MAX_POOLS: usize = 32;
-
 /// Types that manage the memory of descriptor sets.
 ///
 /// # Safety
@@ -171,12 +173,19 @@ impl AllocationHandle {
 /// for as long as you need to allocate so that the pools can keep being reused.
 ///
 /// This allocator only needs to lock when a thread first allocates or when a thread that
-/// previously allocated exits. In all other cases, allocation is lock-free.
+/// previously allocated exits. In all other cases, allocation is lock-free. To also avoid creating
+/// pools while allocating, use [`prewarm`] on each thread that allocates. How large the pools are
+/// and how they grow can be tuned with [`StandardDescriptorSetAllocatorCreateInfo`], and
+/// [`statistics`] tells you how well the current configuration fits your workload.
+///
+/// [`prewarm`]: Self::prewarm
+/// [`statistics`]: Self::statistics
 #[derive(Debug)]
 pub struct StandardDescriptorSetAllocator {
     device: InstanceOwnedDebugWrapper<Arc<Device>>,
     pools: ThreadLocal<UnsafeCell<SortedMap<NonZeroU64, Entry>>>,
     create_info: StandardDescriptorSetAllocatorCreateInfo,
+    counters: Arc<Counters>,
 }
 
 #[derive(Debug)]
@@ -192,15 +201,60 @@ unsafe impl Send for Entry {}
 
 impl StandardDescriptorSetAllocator {
     /// Creates a new `StandardDescriptorSetAllocator`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.set_count` is zero.
+    /// - Panics if `create_info.growth_factor` is less than `1.0`.
+    /// - Panics if `create_info.max_set_count` is less than `create_info.set_count`.
+    /// - Panics if `create_info.max_pools` is zero.
     #[inline]
     pub fn new(
         device: Arc<Device>,
         create_info: StandardDescriptorSetAllocatorCreateInfo,
     ) -> StandardDescriptorSetAllocator {
+        assert_ne!(
+            create_info.set_count, 0,
+            "`create_info.set_count` must not be zero",
+        );
+        assert!(
+            create_info.growth_factor >= 1.0,
+            "`create_info.growth_factor` must be at least `1.0`",
+        );
+        assert!(
+            create_info.max_set_count >= create_info.set_count,
+            "`create_info.max_set_count` must not be less than `create_info.set_count`",
+        );
+        assert_ne!(
+            create_info.max_pools, 0,
+            "`create_info.max_pools` must not be zero",
+        );
+
         StandardDescriptorSetAllocator {
             device: InstanceOwnedDebugWrapper(device),
             pools: ThreadLocal::new(),
             create_info,
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// Returns statistics about the descriptor sets and pools of the allocator, across all
+    /// threads.
+    ///
+    /// The statistics are gathered without locking, which means that they are not necessarily
+    /// consistent with one another if other threads allocate or deallocate in the meantime.
+    #[inline]
+    pub fn statistics(&self) -> DescriptorSetAllocatorStatistics {
+        let counters = &*self.counters;
+
+        DescriptorSetAllocatorStatistics {
+            allocation_count: counters.allocation_count.load(Ordering::Relaxed),
+            total_allocation_count: counters.total_allocation_count.load(Ordering::Relaxed),
+            pool_count: counters.pool_count.load(Ordering::Relaxed),
+            set_capacity: counters.set_capacity.load(Ordering::Relaxed),
+            total_pool_count: counters.total_pool_count.load(Ordering::Relaxed),
+            on_demand_pool_count: counters.on_demand_pool_count.load(Ordering::Relaxed),
+            pool_reset_count: counters.pool_reset_count.load(Ordering::Relaxed),
         }
     }
 
@@ -222,37 +276,143 @@ impl StandardDescriptorSetAllocator {
     pub fn clear_all(&self) {
         unsafe { *self.pools.get_or(Default::default).get() = SortedMap::default() };
     }
-}
 
-unsafe impl DescriptorSetAllocator for StandardDescriptorSetAllocator {
-    #[inline]
-    fn allocate(
+    /// Creates enough pools for the given descriptor set layout and the current thread, so that
+    /// at least `count` descriptor sets can be allocated without creating a new pool.
+    ///
+    /// Creating a pool is much more expensive than allocating from one. Calling this on each
+    /// thread that is going to allocate, for example on worker threads before they start
+    /// recording, keeps pool creation out of the hot path. For descriptor set layouts with a
+    /// variable descriptor count, at most [`max_pools`] pools can be prepared at once.
+    ///
+    /// [`max_pools`]: StandardDescriptorSetAllocatorCreateInfo::max_pools
+    pub fn prewarm(
         &self,
         layout: &Arc<DescriptorSetLayout>,
-        variable_descriptor_count: u32,
-    ) -> Result<DescriptorSetAlloc, Validated<VulkanError>> {
+        count: usize,
+    ) -> Result<(), Validated<VulkanError>> {
+        self.with_entry(layout, false, |entry| match entry {
+            Entry::Fixed(entry) => entry.prewarm(layout, count, self),
+            Entry::Variable(entry) => entry.prewarm(layout, count, self),
+        })
+    }
+
+    fn with_entry<T>(
+        &self,
+        layout: &Arc<DescriptorSetLayout>,
+        on_demand: bool,
+        f: impl FnOnce(&mut Entry) -> Result<T, Validated<VulkanError>>,
+    ) -> Result<T, Validated<VulkanError>> {
         let is_fixed = layout.variable_descriptor_count() == 0;
         let pools = self.pools.get_or_default();
 
         let entry = unsafe { &mut *pools.get() }.get_or_try_insert(layout.id(), || {
+            let set_count = self.create_info.set_count;
+
             if is_fixed {
-                FixedEntry::new(layout, &self.create_info).map(Entry::Fixed)
+                FixedEntry::new(layout, set_count, self, on_demand).map(Entry::Fixed)
             } else {
-                VariableEntry::new(
-                    layout,
-                    &self.create_info,
-                    Arc::new(ArrayQueue::new(MAX_POOLS)),
-                )
-                .map(Entry::Variable)
+                let reserve = Arc::new(VariableReserve {
+                    queue: ArrayQueue::new(self.create_info.max_pools),
+                    counters: self.counters.clone(),
+                });
+
+                VariableEntry::new(layout, set_count, self, on_demand, reserve).map(Entry::Variable)
             }
         })?;
 
-        match entry {
-            Entry::Fixed(entry) => entry.allocate(layout, &self.create_info),
-            Entry::Variable(entry) => {
-                entry.allocate(layout, variable_descriptor_count, &self.create_info)
-            }
+        f(entry)
+    }
+
+    /// Returns the number of descriptor sets that the pool created after one with `set_count`
+    /// descriptor sets should have.
+    fn next_set_count(&self, set_count: usize) -> usize {
+        let next_set_count = (set_count as f32 * self.create_info.growth_factor).ceil() as usize;
+
+        next_set_count.clamp(set_count, self.create_info.max_set_count)
+    }
+
+    fn create_pool(
+        &self,
+        layout: &DescriptorSetLayout,
+        set_count: usize,
+        on_demand: bool,
+    ) -> Result<DescriptorPool, Validated<VulkanError>> {
+        let is_fixed = layout.variable_descriptor_count() == 0;
+
+        let pool = DescriptorPool::new(
+            layout.device().clone(),
+            DescriptorPoolCreateInfo {
+                flags: self
+                    .create_info
+                    .update_after_bind
+                    .then_some(DescriptorPoolCreateFlags::UPDATE_AFTER_BIND)
+                    .unwrap_or_default(),
+                max_sets: set_count as u32,
+                pool_sizes: layout
+                    .descriptor_counts()
+                    .iter()
+                    .map(|(&ty, &count)| {
+                        assert_ne!(ty, DescriptorType::InlineUniformBlock);
+
+                        // Fixed-size descriptor sets are allocated up front, so their pools must
+                        // be able to hold exactly the descriptors of the layout.
+                        let count = if is_fixed {
+                            count
+                        } else {
+                            self.create_info
+                                .pool_sizes
+                                .get(&ty)
+                                .copied()
+                                .unwrap_or(count)
+                        };
+
+                        (ty, count * set_count as u32)
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+        )
+        .map_err(Validated::unwrap)?;
+
+        self.counters.pool_count.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .set_capacity
+            .fetch_add(set_count, Ordering::Relaxed);
+        self.counters
+            .total_pool_count
+            .fetch_add(1, Ordering::Relaxed);
+
+        if on_demand {
+            self.counters
+                .on_demand_pool_count
+                .fetch_add(1, Ordering::Relaxed);
         }
+
+        Ok(pool)
+    }
+}
+
+unsafe impl DescriptorSetAllocator for StandardDescriptorSetAllocator {
+    #[inline]
+    fn allocate(
+        &self,
+        layout: &Arc<DescriptorSetLayout>,
+        variable_descriptor_count: u32,
+    ) -> Result<DescriptorSetAlloc, Validated<VulkanError>> {
+        let alloc = self.with_entry(layout, true, |entry| match entry {
+            Entry::Fixed(entry) => entry.allocate(layout, self),
+            Entry::Variable(entry) => entry.allocate(layout, variable_descriptor_count, self),
+        })?;
+
+        self.counters
+            .allocation_count
+            .fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .total_allocation_count
+            .fetch_add(1, Ordering::Relaxed);
+
+        Ok(alloc)
     }
 
     #[inline]
@@ -260,6 +420,10 @@ unsafe impl DescriptorSetAllocator for StandardDescriptorSetAllocator {
         let is_fixed = allocation.inner.variable_descriptor_count() == 0;
         let ptr = allocation.handle.as_ptr();
 
+        self.counters
+            .allocation_count
+            .fetch_sub(1, Ordering::Relaxed);
+
         if is_fixed {
             // SAFETY: The caller must guarantee that `allocation` refers to one allocated by
             // `self`, therefore `ptr` must be the same one we gave out on allocation. We also know
@@ -267,14 +431,14 @@ unsafe impl DescriptorSetAllocator for StandardDescriptorSetAllocator {
             // allocation isn't deallocated more than once. That means that since we cloned the
             // `Arc` on allocation, at least that strong reference must still keep it alive, and we
             // can safely drop this clone at the end of the scope here.
-            let reserve = unsafe { Arc::from_raw(ptr.cast::<ArrayQueue<DescriptorPoolAlloc>>()) };
+            let reserve = unsafe { Arc::from_raw(ptr.cast::<FixedReserve>()) };
 
             // This cannot happen because every allocation is (supposed to be) returned to the pool
             // whence it came, so there must be enough room for it.
-            debug_assert!(reserve.push(allocation.inner).is_ok());
+            debug_assert!(reserve.queue.push(allocation.inner).is_ok());
         } else {
             // SAFETY: Same as the `Arc::from_raw` above.
-            let reserve = unsafe { Arc::from_raw(ptr.cast::<ArrayQueue<Arc<DescriptorPool>>>()) };
+            let reserve = unsafe { Arc::from_raw(ptr.cast::<VariableReserve>()) };
 
             let pool = allocation.pool;
 
@@ -284,9 +448,11 @@ unsafe impl DescriptorSetAllocator for StandardDescriptorSetAllocator {
             if Arc::strong_count(&pool) == 1 {
                 // If there is not enough space in the reserve, we destroy the pool. The only way
                 // this can happen is if something is resource hogging, forcing new pools to be
-                // created such that the number exceeds `MAX_POOLS`, and then drops them all at
+                // created such that the number exceeds `max_pools`, and then drops them all at
                 // once.
-                let _ = reserve.push(pool);
+                if let Err(pool) = reserve.queue.push(pool) {
+                    self.counters.remove_pool(&pool);
+                }
             }
         }
     }
@@ -315,40 +481,67 @@ unsafe impl DeviceOwned for StandardDescriptorSetAllocator {
     }
 }
 
+#[derive(Debug, Default)]
+struct Counters {
+    allocation_count: AtomicUsize,
+    total_allocation_count: AtomicU64,
+    pool_count: AtomicUsize,
+    set_capacity: AtomicUsize,
+    total_pool_count: AtomicU64,
+    on_demand_pool_count: AtomicU64,
+    pool_reset_count: AtomicU64,
+}
+
+impl Counters {
+    fn remove_pool(&self, pool: &DescriptorPool) {
+        self.pool_count.fetch_sub(1, Ordering::Relaxed);
+        self.set_capacity
+            .fetch_sub(pool.max_sets() as usize, Ordering::Relaxed);
+    }
+}
+
 #[derive(Debug)]
 struct FixedEntry {
     pool: Arc<DescriptorPool>,
-    reserve: Arc<ArrayQueue<DescriptorPoolAlloc>>,
+    reserve: Arc<FixedReserve>,
+    // Pools that were created in advance by `prewarm`, to be used once `reserve` runs out.
+    prewarmed: Vec<(Arc<DescriptorPool>, Arc<FixedReserve>)>,
+    // The number of descriptor sets of the pool that was created last.
+    set_count: usize,
+}
+
+// The descriptor sets of a fixed pool that are not allocated. Each allocation holds a reference to
+// this, so the pool is considered alive until the entry has moved on to another pool and all of
+// its descriptor sets have been deallocated.
+#[derive(Debug)]
+struct FixedReserve {
+    queue: ArrayQueue<DescriptorPoolAlloc>,
+    counters: Arc<Counters>,
+}
+
+impl Drop for FixedReserve {
+    fn drop(&mut self) {
+        self.counters.pool_count.fetch_sub(1, Ordering::Relaxed);
+        self.counters
+            .set_capacity
+            .fetch_sub(self.queue.capacity(), Ordering::Relaxed);
+    }
 }
 
 impl FixedEntry {
     fn new(
         layout: &Arc<DescriptorSetLayout>,
-        create_info: &StandardDescriptorSetAllocatorCreateInfo,
+        set_count: usize,
+        allocator: &StandardDescriptorSetAllocator,
+        on_demand: bool,
     ) -> Result<Self, Validated<VulkanError>> {
-        let pool = DescriptorPool::new(
-            layout.device().clone(),
-            DescriptorPoolCreateInfo {
-                flags: create_info
-                    .update_after_bind
-                    .then_some(DescriptorPoolCreateFlags::UPDATE_AFTER_BIND)
-                    .unwrap_or_default(),
-                max_sets: create_info.set_count as u32,
-                pool_sizes: layout
-                    .descriptor_counts()
-                    .iter()
-                    .map(|(&ty, &count)| {
-                        assert_ne!(ty, DescriptorType::InlineUniformBlock);
-                        (ty, count * create_info.set_count as u32)
-                    })
-                    .collect(),
-                ..Default::default()
-            },
-        )
-        .map_err(Validated::unwrap)?;
+        let pool = allocator.create_pool(layout, set_count, on_demand)?;
+        let reserve = FixedReserve {
+            queue: ArrayQueue::new(set_count),
+            counters: allocator.counters.clone(),
+        };
 
-        let allocate_infos =
-            (0..create_info.set_count).map(|_| DescriptorSetAllocateInfo::new(layout.clone()));
+        let allocate_infos = (0..set_count).map(|_| DescriptorSetAllocateInfo::new(layout.clone()));
 
         let allocs =
             unsafe { pool.allocate_descriptor_sets(allocate_infos) }.map_err(|err| match err {
@@ -364,29 +557,63 @@ impl FixedEntry {
                 },
             })?;
 
-        let reserve = ArrayQueue::new(create_info.set_count);
-
         for alloc in allocs {
-            let _ = reserve.push(alloc);
+            let _ = reserve.queue.push(alloc);
         }
 
         Ok(FixedEntry {
             pool: Arc::new(pool),
             reserve: Arc::new(reserve),
+            prewarmed: Vec::new(),
+            set_count,
         })
     }
 
+    fn prewarm(
+        &mut self,
+        layout: &Arc<DescriptorSetLayout>,
+        count: usize,
+        allocator: &StandardDescriptorSetAllocator,
+    ) -> Result<(), Validated<VulkanError>> {
+        let mut available = self.reserve.queue.len()
+            + self
+                .prewarmed
+                .iter()
+                .map(|(_, reserve)| reserve.queue.len())
+                .sum::<usize>();
+
+        while available < count {
+            let set_count = allocator.next_set_count(self.set_count);
+            let FixedEntry { pool, reserve, .. } =
+                FixedEntry::new(layout, set_count, allocator, false)?;
+            self.prewarmed.push((pool, reserve));
+            self.set_count = set_count;
+            available += set_count;
+        }
+
+        Ok(())
+    }
+
     fn allocate(
         &mut self,
         layout: &Arc<DescriptorSetLayout>,
-        create_info: &StandardDescriptorSetAllocatorCreateInfo,
+        allocator: &StandardDescriptorSetAllocator,
     ) -> Result<DescriptorSetAlloc, Validated<VulkanError>> {
-        let inner = if let Some(inner) = self.reserve.pop() {
+        let inner = if let Some(inner) = self.reserve.queue.pop() {
             inner
         } else {
-            *self = FixedEntry::new(layout, create_info)?;
+            if !self.prewarmed.is_empty() {
+                // Prewarmed pools are used in the order they were created, so that the smaller
+                // ones are used up first.
+                let (pool, reserve) = self.prewarmed.remove(0);
+                self.pool = pool;
+                self.reserve = reserve;
+            } else {
+                let set_count = allocator.next_set_count(self.set_count);
+                *self = FixedEntry::new(layout, set_count, allocator, true)?;
+            }
 
-            self.reserve.pop().unwrap()
+            self.reserve.queue.pop().unwrap()
         };
 
         Ok(DescriptorSetAlloc {
@@ -400,104 +627,135 @@ impl FixedEntry {
 #[derive(Debug)]
 struct VariableEntry {
     pool: Arc<DescriptorPool>,
-    reserve: Arc<ArrayQueue<Arc<DescriptorPool>>>,
+    reserve: Arc<VariableReserve>,
     // The number of sets currently allocated from the Vulkan pool.
     allocations: usize,
+    // The number of descriptor sets of the pool that was created last.
+    set_count: usize,
+}
+
+// The pools of a variable entry that are not in use. Each allocation holds a reference to this, so
+// that it can return the pool it was allocated from once all of the pool's sets are deallocated.
+#[derive(Debug)]
+struct VariableReserve {
+    queue: ArrayQueue<Arc<DescriptorPool>>,
+    counters: Arc<Counters>,
+}
+
+impl Drop for VariableReserve {
+    fn drop(&mut self) {
+        while let Some(pool) = self.queue.pop() {
+            self.counters.remove_pool(&pool);
+        }
+    }
+}
+
+impl Drop for VariableEntry {
+    fn drop(&mut self) {
+        // If there are allocations left, the last one to be deallocated returns the pool to the
+        // reserve instead.
+        if Arc::strong_count(&self.pool) == 1 {
+            self.reserve.counters.remove_pool(&self.pool);
+        }
+    }
 }
 
 impl VariableEntry {
     fn new(
         layout: &DescriptorSetLayout,
-        create_info: &StandardDescriptorSetAllocatorCreateInfo,
-        reserve: Arc<ArrayQueue<Arc<DescriptorPool>>>,
+        set_count: usize,
+        allocator: &StandardDescriptorSetAllocator,
+        on_demand: bool,
+        reserve: Arc<VariableReserve>,
     ) -> Result<Self, Validated<VulkanError>> {
-        let pool = DescriptorPool::new(
-            layout.device().clone(),
-            DescriptorPoolCreateInfo {
-                flags: create_info
-                    .update_after_bind
-                    .then_some(DescriptorPoolCreateFlags::UPDATE_AFTER_BIND)
-                    .unwrap_or_default(),
-                max_sets: create_info.set_count as u32,
-                pool_sizes: layout
-                    .descriptor_counts()
-                    .iter()
-                    .map(|(&ty, &count)| {
-                        assert_ne!(ty, DescriptorType::InlineUniformBlock);
-                        (ty, count * create_info.set_count as u32)
-                    })
-                    .collect(),
-                ..Default::default()
-            },
-        )
-        .map_err(Validated::unwrap)?;
+        let pool = allocator.create_pool(layout, set_count, on_demand)?;
 
         Ok(VariableEntry {
             pool: Arc::new(pool),
             reserve,
             allocations: 0,
+            set_count,
         })
     }
 
+    fn prewarm(
+        &mut self,
+        layout: &DescriptorSetLayout,
+        count: usize,
+        allocator: &StandardDescriptorSetAllocator,
+    ) -> Result<(), Validated<VulkanError>> {
+        // The pools in the reserve are not necessarily reset yet, but all of their sets have been
+        // deallocated.
+        let mut available = (self.pool.max_sets() as usize).saturating_sub(self.allocations);
+        let mut reserved = Vec::with_capacity(self.reserve.queue.len());
+
+        while let Some(pool) = self.reserve.queue.pop() {
+            available += pool.max_sets() as usize;
+            reserved.push(pool);
+        }
+
+        for pool in reserved {
+            // Other threads could have returned pools in the meantime.
+            if let Err(pool) = self.reserve.queue.push(pool) {
+                available -= pool.max_sets() as usize;
+                self.reserve.counters.remove_pool(&pool);
+            }
+        }
+
+        while available < count && !self.reserve.queue.is_full() {
+            let set_count = allocator.next_set_count(self.set_count);
+            let pool = allocator.create_pool(layout, set_count, false)?;
+            let _ = self.reserve.queue.push(Arc::new(pool));
+            self.set_count = set_count;
+            available += set_count;
+        }
+
+        Ok(())
+    }
+
     fn allocate(
         &mut self,
         layout: &Arc<DescriptorSetLayout>,
         variable_descriptor_count: u32,
-        create_info: &StandardDescriptorSetAllocatorCreateInfo,
+        allocator: &StandardDescriptorSetAllocator,
     ) -> Result<DescriptorSetAlloc, Validated<VulkanError>> {
-        if self.allocations >= create_info.set_count {
-            // This can happen if there's only ever one allocation alive at any point in time. In
-            // that case, when deallocating the last set before reaching `set_count`, there will be
-            // 2 references to the pool (one here and one in the allocation) and so the pool won't
-            // be returned to the reserve when deallocating. However, since there are no other
-            // allocations alive, there would be no other allocations that could return it to the
-            // reserve. To avoid dropping the pool unnecessarily, we simply continue using it. In
-            // the case where there are other references, we drop ours, at which point an
-            // allocation still holding a reference will be able to put the pool into the reserve
-            // when deallocated. If the user created a reference themself that will most certainly
-            // lead to a memory leak.
-            //
-            // TODO: This can still run into the A/B/A problem causing the pool to be dropped.
-            if Arc::strong_count(&self.pool) == 1 {
-                // SAFETY: We checked that the pool has a single strong reference above, meaning
-                // that all the allocations we gave out must have been deallocated.
-                unsafe { self.pool.reset() }?;
-
-                self.allocations = 0;
-            } else {
-                if let Some(pool) = self.reserve.pop() {
-                    // SAFETY: We checked that the pool has a single strong reference when
-                    // deallocating, meaning that all the allocations we gave out must have been
-                    // deallocated.
-                    unsafe { pool.reset() }?;
-
-                    self.pool = pool;
-                    self.allocations = 0;
-                } else {
-                    *self = VariableEntry::new(layout, create_info, self.reserve.clone())?;
-                }
-            }
+        if self.allocations >= self.pool.max_sets() as usize {
+            self.next_pool(layout, allocator)?;
         }
 
         let allocate_info = DescriptorSetAllocateInfo {
             variable_descriptor_count,
             ..DescriptorSetAllocateInfo::new(layout.clone())
         };
+        let has_pool_sizes = !allocator.create_info.pool_sizes.is_empty();
 
-        let mut sets = unsafe { self.pool.allocate_descriptor_sets([allocate_info]) }.map_err(
-            |err| match err {
-                Validated::ValidationError(_) => err,
-                Validated::Error(vk_err) => match vk_err {
-                    VulkanError::OutOfHostMemory | VulkanError::OutOfDeviceMemory => err,
-                    // This can't happen as we don't free individual sets.
-                    VulkanError::FragmentedPool => unreachable!(),
-                    // We created the pool to fit the maximum variable descriptor count.
-                    VulkanError::OutOfPoolMemory => unreachable!(),
-                    // Shouldn't ever be returned.
-                    _ => unreachable!(),
-                },
+        let sets = match unsafe { self.pool.allocate_descriptor_sets([allocate_info.clone()]) } {
+            // If the pool sizes were overridden, the pool can run out of descriptors before it
+            // runs out of sets, in which case we try again with the next pool.
+            Err(Validated::Error(VulkanError::OutOfPoolMemory))
+                if has_pool_sizes && self.allocations != 0 =>
+            {
+                self.next_pool(layout, allocator)?;
+
+                unsafe { self.pool.allocate_descriptor_sets([allocate_info]) }
+            }
+            res => res,
+        };
+
+        let mut sets = sets.map_err(|err| match err {
+            Validated::ValidationError(_) => err,
+            Validated::Error(vk_err) => match vk_err {
+                VulkanError::OutOfHostMemory | VulkanError::OutOfDeviceMemory => err,
+                // This can't happen as we don't free individual sets.
+                VulkanError::FragmentedPool => unreachable!(),
+                // The descriptor set needs more descriptors than a whole pool has.
+                VulkanError::OutOfPoolMemory if has_pool_sizes => err,
+                // We created the pool to fit the maximum variable descriptor count.
+                VulkanError::OutOfPoolMemory => unreachable!(),
+                // Shouldn't ever be returned.
+                _ => unreachable!(),
             },
-        )?;
+        })?;
 
         self.allocations += 1;
 
@@ -507,17 +765,63 @@ impl VariableEntry {
             handle: AllocationHandle::from_ptr(Arc::into_raw(self.reserve.clone()) as _),
         })
     }
+
+    fn next_pool(
+        &mut self,
+        layout: &DescriptorSetLayout,
+        allocator: &StandardDescriptorSetAllocator,
+    ) -> Result<(), Validated<VulkanError>> {
+        // This can happen if there's only ever one allocation alive at any point in time. In that
+        // case, when deallocating the last set before reaching the pool's capacity, there will be
+        // 2 references to the pool (one here and one in the allocation) and so the pool won't be
+        // returned to the reserve when deallocating. However, since there are no other
+        // allocations alive, there would be no other allocations that could return it to the
+        // reserve. To avoid dropping the pool unnecessarily, we simply continue using it. In the
+        // case where there are other references, we drop ours, at which point an allocation still
+        // holding a reference will be able to put the pool into the reserve when deallocated. If
+        // the user created a reference themself that will most certainly lead to a memory leak.
+        //
+        // TODO: This can still run into the A/B/A problem causing the pool to be dropped.
+        if Arc::strong_count(&self.pool) == 1 {
+            // SAFETY: We checked that the pool has a single strong reference above, meaning that
+            // all the allocations we gave out must have been deallocated.
+            unsafe { self.pool.reset() }?;
+
+            allocator
+                .counters
+                .pool_reset_count
+                .fetch_add(1, Ordering::Relaxed);
+            self.allocations = 0;
+        } else if let Some(pool) = self.reserve.queue.pop() {
+            // SAFETY: We checked that the pool has a single strong reference when deallocating,
+            // meaning that all the allocations we gave out must have been deallocated.
+            unsafe { pool.reset() }?;
+
+            allocator
+                .counters
+                .pool_reset_count
+                .fetch_add(1, Ordering::Relaxed);
+            self.pool = pool;
+            self.allocations = 0;
+        } else {
+            let set_count = allocator.next_set_count(self.set_count);
+            *self = VariableEntry::new(layout, set_count, allocator, true, self.reserve.clone())?;
+        }
+
+        Ok(())
+    }
 }
 
 /// Parameters to create a new `StandardDescriptorSetAllocator`.
 #[derive(Clone, Debug)]
 pub struct StandardDescriptorSetAllocatorCreateInfo {
-    /// How many descriptor sets should be allocated per pool.
+    /// How many descriptor sets should be allocated for the first pool.
     ///
     /// Each time a thread allocates using some descriptor set layout, and either no pools were
     /// initialized yet or all pools are full, a new pool is allocated for that thread and
     /// descriptor set layout combination. This option tells the allocator how many descriptor sets
-    /// should be allocated for that pool. For fixed-size descriptor set layouts, it always
+    /// should be allocated for the first such pool, while [`growth_factor`] determines how many
+    /// should be allocated for the ones after it. For fixed-size descriptor set layouts, it always
     /// allocates exactly this many descriptor sets at once for the pool, as that is more
     /// performant than allocating them one-by-one. For descriptor set layouts with a variable
     /// descriptor count, it allocates a pool capable of holding exactly this many descriptor sets,
@@ -528,8 +832,52 @@ pub struct StandardDescriptorSetAllocatorCreateInfo {
     /// need to be created, depending on the lifetime of the descriptor sets.
     ///
     /// The default value is `32`.
+    ///
+    /// [`growth_factor`]: Self::growth_factor
     pub set_count: usize,
 
+    /// The factor by which the number of descriptor sets of each new pool grows, compared to the
+    /// pool that was created before it for the same thread and descriptor set layout.
+    ///
+    /// A value greater than `1.0` lets the allocator adapt to workloads that need more descriptor
+    /// sets than [`set_count`], so that it stops having to create pools once the pools are large
+    /// enough. The number of descriptor sets is rounded up and never exceeds [`max_set_count`].
+    ///
+    /// The default value is `1.0`.
+    ///
+    /// [`set_count`]: Self::set_count
+    /// [`max_set_count`]: Self::max_set_count
+    pub growth_factor: f32,
+
+    /// The maximum number of descriptor sets that a pool can grow to when [`growth_factor`] is
+    /// greater than `1.0`.
+    ///
+    /// The default value is `1024`.
+    ///
+    /// [`growth_factor`]: Self::growth_factor
+    pub max_set_count: usize,
+
+    /// The number of descriptors of each type that pools for descriptor set layouts with a
+    /// variable descriptor count should have room for, per descriptor set.
+    ///
+    /// By default, such pools are created so that every descriptor set can be allocated with the
+    /// maximum variable descriptor count of the layout, which can waste a lot of memory if that
+    /// maximum is large. With this option, the pool is created with the given number of
+    /// descriptors multiplied by the number of descriptor sets instead, and when it runs out of
+    /// descriptors, the next pool is used. Descriptor types that are not in the map use the
+    /// descriptor counts of the layout. This option has no effect on fixed-size descriptor set
+    /// layouts, whose pools always fit their descriptor sets exactly.
+    ///
+    /// The default value is empty.
+    pub pool_sizes: HashMap<DescriptorType, u32>,
+
+    /// The maximum number of pools that are kept for reuse per thread and descriptor set layout
+    /// with a variable descriptor count, once all of their descriptor sets have been deallocated.
+    /// Pools beyond this number are destroyed.
+    ///
+    /// The default value is `32`.
+    pub max_pools: usize,
+
     /// Whether to allocate descriptor pools with the
     /// [`DescriptorPoolCreateFlags::UPDATE_AFTER_BIND`] flag set.
     ///
@@ -544,12 +892,73 @@ impl Default for StandardDescriptorSetAllocatorCreateInfo {
     fn default() -> Self {
         StandardDescriptorSetAllocatorCreateInfo {
             set_count: 32,
+            growth_factor: 1.0,
+            max_set_count: 1024,
+            pool_sizes: HashMap::default(),
+            max_pools: 32,
             update_after_bind: false,
             _ne: crate::NonExhaustive(()),
         }
     }
 }
 
+/// Statistics about the descriptor sets and pools of a [`StandardDescriptorSetAllocator`],
+/// returned by [`StandardDescriptorSetAllocator::statistics`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DescriptorSetAllocatorStatistics {
+    /// The number of descriptor sets that are currently allocated.
+    pub allocation_count: usize,
+
+    /// The number of descriptor sets that have been allocated over the lifetime of the allocator.
+    pub total_allocation_count: u64,
+
+    /// The number of descriptor pools that are currently alive.
+    pub pool_count: usize,
+
+    /// The total number of descriptor sets that the descriptor pools that are currently alive
+    /// can hold.
+    pub set_capacity: usize,
+
+    /// The number of descriptor pools that have been created over the lifetime of the
+    /// allocator, including the ones created by [`prewarm`].
+    ///
+    /// [`prewarm`]: StandardDescriptorSetAllocator::prewarm
+    pub total_pool_count: u64,
+
+    /// The number of descriptor pools that had to be created while allocating a descriptor set,
+    /// because the existing pools were exhausted.
+    ///
+    /// If this keeps growing after your application has warmed up, consider increasing
+    /// [`set_count`] or [`growth_factor`], or calling [`prewarm`].
+    ///
+    /// [`set_count`]: StandardDescriptorSetAllocatorCreateInfo::set_count
+    /// [`growth_factor`]: StandardDescriptorSetAllocatorCreateInfo::growth_factor
+    /// [`prewarm`]: StandardDescriptorSetAllocator::prewarm
+    pub on_demand_pool_count: u64,
+
+    /// The number of times that a descriptor pool for a layout with a variable descriptor count
+    /// was reset so that it could be reused.
+    pub pool_reset_count: u64,
+}
+
+impl DescriptorSetAllocatorStatistics {
+    /// Returns the fraction of the capacity of the descriptor pools that is not currently
+    /// allocated, in the range `0.0..=1.0`.
+    ///
+    /// Descriptor sets are never freed individually, so a pool whose descriptor sets were only
+    /// partially deallocated can't be reused until all of them are. A value approaching `1.0`
+    /// therefore means that most of the memory of the pools is wasted. If there are no pools,
+    /// `0.0` is returned.
+    #[inline]
+    pub fn fragmentation(&self) -> f32 {
+        if self.set_capacity == 0 {
+            0.0
+        } else {
+            1.0 - self.allocation_count.min(self.set_capacity) as f32 / self.set_capacity as f32
+        }
+    }
+}
+
 mod sorted_map {
     use smallvec::SmallVec;
 
@@ -30,13 +30,19 @@ pub struct DescriptorSetLayout {
 impl DescriptorSetLayout {
     /// Creates a new `DescriptorSetLayout`.
     #[inline]
+    #[track_caller]
     pub fn new(
         device: Arc<Device>,
         create_info: DescriptorSetLayoutCreateInfo,
     ) -> Result<Arc<DescriptorSetLayout>, Validated<VulkanError>> {
         Self::validate_new(&device, &create_info)?;
 
-        unsafe { Ok(Self::new_unchecked(device, create_info)?) }
+        let descriptor_set_layout = unsafe { Self::new_unchecked(device, create_info)? };
+        descriptor_set_layout
+            .device()
+            .set_auto_debug_utils_object_name(&*descriptor_set_layout, "DescriptorSetLayout");
+
+        Ok(descriptor_set_layout)
     }
 
     fn validate_new(
@@ -85,6 +91,19 @@ impl DescriptorSetLayout {
     pub unsafe fn new_unchecked(
         device: Arc<Device>,
         create_info: DescriptorSetLayoutCreateInfo,
+    ) -> Result<Arc<DescriptorSetLayout>, VulkanError> {
+        if let Some(layout_cache) = device.layout_cache() {
+            return layout_cache.descriptor_set_layout(create_info, |create_info| {
+                Self::create(device.clone(), create_info)
+            });
+        }
+
+        Self::create(device, create_info)
+    }
+
+    unsafe fn create(
+        device: Arc<Device>,
+        create_info: DescriptorSetLayoutCreateInfo,
     ) -> Result<Arc<DescriptorSetLayout>, VulkanError> {
         let &DescriptorSetLayoutCreateInfo {
             flags,
@@ -624,6 +643,7 @@ impl DescriptorSetLayoutBinding {
             image_format: _,
             image_multisampled: _,
             image_scalar_type: _,
+            image_scalar_width: _,
             image_view_type: _,
             stages,
             descriptors: _,
@@ -279,6 +279,103 @@ impl DescriptorSet {
         );
     }
 
+    /// Updates a descriptor set that may be shared, without having to allocate a new one.
+    ///
+    /// Bindings that were created with the [`DescriptorBindingFlags::UPDATE_AFTER_BIND`] flag can
+    /// always be updated. Other bindings can only be updated while the descriptor set is not in
+    /// use, which is the case when `self` is the only reference to it, apart from the `src_set`
+    /// of copies within the set. Command buffers that the descriptor set was bound in keep a
+    /// reference to it until they are dropped.
+    pub fn update_shared(
+        self: &Arc<Self>,
+        descriptor_writes: impl IntoIterator<Item = WriteDescriptorSet>,
+        descriptor_copies: impl IntoIterator<Item = CopyDescriptorSet>,
+    ) -> Result<(), Box<ValidationError>> {
+        let descriptor_writes: SmallVec<[_; 8]> = descriptor_writes.into_iter().collect();
+        let descriptor_copies: SmallVec<[_; 8]> = descriptor_copies.into_iter().collect();
+        if descriptor_writes.is_empty() && descriptor_copies.is_empty() {
+            return Ok(());
+        }
+
+        self.validate_update_shared(&descriptor_writes, &descriptor_copies)?;
+
+        // Holding the lock for the whole update externally synchronizes host access to the
+        // descriptor set.
+        unsafe {
+            Self::update_inner(
+                &self.inner,
+                &mut self.resources.write(),
+                &descriptor_writes,
+                &descriptor_copies,
+            );
+        }
+
+        Ok(())
+    }
+
+    fn validate_update_shared(
+        self: &Arc<Self>,
+        descriptor_writes: &[WriteDescriptorSet],
+        descriptor_copies: &[CopyDescriptorSet],
+    ) -> Result<(), Box<ValidationError>> {
+        self.inner
+            .validate_update(descriptor_writes, descriptor_copies)?;
+
+        let internal_references = descriptor_copies
+            .iter()
+            .filter(|copy| Arc::ptr_eq(&copy.src_set, self))
+            .count();
+
+        if Arc::strong_count(self) - internal_references == 1 {
+            return Ok(());
+        }
+
+        let is_update_after_bind = |binding: u32| {
+            self.layout().bindings()[&binding]
+                .binding_flags
+                .intersects(DescriptorBindingFlags::UPDATE_AFTER_BIND)
+        };
+
+        for (index, write) in descriptor_writes.iter().enumerate() {
+            if !is_update_after_bind(write.binding()) {
+                return Err(Box::new(ValidationError {
+                    context: format!("descriptor_writes[{}].binding", index).into(),
+                    problem: "the descriptor set is in use, and the binding was not created with \
+                        the `DescriptorBindingFlags::UPDATE_AFTER_BIND` flag"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        for (index, copy) in descriptor_copies.iter().enumerate() {
+            if !is_update_after_bind(copy.dst_binding) {
+                return Err(Box::new(ValidationError {
+                    context: format!("descriptor_copies[{}].dst_binding", index).into(),
+                    problem: "the descriptor set is in use, and the binding was not created with \
+                        the `DescriptorBindingFlags::UPDATE_AFTER_BIND` flag"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copies descriptors from other descriptor sets, or from within this one, into this
+    /// descriptor set.
+    ///
+    /// This is equivalent to calling [`update_shared`](Self::update_shared) without any writes,
+    /// and the same requirements apply.
+    #[inline]
+    pub fn copy_descriptor_sets(
+        self: &Arc<Self>,
+        descriptor_copies: impl IntoIterator<Item = CopyDescriptorSet>,
+    ) -> Result<(), Box<ValidationError>> {
+        self.update_shared([], descriptor_copies)
+    }
+
     unsafe fn update_inner(
         inner: &RawDescriptorSet,
         resources: &mut DescriptorSetResources,
@@ -292,7 +389,12 @@ impl DescriptorSet {
         }
 
         for copy in descriptor_copies {
-            resources.copy(copy);
+            // The resources of the set that is updated are already locked.
+            if copy.src_set.handle() == inner.handle() {
+                resources.copy_within(copy);
+            } else {
+                resources.copy(copy);
+            }
         }
     }
 
@@ -481,6 +583,46 @@ impl DescriptorSetResources {
             );
     }
 
+    #[inline]
+    pub(crate) fn copy_within(&mut self, copy: &CopyDescriptorSet) {
+        // When copying between two different bindings, the source binding is temporarily taken
+        // out of the map, so that its resources don't have to be cloned. Only a copy within the
+        // same binding, where the ranges may overlap, needs a copy of the source.
+        if copy.src_binding != copy.dst_binding {
+            let src = self
+                .binding_resources
+                .remove(&copy.src_binding)
+                .expect("descriptor copy has invalid src_binding number");
+            self.binding_resources
+                .get_mut(&copy.dst_binding)
+                .expect("descriptor copy has invalid dst_binding number")
+                .copy(
+                    &src,
+                    copy.src_first_array_element,
+                    copy.dst_first_array_element,
+                    copy.descriptor_count,
+                );
+            self.binding_resources.insert(copy.src_binding, src);
+
+            return;
+        }
+
+        let src = self
+            .binding_resources
+            .get(&copy.src_binding)
+            .expect("descriptor copy has invalid src_binding number")
+            .clone();
+        self.binding_resources
+            .get_mut(&copy.dst_binding)
+            .expect("descriptor copy has invalid dst_binding number")
+            .copy(
+                &src,
+                copy.src_first_array_element,
+                copy.dst_first_array_element,
+                copy.descriptor_count,
+            );
+    }
+
     #[inline]
     pub(crate) fn invalidate(&mut self, invalidate: &InvalidateDescriptorSet) {
         self.binding_resources
@@ -523,6 +665,16 @@ impl DescriptorBindingResources {
                 });
         }
 
+        fn clear_resources<T>(first: usize, resources: &mut [Option<T>], num_elements: u32) {
+            resources
+                .get_mut(first..first + num_elements as usize)
+                .expect("descriptor write for binding out of bounds")
+                .iter_mut()
+                .for_each(|resource| {
+                    *resource = None;
+                });
+        }
+
         let default_image_layout = descriptor_type.default_image_layout();
         let first = write.first_array_element() as usize;
 
@@ -537,6 +689,19 @@ impl DescriptorBindingResources {
                             *resource = Some(());
                         });
                 }
+                // Null descriptors are tracked as having no resource.
+                DescriptorBindingResources::Buffer(resources) => {
+                    clear_resources(first, resources, *num_elements)
+                }
+                DescriptorBindingResources::BufferView(resources) => {
+                    clear_resources(first, resources, *num_elements)
+                }
+                DescriptorBindingResources::ImageView(resources) => {
+                    clear_resources(first, resources, *num_elements)
+                }
+                DescriptorBindingResources::AccelerationStructure(resources) => {
+                    clear_resources(first, resources, *num_elements)
+                }
                 _ => panic!(
                     "descriptor write for binding {} has wrong resource type",
                     write.binding(),
@@ -125,8 +125,8 @@ impl RawDescriptorSet {
             return;
         }
 
-        struct PerDescriptorWrite {
-            write_info: DescriptorWriteInfo,
+        struct PerDescriptorWrite<'a> {
+            write_info: DescriptorWriteInfo<'a>,
             acceleration_structures: ash::vk::WriteDescriptorSetAccelerationStructureKHR<'static>,
             inline_uniform_block: ash::vk::WriteDescriptorSetInlineUniformBlock<'static>,
         }
@@ -46,6 +46,15 @@ impl WriteDescriptorSet {
     ///
     /// For regular descriptor sets, the data for such descriptors is automatically valid, and
     /// dummy writes are not allowed.
+    ///
+    /// For descriptor types other than `Sampler`, this writes a null descriptor instead, which
+    /// requires the [`null_descriptor`] feature to be enabled on the device. A shader that reads
+    /// from a null descriptor gets zeroes, and writes to it are discarded. This lets a binding be
+    /// left empty without having to create a dummy resource for it. Null descriptors can be
+    /// written to buffer, texel buffer, sampled image, storage image and acceleration structure
+    /// bindings, as well as combined image sampler bindings that have immutable samplers.
+    ///
+    /// [`null_descriptor`]: crate::device::DeviceFeatures::null_descriptor
     #[inline]
     pub fn none(binding: u32) -> Self {
         Self::none_array(binding, 0, 1)
@@ -408,6 +417,70 @@ impl WriteDescriptorSet {
         let array_element_count = elements.len();
         debug_assert!(array_element_count != 0);
 
+        // VUID-VkWriteDescriptorSet-dstArrayElement-00321
+        if first_array_element + array_element_count > max_descriptor_count {
+            return Err(Box::new(ValidationError {
+                problem: "`first_array_element` + the number of provided elements is greater than \
+                    the number of descriptors in the descriptor set binding"
+                    .into(),
+                vuids: &["VUID-VkWriteDescriptorSet-dstArrayElement-00321"],
+                ..Default::default()
+            }));
+        }
+
+        if let WriteDescriptorSetElements::None(_) = elements {
+            if layout_binding.descriptor_type != DescriptorType::Sampler {
+                match layout_binding.descriptor_type {
+                    DescriptorType::UniformBuffer
+                    | DescriptorType::StorageBuffer
+                    | DescriptorType::UniformBufferDynamic
+                    | DescriptorType::StorageBufferDynamic
+                    | DescriptorType::UniformTexelBuffer
+                    | DescriptorType::StorageTexelBuffer
+                    | DescriptorType::SampledImage
+                    | DescriptorType::StorageImage
+                    | DescriptorType::AccelerationStructure => (),
+                    DescriptorType::CombinedImageSampler
+                        if !layout_binding.immutable_samplers.is_empty() => {}
+                    _ => {
+                        return Err(Box::new(ValidationError {
+                            context: "elements".into(),
+                            problem: format!(
+                                "contains `none` elements, but null descriptors cannot be \
+                                written to descriptor set binding {}",
+                                binding,
+                            )
+                            .into(),
+                            vuids: &["VUID-VkWriteDescriptorSet-descriptorType-02997"],
+                            ..Default::default()
+                        }));
+                    }
+                }
+
+                if !device.enabled_features().null_descriptor {
+                    return Err(Box::new(ValidationError {
+                        context: "elements".into(),
+                        problem: format!(
+                            "contains `none` elements, and descriptor set binding {} does not \
+                            have the `DescriptorType::Sampler` descriptor type",
+                            binding,
+                        )
+                        .into(),
+                        requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                            Requires::DeviceFeature("null_descriptor"),
+                        ])]),
+                        vuids: &[
+                            "VUID-VkWriteDescriptorSet-descriptorType-02997",
+                            "VUID-VkWriteDescriptorSet-descriptorType-02998",
+                            "VUID-VkWriteDescriptorSetAccelerationStructureKHR-pAccelerationStructures-03580",
+                        ],
+                    }));
+                }
+
+                return Ok(());
+            }
+        }
+
         let validate_image_view =
             |image_view: &ImageView, index: usize| -> Result<(), Box<ValidationError>> {
                 if image_view.image().image_type() == ImageType::Dim3d {
@@ -444,7 +517,7 @@ impl WriteDescriptorSet {
                                         context: format!("elements[{}]", index).into(),
                                         problem: format!(
                                             "the descriptor type is `DescriptorType::{:?}`, and \
-                                            the image view's type is `ImageViewType::Dim2d`,
+                                            the image view's type is `ImageViewType::Dim2d`, \
                                             and was created from a 3D image",
                                             layout_binding.descriptor_type,
                                         )
@@ -462,7 +535,7 @@ impl WriteDescriptorSet {
                                         context: format!("elements[{}]", index).into(),
                                         problem: format!(
                                             "the descriptor type is `DescriptorType::{:?}`, and \
-                                            the image view's type is `ImageViewType::Dim2d`,
+                                            the image view's type is `ImageViewType::Dim2d`, \
                                             and was created from a 3D image",
                                             layout_binding.descriptor_type,
                                         )
@@ -480,8 +553,8 @@ impl WriteDescriptorSet {
                                     problem: "the descriptor type is not \
                                         `DescriptorType::StorageImage`, \
                                         `DescriptorType::SampledImage` or \
-                                        `DescriptorType::CombinedImageSampler`,\
-                                        and the image view's type is `ImageViewType::Dim2D`, \
+                                        `DescriptorType::CombinedImageSampler`, \
+                                        and the image view's type is `ImageViewType::Dim2d`, \
                                         and was created from a 3D image"
                                         .into(),
                                     vuids: &["VUID-VkDescriptorImageInfo-imageView-07795"],
@@ -968,16 +1041,14 @@ impl WriteDescriptorSet {
                     assert_eq!(device, buffer_view.device());
 
                     if !buffer_view
-                        .buffer()
-                        .buffer()
                         .usage()
                         .intersects(BufferUsage::UNIFORM_TEXEL_BUFFER)
                     {
                         return Err(Box::new(ValidationError {
                             context: format!("elements[{}]", index).into(),
                             problem: "the descriptor type is \
-                                `DescriptorType::UniformTexelBuffer`, and the buffer was not \
-                                created with the `BufferUsage::UNIFORM_TEXEL_BUFFER` usage"
+                                `DescriptorType::UniformTexelBuffer`, and the usage of the buffer \
+                                view does not contain `BufferUsage::UNIFORM_TEXEL_BUFFER`"
                                 .into(),
                             vuids: &["VUID-VkWriteDescriptorSet-descriptorType-00334"],
                             ..Default::default()
@@ -1009,16 +1080,14 @@ impl WriteDescriptorSet {
 
                     // TODO: storage_texel_buffer_atomic
                     if !buffer_view
-                        .buffer()
-                        .buffer()
                         .usage()
                         .intersects(BufferUsage::STORAGE_TEXEL_BUFFER)
                     {
                         return Err(Box::new(ValidationError {
                             context: format!("elements[{}]", index).into(),
                             problem: "the descriptor type is \
-                                `DescriptorType::StorageTexelBuffer`, and the buffer was not \
-                                created with the `BufferUsage::STORAGE_TEXEL_BUFFER` usage"
+                                `DescriptorType::StorageTexelBuffer`, and the usage of the buffer \
+                                view does not contain `BufferUsage::STORAGE_TEXEL_BUFFER`"
                                 .into(),
                             vuids: &["VUID-VkWriteDescriptorSet-descriptorType-00335"],
                             ..Default::default()
@@ -1313,35 +1382,66 @@ impl WriteDescriptorSet {
             }
         }
 
-        // VUID-VkWriteDescriptorSet-dstArrayElement-00321
-        if first_array_element + array_element_count > max_descriptor_count {
-            return Err(Box::new(ValidationError {
-                problem: "`first_array_element` + the number of provided elements is greater than \
-                    the number of descriptors in the descriptor set binding"
-                    .into(),
-                vuids: &["VUID-VkWriteDescriptorSet-dstArrayElement-00321"],
-                ..Default::default()
-            }));
-        }
-
         Ok(())
     }
 
-    pub(crate) fn to_vulkan_info(&self, descriptor_type: DescriptorType) -> DescriptorWriteInfo {
+    pub(crate) fn to_vulkan_info(
+        &self,
+        descriptor_type: DescriptorType,
+    ) -> DescriptorWriteInfo<'_> {
         let default_image_layout = descriptor_type.default_image_layout();
 
         match &self.elements {
             WriteDescriptorSetElements::None(num_elements) => {
-                debug_assert!(matches!(descriptor_type, DescriptorType::Sampler));
-                DescriptorWriteInfo::Image(
-                    std::iter::repeat_with(|| ash::vk::DescriptorImageInfo {
-                        sampler: ash::vk::Sampler::null(),
-                        image_view: ash::vk::ImageView::null(),
-                        image_layout: ash::vk::ImageLayout::UNDEFINED,
-                    })
-                    .take(*num_elements as usize)
-                    .collect(),
-                )
+                let num_elements = *num_elements as usize;
+
+                // For any descriptor type other than `Sampler`, this writes null descriptors.
+                match descriptor_type {
+                    DescriptorType::UniformBuffer
+                    | DescriptorType::StorageBuffer
+                    | DescriptorType::UniformBufferDynamic
+                    | DescriptorType::StorageBufferDynamic => DescriptorWriteInfo::Buffer(
+                        std::iter::repeat_with(|| ash::vk::DescriptorBufferInfo {
+                            buffer: ash::vk::Buffer::null(),
+                            offset: 0,
+                            range: ash::vk::WHOLE_SIZE,
+                        })
+                        .take(num_elements)
+                        .collect(),
+                    ),
+                    DescriptorType::UniformTexelBuffer | DescriptorType::StorageTexelBuffer => {
+                        DescriptorWriteInfo::BufferView(
+                            std::iter::repeat(ash::vk::BufferView::null())
+                                .take(num_elements)
+                                .collect(),
+                        )
+                    }
+                    DescriptorType::AccelerationStructure => {
+                        DescriptorWriteInfo::AccelerationStructure(
+                            std::iter::repeat(ash::vk::AccelerationStructureKHR::null())
+                                .take(num_elements)
+                                .collect(),
+                        )
+                    }
+                    _ => {
+                        debug_assert!(matches!(
+                            descriptor_type,
+                            DescriptorType::Sampler
+                                | DescriptorType::CombinedImageSampler
+                                | DescriptorType::SampledImage
+                                | DescriptorType::StorageImage
+                        ));
+                        DescriptorWriteInfo::Image(
+                            std::iter::repeat_with(|| ash::vk::DescriptorImageInfo {
+                                sampler: ash::vk::Sampler::null(),
+                                image_view: ash::vk::ImageView::null(),
+                                image_layout: ash::vk::ImageLayout::UNDEFINED,
+                            })
+                            .take(num_elements)
+                            .collect(),
+                        )
+                    }
+                }
             }
             WriteDescriptorSetElements::Buffer(elements) => {
                 debug_assert!(matches!(
@@ -1457,7 +1557,7 @@ impl WriteDescriptorSet {
                     descriptor_type,
                     DescriptorType::InlineUniformBlock
                 ));
-                DescriptorWriteInfo::InlineUniformBlock(data.clone())
+                DescriptorWriteInfo::InlineUniformBlock(data)
             }
             WriteDescriptorSetElements::AccelerationStructure(elements) => {
                 debug_assert!(matches!(
@@ -1567,11 +1667,11 @@ pub struct DescriptorImageViewInfo {
 }
 
 #[derive(Clone, Debug)]
-pub(crate) enum DescriptorWriteInfo {
+pub(crate) enum DescriptorWriteInfo<'a> {
     Image(SmallVec<[ash::vk::DescriptorImageInfo; 1]>),
     Buffer(SmallVec<[ash::vk::DescriptorBufferInfo; 1]>),
     BufferView(SmallVec<[ash::vk::BufferView; 1]>),
-    InlineUniformBlock(Vec<u8>),
+    InlineUniformBlock(&'a [u8]),
     AccelerationStructure(SmallVec<[ash::vk::AccelerationStructureKHR; 1]>),
 }
 
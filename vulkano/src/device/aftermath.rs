@@ -0,0 +1,68 @@
+//! Hook points for integrating the [NVIDIA Nsight Aftermath SDK].
+//!
+//! Vulkano does not link to the Aftermath SDK itself. Instead, this module lets the application
+//! run its own Aftermath code at the points where the SDK requires it:
+//!
+//! - GPU crash dumps must be enabled with `GFSDK_Aftermath_EnableGpuCrashDumps` before the device
+//!   is created, which is done in [`AftermathHooks::before_create_device`].
+//! - To decode a crash dump, Aftermath asks for the shader binaries by their hash. The SPIR-V code
+//!   of every shader module is passed to [`AftermathHooks::shader_module_created`], so that the
+//!   application can compute its hash with `GFSDK_Aftermath_GetShaderHashSpirv` and keep the code
+//!   around for when it's needed.
+//!
+//! To collect the information that makes crash dumps useful, the device should also be created
+//! with the [`nv_device_diagnostics_config`] extension and the appropriate
+//! [`DeviceCreateInfo::diagnostics_config_flags`]. Commands can be annotated with
+//! [`set_checkpoint`], which Aftermath includes in the crash dump as well.
+//!
+//! This module is only available when the `aftermath` feature is enabled.
+//!
+//! [NVIDIA Nsight Aftermath SDK]: https://developer.nvidia.com/nsight-aftermath
+//! [`nv_device_diagnostics_config`]: crate::device::DeviceExtensions::nv_device_diagnostics_config
+//! [`set_checkpoint`]: crate::command_buffer::RecordingCommandBuffer::set_checkpoint
+
+use super::{physical::PhysicalDevice, DeviceCreateInfo};
+use crate::shader::ShaderModule;
+use parking_lot::{const_rwlock, RwLock};
+use std::sync::Arc;
+
+static HOOKS: RwLock<Option<Arc<dyn AftermathHooks>>> = const_rwlock(None);
+
+/// Functions that vulkano calls at the points where the Aftermath SDK needs to be involved.
+///
+/// All functions have an empty default implementation, so that only the ones that are needed have
+/// to be implemented.
+pub trait AftermathHooks: Send + Sync {
+    /// Called right before a device is created with `physical_device` and `create_info`.
+    ///
+    /// This is where GPU crash dumps should be enabled, and the crash dump callbacks registered.
+    #[inline]
+    fn before_create_device(
+        &self,
+        physical_device: &PhysicalDevice,
+        create_info: &DeviceCreateInfo,
+    ) {
+        let _ = (physical_device, create_info);
+    }
+
+    /// Called after `shader_module` has been created from the SPIR-V words in `code`.
+    ///
+    /// This is where the shader binary should be associated with its Aftermath shader hash.
+    #[inline]
+    fn shader_module_created(&self, shader_module: &Arc<ShaderModule>, code: &[u32]) {
+        let _ = (shader_module, code);
+    }
+}
+
+/// Sets the hooks that vulkano calls for all devices and shader modules that are created
+/// afterwards, replacing any previously set hooks.
+///
+/// If `hooks` is `None`, no hooks are called anymore.
+#[inline]
+pub fn set_aftermath_hooks(hooks: Option<Arc<dyn AftermathHooks>>) {
+    *HOOKS.write() = hooks;
+}
+
+pub(crate) fn hooks() -> Option<Arc<dyn AftermathHooks>> {
+    HOOKS.read().clone()
+}
@@ -0,0 +1,56 @@
+//! Interning of the names of checkpoints, that are set with [`set_checkpoint`].
+//!
+//! A checkpoint marker is an opaque pointer that the implementation hands back as-is when the
+//! checkpoint data of a queue is retrieved. Rather than pointing to the name itself, which would
+//! require the name to outlive any use of the marker, each distinct name is given an index in the
+//! device, and the marker is that index plus one. This way, the name can be looked up again even
+//! after the command buffer that set the checkpoint is long gone, and a marker that was not set
+//! through vulkano is never mistaken for a pointer.
+//!
+//! [`set_checkpoint`]: crate::command_buffer::RecordingCommandBuffer::set_checkpoint
+
+use ahash::HashMap;
+use parking_lot::RwLock;
+use std::{ffi::c_void, sync::Arc};
+
+#[derive(Default)]
+pub(crate) struct CheckpointNames {
+    inner: RwLock<CheckpointNamesInner>,
+}
+
+#[derive(Default)]
+struct CheckpointNamesInner {
+    indices: HashMap<Arc<str>, usize>,
+    names: Vec<Arc<str>>,
+}
+
+impl CheckpointNames {
+    /// Returns the marker for the checkpoint with the given name.
+    pub(crate) fn marker(&self, name: &str) -> *const c_void {
+        if let Some(&index) = self.inner.read().indices.get(name) {
+            return (index + 1) as *const c_void;
+        }
+
+        let mut inner = self.inner.write();
+
+        // Another thread may have added the name in the meantime.
+        if let Some(&index) = inner.indices.get(name) {
+            return (index + 1) as *const c_void;
+        }
+
+        let name: Arc<str> = name.into();
+        let index = inner.names.len();
+        inner.names.push(name.clone());
+        inner.indices.insert(name, index);
+
+        (index + 1) as *const c_void
+    }
+
+    /// Returns the name of the checkpoint with the given marker, or `None` if the marker was not
+    /// returned by [`marker`](Self::marker).
+    pub(crate) fn name(&self, marker: *const c_void) -> Option<Arc<str>> {
+        (marker as usize)
+            .checked_sub(1)
+            .and_then(|index| self.inner.read().names.get(index).cloned())
+    }
+}
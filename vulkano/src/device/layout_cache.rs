@@ -0,0 +1,172 @@
+//! Interning of descriptor set layouts and pipeline layouts, enabled with
+//! [`DeviceCreateInfo::intern_layouts`].
+//!
+//! The caches are keyed on the contents of the create infos. Objects that are referenced by a
+//! create info, such as immutable samplers and descriptor set layouts, are keyed on their ID rather
+//! than being held, so that the caches in the device don't keep objects alive that themselves keep
+//! the device alive. Since IDs are never reused, an entry that refers to a destroyed object can
+//! never be hit again, and is removed along with the other expired entries.
+//!
+//! [`DeviceCreateInfo::intern_layouts`]: super::DeviceCreateInfo::intern_layouts
+
+use crate::{
+    descriptor_set::layout::{
+        DescriptorBindingFlags, DescriptorSetLayout, DescriptorSetLayoutBinding,
+        DescriptorSetLayoutCreateFlags, DescriptorSetLayoutCreateInfo, DescriptorType,
+    },
+    pipeline::layout::{
+        PipelineLayout, PipelineLayoutCreateFlags, PipelineLayoutCreateInfo, PushConstantRange,
+    },
+    shader::ShaderStages,
+    VulkanError,
+};
+use ahash::HashMap;
+use parking_lot::Mutex;
+use std::{
+    num::NonZeroU64,
+    sync::{Arc, Weak},
+};
+
+#[derive(Default)]
+pub(crate) struct LayoutCache {
+    descriptor_set_layouts: Mutex<HashMap<DescriptorSetLayoutKey, Weak<DescriptorSetLayout>>>,
+    pipeline_layouts: Mutex<HashMap<PipelineLayoutKey, Weak<PipelineLayout>>>,
+}
+
+impl LayoutCache {
+    /// Returns the live descriptor set layout that was created from an identical create info, or
+    /// calls `create` with `create_info` and stores the result.
+    pub(crate) fn descriptor_set_layout(
+        &self,
+        create_info: DescriptorSetLayoutCreateInfo,
+        create: impl FnOnce(
+            DescriptorSetLayoutCreateInfo,
+        ) -> Result<Arc<DescriptorSetLayout>, VulkanError>,
+    ) -> Result<Arc<DescriptorSetLayout>, VulkanError> {
+        let key = DescriptorSetLayoutKey::new(&create_info);
+
+        get_or_create(&self.descriptor_set_layouts, key, || create(create_info))
+    }
+
+    /// Returns the live pipeline layout that was created from an identical create info, or calls
+    /// `create` with `create_info` and stores the result.
+    pub(crate) fn pipeline_layout(
+        &self,
+        create_info: PipelineLayoutCreateInfo,
+        create: impl FnOnce(PipelineLayoutCreateInfo) -> Result<Arc<PipelineLayout>, VulkanError>,
+    ) -> Result<Arc<PipelineLayout>, VulkanError> {
+        let key = PipelineLayoutKey::new(&create_info);
+
+        get_or_create(&self.pipeline_layouts, key, || create(create_info))
+    }
+}
+
+fn get_or_create<K, T>(
+    cache: &Mutex<HashMap<K, Weak<T>>>,
+    key: K,
+    create: impl FnOnce() -> Result<Arc<T>, VulkanError>,
+) -> Result<Arc<T>, VulkanError>
+where
+    K: Eq + std::hash::Hash,
+{
+    // The lock is held while creating the object, so that two threads asking for the same layout
+    // at the same time don't both create it.
+    let mut cache = cache.lock();
+
+    if let Some(object) = cache.get(&key).and_then(Weak::upgrade) {
+        return Ok(object);
+    }
+
+    cache.retain(|_, object| object.strong_count() != 0);
+
+    let object = create()?;
+    cache.insert(key, Arc::downgrade(&object));
+
+    Ok(object)
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct DescriptorSetLayoutKey {
+    flags: DescriptorSetLayoutCreateFlags,
+    bindings: Vec<(u32, DescriptorSetLayoutBindingKey)>,
+}
+
+impl DescriptorSetLayoutKey {
+    fn new(create_info: &DescriptorSetLayoutCreateInfo) -> Self {
+        let &DescriptorSetLayoutCreateInfo {
+            flags,
+            ref bindings,
+            _ne: _,
+        } = create_info;
+
+        Self {
+            flags,
+            bindings: bindings
+                .iter()
+                .map(|(&binding_num, binding)| {
+                    (binding_num, DescriptorSetLayoutBindingKey::new(binding))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct DescriptorSetLayoutBindingKey {
+    binding_flags: DescriptorBindingFlags,
+    descriptor_type: DescriptorType,
+    descriptor_count: u32,
+    stages: ShaderStages,
+    immutable_samplers: Vec<NonZeroU64>,
+}
+
+impl DescriptorSetLayoutBindingKey {
+    fn new(binding: &DescriptorSetLayoutBinding) -> Self {
+        let &DescriptorSetLayoutBinding {
+            binding_flags,
+            descriptor_type,
+            descriptor_count,
+            stages,
+            ref immutable_samplers,
+            _ne: _,
+        } = binding;
+
+        Self {
+            binding_flags,
+            descriptor_type,
+            descriptor_count,
+            stages,
+            immutable_samplers: immutable_samplers
+                .iter()
+                .map(|sampler| sampler.id())
+                .collect(),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct PipelineLayoutKey {
+    flags: PipelineLayoutCreateFlags,
+    set_layouts: Vec<NonZeroU64>,
+    push_constant_ranges: Vec<PushConstantRange>,
+}
+
+impl PipelineLayoutKey {
+    fn new(create_info: &PipelineLayoutCreateInfo) -> Self {
+        let &PipelineLayoutCreateInfo {
+            flags,
+            ref set_layouts,
+            ref push_constant_ranges,
+            _ne: _,
+        } = create_info;
+
+        Self {
+            flags,
+            set_layouts: set_layouts
+                .iter()
+                .map(|set_layout| set_layout.id())
+                .collect(),
+            push_constant_ranges: push_constant_ranges.clone(),
+        }
+    }
+}
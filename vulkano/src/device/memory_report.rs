@@ -0,0 +1,198 @@
+//! Observing the device memory that the Vulkan implementation allocates.
+//!
+//! Memory allocated with vulkano's allocators is only part of the device memory that a program
+//! uses. The implementation also allocates device memory internally, for example for pipelines,
+//! descriptor pools and command buffers, which no Vulkan function reports. With the
+//! [`ext_device_memory_report`] extension and the [`device_memory_report`] feature enabled, a
+//! [`DeviceMemoryReportCallback`] can be given in
+//! [`DeviceCreateInfo::device_memory_report_callbacks`], which is then called for every
+//! allocation and free of device memory, whether it's made by the application or by the
+//! implementation.
+//!
+//! ```
+//! use vulkano::device::memory_report::{DeviceMemoryReportCallback, DeviceMemoryReportEventType};
+//!
+//! let (callback, receiver) = DeviceMemoryReportCallback::channel();
+//!
+//! // Pass `callback` in `DeviceCreateInfo::device_memory_report_callbacks`, and then at some
+//! // later point:
+//! for event in receiver.try_iter() {
+//!     if event.event_type == DeviceMemoryReportEventType::AllocationFailed {
+//!         println!("failed to allocate {} bytes on heap {}", event.size, event.heap_index);
+//!     }
+//! }
+//! ```
+//!
+//! The callbacks are called from whichever thread triggered the event, possibly while the
+//! implementation holds internal locks, so they should do as little work as possible.
+//!
+//! [`ext_device_memory_report`]: super::DeviceExtensions::ext_device_memory_report
+//! [`device_memory_report`]: super::DeviceFeatures::device_memory_report
+//! [`DeviceCreateInfo::device_memory_report_callbacks`]: super::DeviceCreateInfo::device_memory_report_callbacks
+
+use crate::{macros::vulkan_enum, DeviceSize};
+use std::{
+    ffi::c_void,
+    fmt::{Debug, Error as FmtError, Formatter},
+    panic::{catch_unwind, AssertUnwindSafe, RefUnwindSafe},
+    ptr,
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+};
+
+/// A user-provided callback that is called when device memory is allocated or freed.
+pub struct DeviceMemoryReportCallback(CallbackData);
+
+type CallbackData = Box<dyn Fn(DeviceMemoryReportCallbackData) + RefUnwindSafe + Send + Sync>;
+
+impl DeviceMemoryReportCallback {
+    /// Returns a new `DeviceMemoryReportCallback` wrapping the provided function.
+    ///
+    /// # Safety
+    ///
+    /// - `func` must not make any calls to the Vulkan API.
+    pub unsafe fn new(
+        func: impl Fn(DeviceMemoryReportCallbackData) + RefUnwindSafe + Send + Sync + 'static,
+    ) -> Arc<Self> {
+        Arc::new(Self(Box::new(func)))
+    }
+
+    /// Returns a new `DeviceMemoryReportCallback` that sends every event to the returned
+    /// receiver.
+    ///
+    /// Events that are sent after the receiver has been dropped are discarded.
+    pub fn channel() -> (Arc<Self>, Receiver<DeviceMemoryReportCallbackData>) {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+
+        // SAFETY: The function doesn't call the Vulkan API.
+        let callback = unsafe {
+            Self::new(move |callback_data| {
+                if let Ok(sender) = sender.lock() {
+                    let _ = sender.send(callback_data);
+                }
+            })
+        };
+
+        (callback, receiver)
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const CallbackData {
+        ptr::addr_of!(self.0)
+    }
+}
+
+impl Debug for DeviceMemoryReportCallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_struct("DeviceMemoryReportCallback")
+            .finish_non_exhaustive()
+    }
+}
+
+pub(super) unsafe extern "system" fn trampoline(
+    callback_data_vk: *const ash::vk::DeviceMemoryReportCallbackDataEXT<'_>,
+    user_data_vk: *mut c_void,
+) {
+    // Since we box the closure, the type system doesn't detect that the `UnwindSafe`
+    // bound is enforced. Therefore we enforce it manually.
+    let _ = catch_unwind(AssertUnwindSafe(move || {
+        let ash::vk::DeviceMemoryReportCallbackDataEXT {
+            s_type: _,
+            p_next: _,
+            flags: _,
+            ty,
+            memory_object_id,
+            size,
+            object_type,
+            object_handle,
+            heap_index,
+            _marker: _,
+        } = *callback_data_vk;
+
+        // Events of types that vulkano doesn't know about yet are skipped.
+        let Ok(event_type) = DeviceMemoryReportEventType::try_from(ty) else {
+            return;
+        };
+
+        let callback_data = DeviceMemoryReportCallbackData {
+            event_type,
+            memory_object_id,
+            size,
+            object_type,
+            object_handle,
+            heap_index,
+        };
+
+        let user_callback = &*user_data_vk.cast_const().cast::<CallbackData>();
+
+        user_callback(callback_data);
+    }));
+}
+
+/// An event that is passed to a [`DeviceMemoryReportCallback`].
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct DeviceMemoryReportCallbackData {
+    /// The type of the event.
+    pub event_type: DeviceMemoryReportEventType,
+
+    /// A unique ID of the device memory object that the event is about.
+    ///
+    /// An allocation and the free of the same memory have the same ID, and an imported memory
+    /// object has the same ID as the memory that it was exported from.
+    pub memory_object_id: u64,
+
+    /// The size of the memory object in bytes.
+    ///
+    /// This is only meaningful for the [`Allocate`], [`Import`] and [`AllocationFailed`] events.
+    ///
+    /// [`Allocate`]: DeviceMemoryReportEventType::Allocate
+    /// [`Import`]: DeviceMemoryReportEventType::Import
+    /// [`AllocationFailed`]: DeviceMemoryReportEventType::AllocationFailed
+    pub size: DeviceSize,
+
+    /// The type of the Vulkan object that the memory is associated with.
+    ///
+    /// For memory that is allocated with `vkAllocateMemory`, this is
+    /// `ash::vk::ObjectType::DEVICE_MEMORY`. For memory that the implementation allocates
+    /// internally, this is the type of the object that the memory belongs to, or
+    /// `ash::vk::ObjectType::UNKNOWN` if it doesn't belong to a specific object.
+    pub object_type: ash::vk::ObjectType,
+
+    /// The handle of the Vulkan object that the memory is associated with, or zero if there is
+    /// none.
+    pub object_handle: u64,
+
+    /// The index of the memory heap that the memory is allocated from.
+    ///
+    /// This is only meaningful for the [`Allocate`], [`Import`] and [`AllocationFailed`] events.
+    ///
+    /// [`Allocate`]: DeviceMemoryReportEventType::Allocate
+    /// [`Import`]: DeviceMemoryReportEventType::Import
+    /// [`AllocationFailed`]: DeviceMemoryReportEventType::AllocationFailed
+    pub heap_index: u32,
+}
+
+vulkan_enum! {
+    #[non_exhaustive]
+
+    /// The type of a device memory report event.
+    DeviceMemoryReportEventType = DeviceMemoryReportEventTypeEXT(i32);
+
+    /// Memory was allocated.
+    Allocate = ALLOCATE,
+
+    /// Memory was freed.
+    Free = FREE,
+
+    /// Memory was imported from an external handle.
+    Import = IMPORT,
+
+    /// Imported memory was freed.
+    Unimport = UNIMPORT,
+
+    /// Allocating memory failed.
+    AllocationFailed = ALLOCATION_FAILED,
+}
@@ -74,6 +74,28 @@
 //! > **Note**: The fact that you need to manually enable features at initialization also means
 //! > that you don't need to worry about a capability not being supported later on in your code.
 //!
+//! ## Portability subset devices
+//!
+//! Devices that support the [`khr_portability_subset`](DeviceExtensions::khr_portability_subset)
+//! extension, such as MoltenVK on macOS and iOS, leave out some functionality that Vulkan
+//! otherwise requires. They are only enumerated if the instance was created with the
+//! `ENUMERATE_PORTABILITY` flag of [`InstanceCreateFlags`], as described in the [instance module],
+//! and the extension is always enabled on them when the device is created.
+//!
+//! Each piece of missing functionality has a feature in [`DeviceFeatures`], such as
+//! [`triangle_fans`](DeviceFeatures::triangle_fans),
+//! [`point_polygons`](DeviceFeatures::point_polygons) and
+//! [`separate_stencil_mask_ref`](DeviceFeatures::separate_stencil_mask_ref), which are only
+//! required on these devices. Functionality that is optional on every device, such as
+//! [`wide_lines`](DeviceFeatures::wide_lines), is often not supported by them either. Vulkano
+//! checks these features wherever the functionality is used, both when creating pipelines and
+//! when setting or using dynamic state, so using something that the device doesn't support is
+//! reported as a [`ValidationError`] that names the feature to enable, rather than failing in the
+//! driver.
+//!
+//! [instance module]: crate::instance#portability-subset-devices-and-the-enumerate_portability-flag
+//! [`InstanceCreateFlags`]: crate::instance::InstanceCreateFlags
+//!
 //! # Queues
 //!
 //! Each physical device proposes one or more *queues* that are divided in *queue families*. A
@@ -92,30 +114,84 @@
 //!
 //! The `Device::new` function returns the newly-created device, but also the list of queues.
 //!
+//! # Adopting externally created objects
+//!
+//! When vulkano shares a process with another library that also uses Vulkan (for example a
+//! video SDK, an engine or code written directly against `ash`), objects created by that library
+//! can be wrapped as vulkano objects using the `from_handle` family of constructors. There are two
+//! destruction policies:
+//!
+//! - `from_handle` transfers ownership to vulkano. The object is destroyed when the vulkano
+//!   object is dropped, and the other library must not destroy it.
+//! - `from_handle_borrowed` leaves ownership with the other library. The vulkano object never
+//!   destroys the handle, and the caller must keep the handle alive for as long as the vulkano
+//!   object, and anything created from it, is alive.
+//!
+//! Borrowed constructors exist for [`Instance`], [`Device`], [`DeviceMemory`], [`RawBuffer`]
+//! and [`RawImage`]. The queues of an adopted device are retrieved from the device handle, so the
+//! `queue_create_infos` passed to [`Device::from_handle_borrowed`] must describe the queues
+//! that the device was actually created with.
+//!
+//! Once adopted, objects behave like any other vulkano object, with the following caveats:
+//!
+//! - The create info passed to the constructor is trusted. Validation of later operations is
+//!   only as accurate as that description; in particular, the enabled extensions and features of
+//!   an adopted device must be exactly those the device was created with.
+//! - Automatic synchronization in [`RecordingCommandBuffer`] and the futures system only knows
+//!   about work submitted through vulkano. If the other library accesses the same resources or
+//!   queues, you must synchronize that access yourself, for example with semaphores or by waiting
+//!   for the queue to become idle. The layout of an adopted image is assumed to be its
+//!   `initial_layout` until vulkano transitions it.
+//! - Memory allocators only keep track of allocations that they made themselves. Adopted
+//!   [`DeviceMemory`] does not count towards [`Device::allocation_count`] if it is borrowed.
+//!
+//! [`DeviceMemory`]: crate::memory::DeviceMemory
+//! [`RawBuffer`]: crate::buffer::sys::RawBuffer
+//! [`RawImage`]: crate::image::sys::RawImage
+//! [`RecordingCommandBuffer`]: crate::command_buffer::RecordingCommandBuffer
+//!
 //! # Extended example
 //!
 //! TODO: write
 
 pub(crate) use self::properties::DevicePropertiesFfi;
-use self::{physical::PhysicalDevice, queue::DeviceQueueInfo};
+use self::{
+    checkpoints::CheckpointNames,
+    layout_cache::LayoutCache,
+    memory_report::{trampoline as memory_report_trampoline, DeviceMemoryReportCallback},
+    physical::PhysicalDevice,
+    queue::DeviceQueueInfo,
+    resources::ResourceRegistry,
+};
 pub use self::{
     properties::DeviceProperties,
-    queue::{Queue, QueueFamilyProperties, QueueFlags, QueueGuard},
+    queue::{
+        CheckpointData, FrameBoundary, FrameBoundaryFlags, Queue, QueueFamilyProperties,
+        QueueFlags, QueueGlobalPriority, QueueGuard, Win32KeyedMutexAcquire,
+        Win32KeyedMutexAcquireReleaseInfo, Win32KeyedMutexRelease,
+    },
 };
 pub use crate::fns::DeviceFunctions;
 use crate::{
     acceleration_structure::{
-        AccelerationStructureBuildGeometryInfo, AccelerationStructureBuildSizesInfo,
-        AccelerationStructureBuildType, AccelerationStructureGeometries,
+        AccelerationStructureBuildGeometryInfo, AccelerationStructureBuildRangeInfo,
+        AccelerationStructureBuildSizesInfo, AccelerationStructureBuildType,
+        AccelerationStructureGeometries, AccelerationStructureGeometryInstancesDataType,
+        BuildAccelerationStructureMode, CopyAccelerationStructureInfo,
     },
-    buffer::BufferCreateInfo,
+    buffer::{BufferCreateInfo, BufferMemory, Subbuffer},
+    deferred::DeferredOperation,
     descriptor_set::layout::{
         DescriptorSetLayoutBinding, DescriptorSetLayoutCreateInfo, DescriptorSetLayoutSupport,
     },
     image::{ImageCreateFlags, ImageCreateInfo, ImageTiling},
-    instance::{Instance, InstanceOwned, InstanceOwnedDebugWrapper},
+    instance::{
+        allocation_callbacks::AllocationCallbacks, Instance, InstanceOwned,
+        InstanceOwnedDebugWrapper,
+    },
     macros::{impl_id_counter, vulkan_bitflags},
     memory::{allocator::DeviceLayout, ExternalMemoryHandleType, MemoryRequirements},
+    micromap::{MicromapBuildInfo, MicromapBuildSizesInfo},
     sync::Sharing,
     Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, Version, VulkanError,
     VulkanObject,
@@ -124,23 +200,31 @@ use ash::vk::Handle;
 use parking_lot::Mutex;
 use smallvec::{smallvec, SmallVec};
 use std::{
-    ffi::CString,
+    ffi::{c_void, CString},
     fmt::{Debug, Error as FmtError, Formatter},
     fs::File,
     mem::MaybeUninit,
     num::NonZeroU64,
     ops::Deref,
+    panic::Location,
     ptr, slice,
     sync::{
-        atomic::{AtomicU32, Ordering},
+        atomic::{AtomicBool, AtomicU32, Ordering},
         Arc,
     },
 };
 
+#[cfg(feature = "aftermath")]
+pub mod aftermath;
+mod checkpoints;
+mod layout_cache;
+pub mod memory_report;
 pub mod physical;
 pub mod private_data;
 pub(crate) mod properties;
 mod queue;
+pub mod requirements;
+mod resources;
 
 // Generated by build.rs
 include!(concat!(env!("OUT_DIR"), "/device_extensions.rs"));
@@ -166,9 +250,17 @@ pub struct Device {
     // This is required for validation in `memory::device_memory`, the count must only be modified
     // in that module.
     pub(crate) allocation_count: AtomicU32,
+    validation_enabled: AtomicBool,
     fence_pool: Mutex<Vec<ash::vk::Fence>>,
     semaphore_pool: Mutex<Vec<ash::vk::Semaphore>>,
     event_pool: Mutex<Vec<ash::vk::Event>>,
+    layout_cache: Option<LayoutCache>,
+    auto_debug_names: bool,
+    checkpoint_names: CheckpointNames,
+    resources: Option<ResourceRegistry>,
+    allocation_callbacks: Option<Arc<AllocationCallbacks>>,
+    _memory_report_callbacks: Vec<Arc<DeviceMemoryReportCallback>>,
+    needs_destruction: bool, // `vkDestroyDevice` is called only if true.
 }
 
 impl Device {
@@ -198,6 +290,12 @@ impl Device {
             enabled_features: _,
             ref physical_devices,
             private_data_slot_request_count: _,
+            diagnostics_config_flags: _,
+            intern_layouts: _,
+            auto_debug_names: _,
+            track_resources: _,
+            allocation_callbacks: _,
+            device_memory_report_callbacks: _,
             _ne: _,
         } = create_info;
 
@@ -340,26 +438,53 @@ impl Device {
             ref enabled_features,
             ref physical_devices,
             private_data_slot_request_count,
+            diagnostics_config_flags,
+            intern_layouts: _,
+            auto_debug_names: _,
+            track_resources: _,
+            ref allocation_callbacks,
+            ref device_memory_report_callbacks,
             _ne: _,
         } = &create_info;
 
-        let queue_create_infos_vk: SmallVec<[_; 2]> = queue_create_infos
+        let mut global_priority_create_infos_vk: SmallVec<[_; 2]> = queue_create_infos
             .iter()
             .map(|queue_create_info| {
+                queue_create_info.global_priority.map(|global_priority| {
+                    ash::vk::DeviceQueueGlobalPriorityCreateInfoKHR {
+                        global_priority: global_priority.into(),
+                        ..Default::default()
+                    }
+                })
+            })
+            .collect();
+
+        let queue_create_infos_vk: SmallVec<[_; 2]> = queue_create_infos
+            .iter()
+            .zip(global_priority_create_infos_vk.iter_mut())
+            .map(|(queue_create_info, global_priority_create_info_vk)| {
                 let &QueueCreateInfo {
                     flags,
                     queue_family_index,
                     ref queues,
+                    global_priority: _,
                     _ne: _,
                 } = queue_create_info;
 
-                ash::vk::DeviceQueueCreateInfo {
+                let mut queue_create_info_vk = ash::vk::DeviceQueueCreateInfo {
                     flags: flags.into(),
                     queue_family_index,
                     queue_count: queues.len() as u32,
                     p_queue_priorities: queues.as_ptr(),
                     ..Default::default()
+                };
+
+                if let Some(next) = global_priority_create_info_vk {
+                    next.p_next = queue_create_info_vk.p_next;
+                    queue_create_info_vk.p_next = <*const _>::cast(next);
                 }
+
+                queue_create_info_vk
             })
             .collect();
 
@@ -393,6 +518,14 @@ impl Device {
             p_enabled_features: ptr::null(),
             ..Default::default()
         };
+
+        // VUID-VkDeviceCreateInfo-pNext-00373
+        if has_khr_get_physical_device_properties2 {
+            create_info_vk.p_next = <*const _>::cast(features_ffi.head_as_ref());
+        } else {
+            create_info_vk.p_enabled_features = &features_ffi.head_as_ref().features;
+        }
+
         let mut device_group_create_info_vk = None;
         let device_group_physical_devices_vk: SmallVec<[_; 2]>;
 
@@ -430,20 +563,54 @@ impl Device {
             create_info_vk.p_next = <*mut _>::cast(next);
         }
 
-        // VUID-VkDeviceCreateInfo-pNext-00373
-        if has_khr_get_physical_device_properties2 {
-            create_info_vk.p_next = <*const _>::cast(features_ffi.head_as_ref());
-        } else {
-            create_info_vk.p_enabled_features = &features_ffi.head_as_ref().features;
+        let mut diagnostics_config_create_info_vk = None;
+
+        if !diagnostics_config_flags.is_empty() {
+            let next = diagnostics_config_create_info_vk.insert(
+                ash::vk::DeviceDiagnosticsConfigCreateInfoNV {
+                    flags: diagnostics_config_flags.into(),
+                    ..Default::default()
+                },
+            );
+
+            next.p_next = create_info_vk.p_next;
+            create_info_vk.p_next = <*mut _>::cast(next);
+        }
+
+        let mut device_memory_report_create_infos_vk: SmallVec<[_; 1]> =
+            device_memory_report_callbacks
+                .iter()
+                .map(|callback| ash::vk::DeviceDeviceMemoryReportCreateInfoEXT {
+                    flags: ash::vk::DeviceMemoryReportFlagsEXT::empty(),
+                    pfn_user_callback: Some(memory_report_trampoline),
+                    p_user_data: callback.as_ptr().cast_mut().cast(),
+                    ..Default::default()
+                })
+                .collect();
+
+        for next in &mut device_memory_report_create_infos_vk {
+            next.p_next = create_info_vk.p_next;
+            create_info_vk.p_next = <*mut _>::cast(next);
+        }
+
+        #[cfg(feature = "aftermath")]
+        if let Some(hooks) = aftermath::hooks() {
+            hooks.before_create_device(&physical_device, &create_info);
         }
 
+        let allocation_callbacks_vk = allocation_callbacks
+            .as_ref()
+            .map(|allocation_callbacks| allocation_callbacks.to_vulkan());
+
         let handle = unsafe {
             let fns = physical_device.instance().fns();
             let mut output = MaybeUninit::uninit();
             (fns.v1_0.create_device)(
                 physical_device.handle(),
                 &create_info_vk,
-                ptr::null(),
+                allocation_callbacks_vk
+                    .as_ref()
+                    .map_or(ptr::null(), |callbacks| callbacks),
                 output.as_mut_ptr(),
             )
             .result()
@@ -464,6 +631,37 @@ impl Device {
         physical_device: Arc<PhysicalDevice>,
         handle: ash::vk::Device,
         create_info: DeviceCreateInfo,
+    ) -> (Arc<Device>, impl ExactSizeIterator<Item = Arc<Queue>>) {
+        Self::from_handle_with_destruction(physical_device, handle, create_info, true)
+    }
+
+    /// Creates a new `Device` from a raw object handle. Unlike `from_handle`, the created
+    /// `Device` does not destroy the inner device when dropped.
+    ///
+    /// The returned queues are retrieved from the device according to
+    /// `create_info.queue_create_infos`, so those must describe the queues that the device was
+    /// created with. See [the module-level documentation](self#adopting-externally-created-objects)
+    /// for which parts of vulkano can be used with an adopted device.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid Vulkan object handle created from `physical_device`.
+    /// - `create_info` must match the info used to create the object.
+    /// - Caller must ensure that the handle will not be destroyed for the lifetime of the returned
+    ///   `Device`, and of any object created from it.
+    pub unsafe fn from_handle_borrowed(
+        physical_device: Arc<PhysicalDevice>,
+        handle: ash::vk::Device,
+        create_info: DeviceCreateInfo,
+    ) -> (Arc<Device>, impl ExactSizeIterator<Item = Arc<Queue>>) {
+        Self::from_handle_with_destruction(physical_device, handle, create_info, false)
+    }
+
+    unsafe fn from_handle_with_destruction(
+        physical_device: Arc<PhysicalDevice>,
+        handle: ash::vk::Device,
+        create_info: DeviceCreateInfo,
+        needs_destruction: bool,
     ) -> (Arc<Device>, impl ExactSizeIterator<Item = Arc<Queue>>) {
         let DeviceCreateInfo {
             queue_create_infos,
@@ -471,9 +669,23 @@ impl Device {
             enabled_extensions,
             physical_devices,
             private_data_slot_request_count: _,
+            diagnostics_config_flags: _,
+            intern_layouts,
+            auto_debug_names,
+            track_resources,
+            allocation_callbacks,
+            device_memory_report_callbacks,
             _ne: _,
         } = create_info;
 
+        // Captures are much easier to navigate with named objects, so name them whenever a
+        // capture could be made.
+        #[cfg(feature = "renderdoc")]
+        let auto_debug_names = auto_debug_names || {
+            let instance = physical_device.instance();
+            instance.enabled_extensions().ext_debug_utils && instance.is_renderdoc_attached()
+        };
+
         let api_version = physical_device.api_version();
         let fns = DeviceFunctions::load(|name| unsafe {
             (physical_device.instance().fns().v1_0.get_device_proc_addr)(handle, name.as_ptr())
@@ -489,6 +701,7 @@ impl Device {
                 flags,
                 queue_family_index,
                 ref queues,
+                global_priority: _,
                 _ne: _,
             } = queue_create_info;
 
@@ -529,9 +742,17 @@ impl Device {
             active_queue_family_indices,
 
             allocation_count: AtomicU32::new(0),
+            validation_enabled: AtomicBool::new(true),
             fence_pool: Mutex::new(Vec::new()),
             semaphore_pool: Mutex::new(Vec::new()),
             event_pool: Mutex::new(Vec::new()),
+            layout_cache: intern_layouts.then(LayoutCache::default),
+            auto_debug_names,
+            checkpoint_names: CheckpointNames::default(),
+            resources: track_resources.then(ResourceRegistry::default),
+            allocation_callbacks,
+            _memory_report_callbacks: device_memory_report_callbacks,
+            needs_destruction,
         });
 
         let queues_iter = {
@@ -544,6 +765,14 @@ impl Device {
         (device, queues_iter)
     }
 
+    /// Returns whether the device is owned by vulkano, and will be destroyed when dropped.
+    ///
+    /// This is `false` if the device was created with [`Device::from_handle_borrowed`].
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        self.needs_destruction
+    }
+
     /// Returns the Vulkan version supported by the device.
     ///
     /// This is the lower of the
@@ -554,6 +783,12 @@ impl Device {
         self.api_version
     }
 
+    /// Returns the allocation callbacks that the device was created with.
+    #[inline]
+    pub fn allocation_callbacks(&self) -> Option<&Arc<AllocationCallbacks>> {
+        self.allocation_callbacks.as_ref()
+    }
+
     /// Returns pointers to the raw Vulkan functions of the device.
     #[inline]
     pub fn fns(&self) -> &DeviceFunctions {
@@ -622,6 +857,38 @@ impl Device {
         self.allocation_count.load(Ordering::Acquire)
     }
 
+    /// Returns whether vulkano validates the parameters of the commands that are recorded with
+    /// this device.
+    ///
+    /// This is `true` unless it was disabled with [`set_validation_enabled`].
+    ///
+    /// [`set_validation_enabled`]: Self::set_validation_enabled
+    #[inline]
+    pub fn validation_enabled(&self) -> bool {
+        self.validation_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Sets whether vulkano validates the parameters of the commands that are recorded with this
+    /// device.
+    ///
+    /// When validation is disabled, the safe methods of [`RecordingCommandBuffer`] skip their
+    /// validation and behave like their `_unchecked` counterparts. This removes the CPU cost of
+    /// validation in shipping builds, once an application has been tested thoroughly with
+    /// validation enabled, preferably also with the Vulkan validation layers. Resource tracking
+    /// and automatic synchronization are not affected.
+    ///
+    /// # Safety
+    ///
+    /// - While validation is disabled, every command that is recorded with this device must meet
+    ///   the requirements that its validation would otherwise check, just like when calling the
+    ///   `_unchecked` methods directly.
+    ///
+    /// [`RecordingCommandBuffer`]: crate::command_buffer::RecordingCommandBuffer
+    #[inline]
+    pub unsafe fn set_validation_enabled(&self, enabled: bool) {
+        self.validation_enabled.store(enabled, Ordering::Relaxed);
+    }
+
     pub(crate) fn fence_pool(&self) -> &Mutex<Vec<ash::vk::Fence>> {
         &self.fence_pool
     }
@@ -634,6 +901,99 @@ impl Device {
         &self.event_pool
     }
 
+    /// Returns whether descriptor set layouts and pipeline layouts created from this device are
+    /// interned.
+    ///
+    /// This is the value of [`DeviceCreateInfo::intern_layouts`] that the device was created with.
+    #[inline]
+    pub fn interns_layouts(&self) -> bool {
+        self.layout_cache.is_some()
+    }
+
+    pub(crate) fn layout_cache(&self) -> Option<&LayoutCache> {
+        self.layout_cache.as_ref()
+    }
+
+    /// Returns whether objects created from this device are automatically given a debug name.
+    ///
+    /// This is the value of [`DeviceCreateInfo::auto_debug_names`] that the device was created
+    /// with. If the `renderdoc` feature is enabled, this is also `true` if RenderDoc was present
+    /// in the process when the device was created, and the [`ext_debug_utils`] extension is
+    /// enabled on the instance.
+    ///
+    /// [`ext_debug_utils`]: crate::instance::InstanceExtensions::ext_debug_utils
+    #[inline]
+    pub fn auto_debug_names(&self) -> bool {
+        self.auto_debug_names
+    }
+
+    pub(crate) fn checkpoint_names(&self) -> &CheckpointNames {
+        &self.checkpoint_names
+    }
+
+    /// Returns whether the buffers, images and device memory allocations that are created from
+    /// this device are tracked for [`dump_resources`].
+    ///
+    /// This is the value of [`DeviceCreateInfo::track_resources`] that the device was created
+    /// with.
+    ///
+    /// [`dump_resources`]: Self::dump_resources
+    #[inline]
+    pub fn tracks_resources(&self) -> bool {
+        self.resources.is_some()
+    }
+
+    pub(crate) fn resources(&self) -> Option<&ResourceRegistry> {
+        self.resources.as_ref()
+    }
+
+    /// Returns a JSON snapshot of the buffers, images and device memory allocations that are
+    /// currently alive on the device, for attaching to bug reports.
+    ///
+    /// Buffers, images and device memory allocations are only included if
+    /// [`DeviceCreateInfo::track_resources`] was enabled when creating the device. Otherwise, the
+    /// snapshot contains only the memory heaps and memory types.
+    ///
+    /// For each buffer and image, the snapshot contains its handle, debug name, size, format and
+    /// usage, and the range of device memory that it is bound to. For each device memory
+    /// allocation, it contains the ranges that buffers and images are bound to, sorted by offset,
+    /// which shows how a memory allocator has laid out the allocation. The memory heaps and
+    /// memory types of the physical device are included as well.
+    ///
+    /// Debug names are included only if they were set through vulkano, for example with
+    /// [`set_debug_utils_object_name`] or with the [`tag`] of an allocation.
+    ///
+    /// Objects that were created from a raw handle are included, but objects that were created
+    /// outside of vulkano are not.
+    ///
+    /// [`set_debug_utils_object_name`]: Self::set_debug_utils_object_name
+    /// [`tag`]: crate::memory::allocator::AllocationCreateInfo::tag
+    pub fn dump_resources(&self) -> String {
+        match &self.resources {
+            Some(resources) => resources.dump(self),
+            None => ResourceRegistry::default().dump(self),
+        }
+    }
+
+    /// Gives a newly created object a debug name, made of `kind` and the location in the
+    /// user's code that created it, if [`DeviceCreateInfo::auto_debug_names`] is enabled.
+    ///
+    /// Every function between the user's code and this one must be `#[track_caller]`, otherwise
+    /// the location will point into vulkano.
+    #[track_caller]
+    pub(crate) fn set_auto_debug_utils_object_name<T: VulkanObject + DeviceOwned>(
+        &self,
+        object: &T,
+        kind: &str,
+    ) {
+        if self.auto_debug_names {
+            let object_name = format!("{} ({})", kind, Location::caller());
+
+            // Naming is only a debugging aid, so a failure shouldn't fail the object's creation.
+            let _ = self.set_debug_utils_object_name(object, Some(&object_name));
+        }
+    }
+
     /// For the given acceleration structure build info and primitive counts, returns the
     /// minimum size required to build the acceleration structure, and the minimum size of the
     /// scratch buffer used during the build operation.
@@ -774,7 +1134,7 @@ impl Device {
         build_info: &AccelerationStructureBuildGeometryInfo,
         max_primitive_counts: &[u32],
     ) -> AccelerationStructureBuildSizesInfo {
-        let (mut build_info_vk, geometries_vk) = build_info.to_vulkan();
+        let (mut build_info_vk, geometries_vk, _extensions_vk) = build_info.to_vulkan();
         build_info_vk = ash::vk::AccelerationStructureBuildGeometryInfoKHR {
             geometry_count: geometries_vk.len() as u32,
             p_geometries: geometries_vk.as_ptr(),
@@ -861,114 +1221,561 @@ impl Device {
         compatibility_vk == ash::vk::AccelerationStructureCompatibilityKHR::COMPATIBLE
     }
 
-    /// Returns whether a descriptor set layout with the given `create_info` could be created
-    /// on the device, and additional supported properties where relevant. `Some` is returned if
-    /// the descriptor set layout is supported, `None` if it is not.
-    ///
-    /// This is primarily useful for checking whether the device supports a descriptor set layout
-    /// that goes beyond the [`max_per_set_descriptors`] limit. A layout that does not exceed
-    /// that limit is guaranteed to be supported, otherwise this function can be called.
+    /// For the given micromap build info, returns the minimum size required to build the
+    /// micromap, and the minimum size of the scratch buffer used during the build operation.
     ///
-    /// The device API version must be at least 1.1, or the [`khr_maintenance3`] extension must
-    /// be enabled on the device.
+    /// The [`micromap`] feature must be enabled on the device.
     ///
-    /// [`max_per_set_descriptors`]: crate::device::DeviceProperties::max_per_set_descriptors
-    /// [`khr_maintenance3`]: crate::device::DeviceExtensions::khr_maintenance3
+    /// [`micromap`]: crate::device::DeviceFeatures::micromap
     #[inline]
-    pub fn descriptor_set_layout_support(
+    pub fn micromap_build_sizes(
         &self,
-        create_info: &DescriptorSetLayoutCreateInfo,
-    ) -> Result<Option<DescriptorSetLayoutSupport>, Box<ValidationError>> {
-        self.validate_descriptor_set_layout_support(create_info)?;
+        build_type: AccelerationStructureBuildType,
+        build_info: &MicromapBuildInfo,
+    ) -> Result<MicromapBuildSizesInfo, Box<ValidationError>> {
+        self.validate_micromap_build_sizes(build_type, build_info)?;
 
-        unsafe { Ok(self.descriptor_set_layout_support_unchecked(create_info)) }
+        unsafe { Ok(self.micromap_build_sizes_unchecked(build_type, build_info)) }
     }
 
-    fn validate_descriptor_set_layout_support(
+    fn validate_micromap_build_sizes(
         &self,
-        create_info: &DescriptorSetLayoutCreateInfo,
+        build_type: AccelerationStructureBuildType,
+        build_info: &MicromapBuildInfo,
     ) -> Result<(), Box<ValidationError>> {
-        if !(self.api_version() >= Version::V1_1 || self.enabled_extensions().khr_maintenance3) {
+        if !self.enabled_extensions().ext_opacity_micromap {
             return Err(Box::new(ValidationError {
-                requires_one_of: RequiresOneOf(&[
-                    RequiresAllOf(&[Requires::APIVersion(Version::V1_1)]),
-                    RequiresAllOf(&[Requires::DeviceExtension("khr_maintenance3")]),
-                ]),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "ext_opacity_micromap",
+                )])]),
                 ..Default::default()
             }));
         }
 
-        // VUID-vkGetDescriptorSetLayoutSupport-pCreateInfo-parameter
-        create_info
+        if !self.enabled_features().micromap {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "micromap",
+                )])]),
+                vuids: &["VUID-vkGetMicromapBuildSizesEXT-micromap-07439"],
+                ..Default::default()
+            }));
+        }
+
+        build_type.validate_device(self).map_err(|err| {
+            err.add_context("build_type")
+                .set_vuids(&["VUID-vkGetMicromapBuildSizesEXT-buildType-parameter"])
+        })?;
+
+        // VUID-vkGetMicromapBuildSizesEXT-pBuildInfo-parameter
+        build_info
             .validate(self)
-            .map_err(|err| err.add_context("create_info"))?;
+            .map_err(|err| err.add_context("build_info"))?;
 
         Ok(())
     }
 
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
-    pub unsafe fn descriptor_set_layout_support_unchecked(
+    pub unsafe fn micromap_build_sizes_unchecked(
         &self,
-        create_info: &DescriptorSetLayoutCreateInfo,
-    ) -> Option<DescriptorSetLayoutSupport> {
-        let &DescriptorSetLayoutCreateInfo {
-            flags,
-            ref bindings,
-            _ne: _,
-        } = create_info;
+        build_type: AccelerationStructureBuildType,
+        build_info: &MicromapBuildInfo,
+    ) -> MicromapBuildSizesInfo {
+        let (mut build_info_vk, usage_counts_vk) = build_info.to_vulkan();
+        build_info_vk = ash::vk::MicromapBuildInfoEXT {
+            usage_counts_count: usage_counts_vk.len() as u32,
+            p_usage_counts: usage_counts_vk.as_ptr(),
+            ..build_info_vk
+        };
 
-        struct PerBinding {
-            immutable_samplers_vk: Vec<ash::vk::Sampler>,
-        }
+        let mut build_sizes_info_vk = ash::vk::MicromapBuildSizesInfoEXT::default();
 
-        let mut bindings_vk = Vec::with_capacity(bindings.len());
-        let mut per_binding_vk = Vec::with_capacity(bindings.len());
-        let mut binding_flags_info_vk = None;
-        let mut binding_flags_vk = Vec::with_capacity(bindings.len());
+        let fns = self.fns();
+        (fns.ext_opacity_micromap.get_micromap_build_sizes_ext)(
+            self.handle,
+            build_type.into(),
+            &build_info_vk,
+            &mut build_sizes_info_vk,
+        );
 
-        let mut support_vk = ash::vk::DescriptorSetLayoutSupport::default();
-        let mut variable_descriptor_count_support_vk = None;
+        MicromapBuildSizesInfo {
+            micromap_size: build_sizes_info_vk.micromap_size,
+            build_scratch_size: build_sizes_info_vk.build_scratch_size,
+            discardable: build_sizes_info_vk.discardable != ash::vk::FALSE,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
 
-        for (&binding_num, binding) in bindings.iter() {
-            let &DescriptorSetLayoutBinding {
-                binding_flags,
-                descriptor_type,
-                descriptor_count,
-                stages,
-                ref immutable_samplers,
-                _ne: _,
-            } = binding;
+    /// Builds or updates an acceleration structure on the host.
+    ///
+    /// This is the host counterpart of [`RecordingCommandBuffer::build_acceleration_structure`].
+    /// All buffers that are referenced by `info` must be host-accessible, including the buffer of
+    /// the destination acceleration structure. If the instance data is [`Pointers`], the buffer
+    /// must contain host addresses instead of device addresses.
+    ///
+    /// If `deferred_operation` is `Some`, the build may be deferred. In that case, the operation
+    /// must be joined until it completes before the acceleration structure can be used. Multiple
+    /// acceleration structures can be built in parallel by deferring each build to its own
+    /// operation, and joining the operations from several threads.
+    ///
+    /// The [`acceleration_structure_host_commands`] feature must be enabled on the device.
+    ///
+    /// # Safety
+    ///
+    /// - The same requirements as for [`RecordingCommandBuffer::build_acceleration_structure`]
+    ///   apply.
+    /// - The buffers and acceleration structures referenced by `info` must not be accessed by the
+    ///   device or by another host operation while the build is in progress.
+    ///
+    /// [`RecordingCommandBuffer::build_acceleration_structure`]: crate::command_buffer::RecordingCommandBuffer::build_acceleration_structure
+    /// [`Pointers`]: crate::acceleration_structure::AccelerationStructureGeometryInstancesDataType::Pointers
+    /// [`acceleration_structure_host_commands`]: crate::device::DeviceFeatures::acceleration_structure_host_commands
+    #[inline]
+    pub unsafe fn build_acceleration_structure(
+        &self,
+        deferred_operation: Option<&Arc<DeferredOperation>>,
+        info: &AccelerationStructureBuildGeometryInfo,
+        build_range_infos: &[AccelerationStructureBuildRangeInfo],
+    ) -> Result<(), Validated<VulkanError>> {
+        self.validate_build_acceleration_structure(deferred_operation, info, build_range_infos)?;
+
+        Ok(self.build_acceleration_structure_unchecked(
+            deferred_operation,
+            info,
+            build_range_infos,
+        )?)
+    }
 
-            bindings_vk.push(ash::vk::DescriptorSetLayoutBinding {
-                binding: binding_num,
-                descriptor_type: descriptor_type.into(),
-                descriptor_count,
-                stage_flags: stages.into(),
-                p_immutable_samplers: ptr::null(),
+    fn validate_build_acceleration_structure(
+        &self,
+        deferred_operation: Option<&Arc<DeferredOperation>>,
+        info: &AccelerationStructureBuildGeometryInfo,
+        build_range_infos: &[AccelerationStructureBuildRangeInfo],
+    ) -> Result<(), Box<ValidationError>> {
+        if !self.enabled_features().acceleration_structure_host_commands {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "acceleration_structure_host_commands",
+                )])]),
+                vuids: &[
+                    "VUID-vkBuildAccelerationStructuresKHR-accelerationStructureHostCommands-03581",
+                ],
                 ..Default::default()
-            });
-            per_binding_vk.push(PerBinding {
-                immutable_samplers_vk: immutable_samplers
-                    .iter()
-                    .map(VulkanObject::handle)
-                    .collect(),
-            });
-            binding_flags_vk.push(binding_flags.into());
+            }));
         }
 
-        for (binding_vk, per_binding_vk) in bindings_vk.iter_mut().zip(per_binding_vk.iter_mut()) {
-            binding_vk.p_immutable_samplers = per_binding_vk.immutable_samplers_vk.as_ptr();
+        if let Some(deferred_operation) = deferred_operation {
+            self.validate_deferred_operation(deferred_operation)?;
         }
 
-        let mut create_info_vk = ash::vk::DescriptorSetLayoutCreateInfo {
-            flags: flags.into(),
-            binding_count: bindings_vk.len() as u32,
-            p_bindings: bindings_vk.as_ptr(),
-            ..Default::default()
-        };
+        // VUID-vkBuildAccelerationStructuresKHR-pInfos-parameter
+        info.validate(self).map_err(|err| err.add_context("info"))?;
 
-        if self.api_version() >= Version::V1_2 || self.enabled_extensions().ext_descriptor_indexing
-        {
+        let &AccelerationStructureBuildGeometryInfo {
+            flags: _,
+            ref mode,
+            ref dst_acceleration_structure,
+            ref geometries,
+            ref scratch_data,
+            _ne: _,
+        } = info;
+
+        let dst_acceleration_structure = dst_acceleration_structure.as_ref().ok_or_else(|| {
+            Box::new(ValidationError {
+                context: "info.dst_acceleration_structure".into(),
+                problem: "is `None`".into(),
+                ..Default::default()
+            })
+        })?;
+        let scratch_data = scratch_data.as_ref().ok_or_else(|| {
+            Box::new(ValidationError {
+                context: "info.scratch_data".into(),
+                problem: "is `None`".into(),
+                ..Default::default()
+            })
+        })?;
+
+        if !is_host_accessible(dst_acceleration_structure.buffer()) {
+            return Err(Box::new(ValidationError {
+                context: "info.dst_acceleration_structure".into(),
+                problem: "the buffer of the acceleration structure is not host-accessible".into(),
+                vuids: &["VUID-vkBuildAccelerationStructuresKHR-pInfos-03722"],
+                ..Default::default()
+            }));
+        }
+
+        if let BuildAccelerationStructureMode::Update(src_acceleration_structure) = mode {
+            if !is_host_accessible(src_acceleration_structure.buffer()) {
+                return Err(Box::new(ValidationError {
+                    context: "info.mode".into(),
+                    problem: "is `BuildAccelerationStructureMode::Update`, and the buffer of the \
+                        source acceleration structure is not host-accessible"
+                        .into(),
+                    vuids: &["VUID-vkBuildAccelerationStructuresKHR-pInfos-03723"],
+                    ..Default::default()
+                }));
+            }
+        }
+
+        if !is_host_accessible(scratch_data) {
+            return Err(Box::new(ValidationError {
+                context: "info.scratch_data".into(),
+                problem: "is not host-accessible".into(),
+                ..Default::default()
+            }));
+        }
+
+        let geometry_buffers: SmallVec<[_; 8]> = match geometries {
+            AccelerationStructureGeometries::Triangles(geometries) => geometries
+                .iter()
+                .flat_map(|geometry| {
+                    [
+                        geometry.vertex_data.as_ref(),
+                        geometry.index_data.as_ref().map(|data| data.as_bytes()),
+                        geometry.transform_data.as_ref().map(|data| data.as_bytes()),
+                        geometry.motion_vertex_data.as_ref(),
+                    ]
+                })
+                .flatten()
+                .collect(),
+            AccelerationStructureGeometries::Aabbs(geometries) => geometries
+                .iter()
+                .filter_map(|geometry| geometry.data.as_ref())
+                .collect(),
+            AccelerationStructureGeometries::Instances(instances_data) => {
+                match &instances_data.data {
+                    AccelerationStructureGeometryInstancesDataType::Values(data) => {
+                        data.as_ref().map(|data| data.as_bytes())
+                    }
+                    AccelerationStructureGeometryInstancesDataType::MotionValues(data) => {
+                        data.as_ref().map(|data| data.as_bytes())
+                    }
+                    AccelerationStructureGeometryInstancesDataType::Pointers(data) => {
+                        data.as_ref().map(|data| data.as_bytes())
+                    }
+                }
+                .into_iter()
+                .collect()
+            }
+        };
+
+        if !geometry_buffers.into_iter().all(is_host_accessible) {
+            return Err(Box::new(ValidationError {
+                context: "info.geometries".into(),
+                problem: "contains data that is not host-accessible".into(),
+                ..Default::default()
+            }));
+        }
+
+        if geometries.len() != build_range_infos.len() {
+            return Err(Box::new(ValidationError {
+                problem: "`info.geometries` and `build_range_infos` do not have the same length"
+                    .into(),
+                vuids: &["VUID-vkBuildAccelerationStructuresKHR-ppBuildRangeInfos-03676"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn build_acceleration_structure_unchecked(
+        &self,
+        deferred_operation: Option<&Arc<DeferredOperation>>,
+        info: &AccelerationStructureBuildGeometryInfo,
+        build_range_infos: &[AccelerationStructureBuildRangeInfo],
+    ) -> Result<(), VulkanError> {
+        let (info_vk, geometries_vk, extensions_vk) = info.to_vulkan_host();
+        let build_range_infos_vk: Vec<_> = build_range_infos
+            .iter()
+            .map(|build_range_info| {
+                let &AccelerationStructureBuildRangeInfo {
+                    primitive_count,
+                    primitive_offset,
+                    first_vertex,
+                    transform_offset,
+                } = build_range_info;
+
+                ash::vk::AccelerationStructureBuildRangeInfoKHR {
+                    primitive_count,
+                    primitive_offset,
+                    first_vertex,
+                    transform_offset,
+                }
+            })
+            .collect();
+
+        // If the operation is deferred, everything that the implementation reads from must stay
+        // at the same address until it completes, so all of it is put in one heap allocation.
+        let mut state = Box::new(HostCommandState {
+            _info: (info.clone(), extensions_vk),
+            info_vk,
+            geometries_vk,
+            build_range_infos_vk,
+            build_range_info_pointers_vk: Vec::new(),
+        });
+        state.info_vk.geometry_count = state.geometries_vk.len() as u32;
+        state.info_vk.p_geometries = state.geometries_vk.as_ptr();
+        state.build_range_info_pointers_vk = vec![state.build_range_infos_vk.as_ptr()];
+
+        let fns = self.fns();
+        let result = (fns
+            .khr_acceleration_structure
+            .build_acceleration_structures_khr)(
+            self.handle,
+            deferred_operation.map_or_else(Default::default, |op| op.handle()),
+            1,
+            &state.info_vk,
+            state.build_range_info_pointers_vk.as_ptr(),
+        );
+
+        finish_host_command(deferred_operation, result, state)
+    }
+
+    /// Copies an acceleration structure on the host.
+    ///
+    /// This is the host counterpart of [`RecordingCommandBuffer::copy_acceleration_structure`].
+    /// The buffers of both acceleration structures must be host-accessible.
+    ///
+    /// If `deferred_operation` is `Some`, the copy may be deferred. In that case, the operation
+    /// must be joined until it completes before the acceleration structures can be used.
+    ///
+    /// The [`acceleration_structure_host_commands`] feature must be enabled on the device.
+    ///
+    /// # Safety
+    ///
+    /// - `info.src` must have been built.
+    /// - The acceleration structures must not be accessed by the device or by another host
+    ///   operation while the copy is in progress.
+    ///
+    /// [`RecordingCommandBuffer::copy_acceleration_structure`]: crate::command_buffer::RecordingCommandBuffer::copy_acceleration_structure
+    /// [`acceleration_structure_host_commands`]: crate::device::DeviceFeatures::acceleration_structure_host_commands
+    #[inline]
+    pub unsafe fn copy_acceleration_structure(
+        &self,
+        deferred_operation: Option<&Arc<DeferredOperation>>,
+        info: &CopyAccelerationStructureInfo,
+    ) -> Result<(), Validated<VulkanError>> {
+        self.validate_copy_acceleration_structure(deferred_operation, info)?;
+
+        Ok(self.copy_acceleration_structure_unchecked(deferred_operation, info)?)
+    }
+
+    fn validate_copy_acceleration_structure(
+        &self,
+        deferred_operation: Option<&Arc<DeferredOperation>>,
+        info: &CopyAccelerationStructureInfo,
+    ) -> Result<(), Box<ValidationError>> {
+        if !self.enabled_features().acceleration_structure_host_commands {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "acceleration_structure_host_commands",
+                )])]),
+                vuids: &[
+                    "VUID-vkCopyAccelerationStructureKHR-accelerationStructureHostCommands-03582",
+                ],
+                ..Default::default()
+            }));
+        }
+
+        if let Some(deferred_operation) = deferred_operation {
+            self.validate_deferred_operation(deferred_operation)?;
+        }
+
+        // VUID-vkCopyAccelerationStructureKHR-pInfo-parameter
+        info.validate(self).map_err(|err| err.add_context("info"))?;
+
+        if !is_host_accessible(info.src.buffer()) {
+            return Err(Box::new(ValidationError {
+                context: "info.src".into(),
+                problem: "the buffer of the acceleration structure is not host-accessible".into(),
+                vuids: &["VUID-vkCopyAccelerationStructureKHR-buffer-03727"],
+                ..Default::default()
+            }));
+        }
+
+        if !is_host_accessible(info.dst.buffer()) {
+            return Err(Box::new(ValidationError {
+                context: "info.dst".into(),
+                problem: "the buffer of the acceleration structure is not host-accessible".into(),
+                vuids: &["VUID-vkCopyAccelerationStructureKHR-buffer-03728"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn copy_acceleration_structure_unchecked(
+        &self,
+        deferred_operation: Option<&Arc<DeferredOperation>>,
+        info: &CopyAccelerationStructureInfo,
+    ) -> Result<(), VulkanError> {
+        let &CopyAccelerationStructureInfo {
+            ref src,
+            ref dst,
+            mode,
+            _ne: _,
+        } = info;
+
+        let state = Box::new(HostCommandState {
+            _info: info.clone(),
+            info_vk: ash::vk::CopyAccelerationStructureInfoKHR {
+                src: src.handle(),
+                dst: dst.handle(),
+                mode: mode.into(),
+                ..Default::default()
+            },
+            geometries_vk: Vec::new(),
+            build_range_infos_vk: Vec::new(),
+            build_range_info_pointers_vk: Vec::new(),
+        });
+
+        let fns = self.fns();
+        let result = (fns
+            .khr_acceleration_structure
+            .copy_acceleration_structure_khr)(
+            self.handle,
+            deferred_operation.map_or_else(Default::default, |op| op.handle()),
+            &state.info_vk,
+        );
+
+        finish_host_command(deferred_operation, result, state)
+    }
+
+    fn validate_deferred_operation(
+        &self,
+        deferred_operation: &DeferredOperation,
+    ) -> Result<(), Box<ValidationError>> {
+        // VUID-vkBuildAccelerationStructuresKHR-deferredOperation-parameter
+        assert_eq!(self, deferred_operation.device().as_ref());
+
+        if deferred_operation.result().is_none() {
+            return Err(Box::new(ValidationError {
+                context: "deferred_operation".into(),
+                problem: "a previous operation that was deferred on it has not completed yet"
+                    .into(),
+                vuids: &[
+                    "VUID-vkBuildAccelerationStructuresKHR-deferredOperation-03678",
+                    "VUID-vkCopyAccelerationStructureKHR-deferredOperation-03678",
+                ],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether a descriptor set layout with the given `create_info` could be created
+    /// on the device, and additional supported properties where relevant. `Some` is returned if
+    /// the descriptor set layout is supported, `None` if it is not.
+    ///
+    /// This is primarily useful for checking whether the device supports a descriptor set layout
+    /// that goes beyond the [`max_per_set_descriptors`] limit. A layout that does not exceed
+    /// that limit is guaranteed to be supported, otherwise this function can be called.
+    ///
+    /// The device API version must be at least 1.1, or the [`khr_maintenance3`] extension must
+    /// be enabled on the device.
+    ///
+    /// [`max_per_set_descriptors`]: crate::device::DeviceProperties::max_per_set_descriptors
+    /// [`khr_maintenance3`]: crate::device::DeviceExtensions::khr_maintenance3
+    #[inline]
+    pub fn descriptor_set_layout_support(
+        &self,
+        create_info: &DescriptorSetLayoutCreateInfo,
+    ) -> Result<Option<DescriptorSetLayoutSupport>, Box<ValidationError>> {
+        self.validate_descriptor_set_layout_support(create_info)?;
+
+        unsafe { Ok(self.descriptor_set_layout_support_unchecked(create_info)) }
+    }
+
+    fn validate_descriptor_set_layout_support(
+        &self,
+        create_info: &DescriptorSetLayoutCreateInfo,
+    ) -> Result<(), Box<ValidationError>> {
+        if !(self.api_version() >= Version::V1_1 || self.enabled_extensions().khr_maintenance3) {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[
+                    RequiresAllOf(&[Requires::APIVersion(Version::V1_1)]),
+                    RequiresAllOf(&[Requires::DeviceExtension("khr_maintenance3")]),
+                ]),
+                ..Default::default()
+            }));
+        }
+
+        // VUID-vkGetDescriptorSetLayoutSupport-pCreateInfo-parameter
+        create_info
+            .validate(self)
+            .map_err(|err| err.add_context("create_info"))?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn descriptor_set_layout_support_unchecked(
+        &self,
+        create_info: &DescriptorSetLayoutCreateInfo,
+    ) -> Option<DescriptorSetLayoutSupport> {
+        let &DescriptorSetLayoutCreateInfo {
+            flags,
+            ref bindings,
+            _ne: _,
+        } = create_info;
+
+        struct PerBinding {
+            immutable_samplers_vk: Vec<ash::vk::Sampler>,
+        }
+
+        let mut bindings_vk = Vec::with_capacity(bindings.len());
+        let mut per_binding_vk = Vec::with_capacity(bindings.len());
+        let mut binding_flags_info_vk = None;
+        let mut binding_flags_vk = Vec::with_capacity(bindings.len());
+
+        let mut support_vk = ash::vk::DescriptorSetLayoutSupport::default();
+        let mut variable_descriptor_count_support_vk = None;
+
+        for (&binding_num, binding) in bindings.iter() {
+            let &DescriptorSetLayoutBinding {
+                binding_flags,
+                descriptor_type,
+                descriptor_count,
+                stages,
+                ref immutable_samplers,
+                _ne: _,
+            } = binding;
+
+            bindings_vk.push(ash::vk::DescriptorSetLayoutBinding {
+                binding: binding_num,
+                descriptor_type: descriptor_type.into(),
+                descriptor_count,
+                stage_flags: stages.into(),
+                p_immutable_samplers: ptr::null(),
+                ..Default::default()
+            });
+            per_binding_vk.push(PerBinding {
+                immutable_samplers_vk: immutable_samplers
+                    .iter()
+                    .map(VulkanObject::handle)
+                    .collect(),
+            });
+            binding_flags_vk.push(binding_flags.into());
+        }
+
+        for (binding_vk, per_binding_vk) in bindings_vk.iter_mut().zip(per_binding_vk.iter_mut()) {
+            binding_vk.p_immutable_samplers = per_binding_vk.immutable_samplers_vk.as_ptr();
+        }
+
+        let mut create_info_vk = ash::vk::DescriptorSetLayoutCreateInfo {
+            flags: flags.into(),
+            binding_count: bindings_vk.len() as u32,
+            p_bindings: bindings_vk.as_ptr(),
+            ..Default::default()
+        };
+
+        if self.api_version() >= Version::V1_2 || self.enabled_extensions().ext_descriptor_indexing
+        {
             let next =
                 binding_flags_info_vk.insert(ash::vk::DescriptorSetLayoutBindingFlagsCreateInfo {
                     binding_count: binding_flags_vk.len() as u32,
@@ -1200,6 +2007,7 @@ impl Device {
             ref drm_format_modifiers,
             drm_format_modifier_plane_layouts: _,
             external_memory_handle_types: _,
+            export_metal_object_types: _,
             _ne: _,
         } = create_info;
 
@@ -1299,6 +2107,7 @@ impl Device {
             ref drm_format_modifiers,
             drm_format_modifier_plane_layouts: _,
             external_memory_handle_types,
+            export_metal_object_types: _,
             _ne: _,
         } = &create_info;
 
@@ -1556,6 +2365,89 @@ impl Device {
         })
     }
 
+    /// Retrieves the properties of an external Windows handle when imported as a given external
+    /// handle type.
+    ///
+    /// An error will be returned if the
+    /// [`khr_external_memory_win32`](DeviceExtensions::khr_external_memory_win32) extension was
+    /// not enabled on the device, or if `handle_type` is
+    /// [`ExternalMemoryHandleType::OpaqueWin32`] or [`ExternalMemoryHandleType::OpaqueWin32Kmt`].
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid Windows handle to external memory that was created outside the
+    ///   Vulkan API.
+    #[inline]
+    pub unsafe fn memory_win32_handle_properties(
+        &self,
+        handle_type: ExternalMemoryHandleType,
+        handle: ash::vk::HANDLE,
+    ) -> Result<MemoryWin32HandleProperties, Validated<VulkanError>> {
+        self.validate_memory_win32_handle_properties(handle_type, handle)?;
+
+        Ok(self.memory_win32_handle_properties_unchecked(handle_type, handle)?)
+    }
+
+    fn validate_memory_win32_handle_properties(
+        &self,
+        handle_type: ExternalMemoryHandleType,
+        _handle: ash::vk::HANDLE,
+    ) -> Result<(), Box<ValidationError>> {
+        if !self.enabled_extensions().khr_external_memory_win32 {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "khr_external_memory_win32",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        handle_type.validate_device(self).map_err(|err| {
+            err.add_context("handle_type")
+                .set_vuids(&["VUID-vkGetMemoryWin32HandlePropertiesKHR-handleType-parameter"])
+        })?;
+
+        if matches!(
+            handle_type,
+            ExternalMemoryHandleType::OpaqueWin32 | ExternalMemoryHandleType::OpaqueWin32Kmt
+        ) {
+            return Err(Box::new(ValidationError {
+                context: "handle_type".into(),
+                problem: "is `ExternalMemoryHandleType::OpaqueWin32` or \
+                    `ExternalMemoryHandleType::OpaqueWin32Kmt`"
+                    .into(),
+                vuids: &["VUID-vkGetMemoryWin32HandlePropertiesKHR-handleType-00666"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn memory_win32_handle_properties_unchecked(
+        &self,
+        handle_type: ExternalMemoryHandleType,
+        handle: ash::vk::HANDLE,
+    ) -> Result<MemoryWin32HandleProperties, VulkanError> {
+        let mut memory_win32_handle_properties = ash::vk::MemoryWin32HandlePropertiesKHR::default();
+
+        let fns = self.fns();
+        (fns.khr_external_memory_win32
+            .get_memory_win32_handle_properties_khr)(
+            self.handle,
+            handle_type.into(),
+            handle,
+            &mut memory_win32_handle_properties,
+        )
+        .result()
+        .map_err(VulkanError::from)?;
+
+        Ok(MemoryWin32HandleProperties {
+            memory_type_bits: memory_win32_handle_properties.memory_type_bits,
+        })
+    }
+
     /// Assigns a human-readable name to `object` for debugging purposes.
     ///
     /// If `object_name` is `None`, a previously set object name is removed.
@@ -1579,14 +2471,72 @@ impl Device {
             ..Default::default()
         };
 
-        unsafe {
-            let fns = self.fns();
-            (fns.ext_debug_utils.set_debug_utils_object_name_ext)(self.handle, &info)
-                .result()
-                .map_err(VulkanError::from)?;
-        }
+        unsafe {
+            let fns = self.fns();
+            (fns.ext_debug_utils.set_debug_utils_object_name_ext)(self.handle, &info)
+                .result()
+                .map_err(VulkanError::from)?;
+        }
+
+        if let Some(resources) = &self.resources {
+            resources.set_name(T::Handle::TYPE, object.handle().as_raw(), object_name);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the `MTLDevice` that this device is implemented with, as an `id<MTLDevice>`
+    /// pointer.
+    ///
+    /// The [`ext_metal_objects`](DeviceExtensions::ext_metal_objects) extension must be enabled
+    /// on the device, and [`ExportMetalObjectTypes::METAL_DEVICE`] must have been specified in
+    /// [`InstanceCreateInfo::export_metal_object_types`] when creating the instance.
+    ///
+    /// [`InstanceCreateInfo::export_metal_object_types`]: crate::instance::InstanceCreateInfo::export_metal_object_types
+    #[inline]
+    pub fn export_metal_device(&self) -> Result<*mut c_void, Box<ValidationError>> {
+        self.validate_export_metal_device()?;
+
+        Ok(unsafe { self.export_metal_device_unchecked() })
+    }
+
+    fn validate_export_metal_device(&self) -> Result<(), Box<ValidationError>> {
+        if !self.enabled_extensions().ext_metal_objects {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "ext_metal_objects",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        if !self
+            .instance()
+            .export_metal_object_types()
+            .intersects(ExportMetalObjectTypes::METAL_DEVICE)
+        {
+            return Err(Box::new(ValidationError {
+                context: "self.instance().export_metal_object_types()".into(),
+                problem: "does not contain `ExportMetalObjectTypes::METAL_DEVICE`".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn export_metal_device_unchecked(&self) -> *mut c_void {
+        let mut device_info_vk = ash::vk::ExportMetalDeviceInfoEXT::default();
+        let mut objects_info_vk = ash::vk::ExportMetalObjectsInfoEXT {
+            p_next: <*mut _>::cast(&mut device_info_vk),
+            ..Default::default()
+        };
+
+        let fns = self.fns();
+        (fns.ext_metal_objects.export_metal_objects_ext)(self.handle, &mut objects_info_vk);
 
-        Ok(())
+        device_info_vk.mtl_device
     }
 
     /// Waits until all work on this device has finished. You should never need to call
@@ -1626,9 +2576,17 @@ impl Debug for Device {
             active_queue_family_indices,
 
             allocation_count,
+            validation_enabled,
             fence_pool: _,
             semaphore_pool: _,
             event_pool: _,
+            layout_cache,
+            auto_debug_names,
+            checkpoint_names: _,
+            resources,
+            allocation_callbacks,
+            _memory_report_callbacks: _,
+            needs_destruction,
         } = self;
 
         f.debug_struct("Device")
@@ -1642,10 +2600,54 @@ impl Debug for Device {
             .field("fns", fns)
             .field("active_queue_family_indices", active_queue_family_indices)
             .field("allocation_count", allocation_count)
+            .field("validation_enabled", validation_enabled)
+            .field("intern_layouts", &layout_cache.is_some())
+            .field("auto_debug_names", auto_debug_names)
+            .field("track_resources", &resources.is_some())
+            .field("allocation_callbacks", allocation_callbacks)
+            .field("needs_destruction", needs_destruction)
             .finish_non_exhaustive()
     }
 }
 
+fn is_host_accessible(buffer: &Subbuffer<[u8]>) -> bool {
+    matches!(buffer.buffer().memory(), BufferMemory::Normal(_)) && buffer.mapped_slice().is_ok()
+}
+
+// The data that a host command reads from. When the command is deferred, this is kept alive by
+// the `DeferredOperation` until the command has completed.
+struct HostCommandState<I, T> {
+    _info: I,
+    info_vk: T,
+    geometries_vk: Vec<ash::vk::AccelerationStructureGeometryKHR<'static>>,
+    build_range_infos_vk: Vec<ash::vk::AccelerationStructureBuildRangeInfoKHR>,
+    build_range_info_pointers_vk: Vec<*const ash::vk::AccelerationStructureBuildRangeInfoKHR>,
+}
+
+// The raw pointers only point into the state itself, and are never accessed concurrently.
+unsafe impl<I: Send, T> Send for HostCommandState<I, T> {}
+unsafe impl<I: Sync, T> Sync for HostCommandState<I, T> {}
+
+unsafe fn finish_host_command<I, T>(
+    deferred_operation: Option<&Arc<DeferredOperation>>,
+    result: ash::vk::Result,
+    state: Box<HostCommandState<I, T>>,
+) -> Result<(), VulkanError>
+where
+    I: Send + Sync + 'static,
+    T: 'static,
+{
+    match result {
+        ash::vk::Result::SUCCESS | ash::vk::Result::OPERATION_NOT_DEFERRED_KHR => Ok(()),
+        ash::vk::Result::OPERATION_DEFERRED_KHR => {
+            deferred_operation.unwrap().set_keep_alive(state);
+
+            Ok(())
+        }
+        err => Err(VulkanError::from(err)),
+    }
+}
+
 impl Drop for Device {
     #[inline]
     fn drop(&mut self) {
@@ -1661,7 +2663,20 @@ impl Drop for Device {
             for &raw_event in self.event_pool.lock().iter() {
                 (fns.v1_0.destroy_event)(self.handle, raw_event, ptr::null());
             }
-            (fns.v1_0.destroy_device)(self.handle, ptr::null());
+
+            if self.needs_destruction {
+                let allocation_callbacks_vk = self
+                    .allocation_callbacks
+                    .as_ref()
+                    .map(|allocation_callbacks| allocation_callbacks.to_vulkan());
+
+                (fns.v1_0.destroy_device)(
+                    self.handle,
+                    allocation_callbacks_vk
+                        .as_ref()
+                        .map_or(ptr::null(), |callbacks| callbacks),
+                );
+            }
         }
     }
 }
@@ -1755,6 +2770,104 @@ pub struct DeviceCreateInfo {
     /// [`ext_private_data`]: DeviceExtensions::ext_private_data
     pub private_data_slot_request_count: u32,
 
+    /// Additional diagnostic information that the implementation should collect, to be used by
+    /// external tools such as NVIDIA Nsight Aftermath when the device is lost.
+    ///
+    /// If not empty, `enabled_extensions` must contain [`nv_device_diagnostics_config`], and the
+    /// [`diagnostics_config`] feature must be enabled.
+    ///
+    /// The default value is empty.
+    ///
+    /// [`nv_device_diagnostics_config`]: DeviceExtensions::nv_device_diagnostics_config
+    /// [`diagnostics_config`]: DeviceFeatures::diagnostics_config
+    pub diagnostics_config_flags: DeviceDiagnosticsConfigFlags,
+
+    /// Whether to intern the descriptor set layouts and pipeline layouts that are created from
+    /// the device.
+    ///
+    /// When enabled, [`DescriptorSetLayout::new`] and [`PipelineLayout::new`] return the existing
+    /// layout if a layout that is still alive was created with an identical create info, instead
+    /// of creating a new Vulkan object. Applications that create many pipelines usually end up
+    /// with many identical layouts, so this reduces both the time spent creating layouts and the
+    /// number of objects in the driver. Because identical layouts are then the same object, it also
+    /// allows descriptor sets to be reused between pipelines that were created separately.
+    ///
+    /// Immutable samplers and descriptor set layouts in the create infos are compared by identity,
+    /// not by their own create infos. Layouts created with `from_handle` are never interned.
+    ///
+    /// This is a vulkano feature that does not correspond to anything in Vulkan.
+    ///
+    /// The default value is `false`.
+    ///
+    /// [`DescriptorSetLayout::new`]: crate::descriptor_set::layout::DescriptorSetLayout::new
+    /// [`PipelineLayout::new`]: crate::pipeline::layout::PipelineLayout::new
+    pub intern_layouts: bool,
+
+    /// Whether to automatically give a debug name to the objects that are created from the
+    /// device.
+    ///
+    /// When enabled, buffers, images, image views, samplers, shader modules, descriptor set
+    /// layouts, pipeline layouts, pipelines, render passes and framebuffers are named after their
+    /// type and the location in your code where they were created, for example
+    /// `Image (src/main.rs:120:17)`. In addition, each render pass instance that is recorded into
+    /// a [`RecordingCommandBuffer`] is surrounded by a debug label region that is named after
+    /// the location where it was begun. This makes captures in graphics debuggers such as
+    /// RenderDoc much easier to navigate.
+    ///
+    /// A name that you set yourself with [`set_debug_utils_object_name`] replaces the automatic
+    /// name. Objects that are created with the `_unchecked` or `from_handle` functions are not
+    /// named automatically. If [`intern_layouts`] is enabled, an interned layout is named after
+    /// the location that most recently requested it.
+    ///
+    /// If set to `true`, the [`ext_debug_utils`] extension must be enabled on the instance.
+    ///
+    /// If the `renderdoc` feature is enabled, and RenderDoc is present in the process, objects are
+    /// named automatically as long as [`ext_debug_utils`] is enabled, even if this is `false`.
+    ///
+    /// This is a vulkano feature that does not correspond to anything in Vulkan.
+    ///
+    /// The default value is `false`.
+    ///
+    /// [`RecordingCommandBuffer`]: crate::command_buffer::RecordingCommandBuffer
+    /// [`set_debug_utils_object_name`]: DeviceOwnedVulkanObject::set_debug_utils_object_name
+    /// [`intern_layouts`]: Self::intern_layouts
+    /// [`ext_debug_utils`]: crate::instance::InstanceExtensions::ext_debug_utils
+    pub auto_debug_names: bool,
+
+    /// Whether to keep track of the buffers, images and device memory allocations that are
+    /// created from the device, so that they are included in [`Device::dump_resources`].
+    ///
+    /// Tracking adds a lock to the creation and destruction of each of these objects, so it is
+    /// meant for debugging and for gathering information for bug reports.
+    ///
+    /// This is a vulkano feature that does not correspond to anything in Vulkan.
+    ///
+    /// The default value is `false`.
+    pub track_resources: bool,
+
+    /// The allocator that the Vulkan implementation should use for the host memory of the
+    /// device.
+    ///
+    /// The device does not inherit the
+    /// [`allocation_callbacks`](crate::instance::InstanceCreateInfo::allocation_callbacks) of
+    /// its instance. If this is `None`, the implementation uses its own allocator.
+    ///
+    /// The default value is `None`.
+    pub allocation_callbacks: Option<Arc<AllocationCallbacks>>,
+
+    /// Callbacks that are called whenever device memory is allocated or freed for the device,
+    /// including the memory that the implementation allocates internally. The callbacks are kept
+    /// alive for as long as the device.
+    ///
+    /// If not empty, `enabled_extensions` must contain [`ext_device_memory_report`], and the
+    /// [`device_memory_report`] feature must be enabled.
+    ///
+    /// The default value is empty.
+    ///
+    /// [`ext_device_memory_report`]: DeviceExtensions::ext_device_memory_report
+    /// [`device_memory_report`]: DeviceFeatures::device_memory_report
+    pub device_memory_report_callbacks: Vec<Arc<DeviceMemoryReportCallback>>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -1767,6 +2880,12 @@ impl Default for DeviceCreateInfo {
             enabled_features: DeviceFeatures::empty(),
             physical_devices: SmallVec::new(),
             private_data_slot_request_count: 0,
+            diagnostics_config_flags: DeviceDiagnosticsConfigFlags::empty(),
+            intern_layouts: false,
+            auto_debug_names: false,
+            track_resources: false,
+            allocation_callbacks: None,
+            device_memory_report_callbacks: Vec::new(),
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -1783,6 +2902,12 @@ impl DeviceCreateInfo {
             ref enabled_features,
             ref physical_devices,
             private_data_slot_request_count,
+            diagnostics_config_flags,
+            intern_layouts: _,
+            auto_debug_names,
+            track_resources: _,
+            allocation_callbacks: _,
+            ref device_memory_report_callbacks,
             _ne: _,
         } = self;
 
@@ -1804,6 +2929,7 @@ impl DeviceCreateInfo {
                 flags: _,
                 queue_family_index,
                 queues: _,
+                global_priority: _,
                 _ne: _,
             } = queue_create_info;
 
@@ -2089,6 +3215,77 @@ impl DeviceCreateInfo {
             }));
         }
 
+        if !diagnostics_config_flags.is_empty() {
+            if !enabled_extensions.nv_device_diagnostics_config {
+                return Err(Box::new(ValidationError {
+                    context: "diagnostics_config_flags".into(),
+                    problem: "is not empty".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                        "nv_device_diagnostics_config",
+                    )])]),
+                    ..Default::default()
+                }));
+            }
+
+            if !enabled_features.diagnostics_config {
+                return Err(Box::new(ValidationError {
+                    context: "diagnostics_config_flags".into(),
+                    problem: "is not empty".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "diagnostics_config",
+                    )])]),
+                    ..Default::default()
+                }));
+            }
+
+            diagnostics_config_flags
+                .validate_physical_device(physical_device)
+                .map_err(|err| {
+                    err.add_context("diagnostics_config_flags")
+                        .set_vuids(&["VUID-VkDeviceDiagnosticsConfigCreateInfoNV-flags-parameter"])
+                })?;
+        }
+
+        if !device_memory_report_callbacks.is_empty() {
+            if !enabled_extensions.ext_device_memory_report {
+                return Err(Box::new(ValidationError {
+                    context: "device_memory_report_callbacks".into(),
+                    problem: "is not empty".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                        "ext_device_memory_report",
+                    )])]),
+                    ..Default::default()
+                }));
+            }
+
+            if !enabled_features.device_memory_report {
+                return Err(Box::new(ValidationError {
+                    context: "device_memory_report_callbacks".into(),
+                    problem: "is not empty".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "device_memory_report",
+                    )])]),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        if auto_debug_names
+            && !physical_device
+                .instance()
+                .enabled_extensions()
+                .ext_debug_utils
+        {
+            return Err(Box::new(ValidationError {
+                context: "auto_debug_names".into(),
+                problem: "is `true`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::InstanceExtension(
+                    "ext_debug_utils",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
         Ok(())
     }
 }
@@ -2116,6 +3313,26 @@ pub struct QueueCreateInfo {
     /// The default value is a single queue with a priority of 0.5.
     pub queues: Vec<f32>,
 
+    /// The priority of the queues relative to the queues of other processes and the system.
+    /// Unlike the priorities in `queues`, this affects the scheduling of work across the whole
+    /// system, so that for example a compositor can preempt the applications it is displaying.
+    ///
+    /// If this is `Some`, the [`khr_global_priority`] or [`ext_global_priority`] extension must be
+    /// enabled on the device. If the [`global_priority_query`] feature is enabled, the priority
+    /// must be in the [`global_priorities`] of the queue family.
+    ///
+    /// Priorities higher than [`QueueGlobalPriority::Medium`] may require the process to have
+    /// special permissions. If it doesn't, creating the device returns
+    /// [`VulkanError::NotPermitted`].
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`khr_global_priority`]: DeviceExtensions::khr_global_priority
+    /// [`ext_global_priority`]: DeviceExtensions::ext_global_priority
+    /// [`global_priority_query`]: DeviceFeatures::global_priority_query
+    /// [`global_priorities`]: QueueFamilyProperties::global_priorities
+    pub global_priority: Option<QueueGlobalPriority>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -2126,6 +3343,7 @@ impl Default for QueueCreateInfo {
             flags: QueueCreateFlags::empty(),
             queue_family_index: 0,
             queues: vec![0.5],
+            global_priority: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -2142,6 +3360,7 @@ impl QueueCreateInfo {
             flags,
             queue_family_index,
             ref queues,
+            global_priority,
             _ne: _,
         } = self;
 
@@ -2170,6 +3389,33 @@ impl QueueCreateInfo {
                 })
             })?;
 
+        if flags.intersects(QueueCreateFlags::PROTECTED) {
+            if !device_features.protected_memory {
+                return Err(Box::new(ValidationError {
+                    context: "flags".into(),
+                    problem: "contains `QueueCreateFlags::PROTECTED`".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "protected_memory",
+                    )])]),
+                    vuids: &["VUID-VkDeviceQueueCreateInfo-flags-02861"],
+                }));
+            }
+
+            if !queue_family_properties
+                .queue_flags
+                .intersects(QueueFlags::PROTECTED)
+            {
+                return Err(Box::new(ValidationError {
+                    problem: "`flags` contains `QueueCreateFlags::PROTECTED`, but the queue \
+                        family indicated by `queue_family_index` does not support protected \
+                        queues"
+                        .into(),
+                    vuids: &["VUID-VkDeviceQueueCreateInfo-flags-06449"],
+                    ..Default::default()
+                }));
+            }
+        }
+
         if queues.is_empty() {
             return Err(Box::new(ValidationError {
                 context: "queues".into(),
@@ -2200,16 +3446,124 @@ impl QueueCreateInfo {
             }
         }
 
+        if let Some(global_priority) = global_priority {
+            if !(device_extensions.khr_global_priority || device_extensions.ext_global_priority) {
+                return Err(Box::new(ValidationError {
+                    context: "global_priority".into(),
+                    problem: "is `Some`".into(),
+                    requires_one_of: RequiresOneOf(&[
+                        RequiresAllOf(&[Requires::DeviceExtension("khr_global_priority")]),
+                        RequiresAllOf(&[Requires::DeviceExtension("ext_global_priority")]),
+                    ]),
+                    ..Default::default()
+                }));
+            }
+
+            global_priority
+                .validate_physical_device(physical_device)
+                .map_err(|err| {
+                    err.add_context("global_priority").set_vuids(&[
+                        "VUID-VkDeviceQueueGlobalPriorityCreateInfoKHR-globalPriority-parameter",
+                    ])
+                })?;
+
+            if device_features.global_priority_query
+                && !queue_family_properties
+                    .global_priorities
+                    .contains(&global_priority)
+            {
+                return Err(Box::new(ValidationError {
+                    problem: "the `global_priority_query` feature is enabled, but \
+                        `global_priority` is not one of the `global_priorities` of the queue \
+                        family indicated by `queue_family_index`"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
         Ok(())
     }
 }
 
+vulkan_bitflags! {
+    #[non_exhaustive]
+
+    /// Flags specifying the diagnostic information that a device should collect.
+    DeviceDiagnosticsConfigFlags = DeviceDiagnosticsConfigFlagsNV(u32);
+
+    /// Generates debug information for shaders, which can be used to map shader crashes back to
+    /// the source code.
+    ENABLE_SHADER_DEBUG_INFO = ENABLE_SHADER_DEBUG_INFO,
+
+    /// Tracks the resources that are in use, so that a crash dump can identify the resources
+    /// that were accessed when a fault occurred.
+    ENABLE_RESOURCE_TRACKING = ENABLE_RESOURCE_TRACKING,
+
+    /// Automatically inserts checkpoints around each draw, dispatch and copy command.
+    ENABLE_AUTOMATIC_CHECKPOINTS = ENABLE_AUTOMATIC_CHECKPOINTS,
+}
+
+vulkan_bitflags! {
+    #[non_exhaustive]
+
+    /// The types of Metal objects that can be exported from Vulkan objects, when the
+    /// [`ext_metal_objects`](DeviceExtensions::ext_metal_objects) extension is enabled.
+    ExportMetalObjectTypes = ExportMetalObjectTypeFlagsEXT(u32);
+
+    /// The `MTLDevice` of a device. This can be specified for an instance.
+    METAL_DEVICE = METAL_DEVICE,
+
+    /// The `MTLCommandQueue` of a queue. This can be specified for an instance.
+    METAL_COMMAND_QUEUE = METAL_COMMAND_QUEUE,
+
+    /// The `MTLBuffer` of a device memory allocation.
+    METAL_BUFFER = METAL_BUFFER,
+
+    /// The `MTLTexture` of an image, image view or buffer view. This can be specified for an
+    /// image.
+    METAL_TEXTURE = METAL_TEXTURE,
+
+    /// The `IOSurfaceRef` of an image. This can be specified for an image.
+    METAL_IOSURFACE = METAL_IOSURFACE,
+
+    /// The `MTLSharedEvent` of a semaphore or event. This can be specified for a semaphore.
+    METAL_SHARED_EVENT = METAL_SHARED_EVENT,
+}
+
+impl ExportMetalObjectTypes {
+    /// Returns one `ExportMetalObjectCreateInfoEXT` for each type in `self`, as the struct only
+    /// holds a single type.
+    pub(crate) fn to_vulkan_create_infos(
+        self,
+    ) -> SmallVec<[ash::vk::ExportMetalObjectCreateInfoEXT<'static>; 2]> {
+        let types_vk = ash::vk::ExportMetalObjectTypeFlagsEXT::from(self);
+
+        (0..u32::BITS)
+            .map(|bit| ash::vk::ExportMetalObjectTypeFlagsEXT::from_raw(1 << bit))
+            .filter(|&type_vk| types_vk.contains(type_vk))
+            .map(
+                |export_object_type| ash::vk::ExportMetalObjectCreateInfoEXT {
+                    export_object_type,
+                    ..Default::default()
+                },
+            )
+            .collect()
+    }
+}
+
 vulkan_bitflags! {
     #[non_exhaustive]
 
     /// Flags specifying additional properties of a queue.
     QueueCreateFlags = DeviceQueueCreateFlags(u32);
 
+    /// The queues are protected-capable, and can execute protected submissions.
+    ///
+    /// The [`protected_memory`] feature must be enabled on the device, and the queue family must
+    /// have the [`QueueFlags::PROTECTED`] flag.
+    ///
+    /// [`protected_memory`]: DeviceFeatures::protected_memory
     PROTECTED = PROTECTED
     RequiresOneOf([
         RequiresAllOf([APIVersion(V1_1)]),
@@ -2297,10 +3651,21 @@ pub struct MemoryFdProperties {
     pub memory_type_bits: u32,
 }
 
+/// The properties of a Windows handle when it is imported.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct MemoryWin32HandleProperties {
+    /// A bitmask of the indices of memory types that can be used with the handle.
+    pub memory_type_bits: u32,
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::device::{
-        Device, DeviceCreateInfo, DeviceExtensions, DeviceFeatures, QueueCreateInfo,
+    use crate::{
+        device::{Device, DeviceCreateInfo, DeviceExtensions, DeviceFeatures, QueueCreateInfo},
+        format::{Format, FormatFeatures},
+        image::ImageTiling,
+        Validated,
     };
     use std::{ffi::CString, sync::Arc};
 
@@ -2346,6 +3711,253 @@ mod tests {
         assert!(Arc::get_mut(&mut device).is_some());
     }
 
+    #[test]
+    fn find_supported_format() {
+        let (device, _) = gfx_dev_and_queue!();
+        let physical_device = device.physical_device();
+
+        assert!(physical_device.best_depth_format().is_some());
+        assert!(physical_device.best_depth_stencil_format().is_some());
+
+        // Required to be supported by the Vulkan specification.
+        assert_eq!(
+            physical_device.find_supported_format(
+                [Format::R8G8B8A8_UNORM],
+                ImageTiling::Optimal,
+                FormatFeatures::SAMPLED_IMAGE | FormatFeatures::COLOR_ATTACHMENT,
+            ),
+            Some(Format::R8G8B8A8_UNORM),
+        );
+        assert_eq!(
+            physical_device.find_supported_buffer_format(
+                [Format::D16_UNORM, Format::R32_UINT],
+                FormatFeatures::UNIFORM_TEXEL_BUFFER,
+            ),
+            Some(Format::R32_UINT),
+        );
+        assert_eq!(
+            physical_device.find_supported_format(
+                [],
+                ImageTiling::Optimal,
+                FormatFeatures::empty()
+            ),
+            None,
+        );
+    }
+
+    #[test]
+    fn validation_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+        assert!(device.validation_enabled());
+
+        unsafe { device.set_validation_enabled(false) };
+        assert!(!device.validation_enabled());
+
+        unsafe { device.set_validation_enabled(true) };
+        assert!(device.validation_enabled());
+    }
+
+    #[test]
+    fn intern_layouts() {
+        use crate::{
+            descriptor_set::layout::{
+                DescriptorSetLayout, DescriptorSetLayoutBinding, DescriptorSetLayoutCreateInfo,
+                DescriptorType,
+            },
+            pipeline::layout::{PipelineLayout, PipelineLayoutCreateInfo},
+            shader::ShaderStages,
+        };
+
+        let instance = instance!();
+        let physical_device = match instance.enumerate_physical_devices().unwrap().next() {
+            Some(p) => p,
+            None => return,
+        };
+
+        let (device, _) = Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                queue_create_infos: vec![QueueCreateInfo {
+                    queue_family_index: 0,
+                    ..Default::default()
+                }],
+                intern_layouts: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(device.interns_layouts());
+
+        let set_layout_create_info = |descriptor_type| DescriptorSetLayoutCreateInfo {
+            bindings: [(
+                0,
+                DescriptorSetLayoutBinding {
+                    stages: ShaderStages::COMPUTE,
+                    ..DescriptorSetLayoutBinding::descriptor_type(descriptor_type)
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+
+        let set_layout_a = DescriptorSetLayout::new(
+            device.clone(),
+            set_layout_create_info(DescriptorType::StorageBuffer),
+        )
+        .unwrap();
+        let set_layout_b = DescriptorSetLayout::new(
+            device.clone(),
+            set_layout_create_info(DescriptorType::StorageBuffer),
+        )
+        .unwrap();
+        let set_layout_c = DescriptorSetLayout::new(
+            device.clone(),
+            set_layout_create_info(DescriptorType::UniformBuffer),
+        )
+        .unwrap();
+        assert!(Arc::ptr_eq(&set_layout_a, &set_layout_b));
+        assert!(!Arc::ptr_eq(&set_layout_a, &set_layout_c));
+
+        let pipeline_layout_create_info = || PipelineLayoutCreateInfo {
+            set_layouts: vec![set_layout_a.clone()],
+            ..Default::default()
+        };
+        let pipeline_layout_a =
+            PipelineLayout::new(device.clone(), pipeline_layout_create_info()).unwrap();
+        let pipeline_layout_b =
+            PipelineLayout::new(device.clone(), pipeline_layout_create_info()).unwrap();
+        assert!(Arc::ptr_eq(&pipeline_layout_a, &pipeline_layout_b));
+    }
+
+    #[test]
+    fn auto_debug_names_requires_debug_utils() {
+        let instance = instance!();
+        let physical_device = match instance.enumerate_physical_devices().unwrap().next() {
+            Some(p) => p,
+            None => return,
+        };
+
+        // The instance created by `instance!` doesn't enable `ext_debug_utils`.
+        assert!(matches!(
+            Device::new(
+                physical_device,
+                DeviceCreateInfo {
+                    queue_create_infos: vec![QueueCreateInfo {
+                        queue_family_index: 0,
+                        ..Default::default()
+                    }],
+                    auto_debug_names: true,
+                    ..Default::default()
+                },
+            ),
+            Err(Validated::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn device_memory_report_requires_extension() {
+        use crate::device::memory_report::DeviceMemoryReportCallback;
+
+        let instance = instance!();
+        let physical_device = match instance.enumerate_physical_devices().unwrap().next() {
+            Some(p) => p,
+            None => return,
+        };
+
+        let (callback, _receiver) = DeviceMemoryReportCallback::channel();
+
+        assert!(matches!(
+            Device::new(
+                physical_device,
+                DeviceCreateInfo {
+                    queue_create_infos: vec![QueueCreateInfo {
+                        queue_family_index: 0,
+                        ..Default::default()
+                    }],
+                    device_memory_report_callbacks: vec![callback],
+                    ..Default::default()
+                },
+            ),
+            Err(Validated::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn dump_resources() {
+        use crate::{
+            buffer::{Buffer, BufferCreateInfo, BufferUsage},
+            memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+            VulkanObject,
+        };
+        use ash::vk::Handle;
+
+        let instance = instance!();
+        let Some(physical_device) = instance.enumerate_physical_devices().unwrap().next() else {
+            return;
+        };
+
+        let (device, _) = Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                queue_create_infos: vec![QueueCreateInfo::default()],
+                track_resources: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+        let buffer = Buffer::new_slice::<u32>(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::STORAGE_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+            16,
+        )
+        .unwrap();
+        let handle = format!("{:#x}", buffer.buffer().handle().as_raw());
+
+        let dump = device.dump_resources();
+        assert!(dump.contains("\"buffers\""));
+        assert!(dump.contains(&handle));
+
+        drop(buffer);
+        assert!(!device.dump_resources().contains(&handle));
+    }
+
+    #[test]
+    fn dump_resources_untracked() {
+        use crate::{
+            buffer::{Buffer, BufferCreateInfo, BufferUsage},
+            memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+            VulkanObject,
+        };
+        use ash::vk::Handle;
+
+        let (device, _) = gfx_dev_and_queue!();
+        assert!(!device.tracks_resources());
+
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+        let buffer = Buffer::new_slice::<u32>(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::STORAGE_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+            16,
+        )
+        .unwrap();
+        let handle = format!("{:#x}", buffer.buffer().handle().as_raw());
+
+        // Without tracking, only the memory properties of the device are included.
+        let dump = device.dump_resources();
+        assert!(dump.contains("\"memory_types\""));
+        assert!(!dump.contains(&handle));
+    }
+
     #[test]
     fn too_many_queues() {
         let instance = instance!();
@@ -7,10 +7,10 @@ use crate::{
         DevicePropertiesFfi,
     },
     display::{Display, DisplayPlaneProperties, DisplayPlanePropertiesRaw, DisplayProperties},
-    format::{DrmFormatModifierProperties, Format, FormatProperties},
+    format::{DrmFormatModifierProperties, Format, FormatFeatures, FormatProperties},
     image::{
-        ImageDrmFormatModifierInfo, ImageFormatInfo, ImageFormatProperties, ImageUsage,
-        SparseImageFormatInfo, SparseImageFormatProperties,
+        ImageDrmFormatModifierInfo, ImageFormatInfo, ImageFormatProperties, ImageTiling,
+        ImageUsage, SparseImageFormatInfo, SparseImageFormatProperties,
     },
     instance::{Instance, InstanceOwned},
     macros::{impl_id_counter, vulkan_bitflags, vulkan_enum},
@@ -119,7 +119,8 @@ impl PhysicalDevice {
             properties =
                 Self::get_properties2(handle, &instance, api_version, &supported_extensions);
             memory_properties = Self::get_memory_properties2(handle, &instance);
-            queue_family_properties = Self::get_queue_family_properties2(handle, &instance);
+            queue_family_properties =
+                Self::get_queue_family_properties2(handle, &instance, &supported_extensions);
         } else {
             supported_features = Self::get_features(handle, &instance);
             properties =
@@ -327,6 +328,7 @@ impl PhysicalDevice {
     unsafe fn get_queue_family_properties2(
         handle: ash::vk::PhysicalDevice,
         instance: &Instance,
+        supported_extensions: &DeviceExtensions,
     ) -> Vec<QueueFamilyProperties> {
         let mut num = 0;
         let fns = instance.fns();
@@ -347,6 +349,21 @@ impl PhysicalDevice {
         }
 
         let mut output = vec![ash::vk::QueueFamilyProperties2::default(); num as usize];
+        let mut global_priority_properties_vk = if supported_extensions.khr_global_priority
+            || supported_extensions.ext_global_priority_query
+        {
+            vec![ash::vk::QueueFamilyGlobalPriorityPropertiesKHR::default(); num as usize]
+        } else {
+            Vec::new()
+        };
+
+        for (output, next) in output
+            .iter_mut()
+            .zip(global_priority_properties_vk.iter_mut())
+        {
+            next.p_next = output.p_next;
+            output.p_next = <*mut _>::cast(next);
+        }
 
         if instance.api_version() >= Version::V1_1 {
             (fns.v1_1.get_physical_device_queue_family_properties2)(
@@ -365,7 +382,22 @@ impl PhysicalDevice {
 
         output
             .into_iter()
-            .map(|family| family.queue_family_properties.into())
+            .enumerate()
+            .map(|(index, family)| {
+                let mut properties = QueueFamilyProperties::from(family.queue_family_properties);
+
+                if let Some(global_priority_properties_vk) =
+                    global_priority_properties_vk.get(index)
+                {
+                    properties.global_priorities = global_priority_properties_vk.priorities
+                        [..global_priority_properties_vk.priority_count as usize]
+                        .iter()
+                        .filter_map(|&priority| priority.try_into().ok())
+                        .collect();
+                }
+
+                properties
+            })
             .collect()
     }
 
@@ -1372,6 +1404,105 @@ impl PhysicalDevice {
         })
     }
 
+    /// Returns the first format in `candidates` that supports all of `required_features` for
+    /// images with the given `tiling`, or `None` if none of them do.
+    ///
+    /// Candidates are tried in order, so they should be listed from most to least preferred.
+    /// Formats that can't be used with this physical device at all, for example because they
+    /// require an extension that is not supported, are skipped. For
+    /// [`ImageTiling::DrmFormatModifier`], the features of all DRM format modifiers that the
+    /// format supports are combined.
+    pub fn find_supported_format(
+        &self,
+        candidates: impl IntoIterator<Item = Format>,
+        tiling: ImageTiling,
+        required_features: FormatFeatures,
+    ) -> Option<Format> {
+        self.find_format(candidates, |format_properties| {
+            let features = match tiling {
+                ImageTiling::Linear => format_properties.linear_tiling_features,
+                ImageTiling::Optimal => format_properties.optimal_tiling_features,
+                ImageTiling::DrmFormatModifier => format_properties
+                    .drm_format_modifier_properties
+                    .iter()
+                    .fold(FormatFeatures::empty(), |total, properties| {
+                        total | properties.drm_format_modifier_tiling_features
+                    }),
+            };
+
+            features.contains(required_features)
+        })
+    }
+
+    /// Returns the first format in `candidates` that supports all of `required_features` for
+    /// buffers, or `None` if none of them do.
+    ///
+    /// This is the equivalent of [`find_supported_format`] for the formats of buffer views, such
+    /// as uniform and storage texel buffers, and vertex buffers.
+    ///
+    /// [`find_supported_format`]: Self::find_supported_format
+    pub fn find_supported_buffer_format(
+        &self,
+        candidates: impl IntoIterator<Item = Format>,
+        required_features: FormatFeatures,
+    ) -> Option<Format> {
+        self.find_format(candidates, |format_properties| {
+            format_properties
+                .buffer_features
+                .contains(required_features)
+        })
+    }
+
+    /// Returns the preferred format for depth attachments with optimal tiling.
+    ///
+    /// The candidates are, in order of preference, [`Format::D32_SFLOAT`],
+    /// [`Format::X8_D24_UNORM_PACK32`] and [`Format::D16_UNORM`]. The Vulkan specification
+    /// requires at least one of them to be supported, so this only returns `None` for
+    /// non-conformant implementations.
+    #[inline]
+    pub fn best_depth_format(&self) -> Option<Format> {
+        self.find_supported_format(
+            [
+                Format::D32_SFLOAT,
+                Format::X8_D24_UNORM_PACK32,
+                Format::D16_UNORM,
+            ],
+            ImageTiling::Optimal,
+            FormatFeatures::DEPTH_STENCIL_ATTACHMENT,
+        )
+    }
+
+    /// Returns the preferred format for combined depth/stencil attachments with optimal tiling.
+    ///
+    /// The candidates are, in order of preference, [`Format::D24_UNORM_S8_UINT`],
+    /// [`Format::D32_SFLOAT_S8_UINT`] and [`Format::D16_UNORM_S8_UINT`]. The first one is
+    /// preferred because it packs depth and stencil into 32 bits, whereas the second one usually
+    /// takes up 64 bits per texel. The Vulkan specification requires at least one of the first
+    /// two to be supported, so this only returns `None` for non-conformant implementations.
+    #[inline]
+    pub fn best_depth_stencil_format(&self) -> Option<Format> {
+        self.find_supported_format(
+            [
+                Format::D24_UNORM_S8_UINT,
+                Format::D32_SFLOAT_S8_UINT,
+                Format::D16_UNORM_S8_UINT,
+            ],
+            ImageTiling::Optimal,
+            FormatFeatures::DEPTH_STENCIL_ATTACHMENT,
+        )
+    }
+
+    fn find_format(
+        &self,
+        candidates: impl IntoIterator<Item = Format>,
+        mut predicate: impl FnMut(&FormatProperties) -> bool,
+    ) -> Option<Format> {
+        candidates.into_iter().find(|&format| {
+            self.validate_format_properties(format).is_ok()
+                && predicate(&unsafe { self.format_properties_unchecked(format) })
+        })
+    }
+
     /// Returns the properties supported for images with a given image configuration.
     ///
     /// `Some` is returned if the configuration is supported, `None` if it is not.
@@ -2799,6 +2930,10 @@ impl PhysicalDevice {
     /// These properties may change during runtime, so the result only reflects the current
     /// situation and is not cached.
     ///
+    /// This can be used to adapt the application's behavior to the tools that are attached, for
+    /// example by disabling the application's own instrumentation when a tool with the
+    /// [`ToolPurposes::PROFILING`] or [`ToolPurposes::TRACING`] purpose is active.
+    ///
     /// The physical device API version must be at least 1.3, or the
     /// [`ext_tooling_info`](crate::device::DeviceExtensions::ext_tooling_info)
     /// extension must be supported by the physical device.
@@ -2847,7 +2982,8 @@ impl PhysicalDevice {
             .result()
             .map_err(VulkanError::from)?;
 
-            let mut tool_properties = Vec::with_capacity(count as usize);
+            let mut tool_properties =
+                vec![ash::vk::PhysicalDeviceToolProperties::default(); count as usize];
             let result = if self.api_version() >= Version::V1_3 {
                 (fns.v1_3.get_physical_device_tool_properties)(
                     self.handle(),
@@ -2865,7 +3001,7 @@ impl PhysicalDevice {
             match result {
                 ash::vk::Result::INCOMPLETE => (),
                 ash::vk::Result::SUCCESS => {
-                    tool_properties.set_len(count as usize);
+                    tool_properties.truncate(count as usize);
 
                     return Ok(tool_properties
                         .into_iter()
@@ -3843,38 +3979,40 @@ vulkan_bitflags! {
 vulkan_enum! {
     #[non_exhaustive]
 
-    // TODO: document
+    /// The behavior of out-of-bounds accesses to buffers by a pipeline.
     PipelineRobustnessBufferBehavior = PipelineRobustnessBufferBehaviorEXT(i32);
 
-    // TODO: document
+    /// The behavior that is enabled on the device with the `robust_buffer_access` and
+    /// `robust_buffer_access2` features.
     DeviceDefault = DEVICE_DEFAULT,
 
-    // TODO: document
+    /// Out-of-bounds accesses are not checked, and result in undefined behavior.
     Disabled = DISABLED,
 
-    // TODO: document
+    /// Out-of-bounds accesses behave as if the `robust_buffer_access` feature was enabled.
     RobustBufferAccess = ROBUST_BUFFER_ACCESS,
 
-    // TODO: document
+    /// Out-of-bounds accesses behave as if the `robust_buffer_access2` feature was enabled.
     RobustBufferAccess2 = ROBUST_BUFFER_ACCESS_2,
 }
 
 vulkan_enum! {
     #[non_exhaustive]
 
-    // TODO: document
+    /// The behavior of out-of-bounds accesses to images by a pipeline.
     PipelineRobustnessImageBehavior = PipelineRobustnessImageBehaviorEXT(i32);
 
-    // TODO: document
+    /// The behavior that is enabled on the device with the `robust_image_access` and
+    /// `robust_image_access2` features.
     DeviceDefault = DEVICE_DEFAULT,
 
-    // TODO: document
+    /// Out-of-bounds accesses are not checked, and result in undefined behavior.
     Disabled = DISABLED,
 
-    // TODO: document
+    /// Out-of-bounds accesses behave as if the `robust_image_access` feature was enabled.
     RobustImageAccess = ROBUST_IMAGE_ACCESS,
 
-    // TODO: document
+    /// Out-of-bounds accesses behave as if the `robust_image_access2` feature was enabled.
     RobustImageAccess2 = ROBUST_IMAGE_ACCESS_2,
 }
 
@@ -1,10 +1,13 @@
 use super::{Device, DeviceOwned, QueueCreateFlags};
 use crate::{
-    command_buffer::{CommandBufferSubmitInfo, SemaphoreSubmitInfo, SubmitInfo},
+    buffer::Buffer,
+    command_buffer::{CommandBufferSubmitInfo, SemaphoreSubmitInfo, SubmitFlags, SubmitInfo},
+    image::Image,
     instance::{debug::DebugUtilsLabel, InstanceOwnedDebugWrapper},
-    macros::vulkan_bitflags,
+    macros::{vulkan_bitflags, vulkan_enum},
     memory::{
-        BindSparseInfo, SparseBufferMemoryBind, SparseImageMemoryBind, SparseImageOpaqueMemoryBind,
+        BindSparseInfo, DeviceMemory, ExternalMemoryHandleType, SparseBufferMemoryBind,
+        SparseImageMemoryBind, SparseImageOpaqueMemoryBind,
     },
     swapchain::{PresentInfo, SemaphorePresentInfo, SwapchainPresentInfo},
     sync::{fence::Fence, semaphore::SemaphoreType, PipelineStages},
@@ -128,6 +131,75 @@ impl Queue {
             _state: self.state.lock(),
         })
     }
+
+    /// Retrieves the checkpoints that the queue most recently reached, for each pipeline stage.
+    ///
+    /// Checkpoints are set in command buffers with [`set_checkpoint`]. This is meant to be used
+    /// after the device has been lost, to find out where the work on the queue went wrong. The
+    /// checkpoint that was reached by [`PipelineStages::TOP_OF_PIPE`] is the most recent one whose
+    /// work was started, while the checkpoint that was reached by
+    /// [`PipelineStages::BOTTOM_OF_PIPE`] is the most recent one whose work was completed. The
+    /// commands between those two checkpoints were in flight when the device was lost.
+    ///
+    /// The [`nv_device_diagnostic_checkpoints`] extension must be enabled on the device.
+    ///
+    /// [`set_checkpoint`]: crate::command_buffer::RecordingCommandBuffer::set_checkpoint
+    /// [`nv_device_diagnostic_checkpoints`]: crate::device::DeviceExtensions::nv_device_diagnostic_checkpoints
+    #[inline]
+    pub fn checkpoint_data(&self) -> Result<Vec<CheckpointData>, Box<ValidationError>> {
+        self.validate_checkpoint_data()?;
+
+        unsafe { Ok(self.checkpoint_data_unchecked()) }
+    }
+
+    fn validate_checkpoint_data(&self) -> Result<(), Box<ValidationError>> {
+        if !self
+            .device
+            .enabled_extensions()
+            .nv_device_diagnostic_checkpoints
+        {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "nv_device_diagnostic_checkpoints",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn checkpoint_data_unchecked(&self) -> Vec<CheckpointData> {
+        let fns = self.device.fns();
+
+        let mut count = 0;
+        (fns.nv_device_diagnostic_checkpoints
+            .get_queue_checkpoint_data_nv)(self.handle, &mut count, ptr::null_mut());
+
+        let mut checkpoint_data_vk = vec![ash::vk::CheckpointDataNV::default(); count as usize];
+        (fns.nv_device_diagnostic_checkpoints
+            .get_queue_checkpoint_data_nv)(
+            self.handle,
+            &mut count,
+            checkpoint_data_vk.as_mut_ptr(),
+        );
+        checkpoint_data_vk.truncate(count as usize);
+
+        let checkpoint_names = self.device.checkpoint_names();
+
+        checkpoint_data_vk
+            .into_iter()
+            .map(|checkpoint_data_vk| CheckpointData {
+                stage: ash::vk::PipelineStageFlags2::from_raw(
+                    checkpoint_data_vk.stage.as_raw().into(),
+                )
+                .into(),
+                name: checkpoint_names.name(checkpoint_data_vk.p_checkpoint_marker),
+                marker: checkpoint_data_vk.p_checkpoint_marker as usize,
+            })
+            .collect()
+    }
 }
 
 impl Drop for Queue {
@@ -530,6 +602,7 @@ impl<'a> QueueGuard<'a> {
         let &PresentInfo {
             wait_semaphores: _,
             ref swapchains,
+            frame_boundary: _,
             _ne: _,
         } = present_info;
 
@@ -572,6 +645,14 @@ impl<'a> QueueGuard<'a> {
     }
 
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(swapchain_count = present_info.swapchains.len()),
+        )
+    )]
     pub unsafe fn present_unchecked(
         &mut self,
         present_info: &PresentInfo,
@@ -579,6 +660,7 @@ impl<'a> QueueGuard<'a> {
         let PresentInfo {
             wait_semaphores,
             swapchains,
+            frame_boundary,
             _ne: _,
         } = present_info;
 
@@ -640,7 +722,8 @@ impl<'a> QueueGuard<'a> {
             }
         }
 
-        let mut results = vec![ash::vk::Result::SUCCESS; swapchains.len()];
+        let mut results: SmallVec<[_; 4]> =
+            SmallVec::from_elem(ash::vk::Result::SUCCESS, swapchains.len());
         let mut info_vk = ash::vk::PresentInfoKHR {
             wait_semaphore_count: wait_semaphores_vk.len() as u32,
             p_wait_semaphores: wait_semaphores_vk.as_ptr(),
@@ -653,6 +736,7 @@ impl<'a> QueueGuard<'a> {
         let mut present_id_info_vk = None;
         let mut present_mode_info_vk = None;
         let mut present_region_info_vk = None;
+        let mut frame_boundary_vk = None;
 
         if has_present_ids {
             let next = present_id_info_vk.insert(ash::vk::PresentIdKHR {
@@ -694,6 +778,40 @@ impl<'a> QueueGuard<'a> {
             info_vk.p_next = <*const _>::cast(next);
         }
 
+        let frame_boundary_images_vk: SmallVec<[_; 4]>;
+        let frame_boundary_buffers_vk: SmallVec<[_; 4]>;
+
+        if let Some(frame_boundary) = frame_boundary {
+            let &FrameBoundary {
+                flags,
+                frame_id,
+                ref images,
+                ref buffers,
+                tag_name,
+                ref tag,
+                _ne: _,
+            } = frame_boundary;
+
+            frame_boundary_images_vk = images.iter().map(VulkanObject::handle).collect();
+            frame_boundary_buffers_vk = buffers.iter().map(VulkanObject::handle).collect();
+
+            let next = frame_boundary_vk.insert(ash::vk::FrameBoundaryEXT {
+                flags: flags.into(),
+                frame_id,
+                image_count: frame_boundary_images_vk.len() as u32,
+                p_images: frame_boundary_images_vk.as_ptr(),
+                buffer_count: frame_boundary_buffers_vk.len() as u32,
+                p_buffers: frame_boundary_buffers_vk.as_ptr(),
+                tag_name,
+                tag_size: tag.len(),
+                p_tag: tag.as_ptr().cast(),
+                ..Default::default()
+            });
+
+            next.p_next = info_vk.p_next;
+            info_vk.p_next = <*const _>::cast(next);
+        }
+
         let fns = self.queue.device().fns();
         let result = (fns.khr_swapchain.queue_present_khr)(self.queue.handle, &info_vk);
 
@@ -791,12 +909,30 @@ impl<'a> QueueGuard<'a> {
                 .map_err(|err| err.add_context(format!("submit_infos[{}]", index)))?;
 
             let &SubmitInfo {
+                flags,
                 ref wait_semaphores,
                 ref command_buffers,
                 ref signal_semaphores,
+                frame_boundary: _,
+                win32_keyed_mutex: _,
                 _ne: _,
             } = submit_info;
 
+            if flags.intersects(SubmitFlags::PROTECTED)
+                && !self.queue.flags.intersects(QueueCreateFlags::PROTECTED)
+            {
+                return Err(Box::new(ValidationError {
+                    problem: format!(
+                        "`submit_infos[{}].flags` contains `SubmitFlags::PROTECTED`, but the \
+                        queue was not created with the `QueueCreateFlags::PROTECTED` flag",
+                        index
+                    )
+                    .into(),
+                    vuids: &["VUID-vkQueueSubmit2-queue-06447"],
+                    ..Default::default()
+                }));
+            }
+
             for (semaphore_index, semaphore_submit_info) in wait_semaphores.iter().enumerate() {
                 let &SemaphoreSubmitInfo {
                     semaphore: _,
@@ -889,6 +1025,18 @@ impl<'a> QueueGuard<'a> {
     }
 
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                queue_family_index = self.queue.queue_family_index,
+                submit_count = submit_infos.len(),
+                has_fence = fence.is_some(),
+            ),
+        )
+    )]
     pub unsafe fn submit_unchecked(
         &mut self,
         submit_infos: &[SubmitInfo],
@@ -899,6 +1047,12 @@ impl<'a> QueueGuard<'a> {
                 wait_semaphore_infos_vk: SmallVec<[ash::vk::SemaphoreSubmitInfo<'static>; 4]>,
                 command_buffer_infos_vk: SmallVec<[ash::vk::CommandBufferSubmitInfo<'static>; 4]>,
                 signal_semaphore_infos_vk: SmallVec<[ash::vk::SemaphoreSubmitInfo<'static>; 4]>,
+                frame_boundary_vk: Option<ash::vk::FrameBoundaryEXT<'static>>,
+                frame_boundary_images_vk: SmallVec<[ash::vk::Image; 4]>,
+                frame_boundary_buffers_vk: SmallVec<[ash::vk::Buffer; 4]>,
+                win32_keyed_mutex_fields1_vk: Option<Win32KeyedMutexAcquireReleaseFields1Vk>,
+                win32_keyed_mutex_vk:
+                    Option<ash::vk::Win32KeyedMutexAcquireReleaseInfoKHR<'static>>,
             }
 
             let (mut submit_info_vk, mut per_submit_vk): (SmallVec<[_; 4]>, SmallVec<[_; 4]>) =
@@ -906,9 +1060,12 @@ impl<'a> QueueGuard<'a> {
                     .iter()
                     .map(|submit_info| {
                         let &SubmitInfo {
+                            flags,
                             ref wait_semaphores,
                             ref command_buffers,
                             ref signal_semaphores,
+                            ref frame_boundary,
+                            ref win32_keyed_mutex,
                             _ne: _,
                         } = submit_info;
 
@@ -918,11 +1075,23 @@ impl<'a> QueueGuard<'a> {
                             signal_semaphore_infos_vk: SmallVec::with_capacity(
                                 signal_semaphores.len(),
                             ),
+                            frame_boundary_vk: None,
+                            frame_boundary_images_vk: SmallVec::new(),
+                            frame_boundary_buffers_vk: SmallVec::new(),
+                            win32_keyed_mutex_fields1_vk: win32_keyed_mutex
+                                .as_ref()
+                                .map(Win32KeyedMutexAcquireReleaseInfo::to_vulkan_fields1),
+                            win32_keyed_mutex_vk: None,
                         };
                         let PerSubmitInfo {
                             wait_semaphore_infos_vk,
                             command_buffer_infos_vk,
                             signal_semaphore_infos_vk,
+                            frame_boundary_vk,
+                            frame_boundary_images_vk,
+                            frame_boundary_buffers_vk,
+                            win32_keyed_mutex_fields1_vk: _,
+                            win32_keyed_mutex_vk: _,
                         } = &mut per_submit_vk;
 
                         for semaphore_submit_info in wait_semaphores {
@@ -972,9 +1141,34 @@ impl<'a> QueueGuard<'a> {
                             });
                         }
 
+                        if let Some(frame_boundary) = frame_boundary {
+                            let &FrameBoundary {
+                                flags,
+                                frame_id,
+                                ref images,
+                                ref buffers,
+                                tag_name,
+                                ref tag,
+                                _ne: _,
+                            } = frame_boundary;
+
+                            frame_boundary_images_vk
+                                .extend(images.iter().map(VulkanObject::handle));
+                            frame_boundary_buffers_vk
+                                .extend(buffers.iter().map(VulkanObject::handle));
+                            *frame_boundary_vk = Some(ash::vk::FrameBoundaryEXT {
+                                flags: flags.into(),
+                                frame_id,
+                                tag_name,
+                                tag_size: tag.len(),
+                                p_tag: tag.as_ptr().cast(),
+                                ..Default::default()
+                            });
+                        }
+
                         (
                             ash::vk::SubmitInfo2 {
-                                flags: ash::vk::SubmitFlags::empty(), // TODO:
+                                flags: flags.into(),
                                 wait_semaphore_info_count: 0,
                                 p_wait_semaphore_infos: ptr::null(),
                                 command_buffer_info_count: 0,
@@ -994,6 +1188,11 @@ impl<'a> QueueGuard<'a> {
                     wait_semaphore_infos_vk,
                     command_buffer_infos_vk,
                     signal_semaphore_infos_vk,
+                    frame_boundary_vk,
+                    frame_boundary_images_vk,
+                    frame_boundary_buffers_vk,
+                    win32_keyed_mutex_fields1_vk,
+                    win32_keyed_mutex_vk,
                 },
             ) in submit_info_vk.iter_mut().zip(per_submit_vk.iter_mut())
             {
@@ -1006,6 +1205,27 @@ impl<'a> QueueGuard<'a> {
                     p_signal_semaphore_infos: signal_semaphore_infos_vk.as_ptr(),
                     ..*submit_info_vk
                 };
+
+                if let Some(frame_boundary_vk) = frame_boundary_vk {
+                    *frame_boundary_vk = ash::vk::FrameBoundaryEXT {
+                        image_count: frame_boundary_images_vk.len() as u32,
+                        p_images: frame_boundary_images_vk.as_ptr(),
+                        buffer_count: frame_boundary_buffers_vk.len() as u32,
+                        p_buffers: frame_boundary_buffers_vk.as_ptr(),
+                        ..*frame_boundary_vk
+                    };
+
+                    frame_boundary_vk.p_next = submit_info_vk.p_next;
+                    submit_info_vk.p_next = <*const _>::cast(frame_boundary_vk);
+                }
+
+                if let Some(win32_keyed_mutex_fields1_vk) = win32_keyed_mutex_fields1_vk {
+                    let next =
+                        win32_keyed_mutex_vk.insert(win32_keyed_mutex_fields1_vk.to_vulkan());
+
+                    next.p_next = submit_info_vk.p_next;
+                    submit_info_vk.p_next = <*const _>::cast(next);
+                }
             }
 
             let fns = self.queue.device.fns();
@@ -1034,6 +1254,7 @@ impl<'a> QueueGuard<'a> {
             .map_err(VulkanError::from)
         } else {
             struct PerSubmitInfo {
+                protected_submit_info_vk: Option<ash::vk::ProtectedSubmitInfo<'static>>,
                 timeline_semaphore_submit_info_vk:
                     Option<ash::vk::TimelineSemaphoreSubmitInfo<'static>>,
                 wait_semaphores_vk: SmallVec<[ash::vk::Semaphore; 4]>,
@@ -1042,6 +1263,12 @@ impl<'a> QueueGuard<'a> {
                 command_buffers_vk: SmallVec<[ash::vk::CommandBuffer; 4]>,
                 signal_semaphores_vk: SmallVec<[ash::vk::Semaphore; 4]>,
                 signal_semaphore_values_vk: SmallVec<[u64; 4]>,
+                frame_boundary_vk: Option<ash::vk::FrameBoundaryEXT<'static>>,
+                frame_boundary_images_vk: SmallVec<[ash::vk::Image; 4]>,
+                frame_boundary_buffers_vk: SmallVec<[ash::vk::Buffer; 4]>,
+                win32_keyed_mutex_fields1_vk: Option<Win32KeyedMutexAcquireReleaseFields1Vk>,
+                win32_keyed_mutex_vk:
+                    Option<ash::vk::Win32KeyedMutexAcquireReleaseInfoKHR<'static>>,
             }
 
             let (mut submit_info_vk, mut per_submit_vk): (SmallVec<[_; 4]>, SmallVec<[_; 4]>) =
@@ -1049,13 +1276,22 @@ impl<'a> QueueGuard<'a> {
                     .iter()
                     .map(|submit_info| {
                         let &SubmitInfo {
+                            flags,
                             ref wait_semaphores,
                             ref command_buffers,
                             ref signal_semaphores,
+                            ref frame_boundary,
+                            ref win32_keyed_mutex,
                             _ne: _,
                         } = submit_info;
 
                         let mut per_submit_vk = PerSubmitInfo {
+                            protected_submit_info_vk: flags
+                                .intersects(SubmitFlags::PROTECTED)
+                                .then(|| ash::vk::ProtectedSubmitInfo {
+                                    protected_submit: ash::vk::TRUE,
+                                    ..Default::default()
+                                }),
                             timeline_semaphore_submit_info_vk: None,
                             wait_semaphores_vk: SmallVec::with_capacity(wait_semaphores.len()),
                             wait_semaphore_values_vk: SmallVec::with_capacity(
@@ -1067,8 +1303,16 @@ impl<'a> QueueGuard<'a> {
                             signal_semaphore_values_vk: SmallVec::with_capacity(
                                 signal_semaphores.len(),
                             ),
+                            frame_boundary_vk: None,
+                            frame_boundary_images_vk: SmallVec::new(),
+                            frame_boundary_buffers_vk: SmallVec::new(),
+                            win32_keyed_mutex_fields1_vk: win32_keyed_mutex
+                                .as_ref()
+                                .map(Win32KeyedMutexAcquireReleaseInfo::to_vulkan_fields1),
+                            win32_keyed_mutex_vk: None,
                         };
                         let PerSubmitInfo {
+                            protected_submit_info_vk: _,
                             timeline_semaphore_submit_info_vk,
                             wait_semaphores_vk,
                             wait_semaphore_values_vk,
@@ -1076,6 +1320,11 @@ impl<'a> QueueGuard<'a> {
                             command_buffers_vk,
                             signal_semaphores_vk,
                             signal_semaphore_values_vk,
+                            frame_boundary_vk,
+                            frame_boundary_images_vk,
+                            frame_boundary_buffers_vk,
+                            win32_keyed_mutex_fields1_vk: _,
+                            win32_keyed_mutex_vk: _,
                         } = &mut per_submit_vk;
 
                         let mut has_timeline_semaphores = false;
@@ -1127,6 +1376,31 @@ impl<'a> QueueGuard<'a> {
                                 Some(ash::vk::TimelineSemaphoreSubmitInfo::default());
                         }
 
+                        if let Some(frame_boundary) = frame_boundary {
+                            let &FrameBoundary {
+                                flags,
+                                frame_id,
+                                ref images,
+                                ref buffers,
+                                tag_name,
+                                ref tag,
+                                _ne: _,
+                            } = frame_boundary;
+
+                            frame_boundary_images_vk
+                                .extend(images.iter().map(VulkanObject::handle));
+                            frame_boundary_buffers_vk
+                                .extend(buffers.iter().map(VulkanObject::handle));
+                            *frame_boundary_vk = Some(ash::vk::FrameBoundaryEXT {
+                                flags: flags.into(),
+                                frame_id,
+                                tag_name,
+                                tag_size: tag.len(),
+                                p_tag: tag.as_ptr().cast(),
+                                ..Default::default()
+                            });
+                        }
+
                         (
                             ash::vk::SubmitInfo {
                                 wait_semaphore_count: 0,
@@ -1146,6 +1420,7 @@ impl<'a> QueueGuard<'a> {
             for (
                 submit_info_vk,
                 PerSubmitInfo {
+                    protected_submit_info_vk,
                     timeline_semaphore_submit_info_vk,
                     wait_semaphores_vk,
                     wait_semaphore_values_vk,
@@ -1153,6 +1428,11 @@ impl<'a> QueueGuard<'a> {
                     command_buffers_vk,
                     signal_semaphores_vk,
                     signal_semaphore_values_vk,
+                    frame_boundary_vk,
+                    frame_boundary_images_vk,
+                    frame_boundary_buffers_vk,
+                    win32_keyed_mutex_fields1_vk,
+                    win32_keyed_mutex_vk,
                 },
             ) in submit_info_vk.iter_mut().zip(per_submit_vk.iter_mut())
             {
@@ -1167,6 +1447,11 @@ impl<'a> QueueGuard<'a> {
                     ..*submit_info_vk
                 };
 
+                if let Some(protected_submit_info_vk) = protected_submit_info_vk {
+                    protected_submit_info_vk.p_next = submit_info_vk.p_next;
+                    submit_info_vk.p_next = <*const _>::cast(protected_submit_info_vk);
+                }
+
                 if let Some(timeline_semaphore_submit_info_vk) = timeline_semaphore_submit_info_vk {
                     *timeline_semaphore_submit_info_vk = ash::vk::TimelineSemaphoreSubmitInfo {
                         wait_semaphore_value_count: wait_semaphore_values_vk.len() as u32,
@@ -1179,6 +1464,27 @@ impl<'a> QueueGuard<'a> {
                     timeline_semaphore_submit_info_vk.p_next = submit_info_vk.p_next;
                     submit_info_vk.p_next = <*mut _>::cast(timeline_semaphore_submit_info_vk);
                 }
+
+                if let Some(frame_boundary_vk) = frame_boundary_vk {
+                    *frame_boundary_vk = ash::vk::FrameBoundaryEXT {
+                        image_count: frame_boundary_images_vk.len() as u32,
+                        p_images: frame_boundary_images_vk.as_ptr(),
+                        buffer_count: frame_boundary_buffers_vk.len() as u32,
+                        p_buffers: frame_boundary_buffers_vk.as_ptr(),
+                        ..*frame_boundary_vk
+                    };
+
+                    frame_boundary_vk.p_next = submit_info_vk.p_next;
+                    submit_info_vk.p_next = <*const _>::cast(frame_boundary_vk);
+                }
+
+                if let Some(win32_keyed_mutex_fields1_vk) = win32_keyed_mutex_fields1_vk {
+                    let next =
+                        win32_keyed_mutex_vk.insert(win32_keyed_mutex_fields1_vk.to_vulkan());
+
+                    next.p_next = submit_info_vk.p_next;
+                    submit_info_vk.p_next = <*const _>::cast(next);
+                }
             }
 
             let fns = self.queue.device.fns();
@@ -1364,6 +1670,372 @@ impl<'a> QueueGuard<'a> {
 #[derive(Debug, Default)]
 struct QueueState {}
 
+/// The most recent checkpoint that a queue reached in a pipeline stage.
+///
+/// See [`Queue::checkpoint_data`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CheckpointData {
+    /// The pipeline stage that was reached.
+    pub stage: PipelineStages,
+
+    /// The name that was given to the checkpoint with [`set_checkpoint`].
+    ///
+    /// This is `None` if the checkpoint was not set through vulkano.
+    ///
+    /// [`set_checkpoint`]: crate::command_buffer::RecordingCommandBuffer::set_checkpoint
+    pub name: Option<Arc<str>>,
+
+    /// The raw checkpoint marker, as it was passed to `vkCmdSetCheckpointNV`.
+    pub marker: usize,
+}
+
+/// Information about the frame that a queue operation belongs to.
+///
+/// Capture tools and profilers normally consider a frame to end when an image is presented to a
+/// swapchain. Applications that render offscreen, or that only do compute work, never present,
+/// so they can attach this to their submissions instead, to tell the tools where each frame
+/// begins and ends.
+///
+/// The [`ext_frame_boundary`] extension and the [`frame_boundary`] feature must be enabled on the
+/// device.
+///
+/// [`ext_frame_boundary`]: crate::device::DeviceExtensions::ext_frame_boundary
+/// [`frame_boundary`]: crate::device::DeviceFeatures::frame_boundary
+#[derive(Clone, Debug)]
+pub struct FrameBoundary {
+    /// Additional properties of the frame boundary.
+    ///
+    /// The default value is empty.
+    pub flags: FrameBoundaryFlags,
+
+    /// The identifier of the frame that the queue operation belongs to.
+    ///
+    /// All queue operations that belong to the same frame should use the same identifier, and
+    /// the identifier should increase with every new frame.
+    ///
+    /// The default value is `0`.
+    pub frame_id: u64,
+
+    /// The images that are considered to be the output of the frame.
+    ///
+    /// The default value is empty.
+    pub images: Vec<Arc<Image>>,
+
+    /// The buffers that are considered to be the output of the frame.
+    ///
+    /// The default value is empty.
+    pub buffers: Vec<Arc<Buffer>>,
+
+    /// An application-defined identifier for the kind of data in `tag`.
+    ///
+    /// The default value is `0`.
+    pub tag_name: u64,
+
+    /// Application-defined data that is passed along to the tools.
+    ///
+    /// The default value is empty.
+    pub tag: Vec<u8>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for FrameBoundary {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            flags: FrameBoundaryFlags::empty(),
+            frame_id: 0,
+            images: Vec::new(),
+            buffers: Vec::new(),
+            tag_name: 0,
+            tag: Vec::new(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+impl FrameBoundary {
+    pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
+        let &Self {
+            flags,
+            frame_id: _,
+            ref images,
+            ref buffers,
+            tag_name: _,
+            tag: _,
+            _ne: _,
+        } = self;
+
+        if !device.enabled_extensions().ext_frame_boundary {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "ext_frame_boundary",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        if !device.enabled_features().frame_boundary {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "frame_boundary",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        flags.validate_device(device).map_err(|err| {
+            err.add_context("flags")
+                .set_vuids(&["VUID-VkFrameBoundaryEXT-flags-parameter"])
+        })?;
+
+        for image in images {
+            // VUID-VkFrameBoundaryEXT-pImages-parameter
+            assert_eq!(device, image.device().as_ref());
+        }
+
+        for buffer in buffers {
+            // VUID-VkFrameBoundaryEXT-pBuffers-parameter
+            assert_eq!(device, buffer.device().as_ref());
+        }
+
+        Ok(())
+    }
+}
+
+vulkan_bitflags! {
+    #[non_exhaustive]
+
+    /// Flags specifying additional properties of a frame boundary.
+    FrameBoundaryFlags = FrameBoundaryFlagsEXT(u32);
+
+    /// The queue operation is the last one of the frame.
+    FRAME_END = FRAME_END,
+}
+
+/// Keyed mutexes to acquire before, and release after, a batch of command buffers is executed.
+///
+/// Direct3D synchronizes access to a shared texture with a keyed mutex. When a texture is
+/// imported into Vulkan, its keyed mutex must be acquired before Vulkan accesses it and released
+/// afterwards, so that Direct3D and Vulkan take turns using it.
+///
+/// The [`khr_win32_keyed_mutex`] extension must be enabled on the device.
+///
+/// [`khr_win32_keyed_mutex`]: crate::device::DeviceExtensions::khr_win32_keyed_mutex
+#[derive(Clone, Debug)]
+pub struct Win32KeyedMutexAcquireReleaseInfo {
+    /// The keyed mutexes to acquire before the command buffers are executed.
+    ///
+    /// The default value is empty.
+    pub acquire_syncs: Vec<Win32KeyedMutexAcquire>,
+
+    /// The keyed mutexes to release after the command buffers have been executed.
+    ///
+    /// The default value is empty.
+    pub release_syncs: Vec<Win32KeyedMutexRelease>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for Win32KeyedMutexAcquireReleaseInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            acquire_syncs: Vec::new(),
+            release_syncs: Vec::new(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+impl Win32KeyedMutexAcquireReleaseInfo {
+    pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
+        let &Self {
+            ref acquire_syncs,
+            ref release_syncs,
+            _ne: _,
+        } = self;
+
+        if !device.enabled_extensions().khr_win32_keyed_mutex {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "khr_win32_keyed_mutex",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        for (index, acquire_sync) in acquire_syncs.iter().enumerate() {
+            validate_keyed_mutex_memory(device, &acquire_sync.memory)
+                .map_err(|err| err.add_context(format!("acquire_syncs[{}].memory", index)))?;
+        }
+
+        for (index, release_sync) in release_syncs.iter().enumerate() {
+            validate_keyed_mutex_memory(device, &release_sync.memory)
+                .map_err(|err| err.add_context(format!("release_syncs[{}].memory", index)))?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn to_vulkan_fields1(&self) -> Win32KeyedMutexAcquireReleaseFields1Vk {
+        let &Self {
+            ref acquire_syncs,
+            ref release_syncs,
+            _ne: _,
+        } = self;
+
+        Win32KeyedMutexAcquireReleaseFields1Vk {
+            acquire_syncs_vk: acquire_syncs
+                .iter()
+                .map(|acquire_sync| acquire_sync.memory.handle())
+                .collect(),
+            acquire_keys_vk: acquire_syncs
+                .iter()
+                .map(|acquire_sync| acquire_sync.key)
+                .collect(),
+            acquire_timeouts_vk: acquire_syncs
+                .iter()
+                .map(|acquire_sync| acquire_sync.timeout)
+                .collect(),
+            release_syncs_vk: release_syncs
+                .iter()
+                .map(|release_sync| release_sync.memory.handle())
+                .collect(),
+            release_keys_vk: release_syncs
+                .iter()
+                .map(|release_sync| release_sync.key)
+                .collect(),
+        }
+    }
+}
+
+pub(crate) struct Win32KeyedMutexAcquireReleaseFields1Vk {
+    acquire_syncs_vk: SmallVec<[ash::vk::DeviceMemory; 4]>,
+    acquire_keys_vk: SmallVec<[u64; 4]>,
+    acquire_timeouts_vk: SmallVec<[u32; 4]>,
+    release_syncs_vk: SmallVec<[ash::vk::DeviceMemory; 4]>,
+    release_keys_vk: SmallVec<[u64; 4]>,
+}
+
+impl Win32KeyedMutexAcquireReleaseFields1Vk {
+    pub(crate) fn to_vulkan(&self) -> ash::vk::Win32KeyedMutexAcquireReleaseInfoKHR<'static> {
+        let Self {
+            acquire_syncs_vk,
+            acquire_keys_vk,
+            acquire_timeouts_vk,
+            release_syncs_vk,
+            release_keys_vk,
+        } = self;
+
+        ash::vk::Win32KeyedMutexAcquireReleaseInfoKHR {
+            acquire_count: acquire_syncs_vk.len() as u32,
+            p_acquire_syncs: acquire_syncs_vk.as_ptr(),
+            p_acquire_keys: acquire_keys_vk.as_ptr(),
+            p_acquire_timeouts: acquire_timeouts_vk.as_ptr(),
+            release_count: release_syncs_vk.len() as u32,
+            p_release_syncs: release_syncs_vk.as_ptr(),
+            p_release_keys: release_keys_vk.as_ptr(),
+            ..Default::default()
+        }
+    }
+}
+
+fn validate_keyed_mutex_memory(
+    device: &Device,
+    memory: &DeviceMemory,
+) -> Result<(), Box<ValidationError>> {
+    // VUID-VkWin32KeyedMutexAcquireReleaseInfoKHR-commonparent
+    assert_eq!(device, memory.device().as_ref());
+
+    if !matches!(
+        memory.imported_handle_type(),
+        Some(
+            ExternalMemoryHandleType::OpaqueWin32
+                | ExternalMemoryHandleType::OpaqueWin32Kmt
+                | ExternalMemoryHandleType::D3D11Texture
+                | ExternalMemoryHandleType::D3D11TextureKmt
+        )
+    ) {
+        return Err(Box::new(ValidationError {
+            problem: "was not imported from a Windows handle".into(),
+            ..Default::default()
+        }));
+    }
+
+    Ok(())
+}
+
+/// A keyed mutex to acquire before a batch of command buffers is executed.
+#[derive(Clone, Debug)]
+pub struct Win32KeyedMutexAcquire {
+    /// The memory whose keyed mutex is acquired.
+    ///
+    /// The memory must have been imported from a Windows handle.
+    ///
+    /// There is no default value.
+    pub memory: Arc<DeviceMemory>,
+
+    /// The key to acquire the keyed mutex with. The keyed mutex is acquired once it has been
+    /// released with the same key.
+    ///
+    /// The default value is `0`.
+    pub key: u64,
+
+    /// The time in milliseconds to wait for the keyed mutex to be released with `key`. If the
+    /// keyed mutex is not acquired within this time, the submission fails.
+    ///
+    /// The default value is `u32::MAX`, which waits forever.
+    pub timeout: u32,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Win32KeyedMutexAcquire {
+    /// Returns a `Win32KeyedMutexAcquire` with the specified `memory`.
+    #[inline]
+    pub fn new(memory: Arc<DeviceMemory>) -> Self {
+        Self {
+            memory,
+            key: 0,
+            timeout: u32::MAX,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// A keyed mutex to release after a batch of command buffers has been executed.
+#[derive(Clone, Debug)]
+pub struct Win32KeyedMutexRelease {
+    /// The memory whose keyed mutex is released.
+    ///
+    /// The memory must have been imported from a Windows handle.
+    ///
+    /// There is no default value.
+    pub memory: Arc<DeviceMemory>,
+
+    /// The key to release the keyed mutex with. The next acquire operation with the same key
+    /// can then proceed.
+    ///
+    /// The default value is `0`.
+    pub key: u64,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Win32KeyedMutexRelease {
+    /// Returns a `Win32KeyedMutexRelease` with the specified `memory`.
+    #[inline]
+    pub fn new(memory: Arc<DeviceMemory>) -> Self {
+        Self {
+            memory,
+            key: 0,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
 /// Properties of a queue family in a physical device.
 #[derive(Clone, Debug)]
 #[non_exhaustive]
@@ -1385,6 +2057,15 @@ pub struct QueueFamilyProperties {
     /// The minimum granularity supported for image transfers, in terms of `[width, height,
     /// depth]`.
     pub min_image_transfer_granularity: [u32; 3],
+
+    /// The global priorities that queues of this family can be created with.
+    ///
+    /// This is empty if neither the [`khr_global_priority`] nor the
+    /// [`ext_global_priority_query`] extension is supported by the physical device.
+    ///
+    /// [`khr_global_priority`]: crate::device::DeviceExtensions::khr_global_priority
+    /// [`ext_global_priority_query`]: crate::device::DeviceExtensions::ext_global_priority_query
+    pub global_priorities: Vec<QueueGlobalPriority>,
 }
 
 impl From<ash::vk::QueueFamilyProperties> for QueueFamilyProperties {
@@ -1400,10 +2081,33 @@ impl From<ash::vk::QueueFamilyProperties> for QueueFamilyProperties {
                 val.min_image_transfer_granularity.height,
                 val.min_image_transfer_granularity.depth,
             ],
+            global_priorities: Vec::new(),
         }
     }
 }
 
+vulkan_enum! {
+    #[non_exhaustive]
+
+    /// The priority of a queue relative to queues of other processes, and of the system itself.
+    QueueGlobalPriority = QueueGlobalPriorityKHR(i32);
+
+    /// Lower than the default priority. This is useful for background tasks.
+    Low = LOW,
+
+    /// The default priority of a queue, if no global priority is given.
+    Medium = MEDIUM,
+
+    /// Higher than the default priority.
+    High = HIGH,
+
+    /// The highest priority, which can preempt the work of all other priorities.
+    ///
+    /// This is usually reserved for system-critical work such as a compositor, so the
+    /// implementation may require the process to have special permissions.
+    Realtime = REALTIME,
+}
+
 vulkan_bitflags! {
     #[non_exhaustive]
 
@@ -0,0 +1,645 @@
+//! Describing what an application needs from a device in one place.
+//!
+//! Applications usually need some extensions, features and limits to work at all, and can make
+//! use of others if they are available. Keeping the [`DeviceExtensions`] and [`DeviceFeatures`]
+//! that are enabled in sync with the checks that choose a physical device, and with the checks
+//! that decide which code paths to take at runtime, is easy to get wrong.
+//!
+//! A [`DeviceRequirements`] lists all of these needs declaratively. [`resolve`] checks it
+//! against a physical device, and produces a [`DeviceCreateInfo`] to pass to [`Device::new`],
+//! together with a [`DeviceRequirementsReport`] of which optional items were granted. If the
+//! physical device doesn't meet the requirements, a [`DeviceRequirementsError`] lists everything
+//! that is missing, so it can also be used to filter physical devices.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::device::physical::PhysicalDevice;
+//! use vulkano::device::{
+//!     requirements::{DeviceLimit, DeviceRequirements, LimitRequirement, QueueRequirement},
+//!     Device, DeviceExtensions, DeviceFeatures, QueueFlags,
+//! };
+//!
+//! # let physical_device: Arc<PhysicalDevice> = todo!();
+//! let requirements = DeviceRequirements {
+//!     required_extensions: DeviceExtensions {
+//!         khr_swapchain: true,
+//!         ..DeviceExtensions::empty()
+//!     },
+//!     required_features: DeviceFeatures {
+//!         dynamic_rendering: true,
+//!         ..DeviceFeatures::empty()
+//!     },
+//!     optional_features: DeviceFeatures {
+//!         sampler_anisotropy: true,
+//!         ..DeviceFeatures::empty()
+//!     },
+//!     limits: vec![LimitRequirement {
+//!         limit: DeviceLimit::MaxPushConstantsSize,
+//!         min_value: 256,
+//!     }],
+//!     queues: vec![
+//!         QueueRequirement::new(QueueFlags::GRAPHICS),
+//!         QueueRequirement {
+//!             optional: true,
+//!             ..QueueRequirement::new(QueueFlags::TRANSFER)
+//!         },
+//!     ],
+//!     ..Default::default()
+//! };
+//!
+//! let (create_info, report) = requirements.resolve(&physical_device).unwrap();
+//! let (device, queues) = Device::new(physical_device, create_info).unwrap();
+//!
+//! if report.granted_features.sampler_anisotropy {
+//!     // Create samplers with anisotropic filtering.
+//! }
+//! ```
+//!
+//! [`DeviceFeatures`] contains the features of all Vulkan versions and extensions, so features
+//! that Vulkan exposes through newer feature structs, such as [`dynamic_rendering`], are
+//! requested the same way as the features of Vulkan 1.0.
+//!
+//! # Serialization
+//!
+//! With the `serde` feature of vulkano enabled, `DeviceRequirements` can be serialized and
+//! deserialized, so that it can be kept in a configuration file. Extensions and features are
+//! written as lists of their Vulkan names, such as `"VK_KHR_swapchain"` and
+//! `"dynamicRendering"`. Deserializing a name that vulkano doesn't know about is an error.
+//!
+//! [`resolve`]: DeviceRequirements::resolve
+//! [`Device::new`]: super::Device::new
+//! [`dynamic_rendering`]: DeviceFeatures::dynamic_rendering
+
+use super::{
+    physical::PhysicalDevice, DeviceCreateInfo, DeviceExtensions, DeviceFeatures, DeviceProperties,
+    QueueCreateInfo, QueueFlags,
+};
+use std::{
+    error::Error,
+    fmt::{Display, Error as FmtError, Formatter},
+};
+
+/// The extensions, features, limits and queues that an application needs from a device.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DeviceRequirements {
+    /// The extensions that the device must support. They are always enabled.
+    ///
+    /// The default value is [`DeviceExtensions::empty()`].
+    #[cfg_attr(feature = "serde", serde(with = "names"))]
+    pub required_extensions: DeviceExtensions,
+
+    /// The extensions that are enabled if the device supports them.
+    ///
+    /// The default value is [`DeviceExtensions::empty()`].
+    #[cfg_attr(feature = "serde", serde(with = "names"))]
+    pub optional_extensions: DeviceExtensions,
+
+    /// The features that the device must support. They are always enabled.
+    ///
+    /// The default value is [`DeviceFeatures::empty()`].
+    #[cfg_attr(feature = "serde", serde(with = "names"))]
+    pub required_features: DeviceFeatures,
+
+    /// The features that are enabled if the device supports them.
+    ///
+    /// The default value is [`DeviceFeatures::empty()`].
+    #[cfg_attr(feature = "serde", serde(with = "names"))]
+    pub optional_features: DeviceFeatures,
+
+    /// The minimum values of device limits that the device must meet.
+    ///
+    /// The default value is empty.
+    pub limits: Vec<LimitRequirement>,
+
+    /// The queues that the application needs.
+    ///
+    /// At least one queue must be granted for the resulting create info to be valid.
+    ///
+    /// The default value is empty.
+    pub queues: Vec<QueueRequirement>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for DeviceRequirements {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            required_extensions: DeviceExtensions::empty(),
+            optional_extensions: DeviceExtensions::empty(),
+            required_features: DeviceFeatures::empty(),
+            optional_features: DeviceFeatures::empty(),
+            limits: Vec::new(),
+            queues: Vec::new(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+impl DeviceRequirements {
+    /// Checks the requirements against `physical_device`, and returns a create info that enables
+    /// everything that was granted, together with a report of the optional items.
+    ///
+    /// An extension is only considered supported if the physical device supports it, and the
+    /// API version and the instance extensions that it depends on are available as well.
+    ///
+    /// Each queue requirement is assigned to the queue family with the fewest capabilities that
+    /// has all the requested flags and enough queues left over, so that for example a transfer
+    /// queue is taken from a dedicated transfer family if there is one. The queues are created
+    /// with a priority of 0.5. [`Device::new`] returns the queues in the order of the
+    /// [`queue_create_infos`], so use the [`queues`] of the report, together with
+    /// [`Queue::queue_family_index`] and [`Queue::queue_index`], to find the queues of each
+    /// requirement.
+    ///
+    /// The other members of the returned create info have their default values, and can be
+    /// changed before passing it to [`Device::new`].
+    ///
+    /// [`Device::new`]: super::Device::new
+    /// [`queue_create_infos`]: DeviceCreateInfo::queue_create_infos
+    /// [`queues`]: DeviceRequirementsReport::queues
+    /// [`Queue::queue_family_index`]: super::Queue::queue_family_index
+    /// [`Queue::queue_index`]: super::Queue::queue_index
+    pub fn resolve(
+        &self,
+        physical_device: &PhysicalDevice,
+    ) -> Result<(DeviceCreateInfo, DeviceRequirementsReport), DeviceRequirementsError> {
+        let &Self {
+            ref required_extensions,
+            ref optional_extensions,
+            ref required_features,
+            ref optional_features,
+            ref limits,
+            ref queues,
+            _ne: _,
+        } = self;
+
+        let missing_extensions = unsupported_extensions(physical_device, required_extensions);
+        let missing_features = required_features.difference(physical_device.supported_features());
+
+        let properties = physical_device.properties();
+        let unmet_limits: Vec<_> = limits
+            .iter()
+            .filter_map(|&requirement| {
+                let value = requirement.limit.value(properties);
+
+                (value.map_or(true, |value| value < requirement.min_value))
+                    .then_some((requirement, value))
+            })
+            .collect();
+
+        let queue_family_properties = physical_device.queue_family_properties();
+        let mut used_queue_counts = vec![0; queue_family_properties.len()];
+        let mut queue_assignments = Vec::with_capacity(queues.len());
+        let mut unmet_queues = Vec::new();
+
+        for (index, requirement) in queues.iter().enumerate() {
+            let queue_family_index = queue_family_properties
+                .iter()
+                .zip(&used_queue_counts)
+                .enumerate()
+                .filter(|&(_, (properties, &used_count))| {
+                    properties.queue_flags.contains(requirement.flags)
+                        && properties.queue_count - used_count >= requirement.count
+                })
+                .min_by_key(|&(_, (properties, _))| properties.queue_flags.count())
+                .map(|(queue_family_index, _)| queue_family_index);
+
+            match queue_family_index {
+                Some(queue_family_index) => {
+                    let first_queue_index = used_queue_counts[queue_family_index];
+                    used_queue_counts[queue_family_index] += requirement.count;
+
+                    queue_assignments.push(Some(QueueAssignment {
+                        queue_family_index: queue_family_index as u32,
+                        first_queue_index,
+                        count: requirement.count,
+                    }));
+                }
+                None => {
+                    if !requirement.optional {
+                        unmet_queues.push(index);
+                    }
+
+                    queue_assignments.push(None);
+                }
+            }
+        }
+
+        if !missing_extensions.is_empty()
+            || missing_features != DeviceFeatures::empty()
+            || !unmet_limits.is_empty()
+            || !unmet_queues.is_empty()
+        {
+            return Err(DeviceRequirementsError {
+                missing_extensions,
+                missing_features,
+                unmet_limits,
+                unmet_queues,
+            });
+        }
+
+        let optional_extensions = optional_extensions.difference(required_extensions);
+        let denied_extensions = unsupported_extensions(physical_device, &optional_extensions);
+        let granted_extensions = optional_extensions.difference(&denied_extensions);
+
+        let optional_features = optional_features.difference(required_features);
+        let granted_features = optional_features.intersection(physical_device.supported_features());
+        let denied_features = optional_features.difference(&granted_features);
+
+        let queue_create_infos = used_queue_counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &used_count)| used_count != 0)
+            .map(|(queue_family_index, &used_count)| QueueCreateInfo {
+                queue_family_index: queue_family_index as u32,
+                queues: vec![0.5; used_count as usize],
+                ..Default::default()
+            })
+            .collect();
+
+        let create_info = DeviceCreateInfo {
+            queue_create_infos,
+            enabled_extensions: required_extensions.union(&granted_extensions),
+            enabled_features: required_features.union(&granted_features),
+            ..Default::default()
+        };
+        let report = DeviceRequirementsReport {
+            granted_extensions,
+            denied_extensions,
+            granted_features,
+            denied_features,
+            queues: queue_assignments,
+        };
+
+        Ok((create_info, report))
+    }
+}
+
+// Returns the extensions in `extensions` that can't be enabled on `physical_device`.
+fn unsupported_extensions(
+    physical_device: &PhysicalDevice,
+    extensions: &DeviceExtensions,
+) -> DeviceExtensions {
+    extensions
+        .into_iter()
+        .filter(|&(name, enabled)| {
+            enabled
+                && DeviceExtensions::from_iter([name])
+                    .check_requirements(
+                        physical_device.supported_extensions(),
+                        physical_device.api_version(),
+                        physical_device.instance().enabled_extensions(),
+                    )
+                    .is_err()
+        })
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// A minimum value that a device limit must have.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LimitRequirement {
+    /// The limit to check.
+    pub limit: DeviceLimit,
+
+    /// The minimum value that the limit must have.
+    pub min_value: u64,
+}
+
+/// A device limit that can be required by a [`LimitRequirement`].
+///
+/// Only limits for which a higher value is always better are included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DeviceLimit {
+    /// [`DeviceProperties::max_image_dimension1_d`].
+    MaxImageDimension1D,
+    /// [`DeviceProperties::max_image_dimension2_d`].
+    MaxImageDimension2D,
+    /// [`DeviceProperties::max_image_dimension3_d`].
+    MaxImageDimension3D,
+    /// [`DeviceProperties::max_image_dimension_cube`].
+    MaxImageDimensionCube,
+    /// [`DeviceProperties::max_image_array_layers`].
+    MaxImageArrayLayers,
+    /// [`DeviceProperties::max_texel_buffer_elements`].
+    MaxTexelBufferElements,
+    /// [`DeviceProperties::max_uniform_buffer_range`].
+    MaxUniformBufferRange,
+    /// [`DeviceProperties::max_storage_buffer_range`].
+    MaxStorageBufferRange,
+    /// [`DeviceProperties::max_push_constants_size`].
+    MaxPushConstantsSize,
+    /// [`DeviceProperties::max_memory_allocation_count`].
+    MaxMemoryAllocationCount,
+    /// [`DeviceProperties::max_memory_allocation_size`].
+    MaxMemoryAllocationSize,
+    /// [`DeviceProperties::max_sampler_allocation_count`].
+    MaxSamplerAllocationCount,
+    /// [`DeviceProperties::max_bound_descriptor_sets`].
+    MaxBoundDescriptorSets,
+    /// [`DeviceProperties::max_per_stage_descriptor_samplers`].
+    MaxPerStageDescriptorSamplers,
+    /// [`DeviceProperties::max_per_stage_descriptor_uniform_buffers`].
+    MaxPerStageDescriptorUniformBuffers,
+    /// [`DeviceProperties::max_per_stage_descriptor_storage_buffers`].
+    MaxPerStageDescriptorStorageBuffers,
+    /// [`DeviceProperties::max_per_stage_descriptor_sampled_images`].
+    MaxPerStageDescriptorSampledImages,
+    /// [`DeviceProperties::max_per_stage_descriptor_storage_images`].
+    MaxPerStageDescriptorStorageImages,
+    /// [`DeviceProperties::max_per_stage_resources`].
+    MaxPerStageResources,
+    /// [`DeviceProperties::max_per_set_descriptors`].
+    MaxPerSetDescriptors,
+    /// [`DeviceProperties::max_vertex_input_attributes`].
+    MaxVertexInputAttributes,
+    /// [`DeviceProperties::max_vertex_input_bindings`].
+    MaxVertexInputBindings,
+    /// [`DeviceProperties::max_compute_shared_memory_size`].
+    MaxComputeSharedMemorySize,
+    /// [`DeviceProperties::max_compute_work_group_invocations`].
+    MaxComputeWorkGroupInvocations,
+    /// [`DeviceProperties::max_color_attachments`].
+    MaxColorAttachments,
+    /// [`DeviceProperties::max_framebuffer_width`].
+    MaxFramebufferWidth,
+    /// [`DeviceProperties::max_framebuffer_height`].
+    MaxFramebufferHeight,
+    /// [`DeviceProperties::max_framebuffer_layers`].
+    MaxFramebufferLayers,
+    /// [`DeviceProperties::max_viewports`].
+    MaxViewports,
+    /// [`DeviceProperties::max_draw_indirect_count`].
+    MaxDrawIndirectCount,
+}
+
+impl DeviceLimit {
+    /// Returns the value of the limit in `properties`, or `None` if the physical device doesn't
+    /// report it.
+    pub fn value(self, properties: &DeviceProperties) -> Option<u64> {
+        let value = match self {
+            Self::MaxImageDimension1D => properties.max_image_dimension1_d,
+            Self::MaxImageDimension2D => properties.max_image_dimension2_d,
+            Self::MaxImageDimension3D => properties.max_image_dimension3_d,
+            Self::MaxImageDimensionCube => properties.max_image_dimension_cube,
+            Self::MaxImageArrayLayers => properties.max_image_array_layers,
+            Self::MaxTexelBufferElements => properties.max_texel_buffer_elements,
+            Self::MaxUniformBufferRange => properties.max_uniform_buffer_range,
+            Self::MaxStorageBufferRange => properties.max_storage_buffer_range,
+            Self::MaxPushConstantsSize => properties.max_push_constants_size,
+            Self::MaxMemoryAllocationCount => properties.max_memory_allocation_count,
+            Self::MaxMemoryAllocationSize => return properties.max_memory_allocation_size,
+            Self::MaxSamplerAllocationCount => properties.max_sampler_allocation_count,
+            Self::MaxBoundDescriptorSets => properties.max_bound_descriptor_sets,
+            Self::MaxPerStageDescriptorSamplers => properties.max_per_stage_descriptor_samplers,
+            Self::MaxPerStageDescriptorUniformBuffers => {
+                properties.max_per_stage_descriptor_uniform_buffers
+            }
+            Self::MaxPerStageDescriptorStorageBuffers => {
+                properties.max_per_stage_descriptor_storage_buffers
+            }
+            Self::MaxPerStageDescriptorSampledImages => {
+                properties.max_per_stage_descriptor_sampled_images
+            }
+            Self::MaxPerStageDescriptorStorageImages => {
+                properties.max_per_stage_descriptor_storage_images
+            }
+            Self::MaxPerStageResources => properties.max_per_stage_resources,
+            Self::MaxPerSetDescriptors => return properties.max_per_set_descriptors.map(u64::from),
+            Self::MaxVertexInputAttributes => properties.max_vertex_input_attributes,
+            Self::MaxVertexInputBindings => properties.max_vertex_input_bindings,
+            Self::MaxComputeSharedMemorySize => properties.max_compute_shared_memory_size,
+            Self::MaxComputeWorkGroupInvocations => properties.max_compute_work_group_invocations,
+            Self::MaxColorAttachments => properties.max_color_attachments,
+            Self::MaxFramebufferWidth => properties.max_framebuffer_width,
+            Self::MaxFramebufferHeight => properties.max_framebuffer_height,
+            Self::MaxFramebufferLayers => properties.max_framebuffer_layers,
+            Self::MaxViewports => properties.max_viewports,
+            Self::MaxDrawIndirectCount => properties.max_draw_indirect_count,
+        };
+
+        Some(u64::from(value))
+    }
+}
+
+/// Queues that an application needs from a device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueueRequirement {
+    /// The capabilities that the queue family of the queues must have.
+    ///
+    /// There is no default value.
+    pub flags: QueueFlags,
+
+    /// The number of queues. They are all taken from the same queue family.
+    ///
+    /// The default value is `1`.
+    pub count: u32,
+
+    /// Whether the device is still suitable if it can't provide the queues.
+    ///
+    /// The default value is `false`.
+    pub optional: bool,
+}
+
+impl QueueRequirement {
+    /// Returns a `QueueRequirement` for one queue with the specified `flags`.
+    #[inline]
+    pub const fn new(flags: QueueFlags) -> Self {
+        Self {
+            flags,
+            count: 1,
+            optional: false,
+        }
+    }
+}
+
+/// The queues that were assigned to a [`QueueRequirement`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct QueueAssignment {
+    /// The index of the queue family that the queues belong to.
+    pub queue_family_index: u32,
+
+    /// The index of the first queue within the queue family.
+    pub first_queue_index: u32,
+
+    /// The number of queues.
+    pub count: u32,
+}
+
+/// The optional items of a [`DeviceRequirements`] that were granted or denied by
+/// [`DeviceRequirements::resolve`].
+#[derive(Clone, Debug)]
+pub struct DeviceRequirementsReport {
+    /// The optional extensions that are supported, and are enabled in the create info.
+    pub granted_extensions: DeviceExtensions,
+
+    /// The optional extensions that are not supported.
+    pub denied_extensions: DeviceExtensions,
+
+    /// The optional features that are supported, and are enabled in the create info.
+    pub granted_features: DeviceFeatures,
+
+    /// The optional features that are not supported.
+    pub denied_features: DeviceFeatures,
+
+    /// The queues that were assigned to each element of [`DeviceRequirements::queues`], in the
+    /// same order, or `None` for optional queues that could not be provided.
+    pub queues: Vec<Option<QueueAssignment>>,
+}
+
+/// Error that is returned when a physical device doesn't meet the required items of a
+/// [`DeviceRequirements`].
+#[derive(Clone, Debug)]
+pub struct DeviceRequirementsError {
+    /// The required extensions that are not supported.
+    pub missing_extensions: DeviceExtensions,
+
+    /// The required features that are not supported.
+    pub missing_features: DeviceFeatures,
+
+    /// The limit requirements that are not met, together with the value of the limit on the
+    /// physical device, if it reports it.
+    pub unmet_limits: Vec<(LimitRequirement, Option<u64>)>,
+
+    /// The indices of the elements of [`DeviceRequirements::queues`] that could not be provided.
+    pub unmet_queues: Vec<usize>,
+}
+
+impl Error for DeviceRequirementsError {}
+
+impl Display for DeviceRequirementsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        let mut problems = Vec::new();
+
+        if !self.missing_extensions.is_empty() {
+            problems.push(format!(
+                "the extensions {:?} are not supported",
+                self.missing_extensions,
+            ));
+        }
+
+        if self.missing_features != DeviceFeatures::empty() {
+            problems.push(format!(
+                "the features {:?} are not supported",
+                self.missing_features,
+            ));
+        }
+
+        for &(requirement, value) in &self.unmet_limits {
+            problems.push(match value {
+                Some(value) => format!(
+                    "the limit {:?} is {}, but at least {} is required",
+                    requirement.limit, value, requirement.min_value,
+                ),
+                None => format!(
+                    "the limit {:?} is not reported, but at least {} is required",
+                    requirement.limit, requirement.min_value,
+                ),
+            });
+        }
+
+        for &index in &self.unmet_queues {
+            problems.push(format!("no queue family can provide `queues[{}]`", index));
+        }
+
+        write!(
+            f,
+            "the physical device does not meet the requirements: {}",
+            problems.join("; "),
+        )
+    }
+}
+
+// Serializes extensions and features as lists of their Vulkan names.
+#[cfg(feature = "serde")]
+mod names {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + IntoIterator<Item = (&'static str, bool)>,
+        S: Serializer,
+    {
+        serializer.collect_seq(
+            value
+                .into_iter()
+                .filter_map(|(name, enabled)| enabled.then_some(name)),
+        )
+    }
+
+    pub(super) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Default + PartialEq + for<'a> FromIterator<&'a str>,
+        D: Deserializer<'de>,
+    {
+        let names = Vec::<String>::deserialize(deserializer)?;
+
+        if let Some(name) = names
+            .iter()
+            .find(|name| T::from_iter([name.as_str()]) == T::default())
+        {
+            return Err(D::Error::custom(format_args!("unknown name `{}`", name)));
+        }
+
+        Ok(names.iter().map(String::as_str).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeviceLimit, DeviceRequirements, LimitRequirement, QueueRequirement};
+    use crate::device::{DeviceFeatures, QueueFlags};
+
+    #[test]
+    fn resolve() {
+        let instance = instance!();
+        let physical_device = match instance.enumerate_physical_devices().unwrap().next() {
+            Some(p) => p,
+            None => return,
+        };
+
+        let requirements = DeviceRequirements {
+            optional_features: *physical_device.supported_features(),
+            queues: vec![
+                QueueRequirement::new(QueueFlags::empty()),
+                QueueRequirement {
+                    count: u32::MAX,
+                    optional: true,
+                    ..QueueRequirement::new(QueueFlags::empty())
+                },
+            ],
+            ..Default::default()
+        };
+        let (create_info, report) = requirements.resolve(&physical_device).unwrap();
+        assert_eq!(
+            create_info.enabled_features,
+            *physical_device.supported_features(),
+        );
+        assert_eq!(report.denied_features, DeviceFeatures::empty());
+        assert!(report.queues[0].is_some());
+        assert!(report.queues[1].is_none());
+        assert_eq!(create_info.queue_create_infos.len(), 1);
+
+        let requirements = DeviceRequirements {
+            limits: vec![LimitRequirement {
+                limit: DeviceLimit::MaxImageDimension2D,
+                min_value: u64::MAX,
+            }],
+            queues: vec![QueueRequirement {
+                count: u32::MAX,
+                ..QueueRequirement::new(QueueFlags::empty())
+            }],
+            ..Default::default()
+        };
+        let err = requirements.resolve(&physical_device).unwrap_err();
+        assert_eq!(err.unmet_limits.len(), 1);
+        assert_eq!(err.unmet_queues, [0]);
+    }
+}
@@ -0,0 +1,452 @@
+//! Tracking of the buffers, images and device memory that are alive on a device, for
+//! [`Device::dump_resources`].
+//!
+//! The registry is only created if [`DeviceCreateInfo::track_resources`] is enabled. It only holds
+//! plain descriptions of the objects, keyed on their handles, so that it doesn't keep any objects
+//! alive. Objects add themselves when they are created and remove themselves when they are
+//! dropped. The descriptions are only formatted when a snapshot is made.
+//!
+//! [`Device::dump_resources`]: super::Device::dump_resources
+//! [`DeviceCreateInfo::track_resources`]: super::DeviceCreateInfo::track_resources
+
+use super::Device;
+use crate::{
+    buffer::{sys::RawBuffer, BufferCreateFlags, BufferMemory, BufferUsage},
+    format::Format,
+    image::{
+        sys::RawImage, ImageCreateFlags, ImageMemory, ImageTiling, ImageType, ImageUsage,
+        SampleCount,
+    },
+    memory::{DeviceMemory, ResourceMemory},
+    DeviceSize, VulkanObject,
+};
+use ahash::HashMap;
+use ash::vk::Handle;
+use parking_lot::Mutex;
+use std::fmt::{Debug, Write};
+
+#[derive(Debug, Default)]
+pub(crate) struct ResourceRegistry {
+    inner: Mutex<ResourceRegistryInner>,
+}
+
+#[derive(Debug, Default)]
+struct ResourceRegistryInner {
+    buffers: HashMap<u64, BufferRecord>,
+    images: HashMap<u64, ImageRecord>,
+    device_memories: HashMap<u64, DeviceMemoryRecord>,
+}
+
+#[derive(Debug)]
+struct BufferRecord {
+    name: Option<String>,
+    size: DeviceSize,
+    flags: BufferCreateFlags,
+    usage: BufferUsage,
+    memory: MemoryRecord,
+}
+
+#[derive(Debug)]
+struct ImageRecord {
+    name: Option<String>,
+    flags: ImageCreateFlags,
+    image_type: ImageType,
+    format: Format,
+    extent: [u32; 3],
+    array_layers: u32,
+    mip_levels: u32,
+    samples: SampleCount,
+    tiling: ImageTiling,
+    usage: ImageUsage,
+    memory: MemoryRecord,
+}
+
+#[derive(Debug)]
+struct DeviceMemoryRecord {
+    name: Option<String>,
+    allocation_size: DeviceSize,
+    memory_type_index: u32,
+    dedicated: bool,
+}
+
+#[derive(Debug)]
+enum MemoryRecord {
+    Unbound,
+    Normal(Vec<MemoryBinding>),
+    Sparse,
+    Swapchain { image_index: u32 },
+    External,
+}
+
+#[derive(Debug)]
+struct MemoryBinding {
+    device_memory: u64,
+    offset: DeviceSize,
+    size: DeviceSize,
+}
+
+impl MemoryBinding {
+    fn new(memory: &ResourceMemory) -> Self {
+        MemoryBinding {
+            device_memory: memory.device_memory().handle().as_raw(),
+            offset: memory.offset(),
+            size: memory.size(),
+        }
+    }
+}
+
+impl ResourceRegistry {
+    pub(crate) fn add_buffer(&self, buffer: &RawBuffer) {
+        let record = BufferRecord {
+            name: None,
+            size: buffer.size(),
+            flags: buffer.flags(),
+            usage: buffer.usage(),
+            memory: MemoryRecord::Unbound,
+        };
+
+        self.inner
+            .lock()
+            .buffers
+            .insert(buffer.handle().as_raw(), record);
+    }
+
+    pub(crate) fn bind_buffer_memory(&self, buffer: &RawBuffer, memory: &BufferMemory) {
+        let memory = match memory {
+            BufferMemory::Normal(memory) => MemoryRecord::Normal(vec![MemoryBinding::new(memory)]),
+            BufferMemory::Sparse => MemoryRecord::Sparse,
+            BufferMemory::External => MemoryRecord::External,
+        };
+
+        if let Some(record) = self.inner.lock().buffers.get_mut(&buffer.handle().as_raw()) {
+            record.memory = memory;
+        }
+    }
+
+    pub(crate) fn remove_buffer(&self, buffer: &RawBuffer) {
+        self.inner.lock().buffers.remove(&buffer.handle().as_raw());
+    }
+
+    pub(crate) fn add_image(&self, image: &RawImage) {
+        let record = ImageRecord {
+            name: None,
+            flags: image.flags(),
+            image_type: image.image_type(),
+            format: image.format(),
+            extent: image.extent(),
+            array_layers: image.array_layers(),
+            mip_levels: image.mip_levels(),
+            samples: image.samples(),
+            tiling: image.tiling(),
+            usage: image.usage(),
+            memory: MemoryRecord::Unbound,
+        };
+
+        self.inner
+            .lock()
+            .images
+            .insert(image.handle().as_raw(), record);
+    }
+
+    pub(crate) fn bind_image_memory(&self, image: &RawImage, memory: &ImageMemory) {
+        let memory = match memory {
+            ImageMemory::Normal(memories) => {
+                MemoryRecord::Normal(memories.iter().map(MemoryBinding::new).collect())
+            }
+            ImageMemory::Sparse(_) => MemoryRecord::Sparse,
+            &ImageMemory::Swapchain { image_index, .. } => MemoryRecord::Swapchain { image_index },
+            ImageMemory::External => MemoryRecord::External,
+        };
+
+        if let Some(record) = self.inner.lock().images.get_mut(&image.handle().as_raw()) {
+            record.memory = memory;
+        }
+    }
+
+    pub(crate) fn remove_image(&self, image: &RawImage) {
+        self.inner.lock().images.remove(&image.handle().as_raw());
+    }
+
+    pub(crate) fn add_device_memory(&self, memory: &DeviceMemory) {
+        let record = DeviceMemoryRecord {
+            name: None,
+            allocation_size: memory.allocation_size(),
+            memory_type_index: memory.memory_type_index(),
+            dedicated: memory.is_dedicated(),
+        };
+
+        self.inner
+            .lock()
+            .device_memories
+            .insert(memory.handle().as_raw(), record);
+    }
+
+    pub(crate) fn remove_device_memory(&self, memory: &DeviceMemory) {
+        self.inner
+            .lock()
+            .device_memories
+            .remove(&memory.handle().as_raw());
+    }
+
+    /// Records the debug utils object name of an object, if it is a tracked object.
+    pub(crate) fn set_name(
+        &self,
+        object_type: ash::vk::ObjectType,
+        handle: u64,
+        name: Option<&str>,
+    ) {
+        let mut inner = self.inner.lock();
+        let name_slot = match object_type {
+            ash::vk::ObjectType::BUFFER => inner.buffers.get_mut(&handle).map(|r| &mut r.name),
+            ash::vk::ObjectType::IMAGE => inner.images.get_mut(&handle).map(|r| &mut r.name),
+            ash::vk::ObjectType::DEVICE_MEMORY => {
+                inner.device_memories.get_mut(&handle).map(|r| &mut r.name)
+            }
+            _ => None,
+        };
+
+        if let Some(name_slot) = name_slot {
+            *name_slot = name.map(ToOwned::to_owned);
+        }
+    }
+
+    /// Writes a JSON snapshot of the tracked objects of `device`.
+    pub(crate) fn dump(&self, device: &Device) -> String {
+        let inner = self.inner.lock();
+        let mut out = String::new();
+
+        let properties = device.physical_device().properties();
+        let memory_properties = device.physical_device().memory_properties();
+
+        out.push_str("{\"device\":{\"name\":");
+        write_string(&mut out, &properties.device_name);
+        write!(
+            out,
+            ",\"api_version\":\"{}\",\"vendor_id\":{},\"device_id\":{},\"driver_version\":{}}}",
+            device.api_version(),
+            properties.vendor_id,
+            properties.device_id,
+            properties.driver_version,
+        )
+        .unwrap();
+
+        out.push_str(",\"memory_heaps\":[");
+        for (index, heap) in memory_properties.memory_heaps.iter().enumerate() {
+            if index != 0 {
+                out.push(',');
+            }
+
+            write!(
+                out,
+                "{{\"index\":{},\"size\":{},\"flags\":",
+                index, heap.size
+            )
+            .unwrap();
+            write_debug(&mut out, &heap.flags);
+            out.push('}');
+        }
+
+        out.push_str("],\"memory_types\":[");
+        for (index, memory_type) in memory_properties.memory_types.iter().enumerate() {
+            if index != 0 {
+                out.push(',');
+            }
+
+            write!(
+                out,
+                "{{\"index\":{},\"heap_index\":{},\"property_flags\":",
+                index, memory_type.heap_index,
+            )
+            .unwrap();
+            write_debug(&mut out, &memory_type.property_flags);
+            out.push('}');
+        }
+
+        // The bindings of each block of device memory, sorted by offset, which shows how the
+        // allocator has laid out the block.
+        let mut memory_bindings: HashMap<u64, Vec<(&str, u64, &MemoryBinding)>> =
+            HashMap::default();
+
+        for (&handle, record) in &inner.buffers {
+            if let MemoryRecord::Normal(bindings) = &record.memory {
+                for binding in bindings {
+                    memory_bindings
+                        .entry(binding.device_memory)
+                        .or_default()
+                        .push(("buffer", handle, binding));
+                }
+            }
+        }
+
+        for (&handle, record) in &inner.images {
+            if let MemoryRecord::Normal(bindings) = &record.memory {
+                for binding in bindings {
+                    memory_bindings
+                        .entry(binding.device_memory)
+                        .or_default()
+                        .push(("image", handle, binding));
+                }
+            }
+        }
+
+        out.push_str("],\"device_memory\":[");
+        for (index, (handle, record)) in sorted(&inner.device_memories).into_iter().enumerate() {
+            if index != 0 {
+                out.push(',');
+            }
+
+            let mut bindings = memory_bindings.remove(&handle).unwrap_or_default();
+            bindings.sort_by_key(|&(_, _, binding)| binding.offset);
+            let used_size: DeviceSize = bindings.iter().map(|&(_, _, binding)| binding.size).sum();
+
+            write!(out, "{{\"handle\":\"{:#x}\",\"name\":", handle).unwrap();
+            write_name(&mut out, &record.name);
+            write!(
+                out,
+                ",\"allocation_size\":{},\"used_size\":{},\"memory_type_index\":{},\
+                \"dedicated\":{},\"bindings\":[",
+                record.allocation_size, used_size, record.memory_type_index, record.dedicated,
+            )
+            .unwrap();
+
+            for (index, (object_type, object_handle, binding)) in bindings.into_iter().enumerate() {
+                if index != 0 {
+                    out.push(',');
+                }
+
+                write!(
+                    out,
+                    "{{\"object_type\":\"{}\",\"handle\":\"{:#x}\",\"offset\":{},\"size\":{}}}",
+                    object_type, object_handle, binding.offset, binding.size,
+                )
+                .unwrap();
+            }
+
+            out.push_str("]}");
+        }
+
+        out.push_str("],\"buffers\":[");
+        for (index, (handle, record)) in sorted(&inner.buffers).into_iter().enumerate() {
+            if index != 0 {
+                out.push(',');
+            }
+
+            write!(out, "{{\"handle\":\"{:#x}\",\"name\":", handle).unwrap();
+            write_name(&mut out, &record.name);
+            write!(out, ",\"size\":{},\"flags\":", record.size).unwrap();
+            write_debug(&mut out, &record.flags);
+            out.push_str(",\"usage\":");
+            write_debug(&mut out, &record.usage);
+            out.push_str(",\"memory\":");
+            write_memory(&mut out, &record.memory);
+            out.push('}');
+        }
+
+        out.push_str("],\"images\":[");
+        for (index, (handle, record)) in sorted(&inner.images).into_iter().enumerate() {
+            if index != 0 {
+                out.push(',');
+            }
+
+            write!(out, "{{\"handle\":\"{:#x}\",\"name\":", handle).unwrap();
+            write_name(&mut out, &record.name);
+            out.push_str(",\"flags\":");
+            write_debug(&mut out, &record.flags);
+            out.push_str(",\"image_type\":");
+            write_debug(&mut out, &record.image_type);
+            out.push_str(",\"format\":");
+            write_debug(&mut out, &record.format);
+            write!(
+                out,
+                ",\"extent\":[{},{},{}],\"array_layers\":{},\"mip_levels\":{},\"samples\":{}",
+                record.extent[0],
+                record.extent[1],
+                record.extent[2],
+                record.array_layers,
+                record.mip_levels,
+                u32::from(record.samples),
+            )
+            .unwrap();
+            out.push_str(",\"tiling\":");
+            write_debug(&mut out, &record.tiling);
+            out.push_str(",\"usage\":");
+            write_debug(&mut out, &record.usage);
+            out.push_str(",\"memory\":");
+            write_memory(&mut out, &record.memory);
+            out.push('}');
+        }
+
+        out.push_str("]}");
+
+        out
+    }
+}
+
+// Returns the entries of `map` sorted by handle, so that the output is stable.
+fn sorted<T>(map: &HashMap<u64, T>) -> Vec<(u64, &T)> {
+    let mut entries: Vec<_> = map.iter().map(|(&handle, value)| (handle, value)).collect();
+    entries.sort_unstable_by_key(|&(handle, _)| handle);
+
+    entries
+}
+
+fn write_memory(out: &mut String, memory: &MemoryRecord) {
+    match memory {
+        MemoryRecord::Unbound => out.push_str("{\"kind\":\"unbound\"}"),
+        MemoryRecord::Normal(bindings) => {
+            out.push_str("{\"kind\":\"normal\",\"bindings\":[");
+
+            for (index, binding) in bindings.iter().enumerate() {
+                if index != 0 {
+                    out.push(',');
+                }
+
+                write!(
+                    out,
+                    "{{\"device_memory\":\"{:#x}\",\"offset\":{},\"size\":{}}}",
+                    binding.device_memory, binding.offset, binding.size,
+                )
+                .unwrap();
+            }
+
+            out.push_str("]}");
+        }
+        MemoryRecord::Sparse => out.push_str("{\"kind\":\"sparse\"}"),
+        MemoryRecord::Swapchain { image_index } => write!(
+            out,
+            "{{\"kind\":\"swapchain\",\"image_index\":{}}}",
+            image_index,
+        )
+        .unwrap(),
+        MemoryRecord::External => out.push_str("{\"kind\":\"external\"}"),
+    }
+}
+
+fn write_name(out: &mut String, name: &Option<String>) {
+    match name {
+        Some(name) => write_string(out, name),
+        None => out.push_str("null"),
+    }
+}
+
+fn write_debug(out: &mut String, value: &impl Debug) {
+    write_string(out, &format!("{:?}", value));
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
@@ -55,17 +55,29 @@
 pub use self::{aspect::*, layout::*, sys::ImageCreateInfo, usage::*};
 use self::{sys::RawImage, view::ImageViewType};
 use crate::{
-    device::{physical::PhysicalDevice, Device, DeviceOwned},
+    buffer::{AllocateBufferError, Buffer, BufferContents, BufferCreateInfo, BufferUsage},
+    command_buffer::{
+        allocator::CommandBufferAllocator, CommandBufferBeginInfo, CommandBufferExecError,
+        CommandBufferExecFuture, CommandBufferLevel, CommandBufferUsage, CopyBufferToImageInfo,
+        RecordingCommandBuffer,
+    },
+    device::{physical::PhysicalDevice, Device, DeviceOwned, ExportMetalObjectTypes, Queue},
     format::{Format, FormatFeatures},
     macros::{vulkan_bitflags, vulkan_bitflags_enum, vulkan_enum},
     memory::{
-        allocator::{AllocationCreateInfo, MemoryAllocator, MemoryAllocatorError},
+        allocator::{
+            set_object_name_from_tag, AllocationCreateInfo, MemoryAllocator, MemoryAllocatorError,
+            MemoryTypeFilter,
+        },
         DedicatedAllocation, ExternalMemoryHandleType, ExternalMemoryHandleTypes,
         ExternalMemoryProperties, MemoryRequirements, ResourceMemory,
     },
     range_map::RangeMap,
     swapchain::Swapchain,
-    sync::{future::AccessError, AccessConflict, CurrentAccess, Sharing},
+    sync::{
+        future::{AccessError, NowFuture},
+        AccessConflict, CurrentAccess, Sharing,
+    },
     DeviceSize, Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, Version,
     VulkanError, VulkanObject,
 };
@@ -74,6 +86,7 @@ use smallvec::SmallVec;
 use std::{
     cmp::max,
     error::Error,
+    ffi::c_void,
     fmt::{Display, Formatter},
     hash::{Hash, Hasher},
     iter::{FusedIterator, Peekable},
@@ -87,7 +100,9 @@ use std::{
 mod aspect;
 mod layout;
 pub mod sampler;
+pub mod sparse;
 pub mod sys;
+pub mod transient;
 mod usage;
 pub mod view;
 
@@ -139,6 +154,7 @@ pub enum ImageMemory {
 
 impl Image {
     /// Creates a new uninitialized `Image`.
+    #[track_caller]
     pub fn new(
         allocator: Arc<dyn MemoryAllocator>,
         create_info: ImageCreateInfo,
@@ -153,6 +169,11 @@ impl Image {
                 Validated::Error(err) => Validated::Error(AllocateImageError::CreateImage(err)),
                 Validated::ValidationError(err) => err.into(),
             })?;
+
+        if let Some(tag) = &allocation_info.tag {
+            set_object_name_from_tag(&raw_image, tag);
+        }
+
         let requirements = raw_image.memory_requirements()[0];
 
         let allocation = allocator
@@ -176,7 +197,94 @@ impl Image {
         Ok(Arc::new(image))
     }
 
+    /// Creates a new `Image` in device-local memory and uploads all elements of `iter` to it,
+    /// together with a future that represents the upload.
+    ///
+    /// The data is written to a host-visible staging buffer, and a command buffer that copies it
+    /// to the first mip level of all array layers of the image is recorded and submitted to
+    /// `queue`. The data must be tightly packed in the layout that
+    /// [`copy_buffer_to_image`] expects. [`ImageUsage::TRANSFER_DST`] is added to
+    /// `create_info.usage` for this. The other mip levels are left uninitialized. The staging
+    /// buffer is kept alive by the future until the copy has finished.
+    ///
+    /// The image must not be accessed until the upload has finished. Either wait for the future,
+    /// or join it with the future of the work that uses the image.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.flags` contains [`ImageCreateFlags::DISJOINT`].
+    /// - Panics if `iter` is empty.
+    ///
+    /// [`copy_buffer_to_image`]: RecordingCommandBuffer::copy_buffer_to_image
+    #[track_caller]
+    pub fn from_iter_device_local<T, I>(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        command_buffer_allocator: Arc<dyn CommandBufferAllocator>,
+        queue: Arc<Queue>,
+        mut create_info: ImageCreateInfo,
+        iter: I,
+    ) -> Result<(Arc<Self>, CommandBufferExecFuture<NowFuture>), Validated<UploadImageError>>
+    where
+        T: BufferContents,
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let staging_buffer = Buffer::from_iter(
+            memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_SRC,
+                sharing: create_info.sharing.clone(),
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            iter,
+        )
+        .map_err(|err| err.map(UploadImageError::AllocateBuffer))?;
+
+        create_info.usage |= ImageUsage::TRANSFER_DST;
+        let image = Image::new(
+            memory_allocator,
+            create_info,
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                ..Default::default()
+            },
+        )
+        .map_err(|err| err.map(UploadImageError::AllocateImage))?;
+
+        let mut builder = RecordingCommandBuffer::new(
+            command_buffer_allocator,
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .map_err(|err| err.map(UploadImageError::CommandBuffer))?;
+        builder.copy_buffer_to_image(CopyBufferToImageInfo::buffer_image(
+            staging_buffer,
+            image.clone(),
+        ))?;
+        let command_buffer = builder
+            .end()
+            .map_err(|err| err.map(UploadImageError::CommandBuffer))?;
+        let future = command_buffer
+            .execute(queue)
+            .map_err(UploadImageError::Execute)?;
+
+        Ok((image, future))
+    }
+
     fn from_raw(inner: RawImage, memory: ImageMemory, layout: ImageLayout) -> Self {
+        if let Some(resources) = inner.device().resources() {
+            resources.bind_image_memory(&inner, &memory);
+        }
+
         let aspects = inner.format().aspects();
         let aspect_list: SmallVec<[ImageAspect; 4]> = aspects.into_iter().collect();
         let mip_level_size = inner.array_layers() as DeviceSize;
@@ -222,6 +330,7 @@ impl Image {
             drm_format_modifiers: Vec::new(),
             drm_format_modifier_plane_layouts: Vec::new(),
             external_memory_handle_types: ExternalMemoryHandleTypes::empty(),
+            export_metal_object_types: ExportMetalObjectTypes::empty(),
             _ne: crate::NonExhaustive(()),
         };
 
@@ -356,6 +465,12 @@ impl Image {
         self.inner.external_memory_handle_types()
     }
 
+    /// Returns the types of Metal objects that can be exported from this image.
+    #[inline]
+    pub fn export_metal_object_types(&self) -> ExportMetalObjectTypes {
+        self.inner.export_metal_object_types()
+    }
+
     /// Returns an `ImageSubresourceLayers` covering the first mip level of the image. All aspects
     /// of the image are selected, or `plane0` if the image is multi-planar.
     #[inline]
@@ -405,6 +520,209 @@ impl Image {
             .subresource_layout_unchecked(aspect, mip_level, array_layer)
     }
 
+    /// Returns the `MTLTexture` that backs the given plane of the image, as an `id<MTLTexture>`
+    /// pointer.
+    ///
+    /// `plane` must be 0 if the image does not have a multi-planar format.
+    ///
+    /// The image must have been created with [`ExportMetalObjectTypes::METAL_TEXTURE`] in
+    /// [`ImageCreateInfo::export_metal_object_types`].
+    #[inline]
+    pub fn export_metal_texture(&self, plane: usize) -> Result<*mut c_void, Box<ValidationError>> {
+        self.inner.export_metal_texture(plane)
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[inline]
+    pub unsafe fn export_metal_texture_unchecked(&self, plane: usize) -> *mut c_void {
+        self.inner.export_metal_texture_unchecked(plane)
+    }
+
+    /// Returns the `IOSurface` that backs the image, as an `IOSurfaceRef` pointer.
+    ///
+    /// The image must have been created with [`ExportMetalObjectTypes::METAL_IOSURFACE`] in
+    /// [`ImageCreateInfo::export_metal_object_types`].
+    #[inline]
+    pub fn export_metal_io_surface(&self) -> Result<*mut c_void, Box<ValidationError>> {
+        self.inner.export_metal_io_surface()
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[inline]
+    pub unsafe fn export_metal_io_surface_unchecked(&self) -> *mut c_void {
+        self.inner.export_metal_io_surface_unchecked()
+    }
+
+    /// Returns the layout that `subresource_range` of the image is currently in, as tracked by
+    /// vulkano, or `None` if the subresources in the range are not all in the same layout.
+    ///
+    /// This takes into account all command buffers that have been submitted so far, and all
+    /// transitions that were declared with [`set_current_layout`]. Command buffers that use the
+    /// image normally transition it back to the layout that vulkano expects when they finish
+    /// executing, so this is usually only different from that layout after an external
+    /// transition.
+    ///
+    /// [`set_current_layout`]: Self::set_current_layout
+    pub fn current_layout(
+        &self,
+        subresource_range: ImageSubresourceRange,
+    ) -> Result<Option<ImageLayout>, Box<ValidationError>> {
+        self.validate_tracked_subresource_range(&subresource_range)?;
+
+        let state = self.state.lock();
+        let mut layouts = self
+            .iter_ranges(subresource_range)
+            .flat_map(|range| state.layouts(range).map(|(_, layout)| layout));
+        let first = layouts.next();
+
+        Ok(first.filter(|&first| layouts.all(|layout| layout == first)))
+    }
+
+    /// Checks that all subresources in `subresource_range` of the image are currently in
+    /// `layout`, as tracked by vulkano.
+    ///
+    /// This can be used to assert that the image is in the layout that an external API expects,
+    /// before handing it over.
+    pub fn check_current_layout(
+        &self,
+        subresource_range: ImageSubresourceRange,
+        layout: ImageLayout,
+    ) -> Result<(), Box<ValidationError>> {
+        match self.current_layout(subresource_range)? {
+            Some(current_layout) if current_layout == layout => Ok(()),
+            current_layout => Err(Box::new(ValidationError {
+                problem: format!(
+                    "the subresource range is expected to be in the `{:?}` layout, but it is \
+                    currently in the {} layout",
+                    layout,
+                    match current_layout {
+                        Some(current_layout) => format!("`{:?}`", current_layout),
+                        None => "mixed".to_owned(),
+                    },
+                )
+                .into(),
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Declares that `subresource_range` of the image has been transitioned to `layout` outside
+    /// of vulkano's tracking, for example by a command buffer that was recorded with the raw API,
+    /// or by an external API that the image is shared with.
+    ///
+    /// Command buffers that are recorded afterwards will transition the range from `layout`
+    /// before the range is first used, and back to the layout that vulkano expects at the end.
+    /// When such a command buffer is submitted, vulkano checks that the range is still in
+    /// `layout`, so that command buffers that were recorded before the transition took place are
+    /// caught.
+    ///
+    /// # Safety
+    ///
+    /// - When the next command buffer that uses the image starts executing, `subresource_range`
+    ///   must be in `layout`.
+    pub unsafe fn set_current_layout(
+        &self,
+        subresource_range: ImageSubresourceRange,
+        layout: ImageLayout,
+    ) -> Result<(), Box<ValidationError>> {
+        self.validate_set_current_layout(&subresource_range, layout)?;
+
+        unsafe { self.set_current_layout_unchecked(subresource_range, layout) };
+
+        Ok(())
+    }
+
+    fn validate_set_current_layout(
+        &self,
+        subresource_range: &ImageSubresourceRange,
+        layout: ImageLayout,
+    ) -> Result<(), Box<ValidationError>> {
+        self.validate_tracked_subresource_range(subresource_range)?;
+
+        layout
+            .validate_device(self.device())
+            .map_err(|err| err.add_context("layout"))?;
+
+        if layout == ImageLayout::Preinitialized {
+            return Err(Box::new(ValidationError {
+                context: "layout".into(),
+                problem: "is `ImageLayout::Preinitialized`, which images can't be transitioned \
+                    to"
+                .into(),
+                ..Default::default()
+            }));
+        }
+
+        let state = self.state.lock();
+
+        for range in self.iter_ranges(subresource_range.clone()) {
+            if state
+                .check_gpu_write(range, ImageLayout::Undefined)
+                .is_err()
+            {
+                return Err(Box::new(ValidationError {
+                    context: "subresource_range".into(),
+                    problem: "is currently in use by the device".into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn set_current_layout_unchecked(
+        &self,
+        subresource_range: ImageSubresourceRange,
+        layout: ImageLayout,
+    ) {
+        let mut state = self.state.lock();
+
+        for range in self.iter_ranges(subresource_range) {
+            state.set_layout(range, layout);
+        }
+
+        // Command buffers that are recorded from now on must take the declared layout as their
+        // starting point, instead of the initial layout of the image.
+        unsafe { self.layout_initialized() };
+    }
+
+    fn validate_tracked_subresource_range(
+        &self,
+        subresource_range: &ImageSubresourceRange,
+    ) -> Result<(), Box<ValidationError>> {
+        subresource_range
+            .validate(self.device())
+            .map_err(|err| err.add_context("subresource_range"))?;
+
+        if !self.format().aspects().contains(subresource_range.aspects) {
+            return Err(Box::new(ValidationError {
+                context: "subresource_range.aspects".into(),
+                problem: "is not a subset of the aspects of the format of the image".into(),
+                ..Default::default()
+            }));
+        }
+
+        if subresource_range.mip_levels.end > self.mip_levels() {
+            return Err(Box::new(ValidationError {
+                context: "subresource_range.mip_levels".into(),
+                problem: "exceeds the number of mip levels of the image".into(),
+                ..Default::default()
+            }));
+        }
+
+        if subresource_range.array_layers.end > self.array_layers() {
+            return Err(Box::new(ValidationError {
+                context: "subresource_range.array_layers".into(),
+                problem: "exceeds the number of array layers of the image".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn range_size(&self) -> DeviceSize {
         self.range_size
     }
@@ -602,6 +920,46 @@ impl From<AllocateImageError> for Validated<AllocateImageError> {
     }
 }
 
+/// Error that can happen when creating a new image in device-local memory and uploading data to
+/// it.
+#[derive(Clone, Debug)]
+pub enum UploadImageError {
+    AllocateBuffer(AllocateBufferError),
+    AllocateImage(AllocateImageError),
+    CommandBuffer(VulkanError),
+    Execute(CommandBufferExecError),
+}
+
+impl Error for UploadImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::AllocateBuffer(err) => Some(err),
+            Self::AllocateImage(err) => Some(err),
+            Self::CommandBuffer(err) => Some(err),
+            Self::Execute(err) => Some(err),
+        }
+    }
+}
+
+impl Display for UploadImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AllocateBuffer(_) => write!(f, "allocating the staging buffer failed"),
+            Self::AllocateImage(_) => write!(f, "allocating the image failed"),
+            Self::CommandBuffer(_) => {
+                write!(f, "recording the command buffer for the upload failed")
+            }
+            Self::Execute(_) => write!(f, "executing the command buffer for the upload failed"),
+        }
+    }
+}
+
+impl From<UploadImageError> for Validated<UploadImageError> {
+    fn from(err: UploadImageError) -> Self {
+        Self::Error(err)
+    }
+}
+
 /// The current state of an image.
 #[derive(Debug)]
 pub(crate) struct ImageState {
@@ -626,6 +984,28 @@ impl ImageState {
         }
     }
 
+    /// Returns the tracked layouts of the subresources in `range`.
+    pub(crate) fn layouts(
+        &self,
+        range: Range<DeviceSize>,
+    ) -> impl Iterator<Item = (Range<DeviceSize>, ImageLayout)> + '_ {
+        self.ranges.range(&range).map(move |(state_range, state)| {
+            (
+                max(state_range.start, range.start)..state_range.end.min(range.end),
+                state.layout,
+            )
+        })
+    }
+
+    pub(crate) fn set_layout(&mut self, range: Range<DeviceSize>, layout: ImageLayout) {
+        self.ranges.split_at(&range.start);
+        self.ranges.split_at(&range.end);
+
+        for (_range, state) in self.ranges.range_mut(&range) {
+            state.layout = layout;
+        }
+    }
+
     #[allow(dead_code)]
     pub(crate) fn check_cpu_read(&self, range: Range<DeviceSize>) -> Result<(), AccessConflict> {
         for (_range, state) in self.ranges.range(&range) {
@@ -788,6 +1168,38 @@ impl ImageState {
         Ok(())
     }
 
+    /// Like `check_gpu_write`, but allows the range to still be locked by `pending_writes`
+    /// earlier submissions of the same command buffer to the same queue.
+    pub(crate) fn check_gpu_write_resubmit(
+        &self,
+        range: Range<DeviceSize>,
+        expected_layout: ImageLayout,
+        pending_writes: usize,
+    ) -> Result<(), AccessError> {
+        for (_range, state) in self.ranges.range(&range) {
+            match &state.current_access {
+                CurrentAccess::Shared {
+                    cpu_reads: 0,
+                    gpu_reads: 0,
+                } => (),
+                &CurrentAccess::GpuExclusive {
+                    gpu_reads: 0,
+                    gpu_writes,
+                } if gpu_writes <= pending_writes => (),
+                _ => return Err(AccessError::AlreadyInUse),
+            }
+
+            if expected_layout != ImageLayout::Undefined && state.layout != expected_layout {
+                return Err(AccessError::UnexpectedImageLayout {
+                    allowed: state.layout,
+                    requested: expected_layout,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) unsafe fn gpu_write_lock(
         &mut self,
         range: Range<DeviceSize>,
@@ -982,7 +1394,6 @@ vulkan_bitflags! {
     /// Flags specifying additional properties of an image.
     ImageCreateFlags = ImageCreateFlags(u32);
 
-    /* TODO: enable
     /// The image will be backed by sparse memory binding (through queue commands) instead of
     /// regular binding (through [`bind_memory`]).
     ///
@@ -990,9 +1401,8 @@ vulkan_bitflags! {
     ///
     /// [`bind_memory`]: sys::RawImage::bind_memory
     /// [`sparse_binding`]: crate::device::DeviceFeatures::sparse_binding
-    SPARSE_BINDING = SPARSE_BINDING,*/
+    SPARSE_BINDING = SPARSE_BINDING,
 
-    /* TODO: enable
     /// The image can be used without being fully resident in memory at the time of use.
     ///
     /// This requires the `sparse_binding` flag as well.
@@ -1006,12 +1416,12 @@ vulkan_bitflags! {
     ///
     /// [`sparse_binding`]: crate::device::DeviceFeatures::sparse_binding
     /// [`sparse_residency_image2_d`]: crate::device::DeviceFeatures::sparse_residency_image2_d
-    /// [`sparse_residency_image2_3`]: crate::device::DeviceFeatures::sparse_residency_image3_d
+    /// [`sparse_residency_image3_d`]: crate::device::DeviceFeatures::sparse_residency_image3_d
     /// [`sparse_residency2_samples`]: crate::device::DeviceFeatures::sparse_residency2_samples
     /// [`sparse_residency4_samples`]: crate::device::DeviceFeatures::sparse_residency4_samples
     /// [`sparse_residency8_samples`]: crate::device::DeviceFeatures::sparse_residency8_samples
     /// [`sparse_residency16_samples`]: crate::device::DeviceFeatures::sparse_residency16_samples
-    SPARSE_RESIDENCY = SPARSE_RESIDENCY,*/
+    SPARSE_RESIDENCY = SPARSE_RESIDENCY,
 
     /* TODO: enable
     /// The buffer's memory can alias with another image or a different part of the same image.
@@ -1089,12 +1499,23 @@ vulkan_bitflags! {
         RequiresAllOf([DeviceExtension(khr_maintenance2)]),
     ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The image is protected, and can only be used in combination with protected memory and other
+    /// protected objects.
+    ///
+    /// The [`protected_memory`] feature must be enabled on the device. This flag can't be
+    /// combined with the sparse flags.
+    ///
+    /// The [`StandardMemoryAllocator`] never allocates from protected memory types, so the memory
+    /// for protected images must come from an allocator whose
+    /// [`memory_type_bits`](crate::memory::allocator::GenericMemoryAllocatorCreateInfo::memory_type_bits)
+    /// includes them.
+    ///
+    /// [`protected_memory`]: crate::device::DeviceFeatures::protected_memory
+    /// [`StandardMemoryAllocator`]: crate::memory::allocator::StandardMemoryAllocator
     PROTECTED = PROTECTED
     RequiresOneOf([
         RequiresAllOf([APIVersion(V1_1)]),
-    ]),*/
+    ]),
 
     /// For images with a multi-planar format, whether each plane will have its memory bound
     /// separately, rather than having a single memory binding for the whole image.
@@ -2292,6 +2713,158 @@ pub struct SparseImageMemoryRequirements {
 
 #[cfg(test)]
 mod tests {
+    use super::{Image, ImageCreateInfo, ImageLayout, ImageSubresourceRange, ImageUsage};
+    use crate::{
+        format::Format,
+        memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn current_layout() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device));
+
+        let image = Image::new(
+            memory_allocator,
+            ImageCreateInfo {
+                format: Format::R8G8B8A8_UNORM,
+                extent: [32, 32, 1],
+                mip_levels: 2,
+                usage: ImageUsage::SAMPLED | ImageUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .unwrap();
+
+        let first_mip_level = ImageSubresourceRange {
+            mip_levels: 0..1,
+            ..image.subresource_range()
+        };
+
+        assert_eq!(
+            image.current_layout(image.subresource_range()).unwrap(),
+            Some(ImageLayout::Undefined),
+        );
+
+        unsafe {
+            image
+                .set_current_layout(first_mip_level.clone(), ImageLayout::TransferDstOptimal)
+                .unwrap();
+        }
+
+        assert!(image
+            .check_current_layout(first_mip_level, ImageLayout::TransferDstOptimal)
+            .is_ok());
+        assert_eq!(
+            image.current_layout(image.subresource_range()).unwrap(),
+            None
+        );
+
+        unsafe {
+            assert!(image
+                .set_current_layout(image.subresource_range(), ImageLayout::Preinitialized)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn from_iter_device_local() {
+        use crate::{
+            buffer::{Buffer, BufferCreateInfo, BufferUsage},
+            command_buffer::{
+                allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo,
+                CommandBufferLevel, CommandBufferUsage, CopyImageToBufferInfo,
+                RecordingCommandBuffer,
+            },
+            memory::allocator::MemoryTypeFilter,
+            sync::GpuFuture,
+            Validated,
+        };
+
+        let (device, queue) = gfx_dev_and_queue!();
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+        let command_buffer_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device,
+            Default::default(),
+        ));
+        let create_info = ImageCreateInfo {
+            format: Format::R8G8B8A8_UNORM,
+            extent: [4, 4, 1],
+            usage: ImageUsage::SAMPLED | ImageUsage::TRANSFER_SRC,
+            ..Default::default()
+        };
+        let data: Vec<u8> = (0..4 * 4 * 4).collect();
+
+        let (image, future) = Image::from_iter_device_local(
+            memory_allocator.clone(),
+            command_buffer_allocator.clone(),
+            queue.clone(),
+            create_info.clone(),
+            data.iter().copied(),
+        )
+        .unwrap();
+        future
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+        assert!(image.usage().contains(ImageUsage::TRANSFER_DST));
+
+        // Read the image back to check that the data was uploaded.
+        let readback_buffer = Buffer::new_slice::<u8>(
+            memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+                ..Default::default()
+            },
+            data.len() as u64,
+        )
+        .unwrap();
+        let mut cbb = RecordingCommandBuffer::new(
+            command_buffer_allocator.clone(),
+            queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        cbb.copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(
+            image,
+            readback_buffer.clone(),
+        ))
+        .unwrap();
+        cbb.end()
+            .unwrap()
+            .execute(queue.clone())
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+        assert_eq!(&*readback_buffer.read().unwrap(), data.as_slice());
+
+        // The data is too small for the image.
+        assert!(matches!(
+            Image::from_iter_device_local(
+                memory_allocator,
+                command_buffer_allocator,
+                queue,
+                create_info,
+                [0u8; 4],
+            ),
+            Err(Validated::ValidationError(_)),
+        ));
+    }
+
     #[test]
     fn max_mip_levels() {
         assert_eq!(super::max_mip_levels([2, 1, 1]), 2);
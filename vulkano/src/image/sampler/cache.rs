@@ -0,0 +1,342 @@
+//! Sharing samplers with identical parameters.
+//!
+//! Devices have a limit on the number of samplers that can exist at the same time, given by the
+//! [`max_sampler_allocation_count`] property, which can be as low as 4000. Applications that create
+//! a sampler for every material or texture can easily run into it, even though most of the
+//! samplers are identical. A [`SamplerCache`] returns the same sampler every time it is asked for
+//! one with the same create info, and can optionally evict samplers that are no longer in use once
+//! it grows past a certain size.
+//!
+//! [`max_sampler_allocation_count`]: crate::device::DeviceProperties::max_sampler_allocation_count
+
+use super::{
+    BorderColor, Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerMipmapMode,
+    SamplerReductionMode,
+};
+use crate::{
+    device::{Device, DeviceOwned},
+    instance::InstanceOwnedDebugWrapper,
+    pipeline::graphics::depth_stencil::CompareOp,
+    Validated, VulkanError,
+};
+use ahash::HashMap;
+use parking_lot::Mutex;
+use std::{
+    fmt::{Debug, Error as FmtError, Formatter},
+    num::NonZeroU64,
+    sync::Arc,
+};
+
+/// A cache of samplers, keyed by their create info.
+///
+/// # Examples
+///
+/// ```
+/// use vulkano::image::sampler::{
+///     cache::{SamplerCache, SamplerCacheCreateInfo},
+///     SamplerCreateInfo,
+/// };
+///
+/// # let device: std::sync::Arc<vulkano::device::Device> = return;
+/// let cache = SamplerCache::new(
+///     device.clone(),
+///     SamplerCacheCreateInfo {
+///         capacity: Some(1024),
+///         ..Default::default()
+///     },
+/// );
+///
+/// let a = cache.get(SamplerCreateInfo::simple_repeat_linear()).unwrap();
+/// let b = cache.get(SamplerCreateInfo::simple_repeat_linear()).unwrap();
+/// assert!(std::sync::Arc::ptr_eq(&a, &b));
+/// ```
+pub struct SamplerCache {
+    device: InstanceOwnedDebugWrapper<Arc<Device>>,
+    capacity: Option<usize>,
+    state: Mutex<SamplerCacheState>,
+}
+
+#[derive(Default)]
+struct SamplerCacheState {
+    entries: HashMap<SamplerKey, SamplerCacheEntry>,
+    // Incremented on every access, so that the least recently used entry has the lowest value.
+    clock: u64,
+}
+
+struct SamplerCacheEntry {
+    sampler: Arc<Sampler>,
+    last_used: u64,
+}
+
+impl SamplerCache {
+    /// Creates a new, empty `SamplerCache`.
+    #[inline]
+    pub fn new(device: Arc<Device>, create_info: SamplerCacheCreateInfo) -> Self {
+        let SamplerCacheCreateInfo { capacity, _ne: _ } = create_info;
+
+        SamplerCache {
+            device: InstanceOwnedDebugWrapper(device),
+            capacity,
+            state: Mutex::new(SamplerCacheState::default()),
+        }
+    }
+
+    /// Returns the maximum number of samplers that the cache holds before evicting unused ones.
+    #[inline]
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the number of samplers that are currently held by the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.state.lock().entries.len()
+    }
+
+    /// Returns whether the cache is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().entries.is_empty()
+    }
+
+    /// Returns a sampler created with `create_info`.
+    ///
+    /// If the cache holds a sampler that was created with an identical create info, that sampler
+    /// is returned. Otherwise, a new sampler is created and added to the cache. If this makes the
+    /// cache exceed its capacity, the least recently used samplers that are not in use outside of
+    /// the cache are evicted. Samplers that are still in use are never evicted, since this would
+    /// not free them and would cause duplicates to be created, so the cache can temporarily
+    /// exceed its capacity.
+    pub fn get(
+        &self,
+        create_info: SamplerCreateInfo,
+    ) -> Result<Arc<Sampler>, Validated<VulkanError>> {
+        let key = SamplerKey::new(&create_info);
+        let mut state = self.state.lock();
+        state.clock += 1;
+        let clock = state.clock;
+
+        if let Some(entry) = state.entries.get_mut(&key) {
+            entry.last_used = clock;
+
+            return Ok(entry.sampler.clone());
+        }
+
+        if let Some(capacity) = self.capacity {
+            // Make room for the new sampler first, so that the slot of an evicted sampler can be
+            // reused if the device is at its limit.
+            evict(&mut state.entries, capacity.saturating_sub(1));
+        }
+
+        let sampler = Sampler::new(self.device.clone(), create_info)?;
+        state.entries.insert(
+            key,
+            SamplerCacheEntry {
+                sampler: sampler.clone(),
+                last_used: clock,
+            },
+        );
+
+        Ok(sampler)
+    }
+
+    /// Removes all samplers that are not in use outside of the cache.
+    pub fn purge_unused(&self) {
+        self.state
+            .lock()
+            .entries
+            .retain(|_, entry| Arc::strong_count(&entry.sampler) > 1);
+    }
+
+    /// Removes all samplers from the cache.
+    ///
+    /// Samplers that are still in use outside of the cache are not destroyed, but will no longer
+    /// be returned by [`get`](Self::get).
+    pub fn clear(&self) {
+        self.state.lock().entries.clear();
+    }
+}
+
+/// Evicts unused entries, least recently used first, until at most `len` entries remain or there
+/// are no more unused entries.
+fn evict(entries: &mut HashMap<SamplerKey, SamplerCacheEntry>, len: usize) {
+    if entries.len() <= len {
+        return;
+    }
+
+    let mut unused: Vec<_> = entries
+        .iter()
+        .filter(|(_, entry)| Arc::strong_count(&entry.sampler) == 1)
+        .map(|(key, entry)| (entry.last_used, key.clone()))
+        .collect();
+    unused.sort_unstable_by_key(|&(last_used, _)| last_used);
+
+    let excess = entries.len() - len;
+
+    for (_, key) in unused.into_iter().take(excess) {
+        entries.remove(&key);
+    }
+}
+
+unsafe impl DeviceOwned for SamplerCache {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl Debug for SamplerCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_struct("SamplerCache")
+            .field("device", &self.device)
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parameters to create a new `SamplerCache`.
+#[derive(Clone, Debug)]
+pub struct SamplerCacheCreateInfo {
+    /// The number of samplers above which the cache starts evicting the least recently used
+    /// samplers that are not in use. If `None`, samplers are never evicted.
+    ///
+    /// A value somewhat below the [`max_sampler_allocation_count`] property of the device leaves
+    /// room for samplers that are created outside of the cache.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`max_sampler_allocation_count`]: crate::device::DeviceProperties::max_sampler_allocation_count
+    pub capacity: Option<usize>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for SamplerCacheCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            capacity: None,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// The parameters of a `SamplerCreateInfo` in a form that can be hashed. Floating-point values are
+/// compared by their bits, and the YCbCr conversion by its identity.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct SamplerKey {
+    mag_filter: Filter,
+    min_filter: Filter,
+    mipmap_mode: SamplerMipmapMode,
+    address_mode: [SamplerAddressMode; 3],
+    mip_lod_bias: u32,
+    anisotropy: Option<u32>,
+    compare: Option<CompareOp>,
+    lod: (u32, u32),
+    border_color: BorderColor,
+    unnormalized_coordinates: bool,
+    reduction_mode: SamplerReductionMode,
+    sampler_ycbcr_conversion: Option<NonZeroU64>,
+}
+
+impl SamplerKey {
+    fn new(create_info: &SamplerCreateInfo) -> Self {
+        let &SamplerCreateInfo {
+            mag_filter,
+            min_filter,
+            mipmap_mode,
+            address_mode,
+            mip_lod_bias,
+            anisotropy,
+            compare,
+            ref lod,
+            border_color,
+            unnormalized_coordinates,
+            reduction_mode,
+            ref sampler_ycbcr_conversion,
+            _ne: _,
+        } = create_info;
+
+        Self {
+            mag_filter,
+            min_filter,
+            mipmap_mode,
+            address_mode,
+            mip_lod_bias: mip_lod_bias.to_bits(),
+            anisotropy: anisotropy.map(f32::to_bits),
+            compare,
+            lod: (lod.start().to_bits(), lod.end().to_bits()),
+            border_color,
+            unnormalized_coordinates,
+            reduction_mode,
+            sampler_ycbcr_conversion: sampler_ycbcr_conversion
+                .as_ref()
+                .map(|conversion| conversion.id()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SamplerCache, SamplerCacheCreateInfo};
+    use crate::image::sampler::{Filter, SamplerCreateInfo};
+    use std::sync::Arc;
+
+    #[test]
+    fn shares_identical_samplers() {
+        let (device, _) = gfx_dev_and_queue!();
+        let cache = SamplerCache::new(device, SamplerCacheCreateInfo::default());
+
+        let a = cache
+            .get(SamplerCreateInfo::simple_repeat_linear())
+            .unwrap();
+        let b = cache
+            .get(SamplerCreateInfo::simple_repeat_linear())
+            .unwrap();
+        let c = cache.get(SamplerCreateInfo::default()).unwrap();
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let (device, _) = gfx_dev_and_queue!();
+        let cache = SamplerCache::new(
+            device,
+            SamplerCacheCreateInfo {
+                capacity: Some(2),
+                ..Default::default()
+            },
+        );
+
+        let create_info = |mip_lod_bias| SamplerCreateInfo {
+            mip_lod_bias,
+            ..Default::default()
+        };
+
+        drop(cache.get(create_info(0.0)).unwrap());
+        let in_use = cache.get(create_info(1.0)).unwrap();
+        drop(cache.get(create_info(2.0)).unwrap());
+        assert_eq!(cache.len(), 2);
+
+        // The sampler that is still in use must not have been evicted.
+        assert!(Arc::ptr_eq(&in_use, &cache.get(create_info(1.0)).unwrap()));
+
+        // Neither sampler can be evicted while both are in use.
+        let in_use_2 = cache.get(create_info(2.0)).unwrap();
+        let _new = cache
+            .get(SamplerCreateInfo {
+                mag_filter: Filter::Linear,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(cache.len(), 3);
+        drop(in_use_2);
+
+        cache.purge_unused();
+        assert_eq!(cache.len(), 2);
+    }
+}
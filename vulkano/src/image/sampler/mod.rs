@@ -35,6 +35,7 @@
 //! - Positive: **minification**. The rendered object is further from the viewer, and each pixel in
 //!   the texture corresponds to less than one framebuffer pixel.
 
+pub mod cache;
 pub mod ycbcr;
 
 use self::ycbcr::SamplerYcbcrConversion;
@@ -110,13 +111,19 @@ pub struct Sampler {
 impl Sampler {
     /// Creates a new `Sampler`.
     #[inline]
+    #[track_caller]
     pub fn new(
         device: Arc<Device>,
         create_info: SamplerCreateInfo,
     ) -> Result<Arc<Sampler>, Validated<VulkanError>> {
         Self::validate_new(&device, &create_info)?;
 
-        unsafe { Ok(Self::new_unchecked(device, create_info)?) }
+        let sampler = unsafe { Self::new_unchecked(device, create_info)? };
+        sampler
+            .device()
+            .set_auto_debug_utils_object_name(&*sampler, "Sampler");
+
+        Ok(sampler)
     }
 
     fn validate_new(
@@ -604,6 +611,8 @@ impl_id_counter!(Sampler);
 
 /// Parameters to create a new `Sampler`.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct SamplerCreateInfo {
     /// How the sampled value of a single mipmap should be calculated,
     /// when magnification is applied (LOD <= 0.0).
@@ -741,8 +750,10 @@ pub struct SamplerCreateInfo {
     /// layout, and only in a combined image sampler descriptor.
     ///
     /// The default value is `None`.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub sampler_ycbcr_conversion: Option<Arc<SamplerYcbcrConversion>>,
 
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _ne: crate::NonExhaustive,
 }
 
@@ -1129,6 +1140,8 @@ pub const LOD_CLAMP_NONE: f32 = ash::vk::LOD_CLAMP_NONE;
 
 /// A mapping between components of a source format and components read by a shader.
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ComponentMapping {
     /// First component.
     pub r: ComponentSwizzle,
@@ -0,0 +1,613 @@
+//! Streaming of partially-resident images.
+//!
+//! An image created with the [`SPARSE_RESIDENCY`] flag doesn't need to have memory bound to all
+//! of it. Its texel data is divided into *sparse image blocks*, or *tiles*, whose shape depends
+//! on the format and the implementation, and memory can be bound to and unbound from each tile
+//! individually. This makes it possible to keep only the parts of a very large texture resident
+//! that are actually being sampled, which is the basis of virtual texturing.
+//!
+//! [`SparseImageStreamer`] takes care of the bookkeeping involved: it queries the tile shape of
+//! the image, suballocates the memory for tiles from larger [`DeviceMemory`] blocks, keeps the
+//! *mip tail* (the smallest mip levels, which are not divided into tiles) resident at all times,
+//! and turns requests to make tiles resident or to evict them into [`BindSparseInfo`]s that can
+//! be executed on a queue that supports sparse binding.
+//!
+//! Deciding which tiles are needed is up to the application. Typically, shaders write the tiles
+//! that they would have liked to sample into a feedback buffer, which is read back on the host
+//! and fed to [`request`]. [`tile_at`] can be used to turn the texel coordinates read back from
+//! the feedback buffer into [`TileCoord`]s.
+//!
+//! [`SPARSE_RESIDENCY`]: super::ImageCreateFlags::SPARSE_RESIDENCY
+//! [`DeviceMemory`]: crate::memory::DeviceMemory
+//! [`BindSparseInfo`]: crate::memory::BindSparseInfo
+//! [`request`]: SparseImageStreamer::request
+//! [`tile_at`]: SparseImageStreamer::tile_at
+
+use super::{mip_level_extent, Image, ImageAspects, ImageMemory, SparseImageFormatFlags};
+use crate::{
+    device::{Device, DeviceOwned},
+    memory::{
+        allocator::{find_memory_type_index, MemoryAllocatorError, MemoryTypeFilter},
+        BindSparseInfo, DeviceMemory, MemoryAllocateInfo, SparseImageMemoryBind,
+        SparseImageOpaqueMemoryBind,
+    },
+    DeviceSize, Validated, ValidationError,
+};
+use ahash::HashMap;
+use std::{cmp::min, sync::Arc};
+
+/// Makes tiles of a sparse image resident on request, and keeps its mip tail resident.
+///
+/// See [the module-level documentation] for more details.
+///
+/// # Examples
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use vulkano::{
+/// #     device::Queue,
+/// #     image::{sparse::{SparseImageStreamer, SparseImageStreamerCreateInfo}, Image},
+/// # };
+/// # let image: Arc<Image> = return;
+/// # let queue: Arc<Queue> = return;
+/// # let feedback: Vec<(u32, [u32; 3])> = return;
+/// #
+/// let mut streamer =
+///     SparseImageStreamer::new(image, SparseImageStreamerCreateInfo::default()).unwrap();
+///
+/// // The mip tail was bound when the streamer was created. Each frame, make the tiles resident
+/// // that the shaders asked for.
+/// for (mip_level, texel) in feedback {
+///     let tile = streamer.tile_at(mip_level, 0, texel);
+///     streamer.request(tile).unwrap();
+/// }
+///
+/// if let Some(bind_info) = streamer.take_bind_info() {
+///     queue.with(|mut q| unsafe { q.bind_sparse_unchecked(&[bind_info], None) }).unwrap();
+/// }
+/// ```
+///
+/// [the module-level documentation]: self
+#[derive(Debug)]
+pub struct SparseImageStreamer {
+    image: Arc<Image>,
+    memory_type_index: u32,
+    aspects: ImageAspects,
+    tile_extent: [u32; 3],
+    tile_size: DeviceSize,
+    mip_tail_first_lod: u32,
+    tiles_per_block: DeviceSize,
+    mip_tail_memory: Vec<Arc<DeviceMemory>>,
+
+    blocks: Vec<Arc<DeviceMemory>>,
+    free_slots: Vec<TileSlot>,
+    released_slots: Vec<TileSlot>,
+    resident_tiles: HashMap<TileCoord, TileSlot>,
+
+    pending_binds: Vec<SparseImageMemoryBind>,
+    pending_opaque_binds: Vec<SparseImageOpaqueMemoryBind>,
+}
+
+impl SparseImageStreamer {
+    /// Creates a new `SparseImageStreamer` for `image`.
+    ///
+    /// This allocates and binds memory for the mip tail and metadata of the image. The bind
+    /// operations are included in the first [`BindSparseInfo`] returned by [`take_bind_info`].
+    ///
+    /// [`take_bind_info`]: Self::take_bind_info
+    pub fn new(
+        image: Arc<Image>,
+        create_info: SparseImageStreamerCreateInfo,
+    ) -> Result<Self, Validated<MemoryAllocatorError>> {
+        Self::validate_new(&image, &create_info)?;
+
+        let SparseImageStreamerCreateInfo {
+            memory_type_filter,
+            tiles_per_block,
+            _ne: _,
+        } = create_info;
+
+        let sparse_requirements = match image.memory() {
+            ImageMemory::Sparse(sparse_requirements) => sparse_requirements,
+            _ => unreachable!(),
+        };
+        let tile_requirements = sparse_requirements
+            .iter()
+            .find(|requirements| {
+                !requirements
+                    .format_properties
+                    .aspects
+                    .intersects(ImageAspects::METADATA)
+            })
+            .unwrap();
+        let memory_requirements = &image.memory_requirements()[0];
+        let memory_type_index = find_memory_type_index(
+            image.device().physical_device().memory_properties(),
+            memory_requirements.memory_type_bits,
+            memory_type_filter,
+        )
+        .ok_or(Validated::Error(MemoryAllocatorError::FindMemoryType))?;
+
+        let mut streamer = SparseImageStreamer {
+            image: image.clone(),
+            memory_type_index,
+            aspects: tile_requirements.format_properties.aspects,
+            tile_extent: tile_requirements.format_properties.image_granularity,
+            tile_size: memory_requirements.layout.alignment().as_devicesize(),
+            mip_tail_first_lod: tile_requirements.image_mip_tail_first_lod,
+            tiles_per_block: tiles_per_block as DeviceSize,
+            mip_tail_memory: Vec::new(),
+
+            blocks: Vec::new(),
+            free_slots: Vec::new(),
+            released_slots: Vec::new(),
+            resident_tiles: HashMap::default(),
+
+            pending_binds: Vec::new(),
+            pending_opaque_binds: Vec::new(),
+        };
+
+        for requirements in sparse_requirements {
+            if requirements.image_mip_tail_size == 0 {
+                continue;
+            }
+
+            let metadata = requirements
+                .format_properties
+                .aspects
+                .intersects(ImageAspects::METADATA);
+            let regions = if requirements
+                .format_properties
+                .flags
+                .intersects(SparseImageFormatFlags::SINGLE_MIPTAIL)
+            {
+                1
+            } else {
+                image.array_layers()
+            };
+            let memory = streamer
+                .allocate_memory(requirements.image_mip_tail_size * regions as DeviceSize)?;
+
+            for region in 0..regions as DeviceSize {
+                let stride = requirements.image_mip_tail_stride.unwrap_or(0);
+
+                streamer
+                    .pending_opaque_binds
+                    .push(SparseImageOpaqueMemoryBind {
+                        offset: requirements.image_mip_tail_offset + region * stride,
+                        size: requirements.image_mip_tail_size,
+                        memory: Some((memory.clone(), region * requirements.image_mip_tail_size)),
+                        metadata,
+                    });
+            }
+
+            streamer.mip_tail_memory.push(memory);
+        }
+
+        Ok(streamer)
+    }
+
+    fn validate_new(
+        image: &Image,
+        create_info: &SparseImageStreamerCreateInfo,
+    ) -> Result<(), Box<ValidationError>> {
+        create_info
+            .validate()
+            .map_err(|err| err.add_context("create_info"))?;
+
+        let sparse_requirements = match image.memory() {
+            ImageMemory::Sparse(sparse_requirements) if !sparse_requirements.is_empty() => {
+                sparse_requirements
+            }
+            _ => {
+                return Err(Box::new(ValidationError {
+                    context: "image".into(),
+                    problem: "was not created with the `ImageCreateFlags::SPARSE_RESIDENCY` \
+                        flag, or was not converted with `RawImage::into_sparse`"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        };
+
+        let tile_aspects_count = sparse_requirements
+            .iter()
+            .filter(|requirements| {
+                !requirements
+                    .format_properties
+                    .aspects
+                    .intersects(ImageAspects::METADATA)
+            })
+            .count();
+
+        if tile_aspects_count != 1 {
+            return Err(Box::new(ValidationError {
+                context: "image".into(),
+                problem: "has aspects with separate sparse image block shapes".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the image that is being streamed.
+    #[inline]
+    pub fn image(&self) -> &Arc<Image> {
+        &self.image
+    }
+
+    /// Returns the extent of a tile, in texels.
+    #[inline]
+    pub fn tile_extent(&self) -> [u32; 3] {
+        self.tile_extent
+    }
+
+    /// Returns the size of the memory of a tile, in bytes.
+    #[inline]
+    pub fn tile_size(&self) -> DeviceSize {
+        self.tile_size
+    }
+
+    /// Returns the first mip level that is part of the mip tail.
+    ///
+    /// Mip levels starting from this one are always resident, and are not divided into tiles.
+    #[inline]
+    pub fn mip_tail_first_lod(&self) -> u32 {
+        self.mip_tail_first_lod
+    }
+
+    /// Returns the number of tiles in each dimension of `mip_level`, or `None` if `mip_level` is
+    /// part of the mip tail.
+    pub fn tile_count(&self, mip_level: u32) -> Option<[u32; 3]> {
+        if mip_level >= min(self.mip_tail_first_lod, self.image.mip_levels()) {
+            return None;
+        }
+
+        let extent = mip_level_extent(self.image.extent(), mip_level)?;
+
+        Some([
+            extent[0].div_ceil(self.tile_extent[0]),
+            extent[1].div_ceil(self.tile_extent[1]),
+            extent[2].div_ceil(self.tile_extent[2]),
+        ])
+    }
+
+    /// Returns the tile that contains the texel at `texel` in `mip_level` and `array_layer`.
+    #[inline]
+    pub fn tile_at(&self, mip_level: u32, array_layer: u32, texel: [u32; 3]) -> TileCoord {
+        TileCoord {
+            mip_level,
+            array_layer,
+            tile: [
+                texel[0] / self.tile_extent[0],
+                texel[1] / self.tile_extent[1],
+                texel[2] / self.tile_extent[2],
+            ],
+        }
+    }
+
+    /// Returns whether memory is bound to `tile`, or will be bound once the pending bind
+    /// operations are executed.
+    #[inline]
+    pub fn is_resident(&self, tile: TileCoord) -> bool {
+        self.resident_tiles.contains_key(&tile)
+    }
+
+    /// Returns the number of tiles that are resident.
+    #[inline]
+    pub fn resident_tile_count(&self) -> usize {
+        self.resident_tiles.len()
+    }
+
+    /// Returns the total size of the memory that was allocated, including the mip tail.
+    pub fn allocated_size(&self) -> DeviceSize {
+        self.blocks
+            .iter()
+            .chain(&self.mip_tail_memory)
+            .map(|memory| memory.allocation_size())
+            .sum()
+    }
+
+    /// Makes `tile` resident, if it isn't already.
+    ///
+    /// Returns whether a bind operation was queued. The tile is only resident once the bind
+    /// operations returned by [`take_bind_info`] have been executed.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `tile` is out of bounds of the image, or is part of the mip tail.
+    ///
+    /// [`take_bind_info`]: Self::take_bind_info
+    pub fn request(&mut self, tile: TileCoord) -> Result<bool, Validated<MemoryAllocatorError>> {
+        let (offset, extent) = self.tile_region(tile);
+
+        if self.resident_tiles.contains_key(&tile) {
+            return Ok(false);
+        }
+
+        let slot = match self.free_slots.pop() {
+            Some(slot) => slot,
+            None => {
+                let memory = self.allocate_memory(self.tiles_per_block * self.tile_size)?;
+                let block = self.blocks.len();
+                self.blocks.push(memory);
+
+                // Hand out the slots in ascending order.
+                self.free_slots.extend(
+                    (1..self.tiles_per_block)
+                        .rev()
+                        .map(|index| TileSlot { block, index }),
+                );
+
+                TileSlot { block, index: 0 }
+            }
+        };
+
+        self.pending_binds.push(SparseImageMemoryBind {
+            aspects: self.aspects,
+            mip_level: tile.mip_level,
+            array_layer: tile.array_layer,
+            offset,
+            extent,
+            memory: Some((self.blocks[slot.block].clone(), slot.index * self.tile_size)),
+        });
+        self.resident_tiles.insert(tile, slot);
+
+        Ok(true)
+    }
+
+    /// Evicts `tile`, if it is resident.
+    ///
+    /// Returns whether an unbind operation was queued. The memory of the tile is reused for other
+    /// tiles only after the next call to [`take_bind_info`].
+    ///
+    /// [`take_bind_info`]: Self::take_bind_info
+    pub fn evict(&mut self, tile: TileCoord) -> bool {
+        let Some(slot) = self.resident_tiles.remove(&tile) else {
+            return false;
+        };
+
+        let (offset, extent) = self.tile_region(tile);
+        self.pending_binds.push(SparseImageMemoryBind {
+            aspects: self.aspects,
+            mip_level: tile.mip_level,
+            array_layer: tile.array_layer,
+            offset,
+            extent,
+            memory: None,
+        });
+        self.released_slots.push(slot);
+
+        true
+    }
+
+    /// Evicts all resident tiles for which `f` returns `false`.
+    pub fn retain(&mut self, mut f: impl FnMut(TileCoord) -> bool) {
+        let evicted: Vec<_> = self
+            .resident_tiles
+            .keys()
+            .copied()
+            .filter(|&tile| !f(tile))
+            .collect();
+
+        for tile in evicted {
+            self.evict(tile);
+        }
+    }
+
+    /// Takes the bind operations that were queued since the last call, or `None` if there are
+    /// none.
+    ///
+    /// The returned `BindSparseInfo` must be executed on a queue that supports sparse binding
+    /// before the tiles that were requested are accessed, and the accesses to evicted tiles must
+    /// have completed before it is executed. Memory of evicted tiles becomes available for reuse
+    /// after this call, so the bind operations must also be executed in the order they were
+    /// taken.
+    pub fn take_bind_info(&mut self) -> Option<BindSparseInfo> {
+        self.free_slots.append(&mut self.released_slots);
+
+        if self.pending_binds.is_empty() && self.pending_opaque_binds.is_empty() {
+            return None;
+        }
+
+        let mut bind_info = BindSparseInfo::default();
+
+        if !self.pending_opaque_binds.is_empty() {
+            bind_info.image_opaque_binds =
+                vec![(self.image.clone(), self.pending_opaque_binds.split_off(0))];
+        }
+
+        if !self.pending_binds.is_empty() {
+            bind_info.image_binds = vec![(self.image.clone(), self.pending_binds.split_off(0))];
+        }
+
+        Some(bind_info)
+    }
+
+    fn tile_region(&self, tile: TileCoord) -> ([u32; 3], [u32; 3]) {
+        let tile_count = self
+            .tile_count(tile.mip_level)
+            .expect("`tile.mip_level` is part of the mip tail");
+        assert!(tile.array_layer < self.image.array_layers());
+        assert!((0..3).all(|i| tile.tile[i] < tile_count[i]));
+
+        let extent = mip_level_extent(self.image.extent(), tile.mip_level).unwrap();
+        let offset = [
+            tile.tile[0] * self.tile_extent[0],
+            tile.tile[1] * self.tile_extent[1],
+            tile.tile[2] * self.tile_extent[2],
+        ];
+
+        // Tiles at the edge of the mip level are clamped to its extent.
+        (
+            offset,
+            [
+                min(self.tile_extent[0], extent[0] - offset[0]),
+                min(self.tile_extent[1], extent[1] - offset[1]),
+                min(self.tile_extent[2], extent[2] - offset[2]),
+            ],
+        )
+    }
+
+    fn allocate_memory(
+        &self,
+        allocation_size: DeviceSize,
+    ) -> Result<Arc<DeviceMemory>, Validated<MemoryAllocatorError>> {
+        DeviceMemory::allocate(
+            self.image.device().clone(),
+            MemoryAllocateInfo {
+                allocation_size,
+                memory_type_index: self.memory_type_index,
+                ..Default::default()
+            },
+        )
+        .map(Arc::new)
+        .map_err(|err| Validated::Error(MemoryAllocatorError::AllocateDeviceMemory(err)))
+    }
+}
+
+unsafe impl DeviceOwned for SparseImageStreamer {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.image.device()
+    }
+}
+
+/// Parameters to create a new `SparseImageStreamer`.
+#[derive(Clone, Debug)]
+pub struct SparseImageStreamerCreateInfo {
+    /// Filter used to find the memory type for the tiles and the mip tail.
+    ///
+    /// The default value is [`MemoryTypeFilter::PREFER_DEVICE`].
+    pub memory_type_filter: MemoryTypeFilter,
+
+    /// The number of tiles that each block of device memory can hold.
+    ///
+    /// Memory for tiles is allocated in blocks of this many tiles at a time. Blocks are never
+    /// freed while the streamer is alive, so this should be chosen based on how much memory the
+    /// resident tiles are expected to take up.
+    ///
+    /// The default value is `256`.
+    pub tiles_per_block: u32,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for SparseImageStreamerCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+            tiles_per_block: 256,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+impl SparseImageStreamerCreateInfo {
+    pub(crate) fn validate(&self) -> Result<(), Box<ValidationError>> {
+        if self.tiles_per_block == 0 {
+            return Err(Box::new(ValidationError {
+                context: "tiles_per_block".into(),
+                problem: "is zero".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+/// Identifies a tile of a sparse image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TileCoord {
+    /// The mip level that the tile is in.
+    pub mip_level: u32,
+
+    /// The array layer that the tile is in.
+    pub array_layer: u32,
+
+    /// The coordinates of the tile within the mip level, in units of tiles.
+    pub tile: [u32; 3],
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TileSlot {
+    block: usize,
+    index: DeviceSize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SparseImageStreamer, SparseImageStreamerCreateInfo};
+    use crate::{
+        format::Format,
+        image::{sys::RawImage, ImageCreateFlags, ImageCreateInfo, ImageType, ImageUsage},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn requires_sparse_residency() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let raw_image = RawImage::new(
+            device,
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: Format::R8G8B8A8_UNORM,
+                extent: [64, 64, 1],
+                usage: ImageUsage::SAMPLED,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(raw_image.flags().is_empty());
+
+        let image = Arc::new(unsafe { raw_image.assume_bound() });
+        assert!(SparseImageStreamer::new(image, SparseImageStreamerCreateInfo::default()).is_err());
+    }
+
+    #[test]
+    fn request_and_evict() {
+        let (device, _) = gfx_dev_and_queue!(sparse_binding, sparse_residency_image2_d);
+
+        let raw_image = RawImage::new(
+            device,
+            ImageCreateInfo {
+                flags: ImageCreateFlags::SPARSE_BINDING | ImageCreateFlags::SPARSE_RESIDENCY,
+                image_type: ImageType::Dim2d,
+                format: Format::R8G8B8A8_UNORM,
+                extent: [1024, 1024, 1],
+                mip_levels: 11,
+                usage: ImageUsage::SAMPLED,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let image = Arc::new(unsafe { raw_image.into_sparse() }.unwrap());
+
+        let mut streamer = SparseImageStreamer::new(
+            image,
+            SparseImageStreamerCreateInfo {
+                tiles_per_block: 4,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let tile = streamer.tile_at(0, 0, [0, 0, 0]);
+        assert!(streamer.request(tile).unwrap());
+        assert!(!streamer.request(tile).unwrap());
+        assert!(streamer.is_resident(tile));
+
+        let bind_info = streamer.take_bind_info().unwrap();
+        assert_eq!(bind_info.image_binds[0].1.len(), 1);
+        assert!(streamer.take_bind_info().is_none());
+
+        assert!(streamer.evict(tile));
+        assert!(!streamer.evict(tile));
+        assert_eq!(streamer.resident_tile_count(), 0);
+        assert!(streamer.take_bind_info().is_some());
+    }
+}
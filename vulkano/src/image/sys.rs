@@ -17,7 +17,7 @@ use super::{
 use crate::format::DrmFormatModifierProperties;
 use crate::{
     cache::OnceCache,
-    device::{Device, DeviceOwned},
+    device::{Device, DeviceOwned, ExportMetalObjectTypes},
     format::{ChromaSampling, Format, FormatFeatures},
     image::{
         max_mip_levels, ImageDrmFormatModifierInfo, ImageFormatInfo, ImageFormatProperties,
@@ -35,7 +35,7 @@ use crate::{
     VulkanObject,
 };
 use smallvec::{smallvec, SmallVec};
-use std::{mem::MaybeUninit, num::NonZeroU64, ptr, sync::Arc};
+use std::{ffi::c_void, mem::MaybeUninit, num::NonZeroU64, ptr, sync::Arc};
 
 /// A raw image, with no memory backing it.
 ///
@@ -68,6 +68,7 @@ pub struct RawImage {
     initial_layout: ImageLayout,
     drm_format_modifier: Option<(u64, u32)>,
     external_memory_handle_types: ExternalMemoryHandleTypes,
+    export_metal_object_types: ExportMetalObjectTypes,
 
     memory_requirements: SmallVec<[MemoryRequirements; 4]>,
     needs_destruction: bool, // `vkDestroyImage` is called only if true.
@@ -77,13 +78,19 @@ pub struct RawImage {
 impl RawImage {
     /// Creates a new `RawImage`.
     #[inline]
+    #[track_caller]
     pub fn new(
         device: Arc<Device>,
         create_info: ImageCreateInfo,
     ) -> Result<RawImage, Validated<VulkanError>> {
         Self::validate_new(&device, &create_info)?;
 
-        unsafe { Ok(RawImage::new_unchecked(device, create_info)?) }
+        let raw_image = unsafe { RawImage::new_unchecked(device, create_info)? };
+        raw_image
+            .device()
+            .set_auto_debug_utils_object_name(&raw_image, "Image");
+
+        Ok(raw_image)
     }
 
     fn validate_new(
@@ -120,6 +127,7 @@ impl RawImage {
             ref drm_format_modifiers,
             ref drm_format_modifier_plane_layouts,
             external_memory_handle_types,
+            export_metal_object_types,
             _ne: _,
         } = &create_info;
 
@@ -219,6 +227,14 @@ impl RawImage {
             create_info_vk.p_next = <*const _>::cast(next);
         }
 
+        let mut export_metal_object_create_infos_vk =
+            export_metal_object_types.to_vulkan_create_infos();
+
+        for next in &mut export_metal_object_create_infos_vk {
+            next.p_next = create_info_vk.p_next;
+            create_info_vk.p_next = <*const _>::cast(next);
+        }
+
         if !view_formats.is_empty() {
             format_list_view_formats_vk = view_formats
                 .iter()
@@ -319,6 +335,7 @@ impl RawImage {
             drm_format_modifiers: _,
             drm_format_modifier_plane_layouts: _,
             external_memory_handle_types,
+            export_metal_object_types,
             _ne: _,
         } = create_info;
 
@@ -366,7 +383,7 @@ impl RawImage {
             smallvec![]
         };
 
-        Ok(RawImage {
+        let raw_image = RawImage {
             handle,
             device: InstanceOwnedDebugWrapper(device),
             id: Self::next_id(),
@@ -387,11 +404,17 @@ impl RawImage {
             sharing,
             drm_format_modifier,
             external_memory_handle_types,
+            export_metal_object_types,
 
             memory_requirements,
             needs_destruction,
             subresource_layout: OnceCache::new(),
-        })
+        };
+        if let Some(resources) = raw_image.device.resources() {
+            resources.add_image(&raw_image);
+        }
+
+        Ok(raw_image)
     }
 
     unsafe fn get_memory_requirements(
@@ -508,8 +531,11 @@ impl RawImage {
         }
     }
 
-    #[allow(dead_code)] // Remove when sparse memory is implemented
-    fn get_sparse_memory_requirements(&self) -> Vec<SparseImageMemoryRequirements> {
+    /// Returns the sparse memory requirements for this image, one element for each aspect that
+    /// has its own requirements.
+    ///
+    /// This is only meaningful for images that were created with the `sparse_residency` flag.
+    pub fn sparse_memory_requirements(&self) -> Vec<SparseImageMemoryRequirements> {
         let device = &self.device;
 
         unsafe {
@@ -842,9 +868,14 @@ impl RawImage {
             // VUID-VkBindImageMemoryInfo-image-07460
             // Ensured by taking ownership of `RawImage`.
 
-            // VUID-VkBindImageMemoryInfo-image-01045
-            // Currently ensured by not having sparse binding flags, but this needs to be checked
-            // once those are enabled.
+            if self.flags.intersects(ImageCreateFlags::SPARSE_BINDING) {
+                return Err(Box::new(ValidationError {
+                    context: "self.flags()".into(),
+                    problem: "contains `ImageCreateFlags::SPARSE_BINDING`".into(),
+                    vuids: &["VUID-VkBindImageMemoryInfo-image-01045"],
+                    ..Default::default()
+                }));
+            }
 
             // VUID-VkBindImageMemoryInfo-memoryOffset-01046
             // Assume that `allocation` was created correctly.
@@ -930,13 +961,32 @@ impl RawImage {
                 }
             }
 
-            if memory_type
+            if self.flags.intersects(ImageCreateFlags::PROTECTED) {
+                if !memory_type
+                    .property_flags
+                    .intersects(MemoryPropertyFlags::PROTECTED)
+                {
+                    return Err(Box::new(ValidationError {
+                        problem: format!(
+                            "`self.flags()` contains `ImageCreateFlags::PROTECTED`, but the \
+                            `property_flags` of the memory type of \
+                            `allocations[{}].device_memory()` does not contain \
+                            `MemoryPropertyFlags::PROTECTED`",
+                            index
+                        )
+                        .into(),
+                        vuids: &["VUID-VkBindImageMemoryInfo-None-01902"],
+                        ..Default::default()
+                    }));
+                }
+            } else if memory_type
                 .property_flags
                 .intersects(MemoryPropertyFlags::PROTECTED)
             {
                 return Err(Box::new(ValidationError {
                     problem: format!(
-                        "the `property_flags` of the memory type of \
+                        "`self.flags()` does not contain `ImageCreateFlags::PROTECTED`, but the \
+                        `property_flags` of the memory type of \
                         `allocations[{}].device_memory()` contains \
                         `MemoryPropertyFlags::PROTECTED`",
                         index
@@ -1080,11 +1130,57 @@ impl RawImage {
     ///
     /// - The image must be backed by suitable memory allocations.
     pub unsafe fn assume_bound(self) -> Image {
+        let layout = self.default_layout();
+
+        Image::from_raw(self, ImageMemory::External, layout)
+    }
+
+    /// Converts this image into an [`Image`] whose memory is bound with sparse bind operations.
+    ///
+    /// No memory is bound to the image at this point. Memory must be bound with sparse bind
+    /// operations on a queue, before the parts of the image that are accessed can be used. For
+    /// images that don't have the `sparse_residency` flag, this means the whole image.
+    ///
+    /// # Safety
+    ///
+    /// - As with [`assume_bound`], vulkano doesn't track the memory that is bound to the image.
+    ///   The parts of the image that are accessed must be backed by suitable memory allocations,
+    ///   bound with sparse bind operations, at the time they are accessed.
+    ///
+    /// [`assume_bound`]: Self::assume_bound
+    pub unsafe fn into_sparse(self) -> Result<Image, (Box<ValidationError>, RawImage)> {
+        if !self.flags.intersects(ImageCreateFlags::SPARSE_BINDING) {
+            return Err((
+                Box::new(ValidationError {
+                    context: "self.flags()".into(),
+                    problem: "does not contain `ImageCreateFlags::SPARSE_BINDING`".into(),
+                    ..Default::default()
+                }),
+                self,
+            ));
+        }
+
+        let sparse_memory_requirements =
+            if self.flags.intersects(ImageCreateFlags::SPARSE_RESIDENCY) {
+                self.sparse_memory_requirements()
+            } else {
+                Vec::new()
+            };
+        let layout = self.default_layout();
+
+        Ok(Image::from_raw(
+            self,
+            ImageMemory::Sparse(sparse_memory_requirements),
+            layout,
+        ))
+    }
+
+    fn default_layout(&self) -> ImageLayout {
         let usage = self
             .usage
             .difference(ImageUsage::TRANSFER_SRC | ImageUsage::TRANSFER_DST);
 
-        let layout = if usage.intersects(ImageUsage::SAMPLED | ImageUsage::INPUT_ATTACHMENT)
+        if usage.intersects(ImageUsage::SAMPLED | ImageUsage::INPUT_ATTACHMENT)
             && usage
                 .difference(ImageUsage::SAMPLED | ImageUsage::INPUT_ATTACHMENT)
                 .is_empty()
@@ -1102,9 +1198,7 @@ impl RawImage {
             ImageLayout::DepthStencilAttachmentOptimal
         } else {
             ImageLayout::General
-        };
-
-        Image::from_raw(self, ImageMemory::External, layout)
+        }
     }
 
     /// # Safety
@@ -1233,29 +1327,7 @@ impl RawImage {
             return Err((VulkanError::from(err), self, allocations.into_iter()));
         }
 
-        let usage = self
-            .usage
-            .difference(ImageUsage::TRANSFER_SRC | ImageUsage::TRANSFER_DST);
-
-        let layout = if usage.intersects(ImageUsage::SAMPLED | ImageUsage::INPUT_ATTACHMENT)
-            && usage
-                .difference(ImageUsage::SAMPLED | ImageUsage::INPUT_ATTACHMENT)
-                .is_empty()
-        {
-            ImageLayout::ShaderReadOnlyOptimal
-        } else if usage.intersects(ImageUsage::COLOR_ATTACHMENT)
-            && usage.difference(ImageUsage::COLOR_ATTACHMENT).is_empty()
-        {
-            ImageLayout::ColorAttachmentOptimal
-        } else if usage.intersects(ImageUsage::DEPTH_STENCIL_ATTACHMENT)
-            && usage
-                .difference(ImageUsage::DEPTH_STENCIL_ATTACHMENT)
-                .is_empty()
-        {
-            ImageLayout::DepthStencilAttachmentOptimal
-        } else {
-            ImageLayout::General
-        };
+        let layout = self.default_layout();
 
         Ok(Image::from_raw(
             self,
@@ -1374,6 +1446,12 @@ impl RawImage {
         self.external_memory_handle_types
     }
 
+    /// Returns the types of Metal objects that can be exported from this image.
+    #[inline]
+    pub fn export_metal_object_types(&self) -> ExportMetalObjectTypes {
+        self.export_metal_object_types
+    }
+
     /// Returns an `ImageSubresourceLayers` covering the first mip level of the image. All aspects
     /// of the image are selected, or `plane0` if the image is multi-planar.
     #[inline]
@@ -1672,11 +1750,126 @@ impl RawImage {
             },
         )
     }
+
+    /// Returns the `MTLTexture` that backs the given plane of the image, as an `id<MTLTexture>`
+    /// pointer.
+    ///
+    /// `plane` must be 0 if the image does not have a multi-planar format.
+    ///
+    /// The image must have been created with [`ExportMetalObjectTypes::METAL_TEXTURE`] in
+    /// [`ImageCreateInfo::export_metal_object_types`].
+    #[inline]
+    pub fn export_metal_texture(&self, plane: usize) -> Result<*mut c_void, Box<ValidationError>> {
+        self.validate_export_metal_texture(plane)?;
+
+        Ok(unsafe { self.export_metal_texture_unchecked(plane) })
+    }
+
+    fn validate_export_metal_texture(&self, plane: usize) -> Result<(), Box<ValidationError>> {
+        if !self
+            .export_metal_object_types
+            .intersects(ExportMetalObjectTypes::METAL_TEXTURE)
+        {
+            return Err(Box::new(ValidationError {
+                context: "self.export_metal_object_types()".into(),
+                problem: "does not contain `ExportMetalObjectTypes::METAL_TEXTURE`".into(),
+                ..Default::default()
+            }));
+        }
+
+        if plane >= self.format.planes().len().max(1) {
+            return Err(Box::new(ValidationError {
+                context: "plane".into(),
+                problem: "is not less than the number of planes in the format of the image".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn export_metal_texture_unchecked(&self, plane: usize) -> *mut c_void {
+        let plane_vk = match plane {
+            0 => ash::vk::ImageAspectFlags::PLANE_0,
+            1 => ash::vk::ImageAspectFlags::PLANE_1,
+            2 => ash::vk::ImageAspectFlags::PLANE_2,
+            _ => unreachable!(),
+        };
+
+        let mut texture_info_vk = ash::vk::ExportMetalTextureInfoEXT {
+            image: self.handle,
+            plane: plane_vk,
+            ..Default::default()
+        };
+        let mut objects_info_vk = ash::vk::ExportMetalObjectsInfoEXT {
+            p_next: <*mut _>::cast(&mut texture_info_vk),
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        (fns.ext_metal_objects.export_metal_objects_ext)(
+            self.device.handle(),
+            &mut objects_info_vk,
+        );
+
+        texture_info_vk.mtl_texture
+    }
+
+    /// Returns the `IOSurface` that backs the image, as an `IOSurfaceRef` pointer.
+    ///
+    /// The image must have been created with [`ExportMetalObjectTypes::METAL_IOSURFACE`] in
+    /// [`ImageCreateInfo::export_metal_object_types`].
+    #[inline]
+    pub fn export_metal_io_surface(&self) -> Result<*mut c_void, Box<ValidationError>> {
+        self.validate_export_metal_io_surface()?;
+
+        Ok(unsafe { self.export_metal_io_surface_unchecked() })
+    }
+
+    fn validate_export_metal_io_surface(&self) -> Result<(), Box<ValidationError>> {
+        if !self
+            .export_metal_object_types
+            .intersects(ExportMetalObjectTypes::METAL_IOSURFACE)
+        {
+            return Err(Box::new(ValidationError {
+                context: "self.export_metal_object_types()".into(),
+                problem: "does not contain `ExportMetalObjectTypes::METAL_IOSURFACE`".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn export_metal_io_surface_unchecked(&self) -> *mut c_void {
+        let mut io_surface_info_vk = ash::vk::ExportMetalIOSurfaceInfoEXT {
+            image: self.handle,
+            ..Default::default()
+        };
+        let mut objects_info_vk = ash::vk::ExportMetalObjectsInfoEXT {
+            p_next: <*mut _>::cast(&mut io_surface_info_vk),
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        (fns.ext_metal_objects.export_metal_objects_ext)(
+            self.device.handle(),
+            &mut objects_info_vk,
+        );
+
+        io_surface_info_vk.io_surface.cast()
+    }
 }
 
 impl Drop for RawImage {
     #[inline]
     fn drop(&mut self) {
+        if let Some(resources) = self.device.resources() {
+            resources.remove_image(self);
+        }
+
         if !self.needs_destruction {
             return;
         }
@@ -1853,6 +2046,16 @@ pub struct ImageCreateInfo {
     /// The default value is empty.
     pub external_memory_handle_types: ExternalMemoryHandleTypes,
 
+    /// The types of Metal objects that are going to be exported from the image.
+    ///
+    /// Only [`ExportMetalObjectTypes::METAL_TEXTURE`] and
+    /// [`ExportMetalObjectTypes::METAL_IOSURFACE`] can be specified. If this is not empty, the
+    /// [`ext_metal_objects`](crate::device::DeviceExtensions::ext_metal_objects) extension must
+    /// be enabled on the device.
+    ///
+    /// The default value is empty.
+    pub export_metal_object_types: ExportMetalObjectTypes,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -1876,6 +2079,7 @@ impl Default for ImageCreateInfo {
             external_memory_handle_types: ExternalMemoryHandleTypes::empty(),
             drm_format_modifiers: Vec::new(),
             drm_format_modifier_plane_layouts: Vec::new(),
+            export_metal_object_types: ExportMetalObjectTypes::empty(),
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -1900,6 +2104,7 @@ impl ImageCreateInfo {
             ref drm_format_modifiers,
             ref drm_format_modifier_plane_layouts,
             external_memory_handle_types,
+            export_metal_object_types,
             _ne: _,
         } = self;
 
@@ -2542,6 +2747,146 @@ impl ImageCreateInfo {
 
         /* Check flags requirements */
 
+        if flags.intersects(ImageCreateFlags::PROTECTED) {
+            if !device.enabled_features().protected_memory {
+                return Err(Box::new(ValidationError {
+                    context: "flags".into(),
+                    problem: "contains `ImageCreateFlags::PROTECTED`".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "protected_memory",
+                    )])]),
+                    vuids: &["VUID-VkImageCreateInfo-flags-01890"],
+                }));
+            }
+
+            if flags
+                .intersects(ImageCreateFlags::SPARSE_BINDING | ImageCreateFlags::SPARSE_RESIDENCY)
+            {
+                return Err(Box::new(ValidationError {
+                    context: "flags".into(),
+                    problem: "contains `ImageCreateFlags::PROTECTED`, but also contains \
+                        `ImageCreateFlags::SPARSE_BINDING` or `ImageCreateFlags::SPARSE_RESIDENCY`"
+                        .into(),
+                    vuids: &["VUID-VkImageCreateInfo-None-01891"],
+                    ..Default::default()
+                }));
+            }
+        }
+
+        if flags.intersects(ImageCreateFlags::SPARSE_BINDING)
+            && !device.enabled_features().sparse_binding
+        {
+            return Err(Box::new(ValidationError {
+                context: "flags".into(),
+                problem: "contains `ImageCreateFlags::SPARSE_BINDING`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "sparse_binding",
+                )])]),
+                vuids: &["VUID-VkImageCreateInfo-flags-00969"],
+            }));
+        }
+
+        if flags.intersects(ImageCreateFlags::SPARSE_RESIDENCY) {
+            if !flags.intersects(ImageCreateFlags::SPARSE_BINDING) {
+                return Err(Box::new(ValidationError {
+                    problem: "`flags` contains `ImageCreateFlags::SPARSE_RESIDENCY`, but does \
+                        not also contain `ImageCreateFlags::SPARSE_BINDING`"
+                        .into(),
+                    vuids: &["VUID-VkImageCreateInfo-flags-00987"],
+                    ..Default::default()
+                }));
+            }
+
+            if tiling == ImageTiling::Linear {
+                return Err(Box::new(ValidationError {
+                    problem: "`flags` contains `ImageCreateFlags::SPARSE_RESIDENCY`, but \
+                        `tiling` is `ImageTiling::Linear`"
+                        .into(),
+                    vuids: &["VUID-VkImageCreateInfo-tiling-04121"],
+                    ..Default::default()
+                }));
+            }
+
+            match image_type {
+                ImageType::Dim1d => {
+                    return Err(Box::new(ValidationError {
+                        problem: "`flags` contains `ImageCreateFlags::SPARSE_RESIDENCY`, but \
+                            `image_type` is `ImageType::Dim1d`"
+                            .into(),
+                        vuids: &["VUID-VkImageCreateInfo-imageType-00970"],
+                        ..Default::default()
+                    }));
+                }
+                ImageType::Dim2d => {
+                    if !device.enabled_features().sparse_residency_image2_d {
+                        return Err(Box::new(ValidationError {
+                            problem: "`flags` contains `ImageCreateFlags::SPARSE_RESIDENCY`, and \
+                                `image_type` is `ImageType::Dim2d`"
+                                .into(),
+                            requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                                Requires::DeviceFeature("sparse_residency_image2_d"),
+                            ])]),
+                            vuids: &["VUID-VkImageCreateInfo-imageType-00971"],
+                            ..Default::default()
+                        }));
+                    }
+
+                    let enabled_features = device.enabled_features();
+                    let (supported, feature, vuid) = match samples {
+                        SampleCount::Sample1 => (true, "", ""),
+                        SampleCount::Sample2 => (
+                            enabled_features.sparse_residency2_samples,
+                            "sparse_residency2_samples",
+                            "VUID-VkImageCreateInfo-imageType-00973",
+                        ),
+                        SampleCount::Sample4 => (
+                            enabled_features.sparse_residency4_samples,
+                            "sparse_residency4_samples",
+                            "VUID-VkImageCreateInfo-imageType-00974",
+                        ),
+                        SampleCount::Sample8 => (
+                            enabled_features.sparse_residency8_samples,
+                            "sparse_residency8_samples",
+                            "VUID-VkImageCreateInfo-imageType-00975",
+                        ),
+                        SampleCount::Sample16 => (
+                            enabled_features.sparse_residency16_samples,
+                            "sparse_residency16_samples",
+                            "VUID-VkImageCreateInfo-imageType-00976",
+                        ),
+                        SampleCount::Sample32 | SampleCount::Sample64 => (true, "", ""),
+                    };
+
+                    if !supported {
+                        return Err(Box::new(ValidationError {
+                            problem: "`flags` contains `ImageCreateFlags::SPARSE_RESIDENCY`, and \
+                                `samples` is not `SampleCount::Sample1`"
+                                .into(),
+                            requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                                Requires::DeviceFeature(feature),
+                            ])]),
+                            vuids: &[vuid],
+                            ..Default::default()
+                        }));
+                    }
+                }
+                ImageType::Dim3d => {
+                    if !device.enabled_features().sparse_residency_image3_d {
+                        return Err(Box::new(ValidationError {
+                            problem: "`flags` contains `ImageCreateFlags::SPARSE_RESIDENCY`, and \
+                                `image_type` is `ImageType::Dim3d`"
+                                .into(),
+                            requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                                Requires::DeviceFeature("sparse_residency_image3_d"),
+                            ])]),
+                            vuids: &["VUID-VkImageCreateInfo-imageType-00972"],
+                            ..Default::default()
+                        }));
+                    }
+                }
+            }
+        }
+
         if flags.intersects(ImageCreateFlags::CUBE_COMPATIBLE) {
             if image_type != ImageType::Dim2d {
                 return Err(Box::new(ValidationError {
@@ -2855,6 +3200,31 @@ impl ImageCreateInfo {
             }
         }
 
+        if !export_metal_object_types.is_empty() {
+            if !device.enabled_extensions().ext_metal_objects {
+                return Err(Box::new(ValidationError {
+                    context: "export_metal_object_types".into(),
+                    problem: "is not empty".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                        "ext_metal_objects",
+                    )])]),
+                    ..Default::default()
+                }));
+            }
+
+            if !(ExportMetalObjectTypes::METAL_TEXTURE | ExportMetalObjectTypes::METAL_IOSURFACE)
+                .contains(export_metal_object_types)
+            {
+                return Err(Box::new(ValidationError {
+                    context: "export_metal_object_types".into(),
+                    problem: "contains types other than `ExportMetalObjectTypes::METAL_TEXTURE` \
+                        and `ExportMetalObjectTypes::METAL_IOSURFACE`"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
         /*
             Some device limits can be exceeded, but only for particular image configurations, which
             must be queried with `image_format_properties`. See:
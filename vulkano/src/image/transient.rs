@@ -0,0 +1,431 @@
+//! Allocation of transient images whose memory is aliased.
+//!
+//! Render targets that only live for part of a frame, such as the intermediate targets of a
+//! post-processing chain, often don't need memory of their own: two images that are never in use
+//! at the same time can occupy the same memory. [`TransientImageAllocator`] takes a list of image
+//! descriptions along with their *lifetimes*, and places the images within shared
+//! [`DeviceMemory`] blocks such that images whose lifetimes overlap never overlap in memory.
+//!
+//! A lifetime is an inclusive range of abstract *pass indices*. What a pass is depends on the
+//! application: it could be a render pass, a task of a task graph in execution order, or any other
+//! ordered step of the frame. Two images whose lifetimes share any pass index are considered to be
+//! in use at the same time.
+//!
+//! # Aliasing and image contents
+//!
+//! Because the memory of an image may have been written through another image since the image was
+//! last used, the contents of aliased images are undefined at the start of their lifetime. Each
+//! aliased image must be transitioned from [`ImageLayout::Undefined`] (discarding its contents)
+//! the first time it is used within its lifetime, and the accesses to the images sharing the
+//! memory must be synchronized with each other, as if they were the same resource.
+//!
+//! [`DeviceMemory`]: crate::memory::DeviceMemory
+//! [`ImageLayout::Undefined`]: super::ImageLayout::Undefined
+
+use super::{sys::RawImage, AllocateImageError, Image, ImageCreateFlags, ImageCreateInfo};
+use crate::{
+    device::{Device, DeviceOwned},
+    image::ImageTiling,
+    memory::{
+        allocator::{align_up, find_memory_type_index, MemoryAllocatorError, MemoryTypeFilter},
+        DeviceMemory, MemoryAllocateInfo, ResourceMemory,
+    },
+    DeviceSize, Validated,
+};
+use std::{
+    cmp::max,
+    ops::{Index, RangeInclusive},
+    sync::Arc,
+};
+
+/// Places transient images in shared memory, aliasing the memory of images whose lifetimes don't
+/// overlap.
+///
+/// See [the module-level documentation] for more details.
+///
+/// # Examples
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use vulkano::{
+/// #     device::Device,
+/// #     format::Format,
+/// #     image::{
+/// #         transient::TransientImageAllocator, ImageCreateInfo, ImageType, ImageUsage,
+/// #     },
+/// #     memory::allocator::MemoryTypeFilter,
+/// # };
+/// # let device: Arc<Device> = return;
+/// #
+/// let create_info = ImageCreateInfo {
+///     image_type: ImageType::Dim2d,
+///     format: Format::R16G16B16A16_SFLOAT,
+///     extent: [3840, 2160, 1],
+///     usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED,
+///     ..Default::default()
+/// };
+///
+/// let mut allocator = TransientImageAllocator::new(device.clone());
+///
+/// // The bloom target is written in pass 0 and read in pass 1, the tonemapping target is written
+/// // in pass 1 and read in pass 2, and the FXAA target is written in pass 2 and read in pass 3.
+/// // The bloom and FXAA targets are never in use at the same time, so they can share memory.
+/// let bloom = allocator.add_image(create_info.clone(), 0..=1);
+/// let tonemap = allocator.add_image(create_info.clone(), 1..=2);
+/// let fxaa = allocator.add_image(create_info, 2..=3);
+///
+/// // SAFETY: Each image is transitioned from `ImageLayout::Undefined` at the start of its
+/// // lifetime, and the passes are synchronized with each other.
+/// let images = unsafe { allocator.allocate(MemoryTypeFilter::PREFER_DEVICE) }.unwrap();
+/// let bloom_image = &images[bloom];
+/// ```
+///
+/// [the module-level documentation]: self
+#[derive(Debug)]
+pub struct TransientImageAllocator {
+    device: Arc<Device>,
+    images: Vec<TransientImageInfo>,
+}
+
+#[derive(Debug)]
+struct TransientImageInfo {
+    create_info: ImageCreateInfo,
+    lifetime: RangeInclusive<u32>,
+}
+
+impl TransientImageAllocator {
+    /// Creates a new `TransientImageAllocator` with no images.
+    #[inline]
+    pub fn new(device: Arc<Device>) -> Self {
+        TransientImageAllocator {
+            device,
+            images: Vec::new(),
+        }
+    }
+
+    /// Adds an image to be allocated, which is in use during the passes in `lifetime`.
+    ///
+    /// Returns an ID that can be used to index the [`TransientImages`] returned by
+    /// [`allocate`].
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.flags` contains [`ImageCreateFlags::DISJOINT`].
+    /// - Panics if `lifetime` is empty.
+    ///
+    /// [`allocate`]: Self::allocate
+    pub fn add_image(
+        &mut self,
+        create_info: ImageCreateInfo,
+        lifetime: RangeInclusive<u32>,
+    ) -> TransientImageId {
+        assert!(!create_info.flags.intersects(ImageCreateFlags::DISJOINT));
+        assert!(!lifetime.is_empty());
+
+        let id = TransientImageId(self.images.len());
+        self.images.push(TransientImageInfo {
+            create_info,
+            lifetime,
+        });
+
+        id
+    }
+
+    /// Creates all the images that were added, allocates the memory for them, and binds the
+    /// images to the memory.
+    ///
+    /// Images are grouped by the memory types that they support, and each group gets a single
+    /// [`DeviceMemory`] block of a memory type chosen with `memory_type_filter`. Within a block,
+    /// each image is placed at the lowest offset that doesn't overlap any image with an
+    /// overlapping lifetime.
+    ///
+    /// # Safety
+    ///
+    /// Images whose lifetimes don't overlap may share memory, which vulkano doesn't track. For each
+    /// of the returned images:
+    ///
+    /// - The image must only be accessed during the passes in its lifetime.
+    /// - The first access within its lifetime must transition the image from
+    ///   [`ImageLayout::Undefined`], as its contents may have been overwritten through another
+    ///   image in the meantime.
+    /// - All accesses to the image must be synchronized with the accesses to any other image that
+    ///   shares its memory, as if they were accesses to the same resource.
+    ///
+    /// [`DeviceMemory`]: crate::memory::DeviceMemory
+    /// [`ImageLayout::Undefined`]: super::ImageLayout::Undefined
+    pub unsafe fn allocate(
+        &self,
+        memory_type_filter: MemoryTypeFilter,
+    ) -> Result<TransientImages, Validated<AllocateImageError>> {
+        let raw_images = self
+            .images
+            .iter()
+            .map(|image| {
+                RawImage::new(self.device.clone(), image.create_info.clone()).map_err(|err| {
+                    match err {
+                        Validated::Error(err) => {
+                            Validated::Error(AllocateImageError::CreateImage(err))
+                        }
+                        Validated::ValidationError(err) => err.into(),
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut placements = vec![Placement::default(); raw_images.len()];
+        let mut groups: Vec<MemoryGroup> = Vec::new();
+
+        // Place the largest images first, which tends to give the tightest packing.
+        let mut order = (0..raw_images.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&index| {
+            std::cmp::Reverse(raw_images[index].memory_requirements()[0].layout.size())
+        });
+
+        for index in order {
+            let raw_image = &raw_images[index];
+            let requirements = raw_image.memory_requirements()[0];
+            let tiling = raw_image.tiling();
+            let alignment = requirements.layout.alignment();
+            let size = requirements.layout.size();
+
+            // Images with different tilings are kept in separate groups, so that we don't need to
+            // account for the buffer-image granularity.
+            let group_index = match groups.iter().position(|group| {
+                group.memory_type_bits & requirements.memory_type_bits != 0
+                    && group.tiling == tiling
+            }) {
+                Some(group_index) => group_index,
+                None => {
+                    groups.push(MemoryGroup {
+                        memory_type_bits: requirements.memory_type_bits,
+                        tiling,
+                        size: 0,
+                        unaliased_size: 0,
+                        members: Vec::new(),
+                    });
+
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[group_index];
+            let lifetime = &self.images[index].lifetime;
+
+            // The images of the group that are in use at the same time as this image.
+            let mut conflicts = group
+                .members
+                .iter()
+                .filter(|&&other| {
+                    let other_lifetime = &self.images[other].lifetime;
+
+                    lifetime.start() <= other_lifetime.end()
+                        && other_lifetime.start() <= lifetime.end()
+                })
+                .map(|&other| {
+                    let placement = &placements[other];
+
+                    (placement.offset, placement.offset + placement.size)
+                })
+                .collect::<Vec<_>>();
+            conflicts.sort_unstable();
+
+            // First fit: try the start of the block, then the end of each conflicting image.
+            let mut offset = 0;
+
+            for &(start, end) in &conflicts {
+                if offset + size <= start {
+                    break;
+                }
+
+                offset = max(offset, align_up(end, alignment));
+            }
+
+            group.memory_type_bits &= requirements.memory_type_bits;
+            group.size = max(group.size, offset + size);
+            group.unaliased_size += size;
+            group.members.push(index);
+            placements[index] = Placement {
+                group: group_index,
+                offset,
+                size,
+            };
+        }
+
+        let memory_properties = self.device.physical_device().memory_properties();
+        let device_memories = groups
+            .iter()
+            .map(|group| {
+                let memory_type_index = find_memory_type_index(
+                    memory_properties,
+                    group.memory_type_bits,
+                    memory_type_filter,
+                )
+                .ok_or(AllocateImageError::AllocateMemory(
+                    MemoryAllocatorError::FindMemoryType,
+                ))?;
+
+                DeviceMemory::allocate(
+                    self.device.clone(),
+                    MemoryAllocateInfo {
+                        allocation_size: group.size,
+                        memory_type_index,
+                        ..Default::default()
+                    },
+                )
+                .map(Arc::new)
+                .map_err(|err| {
+                    Validated::Error(AllocateImageError::AllocateMemory(
+                        MemoryAllocatorError::AllocateDeviceMemory(err),
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, Validated<AllocateImageError>>>()?;
+
+        let images = raw_images
+            .into_iter()
+            .zip(&placements)
+            .map(|(raw_image, placement)| {
+                let requirements = raw_image.memory_requirements()[0];
+
+                // SAFETY: The memory of images that alias each other is never in use at the same
+                // time, as documented.
+                let memory = unsafe {
+                    ResourceMemory::from_device_memory_unchecked(
+                        device_memories[placement.group].clone(),
+                        placement.offset,
+                        requirements.layout.size(),
+                    )
+                };
+
+                // SAFETY: We just created this raw image and haven't bound any memory to it.
+                unsafe { raw_image.bind_memory([memory]) }
+                    .map(Arc::new)
+                    .map_err(|(err, _, _)| {
+                        err.map(AllocateImageError::BindMemory)
+                            .map_validation(|err| err.add_context("RawImage::bind_memory"))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(TransientImages {
+            images,
+            size: groups.iter().map(|group| group.size).sum(),
+            unaliased_size: groups.iter().map(|group| group.unaliased_size).sum(),
+        })
+    }
+}
+
+unsafe impl DeviceOwned for TransientImageAllocator {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Placement {
+    group: usize,
+    offset: DeviceSize,
+    size: DeviceSize,
+}
+
+#[derive(Debug)]
+struct MemoryGroup {
+    memory_type_bits: u32,
+    tiling: ImageTiling,
+    size: DeviceSize,
+    unaliased_size: DeviceSize,
+    members: Vec<usize>,
+}
+
+/// Identifies an image added to a [`TransientImageAllocator`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TransientImageId(usize);
+
+/// The images created by [`TransientImageAllocator::allocate`].
+#[derive(Debug)]
+pub struct TransientImages {
+    images: Vec<Arc<Image>>,
+    size: DeviceSize,
+    unaliased_size: DeviceSize,
+}
+
+impl TransientImages {
+    /// Returns the images, in the order that they were added.
+    #[inline]
+    pub fn images(&self) -> &[Arc<Image>] {
+        &self.images
+    }
+
+    /// Converts `self` into the images, in the order that they were added.
+    #[inline]
+    pub fn into_images(self) -> Vec<Arc<Image>> {
+        self.images
+    }
+
+    /// Returns the total size of the memory that was allocated for the images.
+    #[inline]
+    pub fn size(&self) -> DeviceSize {
+        self.size
+    }
+
+    /// Returns the total size of the memory that would have been needed without aliasing.
+    #[inline]
+    pub fn unaliased_size(&self) -> DeviceSize {
+        self.unaliased_size
+    }
+}
+
+impl Index<TransientImageId> for TransientImages {
+    type Output = Arc<Image>;
+
+    #[inline]
+    fn index(&self, id: TransientImageId) -> &Self::Output {
+        &self.images[id.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransientImageAllocator;
+    use crate::{
+        format::Format,
+        image::{Image, ImageCreateInfo, ImageMemory, ImageType, ImageUsage},
+        memory::allocator::MemoryTypeFilter,
+        DeviceSize, VulkanObject,
+    };
+
+    #[test]
+    fn disjoint_lifetimes_alias() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let create_info = ImageCreateInfo {
+            image_type: ImageType::Dim2d,
+            format: Format::R8G8B8A8_UNORM,
+            extent: [256, 256, 1],
+            usage: ImageUsage::COLOR_ATTACHMENT,
+            ..Default::default()
+        };
+
+        let mut allocator = TransientImageAllocator::new(device);
+        let a = allocator.add_image(create_info.clone(), 0..=1);
+        let b = allocator.add_image(create_info.clone(), 1..=2);
+        let c = allocator.add_image(create_info, 2..=3);
+
+        let images = unsafe { allocator.allocate(MemoryTypeFilter::PREFER_DEVICE) }.unwrap();
+
+        fn memory(image: &Image) -> (ash::vk::DeviceMemory, DeviceSize) {
+            match image.memory() {
+                ImageMemory::Normal(memory) => {
+                    (memory[0].device_memory().handle(), memory[0].offset())
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        // `a` and `c` are never in use at the same time, so they share memory, while `b`
+        // overlaps with both.
+        assert_eq!(memory(&images[a]), memory(&images[c]));
+        assert_ne!(memory(&images[a]), memory(&images[b]));
+        assert_eq!(images.size() * 3, images.unaliased_size() * 2);
+    }
+}
@@ -49,13 +49,19 @@ pub struct ImageView {
 impl ImageView {
     /// Creates a new `ImageView`.
     #[inline]
+    #[track_caller]
     pub fn new(
         image: Arc<Image>,
         create_info: ImageViewCreateInfo,
     ) -> Result<Arc<ImageView>, Validated<VulkanError>> {
         Self::validate_new(&image, &create_info)?;
 
-        unsafe { Ok(Self::new_unchecked(image, create_info)?) }
+        let image_view = unsafe { Self::new_unchecked(image, create_info)? };
+        image_view
+            .device()
+            .set_auto_debug_utils_object_name(&*image_view, "ImageView");
+
+        Ok(image_view)
     }
 
     fn validate_new(
@@ -129,10 +135,10 @@ impl ImageView {
         {
             match view_type {
                 ImageViewType::Dim2d => {
-                    if !image
-                        .flags()
-                        .intersects(ImageCreateFlags::DIM2D_ARRAY_COMPATIBLE)
-                    {
+                    if !image.flags().intersects(
+                        ImageCreateFlags::DIM2D_ARRAY_COMPATIBLE
+                            | ImageCreateFlags::DIM2D_VIEW_COMPATIBLE,
+                    ) {
                         return Err(Box::new(ValidationError {
                             problem: "`create_info.view_type` is `ImageViewType::Dim2d`, and \
                                 `image.image_type()` is `ImageType::Dim3d`, but \
@@ -164,6 +170,22 @@ impl ImageView {
                 _ => unreachable!(),
             }
 
+            if image
+                .flags()
+                .intersects(ImageCreateFlags::SPARSE_BINDING | ImageCreateFlags::SPARSE_RESIDENCY)
+            {
+                return Err(Box::new(ValidationError {
+                    problem: "`create_info.view_type` is `ImageViewType::Dim2d` or \
+                        `ImageViewType::Dim2dArray`, and \
+                        `image.image_type()` is `ImageType::Dim3d`, but \
+                        `image.flags()` contains `ImageCreateFlags::SPARSE_BINDING` or \
+                        `ImageCreateFlags::SPARSE_RESIDENCY`"
+                        .into(),
+                    vuids: &["VUID-VkImageViewCreateInfo-image-04971"],
+                    ..Default::default()
+                }));
+            }
+
             if subresource_range.mip_levels.len() != 1 {
                 return Err(Box::new(ValidationError {
                     problem: "`create_info.view_type` is `ImageViewType::Dim2d` or \
@@ -628,12 +650,31 @@ impl ImageView {
 
     /// Creates a default `ImageView`. Equivalent to
     /// `ImageView::new(image, ImageViewCreateInfo::from_image(image))`.
+    #[track_caller]
     pub fn new_default(image: Arc<Image>) -> Result<Arc<ImageView>, Validated<VulkanError>> {
         let create_info = ImageViewCreateInfo::from_image(&image);
 
         Self::new(image, create_info)
     }
 
+    /// Creates a 2D `ImageView` for each array layer of `image`, in order. Equivalent to calling
+    /// `ImageView::new(image, ImageViewCreateInfo::from_image_array_layer(image, array_layer))`
+    /// for every array layer.
+    ///
+    /// This is useful for rendering to the layers of an image separately, for example to the
+    /// left and right eye layers of a swapchain created with an `image_array_layers` value of 2.
+    pub fn new_per_array_layer(
+        image: Arc<Image>,
+    ) -> Result<Vec<Arc<ImageView>>, Validated<VulkanError>> {
+        (0..image.array_layers())
+            .map(|array_layer| {
+                let create_info = ImageViewCreateInfo::from_image_array_layer(&image, array_layer);
+
+                Self::new(image.clone(), create_info)
+            })
+            .collect()
+    }
+
     /// Creates a new `ImageView` from a raw object handle.
     ///
     /// # Safety
@@ -924,6 +965,46 @@ impl ImageViewCreateInfo {
         }
     }
 
+    /// Returns an `ImageViewCreateInfo` for a 2D view of a single depth slice of a 3D image.
+    ///
+    /// The image must have been created with the [`DIM2D_ARRAY_COMPATIBLE`] or
+    /// [`DIM2D_VIEW_COMPATIBLE`] flag. Only views of images created with the latter can be
+    /// written to descriptor sets.
+    ///
+    /// [`DIM2D_ARRAY_COMPATIBLE`]: ImageCreateFlags::DIM2D_ARRAY_COMPATIBLE
+    /// [`DIM2D_VIEW_COMPATIBLE`]: ImageCreateFlags::DIM2D_VIEW_COMPATIBLE
+    #[inline]
+    pub fn from_image_depth_slice(image: &Image, mip_level: u32, depth_slice: u32) -> Self {
+        Self {
+            view_type: ImageViewType::Dim2d,
+            format: image.format(),
+            subresource_range: ImageSubresourceRange {
+                aspects: image.subresource_range().aspects,
+                mip_levels: mip_level..mip_level + 1,
+                array_layers: depth_slice..depth_slice + 1,
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Returns an `ImageViewCreateInfo` for a 2D view of a single array layer of a 2D image,
+    /// covering all of its mip levels.
+    ///
+    /// This can be used to render to or sample from the layers of an arrayed image separately,
+    /// such as the images of a swapchain with more than one array layer.
+    #[inline]
+    pub fn from_image_array_layer(image: &Image, array_layer: u32) -> Self {
+        Self {
+            view_type: ImageViewType::Dim2d,
+            format: image.format(),
+            subresource_range: ImageSubresourceRange {
+                array_layers: array_layer..array_layer + 1,
+                ..image.subresource_range()
+            },
+            ..Default::default()
+        }
+    }
+
     pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
         let &Self {
             view_type,
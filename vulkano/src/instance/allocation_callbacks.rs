@@ -0,0 +1,329 @@
+//! Routing the host memory allocations of the Vulkan implementation to your own allocator.
+//!
+//! The Vulkan implementation allocates host memory for its own bookkeeping. By default, it uses
+//! an allocator of its own choosing for this. When creating an [`Instance`] or a [`Device`], you
+//! can instead provide [`AllocationCallbacks`] that wrap a [`HostAllocator`], which the
+//! implementation will then call to allocate and free the memory of that object. This is useful
+//! to track how much memory the implementation uses, or to place it in an arena.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{
+//!     alloc::{GlobalAlloc, Layout, System},
+//!     ptr::NonNull,
+//!     sync::atomic::{AtomicUsize, Ordering},
+//! };
+//! use vulkano::instance::allocation_callbacks::{
+//!     AllocationCallbacks, HostAllocator, SystemAllocationScope,
+//! };
+//!
+//! #[derive(Default)]
+//! struct CountingAllocator {
+//!     allocated: AtomicUsize,
+//! }
+//!
+//! unsafe impl HostAllocator for CountingAllocator {
+//!     fn allocate(&self, layout: Layout, _scope: SystemAllocationScope) -> Option<NonNull<u8>> {
+//!         self.allocated.fetch_add(layout.size(), Ordering::Relaxed);
+//!
+//!         NonNull::new(unsafe { System.alloc(layout) })
+//!     }
+//!
+//!     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+//!         self.allocated.fetch_sub(layout.size(), Ordering::Relaxed);
+//!
+//!         System.dealloc(ptr.as_ptr(), layout)
+//!     }
+//! }
+//!
+//! let allocation_callbacks = AllocationCallbacks::new(CountingAllocator::default());
+//! ```
+//!
+//! The allocation callbacks are used for the creation and destruction of the object that they
+//! are given to, and for the host memory that the implementation needs for that object during
+//! its lifetime. Objects that are created from an instance or device, such as buffers or
+//! pipelines, don't use them.
+//!
+//! [`Instance`]: super::Instance
+//! [`Device`]: crate::device::Device
+
+use crate::macros::vulkan_enum;
+use std::{
+    alloc::Layout,
+    ffi::c_void,
+    fmt::{Debug, Error as FmtError, Formatter},
+    mem::{align_of, size_of},
+    panic::{catch_unwind, AssertUnwindSafe, RefUnwindSafe},
+    ptr::{self, NonNull},
+    sync::Arc,
+};
+
+/// An allocator for the host memory that the Vulkan implementation uses.
+///
+/// # Safety
+///
+/// The same rules apply as for [`GlobalAlloc`](std::alloc::GlobalAlloc):
+///
+/// - A successful allocation must return a pointer to a block of memory that fits `layout`, and
+///   that stays valid until it is passed to [`deallocate`](Self::deallocate).
+/// - The implementation must not call into the Vulkan API.
+pub unsafe trait HostAllocator: RefUnwindSafe + Send + Sync {
+    /// Allocates a block of memory that fits `layout`, or returns `None` if the allocation
+    /// failed.
+    ///
+    /// `scope` describes how long the allocation is expected to live.
+    fn allocate(&self, layout: Layout, scope: SystemAllocationScope) -> Option<NonNull<u8>>;
+
+    /// Frees a block of memory that was returned by [`allocate`](Self::allocate).
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been returned by `self.allocate` with the same `layout`, and must not
+    ///   have been deallocated yet.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Called when the implementation has allocated `size` bytes of memory by itself, without
+    /// going through the allocator. This is done for memory that must be executable, for example.
+    ///
+    /// The provided implementation does nothing.
+    #[inline]
+    fn internal_allocation(
+        &self,
+        size: usize,
+        allocation_type: InternalAllocationType,
+        scope: SystemAllocationScope,
+    ) {
+        let _ = (size, allocation_type, scope);
+    }
+
+    /// Called when the implementation has freed memory that it reported to
+    /// [`internal_allocation`](Self::internal_allocation).
+    ///
+    /// The provided implementation does nothing.
+    #[inline]
+    fn internal_free(
+        &self,
+        size: usize,
+        allocation_type: InternalAllocationType,
+        scope: SystemAllocationScope,
+    ) {
+        let _ = (size, allocation_type, scope);
+    }
+}
+
+/// A [`HostAllocator`], wrapped so that it can be given to the Vulkan implementation.
+pub struct AllocationCallbacks(HostAllocatorData);
+
+type HostAllocatorData = Box<dyn HostAllocator>;
+
+impl AllocationCallbacks {
+    /// Returns a new `AllocationCallbacks` wrapping the provided allocator.
+    #[inline]
+    pub fn new(allocator: impl HostAllocator + 'static) -> Arc<Self> {
+        Arc::new(Self(Box::new(allocator)))
+    }
+
+    /// Returns the wrapped allocator.
+    #[inline]
+    pub fn allocator(&self) -> &dyn HostAllocator {
+        self.0.as_ref()
+    }
+
+    pub(crate) fn to_vulkan(&self) -> ash::vk::AllocationCallbacks<'_> {
+        ash::vk::AllocationCallbacks {
+            p_user_data: ptr::addr_of!(self.0).cast_mut().cast(),
+            pfn_allocation: Some(allocation_trampoline),
+            pfn_reallocation: Some(reallocation_trampoline),
+            pfn_free: Some(free_trampoline),
+            pfn_internal_allocation: Some(internal_allocation_trampoline),
+            pfn_internal_free: Some(internal_free_trampoline),
+            ..Default::default()
+        }
+    }
+}
+
+impl Debug for AllocationCallbacks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_struct("AllocationCallbacks")
+            .finish_non_exhaustive()
+    }
+}
+
+// Vulkan doesn't pass the size of the original allocation to the reallocation and free functions,
+// but `HostAllocator` needs to know the layout. So every allocation starts with a header holding
+// its layout, which is placed right before the pointer that is returned to the implementation.
+const HEADER_SIZE: usize = size_of::<Layout>();
+
+// Returns the layout of the whole allocation including the header, and the offset of the memory
+// that is returned to the implementation.
+fn outer_layout(size: usize, alignment: usize) -> Option<(Layout, usize)> {
+    let alignment = alignment.max(align_of::<Layout>());
+    let offset = header_offset(alignment);
+    let layout = Layout::from_size_align(offset.checked_add(size)?, alignment).ok()?;
+
+    Some((layout, offset))
+}
+
+fn header_offset(alignment: usize) -> usize {
+    // Both are powers of two, so the larger one is a multiple of the smaller one.
+    HEADER_SIZE.max(alignment)
+}
+
+unsafe fn allocate(
+    allocator: &dyn HostAllocator,
+    size: usize,
+    alignment: usize,
+    scope: SystemAllocationScope,
+) -> *mut c_void {
+    let Some((layout, offset)) = outer_layout(size, alignment) else {
+        return ptr::null_mut();
+    };
+    let Some(base) = allocator.allocate(layout, scope) else {
+        return ptr::null_mut();
+    };
+
+    let memory = base.as_ptr().add(offset);
+    memory.sub(HEADER_SIZE).cast::<Layout>().write(layout);
+
+    memory.cast()
+}
+
+unsafe fn user_size(memory: *mut c_void) -> usize {
+    let layout = memory.cast::<u8>().sub(HEADER_SIZE).cast::<Layout>().read();
+
+    layout.size() - header_offset(layout.align())
+}
+
+unsafe fn free(allocator: &dyn HostAllocator, memory: *mut c_void) {
+    let memory = memory.cast::<u8>();
+    let layout = memory.sub(HEADER_SIZE).cast::<Layout>().read();
+    let base = memory.sub(header_offset(layout.align()));
+
+    allocator.deallocate(NonNull::new_unchecked(base), layout);
+}
+
+unsafe fn allocator<'a>(user_data_vk: *mut c_void) -> &'a dyn HostAllocator {
+    (*user_data_vk.cast_const().cast::<HostAllocatorData>()).as_ref()
+}
+
+unsafe extern "system" fn allocation_trampoline(
+    user_data_vk: *mut c_void,
+    size: usize,
+    alignment: usize,
+    allocation_scope_vk: ash::vk::SystemAllocationScope,
+) -> *mut c_void {
+    // Since we box the allocator, the type system doesn't detect that the `UnwindSafe` bound is
+    // enforced. Therefore we enforce it manually.
+    catch_unwind(AssertUnwindSafe(move || {
+        let scope = SystemAllocationScope::try_from(allocation_scope_vk).unwrap();
+
+        allocate(allocator(user_data_vk), size, alignment, scope)
+    }))
+    .unwrap_or(ptr::null_mut())
+}
+
+unsafe extern "system" fn reallocation_trampoline(
+    user_data_vk: *mut c_void,
+    original: *mut c_void,
+    size: usize,
+    alignment: usize,
+    allocation_scope_vk: ash::vk::SystemAllocationScope,
+) -> *mut c_void {
+    catch_unwind(AssertUnwindSafe(move || {
+        let allocator = allocator(user_data_vk);
+        let scope = SystemAllocationScope::try_from(allocation_scope_vk).unwrap();
+
+        if original.is_null() {
+            return allocate(allocator, size, alignment, scope);
+        }
+
+        if size == 0 {
+            free(allocator, original);
+
+            return ptr::null_mut();
+        }
+
+        // On failure, the original allocation must be left untouched.
+        let memory = allocate(allocator, size, alignment, scope);
+
+        if !memory.is_null() {
+            let copy_size = user_size(original).min(size);
+            ptr::copy_nonoverlapping(original.cast::<u8>(), memory.cast::<u8>(), copy_size);
+            free(allocator, original);
+        }
+
+        memory
+    }))
+    .unwrap_or(ptr::null_mut())
+}
+
+unsafe extern "system" fn free_trampoline(user_data_vk: *mut c_void, memory: *mut c_void) {
+    let _ = catch_unwind(AssertUnwindSafe(move || {
+        if !memory.is_null() {
+            free(allocator(user_data_vk), memory);
+        }
+    }));
+}
+
+unsafe extern "system" fn internal_allocation_trampoline(
+    user_data_vk: *mut c_void,
+    size: usize,
+    allocation_type_vk: ash::vk::InternalAllocationType,
+    allocation_scope_vk: ash::vk::SystemAllocationScope,
+) {
+    let _ = catch_unwind(AssertUnwindSafe(move || {
+        allocator(user_data_vk).internal_allocation(
+            size,
+            InternalAllocationType::try_from(allocation_type_vk).unwrap(),
+            SystemAllocationScope::try_from(allocation_scope_vk).unwrap(),
+        );
+    }));
+}
+
+unsafe extern "system" fn internal_free_trampoline(
+    user_data_vk: *mut c_void,
+    size: usize,
+    allocation_type_vk: ash::vk::InternalAllocationType,
+    allocation_scope_vk: ash::vk::SystemAllocationScope,
+) {
+    let _ = catch_unwind(AssertUnwindSafe(move || {
+        allocator(user_data_vk).internal_free(
+            size,
+            InternalAllocationType::try_from(allocation_type_vk).unwrap(),
+            SystemAllocationScope::try_from(allocation_scope_vk).unwrap(),
+        );
+    }));
+}
+
+vulkan_enum! {
+    #[non_exhaustive]
+
+    /// How long a host memory allocation is expected to live.
+    SystemAllocationScope = SystemAllocationScope(i32);
+
+    /// The allocation lives for the duration of a Vulkan command.
+    Command = COMMAND,
+
+    /// The allocation lives as long as the Vulkan object that it was made for.
+    Object = OBJECT,
+
+    /// The allocation lives as long as a pipeline cache or validation cache.
+    Cache = CACHE,
+
+    /// The allocation lives as long as the device.
+    Device = DEVICE,
+
+    /// The allocation lives as long as the instance.
+    Instance = INSTANCE,
+}
+
+vulkan_enum! {
+    #[non_exhaustive]
+
+    /// The type of an allocation that the implementation made without the allocator.
+    InternalAllocationType = InternalAllocationType(i32);
+
+    /// The allocation is intended for execution by the host.
+    Executable = EXECUTABLE,
+}
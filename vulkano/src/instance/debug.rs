@@ -38,8 +38,8 @@
 use super::{Instance, InstanceExtensions};
 use crate::{
     macros::{vulkan_bitflags, vulkan_enum},
-    DebugWrapper, Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, Version,
-    VulkanError, VulkanObject,
+    DebugWrapper, DeviceSize, Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError,
+    Version, VulkanError, VulkanObject,
 };
 use std::{
     ffi::{c_void, CStr},
@@ -320,7 +320,7 @@ pub(super) unsafe extern "system" fn trampoline(
     let _ = catch_unwind(AssertUnwindSafe(move || {
         let ash::vk::DebugUtilsMessengerCallbackDataEXT {
             s_type: _,
-            p_next: _,
+            p_next,
             flags: _,
             p_message_id_name,
             message_id_number,
@@ -365,6 +365,7 @@ pub(super) unsafe extern "system" fn trampoline(
                 }
                 .iter(),
             ),
+            device_address_binding: device_address_binding_callback_data(p_next),
         };
 
         let user_callback: &CallbackData = &*user_data_vk.cast_const().cast();
@@ -379,6 +380,33 @@ pub(super) unsafe extern "system" fn trampoline(
     ash::vk::FALSE
 }
 
+unsafe fn device_address_binding_callback_data(
+    mut p_next: *const c_void,
+) -> Option<DeviceAddressBindingCallbackData> {
+    while let Some(next) = p_next.cast::<ash::vk::BaseInStructure<'_>>().as_ref() {
+        if next.s_type == ash::vk::StructureType::DEVICE_ADDRESS_BINDING_CALLBACK_DATA_EXT {
+            let &ash::vk::DeviceAddressBindingCallbackDataEXT {
+                flags,
+                base_address,
+                size,
+                binding_type,
+                ..
+            } = &*p_next.cast::<ash::vk::DeviceAddressBindingCallbackDataEXT<'_>>();
+
+            return Some(DeviceAddressBindingCallbackData {
+                flags: flags.into(),
+                base_address,
+                size,
+                binding_type: binding_type.into(),
+            });
+        }
+
+        p_next = next.p_next.cast();
+    }
+
+    None
+}
+
 /// The data of a message received by the user callback.
 #[non_exhaustive]
 pub struct DebugUtilsMessengerCallbackData<'a> {
@@ -402,6 +430,57 @@ pub struct DebugUtilsMessengerCallbackData<'a> {
 
     /// Objects related to the message.
     pub objects: DebugUtilsMessengerCallbackObjectNameInfoIter<'a>,
+
+    /// If the message has the [`DebugUtilsMessageType::DEVICE_ADDRESS_BINDING`] type, the
+    /// binding event that is being reported.
+    pub device_address_binding: Option<DeviceAddressBindingCallbackData>,
+}
+
+/// A change to the GPU virtual address space, as reported to a messenger callback.
+///
+/// The object that was bound or unbound is the first element of
+/// [`DebugUtilsMessengerCallbackData::objects`].
+///
+/// The address ranges that are reported can be recorded, so that an address that a device fault
+/// points to can be mapped back to the buffer or image that owned it.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct DeviceAddressBindingCallbackData {
+    /// Additional information about the binding event.
+    pub flags: DeviceAddressBindingFlags,
+
+    /// The GPU virtual address at which the range starts.
+    pub base_address: u64,
+
+    /// The size of the range, in bytes.
+    pub size: DeviceSize,
+
+    /// Whether the range was bound or unbound.
+    pub binding_type: DeviceAddressBindingType,
+}
+
+vulkan_bitflags! {
+    #[non_exhaustive]
+
+    /// Additional information about a device address binding event.
+    DeviceAddressBindingFlags = DeviceAddressBindingFlagsEXT(u32);
+
+    /// The binding was made by the implementation for its own internal use, rather than for an
+    /// object that was created by the application.
+    INTERNAL_OBJECT = INTERNAL_OBJECT,
+}
+
+vulkan_enum! {
+    #[non_exhaustive]
+
+    /// The type of a device address binding event.
+    DeviceAddressBindingType = DeviceAddressBindingTypeEXT(i32);
+
+    /// A range of the GPU virtual address space was bound to an object.
+    Bind = BIND,
+
+    /// A range of the GPU virtual address space was unbound from an object.
+    Unbind = UNBIND,
 }
 
 /// The values of [`DebugUtilsLabel`], as returned to a messenger callback.
@@ -509,6 +588,16 @@ vulkan_bitflags! {
 
     /// Specifies a potentially non-optimal use of Vulkan
     PERFORMANCE = PERFORMANCE,
+
+    /// Specifies that a range of the GPU virtual address space was bound or unbound. The details
+    /// are provided in [`DebugUtilsMessengerCallbackData::device_address_binding`].
+    ///
+    /// These messages are only sent for devices that have the [`ext_device_address_binding_report`]
+    /// extension and the [`report_address_binding`] feature enabled.
+    ///
+    /// [`ext_device_address_binding_report`]: crate::device::DeviceExtensions::ext_device_address_binding_report
+    /// [`report_address_binding`]: crate::device::DeviceFeatures::report_address_binding
+    DEVICE_ADDRESS_BINDING = DEVICE_ADDRESS_BINDING_EXT,
 }
 
 /// A label to associate with a span of work in a queue.
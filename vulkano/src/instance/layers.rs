@@ -1,4 +1,8 @@
-use crate::Version;
+use crate::{ValidationError, Version};
+use std::{
+    ffi::{c_char, c_void, CString},
+    ptr,
+};
 
 /// Properties of a layer.
 #[derive(Clone)]
@@ -93,6 +97,263 @@ impl LayerProperties {
     }
 }
 
+/// A setting that configures the behavior of a layer, when creating an instance.
+///
+/// The settings that are available, and the values that they accept, are defined by each layer.
+/// For example, the settings of the Khronos validation layer are listed in its
+/// [documentation](https://vulkan.lunarg.com/doc/sdk/latest/windows/khronos_validation_layer.html).
+///
+/// # Examples
+///
+/// ```
+/// use vulkano::instance::{
+///     InstanceCreateInfo, InstanceExtensions, LayerSetting, LayerSettingValues,
+/// };
+///
+/// const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+///
+/// let create_info = InstanceCreateInfo {
+///     enabled_layers: vec![VALIDATION_LAYER.to_owned()],
+///     enabled_extensions: InstanceExtensions {
+///         ext_layer_settings: true,
+///         ..InstanceExtensions::empty()
+///     },
+///     layer_settings: vec![
+///         // Enable synchronization validation.
+///         LayerSetting::new(
+///             VALIDATION_LAYER,
+///             "validate_sync",
+///             LayerSettingValues::Bool32(vec![true]),
+///         ),
+///         // Enable the best practices checks, including those for NVIDIA devices.
+///         LayerSetting::new(
+///             VALIDATION_LAYER,
+///             "validate_best_practices",
+///             LayerSettingValues::Bool32(vec![true]),
+///         ),
+///         LayerSetting::new(
+///             VALIDATION_LAYER,
+///             "validate_best_practices_nvidia",
+///             LayerSettingValues::Bool32(vec![true]),
+///         ),
+///         // Report each distinct message at most 3 times.
+///         LayerSetting::new(
+///             VALIDATION_LAYER,
+///             "duplicate_message_limit",
+///             LayerSettingValues::Uint32(vec![3]),
+///         ),
+///     ],
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerSetting {
+    /// The name of the layer that the setting applies to.
+    ///
+    /// There is no default value.
+    pub layer_name: String,
+
+    /// The name of the setting.
+    ///
+    /// There is no default value.
+    pub setting_name: String,
+
+    /// The values to assign to the setting.
+    ///
+    /// There is no default value.
+    pub values: LayerSettingValues,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl LayerSetting {
+    /// Returns a `LayerSetting` with the specified `layer_name`, `setting_name` and `values`.
+    #[inline]
+    pub fn new(
+        layer_name: impl Into<String>,
+        setting_name: impl Into<String>,
+        values: LayerSettingValues,
+    ) -> Self {
+        Self {
+            layer_name: layer_name.into(),
+            setting_name: setting_name.into(),
+            values,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Box<ValidationError>> {
+        let Self {
+            layer_name,
+            setting_name,
+            values,
+            _ne: _,
+        } = self;
+
+        if layer_name.contains('\0') {
+            return Err(Box::new(ValidationError {
+                context: "layer_name".into(),
+                problem: "contains a null byte".into(),
+                ..Default::default()
+            }));
+        }
+
+        if setting_name.contains('\0') {
+            return Err(Box::new(ValidationError {
+                context: "setting_name".into(),
+                problem: "contains a null byte".into(),
+                ..Default::default()
+            }));
+        }
+
+        if let LayerSettingValues::String(values) = values {
+            for (index, value) in values.iter().enumerate() {
+                if value.contains('\0') {
+                    return Err(Box::new(ValidationError {
+                        context: format!("values[{}]", index).into(),
+                        problem: "contains a null byte".into(),
+                        ..Default::default()
+                    }));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn to_vulkan_fields1(&self) -> LayerSettingFields1Vk {
+        let Self {
+            layer_name,
+            setting_name,
+            values,
+            _ne: _,
+        } = self;
+
+        let (bool_values_vk, string_values_vk) = match values {
+            LayerSettingValues::Bool32(values) => (
+                values
+                    .iter()
+                    .map(|&value| value as ash::vk::Bool32)
+                    .collect(),
+                Vec::new(),
+            ),
+            LayerSettingValues::String(values) => (
+                Vec::new(),
+                values
+                    .iter()
+                    .map(|value| CString::new(value.as_str()).unwrap())
+                    .collect(),
+            ),
+            _ => (Vec::new(), Vec::new()),
+        };
+        let string_ptrs_vk = string_values_vk
+            .iter()
+            .map(|value: &CString| value.as_ptr())
+            .collect();
+
+        LayerSettingFields1Vk {
+            layer_name_vk: CString::new(layer_name.as_str()).unwrap(),
+            setting_name_vk: CString::new(setting_name.as_str()).unwrap(),
+            bool_values_vk,
+            _string_values_vk: string_values_vk,
+            string_ptrs_vk,
+        }
+    }
+
+    pub(crate) fn to_vulkan<'a>(
+        &'a self,
+        fields1_vk: &'a LayerSettingFields1Vk,
+    ) -> ash::vk::LayerSettingEXT<'a> {
+        let LayerSettingFields1Vk {
+            layer_name_vk,
+            setting_name_vk,
+            bool_values_vk,
+            _string_values_vk: _,
+            string_ptrs_vk,
+        } = fields1_vk;
+
+        let (ty, value_count, p_values): (_, usize, *const c_void) = match &self.values {
+            LayerSettingValues::Bool32(_) => (
+                ash::vk::LayerSettingTypeEXT::BOOL32,
+                bool_values_vk.len(),
+                bool_values_vk.as_ptr().cast(),
+            ),
+            LayerSettingValues::Int32(values) => (
+                ash::vk::LayerSettingTypeEXT::INT32,
+                values.len(),
+                values.as_ptr().cast(),
+            ),
+            LayerSettingValues::Int64(values) => (
+                ash::vk::LayerSettingTypeEXT::INT64,
+                values.len(),
+                values.as_ptr().cast(),
+            ),
+            LayerSettingValues::Uint32(values) => (
+                ash::vk::LayerSettingTypeEXT::UINT32,
+                values.len(),
+                values.as_ptr().cast(),
+            ),
+            LayerSettingValues::Uint64(values) => (
+                ash::vk::LayerSettingTypeEXT::UINT64,
+                values.len(),
+                values.as_ptr().cast(),
+            ),
+            LayerSettingValues::Float32(values) => (
+                ash::vk::LayerSettingTypeEXT::FLOAT32,
+                values.len(),
+                values.as_ptr().cast(),
+            ),
+            LayerSettingValues::Float64(values) => (
+                ash::vk::LayerSettingTypeEXT::FLOAT64,
+                values.len(),
+                values.as_ptr().cast(),
+            ),
+            LayerSettingValues::String(_) => (
+                ash::vk::LayerSettingTypeEXT::STRING,
+                string_ptrs_vk.len(),
+                string_ptrs_vk.as_ptr().cast(),
+            ),
+        };
+
+        ash::vk::LayerSettingEXT {
+            p_layer_name: layer_name_vk.as_ptr(),
+            p_setting_name: setting_name_vk.as_ptr(),
+            ty,
+            value_count: value_count as u32,
+            p_values: if value_count == 0 {
+                ptr::null()
+            } else {
+                p_values
+            },
+            ..Default::default()
+        }
+    }
+}
+
+pub(crate) struct LayerSettingFields1Vk {
+    layer_name_vk: CString,
+    setting_name_vk: CString,
+    bool_values_vk: Vec<ash::vk::Bool32>,
+    _string_values_vk: Vec<CString>,
+    string_ptrs_vk: Vec<*const c_char>,
+}
+
+/// The values of a [`LayerSetting`].
+///
+/// Which variant to use depends on the type that the layer expects for the setting. Settings that
+/// have a single value are given a list with one element.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LayerSettingValues {
+    Bool32(Vec<bool>),
+    Int32(Vec<i32>),
+    Int64(Vec<i64>),
+    Uint32(Vec<u32>),
+    Uint64(Vec<u64>),
+    Float32(Vec<f32>),
+    Float64(Vec<f64>),
+    String(Vec<String>),
+}
+
 #[cfg(test)]
 mod tests {
     use crate::VulkanLibrary;
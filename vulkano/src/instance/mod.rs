@@ -72,16 +72,25 @@
 //! [`InstanceCreateFlags::ENUMERATE_PORTABILITY`] flag when creating the instance. However, if you
 //! do this, your program must be prepared to handle the non-conformant aspects of these devices,
 //! and must enable the appropriate features when creating the `Device` if you intend to use them.
-
-use self::debug::{
-    DebugUtilsMessengerCallback, DebugUtilsMessengerCreateInfo, ValidationFeatureDisable,
-    ValidationFeatureEnable,
+//! See the [device module] for how Vulkano reports the use of missing functionality.
+//!
+//! [device module]: crate::device#portability-subset-devices
+
+pub use self::layers::{LayerProperties, LayerSetting, LayerSettingValues};
+use self::{
+    allocation_callbacks::AllocationCallbacks,
+    debug::{
+        DebugUtilsMessengerCallback, DebugUtilsMessengerCreateInfo, ValidationFeatureDisable,
+        ValidationFeatureEnable,
+    },
 };
-pub use self::layers::LayerProperties;
 use crate::{
     cache::WeakArcOnceCache,
-    device::physical::{
-        PhysicalDevice, PhysicalDeviceGroupProperties, PhysicalDeviceGroupPropertiesRaw,
+    device::{
+        physical::{
+            PhysicalDevice, PhysicalDeviceGroupProperties, PhysicalDeviceGroupPropertiesRaw,
+        },
+        ExportMetalObjectTypes,
     },
     instance::debug::trampoline,
     macros::{impl_id_counter, vulkan_bitflags},
@@ -104,8 +113,11 @@ use std::{
     sync::Arc,
 };
 
+pub mod allocation_callbacks;
 pub mod debug;
 mod layers;
+#[cfg(feature = "renderdoc")]
+pub mod renderdoc;
 
 // Generated by build.rs
 include!(concat!(env!("OUT_DIR"), "/instance_extensions.rs"));
@@ -269,10 +281,13 @@ pub struct Instance {
     enabled_layers: Vec<String>,
     library: Arc<VulkanLibrary>,
     max_api_version: Version,
+    export_metal_object_types: ExportMetalObjectTypes,
+    allocation_callbacks: Option<Arc<AllocationCallbacks>>,
     _user_callbacks: Vec<Arc<DebugUtilsMessengerCallback>>,
 
     physical_devices: WeakArcOnceCache<ash::vk::PhysicalDevice, PhysicalDevice>,
     physical_device_groups: RwLock<(bool, Vec<PhysicalDeviceGroupPropertiesRaw>)>,
+    needs_destruction: bool, // `vkDestroyInstance` is called only if true.
 }
 
 // TODO: fix the underlying cause instead
@@ -321,6 +336,9 @@ impl Instance {
             debug_utils_messengers: _,
             enabled_validation_features: _,
             disabled_validation_features: _,
+            layer_settings: _,
+            export_metal_object_types: _,
+            allocation_callbacks: _,
             _ne,
         } = create_info;
 
@@ -379,6 +397,9 @@ impl Instance {
             ref debug_utils_messengers,
             ref enabled_validation_features,
             ref disabled_validation_features,
+            ref layer_settings,
+            export_metal_object_types,
+            ref allocation_callbacks,
             _ne: _,
         } = &create_info;
 
@@ -493,21 +514,65 @@ impl Instance {
             })
             .collect();
 
+        let layer_settings_fields1_vk: SmallVec<[_; 4]> = layer_settings
+            .iter()
+            .map(LayerSetting::to_vulkan_fields1)
+            .collect();
+        let layer_settings_vk: SmallVec<[_; 4]> = layer_settings
+            .iter()
+            .zip(&layer_settings_fields1_vk)
+            .map(|(layer_setting, fields1_vk)| layer_setting.to_vulkan(fields1_vk))
+            .collect();
+        let mut layer_settings_create_info_vk = None;
+
+        if !layer_settings_vk.is_empty() {
+            let next = layer_settings_create_info_vk.insert(ash::vk::LayerSettingsCreateInfoEXT {
+                setting_count: layer_settings_vk.len() as u32,
+                p_settings: layer_settings_vk.as_ptr(),
+                ..Default::default()
+            });
+
+            next.p_next = create_info_vk.p_next;
+            create_info_vk.p_next = <*const _>::cast(next);
+        }
+
+        let mut export_metal_object_create_infos_vk =
+            export_metal_object_types.to_vulkan_create_infos();
+
+        for next in &mut export_metal_object_create_infos_vk {
+            next.p_next = create_info_vk.p_next;
+            create_info_vk.p_next = <*const _>::cast(next);
+        }
+
         for i in 1..debug_utils_messenger_create_infos_vk.len() {
             debug_utils_messenger_create_infos_vk[i - 1].p_next =
                 <*const _>::cast(&debug_utils_messenger_create_infos_vk[i]);
         }
 
+        if let Some(info) = debug_utils_messenger_create_infos_vk.last_mut() {
+            info.p_next = create_info_vk.p_next;
+        }
+
         if let Some(info) = debug_utils_messenger_create_infos_vk.first() {
             create_info_vk.p_next = <*const _>::cast(info);
         }
 
+        let allocation_callbacks_vk = allocation_callbacks
+            .as_ref()
+            .map(|allocation_callbacks| allocation_callbacks.to_vulkan());
+
         let handle = {
             let mut output = MaybeUninit::uninit();
             let fns = library.fns();
-            (fns.v1_0.create_instance)(&create_info_vk, ptr::null(), output.as_mut_ptr())
-                .result()
-                .map_err(VulkanError::from)?;
+            (fns.v1_0.create_instance)(
+                &create_info_vk,
+                allocation_callbacks_vk
+                    .as_ref()
+                    .map_or(ptr::null(), |callbacks| callbacks),
+                output.as_mut_ptr(),
+            )
+            .result()
+            .map_err(VulkanError::from)?;
             output.assume_init()
         };
 
@@ -521,9 +586,39 @@ impl Instance {
     /// - `handle` must be a valid Vulkan object handle created from `library`.
     /// - `create_info` must match the info used to create the object.
     pub unsafe fn from_handle(
+        library: Arc<VulkanLibrary>,
+        handle: ash::vk::Instance,
+        create_info: InstanceCreateInfo,
+    ) -> Arc<Self> {
+        Self::from_handle_with_destruction(library, handle, create_info, true)
+    }
+
+    /// Creates a new `Instance` from a raw object handle. Unlike `from_handle`, the created
+    /// `Instance` does not destroy the inner instance when dropped.
+    ///
+    /// This is intended for adopting an instance that was created and is owned by another
+    /// library. See [the documentation of the `device`
+    /// module](crate::device#adopting-externally-created-objects) for more information.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid Vulkan object handle created from `library`.
+    /// - `create_info` must match the info used to create the object.
+    /// - Caller must ensure that the handle will not be destroyed for the lifetime of the returned
+    ///   `Instance`, and of any object created from it.
+    pub unsafe fn from_handle_borrowed(
+        library: Arc<VulkanLibrary>,
+        handle: ash::vk::Instance,
+        create_info: InstanceCreateInfo,
+    ) -> Arc<Self> {
+        Self::from_handle_with_destruction(library, handle, create_info, false)
+    }
+
+    unsafe fn from_handle_with_destruction(
         library: Arc<VulkanLibrary>,
         handle: ash::vk::Instance,
         mut create_info: InstanceCreateInfo,
+        needs_destruction: bool,
     ) -> Arc<Self> {
         create_info.max_api_version.get_or_insert_with(|| {
             let api_version = library.api_version();
@@ -546,6 +641,9 @@ impl Instance {
             debug_utils_messengers,
             enabled_validation_features: _,
             disabled_validation_features: _,
+            layer_settings: _,
+            export_metal_object_types,
+            allocation_callbacks,
             _ne: _,
         } = create_info;
 
@@ -567,6 +665,8 @@ impl Instance {
             enabled_layers,
             library,
             max_api_version,
+            export_metal_object_types,
+            allocation_callbacks,
             _user_callbacks: debug_utils_messengers
                 .into_iter()
                 .map(|m| m.user_callback)
@@ -574,6 +674,7 @@ impl Instance {
 
             physical_devices: WeakArcOnceCache::new(),
             physical_device_groups: RwLock::new((false, Vec::new())),
+            needs_destruction,
         })
     }
 
@@ -583,6 +684,14 @@ impl Instance {
         &self.library
     }
 
+    /// Returns whether the instance is owned by vulkano, and will be destroyed when dropped.
+    ///
+    /// This is `false` if the instance was created with [`Instance::from_handle_borrowed`].
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        self.needs_destruction
+    }
+
     /// Returns the flags that the instance was created with.
     #[inline]
     pub fn flags(&self) -> InstanceCreateFlags {
@@ -605,6 +714,19 @@ impl Instance {
         self.max_api_version
     }
 
+    /// Returns the types of Metal objects that can be exported from the devices and queues of
+    /// this instance.
+    #[inline]
+    pub fn export_metal_object_types(&self) -> ExportMetalObjectTypes {
+        self.export_metal_object_types
+    }
+
+    /// Returns the allocation callbacks that the instance was created with.
+    #[inline]
+    pub fn allocation_callbacks(&self) -> Option<&Arc<AllocationCallbacks>> {
+        self.allocation_callbacks.as_ref()
+    }
+
     /// Returns pointers to the raw Vulkan functions of the instance.
     #[inline]
     pub fn fns(&self) -> &InstanceFunctions {
@@ -848,10 +970,23 @@ impl Instance {
 impl Drop for Instance {
     #[inline]
     fn drop(&mut self) {
+        if !self.needs_destruction {
+            return;
+        }
+
         let fns = self.fns();
+        let allocation_callbacks_vk = self
+            .allocation_callbacks
+            .as_ref()
+            .map(|allocation_callbacks| allocation_callbacks.to_vulkan());
 
         unsafe {
-            (fns.v1_0.destroy_instance)(self.handle, ptr::null());
+            (fns.v1_0.destroy_instance)(
+                self.handle,
+                allocation_callbacks_vk
+                    .as_ref()
+                    .map_or(ptr::null(), |callbacks| callbacks),
+            );
         }
     }
 }
@@ -880,10 +1015,13 @@ impl Debug for Instance {
             enabled_layers,
             library,
             max_api_version,
+            export_metal_object_types,
+            allocation_callbacks,
             _user_callbacks: _,
 
             physical_devices: _,
             physical_device_groups: _,
+            needs_destruction,
         } = self;
 
         f.debug_struct("Instance")
@@ -896,6 +1034,9 @@ impl Debug for Instance {
             .field("enabled_layers", enabled_layers)
             .field("library", library)
             .field("max_api_version", max_api_version)
+            .field("export_metal_object_types", export_metal_object_types)
+            .field("allocation_callbacks", allocation_callbacks)
+            .field("needs_destruction", needs_destruction)
             .finish_non_exhaustive()
     }
 }
@@ -974,6 +1115,37 @@ pub struct InstanceCreateInfo {
     /// extension must be enabled on the instance.
     pub disabled_validation_features: Vec<ValidationFeatureDisable>,
 
+    /// Settings to configure the behavior of the enabled layers.
+    ///
+    /// This allows configuring layers from within the application, rather than with environment
+    /// variables or a `vk_layer_settings.txt` file.
+    ///
+    /// If not empty, the
+    /// [`ext_layer_settings`](crate::instance::InstanceExtensions::ext_layer_settings)
+    /// extension must be enabled on the instance.
+    ///
+    /// The default value is empty.
+    pub layer_settings: Vec<LayerSetting>,
+
+    /// The types of Metal objects that can be exported from the devices and queues that are
+    /// created from the instance.
+    ///
+    /// Only [`ExportMetalObjectTypes::METAL_DEVICE`] and
+    /// [`ExportMetalObjectTypes::METAL_COMMAND_QUEUE`] can be specified. To export them, the
+    /// [`ext_metal_objects`](crate::device::DeviceExtensions::ext_metal_objects) extension must
+    /// also be enabled on the device.
+    ///
+    /// The default value is empty.
+    pub export_metal_object_types: ExportMetalObjectTypes,
+
+    /// The allocator that the Vulkan implementation should use for the host memory of the
+    /// instance.
+    ///
+    /// If this is `None`, the implementation uses its own allocator.
+    ///
+    /// The default value is `None`.
+    pub allocation_callbacks: Option<Arc<AllocationCallbacks>>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -992,6 +1164,9 @@ impl Default for InstanceCreateInfo {
             debug_utils_messengers: Vec::new(),
             enabled_validation_features: Vec::new(),
             disabled_validation_features: Vec::new(),
+            layer_settings: Vec::new(),
+            export_metal_object_types: ExportMetalObjectTypes::empty(),
+            allocation_callbacks: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -1031,6 +1206,9 @@ impl InstanceCreateInfo {
             ref debug_utils_messengers,
             ref enabled_validation_features,
             ref disabled_validation_features,
+            ref layer_settings,
+            export_metal_object_types,
+            allocation_callbacks: _,
             _ne: _,
         } = self;
 
@@ -1149,6 +1327,37 @@ impl InstanceCreateInfo {
             }
         }
 
+        if !layer_settings.is_empty() {
+            if !enabled_extensions.ext_layer_settings {
+                return Err(Box::new(ValidationError {
+                    context: "layer_settings".into(),
+                    problem: "is not empty".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                        Requires::InstanceExtension("ext_layer_settings"),
+                    ])]),
+                    ..Default::default()
+                }));
+            }
+
+            for (index, layer_setting) in layer_settings.iter().enumerate() {
+                layer_setting
+                    .validate()
+                    .map_err(|err| err.add_context(format!("layer_settings[{}]", index)))?;
+            }
+        }
+
+        if !(ExportMetalObjectTypes::METAL_DEVICE | ExportMetalObjectTypes::METAL_COMMAND_QUEUE)
+            .contains(export_metal_object_types)
+        {
+            return Err(Box::new(ValidationError {
+                context: "export_metal_object_types".into(),
+                problem: "contains types other than `ExportMetalObjectTypes::METAL_DEVICE` and \
+                    `ExportMetalObjectTypes::METAL_COMMAND_QUEUE`"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
         Ok(())
     }
 }
@@ -1266,4 +1475,71 @@ mod tests {
     fn create_instance() {
         let _ = instance!();
     }
+
+    #[test]
+    fn allocation_callbacks() {
+        use crate::{
+            instance::{
+                allocation_callbacks::{AllocationCallbacks, HostAllocator, SystemAllocationScope},
+                Instance, InstanceCreateInfo,
+            },
+            VulkanLibrary,
+        };
+        use std::{
+            alloc::{GlobalAlloc, Layout, System},
+            ptr::NonNull,
+            sync::{
+                atomic::{AtomicIsize, Ordering},
+                Arc,
+            },
+        };
+
+        struct CountingAllocator {
+            outstanding: Arc<AtomicIsize>,
+        }
+
+        unsafe impl HostAllocator for CountingAllocator {
+            fn allocate(
+                &self,
+                layout: Layout,
+                _scope: SystemAllocationScope,
+            ) -> Option<NonNull<u8>> {
+                self.outstanding.fetch_add(1, Ordering::Relaxed);
+
+                NonNull::new(unsafe { System.alloc(layout) })
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.outstanding.fetch_sub(1, Ordering::Relaxed);
+
+                System.dealloc(ptr.as_ptr(), layout)
+            }
+        }
+
+        let library = match VulkanLibrary::new() {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+        let outstanding = Arc::new(AtomicIsize::new(0));
+        let allocation_callbacks = AllocationCallbacks::new(CountingAllocator {
+            outstanding: outstanding.clone(),
+        });
+        let instance = match Instance::new(
+            library,
+            InstanceCreateInfo {
+                allocation_callbacks: Some(allocation_callbacks.clone()),
+                ..Default::default()
+            },
+        ) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+        assert!(Arc::ptr_eq(
+            instance.allocation_callbacks().unwrap(),
+            &allocation_callbacks,
+        ));
+        drop(instance);
+
+        assert_eq!(outstanding.load(Ordering::Relaxed), 0);
+    }
 }
@@ -0,0 +1,325 @@
+//! Integration with the [RenderDoc] in-application API.
+//!
+//! When the application is launched from RenderDoc, or RenderDoc is otherwise injected into the
+//! process, RenderDoc exposes an API that lets the application control captures itself. This is
+//! useful to capture one specific frame of a long-running application, or to capture work that
+//! is not tied to a swapchain at all, such as compute-only workloads.
+//!
+//! Vulkano never loads RenderDoc into the process itself; it only detects whether it is already
+//! present. If it is not, all the functions in this module do nothing.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::instance::Instance;
+//! # let instance: Arc<Instance> = return;
+//! if let Some(capture) = instance.begin_frame_capture() {
+//!     // Record and submit the work that should be captured...
+//!
+//!     capture.end();
+//! }
+//! ```
+//!
+//! This module is only available when the `renderdoc` feature is enabled.
+//!
+//! [RenderDoc]: https://renderdoc.org
+
+use super::Instance;
+use crate::VulkanObject;
+use ash::vk::Handle;
+use libloading::Library;
+use once_cell::sync::OnceCell;
+use std::{
+    ffi::{c_char, c_int, c_void, CString},
+    fmt::{Debug, Error as FmtError, Formatter},
+    ptr,
+    sync::Arc,
+};
+
+/// `eRENDERDOC_API_Version_1_4_0`
+const API_VERSION_1_4_0: c_int = 10400;
+
+type GetApiFn = unsafe extern "C" fn(version: c_int, out_api_pointers: *mut *mut c_void) -> c_int;
+type DevicePointer = *const c_void;
+type WindowHandle = *const c_void;
+
+/// The function table of version 1.4.0 of the RenderDoc API, `RENDERDOC_API_1_4_0`.
+///
+/// Only the entries that vulkano calls have a precise signature, the others are only there to
+/// keep the layout intact.
+#[allow(dead_code)]
+#[repr(C)]
+struct RenderDocApi {
+    get_api_version: unsafe extern "C" fn(major: *mut c_int, minor: *mut c_int, patch: *mut c_int),
+    set_capture_option_u32: *const c_void,
+    set_capture_option_f32: *const c_void,
+    get_capture_option_u32: *const c_void,
+    get_capture_option_f32: *const c_void,
+    set_focus_toggle_keys: *const c_void,
+    set_capture_keys: *const c_void,
+    get_overlay_bits: *const c_void,
+    mask_overlay_bits: *const c_void,
+    remove_hooks: *const c_void,
+    unload_crash_handler: *const c_void,
+    set_capture_file_path_template: unsafe extern "C" fn(path_template: *const c_char),
+    get_capture_file_path_template: *const c_void,
+    get_num_captures: unsafe extern "C" fn() -> u32,
+    get_capture: *const c_void,
+    trigger_capture: unsafe extern "C" fn(),
+    is_target_control_connected: *const c_void,
+    launch_replay_ui: *const c_void,
+    set_active_window: *const c_void,
+    start_frame_capture: unsafe extern "C" fn(device: DevicePointer, window: WindowHandle),
+    is_frame_capturing: unsafe extern "C" fn() -> u32,
+    end_frame_capture: unsafe extern "C" fn(device: DevicePointer, window: WindowHandle) -> u32,
+    trigger_multi_frame_capture: unsafe extern "C" fn(num_frames: u32),
+    set_capture_file_comments: *const c_void,
+    discard_frame_capture: unsafe extern "C" fn(device: DevicePointer, window: WindowHandle) -> u32,
+}
+
+/// The RenderDoc in-application API, if RenderDoc is present in the process.
+pub(crate) struct RenderDoc {
+    api: &'static RenderDocApi,
+    _library: Library,
+}
+
+// The RenderDoc API is thread-safe.
+unsafe impl Send for RenderDoc {}
+unsafe impl Sync for RenderDoc {}
+
+impl RenderDoc {
+    /// Returns the RenderDoc API, or `None` if RenderDoc is not present in the process.
+    ///
+    /// RenderDoc is only looked for the first time this is called.
+    pub(crate) fn get() -> Option<&'static Self> {
+        static RENDERDOC: OnceCell<Option<RenderDoc>> = OnceCell::new();
+
+        RENDERDOC.get_or_init(|| unsafe { Self::load() }).as_ref()
+    }
+
+    unsafe fn load() -> Option<Self> {
+        let library = unsafe { open_already_loaded()? };
+        let get_api = unsafe { library.get::<GetApiFn>(b"RENDERDOC_GetAPI\0").ok()? };
+
+        let mut api: *mut c_void = ptr::null_mut();
+
+        if unsafe { get_api(API_VERSION_1_4_0, &mut api) } != 1 || api.is_null() {
+            return None;
+        }
+
+        Some(RenderDoc {
+            api: unsafe { &*api.cast::<RenderDocApi>() },
+            _library: library,
+        })
+    }
+
+    fn version(&self) -> (u32, u32, u32) {
+        let (mut major, mut minor, mut patch) = (0, 0, 0);
+        unsafe { (self.api.get_api_version)(&mut major, &mut minor, &mut patch) };
+
+        (major as u32, minor as u32, patch as u32)
+    }
+}
+
+impl Debug for RenderDoc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_struct("RenderDoc")
+            .field("version", &self.version())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(windows)]
+unsafe fn open_already_loaded() -> Option<Library> {
+    unsafe { libloading::os::windows::Library::open_already_loaded("renderdoc.dll") }
+        .ok()
+        .map(Into::into)
+}
+
+#[cfg(all(unix, not(target_vendor = "apple")))]
+unsafe fn open_already_loaded() -> Option<Library> {
+    #[cfg(target_os = "android")]
+    const LIBRARY_NAME: &str = "libVkLayer_GLES_RenderDoc.so";
+    #[cfg(not(target_os = "android"))]
+    const LIBRARY_NAME: &str = "librenderdoc.so";
+
+    // `RTLD_NOLOAD` makes this fail instead of loading RenderDoc if it's not already present.
+    unsafe {
+        libloading::os::unix::Library::open(Some(LIBRARY_NAME), libc::RTLD_NOW | libc::RTLD_NOLOAD)
+    }
+    .ok()
+    .map(Into::into)
+}
+
+#[cfg(not(any(windows, all(unix, not(target_vendor = "apple")))))]
+unsafe fn open_already_loaded() -> Option<Library> {
+    None
+}
+
+/// # RenderDoc integration
+///
+/// These functions are only available when the `renderdoc` feature is enabled. See the
+/// [`renderdoc`](crate::instance::renderdoc) module for more information.
+impl Instance {
+    /// Returns whether RenderDoc is present in the process, so that captures can be made.
+    #[inline]
+    pub fn is_renderdoc_attached(&self) -> bool {
+        RenderDoc::get().is_some()
+    }
+
+    /// Returns the version of the RenderDoc API, or `None` if RenderDoc is not present in the
+    /// process.
+    #[inline]
+    pub fn renderdoc_api_version(&self) -> Option<(u32, u32, u32)> {
+        RenderDoc::get().map(RenderDoc::version)
+    }
+
+    /// Captures the next frame that is presented, as if the capture key was pressed.
+    ///
+    /// Returns `false` if RenderDoc is not present in the process.
+    #[inline]
+    pub fn trigger_capture(&self) -> bool {
+        if let Some(renderdoc) = RenderDoc::get() {
+            unsafe { (renderdoc.api.trigger_capture)() };
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Captures the next `num_frames` frames that are presented.
+    ///
+    /// Returns `false` if RenderDoc is not present in the process.
+    pub fn trigger_multi_frame_capture(&self, num_frames: u32) -> bool {
+        if let Some(renderdoc) = RenderDoc::get() {
+            unsafe { (renderdoc.api.trigger_multi_frame_capture)(num_frames) };
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Begins capturing all the work that is done through this instance, until the returned
+    /// [`FrameCapture`] is ended or dropped.
+    ///
+    /// Unlike [`trigger_capture`], the captured work doesn't need to be delimited by swapchain
+    /// presentation. This makes it possible to capture compute-only workloads, or work that is
+    /// done without a swapchain.
+    ///
+    /// Returns `None` if RenderDoc is not present in the process.
+    ///
+    /// [`trigger_capture`]: Self::trigger_capture
+    pub fn begin_frame_capture(self: &Arc<Self>) -> Option<FrameCapture> {
+        let renderdoc = RenderDoc::get()?;
+        let device_pointer = unsafe { self.renderdoc_device_pointer() };
+        unsafe { (renderdoc.api.start_frame_capture)(device_pointer, ptr::null()) };
+
+        Some(FrameCapture {
+            instance: self.clone(),
+            renderdoc,
+            ended: false,
+        })
+    }
+
+    /// Returns whether RenderDoc is currently capturing a frame.
+    ///
+    /// Returns `false` if RenderDoc is not present in the process.
+    #[inline]
+    pub fn is_frame_capturing(&self) -> bool {
+        RenderDoc::get().map_or(false, |renderdoc| unsafe {
+            (renderdoc.api.is_frame_capturing)() != 0
+        })
+    }
+
+    /// Returns the number of captures that RenderDoc has made so far.
+    ///
+    /// Returns `0` if RenderDoc is not present in the process.
+    #[inline]
+    pub fn renderdoc_num_captures(&self) -> u32 {
+        RenderDoc::get().map_or(0, |renderdoc| unsafe { (renderdoc.api.get_num_captures)() })
+    }
+
+    /// Sets the template that RenderDoc uses for the path of capture files. The capture
+    /// number and extension are appended to it.
+    ///
+    /// Returns `false` if RenderDoc is not present in the process.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `path_template` contains a nul byte.
+    pub fn set_renderdoc_capture_path_template(&self, path_template: &str) -> bool {
+        let path_template = CString::new(path_template).unwrap();
+
+        if let Some(renderdoc) = RenderDoc::get() {
+            unsafe { (renderdoc.api.set_capture_file_path_template)(path_template.as_ptr()) };
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the pointer that RenderDoc uses to identify this instance.
+    ///
+    /// This is the pointer to the dispatch table that is stored at the start of every
+    /// dispatchable Vulkan object, which is what `RENDERDOC_DEVICEPOINTER_FROM_VKINSTANCE` does.
+    unsafe fn renderdoc_device_pointer(&self) -> DevicePointer {
+        unsafe { *(self.handle().as_raw() as *const DevicePointer) }
+    }
+}
+
+/// A RenderDoc capture that was begun with [`Instance::begin_frame_capture`].
+///
+/// The capture is ended when this value is dropped, or when [`end`] or [`discard`] is called.
+///
+/// [`end`]: Self::end
+/// [`discard`]: Self::discard
+#[derive(Debug)]
+#[must_use = "the capture is ended when this value is dropped"]
+pub struct FrameCapture {
+    instance: Arc<Instance>,
+    renderdoc: &'static RenderDoc,
+    ended: bool,
+}
+
+impl FrameCapture {
+    /// Returns the instance that is being captured.
+    #[inline]
+    pub fn instance(&self) -> &Arc<Instance> {
+        &self.instance
+    }
+
+    /// Ends the capture, and saves it.
+    ///
+    /// Returns `false` if RenderDoc failed to make the capture.
+    #[inline]
+    pub fn end(mut self) -> bool {
+        self.ended = true;
+        let device_pointer = unsafe { self.instance.renderdoc_device_pointer() };
+
+        unsafe { (self.renderdoc.api.end_frame_capture)(device_pointer, ptr::null()) != 0 }
+    }
+
+    /// Ends the capture, and throws it away instead of saving it.
+    ///
+    /// This is useful when it only becomes clear during the capture that it's not interesting
+    /// after all.
+    #[inline]
+    pub fn discard(mut self) -> bool {
+        self.ended = true;
+        let device_pointer = unsafe { self.instance.renderdoc_device_pointer() };
+
+        unsafe { (self.renderdoc.api.discard_frame_capture)(device_pointer, ptr::null()) != 0 }
+    }
+}
+
+impl Drop for FrameCapture {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.ended {
+            let device_pointer = unsafe { self.instance.renderdoc_device_pointer() };
+            unsafe { (self.renderdoc.api.end_frame_capture)(device_pointer, ptr::null()) };
+        }
+    }
+}
@@ -94,8 +94,14 @@
 //! |----------------------|----------------------------------------------------------------|
 //! | `macros`             | Include reexports from [`vulkano-macros`]. Enabled by default. |
 //! | `x11`                | Support for X11 platforms. Enabled by default.                 |
+//! | `aftermath`          | Hook points for the NVIDIA [Nsight Aftermath] SDK.             |
+//! | `moltenvk_static`    | Link MoltenVK statically on macOS, instead of loading the      |
+//! |                      | Vulkan loader at runtime.                                      |
 //! | `document_unchecked` | Include `_unchecked` functions in the generated documentation. |
+//! | `renderdoc`          | Triggers [RenderDoc] captures from within the application.     |
 //! | `serde`              | Enables (de)serialization of certain types using [`serde`].    |
+//! | `tracing`            | Emits [`tracing`] spans for expensive operations.              |
+//! | `vma`                | Enables a memory allocator backed by [`vk-mem`].               |
 //!
 //! [`Instance`]: instance::Instance
 //! [`Surface`]: swapchain::Surface
@@ -118,7 +124,11 @@
 //! [`RenderPass`]: render_pass::RenderPass
 //! [`Framebuffer`]: render_pass::Framebuffer
 //! [`vulkano-macros`]: vulkano_macros
+//! [Nsight Aftermath]: https://developer.nvidia.com/nsight-aftermath
+//! [RenderDoc]: https://renderdoc.org
 //! [`serde`]: https://crates.io/crates/serde
+//! [`tracing`]: https://crates.io/crates/tracing
+//! [`vk-mem`]: https://crates.io/crates/vk-mem
 
 pub use ash::vk::Handle;
 use bytemuck::{Pod, Zeroable};
@@ -157,6 +167,7 @@ pub mod instance;
 pub mod library;
 mod macros;
 pub mod memory;
+pub mod micromap;
 pub mod padded;
 pub mod pipeline;
 pub mod query;
@@ -275,6 +286,10 @@ impl Display for VulkanError {
         let msg = match self {
             VulkanError::NotReady => "a resource is not yet ready",
             VulkanError::Timeout => "an operation has not completed in the specified time",
+            VulkanError::PipelineCompileRequired => {
+                "a pipeline could not be created without compiling it, but compilation was not \
+                allowed"
+            }
             VulkanError::OutOfHostMemory => "a host memory allocation has failed",
             VulkanError::OutOfDeviceMemory => "a device memory allocation has failed",
             VulkanError::InitializationFailed => {
@@ -39,8 +39,16 @@ pub struct VulkanLibrary {
 
 impl VulkanLibrary {
     /// Loads the default Vulkan library for this system.
+    ///
+    /// On iOS and tvOS, and on macOS if the `moltenvk_static` feature is enabled, this uses the
+    /// Vulkan implementation that is statically linked into the executable. Otherwise, the Vulkan
+    /// loader is loaded as a dynamic library from the default search paths.
     pub fn new() -> Result<Arc<Self>, LoadingError> {
-        #[cfg(any(target_os = "ios", target_os = "tvos"))]
+        #[cfg(any(
+            target_os = "ios",
+            target_os = "tvos",
+            all(target_os = "macos", feature = "moltenvk_static"),
+        ))]
         #[allow(non_snake_case)]
         fn def_loader_impl() -> Result<Box<dyn Loader>, LoadingError> {
             let loader = crate::statically_linked_vulkan_loader!();
@@ -48,7 +56,11 @@ impl VulkanLibrary {
             Ok(Box::new(loader))
         }
 
-        #[cfg(not(any(target_os = "ios", target_os = "tvos")))]
+        #[cfg(not(any(
+            target_os = "ios",
+            target_os = "tvos",
+            all(target_os = "macos", feature = "moltenvk_static"),
+        )))]
         fn def_loader_impl() -> Result<Box<dyn Loader>, LoadingError> {
             #[cfg(windows)]
             fn get_paths() -> [&'static Path; 1] {
@@ -88,6 +100,55 @@ impl VulkanLibrary {
         def_loader_impl().and_then(VulkanLibrary::with_loader)
     }
 
+    /// Loads the Vulkan library at the given path.
+    ///
+    /// This can be used to load a Vulkan loader or implementation that is bundled with the
+    /// application, instead of the one that is installed on the system.
+    ///
+    /// # Safety
+    ///
+    /// - The dynamic library must be a valid Vulkan implementation.
+    pub unsafe fn from_path(path: impl AsRef<Path>) -> Result<Arc<Self>, LoadingError> {
+        DynamicLibraryLoader::new(path).and_then(VulkanLibrary::with_loader)
+    }
+
+    /// Creates a `VulkanLibrary` from a `vkGetInstanceProcAddr` function that was obtained by
+    /// the caller.
+    ///
+    /// # Safety
+    ///
+    /// - `get_instance_proc_addr` must be the `vkGetInstanceProcAddr` function of a valid Vulkan
+    ///   implementation.
+    /// - `get_instance_proc_addr`, and the functions it returns, must stay valid for as long as
+    ///   the library or any object created from it is alive.
+    pub unsafe fn from_get_instance_proc_addr(
+        get_instance_proc_addr: ash::vk::PFN_vkGetInstanceProcAddr,
+    ) -> Result<Arc<Self>, LoadingError> {
+        struct FunctionPointerLoader(ash::vk::PFN_vkGetInstanceProcAddr);
+
+        unsafe impl Loader for FunctionPointerLoader {
+            unsafe fn get_instance_proc_addr(
+                &self,
+                instance: ash::vk::Instance,
+                name: *const c_char,
+            ) -> ash::vk::PFN_vkVoidFunction {
+                (self.0)(instance, name)
+            }
+        }
+
+        VulkanLibrary::with_loader(FunctionPointerLoader(get_instance_proc_addr))
+    }
+
+    /// Creates a `VulkanLibrary` from an `ash::Entry` that was loaded by the caller.
+    ///
+    /// This lets vulkano share the Vulkan library with other code in the same process that
+    /// already loaded it, such as the host application of a plugin. The entry is kept alive for
+    /// as long as the `VulkanLibrary`.
+    #[inline]
+    pub fn from_entry(entry: ash::Entry) -> Result<Arc<Self>, LoadingError> {
+        VulkanLibrary::with_loader(entry)
+    }
+
     /// Loads a custom Vulkan library.
     pub fn with_loader(loader: impl Loader + 'static) -> Result<Arc<Self>, LoadingError> {
         let fns = EntryFunctions::load(|name| unsafe {
@@ -330,6 +391,17 @@ where
     }
 }
 
+unsafe impl Loader for ash::Entry {
+    #[inline]
+    unsafe fn get_instance_proc_addr(
+        &self,
+        instance: ash::vk::Instance,
+        name: *const c_char,
+    ) -> ash::vk::PFN_vkVoidFunction {
+        (self.static_fn().get_instance_proc_addr)(instance, name)
+    }
+}
+
 impl Debug for dyn Loader {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
         f.debug_struct("Loader").finish_non_exhaustive()
@@ -352,6 +424,16 @@ impl DynamicLibraryLoader {
     pub unsafe fn new(path: impl AsRef<Path>) -> Result<DynamicLibraryLoader, LoadingError> {
         let vk_lib = Library::new(path.as_ref()).map_err(LoadingError::LibraryLoadFailure)?;
 
+        Self::from_library(vk_lib)
+    }
+
+    /// Tries to load `vkGetInstanceProcAddr` in a dynamic library that was already opened by
+    /// the caller.
+    ///
+    /// # Safety
+    ///
+    /// - The dynamic library must be a valid Vulkan implementation.
+    pub unsafe fn from_library(vk_lib: Library) -> Result<DynamicLibraryLoader, LoadingError> {
         let get_instance_proc_addr = *vk_lib
             .get(b"vkGetInstanceProcAddr")
             .map_err(LoadingError::LibraryLoadFailure)?;
@@ -12,6 +12,7 @@ macro_rules! vulkan_bitflags {
     } => {
         $(#[doc = $ty_doc])*
         #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $ty($repr);
 
         impl $ty {
@@ -239,6 +240,7 @@ macro_rules! vulkan_bitflags {
     } => {
         $(#[doc = $ty_doc])*
         #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $ty($repr);
 
         impl $ty {
@@ -568,6 +570,7 @@ macro_rules! vulkan_enum {
     } => {
         $(#[doc = $ty_doc])*
         #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[repr($repr)]
         pub enum $ty {
             $(
@@ -633,6 +636,7 @@ macro_rules! vulkan_enum {
     } => {
         $(#[doc = $ty_doc])*
         #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[non_exhaustive]
         #[repr($repr)]
         pub enum $ty {
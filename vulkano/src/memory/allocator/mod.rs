@@ -208,25 +208,33 @@
 
 mod layout;
 pub mod suballocator;
-
-use self::{array_vec::ArrayVec, suballocator::Region};
+#[cfg(feature = "vma")]
+pub mod vma;
+
+#[cfg(feature = "vma")]
+pub use self::vma::{VmaMemoryAllocator, VmaMemoryAllocatorCreateInfo};
+use self::{
+    array_vec::ArrayVec,
+    suballocator::{Region, SuballocationNode, SuballocationType},
+};
 pub use self::{
     layout::DeviceLayout,
     suballocator::{
-        AllocationType, BuddyAllocator, BumpAllocator, FreeListAllocator, Suballocation,
-        Suballocator, SuballocatorError,
+        AllocationType, BuddyAllocator, BumpAllocator, FragmentationMetrics, FreeListAllocator,
+        Suballocation, Suballocator, SuballocatorError,
     },
 };
 use super::{
     DedicatedAllocation, DeviceAlignment, DeviceMemory, ExternalMemoryHandleTypes,
     MemoryAllocateFlags, MemoryAllocateInfo, MemoryMapFlags, MemoryMapInfo, MemoryProperties,
-    MemoryPropertyFlags, MemoryRequirements, MemoryType,
+    MemoryPropertyFlags, MemoryRequirements, MemoryType, ResourceMemory,
 };
 use crate::{
     device::{Device, DeviceOwned},
     instance::InstanceOwnedDebugWrapper,
-    DeviceSize, Validated, Version, VulkanError,
+    DeviceSize, Validated, Version, VulkanError, VulkanObject,
 };
+use ahash::HashMap;
 use ash::vk::MAX_MEMORY_TYPES;
 use parking_lot::{Mutex, MutexGuard};
 use std::{
@@ -235,6 +243,7 @@ use std::{
     iter::FusedIterator,
     mem,
     ops::BitOr,
+    panic::Location,
     ptr, slice,
     sync::Arc,
 };
@@ -672,16 +681,43 @@ pub struct AllocationCreateInfo {
     /// The default value is [`MemoryAllocatePreference::Unknown`].
     pub allocate_preference: MemoryAllocatePreference,
 
+    /// A name for the allocation, to tell which part of the application it belongs to.
+    ///
+    /// [`GenericMemoryAllocator`] shows the tag in its [report] of outstanding allocations.
+    /// [`Buffer::new`] and [`Image::new`] also use it as the debug utils object name of the
+    /// resource, if the [`ext_debug_utils`] extension is enabled on the instance.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [report]: GenericMemoryAllocator::report
+    /// [`Buffer::new`]: crate::buffer::Buffer::new
+    /// [`Image::new`]: crate::image::Image::new
+    /// [`ext_debug_utils`]: crate::instance::InstanceExtensions::ext_debug_utils
+    pub tag: Option<Arc<str>>,
+
+    /// The location in the source code that the allocation was made from.
+    ///
+    /// [`GenericMemoryAllocator`] shows the origin in its [report] of outstanding allocations.
+    ///
+    /// The default value is the location where `AllocationCreateInfo::default()` was called,
+    /// which is usually right where the resource is created.
+    ///
+    /// [report]: GenericMemoryAllocator::report
+    pub origin: Option<&'static Location<'static>>,
+
     pub _ne: crate::NonExhaustive,
 }
 
 impl Default for AllocationCreateInfo {
     #[inline]
+    #[track_caller]
     fn default() -> Self {
         AllocationCreateInfo {
             memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
             memory_type_bits: u32::MAX,
             allocate_preference: MemoryAllocatePreference::Unknown,
+            tag: None,
+            origin: Some(Location::caller()),
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -953,6 +989,7 @@ impl<S> GenericMemoryAllocator<S> {
     #[allow(clippy::declare_interior_mutable_const)]
     const EMPTY_POOL: DeviceMemoryPool<S> = DeviceMemoryPool {
         blocks: Mutex::new(Vec::new()),
+        dedicated_allocations: Mutex::new(Vec::new()),
         property_flags: MemoryPropertyFlags::empty(),
         atom_size: DeviceAlignment::MIN,
         block_size: 0,
@@ -1106,6 +1143,221 @@ impl<S> GenericMemoryAllocator<S> {
     }
 }
 
+impl<S: Suballocator> GenericMemoryAllocator<S> {
+    /// Returns statistics about the memory currently allocated by the allocator, per memory type,
+    /// per memory heap and in total.
+    ///
+    /// # Locking behavior
+    ///
+    /// This locks each pool in turn while gathering its statistics, which means that the
+    /// statistics of different pools are not necessarily consistent with one another if other
+    /// threads allocate or deallocate in the meantime.
+    pub fn statistics(&self) -> AllocatorStatistics {
+        let memory_properties = self.device.physical_device().memory_properties();
+        let memory_types: Vec<_> = self
+            .pools
+            .iter()
+            .map(DeviceMemoryPool::statistics)
+            .collect();
+        let mut memory_heaps =
+            vec![MemoryStatistics::default(); memory_properties.memory_heaps.len()];
+        let mut total = MemoryStatistics::default();
+
+        for (memory_type, statistics) in memory_properties.memory_types.iter().zip(&memory_types) {
+            memory_heaps[memory_type.heap_index as usize].merge(statistics);
+            total.merge(statistics);
+        }
+
+        AllocatorStatistics {
+            memory_types,
+            memory_heaps,
+            total,
+        }
+    }
+
+    /// Returns a report listing every allocation that is currently outstanding, together with
+    /// its [tag] and origin if they were set.
+    ///
+    /// This is mostly useful for tracking down leaks: calling this right before the allocator
+    /// is dropped, after all resources were supposed to be freed, should give an empty report.
+    ///
+    /// # Locking behavior
+    ///
+    /// This locks each pool in turn while gathering its allocations.
+    ///
+    /// [tag]: Self::set_allocation_tag
+    pub fn report(&self) -> AllocationReport {
+        let mut allocations = Vec::new();
+
+        for (memory_type_index, pool) in self.pools.iter().enumerate() {
+            let memory_type_index = memory_type_index as u32;
+
+            for block in pool.blocks.lock().iter() {
+                let device_memory = block.device_memory.handle();
+
+                allocations.extend(
+                    block
+                        .suballocator
+                        .suballocations()
+                        .filter(|node| node.allocation_type != SuballocationType::Free)
+                        .map(|node| AllocationReportEntry {
+                            memory_type_index,
+                            device_memory,
+                            offset: node.offset,
+                            size: node.size,
+                            allocation_type: node.allocation_type,
+                            dedicated: false,
+                            tag: block
+                                .tags
+                                .get(&node.offset)
+                                .and_then(|tag| tag.name.clone()),
+                            origin: block.tags.get(&node.offset).and_then(|tag| tag.origin),
+                        }),
+                );
+            }
+
+            for entry in pool.dedicated_allocations.lock().iter() {
+                allocations.push(AllocationReportEntry {
+                    memory_type_index,
+                    device_memory: entry.device_memory.handle(),
+                    offset: 0,
+                    size: entry.device_memory.allocation_size(),
+                    allocation_type: SuballocationType::Unknown,
+                    dedicated: true,
+                    tag: entry.tag.name.clone(),
+                    origin: entry.tag.origin,
+                });
+            }
+        }
+
+        AllocationReport { allocations }
+    }
+
+    /// Returns a map of every [`DeviceMemory`] block that is currently allocated, listing the
+    /// suballocations within it along with its [fragmentation metrics].
+    ///
+    /// This is intended for visualizing how memory is laid out, for example to draw a heat-map of
+    /// VRAM usage in a debug view. Dedicated allocations are included as blocks consisting of a
+    /// single suballocation.
+    ///
+    /// # Locking behavior
+    ///
+    /// This locks each pool in turn while gathering its blocks.
+    ///
+    /// [fragmentation metrics]: Suballocator::fragmentation_metrics
+    pub fn memory_map(&self) -> Vec<MemoryBlockMap> {
+        let memory_types = &self
+            .device
+            .physical_device()
+            .memory_properties()
+            .memory_types;
+        let mut blocks = Vec::new();
+
+        for (memory_type_index, pool) in self.pools.iter().enumerate() {
+            let memory_heap_index = memory_types[memory_type_index].heap_index;
+            let memory_type_index = memory_type_index as u32;
+
+            blocks.extend(pool.blocks.lock().iter().map(|block| MemoryBlockMap {
+                memory_type_index,
+                memory_heap_index,
+                device_memory: block.device_memory.handle(),
+                size: block.device_memory.allocation_size(),
+                dedicated: false,
+                suballocations: block.suballocator.suballocations().collect(),
+                fragmentation_metrics: block.suballocator.fragmentation_metrics(),
+            }));
+
+            blocks.extend(pool.dedicated_allocations.lock().iter().map(|entry| {
+                let size = entry.device_memory.allocation_size();
+
+                MemoryBlockMap {
+                    memory_type_index,
+                    memory_heap_index,
+                    device_memory: entry.device_memory.handle(),
+                    size,
+                    dedicated: true,
+                    suballocations: vec![SuballocationNode {
+                        offset: 0,
+                        size,
+                        allocation_type: SuballocationType::Unknown,
+                    }],
+                    fragmentation_metrics: FragmentationMetrics {
+                        allocation_count: 1,
+                        ..Default::default()
+                    },
+                }
+            }));
+        }
+
+        blocks
+    }
+
+    /// Sets the tag of an allocation, which is shown in the [report] of outstanding allocations.
+    /// Pass [`None`] to remove a previously set tag.
+    ///
+    /// The tag is removed automatically once the allocation is deallocated.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `memory` was not allocated by `self`.
+    ///
+    /// [report]: Self::report
+    pub fn set_allocation_tag(&self, memory: &ResourceMemory, tag: Option<Arc<str>>) {
+        let found = self.update_allocation_tag(
+            memory.device_memory(),
+            memory
+                .suballocation_handle
+                .is_some()
+                .then(|| memory.offset()),
+            memory.allocation_handle,
+            |allocation_tag| allocation_tag.name = tag,
+        );
+
+        assert!(found, "`memory` was not allocated by this allocator");
+    }
+
+    /// Calls `f` with the tag of the allocation, and returns whether the allocation was found.
+    fn update_allocation_tag(
+        &self,
+        device_memory: &Arc<DeviceMemory>,
+        suballocation_offset: Option<DeviceSize>,
+        allocation_handle: AllocationHandle,
+        f: impl FnOnce(&mut AllocationTag),
+    ) -> bool {
+        let pool = &self.pools[device_memory.memory_type_index() as usize];
+
+        if let Some(offset) = suballocation_offset {
+            let block_ptr: *const DeviceMemoryBlock<S> = allocation_handle.0.cast();
+            let mut blocks = pool.blocks.lock();
+            let Some(block) = blocks
+                .iter_mut()
+                .find(|block| ptr::addr_of!(***block) == block_ptr)
+            else {
+                return false;
+            };
+
+            let tag = block.tags.entry(offset).or_default();
+            f(tag);
+
+            if tag.is_empty() {
+                block.tags.remove(&offset);
+            }
+        } else {
+            let mut dedicated_allocations = pool.dedicated_allocations.lock();
+            let Some(entry) = dedicated_allocations
+                .iter_mut()
+                .find(|entry| Arc::ptr_eq(&entry.device_memory, device_memory))
+            else {
+                return false;
+            };
+
+            f(&mut entry.tag);
+        }
+
+        true
+    }
+}
+
 unsafe impl<S: Suballocator + Send + 'static> MemoryAllocator for GenericMemoryAllocator<S> {
     fn find_memory_type_index(
         &self,
@@ -1297,6 +1549,14 @@ unsafe impl<S: Suballocator + Send + 'static> MemoryAllocator for GenericMemoryA
     /// [`OutOfPoolMemory`]: MemoryAllocatorError::OutOfPoolMemory
     /// [`DedicatedAllocationRequired`]: MemoryAllocatorError::DedicatedAllocationRequired
     /// [`BlockSizeExceeded`]: MemoryAllocatorError::BlockSizeExceeded
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(size = requirements.layout.size(), ?allocation_type),
+        )
+    )]
     fn allocate(
         &self,
         requirements: MemoryRequirements,
@@ -1318,6 +1578,8 @@ unsafe impl<S: Suballocator + Send + 'static> MemoryAllocator for GenericMemoryA
             memory_type_filter,
             memory_type_bits: _,
             allocate_preference,
+            tag,
+            origin,
             _ne: _,
         } = create_info;
 
@@ -1415,7 +1677,23 @@ unsafe impl<S: Suballocator + Send + 'static> MemoryAllocator for GenericMemoryA
             };
 
             match res {
-                Ok(allocation) => return Ok(allocation),
+                Ok(allocation) => {
+                    if tag.is_some() || origin.is_some() {
+                        self.update_allocation_tag(
+                            &allocation.device_memory,
+                            allocation
+                                .suballocation
+                                .as_ref()
+                                .map(|suballocation| suballocation.offset),
+                            allocation.allocation_handle,
+                            |allocation_tag| {
+                                *allocation_tag = AllocationTag { name: tag, origin };
+                            },
+                        );
+                    }
+
+                    return Ok(allocation);
+                }
                 // Try a different memory type.
                 Err(err) => {
                     memory_type_bits &= !(1 << memory_type_index);
@@ -1444,6 +1722,14 @@ unsafe impl<S: Suballocator + Send + 'static> MemoryAllocator for GenericMemoryA
             )
             .map_err(MemoryAllocatorError::AllocateDeviceMemory)?;
 
+        self.pools[memory_type_index as usize]
+            .dedicated_allocations
+            .lock()
+            .push(DedicatedAllocationEntry {
+                device_memory: device_memory.clone(),
+                tag: AllocationTag::default(),
+            });
+
         Ok(MemoryAlloc {
             device_memory,
             suballocation: None,
@@ -1479,6 +1765,18 @@ unsafe impl<S: Suballocator + Send + 'static> MemoryAllocator for GenericMemoryA
             block.deallocate(suballocation);
 
             drop(pool);
+        } else {
+            let memory_type_index = allocation.device_memory.memory_type_index();
+            let mut dedicated_allocations = self.pools[memory_type_index as usize]
+                .dedicated_allocations
+                .lock();
+
+            if let Some(index) = dedicated_allocations
+                .iter()
+                .position(|entry| Arc::ptr_eq(&entry.device_memory, &allocation.device_memory))
+            {
+                dedicated_allocations.swap_remove(index);
+            }
         }
     }
 }
@@ -1547,6 +1845,8 @@ unsafe impl<S> DeviceOwned for GenericMemoryAllocator<S> {
 #[derive(Debug)]
 pub struct DeviceMemoryPool<S> {
     blocks: Mutex<Vec<Box<DeviceMemoryBlock<S>>>>,
+    // Dedicated allocations aren't suballocated, but we still keep track of them for statistics.
+    dedicated_allocations: Mutex<Vec<DedicatedAllocationEntry>>,
     // This is cached here for faster access, so we don't need to hop through 3 pointers.
     property_flags: MemoryPropertyFlags,
     atom_size: DeviceAlignment,
@@ -1571,12 +1871,62 @@ impl<S> DeviceMemoryPool<S> {
     }
 }
 
+impl<S: Suballocator> DeviceMemoryPool<S> {
+    fn statistics(&self) -> MemoryStatistics {
+        let mut statistics = MemoryStatistics::default();
+
+        for block in self.blocks.lock().iter() {
+            let allocation_size = block.device_memory.allocation_size();
+
+            statistics.block_count += 1;
+            statistics.allocation_count += block.allocation_count;
+            statistics.used_size += allocation_size - block.free_size();
+            statistics.reserved_size += allocation_size;
+            statistics.largest_free_size = statistics
+                .largest_free_size
+                .max(block.suballocator.fragmentation_metrics().largest_free_size);
+        }
+
+        for entry in self.dedicated_allocations.lock().iter() {
+            let allocation_size = entry.device_memory.allocation_size();
+
+            statistics.block_count += 1;
+            statistics.allocation_count += 1;
+            statistics.used_size += allocation_size;
+            statistics.reserved_size += allocation_size;
+        }
+
+        statistics
+    }
+}
+
+#[derive(Debug)]
+struct DedicatedAllocationEntry {
+    device_memory: Arc<DeviceMemory>,
+    tag: AllocationTag,
+}
+
+// The user-provided information about an allocation, for the report.
+#[derive(Clone, Debug, Default)]
+struct AllocationTag {
+    name: Option<Arc<str>>,
+    origin: Option<&'static Location<'static>>,
+}
+
+impl AllocationTag {
+    fn is_empty(&self) -> bool {
+        self.name.is_none() && self.origin.is_none()
+    }
+}
+
 /// A [`DeviceMemory`] block within a [`DeviceMemoryPool`].
 #[derive(Debug)]
 pub struct DeviceMemoryBlock<S> {
     device_memory: Arc<DeviceMemory>,
     suballocator: S,
     allocation_count: usize,
+    // User-provided tags of suballocations, keyed by their offset.
+    tags: HashMap<DeviceSize, AllocationTag>,
 }
 
 impl<S: Suballocator> DeviceMemoryBlock<S> {
@@ -1590,6 +1940,7 @@ impl<S: Suballocator> DeviceMemoryBlock<S> {
             device_memory,
             suballocator,
             allocation_count: 0,
+            tags: HashMap::default(),
         })
     }
 
@@ -1613,6 +1964,7 @@ impl<S: Suballocator> DeviceMemoryBlock<S> {
     }
 
     unsafe fn deallocate(&mut self, suballocation: Suballocation) {
+        self.tags.remove(&suballocation.offset);
         self.suballocator.deallocate(suballocation);
 
         self.allocation_count -= 1;
@@ -1737,6 +2089,209 @@ impl<S> Drop for DeviceMemoryPoolGuard<'_, S> {
     }
 }
 
+/// Memory usage statistics of a [`GenericMemoryAllocator`], for a single memory type, a single
+/// memory heap or the allocator as a whole.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStatistics {
+    /// The number of [`DeviceMemory`] blocks, including dedicated allocations.
+    pub block_count: usize,
+
+    /// The number of allocations that are currently outstanding, including dedicated
+    /// allocations.
+    pub allocation_count: usize,
+
+    /// The number of bytes of the blocks that are not free. This includes any padding that was
+    /// needed for alignment.
+    pub used_size: DeviceSize,
+
+    /// The total number of bytes of the blocks, whether used or not.
+    pub reserved_size: DeviceSize,
+
+    /// The size of the largest free region within any of the blocks. This is the largest
+    /// allocation that could be made without allocating a new block, disregarding alignment.
+    pub largest_free_size: DeviceSize,
+}
+
+impl MemoryStatistics {
+    fn merge(&mut self, other: &Self) {
+        self.block_count += other.block_count;
+        self.allocation_count += other.allocation_count;
+        self.used_size += other.used_size;
+        self.reserved_size += other.reserved_size;
+        self.largest_free_size = self.largest_free_size.max(other.largest_free_size);
+    }
+}
+
+/// Memory usage statistics of a [`GenericMemoryAllocator`], returned by
+/// [`GenericMemoryAllocator::statistics`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllocatorStatistics {
+    /// The statistics of each memory type, indexed by the memory type index.
+    pub memory_types: Vec<MemoryStatistics>,
+
+    /// The statistics of each memory heap, indexed by the memory heap index.
+    pub memory_heaps: Vec<MemoryStatistics>,
+
+    /// The statistics of all memory types combined.
+    pub total: MemoryStatistics,
+}
+
+/// The layout of a single [`DeviceMemory`] block of a [`GenericMemoryAllocator`], returned by
+/// [`GenericMemoryAllocator::memory_map`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryBlockMap {
+    /// The index of the memory type that the block was allocated from.
+    pub memory_type_index: u32,
+
+    /// The index of the memory heap that the memory type corresponds to.
+    pub memory_heap_index: u32,
+
+    /// The handle of the block.
+    pub device_memory: ash::vk::DeviceMemory,
+
+    /// The size of the block in bytes.
+    pub size: DeviceSize,
+
+    /// Whether the block is a dedicated allocation.
+    pub dedicated: bool,
+
+    /// The suballocations within the block, including the free ones.
+    pub suballocations: Vec<SuballocationNode>,
+
+    /// The fragmentation metrics of the block.
+    pub fragmentation_metrics: FragmentationMetrics,
+}
+
+/// A list of the outstanding allocations of a [`GenericMemoryAllocator`], returned by
+/// [`GenericMemoryAllocator::report`].
+///
+/// The `Display` implementation prints one allocation per line.
+#[derive(Clone, Debug)]
+pub struct AllocationReport {
+    /// The outstanding allocations.
+    pub allocations: Vec<AllocationReportEntry>,
+}
+
+impl AllocationReport {
+    /// Returns `true` if there are no outstanding allocations.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.allocations.is_empty()
+    }
+}
+
+impl Display for AllocationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        writeln!(f, "{} outstanding allocation(s)", self.allocations.len())?;
+
+        for entry in &self.allocations {
+            writeln!(f, "{}", entry)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A single outstanding allocation within an [`AllocationReport`].
+#[derive(Clone, Debug)]
+pub struct AllocationReportEntry {
+    /// The index of the memory type that the allocation was made from.
+    pub memory_type_index: u32,
+
+    /// The handle of the [`DeviceMemory`] block that the allocation is part of.
+    pub device_memory: ash::vk::DeviceMemory,
+
+    /// The offset of the allocation within the block.
+    pub offset: DeviceSize,
+
+    /// The size of the allocation.
+    pub size: DeviceSize,
+
+    /// The type of resources that can be bound to the allocation.
+    pub allocation_type: SuballocationType,
+
+    /// Whether the allocation is a dedicated allocation, in which case it spans the whole block.
+    pub dedicated: bool,
+
+    /// The tag that was given in [`AllocationCreateInfo::tag`] or set with
+    /// [`GenericMemoryAllocator::set_allocation_tag`], if any.
+    pub tag: Option<Arc<str>>,
+
+    /// The origin that was given in [`AllocationCreateInfo::origin`], if any.
+    pub origin: Option<&'static Location<'static>>,
+}
+
+impl Display for AllocationReportEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        write!(
+            f,
+            "memory type {}, {:?}, offset {}, size {}, {:?}",
+            self.memory_type_index,
+            self.device_memory,
+            self.offset,
+            self.size,
+            self.allocation_type,
+        )?;
+
+        if self.dedicated {
+            write!(f, ", dedicated")?;
+        }
+
+        if let Some(tag) = &self.tag {
+            write!(f, ", tag `{}`", tag)?;
+        }
+
+        if let Some(origin) = self.origin {
+            write!(f, ", allocated at {}", origin)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Uses `tag` as the debug utils object name of `object`, if the `ext_debug_utils` extension is
+/// enabled.
+pub(crate) fn set_object_name_from_tag<T: VulkanObject + DeviceOwned>(object: &T, tag: &str) {
+    let device = object.device();
+
+    if device.instance().enabled_extensions().ext_debug_utils && !tag.contains('\0') {
+        // The name is only a debugging aid, so failing to set it is not an error.
+        let _ = device.set_debug_utils_object_name(object, Some(tag));
+    }
+}
+
+/// Finds the most suitable memory type index in `memory_type_bits` using the given `filter`, out of
+/// all the memory types in `memory_properties`.
+///
+/// This is the same algorithm that [`GenericMemoryAllocator`] uses.
+pub(crate) fn find_memory_type_index(
+    memory_properties: &MemoryProperties,
+    memory_type_bits: u32,
+    filter: MemoryTypeFilter,
+) -> Option<u32> {
+    let required_flags = filter.required_flags.into();
+    let preferred_flags = filter.preferred_flags.into();
+    let not_preferred_flags = filter.not_preferred_flags.into();
+
+    memory_properties
+        .memory_types
+        .iter()
+        .map(|memory_type| ash::vk::MemoryPropertyFlags::from(memory_type.property_flags))
+        .enumerate()
+        // Filter out memory types which are supported by the memory type bits and have the
+        // required flags set.
+        .filter(|&(index, flags)| {
+            memory_type_bits & (1 << index) != 0 && flags & required_flags == required_flags
+        })
+        // Rank memory types with more of the preferred flags higher, and ones with more of the not
+        // preferred flags lower.
+        .min_by_key(|&(_, flags)| {
+            (!flags & preferred_flags).as_raw().count_ones()
+                + (flags & not_preferred_flags).as_raw().count_ones()
+        })
+        .map(|(index, _)| index as u32)
+}
+
 /// Parameters to create a new [`GenericMemoryAllocator`].
 #[derive(Clone, Debug)]
 pub struct GenericMemoryAllocatorCreateInfo<'a> {
@@ -1766,7 +2321,7 @@ pub struct GenericMemoryAllocatorCreateInfo<'a> {
     /// The default value is [`u32::MAX`].
     ///
     /// [`allocate`]: struct.GenericMemoryAllocator.html#method.allocate
-    /// [`PROTECTED`]: MemoryPropertyFlags::DEVICE_COHERENT
+    /// [`PROTECTED`]: MemoryPropertyFlags::PROTECTED
     pub memory_type_bits: u32,
 
     /// Whether the allocator should use the dedicated allocation APIs.
@@ -1887,3 +2442,103 @@ mod array_vec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AllocationCreateInfo, AllocationType, BuddyAllocator, DeviceLayout, GenericMemoryAllocator,
+        GenericMemoryAllocatorCreateInfo, MemoryAllocator, MemoryStatistics,
+        StandardMemoryAllocator,
+    };
+    use crate::buffer::{Buffer, BufferCreateInfo, BufferMemory, BufferUsage};
+    use std::sync::Arc;
+
+    #[test]
+    fn statistics_and_report() {
+        let (device, _queue) = gfx_dev_and_queue!();
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device));
+
+        assert_eq!(
+            memory_allocator.statistics().total,
+            MemoryStatistics::default()
+        );
+        assert!(memory_allocator.report().is_empty());
+
+        let buffer = Buffer::new_slice::<u32>(
+            memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::STORAGE_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+            1024,
+        )
+        .unwrap();
+        let BufferMemory::Normal(memory) = buffer.buffer().memory() else {
+            unreachable!()
+        };
+
+        let statistics = memory_allocator.statistics();
+        assert_eq!(statistics.total.allocation_count, 1);
+        assert!(statistics.total.used_size >= 4096);
+        assert!(statistics.total.reserved_size >= statistics.total.used_size);
+
+        let memory_type_index = memory.device_memory().memory_type_index() as usize;
+        assert_eq!(statistics.memory_types[memory_type_index], statistics.total);
+
+        memory_allocator.set_allocation_tag(memory, Some("storage".into()));
+
+        let report = memory_allocator.report();
+        assert_eq!(report.allocations.len(), 1);
+        assert_eq!(report.allocations[0].tag.as_deref(), Some("storage"));
+        assert!(report.to_string().contains("tag `storage`"));
+
+        // The allocation and its tag are gone once the buffer is dropped.
+        drop(buffer);
+
+        assert_eq!(memory_allocator.statistics().total.allocation_count, 0);
+        assert!(memory_allocator.report().is_empty());
+    }
+
+    #[test]
+    fn statistics_buddy() {
+        let (device, _queue) = gfx_dev_and_queue!();
+        let block_sizes = vec![
+            1 << 20;
+            device
+                .physical_device()
+                .memory_properties()
+                .memory_types
+                .len()
+        ];
+        let memory_allocator = GenericMemoryAllocator::<BuddyAllocator>::new(
+            device,
+            GenericMemoryAllocatorCreateInfo {
+                block_sizes: &block_sizes,
+                ..Default::default()
+            },
+        );
+
+        let allocation = memory_allocator
+            .allocate_from_type(
+                0,
+                DeviceLayout::from_size_alignment(1024, 1).unwrap(),
+                AllocationType::Linear,
+                false,
+            )
+            .unwrap();
+
+        // The buddy allocator only keeps track of free nodes, which is still enough to report the
+        // allocated space.
+        let statistics = memory_allocator.statistics();
+        assert_eq!(statistics.total.allocation_count, 1);
+        assert!(statistics.total.used_size >= 1024);
+        assert!(statistics.total.largest_free_size > 0);
+        assert_eq!(memory_allocator.report().allocations.len(), 1);
+
+        unsafe { memory_allocator.deallocate(allocation) };
+
+        assert_eq!(memory_allocator.statistics().total.allocation_count, 0);
+        assert!(memory_allocator.report().is_empty());
+    }
+}
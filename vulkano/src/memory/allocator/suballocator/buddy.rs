@@ -1,5 +1,6 @@
 use super::{
-    AllocationType, Region, Suballocation, SuballocationNode, Suballocator, SuballocatorError,
+    AllocationType, FragmentationMetrics, Region, Suballocation, SuballocationNode,
+    SuballocationType, Suballocator, SuballocatorError,
 };
 use crate::{
     memory::{
@@ -66,6 +67,7 @@ pub struct BuddyAllocator {
     // Each free-list is sorted by offset because we want to find the first-fit as this strategy
     // minimizes external fragmentation.
     free_list: ArrayVec<Vec<DeviceSize>, { Self::MAX_ORDERS }>,
+    allocation_count: usize,
 }
 
 impl BuddyAllocator {
@@ -74,10 +76,14 @@ impl BuddyAllocator {
     /// Arbitrary maximum number of orders, used to avoid a 2D `Vec`. Together with a minimum node
     /// size of 16, this is enough for a 32GiB region.
     const MAX_ORDERS: usize = 32;
+
+    fn region_size(&self) -> DeviceSize {
+        BuddyAllocator::MIN_NODE_SIZE << (self.free_list.len() - 1)
+    }
 }
 
 unsafe impl Suballocator for BuddyAllocator {
-    type Suballocations<'a> = std::iter::Empty<SuballocationNode>;
+    type Suballocations<'a> = std::vec::IntoIter<SuballocationNode>;
 
     /// Creates a new `BuddyAllocator` for the given [region].
     ///
@@ -106,6 +112,7 @@ unsafe impl Suballocator for BuddyAllocator {
             region_offset: region.offset(),
             free_size: region.size(),
             free_list,
+            allocation_count: 0,
         }
     }
 
@@ -186,6 +193,7 @@ unsafe impl Suballocator for BuddyAllocator {
                     // This can't overflow because suballocation sizes in the free-list are
                     // constrained by the remaining size of the region.
                     self.free_size -= size;
+                    self.allocation_count += 1;
 
                     return Ok(Suballocation {
                         offset,
@@ -241,6 +249,7 @@ unsafe impl Suballocator for BuddyAllocator {
                     // The sizes of suballocations allocated by `self` are constrained by that of
                     // its region, so they can't possibly overflow when added up.
                     self.free_size += size;
+                    self.allocation_count -= 1;
 
                     break;
                 }
@@ -261,8 +270,76 @@ unsafe impl Suballocator for BuddyAllocator {
     #[inline]
     fn cleanup(&mut self) {}
 
-    #[inline]
+    /// Returns an iterator over the current suballocations.
+    ///
+    /// The `BuddyAllocator` only keeps track of free nodes, so the space between two free nodes is
+    /// reported as a single suballocation of type [`SuballocationType::Unknown`], even if it
+    /// consists of multiple allocations.
     fn suballocations(&self) -> Self::Suballocations<'_> {
-        todo!()
+        let mut free_nodes = self
+            .free_list
+            .iter()
+            .enumerate()
+            .flat_map(|(order, free_list)| {
+                let size = BuddyAllocator::MIN_NODE_SIZE << order;
+
+                free_list.iter().map(move |&offset| (offset, size))
+            })
+            .collect::<Vec<_>>();
+        free_nodes.sort_unstable_by_key(|&(offset, _)| offset);
+
+        let mut nodes = Vec::with_capacity(2 * free_nodes.len() + 1);
+        let mut allocated_start = self.region_offset;
+
+        for (offset, size) in free_nodes {
+            if offset > allocated_start {
+                nodes.push(SuballocationNode {
+                    offset: allocated_start,
+                    size: offset - allocated_start,
+                    allocation_type: SuballocationType::Unknown,
+                });
+            }
+
+            nodes.push(SuballocationNode {
+                offset,
+                size,
+                allocation_type: SuballocationType::Free,
+            });
+            allocated_start = offset + size;
+        }
+
+        let region_end = self.region_offset + self.region_size();
+
+        if region_end > allocated_start {
+            nodes.push(SuballocationNode {
+                offset: allocated_start,
+                size: region_end - allocated_start,
+                allocation_type: SuballocationType::Unknown,
+            });
+        }
+
+        nodes.into_iter()
+    }
+
+    #[inline]
+    fn fragmentation_metrics(&self) -> FragmentationMetrics {
+        let (free_region_count, largest_free_size) = self
+            .free_list
+            .iter()
+            .enumerate()
+            .filter(|(_, free_list)| !free_list.is_empty())
+            .fold((0, 0), |(count, _), (order, free_list)| {
+                (
+                    count + free_list.len(),
+                    BuddyAllocator::MIN_NODE_SIZE << order,
+                )
+            });
+
+        FragmentationMetrics {
+            free_size: self.free_size,
+            free_region_count,
+            largest_free_size,
+            allocation_count: self.allocation_count,
+        }
     }
 }
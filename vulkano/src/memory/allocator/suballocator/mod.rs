@@ -151,6 +151,32 @@ pub unsafe trait Suballocator {
     fn suballocations(&self) -> Self::Suballocations<'_>
     where
         Self: Sized;
+
+    /// Returns metrics describing how fragmented the [region] currently is.
+    ///
+    /// The default implementation derives the metrics from [`suballocations`]. Suballocators
+    /// that report multiple adjacent allocations as a single suballocation should override it to
+    /// give an accurate allocation count.
+    ///
+    /// [region]: Self#regions
+    /// [`suballocations`]: Self::suballocations
+    fn fragmentation_metrics(&self) -> FragmentationMetrics
+    where
+        Self: Sized,
+    {
+        self.suballocations()
+            .fold(FragmentationMetrics::default(), |mut metrics, node| {
+                if node.allocation_type == SuballocationType::Free {
+                    metrics.free_size += node.size;
+                    metrics.free_region_count += 1;
+                    metrics.largest_free_size = metrics.largest_free_size.max(node.size);
+                } else {
+                    metrics.allocation_count += 1;
+                }
+
+                metrics
+            })
+    }
 }
 
 impl Debug for dyn Suballocator {
@@ -331,6 +357,43 @@ pub struct SuballocationNode {
     pub allocation_type: SuballocationType,
 }
 
+/// Metrics describing the fragmentation of a [suballocator]'s region, returned by
+/// [`Suballocator::fragmentation_metrics`].
+///
+/// [suballocator]: Suballocator
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FragmentationMetrics {
+    /// The total size of the free regions.
+    pub free_size: DeviceSize,
+
+    /// The number of free regions.
+    pub free_region_count: usize,
+
+    /// The size of the largest free region. This is the largest allocation that can succeed,
+    /// disregarding alignment.
+    pub largest_free_size: DeviceSize,
+
+    /// The number of suballocations that are currently allocated.
+    pub allocation_count: usize,
+}
+
+impl FragmentationMetrics {
+    /// Returns the fraction of the free space that is *not* part of the largest free region, in
+    /// the range `0.0..=1.0`.
+    ///
+    /// A value of `0.0` means that all of the free space is contiguous, and values approaching
+    /// `1.0` mean that the free space is split up into many small regions. If there is no free
+    /// space, `0.0` is returned.
+    #[inline]
+    pub fn fragmentation(&self) -> f32 {
+        if self.free_size == 0 {
+            0.0
+        } else {
+            1.0 - self.largest_free_size as f32 / self.free_size as f32
+        }
+    }
+}
+
 /// Tells us if an allocation within a [suballocator]'s list/tree of suballocations is free, and if
 /// not, what type of resources can be bound to it. The suballocator needs to keep track of this in
 /// order to be able to respect the buffer-image granularity.
@@ -456,6 +519,99 @@ mod tests {
         unsafe { allocator.deallocate(alloc) };
     }
 
+    #[test]
+    fn free_list_allocator_fragmentation_metrics() {
+        const REGION_SIZE: DeviceSize = 4 * 256;
+        const LAYOUT: DeviceLayout = unwrap(DeviceLayout::from_size_alignment(256, 1));
+
+        let mut allocator = FreeListAllocator::new(Region::new(0, REGION_SIZE).unwrap());
+        let metrics = allocator.fragmentation_metrics();
+        assert_eq!(metrics.free_size, REGION_SIZE);
+        assert_eq!(metrics.free_region_count, 1);
+        assert_eq!(metrics.largest_free_size, REGION_SIZE);
+        assert_eq!(metrics.allocation_count, 0);
+        assert_eq!(metrics.fragmentation(), 0.0);
+
+        let allocs = (0..4)
+            .map(|_| {
+                allocator
+                    .allocate(LAYOUT, AllocationType::Unknown, DeviceAlignment::MIN)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(allocator.fragmentation_metrics().allocation_count, 4);
+
+        // Free every other allocation, leaving two disjoint free regions.
+        let mut allocs = allocs.into_iter();
+        let (a, b, c, d) = (
+            allocs.next().unwrap(),
+            allocs.next().unwrap(),
+            allocs.next().unwrap(),
+            allocs.next().unwrap(),
+        );
+        unsafe {
+            allocator.deallocate(a);
+            allocator.deallocate(c);
+        }
+
+        let metrics = allocator.fragmentation_metrics();
+        assert_eq!(metrics.free_size, 2 * 256);
+        assert_eq!(metrics.free_region_count, 2);
+        assert_eq!(metrics.largest_free_size, 256);
+        assert_eq!(metrics.allocation_count, 2);
+        assert_eq!(metrics.fragmentation(), 0.5);
+
+        unsafe {
+            allocator.deallocate(b);
+            allocator.deallocate(d);
+        }
+
+        assert_eq!(allocator.fragmentation_metrics().free_region_count, 1);
+    }
+
+    #[test]
+    fn buddy_allocator_suballocations() {
+        const REGION_SIZE: DeviceSize = 4 * BuddyAllocator::MIN_NODE_SIZE;
+
+        let mut allocator = BuddyAllocator::new(Region::new(0, REGION_SIZE).unwrap());
+        let alloc = allocator
+            .allocate(DUMMY_LAYOUT, AllocationType::Unknown, DeviceAlignment::MIN)
+            .unwrap();
+
+        let nodes = allocator.suballocations().collect::<Vec<_>>();
+        assert_eq!(
+            nodes,
+            [
+                SuballocationNode {
+                    offset: 0,
+                    size: BuddyAllocator::MIN_NODE_SIZE,
+                    allocation_type: SuballocationType::Unknown,
+                },
+                SuballocationNode {
+                    offset: BuddyAllocator::MIN_NODE_SIZE,
+                    size: BuddyAllocator::MIN_NODE_SIZE,
+                    allocation_type: SuballocationType::Free,
+                },
+                SuballocationNode {
+                    offset: 2 * BuddyAllocator::MIN_NODE_SIZE,
+                    size: 2 * BuddyAllocator::MIN_NODE_SIZE,
+                    allocation_type: SuballocationType::Free,
+                },
+            ],
+        );
+
+        let metrics = allocator.fragmentation_metrics();
+        assert_eq!(metrics.free_size, 3 * BuddyAllocator::MIN_NODE_SIZE);
+        assert_eq!(metrics.free_region_count, 2);
+        assert_eq!(metrics.largest_free_size, 2 * BuddyAllocator::MIN_NODE_SIZE);
+        assert_eq!(metrics.allocation_count, 1);
+
+        unsafe { allocator.deallocate(alloc) };
+
+        assert_eq!(allocator.fragmentation_metrics().allocation_count, 0);
+        assert_eq!(allocator.suballocations().len(), 1);
+    }
+
     #[test]
     fn free_list_allocator_respects_alignment() {
         const REGION_SIZE: DeviceSize = 10 * 256;
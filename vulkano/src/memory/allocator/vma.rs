@@ -0,0 +1,611 @@
+//! A [memory allocator] backed by the [Vulkan Memory Allocator] library.
+//!
+//! This is meant for applications that already rely on VMA, for example when migrating an
+//! existing engine to vulkano: [`VmaMemoryAllocator`] implements the [`MemoryAllocator`] trait,
+//! so it can be used everywhere that vulkano takes a memory allocator, while the allocation
+//! strategy, budgeting and statistics remain those of VMA. The underlying [`vk_mem::Allocator`]
+//! can be accessed with [`VmaMemoryAllocator::vma_allocator`].
+//!
+//! This module is only available when the `vma` feature is enabled.
+//!
+//! # Limitations
+//!
+//! VMA owns the [`DeviceMemory`] blocks that it allocates. Each block is therefore represented by
+//! a *borrowed* `DeviceMemory` object (see [`DeviceMemory::from_handle_borrowed`]), which is shared
+//! by all allocations made from the block and is only released once the last of them has been
+//! deallocated. You must not keep the `DeviceMemory` of an allocation alive after all resources
+//! that are bound to the block have been dropped, as VMA may free the block at that point.
+//!
+//! Exporting memory is not supported, so [`MemoryAllocator::allocate_dedicated`] returns an error
+//! if `export_handle_types` is not empty.
+//!
+//! [memory allocator]: MemoryAllocator
+//! [Vulkan Memory Allocator]: https://github.com/GPUOpen-LibrariesAndSDKs/VulkanMemoryAllocator
+
+use super::{
+    align_up, find_memory_type_index, AllocationCreateInfo, AllocationHandle, AllocationType,
+    DeviceLayout, MemoryAlloc, MemoryAllocatePreference, MemoryAllocator, MemoryAllocatorError,
+    MemoryTypeFilter, Suballocation,
+};
+use crate::{
+    device::{Device, DeviceOwned},
+    instance::InstanceOwnedDebugWrapper,
+    memory::{
+        DedicatedAllocation, DeviceMemory, ExternalMemoryHandleTypes, MemoryAllocateFlags,
+        MemoryAllocateInfo, MemoryPropertyFlags, MemoryRequirements,
+    },
+    DeviceSize, Validated, ValidationError, Version, VulkanError, VulkanObject,
+};
+use ahash::HashMap;
+use parking_lot::Mutex;
+use std::{
+    collections::hash_map::Entry,
+    ffi::c_void,
+    fmt::{Debug, Error as FmtError, Formatter},
+    ptr::NonNull,
+    sync::Arc,
+};
+
+/// A [`MemoryAllocator`] that delegates to the [Vulkan Memory Allocator] library through the
+/// [`vk_mem`] crate.
+///
+/// See [the module-level documentation] for more details.
+///
+/// [Vulkan Memory Allocator]: https://github.com/GPUOpen-LibrariesAndSDKs/VulkanMemoryAllocator
+/// [the module-level documentation]: self
+pub struct VmaMemoryAllocator {
+    // This must be dropped before `device`.
+    allocator: vk_mem::Allocator,
+    device: InstanceOwnedDebugWrapper<Arc<Device>>,
+    memory_type_bits: u32,
+    flags: MemoryAllocateFlags,
+    dedicated_allocation: bool,
+    // The `DeviceMemory` blocks that VMA currently has allocations in.
+    blocks: Mutex<HashMap<ash::vk::DeviceMemory, VmaBlock>>,
+}
+
+struct VmaBlock {
+    device_memory: Arc<DeviceMemory>,
+    // The number of allocations that we handed out from this block and haven't been deallocated.
+    allocation_count: usize,
+}
+
+impl VmaMemoryAllocator {
+    /// Creates a new `VmaMemoryAllocator`.
+    pub fn new(
+        device: Arc<Device>,
+        create_info: VmaMemoryAllocatorCreateInfo,
+    ) -> Result<Self, VulkanError> {
+        let VmaMemoryAllocatorCreateInfo {
+            preferred_large_heap_block_size,
+            memory_type_bits,
+            _ne: _,
+        } = create_info;
+
+        let instance = device.instance();
+        let library = instance.library();
+
+        // SAFETY: `vkGetInstanceProcAddr` can always be queried through itself using a valid
+        // instance, and the library outlives the allocator because the device keeps it alive.
+        let instance_ash = unsafe {
+            let static_fn = ash::StaticFn::load(|name| {
+                library
+                    .get_instance_proc_addr(instance.handle(), name.as_ptr())
+                    .map_or(std::ptr::null(), |func| func as *const c_void)
+            });
+
+            ash::Instance::load(&static_fn, instance.handle())
+        };
+        // SAFETY: The device was created from the instance.
+        let device_ash = unsafe { ash::Device::load(&instance.fns().v1_0, device.handle()) };
+
+        let mut flags_vma = vk_mem::AllocatorCreateFlags::empty();
+        let mut flags = MemoryAllocateFlags::empty();
+
+        if device.api_version() < Version::V1_1
+            && device.enabled_extensions().khr_dedicated_allocation
+        {
+            flags_vma |= vk_mem::AllocatorCreateFlags::KHR_DEDICATED_ALLOCATION;
+        }
+
+        // VUID-VkMemoryAllocateInfo-flags-03331
+        if device.enabled_features().buffer_device_address
+            && !device.enabled_extensions().ext_buffer_device_address
+            && (device.api_version() >= Version::V1_1
+                || device.enabled_extensions().khr_device_group)
+        {
+            flags_vma |= vk_mem::AllocatorCreateFlags::BUFFER_DEVICE_ADDRESS;
+            flags |= MemoryAllocateFlags::DEVICE_ADDRESS;
+        }
+
+        let mut create_info_vma = vk_mem::AllocatorCreateInfo::new(
+            &instance_ash,
+            &device_ash,
+            device.physical_device().handle(),
+        );
+        create_info_vma.flags = flags_vma;
+        create_info_vma.preferred_large_heap_block_size = preferred_large_heap_block_size;
+        create_info_vma.vulkan_api_version = device.api_version().try_into().unwrap();
+
+        let allocator =
+            unsafe { vk_mem::Allocator::new(create_info_vma) }.map_err(VulkanError::from)?;
+
+        let dedicated_allocation = device.api_version() >= Version::V1_1
+            || device.enabled_extensions().khr_dedicated_allocation;
+
+        Ok(VmaMemoryAllocator {
+            allocator,
+            device: InstanceOwnedDebugWrapper(device),
+            memory_type_bits,
+            flags,
+            dedicated_allocation,
+            blocks: Mutex::default(),
+        })
+    }
+
+    /// Returns the underlying VMA allocator.
+    ///
+    /// This can be used to query VMA's statistics and budgets. You must not free allocations made
+    /// through vulkano using the returned allocator.
+    #[inline]
+    pub fn vma_allocator(&self) -> &vk_mem::Allocator {
+        &self.allocator
+    }
+
+    fn allocate_vma(
+        &self,
+        memory_type_index: u32,
+        layout: DeviceLayout,
+        allocation_type: AllocationType,
+        flags_vma: vk_mem::AllocationCreateFlags,
+        dedicated_allocation: Option<DedicatedAllocation<'_>>,
+    ) -> Result<MemoryAlloc, MemoryAllocatorError> {
+        let memory_type = &self
+            .device
+            .physical_device()
+            .memory_properties()
+            .memory_types[memory_type_index as usize];
+        let is_host_visible = memory_type
+            .property_flags
+            .intersects(MemoryPropertyFlags::HOST_VISIBLE);
+        let is_coherent = memory_type
+            .property_flags
+            .intersects(MemoryPropertyFlags::HOST_COHERENT);
+
+        // Pad the size to the non-coherent atom size, so that flushing and invalidating whole
+        // allocations is valid.
+        let mut size = layout.size();
+
+        if is_host_visible && !is_coherent {
+            let atom_size = self
+                .device
+                .physical_device()
+                .properties()
+                .non_coherent_atom_size;
+            size = align_up(size, atom_size);
+        }
+
+        let create_info_vma = vk_mem::AllocationCreateInfo {
+            flags: if is_host_visible {
+                flags_vma | vk_mem::AllocationCreateFlags::MAPPED
+            } else {
+                flags_vma
+            },
+            usage: vk_mem::MemoryUsage::Unknown,
+            memory_type_bits: 1 << memory_type_index,
+            ..Default::default()
+        };
+        let is_dedicated = flags_vma.contains(vk_mem::AllocationCreateFlags::DEDICATED_MEMORY);
+        let dedicated_allocation = dedicated_allocation.filter(|_| is_dedicated);
+
+        let allocation_vma = unsafe {
+            match dedicated_allocation {
+                Some(DedicatedAllocation::Buffer(buffer)) => self
+                    .allocator
+                    .allocate_memory_for_buffer(buffer.handle(), &create_info_vma),
+                Some(DedicatedAllocation::Image(image)) => self
+                    .allocator
+                    .allocate_memory_for_image(image.handle(), &create_info_vma),
+                None => self.allocator.allocate_memory(
+                    &ash::vk::MemoryRequirements {
+                        size,
+                        alignment: layout.alignment().as_devicesize(),
+                        memory_type_bits: 1 << memory_type_index,
+                    },
+                    &create_info_vma,
+                ),
+            }
+        }
+        .map_err(|err| {
+            if flags_vma.contains(vk_mem::AllocationCreateFlags::NEVER_ALLOCATE) {
+                MemoryAllocatorError::OutOfPoolMemory
+            } else {
+                MemoryAllocatorError::AllocateDeviceMemory(Validated::Error(err.into()))
+            }
+        })?;
+        let info = self.allocator.get_allocation_info2(&allocation_vma);
+        let device_memory = self.acquire_block(
+            &info,
+            dedicated_allocation.filter(|_| info.dedicated_memory),
+        );
+        let info = info.allocation_info;
+
+        let handle =
+            AllocationHandle::from_ptr(Box::into_raw(Box::new(allocation_vma)).cast::<()>());
+
+        Ok(MemoryAlloc {
+            device_memory,
+            suballocation: Some(Suballocation {
+                offset: info.offset,
+                size: layout.size(),
+                allocation_type,
+                handle,
+            }),
+            allocation_handle: handle,
+        })
+    }
+
+    /// Returns the `DeviceMemory` of the block that the allocation was made from, creating it if
+    /// this is the first allocation in the block, and counts the allocation towards the block.
+    fn acquire_block(
+        &self,
+        info: &vk_mem::AllocationInfo2,
+        dedicated_allocation: Option<DedicatedAllocation<'_>>,
+    ) -> Arc<DeviceMemory> {
+        let allocation_info = &info.allocation_info;
+        let mut blocks = self.blocks.lock();
+
+        let block = blocks
+            .entry(allocation_info.device_memory)
+            .or_insert_with(|| {
+                // SAFETY: VMA keeps the block allocated as long as it contains allocations. We
+                // only drop our reference to the block once its last allocation is deallocated.
+                let mut device_memory = unsafe {
+                    DeviceMemory::from_handle_borrowed(
+                        self.device.clone(),
+                        allocation_info.device_memory,
+                        MemoryAllocateInfo {
+                            allocation_size: info.block_size,
+                            memory_type_index: allocation_info.memory_type,
+                            dedicated_allocation,
+                            export_handle_types: ExternalMemoryHandleTypes::empty(),
+                            flags: self.flags,
+                            ..Default::default()
+                        },
+                    )
+                };
+
+                if let Some(ptr) = NonNull::new(allocation_info.mapped_data) {
+                    // SAFETY: VMA maps whole blocks and keeps persistently-mapped blocks mapped
+                    // for as long as they contain allocations, so the pointer to the start of the
+                    // block is the pointer to the allocation minus its offset.
+                    unsafe {
+                        let ptr = ptr
+                            .as_ptr()
+                            .cast::<u8>()
+                            .sub(allocation_info.offset as usize)
+                            .cast::<c_void>();
+                        device_memory
+                            .set_borrowed_mapping(NonNull::new_unchecked(ptr), 0..info.block_size);
+                    }
+                }
+
+                VmaBlock {
+                    device_memory: Arc::new(device_memory),
+                    allocation_count: 0,
+                }
+            });
+        block.allocation_count += 1;
+
+        block.device_memory.clone()
+    }
+}
+
+unsafe impl MemoryAllocator for VmaMemoryAllocator {
+    fn find_memory_type_index(
+        &self,
+        memory_type_bits: u32,
+        filter: MemoryTypeFilter,
+    ) -> Option<u32> {
+        find_memory_type_index(
+            self.device.physical_device().memory_properties(),
+            memory_type_bits,
+            filter,
+        )
+    }
+
+    fn allocate_from_type(
+        &self,
+        memory_type_index: u32,
+        layout: DeviceLayout,
+        allocation_type: AllocationType,
+        never_allocate: bool,
+    ) -> Result<MemoryAlloc, MemoryAllocatorError> {
+        let flags_vma = if never_allocate {
+            vk_mem::AllocationCreateFlags::NEVER_ALLOCATE
+        } else {
+            vk_mem::AllocationCreateFlags::empty()
+        };
+
+        self.allocate_vma(memory_type_index, layout, allocation_type, flags_vma, None)
+    }
+
+    fn allocate(
+        &self,
+        requirements: MemoryRequirements,
+        allocation_type: AllocationType,
+        create_info: AllocationCreateInfo,
+        mut dedicated_allocation: Option<DedicatedAllocation<'_>>,
+    ) -> Result<MemoryAlloc, MemoryAllocatorError> {
+        let MemoryRequirements {
+            layout,
+            mut memory_type_bits,
+            prefers_dedicated_allocation,
+            requires_dedicated_allocation,
+        } = requirements;
+
+        memory_type_bits &= self.memory_type_bits;
+        memory_type_bits &= create_info.memory_type_bits;
+
+        let AllocationCreateInfo {
+            memory_type_filter,
+            memory_type_bits: _,
+            allocate_preference,
+            tag: _,
+            origin: _,
+            _ne: _,
+        } = create_info;
+
+        if !self.dedicated_allocation {
+            dedicated_allocation = None;
+        }
+
+        let flags_vma = match allocate_preference {
+            MemoryAllocatePreference::Unknown => {
+                // VUID-vkBindBufferMemory-buffer-01444
+                // VUID-vkBindImageMemory-image-01445
+                if requires_dedicated_allocation || prefers_dedicated_allocation {
+                    vk_mem::AllocationCreateFlags::DEDICATED_MEMORY
+                } else {
+                    vk_mem::AllocationCreateFlags::empty()
+                }
+            }
+            MemoryAllocatePreference::NeverAllocate => {
+                if requires_dedicated_allocation {
+                    return Err(MemoryAllocatorError::DedicatedAllocationRequired);
+                }
+
+                vk_mem::AllocationCreateFlags::NEVER_ALLOCATE
+            }
+            MemoryAllocatePreference::AlwaysAllocate => {
+                vk_mem::AllocationCreateFlags::DEDICATED_MEMORY
+            }
+        };
+
+        let mut memory_type_index = self
+            .find_memory_type_index(memory_type_bits, memory_type_filter)
+            .ok_or(MemoryAllocatorError::FindMemoryType)?;
+
+        loop {
+            match self.allocate_vma(
+                memory_type_index,
+                layout,
+                allocation_type,
+                flags_vma,
+                dedicated_allocation,
+            ) {
+                Ok(allocation) => return Ok(allocation),
+                // Try a different memory type.
+                Err(err) => {
+                    memory_type_bits &= !(1 << memory_type_index);
+                    memory_type_index = self
+                        .find_memory_type_index(memory_type_bits, memory_type_filter)
+                        .ok_or(err)?;
+                }
+            }
+        }
+    }
+
+    fn allocate_dedicated(
+        &self,
+        memory_type_index: u32,
+        allocation_size: DeviceSize,
+        dedicated_allocation: Option<DedicatedAllocation<'_>>,
+        export_handle_types: ExternalMemoryHandleTypes,
+    ) -> Result<MemoryAlloc, MemoryAllocatorError> {
+        if !export_handle_types.is_empty() {
+            return Err(MemoryAllocatorError::AllocateDeviceMemory(
+                Box::new(ValidationError {
+                    context: "export_handle_types".into(),
+                    problem: "is not empty, but `VmaMemoryAllocator` does not support exporting \
+                        memory"
+                        .into(),
+                    ..Default::default()
+                })
+                .into(),
+            ));
+        }
+
+        let layout = DeviceLayout::from_size_alignment(allocation_size, 1).ok_or_else(|| {
+            MemoryAllocatorError::AllocateDeviceMemory(
+                Box::new(ValidationError {
+                    context: "allocation_size".into(),
+                    problem: "is zero or exceeds `DeviceLayout::MAX_SIZE`".into(),
+                    ..Default::default()
+                })
+                .into(),
+            )
+        })?;
+
+        self.allocate_vma(
+            memory_type_index,
+            layout,
+            AllocationType::Unknown,
+            vk_mem::AllocationCreateFlags::DEDICATED_MEMORY,
+            dedicated_allocation.filter(|_| self.dedicated_allocation),
+        )
+    }
+
+    unsafe fn deallocate(&self, allocation: MemoryAlloc) {
+        // SAFETY: The caller must guarantee that `allocation` refers to one allocated by `self`,
+        // therefore the handle must be the boxed VMA allocation that we gave out on allocation.
+        let mut allocation_vma = Box::from_raw(
+            allocation
+                .allocation_handle
+                .as_ptr()
+                .cast::<vk_mem::Allocation>(),
+        );
+
+        // The lock is held while freeing, so that a new block that VMA allocates with the same
+        // handle can't be mistaken for the one that is being freed.
+        let mut blocks = self.blocks.lock();
+
+        self.allocator.free_memory(&mut allocation_vma);
+
+        if let Entry::Occupied(mut entry) = blocks.entry(allocation.device_memory.handle()) {
+            let block = entry.get_mut();
+            block.allocation_count -= 1;
+
+            if block.allocation_count == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
+unsafe impl DeviceOwned for VmaMemoryAllocator {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl Debug for VmaMemoryAllocator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_struct("VmaMemoryAllocator")
+            .field("device", &self.device)
+            .field("memory_type_bits", &self.memory_type_bits)
+            .field("flags", &self.flags)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parameters to create a new [`VmaMemoryAllocator`].
+#[derive(Clone, Debug)]
+pub struct VmaMemoryAllocatorCreateInfo {
+    /// The preferred size of the [`DeviceMemory`] blocks that VMA allocates from large heaps.
+    ///
+    /// A value of `0` lets VMA choose its own default.
+    ///
+    /// The default value is `0`.
+    pub preferred_large_heap_block_size: DeviceSize,
+
+    /// Lets you restrict the memory types that the allocator may allocate from, in the same way
+    /// as the `memory_type_bits` field of [`GenericMemoryAllocatorCreateInfo`].
+    ///
+    /// The default value is [`u32::MAX`].
+    ///
+    /// [`GenericMemoryAllocatorCreateInfo`]: super::GenericMemoryAllocatorCreateInfo
+    pub memory_type_bits: u32,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for VmaMemoryAllocatorCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        VmaMemoryAllocatorCreateInfo {
+            preferred_large_heap_block_size: 0,
+            memory_type_bits: u32::MAX,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations_share_block() {
+        let (device, _) = gfx_dev_and_queue!();
+        let allocator =
+            VmaMemoryAllocator::new(device.clone(), VmaMemoryAllocatorCreateInfo::default())
+                .unwrap();
+        let layout = DeviceLayout::from_size_alignment(256, 256).unwrap();
+
+        let first = allocator
+            .allocate_from_type(0, layout, AllocationType::Linear, false)
+            .unwrap();
+        let second = allocator
+            .allocate_from_type(0, layout, AllocationType::Linear, false)
+            .unwrap();
+
+        // Both allocations are small enough to be placed in the same block, which must be
+        // represented by a single `DeviceMemory` spanning the whole block.
+        assert!(Arc::ptr_eq(&first.device_memory, &second.device_memory));
+
+        for allocation in [&first, &second] {
+            let suballocation = allocation.suballocation.as_ref().unwrap();
+            assert!(
+                suballocation.offset + suballocation.size
+                    <= allocation.device_memory.allocation_size()
+            );
+        }
+
+        let device_memory = first.device_memory.clone();
+        assert_eq!(
+            allocator.blocks.lock()[&device_memory.handle()].allocation_count,
+            2
+        );
+
+        unsafe { allocator.deallocate(first) };
+
+        // The remaining allocation keeps the block alive.
+        assert_eq!(
+            allocator.blocks.lock()[&device_memory.handle()].allocation_count,
+            1
+        );
+        assert!(Arc::ptr_eq(&device_memory, &second.device_memory));
+
+        unsafe { allocator.deallocate(second) };
+
+        assert!(allocator.blocks.lock().is_empty());
+        assert_eq!(Arc::strong_count(&device_memory), 1);
+    }
+
+    #[test]
+    fn mapped_block() {
+        let (device, _) = gfx_dev_and_queue!();
+        let allocator =
+            VmaMemoryAllocator::new(device.clone(), VmaMemoryAllocatorCreateInfo::default())
+                .unwrap();
+        let layout = DeviceLayout::from_size_alignment(256, 256).unwrap();
+
+        let memory_type_index = allocator
+            .find_memory_type_index(u32::MAX, MemoryTypeFilter::HOST_SEQUENTIAL_WRITE)
+            .unwrap();
+        let allocations: Vec<_> = (0..4)
+            .map(|_| {
+                allocator
+                    .allocate_from_type(memory_type_index, layout, AllocationType::Linear, false)
+                    .unwrap()
+            })
+            .collect();
+
+        for allocation in &allocations {
+            let mapping_state = allocation.device_memory.mapping_state().unwrap();
+            assert_eq!(mapping_state.offset(), 0);
+            assert_eq!(
+                mapping_state.size(),
+                allocation.device_memory.allocation_size()
+            );
+        }
+
+        for allocation in allocations {
+            unsafe { allocator.deallocate(allocation) };
+        }
+
+        assert!(allocator.blocks.lock().is_empty());
+    }
+}
@@ -57,6 +57,7 @@ pub struct DeviceMemory {
     mapping_state: Option<MappingState>,
     atom_size: DeviceAlignment,
     is_coherent: bool,
+    needs_destruction: bool, // `vkFreeMemory` is called only if true.
 }
 
 impl DeviceMemory {
@@ -138,6 +139,17 @@ impl DeviceMemory {
     }
 
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                allocation_size = allocate_info.allocation_size,
+                memory_type_index = allocate_info.memory_type_index,
+            ),
+        )
+    )]
     #[inline(never)]
     pub unsafe fn allocate_unchecked(
         device: Arc<Device>,
@@ -293,7 +305,7 @@ impl DeviceMemory {
             .property_flags
             .intersects(MemoryPropertyFlags::HOST_COHERENT);
 
-        Ok(DeviceMemory {
+        let memory = DeviceMemory {
             handle,
             device: InstanceOwnedDebugWrapper(device),
             id: Self::next_id(),
@@ -308,7 +320,13 @@ impl DeviceMemory {
             mapping_state: None,
             atom_size,
             is_coherent,
-        })
+            needs_destruction: true,
+        };
+        if let Some(resources) = memory.device.resources() {
+            resources.add_device_memory(&memory);
+        }
+
+        Ok(memory)
     }
 
     /// Creates a new `DeviceMemory` from a raw object handle.
@@ -322,6 +340,42 @@ impl DeviceMemory {
         device: Arc<Device>,
         handle: ash::vk::DeviceMemory,
         allocate_info: MemoryAllocateInfo<'_>,
+    ) -> Self {
+        // The allocation is now freed by us, so it must be counted as well.
+        device.allocation_count.fetch_add(1, Ordering::Release);
+
+        Self::from_handle_with_destruction(device, handle, allocate_info, true)
+    }
+
+    /// Creates a new `DeviceMemory` from a raw object handle. Unlike `from_handle`, the created
+    /// `DeviceMemory` does not free the inner memory when dropped.
+    ///
+    /// Borrowed allocations are not included in [`Device::allocation_count`].
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid Vulkan object handle created from `device`.
+    /// - `allocate_info` must match the info used to create the object.
+    /// - Caller must ensure that the handle will not be freed for the lifetime of the returned
+    ///   `DeviceMemory`.
+    /// - If the memory is host-mapped by its owner, it must not be mapped through the returned
+    ///   `DeviceMemory`.
+    ///
+    /// [`Device::allocation_count`]: crate::device::Device::allocation_count
+    #[inline]
+    pub unsafe fn from_handle_borrowed(
+        device: Arc<Device>,
+        handle: ash::vk::DeviceMemory,
+        allocate_info: MemoryAllocateInfo<'_>,
+    ) -> Self {
+        Self::from_handle_with_destruction(device, handle, allocate_info, false)
+    }
+
+    unsafe fn from_handle_with_destruction(
+        device: Arc<Device>,
+        handle: ash::vk::DeviceMemory,
+        allocate_info: MemoryAllocateInfo<'_>,
+        needs_destruction: bool,
     ) -> Self {
         let MemoryAllocateInfo {
             allocation_size,
@@ -339,7 +393,7 @@ impl DeviceMemory {
             .property_flags
             .intersects(MemoryPropertyFlags::HOST_COHERENT);
 
-        DeviceMemory {
+        let memory = DeviceMemory {
             handle,
             device: InstanceOwnedDebugWrapper(device),
             id: Self::next_id(),
@@ -354,7 +408,13 @@ impl DeviceMemory {
             mapping_state: None,
             atom_size,
             is_coherent,
+            needs_destruction,
+        };
+        if let Some(resources) = memory.device.resources() {
+            resources.add_device_memory(&memory);
         }
+
+        memory
     }
 
     /// Returns the index of the memory type that this memory was allocated from.
@@ -409,6 +469,26 @@ impl DeviceMemory {
         self.atom_size
     }
 
+    /// Records that borrowed memory was host-mapped by its owner, so that it can be accessed
+    /// through the returned `DeviceMemory` without mapping it again.
+    ///
+    /// # Safety
+    ///
+    /// - `self` must have been created with [`DeviceMemory::from_handle_borrowed`].
+    /// - `ptr` must point to the mapped memory at offset `range.start`, and `range` must be
+    ///   mapped for the lifetime of `self`.
+    #[cfg(feature = "vma")]
+    pub(crate) unsafe fn set_borrowed_mapping(
+        &mut self,
+        ptr: NonNull<c_void>,
+        range: Range<DeviceSize>,
+    ) {
+        debug_assert!(!self.needs_destruction);
+        debug_assert!(range.end <= self.allocation_size);
+
+        self.mapping_state = Some(MappingState { ptr, range });
+    }
+
     pub(crate) fn is_coherent(&self) -> bool {
         self.is_coherent
     }
@@ -903,6 +983,14 @@ impl DeviceMemory {
 impl Drop for DeviceMemory {
     #[inline]
     fn drop(&mut self) {
+        if let Some(resources) = self.device.resources() {
+            resources.remove_device_memory(self);
+        }
+
+        if !self.needs_destruction {
+            return;
+        }
+
         unsafe {
             let fns = self.device.fns();
             (fns.v1_0.free_memory)(self.device.handle(), self.handle, ptr::null());
@@ -1206,23 +1294,44 @@ pub enum MemoryImportInfo {
 
     /// Import memory from a Windows handle.
     ///
-    /// `handle_type` must be either [`ExternalMemoryHandleType::OpaqueWin32`] or
-    /// [`ExternalMemoryHandleType::OpaqueWin32Kmt`].
+    /// `handle_type` must be one of [`ExternalMemoryHandleType::OpaqueWin32`],
+    /// [`ExternalMemoryHandleType::OpaqueWin32Kmt`], [`ExternalMemoryHandleType::D3D11Texture`]
+    /// or [`ExternalMemoryHandleType::D3D11TextureKmt`].
+    ///
+    /// To import a Direct3D 11 texture as an image, create the image with the handle type in
+    /// [`ImageCreateInfo::external_memory_handle_types`], and import the memory with
+    /// [`MemoryAllocateInfo::dedicated_allocation`] set to that image. Access to the texture can
+    /// then be synchronized with Direct3D through its keyed mutex, using
+    /// [`SubmitInfo::win32_keyed_mutex`].
     ///
     /// # Safety
     ///
     /// - `handle` must be a valid Windows handle.
     /// - Vulkan will not take ownership of `handle`.
-    /// - If `handle_type` is [`ExternalMemoryHandleType::OpaqueWin32`], it owns a reference to the
-    ///   underlying resource and must eventually be closed by the caller.
-    /// - If `handle_type` is [`ExternalMemoryHandleType::OpaqueWin32Kmt`], it does not own a
-    ///   reference to the underlying resource.
-    /// - `handle` must be created by the Vulkan API.
-    /// - [`MemoryAllocateInfo::allocation_size`] and [`MemoryAllocateInfo::memory_type_index`]
-    ///   must match those of the original memory allocation.
-    /// - If the original memory allocation used [`MemoryAllocateInfo::dedicated_allocation`], the
-    ///   imported one must also use it, and the associated buffer or image must be defined
-    ///   identically to the original.
+    /// - If `handle_type` is [`ExternalMemoryHandleType::OpaqueWin32`] or
+    ///   [`ExternalMemoryHandleType::D3D11Texture`], it owns a reference to the underlying
+    ///   resource and must eventually be closed by the caller.
+    /// - If `handle_type` is [`ExternalMemoryHandleType::OpaqueWin32Kmt`] or
+    ///   [`ExternalMemoryHandleType::D3D11TextureKmt`], it does not own a reference to the
+    ///   underlying resource.
+    /// - If `handle_type` is [`ExternalMemoryHandleType::OpaqueWin32`] or
+    ///   [`ExternalMemoryHandleType::OpaqueWin32Kmt`]:
+    ///   - `handle` must be created by the Vulkan API.
+    ///   - [`MemoryAllocateInfo::allocation_size`] and [`MemoryAllocateInfo::memory_type_index`]
+    ///     must match those of the original memory allocation.
+    ///   - If the original memory allocation used [`MemoryAllocateInfo::dedicated_allocation`],
+    ///     the imported one must also use it, and the associated buffer or image must be defined
+    ///     identically to the original.
+    /// - If `handle_type` is [`ExternalMemoryHandleType::D3D11Texture`] or
+    ///   [`ExternalMemoryHandleType::D3D11TextureKmt`]:
+    ///   - `handle` must refer to a Direct3D 11 texture that was created as shareable.
+    ///   - [`MemoryAllocateInfo::memory_type_index`] must be one of the memory types returned by
+    ///     [`Device::memory_win32_handle_properties`], if that is supported, and
+    ///     [`MemoryAllocateInfo::dedicated_allocation`] must be set to an image whose properties
+    ///     match those of the texture.
+    ///
+    /// [`ImageCreateInfo::external_memory_handle_types`]: crate::image::ImageCreateInfo::external_memory_handle_types
+    /// [`SubmitInfo::win32_keyed_mutex`]: crate::command_buffer::SubmitInfo::win32_keyed_mutex
     Win32 {
         handle_type: ExternalMemoryHandleType,
         handle: ash::vk::HANDLE,
@@ -1309,11 +1418,21 @@ impl MemoryImportInfo {
                         // VUID-VkMemoryDedicatedAllocateInfo-image-01878
                         // Can't validate, must be ensured by user
                     }
+                    ExternalMemoryHandleType::D3D11Texture
+                    | ExternalMemoryHandleType::D3D11TextureKmt => {
+                        // VUID-VkMemoryAllocateInfo-allocationSize-01743
+                        // Can't validate, must be ensured by user
+
+                        // VUID-VkMemoryDedicatedAllocateInfo-image-01876
+                        // Can't validate, must be ensured by user
+                    }
                     _ => {
                         return Err(Box::new(ValidationError {
                             context: "handle_type".into(),
-                            problem: "is not `ExternalMemoryHandleType::OpaqueWin32` or \
-                                `ExternalMemoryHandleType::OpaqueWin32Kmt`"
+                            problem: "is not `ExternalMemoryHandleType::OpaqueWin32`, \
+                                `ExternalMemoryHandleType::OpaqueWin32Kmt`, \
+                                `ExternalMemoryHandleType::D3D11Texture` or \
+                                `ExternalMemoryHandleType::D3D11TextureKmt`"
                                 .into(),
                             vuids: &["VUID-VkImportMemoryWin32HandleInfoKHR-handleType-00660"],
                             ..Default::default()
@@ -2344,7 +2463,10 @@ unsafe impl Sync for MappedDeviceMemory {}
 #[cfg(test)]
 mod tests {
     use super::MemoryAllocateInfo;
-    use crate::memory::{DeviceMemory, MemoryMapFlags, MemoryMapInfo, MemoryPropertyFlags};
+    use crate::{
+        memory::{DeviceMemory, MemoryMapFlags, MemoryMapInfo, MemoryPropertyFlags},
+        VulkanObject,
+    };
     use std::{ptr, ptr::NonNull};
 
     #[test]
@@ -2361,6 +2483,41 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn from_handle_borrowed() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory = DeviceMemory::allocate(
+            device.clone(),
+            MemoryAllocateInfo {
+                allocation_size: 256,
+                memory_type_index: 0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(device.allocation_count(), 1);
+
+        let borrowed = unsafe {
+            DeviceMemory::from_handle_borrowed(
+                device.clone(),
+                memory.handle(),
+                MemoryAllocateInfo {
+                    allocation_size: 256,
+                    memory_type_index: 0,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(borrowed.handle(), memory.handle());
+        assert_eq!(device.allocation_count(), 1);
+
+        drop(borrowed);
+        assert_eq!(device.allocation_count(), 1);
+
+        drop(memory);
+        assert_eq!(device.allocation_count(), 0);
+    }
+
     #[test]
     fn zero_size() {
         let (device, _) = gfx_dev_and_queue!();
@@ -0,0 +1,809 @@
+//! An opaque data structure that stores additional per-triangle data for acceleration structures.
+//!
+//! A micromap subdivides each triangle of a bottom-level acceleration structure into a number of
+//! micro-triangles, and stores a value for each of them. Currently the only type of micromap is an
+//! *opacity micromap*, which stores for each micro-triangle whether it is opaque, transparent or
+//! unknown. During ray traversal, hits on opaque and transparent micro-triangles are resolved by
+//! the implementation directly, and only hits on unknown micro-triangles invoke the any-hit
+//! shader. This can greatly reduce the cost of alpha-tested geometry such as foliage.
+//!
+//! # Building a micromap
+//!
+//! Like an acceleration structure, a micromap is in an uninitialized state when it is created,
+//! and must first be *built* on the device using the [`build_micromap`] command buffer command.
+//! The input to the build consists of:
+//! - [`MicromapBuildInfo::data`], containing the packed opacity values of all triangles.
+//! - [`MicromapBuildInfo::triangle_array`], containing one [`MicromapTriangle`] for each
+//!   triangle, which describes where its data starts and how it is subdivided.
+//! - [`MicromapBuildInfo::usage_counts`], which sums up how many triangles use each combination of
+//!   format and subdivision level.
+//!
+//! Use [`Device::micromap_build_sizes`] to find out how large the micromap and scratch buffers
+//! must be.
+//!
+//! # Using a micromap
+//!
+//! A built micromap is attached to the triangle geometry of a bottom-level acceleration structure
+//! by setting [`AccelerationStructureGeometryTrianglesData::opacity_micromap`]. Each triangle of
+//! the geometry then selects a triangle of the micromap through an index buffer, or one of the
+//! special indices in [`OpacityMicromapSpecialIndex`].
+//!
+//! [`build_micromap`]: crate::command_buffer::RecordingCommandBuffer::build_micromap
+//! [`AccelerationStructureGeometryTrianglesData::opacity_micromap`]: crate::acceleration_structure::AccelerationStructureGeometryTrianglesData::opacity_micromap
+
+use crate::{
+    buffer::{BufferUsage, Subbuffer},
+    device::{Device, DeviceOwned},
+    instance::InstanceOwnedDebugWrapper,
+    macros::{impl_id_counter, vulkan_bitflags, vulkan_enum},
+    DeviceSize, Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, VulkanError,
+    VulkanObject,
+};
+use bytemuck::{Pod, Zeroable};
+use std::{
+    mem::{size_of, MaybeUninit},
+    num::NonZeroU64,
+    ptr,
+    sync::Arc,
+};
+
+/// An opaque data structure that stores additional per-triangle data for acceleration structures.
+#[derive(Debug)]
+pub struct Micromap {
+    device: InstanceOwnedDebugWrapper<Arc<Device>>,
+    handle: ash::vk::MicromapEXT,
+    id: NonZeroU64,
+
+    create_flags: MicromapCreateFlags,
+    buffer: Subbuffer<[u8]>,
+    ty: MicromapType,
+}
+
+impl Micromap {
+    /// Creates a new `Micromap`.
+    ///
+    /// The [`micromap`] feature must be enabled on the device.
+    ///
+    /// # Safety
+    ///
+    /// - `create_info.buffer` (and any subbuffer it overlaps with) must not be accessed while it
+    ///   is bound to the micromap.
+    ///
+    /// [`micromap`]: crate::device::DeviceFeatures::micromap
+    #[inline]
+    pub unsafe fn new(
+        device: Arc<Device>,
+        create_info: MicromapCreateInfo,
+    ) -> Result<Arc<Self>, Validated<VulkanError>> {
+        Self::validate_new(&device, &create_info)?;
+
+        Ok(Self::new_unchecked(device, create_info)?)
+    }
+
+    fn validate_new(
+        device: &Device,
+        create_info: &MicromapCreateInfo,
+    ) -> Result<(), Box<ValidationError>> {
+        if !device.enabled_extensions().ext_opacity_micromap {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "ext_opacity_micromap",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        if !device.enabled_features().micromap {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "micromap",
+                )])]),
+                vuids: &["VUID-vkCreateMicromapEXT-micromap-07430"],
+                ..Default::default()
+            }));
+        }
+
+        // VUID-vkCreateMicromapEXT-pCreateInfo-parameter
+        create_info
+            .validate(device)
+            .map_err(|err| err.add_context("create_info"))?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn new_unchecked(
+        device: Arc<Device>,
+        create_info: MicromapCreateInfo,
+    ) -> Result<Arc<Self>, VulkanError> {
+        let &MicromapCreateInfo {
+            create_flags,
+            ref buffer,
+            ty,
+            _ne: _,
+        } = &create_info;
+
+        let create_info_vk = ash::vk::MicromapCreateInfoEXT {
+            create_flags: create_flags.into(),
+            buffer: buffer.buffer().handle(),
+            offset: buffer.offset(),
+            size: buffer.size(),
+            ty: ty.into(),
+            device_address: 0,
+            ..Default::default()
+        };
+
+        let handle = {
+            let fns = device.fns();
+            let mut output = MaybeUninit::uninit();
+            (fns.ext_opacity_micromap.create_micromap_ext)(
+                device.handle(),
+                &create_info_vk,
+                ptr::null(),
+                output.as_mut_ptr(),
+            )
+            .result()
+            .map_err(VulkanError::from)?;
+            output.assume_init()
+        };
+
+        Ok(Self::from_handle(device, handle, create_info))
+    }
+
+    /// Creates a new `Micromap` from a raw object handle.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid Vulkan object handle created from `device`.
+    /// - `create_info` must match the info used to create the object.
+    pub unsafe fn from_handle(
+        device: Arc<Device>,
+        handle: ash::vk::MicromapEXT,
+        create_info: MicromapCreateInfo,
+    ) -> Arc<Self> {
+        let MicromapCreateInfo {
+            create_flags,
+            buffer,
+            ty,
+            _ne: _,
+        } = create_info;
+
+        Arc::new(Self {
+            device: InstanceOwnedDebugWrapper(device),
+            handle,
+            id: Self::next_id(),
+
+            create_flags,
+            buffer,
+            ty,
+        })
+    }
+
+    /// Returns the flags the micromap was created with.
+    #[inline]
+    pub fn create_flags(&self) -> MicromapCreateFlags {
+        self.create_flags
+    }
+
+    /// Returns the subbuffer that the micromap is stored on.
+    #[inline]
+    pub fn buffer(&self) -> &Subbuffer<[u8]> {
+        &self.buffer
+    }
+
+    /// Returns the size of the micromap.
+    #[inline]
+    pub fn size(&self) -> DeviceSize {
+        self.buffer.size()
+    }
+
+    /// Returns the type of the micromap.
+    #[inline]
+    pub fn ty(&self) -> MicromapType {
+        self.ty
+    }
+}
+
+impl Drop for Micromap {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let fns = self.device.fns();
+            (fns.ext_opacity_micromap.destroy_micromap_ext)(
+                self.device.handle(),
+                self.handle,
+                ptr::null(),
+            )
+        }
+    }
+}
+
+unsafe impl VulkanObject for Micromap {
+    type Handle = ash::vk::MicromapEXT;
+
+    #[inline]
+    fn handle(&self) -> Self::Handle {
+        self.handle
+    }
+}
+
+unsafe impl DeviceOwned for Micromap {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl_id_counter!(Micromap);
+
+vulkan_enum! {
+    #[non_exhaustive]
+
+    /// The type of a micromap.
+    MicromapType = MicromapTypeEXT(i32);
+
+    /// The micromap stores the opacity of each micro-triangle.
+    OpacityMicromap = OPACITY_MICROMAP,
+}
+
+/// Parameters to create a new `Micromap`.
+#[derive(Clone, Debug)]
+pub struct MicromapCreateInfo {
+    /// Specifies how to create the micromap.
+    ///
+    /// The default value is empty.
+    pub create_flags: MicromapCreateFlags,
+
+    /// The subbuffer to store the micromap on.
+    ///
+    /// The subbuffer must have an `offset` that is a multiple of 256, and its `usage` must include
+    /// [`BufferUsage::MICROMAP_STORAGE`]. It must not be accessed while it is bound to the
+    /// micromap.
+    ///
+    /// There is no default value.
+    pub buffer: Subbuffer<[u8]>,
+
+    /// The type of micromap to create.
+    ///
+    /// The default value is [`MicromapType::OpacityMicromap`].
+    pub ty: MicromapType,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl MicromapCreateInfo {
+    /// Returns a `MicromapCreateInfo` with the specified `buffer`.
+    #[inline]
+    pub fn new(buffer: Subbuffer<[u8]>) -> Self {
+        Self {
+            create_flags: MicromapCreateFlags::empty(),
+            buffer,
+            ty: MicromapType::OpacityMicromap,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+
+    pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
+        let &Self {
+            create_flags,
+            ref buffer,
+            ty,
+            _ne: _,
+        } = self;
+
+        create_flags.validate_device(device).map_err(|err| {
+            err.add_context("create_flags")
+                .set_vuids(&["VUID-VkMicromapCreateInfoEXT-createFlags-parameter"])
+        })?;
+
+        ty.validate_device(device).map_err(|err| {
+            err.add_context("ty")
+                .set_vuids(&["VUID-VkMicromapCreateInfoEXT-type-parameter"])
+        })?;
+
+        if !buffer
+            .buffer()
+            .usage()
+            .intersects(BufferUsage::MICROMAP_STORAGE)
+        {
+            return Err(Box::new(ValidationError {
+                context: "buffer".into(),
+                problem: "the buffer was not created with the `MICROMAP_STORAGE` usage".into(),
+                ..Default::default()
+            }));
+        }
+
+        if buffer.offset() % 256 != 0 {
+            return Err(Box::new(ValidationError {
+                context: "buffer".into(),
+                problem: "the offset of the buffer is not a multiple of 256".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+vulkan_bitflags! {
+    #[non_exhaustive]
+
+    /// Flags that control how a micromap is created.
+    MicromapCreateFlags = MicromapCreateFlagsEXT(u32);
+
+    /* TODO: enable
+    // TODO: document
+    DEVICE_ADDRESS_CAPTURE_REPLAY = DEVICE_ADDRESS_CAPTURE_REPLAY, */
+}
+
+/// Data and other parameters for a micromap build operation.
+#[derive(Clone, Debug)]
+pub struct MicromapBuildInfo {
+    /// The type of micromap to build.
+    ///
+    /// The default value is [`MicromapType::OpacityMicromap`].
+    pub ty: MicromapType,
+
+    /// Specifies how to build the micromap.
+    ///
+    /// The default value is empty.
+    pub flags: BuildMicromapFlags,
+
+    /// The micromap to build.
+    ///
+    /// This can be `None` when calling [`Device::micromap_build_sizes`], but must be `Some`
+    /// otherwise.
+    ///
+    /// The default value is `None`.
+    pub dst_micromap: Option<Arc<Micromap>>,
+
+    /// How many triangles use each combination of format and subdivision level.
+    ///
+    /// The default value is empty.
+    pub usage_counts: Vec<MicromapUsage>,
+
+    /// The packed opacity values of all triangles.
+    ///
+    /// The buffer must have been created with the [`BufferUsage::MICROMAP_BUILD_INPUT_READ_ONLY`]
+    /// usage, and the device address must be a multiple of 256.
+    ///
+    /// This can be `None` when calling [`Device::micromap_build_sizes`], but must be `Some`
+    /// otherwise.
+    ///
+    /// The default value is `None`.
+    pub data: Option<Subbuffer<[u8]>>,
+
+    /// Scratch memory to be used for the build.
+    ///
+    /// This can be `None` when calling [`Device::micromap_build_sizes`], but must be `Some`
+    /// otherwise.
+    ///
+    /// The default value is `None`.
+    pub scratch_data: Option<Subbuffer<[u8]>>,
+
+    /// An array of [`MicromapTriangle`] values, one for each triangle in the micromap.
+    ///
+    /// The buffer must have been created with the [`BufferUsage::MICROMAP_BUILD_INPUT_READ_ONLY`]
+    /// usage, and the device address must be a multiple of 4.
+    ///
+    /// This can be `None` when calling [`Device::micromap_build_sizes`], but must be `Some`
+    /// otherwise.
+    ///
+    /// The default value is `None`.
+    pub triangle_array: Option<Subbuffer<[MicromapTriangle]>>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for MicromapBuildInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            ty: MicromapType::OpacityMicromap,
+            flags: BuildMicromapFlags::empty(),
+            dst_micromap: None,
+            usage_counts: Vec::new(),
+            data: None,
+            scratch_data: None,
+            triangle_array: None,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+impl MicromapBuildInfo {
+    pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
+        let &Self {
+            ty,
+            flags,
+            ref dst_micromap,
+            ref usage_counts,
+            data: _,
+            scratch_data: _,
+            triangle_array: _,
+            _ne: _,
+        } = self;
+
+        ty.validate_device(device).map_err(|err| {
+            err.add_context("ty")
+                .set_vuids(&["VUID-VkMicromapBuildInfoEXT-type-parameter"])
+        })?;
+
+        flags.validate_device(device).map_err(|err| {
+            err.add_context("flags")
+                .set_vuids(&["VUID-VkMicromapBuildInfoEXT-flags-parameter"])
+        })?;
+
+        if let Some(dst_micromap) = dst_micromap {
+            // VUID-VkMicromapBuildInfoEXT-commonparent
+            assert_eq!(device, dst_micromap.device().as_ref());
+
+            if dst_micromap.ty() != ty {
+                return Err(Box::new(ValidationError {
+                    problem: "`dst_micromap.ty()` does not equal `ty`".into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        let properties = device.physical_device().properties();
+
+        for (index, usage_count) in usage_counts.iter().enumerate() {
+            usage_count
+                .validate(device)
+                .map_err(|err| err.add_context(format!("usage_counts[{}]", index)))?;
+
+            let max_subdivision_level = match usage_count.format {
+                OpacityMicromapFormat::TwoState => properties.max_opacity2_state_subdivision_level,
+                OpacityMicromapFormat::FourState => properties.max_opacity4_state_subdivision_level,
+            }
+            .unwrap_or(0);
+
+            if usage_count.subdivision_level > max_subdivision_level {
+                return Err(Box::new(ValidationError {
+                    context: format!("usage_counts[{}].subdivision_level", index).into(),
+                    problem: "is greater than the maximum subdivision level for \
+                        `usage_counts.format`, as given by the \
+                        `max_opacity2_state_subdivision_level` or \
+                        `max_opacity4_state_subdivision_level` device property"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn to_vulkan(
+        &self,
+    ) -> (
+        ash::vk::MicromapBuildInfoEXT<'static>,
+        Vec<ash::vk::MicromapUsageEXT>,
+    ) {
+        let &Self {
+            ty,
+            flags,
+            ref dst_micromap,
+            ref usage_counts,
+            ref data,
+            ref scratch_data,
+            ref triangle_array,
+            _ne: _,
+        } = self;
+
+        let usage_counts_vk = usage_counts.iter().map(MicromapUsage::to_vulkan).collect();
+
+        (
+            ash::vk::MicromapBuildInfoEXT {
+                ty: ty.into(),
+                flags: flags.into(),
+                mode: ash::vk::BuildMicromapModeEXT::BUILD,
+                dst_micromap: dst_micromap
+                    .as_ref()
+                    .map_or_else(Default::default, VulkanObject::handle),
+                usage_counts_count: 0,
+                p_usage_counts: ptr::null(),
+                pp_usage_counts: ptr::null(),
+                data: ash::vk::DeviceOrHostAddressConstKHR {
+                    device_address: data
+                        .as_ref()
+                        .map_or(0, |data| data.device_address().unwrap().get()),
+                },
+                scratch_data: ash::vk::DeviceOrHostAddressKHR {
+                    device_address: scratch_data.as_ref().map_or(0, |scratch_data| {
+                        scratch_data.device_address().unwrap().get()
+                    }),
+                },
+                triangle_array: ash::vk::DeviceOrHostAddressConstKHR {
+                    device_address: triangle_array.as_ref().map_or(0, |triangle_array| {
+                        triangle_array.device_address().unwrap().get()
+                    }),
+                },
+                triangle_array_stride: size_of::<MicromapTriangle>() as DeviceSize,
+                ..Default::default()
+            },
+            usage_counts_vk,
+        )
+    }
+}
+
+vulkan_bitflags! {
+    #[non_exhaustive]
+
+    /// Flags to control how a micromap should be built.
+    BuildMicromapFlags = BuildMicromapFlagsEXT(u32);
+
+    /// Prioritize for best trace performance, with possibly longer build times.
+    PREFER_FAST_TRACE = PREFER_FAST_TRACE,
+
+    /// Prioritize for shorter build time, with possibly suboptimal trace performance.
+    PREFER_FAST_BUILD = PREFER_FAST_BUILD,
+
+    /// Allow the micromap to be compacted when copying it.
+    ALLOW_COMPACTION = ALLOW_COMPACTION,
+}
+
+/// The number of triangles that use a combination of format and subdivision level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MicromapUsage {
+    /// The number of triangles that use this format and subdivision level.
+    pub count: u32,
+
+    /// The subdivision level. A triangle with subdivision level `n` is split into `4^n`
+    /// micro-triangles.
+    pub subdivision_level: u32,
+
+    /// The format of the opacity values.
+    pub format: OpacityMicromapFormat,
+}
+
+impl MicromapUsage {
+    pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
+        let &Self {
+            count: _,
+            subdivision_level: _,
+            format,
+        } = self;
+
+        format
+            .validate_device(device)
+            .map_err(|err| err.add_context("format"))?;
+
+        Ok(())
+    }
+
+    pub(crate) fn to_vulkan(&self) -> ash::vk::MicromapUsageEXT {
+        let &Self {
+            count,
+            subdivision_level,
+            format,
+        } = self;
+
+        ash::vk::MicromapUsageEXT {
+            count,
+            subdivision_level,
+            format: ash::vk::OpacityMicromapFormatEXT::from(format).as_raw() as u32,
+        }
+    }
+}
+
+vulkan_enum! {
+    #[non_exhaustive]
+
+    /// The format of the opacity values in an opacity micromap.
+    OpacityMicromapFormat = OpacityMicromapFormatEXT(i32);
+
+    /// Each micro-triangle is either opaque or transparent, using 1 bit per micro-triangle.
+    TwoState = TYPE_2_STATE,
+
+    /// Each micro-triangle is opaque, transparent, unknown-opaque or unknown-transparent, using 2
+    /// bits per micro-triangle.
+    FourState = TYPE_4_STATE,
+}
+
+/// Describes the data of one triangle in a micromap build.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Zeroable, Pod)]
+#[repr(C)]
+pub struct MicromapTriangle {
+    /// The byte offset of the data of the triangle, in [`MicromapBuildInfo::data`].
+    pub data_offset: u32,
+
+    /// The subdivision level of the triangle.
+    pub subdivision_level: u16,
+
+    /// The raw value of the [`OpacityMicromapFormat`] of the triangle.
+    pub format: u16,
+}
+
+/// Special indices that can be used in the index buffer of an opacity micromap, instead of
+/// referring to a triangle of the micromap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+#[non_exhaustive]
+pub enum OpacityMicromapSpecialIndex {
+    /// The whole triangle is transparent.
+    FullyTransparent = ash::vk::OpacityMicromapSpecialIndexEXT::FULLY_TRANSPARENT.as_raw(),
+
+    /// The whole triangle is opaque.
+    FullyOpaque = ash::vk::OpacityMicromapSpecialIndexEXT::FULLY_OPAQUE.as_raw(),
+
+    /// The whole triangle is unknown, and treated as transparent if the opacity is forced to two
+    /// states.
+    FullyUnknownTransparent =
+        ash::vk::OpacityMicromapSpecialIndexEXT::FULLY_UNKNOWN_TRANSPARENT.as_raw(),
+
+    /// The whole triangle is unknown, and treated as opaque if the opacity is forced to two
+    /// states.
+    FullyUnknownOpaque = ash::vk::OpacityMicromapSpecialIndexEXT::FULLY_UNKNOWN_OPAQUE.as_raw(),
+}
+
+impl OpacityMicromapSpecialIndex {
+    /// Returns the value of the special index as it is stored in a 32-bit index buffer.
+    #[inline]
+    pub fn as_u32(self) -> u32 {
+        self as i32 as u32
+    }
+}
+
+/// The minimum sizes needed for various resources during a micromap build operation.
+#[derive(Clone, Debug)]
+pub struct MicromapBuildSizesInfo {
+    /// The minimum required size of the micromap.
+    pub micromap_size: DeviceSize,
+
+    /// The minimum required size of the scratch data buffer for a build operation.
+    pub build_scratch_size: DeviceSize,
+
+    /// Whether the micromap can be discarded after an acceleration structure has been built from
+    /// it, because the acceleration structure doesn't need it anymore.
+    pub discardable: bool,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Micromap, MicromapBuildInfo, MicromapCreateInfo, MicromapUsage, OpacityMicromapFormat,
+        OpacityMicromapSpecialIndex,
+    };
+    use crate::{
+        acceleration_structure::AccelerationStructureBuildType,
+        buffer::{Buffer, BufferCreateInfo, BufferUsage},
+        memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn special_index_values() {
+        assert_eq!(
+            OpacityMicromapSpecialIndex::FullyTransparent.as_u32(),
+            u32::MAX
+        );
+        assert_eq!(
+            OpacityMicromapSpecialIndex::FullyOpaque.as_u32(),
+            u32::MAX - 1
+        );
+        assert_eq!(
+            OpacityMicromapSpecialIndex::FullyUnknownTransparent.as_u32(),
+            u32::MAX - 2
+        );
+        assert_eq!(
+            OpacityMicromapSpecialIndex::FullyUnknownOpaque.as_u32(),
+            u32::MAX - 3
+        );
+    }
+
+    #[test]
+    fn missing_extension() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+        let buffer = Buffer::new_slice::<u8>(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+            256,
+        )
+        .unwrap();
+
+        assert!(unsafe { Micromap::new(device.clone(), MicromapCreateInfo::new(buffer)) }.is_err());
+        assert!(device
+            .micromap_build_sizes(
+                AccelerationStructureBuildType::Device,
+                &MicromapBuildInfo::default(),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn create() {
+        let (device, _) = gfx_dev_and_queue!(
+            micromap;
+            ext_opacity_micromap,
+            khr_acceleration_structure,
+            khr_synchronization2,
+            khr_deferred_host_operations,
+            khr_buffer_device_address,
+            ext_descriptor_indexing
+        );
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+        let build_info = MicromapBuildInfo {
+            usage_counts: vec![MicromapUsage {
+                count: 1,
+                subdivision_level: 0,
+                format: OpacityMicromapFormat::TwoState,
+            }],
+            ..Default::default()
+        };
+        let build_sizes = device
+            .micromap_build_sizes(AccelerationStructureBuildType::Device, &build_info)
+            .unwrap();
+        assert!(build_sizes.micromap_size > 0);
+
+        let buffer = Buffer::new_slice::<u8>(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::MICROMAP_STORAGE,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+            build_sizes.micromap_size + 256,
+        )
+        .unwrap();
+
+        // The offset of the buffer must be a multiple of 256.
+        assert!(unsafe {
+            Micromap::new(
+                device.clone(),
+                MicromapCreateInfo::new(buffer.clone().slice(1..)),
+            )
+        }
+        .is_err());
+
+        let micromap = unsafe {
+            Micromap::new(
+                device.clone(),
+                MicromapCreateInfo::new(buffer.slice(..build_sizes.micromap_size)),
+            )
+        }
+        .unwrap();
+        assert_eq!(micromap.size(), build_sizes.micromap_size);
+    }
+
+    #[test]
+    fn subdivision_level_too_high() {
+        let (device, _) = gfx_dev_and_queue!(
+            micromap;
+            ext_opacity_micromap,
+            khr_acceleration_structure,
+            khr_synchronization2,
+            khr_deferred_host_operations,
+            khr_buffer_device_address,
+            ext_descriptor_indexing
+        );
+
+        let max_subdivision_level = device
+            .physical_device()
+            .properties()
+            .max_opacity2_state_subdivision_level
+            .unwrap_or(0);
+        let build_info = MicromapBuildInfo {
+            usage_counts: vec![MicromapUsage {
+                count: 1,
+                subdivision_level: max_subdivision_level + 1,
+                format: OpacityMicromapFormat::TwoState,
+            }],
+            ..Default::default()
+        };
+
+        assert!(device
+            .micromap_build_sizes(AccelerationStructureBuildType::Device, &build_info)
+            .is_err());
+    }
+}
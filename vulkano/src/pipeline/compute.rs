@@ -13,14 +13,14 @@
 //! binding any descriptor sets and/or push constants that the pipeline needs, and then issuing a
 //! `dispatch` command on the command buffer.
 
-use super::{PipelineCreateFlags, PipelineShaderStageCreateInfo};
+use super::{PipelineCreateFlags, PipelineRobustness, PipelineShaderStageCreateInfo};
 use crate::{
     device::{Device, DeviceOwned, DeviceOwnedDebugWrapper},
     instance::InstanceOwnedDebugWrapper,
     macros::impl_id_counter,
     pipeline::{cache::PipelineCache, layout::PipelineLayout, Pipeline, PipelineBindPoint},
     shader::{spirv::ExecutionModel, DescriptorBindingRequirements, ShaderStage},
-    Validated, ValidationError, VulkanError, VulkanObject,
+    Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, VulkanError, VulkanObject,
 };
 use ahash::HashMap;
 use std::{ffi::CString, fmt::Debug, mem::MaybeUninit, num::NonZeroU64, ptr, sync::Arc};
@@ -41,6 +41,7 @@ pub struct ComputePipeline {
 
     flags: PipelineCreateFlags,
     layout: DeviceOwnedDebugWrapper<Arc<PipelineLayout>>,
+    robustness: PipelineRobustness,
 
     descriptor_binding_requirements: HashMap<(u32, u32), DescriptorBindingRequirements>,
     num_used_descriptor_sets: u32,
@@ -49,6 +50,7 @@ pub struct ComputePipeline {
 impl ComputePipeline {
     /// Creates a new `ComputePipeline`.
     #[inline]
+    #[track_caller]
     pub fn new(
         device: Arc<Device>,
         cache: Option<Arc<PipelineCache>>,
@@ -56,7 +58,12 @@ impl ComputePipeline {
     ) -> Result<Arc<ComputePipeline>, Validated<VulkanError>> {
         Self::validate_new(&device, cache.as_ref().map(AsRef::as_ref), &create_info)?;
 
-        unsafe { Ok(Self::new_unchecked(device, cache, create_info)?) }
+        let pipeline = unsafe { Self::new_unchecked(device, cache, create_info)? };
+        pipeline
+            .device()
+            .set_auto_debug_utils_object_name(&*pipeline, "ComputePipeline");
+
+        Ok(pipeline)
     }
 
     fn validate_new(
@@ -75,6 +82,10 @@ impl ComputePipeline {
     }
 
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(flags = ?create_info.flags))
+    )]
     pub unsafe fn new_unchecked(
         device: Arc<Device>,
         cache: Option<Arc<PipelineCache>>,
@@ -85,6 +96,7 @@ impl ComputePipeline {
             ref stage,
             ref layout,
             ref base_pipeline,
+            robustness,
             _ne: _,
         } = &create_info;
 
@@ -94,12 +106,16 @@ impl ComputePipeline {
         let specialization_map_entries_vk: Vec<_>;
         let mut specialization_data_vk: Vec<u8>;
         let required_subgroup_size_create_info;
+        let mut stage_robustness_create_info_vk;
+        let mut module_identifier_create_info_vk;
 
         {
             let &PipelineShaderStageCreateInfo {
                 flags,
                 ref entry_point,
                 ref required_subgroup_size,
+                robustness: stage_robustness,
+                ref module_identifier,
                 _ne: _,
             } = stage;
 
@@ -139,16 +155,44 @@ impl ComputePipeline {
                         ..Default::default()
                     }
                 });
+            stage_robustness_create_info_vk = (!stage_robustness.is_device_default())
+                .then(|| ash::vk::PipelineRobustnessCreateInfoEXT::from(stage_robustness));
+            let mut stage_p_next: *const _ = required_subgroup_size_create_info.as_ref().map_or(
+                ptr::null(),
+                |required_subgroup_size_create_info| {
+                    <*const _>::cast(required_subgroup_size_create_info)
+                },
+            );
+
+            if let Some(info) = stage_robustness_create_info_vk.as_mut() {
+                info.p_next = stage_p_next;
+                stage_p_next = <*const _>::cast(info);
+            }
+
+            module_identifier_create_info_vk = module_identifier.as_ref().map(|identifier| {
+                let identifier = identifier.as_bytes();
+
+                ash::vk::PipelineShaderStageModuleIdentifierCreateInfoEXT {
+                    identifier_size: identifier.len() as u32,
+                    p_identifier: identifier.as_ptr(),
+                    ..Default::default()
+                }
+            });
+
+            if let Some(info) = module_identifier_create_info_vk.as_mut() {
+                info.p_next = stage_p_next;
+                stage_p_next = <*const _>::cast(info);
+            }
+
             stage_vk = ash::vk::PipelineShaderStageCreateInfo {
-                p_next: required_subgroup_size_create_info.as_ref().map_or(
-                    ptr::null(),
-                    |required_subgroup_size_create_info| {
-                        <*const _>::cast(required_subgroup_size_create_info)
-                    },
-                ),
+                p_next: stage_p_next,
                 flags: flags.into(),
                 stage: ShaderStage::from(entry_point_info.execution_model).into(),
-                module: entry_point.module().handle(),
+                module: if module_identifier.is_some() {
+                    ash::vk::ShaderModule::null()
+                } else {
+                    entry_point.module().handle()
+                },
                 p_name: name_vk.as_ptr(),
                 p_specialization_info: if specialization_info_vk.data_size == 0 {
                     ptr::null()
@@ -159,7 +203,19 @@ impl ComputePipeline {
             };
         }
 
-        let create_infos_vk = ash::vk::ComputePipelineCreateInfo {
+        let mut robustness_create_info_vk = (!robustness.is_device_default())
+            .then(|| ash::vk::PipelineRobustnessCreateInfoEXT::from(robustness));
+
+        // When the flags are also given in this structure, the `flags` of the create info are
+        // ignored.
+        let mut flags2_create_info_vk = device.enabled_extensions().khr_maintenance5.then(|| {
+            ash::vk::PipelineCreateFlags2CreateInfoKHR {
+                flags: flags.into(),
+                ..Default::default()
+            }
+        });
+
+        let mut create_infos_vk = ash::vk::ComputePipelineCreateInfo {
             flags: flags.into(),
             stage: stage_vk,
             layout: layout.handle(),
@@ -170,6 +226,16 @@ impl ComputePipeline {
             ..Default::default()
         };
 
+        if let Some(info) = robustness_create_info_vk.as_mut() {
+            info.p_next = create_infos_vk.p_next;
+            create_infos_vk.p_next = <*const _>::cast(info);
+        }
+
+        if let Some(info) = flags2_create_info_vk.as_mut() {
+            info.p_next = create_infos_vk.p_next;
+            create_infos_vk.p_next = <*const _>::cast(info);
+        }
+
         let handle = {
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
@@ -206,6 +272,7 @@ impl ComputePipeline {
             stage,
             layout,
             base_pipeline: _,
+            robustness,
             _ne: _,
         } = create_info;
 
@@ -230,6 +297,7 @@ impl ComputePipeline {
 
             flags,
             layout: DeviceOwnedDebugWrapper(layout),
+            robustness,
 
             descriptor_binding_requirements,
             num_used_descriptor_sets,
@@ -247,6 +315,12 @@ impl ComputePipeline {
     pub fn flags(&self) -> PipelineCreateFlags {
         self.flags
     }
+
+    /// Returns the robustness behavior that the pipeline was created with.
+    #[inline]
+    pub fn robustness(&self) -> &PipelineRobustness {
+        &self.robustness
+    }
 }
 
 impl Pipeline for ComputePipeline {
@@ -328,6 +402,17 @@ pub struct ComputePipelineCreateInfo {
     /// The default value is `None`.
     pub base_pipeline: Option<Arc<ComputePipeline>>,
 
+    /// The robustness behavior of the pipeline.
+    ///
+    /// This can be overridden for the shader stage with
+    /// [`PipelineShaderStageCreateInfo::robustness`]. If the behavior is not the device default,
+    /// the [`ext_pipeline_robustness`] extension must be enabled on the device.
+    ///
+    /// The default value is the device default behavior.
+    ///
+    /// [`ext_pipeline_robustness`]: crate::device::DeviceExtensions::ext_pipeline_robustness
+    pub robustness: PipelineRobustness,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -340,6 +425,7 @@ impl ComputePipelineCreateInfo {
             stage,
             layout,
             base_pipeline: None,
+            robustness: PipelineRobustness::default(),
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -350,6 +436,7 @@ impl ComputePipelineCreateInfo {
             ref stage,
             ref layout,
             ref base_pipeline,
+            robustness,
             _ne: _,
         } = self;
 
@@ -358,6 +445,23 @@ impl ComputePipelineCreateInfo {
                 .set_vuids(&["VUID-VkComputePipelineCreateInfo-flags-parameter"])
         })?;
 
+        if flags.intersects(PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED)
+            && !device.enabled_features().pipeline_creation_cache_control
+        {
+            return Err(Box::new(ValidationError {
+                context: "flags".into(),
+                problem: "contains `PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "pipeline_creation_cache_control",
+                )])]),
+                vuids: &["VUID-VkComputePipelineCreateInfo-pipelineCreationCacheControl-02875"],
+            }));
+        }
+
+        robustness
+            .validate(device)
+            .map_err(|err| err.add_context("robustness"))?;
+
         stage
             .validate(device)
             .map_err(|err| err.add_context("stage"))?;
@@ -397,9 +501,22 @@ impl ComputePipelineCreateInfo {
             flags: _,
             ref entry_point,
             required_subgroup_size: _vk,
+            robustness: _,
+            ref module_identifier,
             _ne: _,
         } = &stage;
 
+        if module_identifier.is_some()
+            && !flags.intersects(PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED)
+        {
+            return Err(Box::new(ValidationError {
+                problem: "`stage.module_identifier` is `Some`, but `flags` does not contain \
+                    `PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED`"
+                    .into(),
+                ..Default::default()
+            }));
+        }
+
         let entry_point_info = entry_point.info();
 
         if !matches!(entry_point_info.execution_model, ExecutionModel::GLCompute) {
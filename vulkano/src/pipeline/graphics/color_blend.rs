@@ -26,6 +26,8 @@ use std::iter;
 /// Describes how the color output of the fragment shader is written to the attachment. See the
 /// documentation of the `blend` module for more info.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ColorBlendState {
     /// Additional properties of the color blend state.
     ///
@@ -58,6 +60,17 @@ pub struct ColorBlendState {
     /// The default value is `[0.0; 4]`.
     pub blend_constants: [f32; 4],
 
+    /// Additional parameters for attachments that use an advanced [`BlendOp`].
+    ///
+    /// If set to `Some`, the
+    /// [`ext_blend_operation_advanced`](crate::device::DeviceExtensions::ext_blend_operation_advanced)
+    /// extension must be enabled on the device. If set to `None`, the defaults of
+    /// [`AdvancedBlendState`] are used.
+    ///
+    /// The default value is `None`.
+    pub advanced_blend: Option<AdvancedBlendState>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _ne: crate::NonExhaustive,
 }
 
@@ -70,6 +83,7 @@ impl Default for ColorBlendState {
             logic_op: None,
             attachments: Vec::new(),
             blend_constants: [0.0; 4],
+            advanced_blend: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -100,6 +114,7 @@ impl ColorBlendState {
                 .map(|_| ColorBlendAttachmentState::default())
                 .collect(),
             blend_constants: [0.0; 4],
+            advanced_blend: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -166,9 +181,12 @@ impl ColorBlendState {
             logic_op,
             ref attachments,
             blend_constants: _,
+            ref advanced_blend,
             _ne: _,
         } = self;
 
+        let properties = device.physical_device().properties();
+
         flags.validate_device(device).map_err(|err| {
             err.add_context("flags")
                 .set_vuids(&["VUID-VkPipelineColorBlendStateCreateInfo-flags-parameter"])
@@ -221,6 +239,75 @@ impl ColorBlendState {
             }
         }
 
+        let mut first_advanced_blend: Option<(usize, &AttachmentBlend)> = None;
+
+        for (index, blend) in attachments
+            .iter()
+            .enumerate()
+            .filter_map(|(index, state)| Some((index, state.blend.as_ref()?)))
+            .filter(|(_, blend)| blend.color_blend_op.is_advanced())
+        {
+            if attachments.len() as u32
+                > properties.advanced_blend_max_color_attachments.unwrap_or(0)
+            {
+                return Err(Box::new(ValidationError {
+                    problem: format!(
+                        "`attachments[{}].blend.color_blend_op` is an advanced blend operation, \
+                        but the length of `attachments` is greater than the \
+                        `advanced_blend_max_color_attachments` limit",
+                        index
+                    )
+                    .into(),
+                    vuids: &["VUID-VkPipelineColorBlendAttachmentState-colorBlendOp-01410"],
+                    ..Default::default()
+                }));
+            }
+
+            if properties.advanced_blend_independent_blend != Some(true) {
+                if let Some((first_index, first_blend)) = first_advanced_blend {
+                    if blend.color_blend_op != first_blend.color_blend_op
+                        || blend.alpha_blend_op != first_blend.alpha_blend_op
+                    {
+                        return Err(Box::new(ValidationError {
+                            problem: format!(
+                                "`attachments[{0}].blend` and `attachments[{1}].blend` \
+                                both use an advanced blend operation, and the \
+                                `advanced_blend_independent_blend` device property is \
+                                `false`, but the blend operations of \
+                                `attachments[{0}].blend` and `attachments[{1}].blend` differ",
+                                first_index, index
+                            )
+                            .into(),
+                            vuids: &[
+                                "VUID-VkPipelineColorBlendAttachmentState-advancedBlendIndependentBlend-01407",
+                                "VUID-VkPipelineColorBlendAttachmentState-advancedBlendIndependentBlend-01408",
+                            ],
+                            ..Default::default()
+                        }));
+                    }
+                } else {
+                    first_advanced_blend = Some((index, blend));
+                }
+            }
+        }
+
+        if let Some(advanced_blend) = advanced_blend {
+            if !device.enabled_extensions().ext_blend_operation_advanced {
+                return Err(Box::new(ValidationError {
+                    context: "advanced_blend".into(),
+                    problem: "is `Some`".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                        "ext_blend_operation_advanced",
+                    )])]),
+                    ..Default::default()
+                }));
+            }
+
+            advanced_blend
+                .validate(device)
+                .map_err(|err| err.add_context("advanced_blend"))?;
+        }
+
         Ok(())
     }
 
@@ -548,6 +635,8 @@ impl Default for LogicOp {
 /// Describes how a framebuffer color attachment is handled in the pipeline during the color
 /// blend stage.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ColorBlendAttachmentState {
     /// The blend parameters for the attachment.
     ///
@@ -613,8 +702,145 @@ impl ColorBlendAttachmentState {
     }
 }
 
+/// Additional parameters for advanced blend operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AdvancedBlendState {
+    /// Whether the source color is treated as premultiplied by its alpha.
+    ///
+    /// If set to `false`, the
+    /// [`advanced_blend_non_premultiplied_src_color`](crate::device::DeviceProperties::advanced_blend_non_premultiplied_src_color)
+    /// device property must be `true`.
+    ///
+    /// The default value is `true`.
+    pub src_premultiplied: bool,
+
+    /// Whether the destination color is treated as premultiplied by its alpha.
+    ///
+    /// If set to `false`, the
+    /// [`advanced_blend_non_premultiplied_dst_color`](crate::device::DeviceProperties::advanced_blend_non_premultiplied_dst_color)
+    /// device property must be `true`.
+    ///
+    /// The default value is `true`.
+    pub dst_premultiplied: bool,
+
+    /// How the coverage of the source and destination are assumed to overlap.
+    ///
+    /// If set to a value other than `BlendOverlap::Uncorrelated`, the
+    /// [`advanced_blend_correlated_overlap`](crate::device::DeviceProperties::advanced_blend_correlated_overlap)
+    /// device property must be `true`.
+    ///
+    /// The default value is [`BlendOverlap::Uncorrelated`].
+    pub blend_overlap: BlendOverlap,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for AdvancedBlendState {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            src_premultiplied: true,
+            dst_premultiplied: true,
+            blend_overlap: BlendOverlap::Uncorrelated,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+impl AdvancedBlendState {
+    pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
+        let &Self {
+            src_premultiplied,
+            dst_premultiplied,
+            blend_overlap,
+            _ne: _,
+        } = self;
+
+        let properties = device.physical_device().properties();
+
+        blend_overlap.validate_device(device).map_err(|err| {
+            err.add_context("blend_overlap").set_vuids(&[
+                "VUID-VkPipelineColorBlendAdvancedStateCreateInfoEXT-blendOverlap-parameter",
+            ])
+        })?;
+
+        if !src_premultiplied && properties.advanced_blend_non_premultiplied_src_color != Some(true)
+        {
+            return Err(Box::new(ValidationError {
+                context: "src_premultiplied".into(),
+                problem: "is `false`, but the `advanced_blend_non_premultiplied_src_color` \
+                    device property is `false`"
+                    .into(),
+                vuids: &[
+                    "VUID-VkPipelineColorBlendAdvancedStateCreateInfoEXT-srcPremultiplied-01424",
+                ],
+                ..Default::default()
+            }));
+        }
+
+        if !dst_premultiplied && properties.advanced_blend_non_premultiplied_dst_color != Some(true)
+        {
+            return Err(Box::new(ValidationError {
+                context: "dst_premultiplied".into(),
+                problem: "is `false`, but the `advanced_blend_non_premultiplied_dst_color` \
+                    device property is `false`"
+                    .into(),
+                vuids: &[
+                    "VUID-VkPipelineColorBlendAdvancedStateCreateInfoEXT-dstPremultiplied-01425",
+                ],
+                ..Default::default()
+            }));
+        }
+
+        if blend_overlap != BlendOverlap::Uncorrelated
+            && properties.advanced_blend_correlated_overlap != Some(true)
+        {
+            return Err(Box::new(ValidationError {
+                context: "blend_overlap".into(),
+                problem: "is not `BlendOverlap::Uncorrelated`, but the \
+                    `advanced_blend_correlated_overlap` device property is `false`"
+                    .into(),
+                vuids: &["VUID-VkPipelineColorBlendAdvancedStateCreateInfoEXT-blendOverlap-01426"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+vulkan_enum! {
+    #[non_exhaustive]
+
+    /// How the coverage of the source and destination of an advanced blend operation are assumed
+    /// to overlap within a pixel.
+    BlendOverlap = BlendOverlapEXT(i32);
+
+    /// The source and destination coverage are assumed to be uncorrelated.
+    Uncorrelated = UNCORRELATED,
+
+    /// The source and destination coverage are assumed to be mutually exclusive.
+    Disjoint = DISJOINT,
+
+    /// The source and destination coverage are assumed to overlap as much as possible.
+    Conjoint = CONJOINT,
+}
+
+impl Default for BlendOverlap {
+    /// Returns `BlendOverlap::Uncorrelated`.
+    #[inline]
+    fn default() -> Self {
+        Self::Uncorrelated
+    }
+}
+
 /// Describes how the blending system should behave for an attachment.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct AttachmentBlend {
     /// The operation to apply to the source color component before applying `color_op`.
     ///
@@ -758,6 +984,38 @@ impl AttachmentBlend {
                 .set_vuids(&["VUID-VkPipelineColorBlendAttachmentState-alphaBlendOp-parameter"])
         })?;
 
+        if (color_blend_op.is_advanced() || alpha_blend_op.is_advanced())
+            && color_blend_op != alpha_blend_op
+        {
+            return Err(Box::new(ValidationError {
+                problem: "`color_blend_op` or `alpha_blend_op` is an advanced blend operation, \
+                    but `color_blend_op` does not equal `alpha_blend_op`"
+                    .into(),
+                vuids: &["VUID-VkPipelineColorBlendAttachmentState-colorBlendOp-01406"],
+                ..Default::default()
+            }));
+        }
+
+        if color_blend_op.is_advanced()
+            && !color_blend_op.is_advanced_basic()
+            && device
+                .physical_device()
+                .properties()
+                .advanced_blend_all_operations
+                != Some(true)
+        {
+            return Err(Box::new(ValidationError {
+                context: "color_blend_op".into(),
+                problem: "is an advanced blend operation that is only supported if the \
+                    `advanced_blend_all_operations` device property is `true`, but it is `false`"
+                    .into(),
+                vuids: &[
+                    "VUID-VkPipelineColorBlendAttachmentState-advancedBlendAllOperations-01409",
+                ],
+                ..Default::default()
+            }));
+        }
+
         if !device.enabled_features().dual_src_blend {
             if matches!(
                 src_color_blend_factor,
@@ -1003,6 +1261,15 @@ vulkan_enum! {
 
     /// The arithmetic operation that is applied between the `source` and `destination` component
     /// values, after the appropriate `BlendFactor` is applied to both.
+    ///
+    /// The operations after `Max` are *advanced* blend operations. They ignore the blend factors,
+    /// operate on all color components at once, and require the
+    /// [`ext_blend_operation_advanced`](crate::device::DeviceExtensions::ext_blend_operation_advanced)
+    /// extension to be enabled on the device. Unless the
+    /// [`advanced_blend_coherent_operations`](crate::device::DeviceFeatures::advanced_blend_coherent_operations)
+    /// feature is enabled, a barrier with
+    /// [`AccessFlags::COLOR_ATTACHMENT_READ_NONCOHERENT`](crate::sync::AccessFlags::COLOR_ATTACHMENT_READ_NONCOHERENT)
+    /// must be recorded between draws that blend overlapping fragments with these operations.
     BlendOp = BlendOp(i32);
 
     /// `source + destination`.
@@ -1020,349 +1287,366 @@ vulkan_enum! {
     /// `max(source, destination)`.
     Max = MAX,
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *clear*: the result is zero.
     Zero = ZERO_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *source*: the result is the source.
     Src = SRC_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *destination*: the result is the destination.
     Dst = DST_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *source over*: the source is placed over the destination.
     SrcOver = SRC_OVER_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *destination over*: the destination is placed over the source.
     DstOver = DST_OVER_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *source in*: the part of the source inside the destination replaces the
+    /// destination.
     SrcIn = SRC_IN_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *destination in*: the part of the destination inside the source replaces
+    /// the source.
     DstIn = DST_IN_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *source out*: the part of the source outside the destination replaces the
+    /// destination.
     SrcOut = SRC_OUT_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *destination out*: the part of the destination outside the source replaces
+    /// the source.
     DstOut = DST_OUT_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *source atop*: the part of the source inside the destination is placed over
+    /// the destination.
     SrcAtop = SRC_ATOP_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *destination atop*: the part of the destination inside the source is placed
+    /// over the source.
     DstAtop = DST_ATOP_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Porter-Duff *xor*: the parts of the source and destination outside of each other are
+    /// combined.
     Xor = XOR_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The source and destination colors are multiplied.
     Multiply = MULTIPLY_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The inverses of the source and destination colors are multiplied, and the result is
+    /// inverted.
     Screen = SCREEN_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// `Multiply` or `Screen`, depending on the destination color.
     Overlay = OVERLAY_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The darker of the source and destination colors.
     Darken = DARKEN_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The lighter of the source and destination colors.
     Lighten = LIGHTEN_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The destination color is brightened to reflect the source color.
     Colordodge = COLORDODGE_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The destination color is darkened to reflect the source color.
     Colorburn = COLORBURN_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// `Multiply` or `Screen`, depending on the source color.
     Hardlight = HARDLIGHT_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The destination color is darkened or lightened, depending on the source color.
     Softlight = SOFTLIGHT_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The absolute difference between the source and destination colors.
     Difference = DIFFERENCE_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Similar to `Difference`, but with lower contrast.
     Exclusion = EXCLUSION_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The destination color is inverted.
     Invert = INVERT_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The destination color is inverted, weighted by the source color.
     InvertRgb = INVERT_RGB_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The sum of the source and destination colors.
     Lineardodge = LINEARDODGE_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The sum of the source and destination colors, minus one.
     Linearburn = LINEARBURN_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// `Colorburn` or `Colordodge`, depending on the source color.
     Vividlight = VIVIDLIGHT_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// `Linearburn` or `Lineardodge`, depending on the source color.
     Linearlight = LINEARLIGHT_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// `Darken` or `Lighten`, depending on the source color.
     Pinlight = PINLIGHT_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Each color component is set to zero or one, depending on the sum of the source and
+    /// destination components.
     Hardmix = HARDMIX_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The hue of the source color with the saturation and luminosity of the destination
+    /// color.
     HslHue = HSL_HUE_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The saturation of the source color with the hue and luminosity of the destination
+    /// color.
     HslSaturation = HSL_SATURATION_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The hue and saturation of the source color with the luminosity of the destination
+    /// color.
     HslColor = HSL_COLOR_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The luminosity of the source color with the hue and saturation of the destination
+    /// color.
     HslLuminosity = HSL_LUMINOSITY_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The sum of the source and destination, without clamping.
     Plus = PLUS_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The sum of the source and destination, clamped to the range [0, 1].
     PlusClamped = PLUS_CLAMPED_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The sum of the source and destination, with the color clamped to the resulting alpha.
     PlusClampedAlpha = PLUS_CLAMPED_ALPHA_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The sum of the source and destination, offset to darken the result.
     PlusDarker = PLUS_DARKER_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The source subtracted from the destination, without clamping.
     Minus = MINUS_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The source subtracted from the destination, clamped to the range [0, 1].
     MinusClamped = MINUS_CLAMPED_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The contrast of the destination is adjusted by the source.
     Contrast = CONTRAST_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The destination color is inverted, as defined by OpenVG.
     InvertOvg = INVERT_OVG_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The red component of the source with the green and blue components of the destination.
     Red = RED_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The green component of the source with the red and blue components of the
+    /// destination.
     Green = GREEN_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// The blue component of the source with the red and green components of the destination.
     Blue = BLUE_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_blend_operation_advanced)]),
-    ]),*/
+    ]),
 }
 
 impl BlendOp {
     /// Returns whether the blend op will use the specified blend factors, or ignore them.
     #[inline]
     pub const fn uses_blend_factors(self) -> bool {
-        match self {
-            BlendOp::Add | BlendOp::Subtract | BlendOp::ReverseSubtract => true,
-            BlendOp::Min | BlendOp::Max => false,
-        }
+        matches!(
+            self,
+            BlendOp::Add | BlendOp::Subtract | BlendOp::ReverseSubtract
+        )
     }
 
-    const fn source_components_used(self, output_component: usize) -> [ColorComponents; 2] {
-        match self {
+    /// Returns whether this is an advanced blend operation, provided by the
+    /// [`ext_blend_operation_advanced`](crate::device::DeviceExtensions::ext_blend_operation_advanced)
+    /// extension.
+    #[inline]
+    pub const fn is_advanced(self) -> bool {
+        !matches!(
+            self,
             BlendOp::Add
-            | BlendOp::Subtract
-            | BlendOp::ReverseSubtract
-            | BlendOp::Min
-            | BlendOp::Max => [
+                | BlendOp::Subtract
+                | BlendOp::ReverseSubtract
+                | BlendOp::Min
+                | BlendOp::Max
+        )
+    }
+
+    /// Returns whether this advanced blend operation is available even if the
+    /// [`advanced_blend_all_operations`](crate::device::DeviceProperties::advanced_blend_all_operations)
+    /// property is `false`.
+    const fn is_advanced_basic(self) -> bool {
+        matches!(
+            self,
+            BlendOp::Multiply
+                | BlendOp::Screen
+                | BlendOp::Overlay
+                | BlendOp::Darken
+                | BlendOp::Lighten
+                | BlendOp::Colordodge
+                | BlendOp::Colorburn
+                | BlendOp::Hardlight
+                | BlendOp::Softlight
+                | BlendOp::Difference
+                | BlendOp::Exclusion
+                | BlendOp::HslHue
+                | BlendOp::HslSaturation
+                | BlendOp::HslColor
+                | BlendOp::HslLuminosity
+        )
+    }
+
+    const fn source_components_used(self, output_component: usize) -> [ColorComponents; 2] {
+        if self.is_advanced() {
+            // Advanced operations work on premultiplied colors, so every output component
+            // depends on the source alpha. The color outputs depend on all source colors.
+            if output_component == 3 {
+                [ColorComponents::A, ColorComponents::empty()]
+            } else {
+                [
+                    ColorComponents::R
+                        .union(ColorComponents::G)
+                        .union(ColorComponents::B)
+                        .union(ColorComponents::A),
+                    ColorComponents::empty(),
+                ]
+            }
+        } else {
+            [
                 ColorComponents::from_index(output_component),
                 ColorComponents::empty(),
-            ],
+            ]
         }
     }
 }
@@ -21,6 +21,8 @@ use std::ops::RangeInclusive;
 /// The state in a graphics pipeline describing how the depth, depth bounds and stencil tests
 /// should behave.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct DepthStencilState {
     /// Additional properties of the depth/stencil state.
     ///
@@ -51,6 +53,7 @@ pub struct DepthStencilState {
     /// The default value is `None`.
     pub stencil: Option<StencilState>,
 
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _ne: crate::NonExhaustive,
 }
 
@@ -183,6 +186,8 @@ vulkan_bitflags! {
 
 /// The state in a graphics pipeline describing how the depth test should behave when enabled.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct DepthState {
     /// Sets whether the value in the depth buffer will be updated when the depth test succeeds.
     ///
@@ -242,6 +247,8 @@ impl DepthState {
 
 /// The state in a graphics pipeline describing how the stencil test should behave when enabled.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct StencilState {
     /// The stencil operation state to use for points and lines, and for triangles whose front is
     /// facing the user.
@@ -287,6 +294,8 @@ impl StencilState {
 
 /// Stencil test operations for a single face.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct StencilOpState {
     /// The stencil operations to perform.
     ///
@@ -334,6 +343,8 @@ impl Default for StencilOpState {
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct StencilOps {
     /// The operation to perform when the stencil test failed.
     ///
@@ -6,6 +6,8 @@ use crate::{
 
 /// The state in a graphics pipeline describing how the input assembly stage should behave.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct InputAssemblyState {
     /// The type of primitives.
     ///
@@ -32,6 +34,7 @@ pub struct InputAssemblyState {
     /// The default value is `false`.
     pub primitive_restart_enable: bool,
 
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _ne: crate::NonExhaustive,
 }
 
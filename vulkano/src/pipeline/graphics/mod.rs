@@ -76,6 +76,12 @@
 //! command buffer, binding the necessary vertex buffers, binding any descriptor sets, setting push
 //! constants, and setting any dynamic state that the pipeline may need. Then you issue a `draw`
 //! command.
+//!
+//! # Describing state in data files
+//!
+//! With the `serde` feature enabled, the vertex input, input assembly, rasterization, multisample,
+//! depth/stencil and color blend state structs implement `Serialize` and `Deserialize`, so that
+//! they can be loaded from data files. Fields that are missing take their default values.
 
 use self::{
     color_blend::ColorBlendState,
@@ -95,7 +101,7 @@ use super::{
     inout_interface::{shader_interface_location_info, ShaderInterfaceLocationInfo},
     shader::inout_interface::validate_interfaces_compatible,
     DynamicState, Pipeline, PipelineBindPoint, PipelineCreateFlags, PipelineLayout,
-    PipelineShaderStageCreateInfo,
+    PipelineRobustness, PipelineShaderStageCreateInfo,
 };
 use crate::{
     device::{Device, DeviceOwned, DeviceOwnedDebugWrapper},
@@ -104,10 +110,10 @@ use crate::{
     instance::InstanceOwnedDebugWrapper,
     macros::impl_id_counter,
     pipeline::graphics::{
-        color_blend::ColorBlendAttachmentState,
+        color_blend::{AdvancedBlendState, ColorBlendAttachmentState},
         conservative_rasterization::ConservativeRasterizationState,
         depth_stencil::{StencilOpState, StencilState},
-        rasterization::{CullMode, DepthBiasState},
+        rasterization::{CullMode, DepthBiasState, ProvokingVertexMode},
         subpass::PipelineRenderingCreateInfo,
         tessellation::TessellationDomainOrigin,
         vertex_input::{
@@ -115,7 +121,7 @@ use crate::{
         },
     },
     shader::{
-        spirv::{ExecutionMode, ExecutionModel, Instruction, StorageClass},
+        spirv::{BuiltIn, ExecutionMode, ExecutionModel, Id, Instruction, Spirv, StorageClass},
         DescriptorBindingRequirements, ShaderStage, ShaderStages,
     },
     Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, VulkanError, VulkanObject,
@@ -167,6 +173,7 @@ pub struct GraphicsPipeline {
 
     discard_rectangle_state: Option<DiscardRectangleState>,
     conservative_rasterization_state: Option<ConservativeRasterizationState>,
+    robustness: PipelineRobustness,
 
     descriptor_binding_requirements: HashMap<(u32, u32), DescriptorBindingRequirements>,
     num_used_descriptor_sets: u32,
@@ -180,6 +187,7 @@ pub struct GraphicsPipeline {
 impl GraphicsPipeline {
     /// Creates a new `GraphicsPipeline`.
     #[inline]
+    #[track_caller]
     pub fn new(
         device: Arc<Device>,
         cache: Option<Arc<PipelineCache>>,
@@ -187,7 +195,12 @@ impl GraphicsPipeline {
     ) -> Result<Arc<Self>, Validated<VulkanError>> {
         Self::validate_new(&device, cache.as_ref().map(AsRef::as_ref), &create_info)?;
 
-        unsafe { Ok(Self::new_unchecked(device, cache, create_info)?) }
+        let pipeline = unsafe { Self::new_unchecked(device, cache, create_info)? };
+        pipeline
+            .device()
+            .set_auto_debug_utils_object_name(&*pipeline, "GraphicsPipeline");
+
+        Ok(pipeline)
     }
 
     fn validate_new(
@@ -202,6 +215,10 @@ impl GraphicsPipeline {
     }
 
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(flags = ?create_info.flags))
+    )]
     pub unsafe fn new_unchecked(
         device: Arc<Device>,
         cache: Option<Arc<PipelineCache>>,
@@ -227,6 +244,7 @@ impl GraphicsPipeline {
 
             ref discard_rectangle_state,
             ref conservative_rasterization_state,
+            robustness,
             _ne: _,
         } = &create_info;
 
@@ -237,6 +255,9 @@ impl GraphicsPipeline {
             specialization_data_vk: Vec<u8>,
             required_subgroup_size_create_info:
                 Option<ash::vk::PipelineShaderStageRequiredSubgroupSizeCreateInfo<'static>>,
+            robustness_create_info_vk: Option<ash::vk::PipelineRobustnessCreateInfoEXT<'static>>,
+            module_identifier_create_info_vk:
+                Option<ash::vk::PipelineShaderStageModuleIdentifierCreateInfoEXT<'static>>,
         }
 
         let (mut stages_vk, mut per_stage_vk): (SmallVec<[_; 5]>, SmallVec<[_; 5]>) = stages
@@ -246,6 +267,8 @@ impl GraphicsPipeline {
                     flags,
                     ref entry_point,
                     ref required_subgroup_size,
+                    robustness: stage_robustness,
+                    ref module_identifier,
                     _ne: _,
                 } = stage;
 
@@ -281,7 +304,11 @@ impl GraphicsPipeline {
                     ash::vk::PipelineShaderStageCreateInfo {
                         flags: flags.into(),
                         stage: stage.into(),
-                        module: entry_point.module().handle(),
+                        module: if module_identifier.is_some() {
+                            ash::vk::ShaderModule::null()
+                        } else {
+                            entry_point.module().handle()
+                        },
                         p_name: ptr::null(),
                         p_specialization_info: ptr::null(),
                         ..Default::default()
@@ -298,6 +325,19 @@ impl GraphicsPipeline {
                         specialization_map_entries_vk,
                         specialization_data_vk,
                         required_subgroup_size_create_info,
+                        robustness_create_info_vk: (!stage_robustness.is_device_default())
+                            .then(|| stage_robustness.into()),
+                        module_identifier_create_info_vk: module_identifier.as_ref().map(
+                            |identifier| {
+                                let identifier = identifier.as_bytes();
+
+                                ash::vk::PipelineShaderStageModuleIdentifierCreateInfoEXT {
+                                    identifier_size: identifier.len() as u32,
+                                    p_identifier: identifier.as_ptr(),
+                                    ..Default::default()
+                                }
+                            },
+                        ),
                     },
                 )
             })
@@ -311,6 +351,8 @@ impl GraphicsPipeline {
                 specialization_map_entries_vk,
                 specialization_data_vk,
                 required_subgroup_size_create_info,
+                robustness_create_info_vk,
+                module_identifier_create_info_vk,
             },
         ) in stages_vk.iter_mut().zip(per_stage_vk.iter_mut())
         {
@@ -326,6 +368,16 @@ impl GraphicsPipeline {
                 ..*stage_vk
             };
 
+            if let Some(info) = robustness_create_info_vk.as_mut() {
+                info.p_next = stage_vk.p_next;
+                stage_vk.p_next = <*const _>::cast(info);
+            }
+
+            if let Some(info) = module_identifier_create_info_vk.as_mut() {
+                info.p_next = stage_vk.p_next;
+                stage_vk.p_next = <*const _>::cast(info);
+            }
+
             *specialization_info_vk = ash::vk::SpecializationInfo {
                 p_map_entries: specialization_map_entries_vk.as_ptr(),
                 p_data: specialization_data_vk.as_ptr().cast(),
@@ -473,37 +525,51 @@ impl GraphicsPipeline {
         let mut viewport_state_vk = None;
         let mut viewports_vk: SmallVec<[_; 2]> = SmallVec::new();
         let mut scissors_vk: SmallVec<[_; 2]> = SmallVec::new();
+        let mut viewport_depth_clip_control_vk = None;
 
         if let Some(viewport_state) = viewport_state {
-            let ViewportState {
-                viewports,
-                scissors,
+            let &ViewportState {
+                ref viewports,
+                ref scissors,
+                depth_clip_negative_one_to_one,
                 _ne: _,
             } = viewport_state;
 
             viewports_vk.extend(viewports.iter().map(Into::into));
             scissors_vk.extend(scissors.iter().map(Into::into));
 
-            let _ = viewport_state_vk.insert(ash::vk::PipelineViewportStateCreateInfo {
-                flags: ash::vk::PipelineViewportStateCreateFlags::empty(),
-                viewport_count: viewports_vk.len() as u32,
-                p_viewports: if viewports_vk.is_empty() {
-                    ptr::null()
-                } else {
-                    viewports_vk.as_ptr()
-                },
-                scissor_count: scissors_vk.len() as u32,
-                p_scissors: if scissors_vk.is_empty() {
-                    ptr::null()
-                } else {
-                    scissors_vk.as_ptr()
-                },
-                ..Default::default()
-            });
+            let viewport_state_vk =
+                viewport_state_vk.insert(ash::vk::PipelineViewportStateCreateInfo {
+                    flags: ash::vk::PipelineViewportStateCreateFlags::empty(),
+                    viewport_count: viewports_vk.len() as u32,
+                    p_viewports: if viewports_vk.is_empty() {
+                        ptr::null()
+                    } else {
+                        viewports_vk.as_ptr()
+                    },
+                    scissor_count: scissors_vk.len() as u32,
+                    p_scissors: if scissors_vk.is_empty() {
+                        ptr::null()
+                    } else {
+                        scissors_vk.as_ptr()
+                    },
+                    ..Default::default()
+                });
+
+            if depth_clip_negative_one_to_one {
+                let next = viewport_depth_clip_control_vk.insert(
+                    ash::vk::PipelineViewportDepthClipControlCreateInfoEXT {
+                        negative_one_to_one: ash::vk::TRUE,
+                        ..Default::default()
+                    },
+                );
+                viewport_state_vk.p_next = <*const _>::cast(next);
+            }
         }
 
         let mut rasterization_state_vk = None;
         let mut rasterization_line_state_vk = None;
+        let mut rasterization_provoking_vertex_state_vk = None;
 
         if let Some(rasterization_state) = rasterization_state {
             let &RasterizationState {
@@ -516,6 +582,7 @@ impl GraphicsPipeline {
                 line_width,
                 line_rasterization_mode,
                 line_stipple,
+                provoking_vertex_mode,
                 _ne: _,
             } = rasterization_state;
 
@@ -569,6 +636,18 @@ impl GraphicsPipeline {
                         ..Default::default()
                     },
                 );
+                next.p_next = rasterization_state.p_next;
+                rasterization_state.p_next = <*const _>::cast(next);
+            }
+
+            if provoking_vertex_mode != ProvokingVertexMode::FirstVertex {
+                let next = rasterization_provoking_vertex_state_vk.insert(
+                    ash::vk::PipelineRasterizationProvokingVertexStateCreateInfoEXT {
+                        provoking_vertex_mode: provoking_vertex_mode.into(),
+                        ..Default::default()
+                    },
+                );
+                next.p_next = rasterization_state.p_next;
                 rasterization_state.p_next = <*const _>::cast(next);
             }
         }
@@ -684,6 +763,7 @@ impl GraphicsPipeline {
         let mut color_blend_attachments_vk: SmallVec<[_; 4]> = SmallVec::new();
         let mut color_write_vk = None;
         let mut color_write_enables_vk: SmallVec<[_; 4]> = SmallVec::new();
+        let mut color_blend_advanced_vk = None;
 
         if let Some(color_blend_state) = color_blend_state {
             let &ColorBlendState {
@@ -691,6 +771,7 @@ impl GraphicsPipeline {
                 logic_op,
                 ref attachments,
                 blend_constants,
+                ref advanced_blend,
                 _ne: _,
             } = color_blend_state;
 
@@ -750,6 +831,27 @@ impl GraphicsPipeline {
                     p_color_write_enables: color_write_enables_vk.as_ptr(),
                     ..Default::default()
                 });
+                next.p_next = color_blend_state_vk.p_next;
+                color_blend_state_vk.p_next = <*const _>::cast(next);
+            }
+
+            if let Some(advanced_blend) = advanced_blend {
+                let &AdvancedBlendState {
+                    src_premultiplied,
+                    dst_premultiplied,
+                    blend_overlap,
+                    _ne: _,
+                } = advanced_blend;
+
+                let next = color_blend_advanced_vk.insert(
+                    ash::vk::PipelineColorBlendAdvancedStateCreateInfoEXT {
+                        src_premultiplied: src_premultiplied as ash::vk::Bool32,
+                        dst_premultiplied: dst_premultiplied as ash::vk::Bool32,
+                        blend_overlap: blend_overlap.into(),
+                        ..Default::default()
+                    },
+                );
+                next.p_next = color_blend_state_vk.p_next;
                 color_blend_state_vk.p_next = <*const _>::cast(next);
             }
         }
@@ -845,6 +947,18 @@ impl GraphicsPipeline {
             );
         }
 
+        let mut robustness_create_info_vk = (!robustness.is_device_default())
+            .then(|| ash::vk::PipelineRobustnessCreateInfoEXT::from(robustness));
+
+        // When the flags are also given in this structure, the `flags` of the create info are
+        // ignored.
+        let mut flags2_create_info_vk = device.enabled_extensions().khr_maintenance5.then(|| {
+            ash::vk::PipelineCreateFlags2CreateInfoKHR {
+                flags: flags.into(),
+                ..Default::default()
+            }
+        });
+
         /*
             Create
         */
@@ -909,6 +1023,16 @@ impl GraphicsPipeline {
             create_info_vk.p_next = <*const _>::cast(info);
         }
 
+        if let Some(info) = robustness_create_info_vk.as_mut() {
+            info.p_next = create_info_vk.p_next;
+            create_info_vk.p_next = <*const _>::cast(info);
+        }
+
+        if let Some(info) = flags2_create_info_vk.as_mut() {
+            info.p_next = create_info_vk.p_next;
+            create_info_vk.p_next = <*const _>::cast(info);
+        }
+
         if let Some(info) = rendering_create_info_vk.as_mut() {
             info.p_next = create_info_vk.p_next;
             create_info_vk.p_next = <*const _>::cast(info);
@@ -978,6 +1102,7 @@ impl GraphicsPipeline {
 
             discard_rectangle_state,
             conservative_rasterization_state,
+            robustness,
 
             _ne: _,
         } = create_info;
@@ -1084,6 +1209,8 @@ impl GraphicsPipeline {
                 DynamicState::DepthBias,
                 DynamicState::LineWidth,
                 DynamicState::LineStipple,
+                DynamicState::LineRasterizationMode,
+                DynamicState::LineStippleEnable,
             ]);
         }
 
@@ -1111,7 +1238,11 @@ impl GraphicsPipeline {
         }
 
         if discard_rectangle_state.is_some() {
-            fixed_state.extend([DynamicState::DiscardRectangle]);
+            fixed_state.extend([
+                DynamicState::DiscardRectangle,
+                DynamicState::DiscardRectangleEnable,
+                DynamicState::DiscardRectangleMode,
+            ]);
         }
 
         if conservative_rasterization_state.is_some() {
@@ -1145,6 +1276,7 @@ impl GraphicsPipeline {
 
             discard_rectangle_state,
             conservative_rasterization_state,
+            robustness,
 
             descriptor_binding_requirements,
             num_used_descriptor_sets,
@@ -1250,6 +1382,12 @@ impl GraphicsPipeline {
         self.conservative_rasterization_state.as_ref()
     }
 
+    /// Returns the robustness behavior that the pipeline was created with.
+    #[inline]
+    pub fn robustness(&self) -> &PipelineRobustness {
+        &self.robustness
+    }
+
     /// If the pipeline has a fragment shader, returns the fragment tests stages used.
     #[inline]
     pub fn fragment_tests_stages(&self) -> Option<FragmentTestsStages> {
@@ -1447,6 +1585,17 @@ pub struct GraphicsPipelineCreateInfo {
     /// The default value is `None`.
     pub conservative_rasterization_state: Option<ConservativeRasterizationState>,
 
+    /// The robustness behavior of the pipeline.
+    ///
+    /// This can be overridden for individual shader stages with
+    /// [`PipelineShaderStageCreateInfo::robustness`]. If the behavior is not the device default,
+    /// the [`ext_pipeline_robustness`] extension must be enabled on the device.
+    ///
+    /// The default value is the device default behavior.
+    ///
+    /// [`ext_pipeline_robustness`]: crate::device::DeviceExtensions::ext_pipeline_robustness
+    pub robustness: PipelineRobustness,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -1474,6 +1623,7 @@ impl GraphicsPipelineCreateInfo {
 
             discard_rectangle_state: None,
             conservative_rasterization_state: None,
+            robustness: PipelineRobustness::default(),
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -1499,6 +1649,7 @@ impl GraphicsPipelineCreateInfo {
 
             ref discard_rectangle_state,
             ref conservative_rasterization_state,
+            robustness,
             _ne: _,
         } = self;
 
@@ -1507,6 +1658,23 @@ impl GraphicsPipelineCreateInfo {
                 .set_vuids(&["VUID-VkGraphicsPipelineCreateInfo-flags-parameter"])
         })?;
 
+        if flags.intersects(PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED)
+            && !device.enabled_features().pipeline_creation_cache_control
+        {
+            return Err(Box::new(ValidationError {
+                context: "flags".into(),
+                problem: "contains `PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "pipeline_creation_cache_control",
+                )])]),
+                vuids: &["VUID-VkGraphicsPipelineCreateInfo-pipelineCreationCacheControl-02878"],
+            }));
+        }
+
+        robustness
+            .validate(device)
+            .map_err(|err| err.add_context("robustness"))?;
+
         if flags.intersects(PipelineCreateFlags::DERIVATIVE) {
             let base_pipeline = base_pipeline.as_ref().ok_or_else(|| {
                 Box::new(ValidationError {
@@ -1952,9 +2120,25 @@ impl GraphicsPipelineCreateInfo {
                 flags: _,
                 ref entry_point,
                 required_subgroup_size: _vk,
+                robustness: _,
+                ref module_identifier,
                 _ne: _,
             } = stage;
 
+            if module_identifier.is_some()
+                && !flags.intersects(PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED)
+            {
+                return Err(Box::new(ValidationError {
+                    problem: format!(
+                        "`stages[{}].module_identifier` is `Some`, but `flags` does not contain \
+                        `PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED`",
+                        stage_index
+                    )
+                    .into(),
+                    ..Default::default()
+                }));
+            }
+
             let entry_point_info = entry_point.info();
             let execution_model = entry_point_info.execution_model;
 
@@ -2288,6 +2472,7 @@ impl GraphicsPipelineCreateInfo {
             let ViewportState {
                 ref viewports,
                 ref scissors,
+                depth_clip_negative_one_to_one: _,
                 _ne: _,
             } = viewport_state;
 
@@ -2370,6 +2555,7 @@ impl GraphicsPipelineCreateInfo {
                 line_width,
                 line_rasterization_mode: _,
                 line_stipple,
+                provoking_vertex_mode: _,
                 _ne: _,
             } = rasterization_state;
 
@@ -2576,6 +2762,41 @@ impl GraphicsPipelineCreateInfo {
             }
         }
 
+        if let (Some(vertex_stage), None, None, Some(input_assembly_state)) = (
+            vertex_stage,
+            tessellation_evaluation_stage,
+            geometry_stage,
+            input_assembly_state,
+        ) {
+            if !device.enabled_features().maintenance5
+                && input_assembly_state.topology == PrimitiveTopology::PointList
+                && (!dynamic_state.contains(&DynamicState::PrimitiveTopology)
+                    || !device
+                        .physical_device()
+                        .properties()
+                        .dynamic_primitive_topology_unrestricted
+                        .unwrap_or(false))
+                && !vertex_stage
+                    .entry_point
+                    .info()
+                    .output_interface
+                    .iter()
+                    .any(|variable| variable.built_in == Some(BuiltIn::PointSize))
+            {
+                return Err(Box::new(ValidationError {
+                    problem: "`input_assembly_state.topology` is `PrimitiveTopology::PointList`, \
+                        and the vertex shader in `stages` does not write to the `PointSize` \
+                        built-in"
+                        .into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "maintenance5",
+                    )])]),
+                    vuids: &["VUID-VkGraphicsPipelineCreateInfo-topology-08773"],
+                    ..Default::default()
+                }));
+            }
+        }
+
         if let Some(conservative_rasterization_state) = conservative_rasterization_state {
             let properties = device.physical_device().properties();
 
@@ -2706,6 +2927,78 @@ impl GraphicsPipelineCreateInfo {
             // VUID-VkGraphicsPipelineCreateInfo-renderPass-06061
         }
 
+        if let Some(fragment_stage) = fragment_stage {
+            let spirv = fragment_stage.entry_point.module().spirv();
+            let entry_point_function = spirv.function(fragment_stage.entry_point.id());
+            let tile_image_reads = TileImageReads::new(spirv, fragment_stage.entry_point.id());
+
+            if tile_image_reads.any()
+                && matches!(subpass, Some(PipelineSubpassType::BeginRenderPass(_)))
+            {
+                return Err(Box::new(ValidationError {
+                    problem: "the fragment shader in `stages` reads from a tile image, but \
+                        `subpass` is `PipelineSubpassType::BeginRenderPass`"
+                        .into(),
+                    vuids: &["VUID-VkGraphicsPipelineCreateInfo-renderPass-08710"],
+                    ..Default::default()
+                }));
+            }
+
+            let early_fragment_tests =
+                entry_point_function
+                    .execution_modes()
+                    .iter()
+                    .any(|instruction| {
+                        matches!(
+                            instruction,
+                            Instruction::ExecutionMode {
+                                mode: ExecutionMode::EarlyFragmentTests,
+                                ..
+                            }
+                        )
+                    });
+
+            if let (true, Some(depth_stencil_state)) = (early_fragment_tests, depth_stencil_state) {
+                if tile_image_reads.depth
+                    && !dynamic_state.contains(&DynamicState::DepthWriteEnable)
+                    && depth_stencil_state
+                        .depth
+                        .as_ref()
+                        .map_or(false, |depth_state| depth_state.write_enable)
+                {
+                    return Err(Box::new(ValidationError {
+                        problem: "the fragment shader in `stages` uses early fragment tests and \
+                            reads the depth aspect from a tile image, but \
+                            `depth_stencil_state.depth.write_enable` is `true`"
+                            .into(),
+                        vuids: &["VUID-VkGraphicsPipelineCreateInfo-pStages-08711"],
+                        ..Default::default()
+                    }));
+                }
+
+                if tile_image_reads.stencil
+                    && !dynamic_state.contains(&DynamicState::StencilWriteMask)
+                    && depth_stencil_state
+                        .stencil
+                        .as_ref()
+                        .map_or(false, |stencil_state| {
+                            stencil_state.front.write_mask != 0
+                                || stencil_state.back.write_mask != 0
+                        })
+                {
+                    return Err(Box::new(ValidationError {
+                        problem: "the fragment shader in `stages` uses early fragment tests and \
+                            reads the stencil aspect from a tile image, but \
+                            `depth_stencil_state.stencil.front.write_mask` or \
+                            `depth_stencil_state.stencil.back.write_mask` is not 0"
+                            .into(),
+                        vuids: &["VUID-VkGraphicsPipelineCreateInfo-pStages-08712"],
+                        ..Default::default()
+                    }));
+                }
+            }
+        }
+
         if let (Some(input_assembly_state), Some(_)) = (input_assembly_state, tessellation_state) {
             if input_assembly_state.topology != PrimitiveTopology::PatchList {
                 return Err(Box::new(ValidationError {
@@ -3052,3 +3345,46 @@ pub enum FragmentTestsStages {
     Late,
     EarlyAndLate,
 }
+
+/// The aspects of the attachments that a fragment shader reads directly from tile images.
+#[derive(Clone, Copy, Debug, Default)]
+struct TileImageReads {
+    color: bool,
+    depth: bool,
+    stencil: bool,
+}
+
+impl TileImageReads {
+    /// Finds the tile image reads of the entry point `entry_point`, including the reads in the
+    /// functions that it calls.
+    fn new(spirv: &Spirv, entry_point: Id) -> Self {
+        let mut reads = TileImageReads::default();
+        let mut visited = HashSet::default();
+        let mut pending = vec![entry_point];
+
+        while let Some(function_id) = pending.pop() {
+            if !visited.insert(function_id) {
+                continue;
+            }
+
+            let function = spirv.function(function_id);
+
+            for instruction in function.instructions() {
+                match instruction {
+                    Instruction::ColorAttachmentReadEXT { .. } => reads.color = true,
+                    Instruction::DepthAttachmentReadEXT { .. } => reads.depth = true,
+                    Instruction::StencilAttachmentReadEXT { .. } => reads.stencil = true,
+                    _ => (),
+                }
+            }
+
+            pending.extend(function.called_functions().iter().copied());
+        }
+
+        reads
+    }
+
+    fn any(self) -> bool {
+        self.color || self.depth || self.stencil
+    }
+}
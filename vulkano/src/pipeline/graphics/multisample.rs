@@ -9,6 +9,8 @@ use crate::{
 
 /// State of the multisampling.
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct MultisampleState {
     /// The number of rasterization samples to take per pixel. The GPU will pick this many
     /// different locations within each pixel and assign to each of these locations a different
@@ -56,6 +58,7 @@ pub struct MultisampleState {
     /// The default value is `false`.
     pub alpha_to_one_enable: bool,
 
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _ne: crate::NonExhaustive,
 }
 
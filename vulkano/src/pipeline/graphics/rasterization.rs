@@ -6,6 +6,8 @@ use crate::{
 
 /// The state in a graphics pipeline describing how the rasterization stage should behave.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct RasterizationState {
     /// If true, then the depth value of the vertices will be clamped to the range [0.0, 1.0]. If
     /// false, fragments whose depth is outside of this range will be discarded.
@@ -78,6 +80,19 @@ pub struct RasterizationState {
     /// The default value is `None`.
     pub line_stipple: Option<LineStipple>,
 
+    /// Which vertex of a primitive is used as the provoking vertex, whose values are used for
+    /// flat-shaded fragment shader inputs.
+    ///
+    /// If this is not set to `FirstVertex`, the
+    /// [`ext_provoking_vertex`](crate::device::DeviceExtensions::ext_provoking_vertex)
+    /// extension and the
+    /// [`provoking_vertex_last`](crate::device::DeviceFeatures::provoking_vertex_last)
+    /// feature must be enabled on the device.
+    ///
+    /// The default value is [`ProvokingVertexMode::FirstVertex`].
+    pub provoking_vertex_mode: ProvokingVertexMode,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _ne: crate::NonExhaustive,
 }
 
@@ -94,6 +109,7 @@ impl Default for RasterizationState {
             line_width: 1.0,
             line_rasterization_mode: Default::default(),
             line_stipple: None,
+            provoking_vertex_mode: Default::default(),
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -144,6 +160,7 @@ impl RasterizationState {
             line_width: _,
             line_rasterization_mode,
             ref line_stipple,
+            provoking_vertex_mode,
             _ne: _,
         } = self;
 
@@ -158,6 +175,10 @@ impl RasterizationState {
             .validate_device(device)
             .map_err(|err| err.add_context("line_rasterization_mode").set_vuids(&["VUID-VkPipelineRasterizationLineStateCreateInfoEXT-lineRasterizationMode-parameter"]))?;
 
+        provoking_vertex_mode
+            .validate_device(device)
+            .map_err(|err| err.add_context("provoking_vertex_mode").set_vuids(&["VUID-VkPipelineRasterizationProvokingVertexStateCreateInfoEXT-provokingVertexMode-parameter"]))?;
+
         if depth_clamp_enable && !device.enabled_features().depth_clamp {
             return Err(Box::new(ValidationError {
                 context: "depth_clamp_enable".into(),
@@ -344,12 +365,29 @@ impl RasterizationState {
             }
         }
 
+        if provoking_vertex_mode == ProvokingVertexMode::LastVertex
+            && !device.enabled_features().provoking_vertex_last
+        {
+            return Err(Box::new(ValidationError {
+                context: "provoking_vertex_mode".into(),
+                problem: "is `ProvokingVertexMode::LastVertex`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "provoking_vertex_last",
+                )])]),
+                vuids: &[
+                    "VUID-VkPipelineRasterizationProvokingVertexStateCreateInfoEXT-provokingVertexMode-04883",
+                ],
+            }));
+        }
+
         Ok(())
     }
 }
 
 /// The values to use for depth biasing.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct DepthBiasState {
     /// Specifies a constant factor to be multiplied to every depth value.
     ///
@@ -519,6 +557,7 @@ impl Default for LineRasterizationMode {
 
 /// The parameters of a stippled line.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineStipple {
     /// The repeat factor used in stippled line rasterization. Must be between 1 and 256 inclusive.
     pub factor: u32,
@@ -526,3 +565,41 @@ pub struct LineStipple {
     /// The bit pattern used in stippled line rasterization.
     pub pattern: u16,
 }
+
+vulkan_enum! {
+    #[non_exhaustive]
+
+    /// Which vertex of a primitive is the provoking vertex.
+    ///
+    /// The provoking vertex supplies the values of fragment shader inputs that are decorated
+    /// with `flat`, and of the primitive's `PrimitiveShadingRateKHR` output. Vulkan uses the
+    /// first vertex by default, while OpenGL uses the last vertex.
+    ///
+    /// When transform feedback is active, the order of the captured vertices only preserves the
+    /// provoking vertex if the
+    /// [`transform_feedback_preserves_provoking_vertex`](crate::device::DeviceFeatures::transform_feedback_preserves_provoking_vertex)
+    /// feature is enabled. For triangle fans, the
+    /// [`transform_feedback_preserves_triangle_fan_provoking_vertex`](crate::device::DeviceProperties::transform_feedback_preserves_triangle_fan_provoking_vertex)
+    /// property must additionally be `true`.
+    ProvokingVertexMode = ProvokingVertexModeEXT(i32);
+
+    /// The first vertex of each primitive is the provoking vertex.
+    FirstVertex = FIRST_VERTEX,
+
+    /// The last vertex of each primitive is the provoking vertex.
+    ///
+    /// The [`provoking_vertex_last`](crate::device::DeviceFeatures::provoking_vertex_last)
+    /// feature must be enabled on the device.
+    LastVertex = LAST_VERTEX
+    RequiresOneOf([
+        RequiresAllOf([DeviceExtension(ext_provoking_vertex)]),
+    ]),
+}
+
+impl Default for ProvokingVertexMode {
+    /// Returns `ProvokingVertexMode::FirstVertex`.
+    #[inline]
+    fn default() -> Self {
+        Self::FirstVertex
+    }
+}
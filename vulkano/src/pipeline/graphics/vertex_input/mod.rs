@@ -114,6 +114,8 @@ mod vertex;
 
 /// The state in a graphics pipeline describing how the vertex input stage should behave.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct VertexInputState {
     /// A description of the vertex buffers that the vertex input stage will read from.
     pub bindings: HashMap<u32, VertexInputBindingDescription>,
@@ -122,6 +124,7 @@ pub struct VertexInputState {
     /// and the components of that location in the shader.
     pub attributes: HashMap<u32, VertexInputAttributeDescription>,
 
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _ne: crate::NonExhaustive,
 }
 
@@ -454,6 +457,8 @@ impl Default for VertexInputState {
 
 /// Describes a single vertex buffer binding.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct VertexInputBindingDescription {
     /// The number of bytes from the start of one element in the vertex buffer to the start of the
     /// next element. This can be simply the size of the data in each element, but larger strides
@@ -467,6 +472,7 @@ pub struct VertexInputBindingDescription {
     /// The default value is [`VertexInputRate::Vertex`].
     pub input_rate: VertexInputRate,
 
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _ne: crate::NonExhaustive,
 }
 
@@ -583,6 +589,8 @@ impl VertexInputBindingDescription {
 
 /// Describes a single vertex buffer attribute mapping.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct VertexInputAttributeDescription {
     /// The vertex buffer binding number that this attribute should take its data from.
     ///
@@ -606,6 +614,7 @@ pub struct VertexInputAttributeDescription {
     /// The default value is `0`.
     pub offset: u32,
 
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _ne: crate::NonExhaustive,
 }
 
@@ -689,6 +698,7 @@ impl VertexInputAttributeDescription {
 
 /// How the vertex source should be unrolled.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VertexInputRate {
     /// Each element of the source corresponds to a vertex.
     Vertex,
@@ -81,6 +81,20 @@ pub struct ViewportState {
     /// [`DynamicState::ScissorWithCount`]: crate::pipeline::DynamicState::ScissorWithCount
     pub scissors: SmallVec<[Scissor; 1]>,
 
+    /// If true, the depth range of clip space is -1.0 to 1.0, as in OpenGL. If false, it is 0.0
+    /// to 1.0, the Vulkan default.
+    ///
+    /// With the OpenGL convention, a clip space depth `z` is mapped to a framebuffer depth of
+    /// `(z + 1.0) / 2.0` before the viewport's depth range is applied. This allows projection
+    /// matrices written for OpenGL to be used unmodified.
+    ///
+    /// If set to `true`, the
+    /// [`depth_clip_control`](crate::device::DeviceFeatures::depth_clip_control) feature must be
+    /// enabled on the device.
+    ///
+    /// The default value is `false`.
+    pub depth_clip_negative_one_to_one: bool,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -90,6 +104,7 @@ impl Default for ViewportState {
         Self {
             viewports: smallvec![Viewport::default()],
             scissors: smallvec![Scissor::default()],
+            depth_clip_negative_one_to_one: false,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -103,6 +118,7 @@ impl ViewportState {
         Self {
             viewports: SmallVec::new(),
             scissors: SmallVec::new(),
+            depth_clip_negative_one_to_one: false,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -116,6 +132,7 @@ impl ViewportState {
         Self {
             viewports,
             scissors,
+            depth_clip_negative_one_to_one: false,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -129,14 +146,16 @@ impl ViewportState {
         Self {
             viewports,
             scissors,
+            depth_clip_negative_one_to_one: false,
             _ne: crate::NonExhaustive(()),
         }
     }
 
     pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
-        let Self {
-            viewports,
-            scissors,
+        let &Self {
+            ref viewports,
+            ref scissors,
+            depth_clip_negative_one_to_one,
             _ne: _,
         } = self;
 
@@ -223,6 +242,19 @@ impl ViewportState {
             }));
         }
 
+        if depth_clip_negative_one_to_one && !device.enabled_features().depth_clip_control {
+            return Err(Box::new(ValidationError {
+                context: "depth_clip_negative_one_to_one".into(),
+                problem: "is `true`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "depth_clip_control",
+                )])]),
+                vuids: &[
+                    "VUID-VkPipelineViewportDepthClipControlCreateInfoEXT-negativeOneToOne-06470",
+                ],
+            }));
+        }
+
         Ok(())
     }
 }
@@ -97,13 +97,19 @@ pub struct PipelineLayout {
 
 impl PipelineLayout {
     /// Creates a new `PipelineLayout`.
+    #[track_caller]
     pub fn new(
         device: Arc<Device>,
         create_info: PipelineLayoutCreateInfo,
     ) -> Result<Arc<PipelineLayout>, Validated<VulkanError>> {
         Self::validate_new(&device, &create_info)?;
 
-        unsafe { Ok(Self::new_unchecked(device, create_info)?) }
+        let pipeline_layout = unsafe { Self::new_unchecked(device, create_info)? };
+        pipeline_layout
+            .device()
+            .set_auto_debug_utils_object_name(&*pipeline_layout, "PipelineLayout");
+
+        Ok(pipeline_layout)
     }
 
     fn validate_new(
@@ -122,6 +128,19 @@ impl PipelineLayout {
     pub unsafe fn new_unchecked(
         device: Arc<Device>,
         create_info: PipelineLayoutCreateInfo,
+    ) -> Result<Arc<PipelineLayout>, VulkanError> {
+        if let Some(layout_cache) = device.layout_cache() {
+            return layout_cache.pipeline_layout(create_info, |create_info| {
+                Self::create(device.clone(), create_info)
+            });
+        }
+
+        Self::create(device, create_info)
+    }
+
+    unsafe fn create(
+        device: Arc<Device>,
+        create_info: PipelineLayoutCreateInfo,
     ) -> Result<Arc<PipelineLayout>, VulkanError> {
         let &PipelineLayoutCreateInfo {
             flags,
@@ -923,7 +942,7 @@ vulkan_bitflags! {
 }
 
 /// Description of a range of the push constants of a pipeline layout.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct PushConstantRange {
     /// The stages which can access this range. A stage can access at most one push constant range.
     ///
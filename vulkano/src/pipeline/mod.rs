@@ -12,9 +12,13 @@ pub use self::{
     compute::ComputePipeline, graphics::GraphicsPipeline, layout::PipelineLayout, shader::*,
 };
 use crate::{
-    device::DeviceOwned,
+    device::{
+        physical::{PipelineRobustnessBufferBehavior, PipelineRobustnessImageBehavior},
+        Device, DeviceOwned,
+    },
     macros::{vulkan_bitflags, vulkan_enum},
     shader::DescriptorBindingRequirements,
+    Requires, RequiresAllOf, RequiresOneOf, ValidationError,
 };
 use ahash::HashMap;
 use std::sync::Arc;
@@ -107,13 +111,21 @@ vulkan_bitflags! {
         RequiresAllOf([DeviceExtension(khr_device_group)]),
     ]),*/
 
-    /* TODO: enable
-    // TODO: document
+    /// If the pipeline can't be created from the pipeline cache, and would need to be compiled,
+    /// then creating the pipeline fails with [`VulkanError::PipelineCompileRequired`] instead.
+    ///
+    /// This must be set when a shader stage is given a [`module_identifier`].
+    ///
+    /// The [`pipeline_creation_cache_control`] feature must be enabled on the device.
+    ///
+    /// [`VulkanError::PipelineCompileRequired`]: crate::VulkanError::PipelineCompileRequired
+    /// [`module_identifier`]: PipelineShaderStageCreateInfo::module_identifier
+    /// [`pipeline_creation_cache_control`]: crate::device::DeviceFeatures::pipeline_creation_cache_control
     FAIL_ON_PIPELINE_COMPILE_REQUIRED = FAIL_ON_PIPELINE_COMPILE_REQUIRED
     RequiresOneOf([
         RequiresAllOf([APIVersion(V1_3)]),
         RequiresAllOf([DeviceExtension(ext_pipeline_creation_cache_control)]),
-    ]),*/
+    ]),
 
     /* TODO: enable
     // TODO: document
@@ -289,6 +301,13 @@ vulkan_bitflags! {
     ]),*/
 }
 
+impl From<PipelineCreateFlags> for ash::vk::PipelineCreateFlags2KHR {
+    #[inline]
+    fn from(val: PipelineCreateFlags) -> Self {
+        Self::from_raw(ash::vk::PipelineCreateFlags::from(val).as_raw().into())
+    }
+}
+
 vulkan_enum! {
     #[non_exhaustive]
 
@@ -553,6 +572,27 @@ vulkan_enum! {
         RequiresAllOf([DeviceExtension(ext_discard_rectangles)]),
     ]),
 
+    /// Whether the discard rectangle test is enabled. The test is enabled if
+    /// [`GraphicsPipelineCreateInfo::discard_rectangle_state`](crate::pipeline::graphics::GraphicsPipelineCreateInfo::discard_rectangle_state)
+    /// is `Some`.
+    ///
+    /// Set with
+    /// [`set_discard_rectangle_enable`](crate::command_buffer::RecordingCommandBuffer::set_discard_rectangle_enable).
+    DiscardRectangleEnable = DISCARD_RECTANGLE_ENABLE_EXT
+    RequiresOneOf([
+        RequiresAllOf([DeviceExtension(ext_discard_rectangles)]),
+    ]),
+
+    /// The value of
+    /// [`DiscardRectangleState::mode`](crate::pipeline::graphics::discard_rectangle::DiscardRectangleState::mode).
+    ///
+    /// Set with
+    /// [`set_discard_rectangle_mode`](crate::command_buffer::RecordingCommandBuffer::set_discard_rectangle_mode).
+    DiscardRectangleMode = DISCARD_RECTANGLE_MODE_EXT
+    RequiresOneOf([
+        RequiresAllOf([DeviceExtension(ext_discard_rectangles)]),
+    ]),
+
     /* TODO: enable
     // TODO: document
     SampleLocations = SAMPLE_LOCATIONS_EXT
@@ -778,19 +818,26 @@ vulkan_enum! {
         RequiresAllOf([DeviceExtension(ext_extended_dynamic_state3)]),
     ]), */
 
-    /* TODO: enable
-    // TODO: document
+    /// The value of
+    /// [`RasterizationState::line_rasterization_mode`](crate::pipeline::graphics::rasterization::RasterizationState::line_rasterization_mode).
+    ///
+    /// Set with
+    /// [`set_line_rasterization_mode`](crate::command_buffer::RecordingCommandBuffer::set_line_rasterization_mode).
     LineRasterizationMode = LINE_RASTERIZATION_MODE_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_extended_dynamic_state3)]),
-    ]), */
+    ]),
 
-    /* TODO: enable
-    // TODO: document
+    /// Whether
+    /// [`RasterizationState::line_stipple`](crate::pipeline::graphics::rasterization::RasterizationState::line_stipple)
+    /// is enabled.
+    ///
+    /// Set with
+    /// [`set_line_stipple_enable`](crate::command_buffer::RecordingCommandBuffer::set_line_stipple_enable).
     LineStippleEnable = LINE_STIPPLE_ENABLE_EXT
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_extended_dynamic_state3)]),
-    ]), */
+    ]),
 
     /* TODO: enable
     // TODO: document
@@ -869,3 +916,191 @@ vulkan_enum! {
         RequiresAllOf([DeviceExtension(ext_extended_dynamic_state3)]),
     ]), */
 }
+
+/// The robustness behavior of a pipeline or shader stage, which determines what happens when a
+/// shader accesses a resource out of bounds.
+///
+/// Robust behavior normally has to be enabled for the whole device with the
+/// [`robust_buffer_access`], [`robust_image_access`] or robustness2 features, which makes all
+/// pipelines pay for it. With this, robustness can instead be requested only for the pipelines or
+/// shader stages that need it, such as ones that run untrusted shaders, while other pipelines
+/// keep the [device default] behavior.
+///
+/// [`robust_buffer_access`]: crate::device::DeviceFeatures::robust_buffer_access
+/// [`robust_image_access`]: crate::device::DeviceFeatures::robust_image_access
+/// [device default]: PipelineRobustnessBufferBehavior::DeviceDefault
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PipelineRobustness {
+    /// The behavior of out-of-bounds accesses to storage buffers, storage texel buffers and
+    /// physical storage buffers.
+    ///
+    /// The default value is [`PipelineRobustnessBufferBehavior::DeviceDefault`].
+    pub storage_buffers: PipelineRobustnessBufferBehavior,
+
+    /// The behavior of out-of-bounds accesses to uniform buffers and uniform texel buffers.
+    ///
+    /// The default value is [`PipelineRobustnessBufferBehavior::DeviceDefault`].
+    pub uniform_buffers: PipelineRobustnessBufferBehavior,
+
+    /// The behavior of out-of-bounds accesses to vertex input attributes.
+    ///
+    /// The default value is [`PipelineRobustnessBufferBehavior::DeviceDefault`].
+    pub vertex_inputs: PipelineRobustnessBufferBehavior,
+
+    /// The behavior of out-of-bounds accesses to images.
+    ///
+    /// The default value is [`PipelineRobustnessImageBehavior::DeviceDefault`].
+    pub images: PipelineRobustnessImageBehavior,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for PipelineRobustness {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            storage_buffers: PipelineRobustnessBufferBehavior::DeviceDefault,
+            uniform_buffers: PipelineRobustnessBufferBehavior::DeviceDefault,
+            vertex_inputs: PipelineRobustnessBufferBehavior::DeviceDefault,
+            images: PipelineRobustnessImageBehavior::DeviceDefault,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+impl PipelineRobustness {
+    /// Returns whether all the behaviors are the device default.
+    #[inline]
+    pub fn is_device_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
+        let &Self {
+            storage_buffers,
+            uniform_buffers,
+            vertex_inputs,
+            images,
+            _ne: _,
+        } = self;
+
+        if self.is_device_default() {
+            return Ok(());
+        }
+
+        if !device.enabled_extensions().ext_pipeline_robustness {
+            return Err(Box::new(ValidationError {
+                problem: "is not the device default".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                    "ext_pipeline_robustness",
+                )])]),
+                ..Default::default()
+            }));
+        }
+
+        storage_buffers.validate_device(device).map_err(|err| {
+            err.add_context("storage_buffers")
+                .set_vuids(&["VUID-VkPipelineRobustnessCreateInfoEXT-storageBuffers-parameter"])
+        })?;
+
+        uniform_buffers.validate_device(device).map_err(|err| {
+            err.add_context("uniform_buffers")
+                .set_vuids(&["VUID-VkPipelineRobustnessCreateInfoEXT-uniformBuffers-parameter"])
+        })?;
+
+        vertex_inputs.validate_device(device).map_err(|err| {
+            err.add_context("vertex_inputs")
+                .set_vuids(&["VUID-VkPipelineRobustnessCreateInfoEXT-vertexInputs-parameter"])
+        })?;
+
+        images.validate_device(device).map_err(|err| {
+            err.add_context("images")
+                .set_vuids(&["VUID-VkPipelineRobustnessCreateInfoEXT-images-parameter"])
+        })?;
+
+        if !device.enabled_features().pipeline_robustness {
+            if storage_buffers != PipelineRobustnessBufferBehavior::DeviceDefault {
+                return Err(Box::new(ValidationError {
+                    context: "storage_buffers".into(),
+                    problem: "is not `PipelineRobustnessBufferBehavior::DeviceDefault`".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "pipeline_robustness",
+                    )])]),
+                    vuids: &["VUID-VkPipelineRobustnessCreateInfoEXT-pipelineRobustness-06926"],
+                }));
+            }
+
+            if uniform_buffers != PipelineRobustnessBufferBehavior::DeviceDefault {
+                return Err(Box::new(ValidationError {
+                    context: "uniform_buffers".into(),
+                    problem: "is not `PipelineRobustnessBufferBehavior::DeviceDefault`".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "pipeline_robustness",
+                    )])]),
+                    vuids: &["VUID-VkPipelineRobustnessCreateInfoEXT-pipelineRobustness-06927"],
+                }));
+            }
+
+            if vertex_inputs != PipelineRobustnessBufferBehavior::DeviceDefault {
+                return Err(Box::new(ValidationError {
+                    context: "vertex_inputs".into(),
+                    problem: "is not `PipelineRobustnessBufferBehavior::DeviceDefault`".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "pipeline_robustness",
+                    )])]),
+                    vuids: &["VUID-VkPipelineRobustnessCreateInfoEXT-pipelineRobustness-06928"],
+                }));
+            }
+
+            if images != PipelineRobustnessImageBehavior::DeviceDefault {
+                return Err(Box::new(ValidationError {
+                    context: "images".into(),
+                    problem: "is not `PipelineRobustnessImageBehavior::DeviceDefault`".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                        "pipeline_robustness",
+                    )])]),
+                    vuids: &["VUID-VkPipelineRobustnessCreateInfoEXT-pipelineRobustness-06929"],
+                }));
+            }
+        }
+
+        if images == PipelineRobustnessImageBehavior::RobustImageAccess
+            && !device
+                .physical_device()
+                .supported_features()
+                .robust_image_access
+        {
+            return Err(Box::new(ValidationError {
+                context: "images".into(),
+                problem: "is `PipelineRobustnessImageBehavior::RobustImageAccess`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "robust_image_access",
+                )])]),
+                vuids: &["VUID-VkPipelineRobustnessCreateInfoEXT-robustImageAccess-06930"],
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+impl From<PipelineRobustness> for ash::vk::PipelineRobustnessCreateInfoEXT<'static> {
+    #[inline]
+    fn from(val: PipelineRobustness) -> Self {
+        let PipelineRobustness {
+            storage_buffers,
+            uniform_buffers,
+            vertex_inputs,
+            images,
+            _ne: _,
+        } = val;
+
+        Self {
+            storage_buffers: storage_buffers.into(),
+            uniform_buffers: uniform_buffers.into(),
+            vertex_inputs: vertex_inputs.into(),
+            images: images.into(),
+            ..Default::default()
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use crate::{
     device::Device,
     macros::vulkan_bitflags,
+    pipeline::PipelineRobustness,
     shader::{
-        spirv::{BuiltIn, Decoration, ExecutionMode, Id, Instruction},
-        EntryPoint, ShaderStage,
+        spirv::{
+            BuiltIn, Decoration, ExecutionMode, ExecutionModel, Id, Instruction, StorageClass,
+        },
+        EntryPoint, ShaderModuleIdentifier, ShaderStage,
     },
     Requires, RequiresAllOf, RequiresOneOf, ValidationError,
 };
@@ -39,6 +42,38 @@ pub struct PipelineShaderStageCreateInfo {
     /// The default value is None.
     pub required_subgroup_size: Option<u32>,
 
+    /// The robustness behavior of this shader stage.
+    ///
+    /// This overrides the robustness behavior that is specified for the pipeline as a whole.
+    /// If the behavior is not the device default, the [`ext_pipeline_robustness`] extension must
+    /// be enabled on the device.
+    ///
+    /// The default value is the device default behavior.
+    ///
+    /// [`ext_pipeline_robustness`]: crate::device::DeviceExtensions::ext_pipeline_robustness
+    pub robustness: PipelineRobustness,
+
+    /// The identifier of the shader module of `entry_point`.
+    ///
+    /// If this is `Some`, the pipeline is created from the identifier, rather than from the shader
+    /// module itself. This only succeeds if the implementation finds the pipeline in its pipeline
+    /// cache, so the pipeline must be created with
+    /// [`PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED`], and creation fails with
+    /// [`VulkanError::PipelineCompileRequired`] otherwise. In that case, the pipeline should be
+    /// created again without the identifier.
+    ///
+    /// `entry_point` is still used by vulkano to validate the pipeline and to determine its
+    /// interface, so the shader module must still be available.
+    ///
+    /// If this is `Some`, the [`shader_module_identifier`] feature must be enabled on the device.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED`]: crate::pipeline::PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED
+    /// [`VulkanError::PipelineCompileRequired`]: crate::VulkanError::PipelineCompileRequired
+    /// [`shader_module_identifier`]: crate::device::DeviceFeatures::shader_module_identifier
+    pub module_identifier: Option<ShaderModuleIdentifier>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -50,6 +85,8 @@ impl PipelineShaderStageCreateInfo {
             flags: PipelineShaderStageCreateFlags::empty(),
             entry_point,
             required_subgroup_size: None,
+            robustness: PipelineRobustness::default(),
+            module_identifier: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -59,6 +96,8 @@ impl PipelineShaderStageCreateInfo {
             flags,
             ref entry_point,
             required_subgroup_size,
+            robustness,
+            module_identifier,
             _ne: _,
         } = self;
 
@@ -172,6 +211,10 @@ impl PipelineShaderStageCreateInfo {
 
         let mut clip_distance_array_size = 0;
         let mut cull_distance_array_size = 0;
+        let entry_point_interface = match spirv.function(entry_point.id()).entry_point() {
+            Some(Instruction::EntryPoint { interface, .. }) => interface.as_slice(),
+            _ => &[],
+        };
 
         for instruction in spirv.decorations() {
             if let Instruction::Decorate {
@@ -254,6 +297,40 @@ impl PipelineShaderStageCreateInfo {
                             }));
                         }
                     }
+                    BuiltIn::FragStencilRefEXT => {
+                        // The module may contain other entry points that don't use the variable.
+                        if !entry_point_interface.contains(&target) {
+                            continue;
+                        }
+
+                        if execution_model != ExecutionModel::Fragment {
+                            return Err(Box::new(ValidationError {
+                                context: "entry_point".into(),
+                                problem: "the `FragStencilRefEXT` built-in variable is used, but \
+                                    the execution model of the entry point is not `Fragment`"
+                                    .into(),
+                                vuids: &["VUID-FragStencilRefEXT-FragStencilRefEXT-04223"],
+                                ..Default::default()
+                            }));
+                        }
+
+                        if !matches!(
+                            *spirv.id(target).instruction(),
+                            Instruction::Variable {
+                                storage_class: StorageClass::Output,
+                                ..
+                            }
+                        ) {
+                            return Err(Box::new(ValidationError {
+                                context: "entry_point".into(),
+                                problem: "the `FragStencilRefEXT` built-in variable is not \
+                                    declared with the `Output` storage class"
+                                    .into(),
+                                vuids: &["VUID-FragStencilRefEXT-FragStencilRefEXT-04224"],
+                                ..Default::default()
+                            }));
+                        }
+                    }
                     _ => (),
                 }
             }
@@ -489,6 +566,21 @@ impl PipelineShaderStageCreateInfo {
             }
         }
 
+        robustness
+            .validate(device)
+            .map_err(|err| err.add_context("robustness"))?;
+
+        if module_identifier.is_some() && !device.enabled_features().shader_module_identifier {
+            return Err(Box::new(ValidationError {
+                context: "module_identifier".into(),
+                problem: "is `Some`".into(),
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "shader_module_identifier",
+                )])]),
+                vuids: &["VUID-VkPipelineShaderStageModuleIdentifierCreateInfoEXT-pNext-06850"],
+            }));
+        }
+
         // TODO:
         // VUID-VkPipelineShaderStageCreateInfo-module-08987
 
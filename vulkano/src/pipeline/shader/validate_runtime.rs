@@ -1053,6 +1053,10 @@ impl<'a> RuntimeValidator<'a> {
         // Only GLCompute, TaskEXT and MeshEXT.
         let mut workgroup_memory_size = 0;
 
+        // With `WorkgroupMemoryExplicitLayoutKHR`, all variables whose type is decorated with
+        // `Block` alias each other, so only the largest of them counts towards the total.
+        let mut workgroup_block_memory_size = 0;
+
         let mut stream_sizes: HashMap<u32, HashMap<u32, DeviceSize>> = HashMap::default();
 
         for instruction in self.spirv.global_variables() {
@@ -1148,8 +1152,25 @@ impl<'a> RuntimeValidator<'a> {
                             | ExecutionModel::TaskEXT
                             | ExecutionModel::MeshEXT
                     ) {
+                        let is_explicit_block =
+                            self.spirv
+                                .id(type_id)
+                                .decorations()
+                                .iter()
+                                .any(|instruction| {
+                                    matches!(
+                                        instruction,
+                                        Instruction::Decorate {
+                                            decoration: Decoration::Block,
+                                            ..
+                                        }
+                                    )
+                                });
+
                         if let Some(size) = size_of_type(self.spirv, type_id) {
-                            if has_aliased {
+                            if is_explicit_block {
+                                workgroup_block_memory_size = workgroup_block_memory_size.max(size);
+                            } else if has_aliased {
                                 workgroup_memory_size = workgroup_memory_size.max(size);
                             } else {
                                 workgroup_memory_size += size;
@@ -1671,6 +1692,8 @@ impl<'a> RuntimeValidator<'a> {
             }
         }
 
+        workgroup_memory_size += workgroup_block_memory_size;
+
         match self.execution_model {
             ExecutionModel::Vertex => {
                 if input_locations_required > properties.max_vertex_input_attributes {
@@ -2265,6 +2288,31 @@ impl<'a> RuntimeValidator<'a> {
                             }
                             _ => (),
                         }
+
+                        // The checks above only require some floating point atomic feature to be
+                        // enabled for the storage class. The operation itself is only supported
+                        // if the feature for its specific kind and width is enabled.
+                        if let Some((operation, feature, enabled)) = float_atomic_feature(
+                            self.device.enabled_features(),
+                            instruction,
+                            storage_class,
+                            width,
+                        ) {
+                            if !enabled {
+                                return Err(Box::new(ValidationError {
+                                    problem: format!(
+                                        "{} is performed on a {}-bit floating point value \
+                                        with a storage class of `StorageClass::{:?}`",
+                                        operation, width, storage_class,
+                                    )
+                                    .into(),
+                                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[
+                                        Requires::DeviceFeature(feature),
+                                    ])]),
+                                    ..Default::default()
+                                }));
+                            }
+                        }
                     }
                     _ => (),
                 }
@@ -2908,6 +2956,120 @@ impl<'a> RuntimeValidator<'a> {
     }
 }
 
+/// Returns a description of the floating point atomic operation `instruction`, performed on a
+/// `width`-bit value through a pointer with the given storage class, along with the device
+/// feature that it requires and whether that feature is enabled.
+fn float_atomic_feature(
+    features: &DeviceFeatures,
+    instruction: &Instruction,
+    storage_class: StorageClass,
+    width: u32,
+) -> Option<(&'static str, &'static str, bool)> {
+    enum Kind {
+        Atomics,
+        Add,
+        MinMax,
+    }
+
+    let (operation, kind) = match instruction {
+        Instruction::AtomicFAddEXT { .. } => ("an atomic add operation", Kind::Add),
+        Instruction::AtomicFMinEXT { .. } | Instruction::AtomicFMaxEXT { .. } => {
+            ("an atomic min or max operation", Kind::MinMax)
+        }
+        _ => ("an atomic load, store or exchange operation", Kind::Atomics),
+    };
+
+    let (feature, enabled) = match (storage_class, width, kind) {
+        (StorageClass::StorageBuffer, 16, Kind::Atomics) => (
+            "shader_buffer_float16_atomics",
+            features.shader_buffer_float16_atomics,
+        ),
+        (StorageClass::StorageBuffer, 16, Kind::Add) => (
+            "shader_buffer_float16_atomic_add",
+            features.shader_buffer_float16_atomic_add,
+        ),
+        (StorageClass::StorageBuffer, 16, Kind::MinMax) => (
+            "shader_buffer_float16_atomic_min_max",
+            features.shader_buffer_float16_atomic_min_max,
+        ),
+        (StorageClass::StorageBuffer, 32, Kind::Atomics) => (
+            "shader_buffer_float32_atomics",
+            features.shader_buffer_float32_atomics,
+        ),
+        (StorageClass::StorageBuffer, 32, Kind::Add) => (
+            "shader_buffer_float32_atomic_add",
+            features.shader_buffer_float32_atomic_add,
+        ),
+        (StorageClass::StorageBuffer, 32, Kind::MinMax) => (
+            "shader_buffer_float32_atomic_min_max",
+            features.shader_buffer_float32_atomic_min_max,
+        ),
+        (StorageClass::StorageBuffer, 64, Kind::Atomics) => (
+            "shader_buffer_float64_atomics",
+            features.shader_buffer_float64_atomics,
+        ),
+        (StorageClass::StorageBuffer, 64, Kind::Add) => (
+            "shader_buffer_float64_atomic_add",
+            features.shader_buffer_float64_atomic_add,
+        ),
+        (StorageClass::StorageBuffer, 64, Kind::MinMax) => (
+            "shader_buffer_float64_atomic_min_max",
+            features.shader_buffer_float64_atomic_min_max,
+        ),
+        (StorageClass::Workgroup, 16, Kind::Atomics) => (
+            "shader_shared_float16_atomics",
+            features.shader_shared_float16_atomics,
+        ),
+        (StorageClass::Workgroup, 16, Kind::Add) => (
+            "shader_shared_float16_atomic_add",
+            features.shader_shared_float16_atomic_add,
+        ),
+        (StorageClass::Workgroup, 16, Kind::MinMax) => (
+            "shader_shared_float16_atomic_min_max",
+            features.shader_shared_float16_atomic_min_max,
+        ),
+        (StorageClass::Workgroup, 32, Kind::Atomics) => (
+            "shader_shared_float32_atomics",
+            features.shader_shared_float32_atomics,
+        ),
+        (StorageClass::Workgroup, 32, Kind::Add) => (
+            "shader_shared_float32_atomic_add",
+            features.shader_shared_float32_atomic_add,
+        ),
+        (StorageClass::Workgroup, 32, Kind::MinMax) => (
+            "shader_shared_float32_atomic_min_max",
+            features.shader_shared_float32_atomic_min_max,
+        ),
+        (StorageClass::Workgroup, 64, Kind::Atomics) => (
+            "shader_shared_float64_atomics",
+            features.shader_shared_float64_atomics,
+        ),
+        (StorageClass::Workgroup, 64, Kind::Add) => (
+            "shader_shared_float64_atomic_add",
+            features.shader_shared_float64_atomic_add,
+        ),
+        (StorageClass::Workgroup, 64, Kind::MinMax) => (
+            "shader_shared_float64_atomic_min_max",
+            features.shader_shared_float64_atomic_min_max,
+        ),
+        (StorageClass::Image, 32, Kind::Atomics) => (
+            "shader_image_float32_atomics",
+            features.shader_image_float32_atomics,
+        ),
+        (StorageClass::Image, 32, Kind::Add) => (
+            "shader_image_float32_atomic_add",
+            features.shader_image_float32_atomic_add,
+        ),
+        (StorageClass::Image, 32, Kind::MinMax) => (
+            "shader_image_float32_atomic_min_max",
+            features.shader_image_float32_atomic_min_max,
+        ),
+        _ => return None,
+    };
+
+    Some((operation, feature, enabled))
+}
+
 // TODO: spec clarification
 
 // VUID-RuntimeSpirv-maxMeshOutputMemorySize-08756
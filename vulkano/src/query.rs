@@ -9,15 +9,22 @@ use crate::{
     device::{Device, DeviceOwned},
     instance::InstanceOwnedDebugWrapper,
     macros::{impl_id_counter, vulkan_bitflags, vulkan_enum},
+    sync::future::{FenceSignalFuture, GpuFuture},
     DeviceSize, Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, Version,
     VulkanError, VulkanObject,
 };
 use std::{
+    fmt::{Debug, Error as FmtError, Formatter},
+    future::Future,
     mem::{size_of_val, MaybeUninit},
     num::NonZeroU64,
     ops::Range,
+    pin::Pin,
     ptr,
     sync::Arc,
+    task::{Context, Poll},
+    thread,
+    time::{Duration, Instant},
 };
 
 /// A collection of one or more queries of a particular type.
@@ -150,7 +157,8 @@ impl QueryPool {
             | QueryType::AccelerationStructureSerializationSize
             | QueryType::AccelerationStructureSerializationBottomLevelPointers
             | QueryType::AccelerationStructureSize
-            | QueryType::MeshPrimitivesGenerated => 1,
+            | QueryType::MeshPrimitivesGenerated
+            | QueryType::PrimitivesGenerated => 1,
             QueryType::PipelineStatistics => self.pipeline_statistics.count() as DeviceSize,
         }) + result_flags.intersects(QueryResultFlags::WITH_AVAILABILITY) as DeviceSize
     }
@@ -298,6 +306,88 @@ impl QueryPool {
         }
     }
 
+    /// Returns a handle to the results of a range of queries, that can be polled without
+    /// blocking.
+    ///
+    /// Unlike [`get_results`] with [`WAIT`], this doesn't stall the current thread until the
+    /// queries have finished. Instead, the returned [`PendingQueryResults`] can be checked each
+    /// frame with [`try_get`], or awaited as a [`Future`]. Availability is determined with
+    /// [`WITH_AVAILABILITY`], so queries that were never submitted are reported as not available,
+    /// rather than blocking forever.
+    ///
+    /// [`get_results`]: Self::get_results
+    /// [`WAIT`]: QueryResultFlags::WAIT
+    /// [`try_get`]: PendingQueryResults::try_get
+    /// [`WITH_AVAILABILITY`]: QueryResultFlags::WITH_AVAILABILITY
+    #[inline]
+    pub fn pending_results(
+        self: &Arc<Self>,
+        range: Range<u32>,
+    ) -> Result<PendingQueryResults, Box<ValidationError>> {
+        self.validate_pending_results(range.clone())?;
+
+        Ok(PendingQueryResults {
+            query_pool: self.clone(),
+            range,
+            fence: None,
+        })
+    }
+
+    /// Same as [`pending_results`], but the results are only read once `fence` is signaled.
+    ///
+    /// `fence` should be the future of the submission that ends the queries. Until it's
+    /// signaled, polling only checks the fence, and [`PendingQueryResults::wait`] waits on the
+    /// fence with a timeout. For polling to make progress, `fence` must have been flushed.
+    ///
+    /// [`pending_results`]: Self::pending_results
+    #[inline]
+    pub fn pending_results_after_fence<F>(
+        self: &Arc<Self>,
+        range: Range<u32>,
+        fence: Arc<FenceSignalFuture<F>>,
+    ) -> Result<PendingQueryResults, Box<ValidationError>>
+    where
+        F: GpuFuture + Send + 'static,
+    {
+        self.validate_pending_results(range.clone())?;
+
+        if fence.device() != self.device() {
+            return Err(Box::new(ValidationError {
+                problem: "`fence` and `self` were not created from the same device".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(PendingQueryResults {
+            query_pool: self.clone(),
+            range,
+            fence: Some(fence),
+        })
+    }
+
+    fn validate_pending_results(&self, range: Range<u32>) -> Result<(), Box<ValidationError>> {
+        if range.is_empty() {
+            return Err(Box::new(ValidationError {
+                context: "range".into(),
+                problem: "is empty".into(),
+                ..Default::default()
+            }));
+        }
+
+        if range.end > self.query_count {
+            return Err(Box::new(ValidationError {
+                problem: "`range.end` is greater than `self.query_count`".into(),
+                vuids: &[
+                    "VUID-vkGetQueryPoolResults-firstQuery-00813",
+                    "VUID-vkGetQueryPoolResults-firstQuery-00816",
+                ],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
     /// Resets a range of queries.
     ///
     /// The [`host_query_reset`] feature must be enabled on the device.
@@ -613,6 +703,23 @@ vulkan_enum! {
     RequiresOneOf([
         RequiresAllOf([DeviceExtension(ext_mesh_shader)]),
     ]),
+
+    /// Queries the number of primitives that are output by the last pre-rasterization shader
+    /// stage, before clipping and rasterization.
+    ///
+    /// Drawing with rasterizer discard enabled while this query is active requires the
+    /// [`primitives_generated_query_with_rasterizer_discard`] feature to be enabled on the
+    /// device.
+    ///
+    /// Used with the [`begin_query`] and [`end_query`] commands.
+    ///
+    /// [`primitives_generated_query_with_rasterizer_discard`]: crate::device::DeviceFeatures::primitives_generated_query_with_rasterizer_discard
+    /// [`begin_query`]: crate::command_buffer::RecordingCommandBuffer::begin_query
+    /// [`end_query`]: crate::command_buffer::RecordingCommandBuffer::end_query
+    PrimitivesGenerated = PRIMITIVES_GENERATED_EXT
+    RequiresOneOf([
+        RequiresAllOf([DeviceExtension(ext_primitives_generated_query)]),
+    ]),
 }
 
 vulkan_bitflags! {
@@ -720,6 +827,258 @@ vulkan_bitflags! {
     ]),
 }
 
+/// The results of a range of queries, that may not be available yet.
+///
+/// This is returned by [`QueryPool::pending_results`] and
+/// [`QueryPool::pending_results_after_fence`]. The results are returned as `u64` values,
+/// [`QueryPool::result_len`] of them per query, without the availability element. For pipeline
+/// statistics queries, they can be decoded with [`PipelineStatistics::from_results`].
+///
+/// As a [`Future`], this is a spin future: Vulkan has no way to be notified when query results
+/// become available, so the task is woken up again immediately each time it is polled.
+pub struct PendingQueryResults {
+    query_pool: Arc<QueryPool>,
+    range: Range<u32>,
+    fence: Option<Arc<dyn PendingFence>>,
+}
+
+impl PendingQueryResults {
+    /// Returns the query pool that the results are read from.
+    #[inline]
+    pub fn query_pool(&self) -> &Arc<QueryPool> {
+        &self.query_pool
+    }
+
+    /// Returns the range of queries whose results are read.
+    #[inline]
+    pub fn range(&self) -> Range<u32> {
+        self.range.clone()
+    }
+
+    /// Returns the results if all of them are available, or `None` if some are still pending.
+    ///
+    /// This never blocks.
+    pub fn try_get(&self) -> Result<Option<Vec<u64>>, VulkanError> {
+        if let Some(fence) = &self.fence {
+            if !fence.is_signaled()? {
+                return Ok(None);
+            }
+        }
+
+        self.read(QueryResultFlags::empty())
+    }
+
+    /// Blocks the current thread until all results are available, and returns them.
+    ///
+    /// If `timeout` is `None`, the wait is infinite. Otherwise, [`VulkanError::Timeout`] is
+    /// returned if the results are not available after `timeout` has elapsed. If a fence was
+    /// provided, [`VulkanError::NotReady`] is returned if the results are still not available
+    /// once the fence is signaled, which means that the queries were not ended by that
+    /// submission.
+    pub fn wait(&self, timeout: Option<Duration>) -> Result<Vec<u64>, Validated<VulkanError>> {
+        if let Some(fence) = &self.fence {
+            fence.wait(timeout)?;
+
+            return self
+                .read(QueryResultFlags::empty())?
+                .ok_or(Validated::Error(VulkanError::NotReady));
+        }
+
+        let Some(timeout) = timeout else {
+            return Ok(self.read(QueryResultFlags::WAIT)?.unwrap());
+        };
+
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(results) = self.read(QueryResultFlags::empty())? {
+                return Ok(results);
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Validated::Error(VulkanError::Timeout));
+            }
+
+            thread::yield_now();
+        }
+    }
+
+    // Reads the results with `WITH_AVAILABILITY`, and strips the availability elements if all
+    // results are available.
+    fn read(&self, flags: QueryResultFlags) -> Result<Option<Vec<u64>>, VulkanError> {
+        let flags = flags | QueryResultFlags::WITH_AVAILABILITY;
+        let per_query_len = self.query_pool.result_len(flags) as usize;
+        let mut results = vec![0u64; per_query_len * self.range.len()];
+
+        // Only `NOT_READY` is reported as `false`, and the availability elements tell us the same.
+        unsafe {
+            self.query_pool
+                .get_results_unchecked(self.range.clone(), &mut results, flags)?
+        };
+
+        if results
+            .chunks_exact(per_query_len)
+            .any(|query| query[per_query_len - 1] == 0)
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            results
+                .chunks_exact(per_query_len)
+                .flat_map(|query| &query[..per_query_len - 1])
+                .copied()
+                .collect(),
+        ))
+    }
+}
+
+impl Future for PendingQueryResults {
+    type Output = Result<Vec<u64>, VulkanError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Like for fences, we can only poll, so this is a spin future.
+        match self.try_get() {
+            Ok(Some(results)) => Poll::Ready(Ok(results)),
+            Ok(None) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+impl Debug for PendingQueryResults {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_struct("PendingQueryResults")
+            .field("query_pool", &self.query_pool)
+            .field("range", &self.range)
+            .field("fence", &self.fence.is_some())
+            .finish()
+    }
+}
+
+// Erases the type of the previous future of a `FenceSignalFuture`.
+trait PendingFence: Send + Sync {
+    fn is_signaled(&self) -> Result<bool, VulkanError>;
+
+    fn wait(&self, timeout: Option<Duration>) -> Result<(), Validated<VulkanError>>;
+}
+
+impl<F> PendingFence for FenceSignalFuture<F>
+where
+    F: GpuFuture + Send,
+{
+    fn is_signaled(&self) -> Result<bool, VulkanError> {
+        FenceSignalFuture::is_signaled(self)
+    }
+
+    fn wait(&self, timeout: Option<Duration>) -> Result<(), Validated<VulkanError>> {
+        FenceSignalFuture::wait(self, timeout)
+    }
+}
+
+/// The results of a single [`QueryType::PipelineStatistics`] query.
+///
+/// Each field is `None` if the corresponding flag was not enabled in
+/// [`QueryPoolCreateInfo::pipeline_statistics`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PipelineStatistics {
+    /// The value of [`QueryPipelineStatisticFlags::INPUT_ASSEMBLY_VERTICES`].
+    pub input_assembly_vertices: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::INPUT_ASSEMBLY_PRIMITIVES`].
+    pub input_assembly_primitives: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::VERTEX_SHADER_INVOCATIONS`].
+    pub vertex_shader_invocations: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::GEOMETRY_SHADER_INVOCATIONS`].
+    pub geometry_shader_invocations: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::GEOMETRY_SHADER_PRIMITIVES`].
+    pub geometry_shader_primitives: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::CLIPPING_INVOCATIONS`].
+    pub clipping_invocations: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::CLIPPING_PRIMITIVES`].
+    pub clipping_primitives: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::FRAGMENT_SHADER_INVOCATIONS`].
+    pub fragment_shader_invocations: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::TESSELLATION_CONTROL_SHADER_PATCHES`].
+    pub tessellation_control_shader_patches: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::TESSELLATION_EVALUATION_SHADER_INVOCATIONS`].
+    pub tessellation_evaluation_shader_invocations: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::COMPUTE_SHADER_INVOCATIONS`].
+    pub compute_shader_invocations: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::TASK_SHADER_INVOCATIONS`].
+    pub task_shader_invocations: Option<u64>,
+
+    /// The value of [`QueryPipelineStatisticFlags::MESH_SHADER_INVOCATIONS`].
+    pub mesh_shader_invocations: Option<u64>,
+}
+
+impl PipelineStatistics {
+    /// Decodes the results of a single query, as written by [`QueryPool::get_results`] or
+    /// returned by [`PendingQueryResults`], for a query pool with the `flags` statistics.
+    ///
+    /// Vulkan writes one value for each enabled statistic, in the order of the bits of the flags.
+    /// Any elements after the last statistic, such as an availability element, are ignored.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `results` has fewer than `flags.count()` elements.
+    pub fn from_results<T>(flags: QueryPipelineStatisticFlags, results: &[T]) -> Self
+    where
+        T: QueryResultElement + Copy + Into<u64>,
+    {
+        assert!(
+            results.len() >= flags.count() as usize,
+            "`results` has fewer elements than the number of statistics in `flags`",
+        );
+
+        let mut values = results.iter().map(|&value| value.into());
+        let mut next = |flag: QueryPipelineStatisticFlags| {
+            flags.intersects(flag).then(|| values.next().unwrap())
+        };
+
+        PipelineStatistics {
+            input_assembly_vertices: next(QueryPipelineStatisticFlags::INPUT_ASSEMBLY_VERTICES),
+            input_assembly_primitives: next(QueryPipelineStatisticFlags::INPUT_ASSEMBLY_PRIMITIVES),
+            vertex_shader_invocations: next(QueryPipelineStatisticFlags::VERTEX_SHADER_INVOCATIONS),
+            geometry_shader_invocations: next(
+                QueryPipelineStatisticFlags::GEOMETRY_SHADER_INVOCATIONS,
+            ),
+            geometry_shader_primitives: next(
+                QueryPipelineStatisticFlags::GEOMETRY_SHADER_PRIMITIVES,
+            ),
+            clipping_invocations: next(QueryPipelineStatisticFlags::CLIPPING_INVOCATIONS),
+            clipping_primitives: next(QueryPipelineStatisticFlags::CLIPPING_PRIMITIVES),
+            fragment_shader_invocations: next(
+                QueryPipelineStatisticFlags::FRAGMENT_SHADER_INVOCATIONS,
+            ),
+            tessellation_control_shader_patches: next(
+                QueryPipelineStatisticFlags::TESSELLATION_CONTROL_SHADER_PATCHES,
+            ),
+            tessellation_evaluation_shader_invocations: next(
+                QueryPipelineStatisticFlags::TESSELLATION_EVALUATION_SHADER_INVOCATIONS,
+            ),
+            compute_shader_invocations: next(
+                QueryPipelineStatisticFlags::COMPUTE_SHADER_INVOCATIONS,
+            ),
+            task_shader_invocations: next(QueryPipelineStatisticFlags::TASK_SHADER_INVOCATIONS),
+            mesh_shader_invocations: next(QueryPipelineStatisticFlags::MESH_SHADER_INVOCATIONS),
+        }
+    }
+}
+
 /// A trait for elements of buffers that can be used as a destination for query results.
 ///
 /// # Safety
@@ -771,7 +1130,7 @@ vulkan_bitflags! {
 mod tests {
     use super::QueryPoolCreateInfo;
     use crate::{
-        query::{QueryPool, QueryType},
+        query::{PipelineStatistics, QueryPipelineStatisticFlags, QueryPool, QueryType},
         Validated,
     };
 
@@ -789,4 +1148,41 @@ mod tests {
             Err(Validated::ValidationError(_)),
         ));
     }
+
+    #[test]
+    fn pending_results_range() {
+        let (device, _) = gfx_dev_and_queue!();
+        let query_pool = QueryPool::new(
+            device,
+            QueryPoolCreateInfo {
+                query_count: 4,
+                ..QueryPoolCreateInfo::query_type(QueryType::Occlusion)
+            },
+        )
+        .unwrap();
+
+        assert!(query_pool.pending_results(2..2).is_err());
+        assert!(query_pool.pending_results(2..5).is_err());
+        assert!(query_pool.pending_results(0..4).is_ok());
+    }
+
+    #[test]
+    fn pipeline_statistics_from_results() {
+        let flags = QueryPipelineStatisticFlags::INPUT_ASSEMBLY_VERTICES
+            | QueryPipelineStatisticFlags::FRAGMENT_SHADER_INVOCATIONS
+            | QueryPipelineStatisticFlags::COMPUTE_SHADER_INVOCATIONS;
+
+        // The trailing element is the availability, which is ignored.
+        let statistics = PipelineStatistics::from_results(flags, &[3u64, 5, 7, 1]);
+
+        assert_eq!(
+            statistics,
+            PipelineStatistics {
+                input_assembly_vertices: Some(3),
+                fragment_shader_invocations: Some(5),
+                compute_shader_invocations: Some(7),
+                ..Default::default()
+            },
+        );
+    }
 }
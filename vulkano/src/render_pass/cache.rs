@@ -0,0 +1,345 @@
+use super::{
+    Framebuffer, FramebufferCreateFlags, FramebufferCreateInfo, RenderPass, RenderPassCreateInfo,
+};
+use crate::{
+    device::{Device, DeviceOwned},
+    image::view::ImageView,
+    instance::InstanceOwnedDebugWrapper,
+    Validated, VulkanError,
+};
+use ahash::HashMap;
+use parking_lot::Mutex;
+use std::{
+    fmt::{Debug, Error as FmtError, Formatter},
+    num::NonZeroU64,
+    sync::Arc,
+};
+
+/// A cache of render passes, keyed by their create info.
+///
+/// Render passes that are created from identical create infos are compatible with each other, but
+/// they are still different objects, so pipelines and framebuffers created for one of them can't
+/// be shared with the others without checking compatibility first. Creating render passes through
+/// a `RenderPassCache` ensures that every description results in a single render pass object.
+///
+/// ```
+/// use vulkano::render_pass::{RenderPassCache, RenderPassCreateInfo, SubpassDescription};
+///
+/// # let device: std::sync::Arc<vulkano::device::Device> = return;
+/// let cache = RenderPassCache::new(device.clone());
+/// let create_info = RenderPassCreateInfo {
+///     subpasses: vec![SubpassDescription::default()],
+///     ..Default::default()
+/// };
+///
+/// let a = cache.get(create_info.clone()).unwrap();
+/// let b = cache.get(create_info).unwrap();
+/// assert!(std::sync::Arc::ptr_eq(&a, &b));
+/// ```
+pub struct RenderPassCache {
+    device: InstanceOwnedDebugWrapper<Arc<Device>>,
+    render_passes: Mutex<HashMap<RenderPassCreateInfo, Arc<RenderPass>>>,
+}
+
+impl RenderPassCache {
+    /// Creates a new, empty `RenderPassCache`.
+    #[inline]
+    pub fn new(device: Arc<Device>) -> Self {
+        RenderPassCache {
+            device: InstanceOwnedDebugWrapper(device),
+            render_passes: Mutex::default(),
+        }
+    }
+
+    /// Returns the number of render passes that are held by the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.render_passes.lock().len()
+    }
+
+    /// Returns whether the cache is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.render_passes.lock().is_empty()
+    }
+
+    /// Returns a render pass created with `create_info`.
+    ///
+    /// If the cache holds a render pass that was created with an identical create info, that
+    /// render pass is returned. Otherwise, a new render pass is created and added to the cache.
+    pub fn get(
+        &self,
+        create_info: RenderPassCreateInfo,
+    ) -> Result<Arc<RenderPass>, Validated<VulkanError>> {
+        let mut render_passes = self.render_passes.lock();
+
+        if let Some(render_pass) = render_passes.get(&create_info) {
+            return Ok(render_pass.clone());
+        }
+
+        let render_pass = RenderPass::new(self.device.clone(), create_info.clone())?;
+        render_passes.insert(create_info, render_pass.clone());
+
+        Ok(render_pass)
+    }
+
+    /// Removes all render passes from the cache.
+    ///
+    /// Render passes that are still in use outside of the cache are not destroyed, but will no
+    /// longer be returned by [`get`](Self::get).
+    pub fn clear(&self) {
+        self.render_passes.lock().clear();
+    }
+}
+
+unsafe impl DeviceOwned for RenderPassCache {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl Debug for RenderPassCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_struct("RenderPassCache")
+            .field("device", &self.device)
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A cache of framebuffers, keyed by their render pass and attachments.
+///
+/// Applications often create a framebuffer for every frame, even though the attachments are the
+/// same ones as in a previous frame, for example when cycling through the images of a swapchain
+/// or a small set of transient images. A `FramebufferCache` returns the existing framebuffer in
+/// that case.
+///
+/// Since a framebuffer keeps its attachments alive, the cache would otherwise prevent image views
+/// from being destroyed. Whenever a framebuffer is created, and when calling
+/// [`purge_unused`](Self::purge_unused), entries are removed if any of their attachments is no
+/// longer referenced outside of the cache, for example after the swapchain was recreated. The
+/// render pass and the attachments are compared by identity, so framebuffers are only shared
+/// between uses of the same objects.
+#[derive(Default)]
+pub struct FramebufferCache {
+    framebuffers: Mutex<HashMap<FramebufferKey, Arc<Framebuffer>>>,
+}
+
+impl FramebufferCache {
+    /// Creates a new, empty `FramebufferCache`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of framebuffers that are held by the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.framebuffers.lock().len()
+    }
+
+    /// Returns whether the cache is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.framebuffers.lock().is_empty()
+    }
+
+    /// Returns a framebuffer for `render_pass`, created with `create_info`.
+    ///
+    /// If the cache holds a framebuffer that was created for the same render pass with the same
+    /// attachments and parameters, that framebuffer is returned. Otherwise, entries whose
+    /// attachments are no longer in use are removed, and a new framebuffer is created and added to
+    /// the cache.
+    pub fn get(
+        &self,
+        render_pass: Arc<RenderPass>,
+        create_info: FramebufferCreateInfo,
+    ) -> Result<Arc<Framebuffer>, Validated<VulkanError>> {
+        let key = FramebufferKey::new(&render_pass, &create_info);
+        let mut framebuffers = self.framebuffers.lock();
+
+        if let Some(framebuffer) = framebuffers.get(&key) {
+            return Ok(framebuffer.clone());
+        }
+
+        purge_unused(&mut framebuffers);
+
+        let framebuffer = Framebuffer::new(render_pass, create_info)?;
+        framebuffers.insert(key, framebuffer.clone());
+
+        Ok(framebuffer)
+    }
+
+    /// Removes all framebuffers that have an attachment that is not referenced outside of the
+    /// cache.
+    pub fn purge_unused(&self) {
+        purge_unused(&mut self.framebuffers.lock());
+    }
+
+    /// Removes all framebuffers that use `image_view` as an attachment.
+    pub fn remove_image_view(&self, image_view: &ImageView) {
+        let id = image_view.id();
+
+        self.framebuffers
+            .lock()
+            .retain(|key, _| !key.attachments.contains(&id));
+    }
+
+    /// Removes all framebuffers from the cache.
+    pub fn clear(&self) {
+        self.framebuffers.lock().clear();
+    }
+}
+
+fn purge_unused(framebuffers: &mut HashMap<FramebufferKey, Arc<Framebuffer>>) {
+    // An image view can be an attachment of several cached framebuffers, so count the references
+    // that are held by the cache itself.
+    let mut cache_references: HashMap<NonZeroU64, usize> = HashMap::default();
+
+    for framebuffer in framebuffers.values() {
+        for attachment in framebuffer.attachments() {
+            *cache_references.entry(attachment.id()).or_default() += 1;
+        }
+    }
+
+    // Framebuffers that are still in use outside of the cache are kept regardless.
+    framebuffers.retain(|_, framebuffer| {
+        Arc::strong_count(framebuffer) > 1
+            || framebuffer.attachments().iter().all(|attachment| {
+                Arc::strong_count(attachment) > cache_references[&attachment.id()]
+            })
+    });
+}
+
+impl Debug for FramebufferCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_struct("FramebufferCache")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct FramebufferKey {
+    render_pass: NonZeroU64,
+    flags: FramebufferCreateFlags,
+    attachments: Vec<NonZeroU64>,
+    extent: [u32; 2],
+    layers: u32,
+}
+
+impl FramebufferKey {
+    fn new(render_pass: &RenderPass, create_info: &FramebufferCreateInfo) -> Self {
+        let &FramebufferCreateInfo {
+            flags,
+            ref attachments,
+            extent,
+            layers,
+            _ne: _,
+        } = create_info;
+
+        Self {
+            render_pass: render_pass.id(),
+            flags,
+            attachments: attachments.iter().map(|view| view.id()).collect(),
+            extent,
+            layers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FramebufferCache, RenderPassCache};
+    use crate::{
+        format::Format,
+        image::{view::ImageView, Image, ImageCreateInfo, ImageUsage},
+        memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+        render_pass::{FramebufferCreateInfo, RenderPassCreateInfo, SubpassDescription},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn render_pass_dedup() {
+        let (device, _) = gfx_dev_and_queue!();
+        let cache = RenderPassCache::new(device);
+
+        let create_info = RenderPassCreateInfo {
+            subpasses: vec![SubpassDescription::default()],
+            ..Default::default()
+        };
+        let a = cache.get(create_info.clone()).unwrap();
+        let b = cache.get(create_info).unwrap();
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn framebuffer_invalidation() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+        let render_pass = single_pass_renderpass!(
+            device.clone(),
+            attachments: {
+                color: {
+                    format: Format::R8G8B8A8_UNORM,
+                    samples: 1,
+                    load_op: Clear,
+                    store_op: DontCare,
+                },
+            },
+            pass: {
+                color: [color],
+                depth_stencil: {},
+            },
+        )
+        .unwrap();
+
+        let new_view = || {
+            let image = Image::new(
+                memory_allocator.clone(),
+                ImageCreateInfo {
+                    format: Format::R8G8B8A8_UNORM,
+                    extent: [32, 32, 1],
+                    usage: ImageUsage::COLOR_ATTACHMENT,
+                    ..Default::default()
+                },
+                AllocationCreateInfo::default(),
+            )
+            .unwrap();
+
+            ImageView::new_default(image).unwrap()
+        };
+
+        let cache = FramebufferCache::new();
+        let view_a = new_view();
+        let view_b = new_view();
+
+        let create_info = |view: &Arc<ImageView>| FramebufferCreateInfo {
+            attachments: vec![view.clone()],
+            ..Default::default()
+        };
+
+        let a = cache
+            .get(render_pass.clone(), create_info(&view_a))
+            .unwrap();
+        let a2 = cache
+            .get(render_pass.clone(), create_info(&view_a))
+            .unwrap();
+        assert!(Arc::ptr_eq(&a, &a2));
+        drop((a, a2));
+
+        cache
+            .get(render_pass.clone(), create_info(&view_b))
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // Once the view is dropped, the framebuffer that uses it is no longer useful.
+        drop(view_a);
+        cache.purge_unused();
+        assert_eq!(cache.len(), 1);
+    }
+}
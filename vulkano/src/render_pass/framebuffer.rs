@@ -49,6 +49,7 @@ pub struct Framebuffer {
 
 impl Framebuffer {
     /// Creates a new `Framebuffer`.
+    #[track_caller]
     pub fn new(
         render_pass: Arc<RenderPass>,
         mut create_info: FramebufferCreateInfo,
@@ -56,7 +57,12 @@ impl Framebuffer {
         create_info.set_auto_extent_layers(&render_pass);
         Self::validate_new(&render_pass, &create_info)?;
 
-        unsafe { Ok(Self::new_unchecked(render_pass, create_info)?) }
+        let framebuffer = unsafe { Self::new_unchecked(render_pass, create_info)? };
+        framebuffer
+            .device()
+            .set_auto_debug_utils_object_name(&*framebuffer, "Framebuffer");
+
+        Ok(framebuffer)
     }
 
     fn validate_new(
@@ -16,7 +16,10 @@
 //! Consequently you can create graphics pipelines from a render pass object alone.
 //! A `Framebuffer` object is only needed when you actually add draw commands to a command buffer.
 
-pub use self::framebuffer::{Framebuffer, FramebufferCreateFlags, FramebufferCreateInfo};
+pub use self::{
+    cache::{FramebufferCache, RenderPassCache},
+    framebuffer::{Framebuffer, FramebufferCreateFlags, FramebufferCreateInfo},
+};
 use crate::{
     device::{Device, DeviceOwned, QueueFlags},
     format::{ClearValueType, Format, FormatFeatures, NumericType},
@@ -39,6 +42,7 @@ use std::{
 
 #[macro_use]
 mod macros;
+mod cache;
 mod create;
 mod framebuffer;
 
@@ -122,6 +126,7 @@ impl RenderPass {
     ///
     /// - Panics if `create_info.subpasses` is empty.
     /// - Panics if any element of `create_info.attachments` has a `format` of `None`.
+    #[track_caller]
     pub fn new(
         device: Arc<Device>,
         mut create_info: RenderPassCreateInfo,
@@ -141,7 +146,12 @@ impl RenderPass {
 
         Self::validate_new(&device, &create_info)?;
 
-        unsafe { Ok(Self::new_unchecked(device, create_info)?) }
+        let render_pass = unsafe { Self::new_unchecked(device, create_info)? };
+        render_pass
+            .device()
+            .set_auto_debug_utils_object_name(&*render_pass, "RenderPass");
+
+        Ok(render_pass)
     }
 
     fn validate_new(
@@ -721,7 +731,7 @@ impl From<Subpass> for (Arc<RenderPass>, u32) {
 }
 
 /// Parameters to create a new `RenderPass`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RenderPassCreateInfo {
     /// Additional properties of the render pass.
     ///
@@ -1595,7 +1605,7 @@ vulkan_bitflags! {
 }
 
 /// Describes an attachment that will be used in a render pass.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct AttachmentDescription {
     /// Additional properties of the attachment.
     ///
@@ -2156,7 +2166,7 @@ vulkan_bitflags! {
 /// If an attachment is used more than once, i.e. a given `AttachmentReference::attachment` occurs
 /// more than once in the `SubpassDescription`, then their `AttachmentReference::layout` must be
 /// the same as well.
-#[derive(Debug, Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SubpassDescription {
     /// Additional properties of the subpass.
     ///
@@ -2816,7 +2826,7 @@ impl SubpassDescription {
                 .contains_enum(stencil_resolve_mode)
             {
                 return Err(Box::new(ValidationError {
-                    problem: "stencil_resolve_mode` is not one of the modes in the \
+                    problem: "`stencil_resolve_mode` is not one of the modes in the \
                         `supported_stencil_resolve_modes` device property"
                         .into(),
                     vuids: &[
@@ -3019,7 +3029,7 @@ vulkan_bitflags! {
 }
 
 /// A reference to an attachment in a subpass description of a render pass.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct AttachmentReference {
     /// The number of the attachment being referred to.
     ///
@@ -3217,7 +3227,7 @@ impl AttachmentReference {
 /// before the render pass instance began (for `src_subpass`), or on commands that will be
 /// submitted after the render pass instance ends (for `dst_subpass`). The values must not both be
 /// `None`.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SubpassDependency {
     /// The index of the subpass that writes the data that `dst_subpass` is going to use.
     ///
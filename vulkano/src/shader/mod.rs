@@ -184,6 +184,10 @@
 //!     feature. <sup>[\[02691\]]</sup>
 //!   - The bound buffer view's format must have the
 //!     [`FormatFeatures::STORAGE_TEXEL_BUFFER_ATOMIC`] format feature. <sup>[\[07888\]]</sup>
+//!   - If the `Sampled Type` operand of the `OpTypeImage` declaration has a `Width` of 64, and
+//!     the [`sparse_image_int64_atomics`](DeviceFeatures::sparse_image_int64_atomics) feature is
+//!     not enabled, the underlying image of the bound image view must not have been created with
+//!     [`ImageCreateFlags::SPARSE_RESIDENCY`]. <sup>[\[04474\]]</sup>
 //!
 //! ## Image sampling
 //!
@@ -329,6 +333,7 @@
 //! [\[04471\]]: https://registry.khronos.org/vulkan/specs/1.3-extensions/html/vkspec.html#VUID-vkCmdDispatch-SampledType-04471
 //! [\[04472\]]: https://registry.khronos.org/vulkan/specs/1.3-extensions/html/vkspec.html#VUID-vkCmdDispatch-SampledType-04472
 //! [\[04473\]]: https://registry.khronos.org/vulkan/specs/1.3-extensions/html/vkspec.html#VUID-vkCmdDispatch-SampledType-04473
+//! [\[04474\]]: https://registry.khronos.org/vulkan/specs/1.3-extensions/html/vkspec.html#VUID-vkCmdDispatch-sparseImageInt64Atomics-04474
 //! [\[04553\]]: https://registry.khronos.org/vulkan/specs/1.3-extensions/html/vkspec.html#VUID-vkCmdDispatch-magFilter-04553
 //! [\[04770\]]: https://registry.khronos.org/vulkan/specs/1.3-extensions/html/vkspec.html#VUID-vkCmdDispatch-mipmapMode-04770
 //! [\[06274\]]: https://registry.khronos.org/vulkan/specs/1.3-extensions/html/vkspec.html#VUID-RuntimeSpirv-NonUniform-06274
@@ -401,22 +406,22 @@
 //! [\[09218\]]: https://registry.khronos.org/vulkan/specs/1.3-extensions/html/vkspec.html#VUID-RuntimeSpirv-MeshEXT-09218
 //! [\[09219\]]: https://registry.khronos.org/vulkan/specs/1.3-extensions/html/vkspec.html#VUID-RuntimeSpirv-OpImageBlockMatchWindow-09219
 
-use self::spirv::{Id, Instruction};
+use self::spirv::{BuiltIn, Id, Instruction};
 #[cfg(doc)]
 use crate::{
     acceleration_structure::BuildAccelerationStructureFlags,
     descriptor_set::layout::DescriptorBindingFlags,
-    device::{physical::PhysicalDevice, DeviceFeatures, DeviceProperties},
+    device::DeviceProperties,
     format::FormatFeatures,
     image::{
         sampler::{Filter, Sampler, SamplerCreateInfo, SamplerMipmapMode, SamplerReductionMode},
         view::ImageView,
-        Image, ImageFormatProperties,
+        Image, ImageCreateFlags, ImageFormatProperties,
     },
 };
 use crate::{
     descriptor_set::layout::DescriptorType,
-    device::{Device, DeviceOwned},
+    device::{physical::PhysicalDevice, Device, DeviceExtensions, DeviceFeatures, DeviceOwned},
     format::{Format, NumericType},
     image::view::ImageViewType,
     instance::InstanceOwnedDebugWrapper,
@@ -424,8 +429,8 @@ use crate::{
     pipeline::layout::PushConstantRange,
     shader::spirv::{Capability, Spirv},
     sync::PipelineStages,
-    Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, Version, VulkanError,
-    VulkanObject,
+    DeviceSize, Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, Version,
+    VulkanError, VulkanObject,
 };
 use ahash::{HashMap, HashSet};
 use bytemuck::bytes_of;
@@ -434,6 +439,7 @@ use smallvec::SmallVec;
 use spirv::ExecutionModel;
 use std::{
     collections::hash_map::Entry,
+    fmt::{Debug, Error as FmtError, Formatter},
     mem::{discriminant, size_of_val, MaybeUninit},
     num::NonZeroU64,
     ptr,
@@ -455,6 +461,7 @@ pub struct ShaderModule {
 
     spirv: Spirv,
     specialization_constants: HashMap<u32, SpecializationConstant>,
+    specialization_constant_names: HashMap<u32, String>,
 }
 
 impl ShaderModule {
@@ -464,6 +471,7 @@ impl ShaderModule {
     ///
     /// - The SPIR-V code in `create_info.code` must be valid.
     #[inline]
+    #[track_caller]
     pub unsafe fn new(
         device: Arc<Device>,
         create_info: ShaderModuleCreateInfo<'_>,
@@ -478,7 +486,12 @@ impl ShaderModule {
 
         Self::validate_new(&device, &create_info, &spirv)?;
 
-        Ok(Self::new_with_spirv_unchecked(device, create_info, spirv)?)
+        let shader_module = Self::new_with_spirv_unchecked(device, create_info, spirv)?;
+        shader_module
+            .device()
+            .set_auto_debug_utils_object_name(&*shader_module, "ShaderModule");
+
+        Ok(shader_module)
     }
 
     fn validate_new(
@@ -502,6 +515,14 @@ impl ShaderModule {
         Self::new_with_spirv_unchecked(device, create_info, spirv)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(code_size = size_of_val(create_info.code)),
+        )
+    )]
     unsafe fn new_with_spirv_unchecked(
         device: Arc<Device>,
         create_info: ShaderModuleCreateInfo<'_>,
@@ -530,12 +551,14 @@ impl ShaderModule {
             output.assume_init()
         };
 
-        Ok(Self::from_handle_with_spirv(
-            device,
-            handle,
-            create_info,
-            spirv,
-        ))
+        let shader_module = Self::from_handle_with_spirv(device, handle, create_info, spirv);
+
+        #[cfg(feature = "aftermath")]
+        if let Some(hooks) = crate::device::aftermath::hooks() {
+            hooks.shader_module_created(&shader_module, code);
+        }
+
+        Ok(shader_module)
     }
 
     /// Creates a new `ShaderModule` from a raw object handle.
@@ -561,6 +584,7 @@ impl ShaderModule {
     ) -> Arc<ShaderModule> {
         let ShaderModuleCreateInfo { code: _, _ne: _ } = create_info;
         let specialization_constants = reflect::specialization_constants(&spirv);
+        let specialization_constant_names = reflect::specialization_constant_names(&spirv);
 
         Arc::new(ShaderModule {
             handle,
@@ -569,6 +593,7 @@ impl ShaderModule {
 
             spirv,
             specialization_constants,
+            specialization_constant_names,
         })
     }
 
@@ -606,6 +631,50 @@ impl ShaderModule {
         Self::new(device, ShaderModuleCreateInfo::new(&words))
     }
 
+    /// Returns the identifier of the shader module, which can be used to create pipelines without
+    /// the module in later runs of the application.
+    ///
+    /// The [`shader_module_identifier`](crate::device::DeviceFeatures::shader_module_identifier)
+    /// feature must be enabled on the device.
+    #[inline]
+    pub fn identifier(&self) -> Result<ShaderModuleIdentifier, Box<ValidationError>> {
+        self.validate_identifier()?;
+
+        Ok(unsafe { self.identifier_unchecked() })
+    }
+
+    fn validate_identifier(&self) -> Result<(), Box<ValidationError>> {
+        if !self.device.enabled_features().shader_module_identifier {
+            return Err(Box::new(ValidationError {
+                requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceFeature(
+                    "shader_module_identifier",
+                )])]),
+                vuids: &["VUID-vkGetShaderModuleIdentifierEXT-shaderModuleIdentifier-06884"],
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn identifier_unchecked(&self) -> ShaderModuleIdentifier {
+        let mut identifier_vk = ash::vk::ShaderModuleIdentifierEXT::default();
+
+        let fns = self.device.fns();
+        (fns.ext_shader_module_identifier
+            .get_shader_module_identifier_ext)(
+            self.device.handle(),
+            self.handle,
+            &mut identifier_vk,
+        );
+
+        ShaderModuleIdentifier {
+            size: identifier_vk.identifier_size as usize,
+            data: identifier_vk.identifier,
+        }
+    }
+
     /// Returns the specialization constants that are defined in the module,
     /// along with their default values.
     ///
@@ -616,6 +685,26 @@ impl ShaderModule {
         &self.specialization_constants
     }
 
+    /// Returns the names of the specialization constants that are defined in the module, indexed
+    /// by their `constant_id`.
+    ///
+    /// Constants that were not given a name in the shader, or whose name was stripped from the
+    /// SPIR-V code, are not included.
+    #[inline]
+    pub fn specialization_constant_names(&self) -> &HashMap<u32, String> {
+        &self.specialization_constant_names
+    }
+
+    /// Returns the `constant_id` of the specialization constant with the given name, if it exists.
+    #[inline]
+    pub fn specialization_constant_id(&self, name: &str) -> Option<u32> {
+        self.specialization_constant_names
+            .iter()
+            .find_map(|(&constant_id, constant_name)| {
+                (constant_name == name).then_some(constant_id)
+            })
+    }
+
     /// Applies the specialization constants to the shader module,
     /// and returns a specialized version of the module.
     ///
@@ -633,6 +722,42 @@ impl ShaderModule {
         SpecializedShaderModule::new(self.clone(), specialization_info)
     }
 
+    /// Applies typed specialization constants to the shader module, and returns a specialized
+    /// version of the module.
+    ///
+    /// This is usually called with a struct generated by the `shader!` macro. Unlike
+    /// [`specialize`], which ignores values for constant IDs that the module doesn't define, this
+    /// returns an error if `constants` provides a value for a constant that doesn't exist in the
+    /// module, so that a mismatch between the struct and the module doesn't silently fall back to
+    /// the default values.
+    ///
+    /// [`specialize`]: Self::specialize
+    #[inline]
+    pub fn specialize_with(
+        self: &Arc<Self>,
+        constants: &impl SpecializationConstants,
+    ) -> Result<Arc<SpecializedShaderModule>, Box<ValidationError>> {
+        let specialization_info: HashMap<_, _> =
+            constants.specialization_info().into_iter().collect();
+
+        for &constant_id in specialization_info.keys() {
+            if !self.specialization_constants.contains_key(&constant_id) {
+                return Err(Box::new(ValidationError {
+                    context: "constants".into(),
+                    problem: format!(
+                        "provides a value for the specialization constant with ID {}, but the \
+                        shader module does not define it",
+                        constant_id,
+                    )
+                    .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        self.specialize(specialization_info)
+    }
+
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
     #[inline]
     pub unsafe fn specialize_unchecked(
@@ -670,6 +795,15 @@ impl ShaderModule {
         }
     }
 
+    /// Equivalent to calling [`specialize`] with empty specialization info,
+    /// and then calling [`SpecializedShaderModule::entry_points`].
+    ///
+    /// [`specialize`]: Self::specialize
+    #[inline]
+    pub fn entry_points(self: &Arc<Self>) -> impl ExactSizeIterator<Item = EntryPoint> {
+        unsafe { self.specialize_unchecked(HashMap::default()).entry_points() }
+    }
+
     /// Equivalent to calling [`specialize`] with empty specialization info,
     /// and then calling [`SpecializedShaderModule::single_entry_point`].
     ///
@@ -848,6 +982,352 @@ impl<'a> ShaderModuleCreateInfo<'a> {
     }
 }
 
+/// An opaque identifier of a shader module, that can be used in place of the module itself when
+/// creating a pipeline.
+///
+/// If the implementation finds a pipeline that was created with the same shader module in its
+/// pipeline cache, it can create the pipeline from the identifier alone, without needing the
+/// SPIR-V code. Identifiers can be stored alongside a [`PipelineCache`], so that they can be
+/// reused in later runs of the application. They are only valid on devices that have the same
+/// [`shader_module_identifier_algorithm_uuid`] as the device that they were obtained from.
+///
+/// [`PipelineCache`]: crate::pipeline::cache::PipelineCache
+/// [`shader_module_identifier_algorithm_uuid`]: crate::device::DeviceProperties::shader_module_identifier_algorithm_uuid
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShaderModuleIdentifier {
+    size: usize,
+    data: [u8; ash::vk::MAX_SHADER_MODULE_IDENTIFIER_SIZE_EXT],
+}
+
+impl ShaderModuleIdentifier {
+    /// The maximum size of a shader module identifier in bytes.
+    pub const MAX_SIZE: usize = ash::vk::MAX_SHADER_MODULE_IDENTIFIER_SIZE_EXT;
+
+    /// Creates a `ShaderModuleIdentifier` from bytes that were previously returned by
+    /// [`as_bytes`](Self::as_bytes).
+    ///
+    /// Returns `None` if `bytes` is longer than [`MAX_SIZE`](Self::MAX_SIZE).
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() > Self::MAX_SIZE {
+            return None;
+        }
+
+        let mut data = [0; Self::MAX_SIZE];
+        data[..bytes.len()].copy_from_slice(bytes);
+
+        Some(Self {
+            size: bytes.len(),
+            data,
+        })
+    }
+
+    /// Returns the bytes of the identifier.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.size]
+    }
+}
+
+impl Debug for ShaderModuleIdentifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.debug_tuple("ShaderModuleIdentifier")
+            .field(&self.as_bytes())
+            .finish()
+    }
+}
+
+/// The device extensions and features that must be enabled on a device, in order to create
+/// shader modules from a set of SPIR-V code.
+///
+/// This is determined from the capabilities and extensions that the SPIR-V code declares, as
+/// well as the SPIR-V version that it uses. It can be used to fill in the `enabled_extensions`
+/// and `enabled_features` of [`DeviceCreateInfo`], so that the enabled extensions and features
+/// don't need to be kept in sync with the shaders by hand.
+///
+/// Only the requirements of the SPIR-V code itself are taken into account. Requirements that are
+/// expressed as device properties, rather than features or extensions, are not checked until the
+/// shader module is created. Any requirements of the rest of the application, such as for the
+/// resources or pipeline state that the shaders are used with, must be added separately.
+///
+/// # Examples
+///
+/// ```
+/// # use vulkano::{
+/// #     device::{Device, DeviceCreateInfo, QueueCreateInfo},
+/// #     shader::ShaderRequirements,
+/// # };
+/// # let physical_device: std::sync::Arc<vulkano::device::physical::PhysicalDevice> = return;
+/// # let vertex_shader_code: &[u32] = return;
+/// # let fragment_shader_code: &[u32] = return;
+/// let requirements =
+///     ShaderRequirements::new(&physical_device, [vertex_shader_code, fragment_shader_code])
+///         .expect("the physical device doesn't support the shaders");
+///
+/// let (device, mut queues) = Device::new(
+///     physical_device,
+///     DeviceCreateInfo {
+///         enabled_extensions: requirements.device_extensions,
+///         enabled_features: requirements.device_features,
+///         queue_create_infos: vec![QueueCreateInfo::default()],
+///         ..Default::default()
+///     },
+/// )
+/// .unwrap();
+/// ```
+///
+/// [`DeviceCreateInfo`]: crate::device::DeviceCreateInfo
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderRequirements {
+    /// The device extensions that must be enabled.
+    ///
+    /// The dependencies of these extensions are not included. They are enabled automatically
+    /// when creating the device.
+    pub device_extensions: DeviceExtensions,
+
+    /// The device features that must be enabled.
+    pub device_features: DeviceFeatures,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for ShaderRequirements {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            device_extensions: DeviceExtensions::empty(),
+            device_features: DeviceFeatures::empty(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+impl ShaderRequirements {
+    /// Returns the device extensions and features that must be enabled in order to create shader
+    /// modules from each element of `codes`, on a device created from `physical_device`.
+    ///
+    /// If a requirement can be met in several ways, then a way that is already met by the API
+    /// version of the physical device, or by an earlier requirement, is preferred. Otherwise, the
+    /// first extension or feature that is supported by the physical device is chosen.
+    ///
+    /// Returns an error if a SPIR-V code can't be parsed, or if it uses something that the
+    /// physical device doesn't support.
+    pub fn new<'a>(
+        physical_device: &PhysicalDevice,
+        codes: impl IntoIterator<Item = &'a [u32]>,
+    ) -> Result<Self, Box<ValidationError>> {
+        let mut requirements = Self::default();
+
+        for (index, code) in codes.into_iter().enumerate() {
+            let spirv = Spirv::new(code).map_err(|err| {
+                Box::new(ValidationError {
+                    context: format!("codes[{}]", index).into(),
+                    problem: format!("error while parsing: {}", err).into(),
+                    ..Default::default()
+                })
+            })?;
+
+            requirements
+                .add_spirv(physical_device, &spirv)
+                .map_err(|err| err.add_context(format!("codes[{}]", index)))?;
+        }
+
+        Ok(requirements)
+    }
+
+    fn add_spirv(
+        &mut self,
+        physical_device: &PhysicalDevice,
+        spirv: &Spirv,
+    ) -> Result<(), Box<ValidationError>> {
+        let spirv_version = Version {
+            patch: 0, // Ignore the patch version
+            ..spirv.version()
+        };
+
+        match spirv_version {
+            Version::V1_0 => (),
+            Version::V1_1 | Version::V1_2 | Version::V1_3 => self.add(
+                physical_device,
+                RequiresOneOf(&[RequiresAllOf(&[Requires::APIVersion(Version::V1_1)])]),
+                vec![SpirvRequirement::APIVersion(Version::V1_1)],
+                || {
+                    format!(
+                        "uses SPIR-V version {}.{}",
+                        spirv_version.major, spirv_version.minor
+                    )
+                },
+            )?,
+            Version::V1_4 => self.add(
+                physical_device,
+                RequiresOneOf(&[
+                    RequiresAllOf(&[Requires::APIVersion(Version::V1_2)]),
+                    RequiresAllOf(&[Requires::DeviceExtension("khr_spirv_1_4")]),
+                ]),
+                vec![
+                    SpirvRequirement::APIVersion(Version::V1_2),
+                    SpirvRequirement::DeviceExtensions(DeviceExtensions {
+                        khr_spirv_1_4: true,
+                        ..DeviceExtensions::empty()
+                    }),
+                ],
+                || {
+                    format!(
+                        "uses SPIR-V version {}.{}",
+                        spirv_version.major, spirv_version.minor
+                    )
+                },
+            )?,
+            Version::V1_5 => self.add(
+                physical_device,
+                RequiresOneOf(&[RequiresAllOf(&[Requires::APIVersion(Version::V1_2)])]),
+                vec![SpirvRequirement::APIVersion(Version::V1_2)],
+                || {
+                    format!(
+                        "uses SPIR-V version {}.{}",
+                        spirv_version.major, spirv_version.minor
+                    )
+                },
+            )?,
+            Version::V1_6 => self.add(
+                physical_device,
+                RequiresOneOf(&[RequiresAllOf(&[Requires::APIVersion(Version::V1_3)])]),
+                vec![SpirvRequirement::APIVersion(Version::V1_3)],
+                || {
+                    format!(
+                        "uses SPIR-V version {}.{}",
+                        spirv_version.major, spirv_version.minor
+                    )
+                },
+            )?,
+            _ => {
+                return Err(Box::new(ValidationError {
+                    problem: format!(
+                        "uses SPIR-V version {}.{}, which is not supported by Vulkan",
+                        spirv_version.major, spirv_version.minor
+                    )
+                    .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        for &capability in spirv
+            .capabilities()
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Capability { capability } => Some(capability),
+                _ => None,
+            })
+        {
+            let (requires_one_of, alternatives) = spirv_capability_requirements(capability)
+                .ok_or_else(|| {
+                    Box::new(ValidationError {
+                        problem: format!(
+                            "uses the SPIR-V capability `{:?}`, which is not supported by Vulkan",
+                            capability,
+                        )
+                        .into(),
+                        ..Default::default()
+                    })
+                })?;
+            self.add(physical_device, requires_one_of, alternatives, || {
+                format!("uses the SPIR-V capability `{:?}`", capability)
+            })?;
+        }
+
+        for extension in spirv
+            .extensions()
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Extension { name } => Some(name.as_str()),
+                _ => None,
+            })
+        {
+            let (requires_one_of, alternatives) = spirv_extension_requirements(extension)
+                .ok_or_else(|| {
+                    Box::new(ValidationError {
+                        problem: format!(
+                            "uses the SPIR-V extension `{}`, which is not supported by Vulkan",
+                            extension,
+                        )
+                        .into(),
+                        ..Default::default()
+                    })
+                })?;
+            self.add(physical_device, requires_one_of, alternatives, || {
+                format!("uses the SPIR-V extension `{}`", extension)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        physical_device: &PhysicalDevice,
+        requires_one_of: RequiresOneOf,
+        alternatives: Vec<SpirvRequirement>,
+        problem: impl FnOnce() -> String,
+    ) -> Result<(), Box<ValidationError>> {
+        if alternatives.is_empty() {
+            return Ok(());
+        }
+
+        let is_met = |alternative: &SpirvRequirement| match alternative {
+            &SpirvRequirement::APIVersion(version) => physical_device.api_version() >= version,
+            SpirvRequirement::DeviceExtensions(extensions) => {
+                self.device_extensions.contains(extensions)
+            }
+            SpirvRequirement::DeviceFeatures(features) => self.device_features.contains(features),
+        };
+
+        if alternatives.iter().any(is_met) {
+            return Ok(());
+        }
+
+        let is_supported = |alternative: &&SpirvRequirement| match alternative {
+            SpirvRequirement::APIVersion(_) => false,
+            SpirvRequirement::DeviceExtensions(extensions) => {
+                physical_device.supported_extensions().contains(extensions)
+            }
+            SpirvRequirement::DeviceFeatures(features) => {
+                physical_device.supported_features().contains(features)
+            }
+        };
+
+        match alternatives.iter().find(is_supported) {
+            Some(SpirvRequirement::DeviceExtensions(extensions)) => {
+                self.device_extensions = self.device_extensions.union(extensions);
+            }
+            Some(SpirvRequirement::DeviceFeatures(features)) => {
+                self.device_features = self.device_features.union(features);
+            }
+            Some(SpirvRequirement::APIVersion(_)) => unreachable!(),
+            None => {
+                return Err(Box::new(ValidationError {
+                    problem: format!(
+                        "{}, but the physical device does not support any of the requirements",
+                        problem(),
+                    )
+                    .into(),
+                    requires_one_of,
+                    ..Default::default()
+                }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// One of the alternative ways to meet a requirement of SPIR-V code.
+enum SpirvRequirement {
+    APIVersion(Version),
+    DeviceExtensions(DeviceExtensions),
+    DeviceFeatures(DeviceFeatures),
+}
+
 /// The value to provide for a specialization constant, when creating a pipeline.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SpecializationConstant {
@@ -977,6 +1457,16 @@ impl From<f64> for SpecializationConstant {
     }
 }
 
+/// A type that provides values for the specialization constants of a shader module.
+///
+/// The `shader!` macro of `vulkano-shaders` generates a struct implementing this trait for each
+/// entry point of a shader that has specialization constants, with one field per constant. It is
+/// passed to [`ShaderModule::specialize_with`].
+pub trait SpecializationConstants {
+    /// Returns the `constant_id` and value of each specialization constant.
+    fn specialization_info(&self) -> Vec<(u32, SpecializationConstant)>;
+}
+
 /// A shader module with specialization constants applied.
 #[derive(Debug)]
 pub struct SpecializedShaderModule {
@@ -1107,6 +1597,21 @@ impl SpecializedShaderModule {
         })
     }
 
+    /// Returns all entry points in the module, in the order in which they are declared.
+    ///
+    /// This is useful for modules that contain many entry points, such as those produced by
+    /// rust-gpu, where the names of the entry points are not necessarily known in advance.
+    #[inline]
+    pub fn entry_points(self: &Arc<Self>) -> impl ExactSizeIterator<Item = EntryPoint> {
+        let module = self.clone();
+
+        (0..self.entry_point_infos.len()).map(move |info_index| EntryPoint {
+            module: module.clone(),
+            id: module.entry_point_infos[info_index].0,
+            info_index,
+        })
+    }
+
     /// Returns information about the entry point if `self` only contains a single entry point,
     /// `None` otherwise.
     #[inline]
@@ -1149,6 +1654,108 @@ pub struct EntryPointInfo {
     pub execution_model: ExecutionModel,
     pub descriptor_binding_requirements: HashMap<(u32, u32), DescriptorBindingRequirements>,
     pub push_constant_requirements: Option<PushConstantRange>,
+
+    /// The names of the descriptor bindings in `descriptor_binding_requirements`.
+    ///
+    /// This is the name of the variable in the shader or, if the variable has no name (such as
+    /// a uniform block without an instance name), the name of its type. Bindings for which neither
+    /// name is available are not included.
+    pub descriptor_binding_names: HashMap<(u32, u32), String>,
+
+    /// The layout of the push constant block that is used by the entry point.
+    pub push_constant_block: Option<BlockInfo>,
+
+    /// The input variables of the entry point.
+    ///
+    /// Members of input blocks are listed individually.
+    pub input_interface: Vec<InterfaceVariableInfo>,
+
+    /// The output variables of the entry point.
+    ///
+    /// Members of output blocks are listed individually.
+    pub output_interface: Vec<InterfaceVariableInfo>,
+}
+
+impl EntryPointInfo {
+    /// Returns the descriptor set and binding number of the descriptor binding with the given
+    /// name, as listed in `descriptor_binding_names`.
+    #[inline]
+    pub fn descriptor_binding_by_name(&self, name: &str) -> Option<(u32, u32)> {
+        self.descriptor_binding_names
+            .iter()
+            .find_map(|(&key, binding_name)| (binding_name == name).then_some(key))
+    }
+}
+
+/// The layout of a block of data that is provided to a shader, such as a push constant block.
+#[derive(Clone, Debug)]
+pub struct BlockInfo {
+    /// The name of the block type, if available.
+    pub name: Option<String>,
+
+    /// The members of the block, in the order they are declared in.
+    pub members: Vec<BlockMemberInfo>,
+}
+
+impl BlockInfo {
+    /// Returns the member with the given name.
+    #[inline]
+    pub fn member(&self, name: &str) -> Option<&BlockMemberInfo> {
+        self.members
+            .iter()
+            .find(|member| member.name.as_deref() == Some(name))
+    }
+}
+
+/// A member of a [`BlockInfo`].
+#[derive(Clone, Debug)]
+pub struct BlockMemberInfo {
+    /// The name of the member, if available.
+    pub name: Option<String>,
+
+    /// The offset in bytes of the member from the start of the block.
+    pub offset: u32,
+
+    /// The size in bytes of the member, or `None` if it is a runtime-sized array.
+    pub size: Option<DeviceSize>,
+}
+
+/// An input or output variable of a shader entry point.
+#[derive(Clone, Debug)]
+pub struct InterfaceVariableInfo {
+    /// The name of the variable, or of the block member, if available.
+    pub name: Option<String>,
+
+    /// The location of the variable, or `None` if it is a built-in.
+    pub location: Option<u32>,
+
+    /// The first component of the location that the variable occupies.
+    pub component: u32,
+
+    /// The index of the variable, used for dual-source blending.
+    pub index: u32,
+
+    /// The built-in that the variable corresponds to, or `None` if it is a user-defined variable.
+    pub built_in: Option<BuiltIn>,
+
+    /// The numeric type of the components of the variable, or `None` if the variable is not made
+    /// up of numeric scalars, vectors and matrices, or arrays of these.
+    pub numeric_type: Option<NumericType>,
+
+    /// The number of components in each element, which is the number of components of a vector,
+    /// or the number of rows of a matrix.
+    pub num_components: u32,
+
+    /// The number of elements of the variable, which is the product of the lengths of all
+    /// array dimensions, multiplied by the number of columns for a matrix. This is 1 for a
+    /// variable that is not an array or matrix.
+    ///
+    /// For stages that receive or write one value per vertex, such as tessellation and geometry
+    /// shaders, the outer per-vertex array is not included.
+    pub num_elements: u32,
+
+    /// Whether the components are 64 bits wide.
+    pub is_64bit: bool,
 }
 
 /// Represents a shader entry point in a shader module.
@@ -1205,6 +1812,11 @@ pub struct DescriptorBindingRequirements {
     /// This is `None` for non-image bindings.
     pub image_scalar_type: Option<NumericType>,
 
+    /// The bit width of the base scalar type required for the format of image views bound to
+    /// this binding. If this is 64, the format must have 64-bit components, otherwise it must
+    /// not. This is `None` for non-image bindings.
+    pub image_scalar_width: Option<u32>,
+
     /// The view type that is required for image views bound to this binding.
     /// This is `None` for non-image bindings.
     pub image_view_type: Option<ImageViewType>,
@@ -1268,6 +1880,7 @@ impl DescriptorBindingRequirements {
             image_format,
             image_multisampled,
             image_scalar_type,
+            image_scalar_width,
             image_view_type,
             stages,
             descriptors,
@@ -1304,6 +1917,15 @@ impl DescriptorBindingRequirements {
             }
         }
 
+        if let (Some(first), Some(second)) = (*image_scalar_width, other.image_scalar_width) {
+            if first != second {
+                return Err(Box::new(ValidationError {
+                    problem: "the descriptors require different scalar widths".into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
         if let (Some(first), Some(second)) = (*image_view_type, other.image_view_type) {
             if first != second {
                 return Err(Box::new(ValidationError {
@@ -1327,6 +1949,7 @@ impl DescriptorBindingRequirements {
         *descriptor_count = (*descriptor_count).max(other.descriptor_count);
         *image_format = image_format.or(other.image_format);
         *image_scalar_type = image_scalar_type.or(other.image_scalar_type);
+        *image_scalar_width = image_scalar_width.or(other.image_scalar_width);
         *image_view_type = image_view_type.or(other.image_view_type);
         *stages |= other.stages;
 
@@ -1551,3 +2174,183 @@ impl From<ShaderStages> for PipelineStages {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ShaderModule, ShaderModuleCreateInfo, ShaderRequirements};
+    use crate::{
+        device::{DeviceExtensions, DeviceFeatures},
+        shader::spirv::ExecutionModel,
+        Version,
+    };
+
+    // Assembles a module that only declares `capabilities` and `extensions`, which is all that
+    // `ShaderRequirements` looks at.
+    fn requirements_module(
+        version: Version,
+        capabilities: &[u32],
+        extensions: &[&str],
+    ) -> Vec<u32> {
+        let mut words = vec![
+            0x07230203,
+            version.major << 16 | version.minor << 8,
+            0,
+            1,
+            0,
+        ];
+
+        for &capability in capabilities {
+            // OpCapability
+            words.extend([2 << 16 | 17, capability]);
+        }
+
+        for extension in extensions {
+            let mut bytes = extension.as_bytes().to_vec();
+            bytes.resize((bytes.len() / 4 + 1) * 4, 0);
+            let name: Vec<u32> = bytes
+                .chunks_exact(4)
+                .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+                .collect();
+
+            // OpExtension
+            words.push(((name.len() as u32 + 1) << 16) | 10);
+            words.extend(name);
+        }
+
+        // OpMemoryModel Logical GLSL450
+        words.extend([3 << 16 | 14, 0, 1]);
+
+        words
+    }
+
+    #[test]
+    fn requirements_none() {
+        let (device, _queue) = gfx_dev_and_queue!();
+
+        // Shader
+        let code = requirements_module(Version::V1_0, &[1], &[]);
+        let requirements = ShaderRequirements::new(device.physical_device(), [&code[..]]).unwrap();
+
+        assert_eq!(requirements.device_extensions, DeviceExtensions::empty());
+        assert_eq!(requirements.device_features, DeviceFeatures::empty());
+    }
+
+    #[test]
+    fn requirements_feature() {
+        let (device, _queue) = gfx_dev_and_queue!();
+        let physical_device = device.physical_device();
+
+        // Shader, Int8
+        let code = requirements_module(Version::V1_0, &[1, 39], &[]);
+        let result = ShaderRequirements::new(physical_device, [&code[..]]);
+
+        if physical_device.supported_features().shader_int8 {
+            let requirements = result.unwrap();
+            assert_eq!(
+                requirements.device_features,
+                DeviceFeatures {
+                    shader_int8: true,
+                    ..DeviceFeatures::empty()
+                }
+            );
+        } else {
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn requirements_extension() {
+        let (device, _queue) = gfx_dev_and_queue!();
+        let physical_device = device.physical_device();
+
+        let code = requirements_module(Version::V1_0, &[1], &["SPV_KHR_integer_dot_product"]);
+        let result = ShaderRequirements::new(physical_device, [&code[..]]);
+
+        // The extension is core in Vulkan 1.3, so the device extension is only needed before
+        // that.
+        if physical_device.api_version() >= Version::V1_3 {
+            assert_eq!(result.unwrap().device_extensions, DeviceExtensions::empty());
+        } else if physical_device
+            .supported_extensions()
+            .khr_shader_integer_dot_product
+        {
+            assert_eq!(
+                result.unwrap().device_extensions,
+                DeviceExtensions {
+                    khr_shader_integer_dot_product: true,
+                    ..DeviceExtensions::empty()
+                }
+            );
+        } else {
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn requirements_invalid() {
+        let (device, _queue) = gfx_dev_and_queue!();
+        let physical_device = device.physical_device();
+
+        let valid = requirements_module(Version::V1_0, &[1], &[]);
+
+        // Not SPIR-V at all.
+        let err =
+            ShaderRequirements::new(physical_device, [&valid[..], &[0u32, 1, 2][..]]).unwrap_err();
+        assert_eq!(err.context, "codes[1]");
+
+        // A SPIR-V version that no Vulkan version supports.
+        let future_version = requirements_module(Version::major_minor(1, 15), &[1], &[]);
+        assert!(ShaderRequirements::new(physical_device, [&future_version[..]]).is_err());
+
+        // A capability that Vulkan doesn't support: Kernel
+        let kernel = requirements_module(Version::V1_0, &[6], &[]);
+        assert!(ShaderRequirements::new(physical_device, [&kernel[..]]).is_err());
+    }
+
+    #[test]
+    fn entry_points() {
+        let (device, _queue) = gfx_dev_and_queue!();
+
+        /*
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %3 "main_a"
+        OpEntryPoint GLCompute %4 "main_b"
+        OpExecutionMode %3 LocalSize 1 1 1
+        OpExecutionMode %4 LocalSize 1 1 1
+        %1 = OpTypeVoid
+        %2 = OpTypeFunction %1
+        %3 = OpFunction %1 None %2
+        %5 = OpLabel
+        OpReturn
+        OpFunctionEnd
+        %4 = OpFunction %1 None %2
+        %6 = OpLabel
+        OpReturn
+        OpFunctionEnd
+        */
+        const MODULE: [u32; 55] = [
+            119734787, 65536, 0, 7, 0, 131089, 1, 196622, 0, 1, 327695, 5, 3, 1852399981, 24927,
+            327695, 5, 4, 1852399981, 25183, 393232, 3, 17, 1, 1, 1, 393232, 4, 17, 1, 1, 1,
+            131091, 1, 196641, 2, 1, 327734, 1, 3, 0, 2, 131320, 5, 65789, 65592, 327734, 1, 4, 0,
+            2, 131320, 6, 65789, 65592,
+        ];
+        let module = unsafe {
+            ShaderModule::new(device.clone(), ShaderModuleCreateInfo::new(&MODULE)).unwrap()
+        };
+
+        assert!(module.single_entry_point().is_none());
+
+        let entry_points: Vec<_> = module.entry_points().collect();
+        assert_eq!(entry_points.len(), 2);
+        assert_eq!(entry_points[0].info().name, "main_a");
+        assert_eq!(entry_points[1].info().name, "main_b");
+        assert!(entry_points.iter().all(|entry_point| matches!(
+            entry_point.info().execution_model,
+            ExecutionModel::GLCompute,
+        )));
+
+        let main_b = module.entry_point("main_b").unwrap();
+        assert_eq!(main_b.info().name, entry_points[1].info().name);
+    }
+}
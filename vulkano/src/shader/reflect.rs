@@ -4,17 +4,26 @@ use super::DescriptorBindingRequirements;
 use crate::{
     descriptor_set::layout::DescriptorType,
     image::view::ImageViewType,
-    pipeline::layout::PushConstantRange,
+    pipeline::{
+        layout::PushConstantRange,
+        shader::inout_interface::{
+            input_output_map, InputOutputData, InputOutputKey, InputOutputUserKey,
+        },
+    },
     shader::{
-        spirv::{Decoration, Dim, Id, Instruction, Spirv, StorageClass},
-        DescriptorIdentifier, DescriptorRequirements, EntryPointInfo, NumericType, ShaderStage,
-        ShaderStages, SpecializationConstant,
+        spirv::{
+            BuiltIn, Decoration, Dim, ExecutionMode, ExecutionModel, Id, Instruction, Spirv,
+            StorageClass,
+        },
+        BlockInfo, BlockMemberInfo, DescriptorIdentifier, DescriptorRequirements, EntryPointInfo,
+        InterfaceVariableInfo, NumericType, ShaderStage, ShaderStages, SpecializationConstant,
     },
     DeviceSize, Version,
 };
 use ahash::{HashMap, HashSet};
 use half::f16;
 use smallvec::{smallvec, SmallVec};
+use std::convert::Infallible;
 
 /// Returns an iterator over all entry points in `spirv`, with information about the entry point.
 #[inline]
@@ -26,7 +35,7 @@ pub fn entry_points(spirv: &Spirv) -> impl Iterator<Item = (Id, EntryPointInfo)>
             execution_model,
             entry_point,
             ref name,
-            ..
+            ref interface,
         } = instruction
         else {
             return None;
@@ -40,12 +49,33 @@ pub fn entry_points(spirv: &Spirv) -> impl Iterator<Item = (Id, EntryPointInfo)>
             stage,
             entry_point,
         );
-        let push_constant_requirements = push_constant_requirements(
+        let (push_constant_variable, push_constant_requirements) = push_constant_requirements(
             &interface_variables.push_constant,
             spirv,
             stage,
             entry_point,
-        );
+        )
+        .unzip();
+
+        let descriptor_binding_names = interface_variables
+            .descriptor_binding
+            .iter()
+            .filter(|(_, variable)| {
+                descriptor_binding_requirements.contains_key(&(variable.set, variable.binding))
+            })
+            .filter_map(|(&variable_id, variable)| {
+                Some((
+                    (variable.set, variable.binding),
+                    variable_name(spirv, variable_id)?,
+                ))
+            })
+            .collect();
+        let push_constant_block =
+            push_constant_variable.map(|variable_id| push_constant_block(spirv, variable_id));
+        let input_interface =
+            interface_variable_infos(spirv, execution_model, interface, StorageClass::Input);
+        let output_interface =
+            interface_variable_infos(spirv, execution_model, interface, StorageClass::Output);
 
         Some((
             entry_point,
@@ -54,6 +84,10 @@ pub fn entry_points(spirv: &Spirv) -> impl Iterator<Item = (Id, EntryPointInfo)>
                 execution_model,
                 descriptor_binding_requirements,
                 push_constant_requirements,
+                descriptor_binding_names,
+                push_constant_block,
+                input_interface,
+                output_interface,
             },
         ))
     })
@@ -674,23 +708,37 @@ fn descriptor_binding_requirements_of(spirv: &Spirv, variable_id: Id) -> Descrip
                 );
                 reqs.image_format = image_format.into();
                 reqs.image_multisampled = ms != 0;
-                reqs.image_scalar_type = Some(match *spirv.id(sampled_type).instruction() {
-                    Instruction::TypeInt {
-                        width, signedness, ..
-                    } => {
-                        assert_eq!(width, 32); // TODO: 64-bit components
-                        match signedness {
-                            0 => NumericType::Uint,
-                            1 => NumericType::Int,
-                            _ => unreachable!(),
+                let (image_scalar_type, image_scalar_width) =
+                    match *spirv.id(sampled_type).instruction() {
+                        Instruction::TypeInt {
+                            width, signedness, ..
+                        } => {
+                            assert!(
+                                width == 32 || width == 64,
+                                "Vulkan requires that the Sampled Type of an OpTypeImage is a \
+                                32-bit or 64-bit integer, or a 32-bit float",
+                            );
+                            let numeric_type = match signedness {
+                                0 => NumericType::Uint,
+                                1 => NumericType::Int,
+                                _ => unreachable!(),
+                            };
+
+                            (numeric_type, width)
                         }
-                    }
-                    Instruction::TypeFloat { width, .. } => {
-                        assert_eq!(width, 32); // TODO: 64-bit components
-                        NumericType::Float
-                    }
-                    _ => unreachable!(),
-                });
+                        Instruction::TypeFloat { width, .. } => {
+                            assert_eq!(
+                                width, 32,
+                                "Vulkan requires that the Sampled Type of an OpTypeImage is a \
+                                32-bit or 64-bit integer, or a 32-bit float",
+                            );
+
+                            (NumericType::Float, width)
+                        }
+                        _ => unreachable!(),
+                    };
+                reqs.image_scalar_type = Some(image_scalar_type);
+                reqs.image_scalar_width = Some(image_scalar_width);
 
                 match dim {
                     Dim::SubpassData => {
@@ -861,13 +909,13 @@ fn push_constant_requirements_of(spirv: &Spirv, pointer_type_id: Id) -> PushCons
     }
 }
 
-/// Extracts the `PushConstantRange` from `spirv`.
+/// Extracts the `PushConstantRange` from `spirv`, along with the push constant variable.
 fn push_constant_requirements(
     global: &HashMap<Id, PushConstantRange>,
     spirv: &Spirv,
     stage: ShaderStage,
     function_id: Id,
-) -> Option<PushConstantRange> {
+) -> Option<(Id, PushConstantRange)> {
     fn find_variables_used(
         function_id: Id,
         global: &HashMap<Id, PushConstantRange>,
@@ -951,11 +999,261 @@ fn push_constant_requirements(
     let variable_id = variables.into_iter().next()?;
     let mut push_constant_range = global.get(&variable_id).copied().unwrap();
     push_constant_range.stages = stage.into();
-    Some(push_constant_range)
+    Some((variable_id, push_constant_range))
+}
+
+/// Returns the layout of the push constant block of `variable_id`.
+fn push_constant_block(spirv: &Spirv, variable_id: Id) -> BlockInfo {
+    let pointer_type_id = match *spirv.id(variable_id).instruction() {
+        Instruction::Variable { result_type_id, .. } => result_type_id,
+        _ => unreachable!(),
+    };
+    let struct_type_id = match *spirv.id(pointer_type_id).instruction() {
+        Instruction::TypePointer { ty, .. } => ty,
+        _ => unreachable!(),
+    };
+    let struct_type_id_info = spirv.id(struct_type_id);
+    let member_types = match struct_type_id_info.instruction() {
+        Instruction::TypeStruct { member_types, .. } => member_types,
+        _ => unreachable!(),
+    };
+
+    let mut end_of_previous = 0;
+    let members = member_types
+        .iter()
+        .zip(struct_type_id_info.members())
+        .map(|(&member_type, member_info)| {
+            let offset = member_info
+                .decorations()
+                .iter()
+                .find_map(|instruction| match *instruction {
+                    Instruction::MemberDecorate {
+                        decoration: Decoration::Offset { byte_offset },
+                        ..
+                    } => Some(byte_offset),
+                    _ => None,
+                })
+                .unwrap_or(end_of_previous);
+            let size = size_of_type(spirv, member_type);
+            end_of_previous = offset + size.unwrap_or(0) as u32;
+
+            BlockMemberInfo {
+                name: member_info
+                    .names()
+                    .iter()
+                    .find_map(|instruction| match *instruction {
+                        Instruction::MemberName { ref name, .. } => Some(name.clone()),
+                        _ => None,
+                    })
+                    .filter(|name| !name.is_empty()),
+                offset,
+                size,
+            }
+        })
+        .collect();
+
+    BlockInfo {
+        name: name_of(spirv, struct_type_id),
+        members,
+    }
+}
+
+/// Returns the input or output variables in `interface`, depending on `storage_class`.
+fn interface_variable_infos(
+    spirv: &Spirv,
+    execution_model: ExecutionModel,
+    interface: &[Id],
+    storage_class: StorageClass,
+) -> Vec<InterfaceVariableInfo> {
+    let mut result = Vec::new();
+
+    for &variable_id in interface {
+        input_output_map(
+            spirv,
+            execution_model,
+            variable_id,
+            storage_class,
+            |key, data| -> Result<(), Infallible> {
+                let InputOutputData {
+                    variable_id,
+                    block,
+                    type_id,
+                    ..
+                } = data;
+
+                let name = match block {
+                    Some(block) => spirv.id(block.type_id).members()[block.member_index]
+                        .names()
+                        .iter()
+                        .find_map(|instruction| match *instruction {
+                            Instruction::MemberName { ref name, .. } => Some(name.clone()),
+                            _ => None,
+                        })
+                        .filter(|name| !name.is_empty()),
+                    None => name_of(spirv, variable_id),
+                };
+                let (location, component, index, built_in) = match key {
+                    InputOutputKey::User(InputOutputUserKey {
+                        location,
+                        component,
+                        index,
+                    }) => (Some(location), component, index, None),
+                    InputOutputKey::BuiltIn(built_in) => (None, 0, 0, Some(built_in)),
+                };
+                let (numeric_type, num_components, num_elements, is_64bit) =
+                    match interface_type_shape(spirv, type_id) {
+                        Some((numeric_type, num_components, num_elements, is_64bit)) => {
+                            (Some(numeric_type), num_components, num_elements, is_64bit)
+                        }
+                        None => (None, 0, 0, false),
+                    };
+
+                result.push(InterfaceVariableInfo {
+                    name,
+                    location,
+                    component,
+                    index,
+                    built_in,
+                    numeric_type,
+                    num_components,
+                    num_elements,
+                    is_64bit,
+                });
+
+                Ok(())
+            },
+        )
+        .unwrap();
+    }
+
+    result
+}
+
+/// Returns the numeric type, the number of components, the number of elements and whether the
+/// components are 64-bit, for a scalar, vector, matrix or array type.
+fn interface_type_shape(spirv: &Spirv, type_id: Id) -> Option<(NumericType, u32, u32, bool)> {
+    match *spirv.id(type_id).instruction() {
+        Instruction::TypeInt {
+            width, signedness, ..
+        } => {
+            let numeric_type = if signedness == 1 {
+                NumericType::Int
+            } else {
+                NumericType::Uint
+            };
+
+            Some((numeric_type, 1, 1, width == 64))
+        }
+        Instruction::TypeFloat { width, .. } => Some((NumericType::Float, 1, 1, width == 64)),
+        Instruction::TypeVector {
+            component_type,
+            component_count,
+            ..
+        } => interface_type_shape(spirv, component_type)
+            .map(|(numeric_type, _, _, is_64bit)| (numeric_type, component_count, 1, is_64bit)),
+        Instruction::TypeMatrix {
+            column_type,
+            column_count,
+            ..
+        } => interface_type_shape(spirv, column_type).map(
+            |(numeric_type, num_components, _, is_64bit)| {
+                (numeric_type, num_components, column_count, is_64bit)
+            },
+        ),
+        Instruction::TypeArray {
+            element_type,
+            length,
+            ..
+        } => {
+            let length = get_constant(spirv, length)? as u32;
+
+            interface_type_shape(spirv, element_type).map(
+                |(numeric_type, num_components, num_elements, is_64bit)| {
+                    (
+                        numeric_type,
+                        num_components,
+                        num_elements * length,
+                        is_64bit,
+                    )
+                },
+            )
+        }
+        _ => None,
+    }
+}
+
+/// Returns the name of `id`, if it has a name that is not empty.
+fn name_of(spirv: &Spirv, id: Id) -> Option<String> {
+    spirv
+        .id(id)
+        .names()
+        .iter()
+        .find_map(|instruction| match *instruction {
+            Instruction::Name { ref name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .filter(|name| !name.is_empty())
+}
+
+/// Returns the name of the variable `variable_id`, or if it has no name, the name of the type
+/// that it points to, ignoring arrays.
+fn variable_name(spirv: &Spirv, variable_id: Id) -> Option<String> {
+    name_of(spirv, variable_id).or_else(|| {
+        let mut type_id = match *spirv.id(variable_id).instruction() {
+            Instruction::Variable { result_type_id, .. } => {
+                match *spirv.id(result_type_id).instruction() {
+                    Instruction::TypePointer { ty, .. } => ty,
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+
+        while let Instruction::TypeArray { element_type, .. }
+        | Instruction::TypeRuntimeArray { element_type, .. } =
+            *spirv.id(type_id).instruction()
+        {
+            type_id = element_type;
+        }
+
+        name_of(spirv, type_id)
+    })
+}
+
+/// Extracts the names of the specialization constants from `spirv`.
+pub fn specialization_constant_names(spirv: &Spirv) -> HashMap<u32, String> {
+    spirv
+        .constants()
+        .iter()
+        .filter_map(|instruction| match *instruction {
+            Instruction::SpecConstantFalse { result_id, .. }
+            | Instruction::SpecConstantTrue { result_id, .. }
+            | Instruction::SpecConstant { result_id, .. } => {
+                let constant_id =
+                    spirv
+                        .id(result_id)
+                        .decorations()
+                        .iter()
+                        .find_map(|instruction| match *instruction {
+                            Instruction::Decorate {
+                                decoration:
+                                    Decoration::SpecId {
+                                        specialization_constant_id,
+                                    },
+                                ..
+                            } => Some(specialization_constant_id),
+                            _ => None,
+                        })?;
+
+                Some((constant_id, name_of(spirv, result_id)?))
+            }
+            _ => None,
+        })
+        .collect()
 }
 
 /// Extracts the `SpecializationConstant` map from `spirv`.
-pub(super) fn specialization_constants(spirv: &Spirv) -> HashMap<u32, SpecializationConstant> {
+pub fn specialization_constants(spirv: &Spirv) -> HashMap<u32, SpecializationConstant> {
     let get_constant_id = |result_id| {
         spirv
             .id(result_id)
@@ -1041,6 +1339,106 @@ pub(super) fn specialization_constants(spirv: &Spirv) -> HashMap<u32, Specializa
         .collect()
 }
 
+/// Returns the constant IDs of the specialization constants that are used by the entry point
+/// `entry_point`.
+///
+/// A specialization constant is used if it's referenced, directly or through other constants or
+/// types, by the interface of the entry point, by a function that the entry point calls, or by the
+/// workgroup size of the entry point.
+pub fn entry_point_specialization_constants(spirv: &Spirv, entry_point: Id) -> HashSet<u32> {
+    let mut pending: Vec<Id> = vec![entry_point];
+
+    for instruction in spirv.entry_points() {
+        if let Instruction::EntryPoint {
+            execution_model,
+            entry_point: id,
+            ref interface,
+            ..
+        } = *instruction
+        {
+            if id != entry_point {
+                continue;
+            }
+
+            pending.extend(interface);
+
+            if matches!(
+                execution_model,
+                ExecutionModel::GLCompute
+                    | ExecutionModel::Kernel
+                    | ExecutionModel::TaskNV
+                    | ExecutionModel::MeshNV
+                    | ExecutionModel::TaskEXT
+                    | ExecutionModel::MeshEXT
+            ) {
+                pending.extend(spirv.decorations().iter().filter_map(|instruction| {
+                    match *instruction {
+                        Instruction::Decorate {
+                            target,
+                            decoration:
+                                Decoration::BuiltIn {
+                                    built_in: BuiltIn::WorkgroupSize,
+                                },
+                        } => Some(target),
+                        _ => None,
+                    }
+                }));
+            }
+        }
+    }
+
+    for instruction in spirv.function(entry_point).execution_modes() {
+        if let Instruction::ExecutionModeId {
+            mode:
+                ExecutionMode::LocalSizeId {
+                    x_size,
+                    y_size,
+                    z_size,
+                },
+            ..
+        } = *instruction
+        {
+            pending.extend([x_size, y_size, z_size]);
+        }
+    }
+
+    let mut visited = HashSet::default();
+    let mut constant_ids = HashSet::default();
+
+    while let Some(id) = pending.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+
+        let id_info = spirv.id(id);
+
+        constant_ids.extend(id_info.decorations().iter().find_map(
+            |instruction| match *instruction {
+                Instruction::Decorate {
+                    decoration:
+                        Decoration::SpecId {
+                            specialization_constant_id,
+                        },
+                    ..
+                } => Some(specialization_constant_id),
+                _ => None,
+            },
+        ));
+
+        let instructions = match spirv.functions().get(&id) {
+            Some(function) => function.instructions(),
+            None => std::slice::from_ref(id_info.instruction()),
+        };
+
+        for instruction in instructions {
+            pending.extend(instruction.result_type_id());
+            pending.extend(instruction.operand_ids());
+        }
+    }
+
+    constant_ids
+}
+
 /// Returns the size of a type, or `None` if its size cannot be determined.
 pub(crate) fn size_of_type(spirv: &Spirv, id: Id) -> Option<DeviceSize> {
     let id_info = spirv.id(id);
@@ -1356,6 +1754,12 @@ mod tests {
                 size: 8,
             })
         );
+        let push_constant_block = main_cs.push_constant_block.as_ref().unwrap();
+        assert_eq!(push_constant_block.name.as_deref(), Some("PushCS"));
+        assert_eq!(push_constant_block.members.len(), 2);
+        let member_b = push_constant_block.member("b").unwrap();
+        assert_eq!(member_b.offset, 4);
+        assert_eq!(member_b.size, Some(4));
         let main_fs = &entry_points["main_fs"];
         assert_eq!(
             main_fs.push_constant_requirements,
@@ -1,16 +1,20 @@
 use super::{PresentMode, Swapchain};
 use crate::{
     buffer::Buffer,
-    device::{Device, DeviceOwned, Queue},
+    device::{Device, DeviceOwned, FrameBoundary, Queue},
     image::{Image, ImageLayout},
     sync::{
         fence::Fence,
-        future::{queue_present, AccessCheckError, AccessError, GpuFuture, SubmitAnyBuilder},
+        future::{
+            queue_present, submit_signal_semaphore, AccessCheckError, AccessError, GpuFuture,
+            SubmitAnyBuilder,
+        },
         semaphore::{Semaphore, SemaphoreType},
     },
     DeviceSize, Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, VulkanError,
     VulkanObject,
 };
+use parking_lot::Mutex;
 use smallvec::smallvec;
 use std::{
     fmt::Debug,
@@ -127,6 +131,12 @@ impl AcquireNextImageInfo {
 /// The second field in the tuple in the Ok result is a bool represent if the acquisition was
 /// suboptimal. In this case the acquired image is still usable, but the swapchain should be
 /// recreated as the Surface's properties no longer match the swapchain.
+///
+/// The acquire signals both a semaphore, which the operations that are executed after the
+/// returned future wait on, and a fence. The fence makes it possible to know on the CPU side when
+/// the image has actually been acquired, without submitting anything to a queue, by using
+/// [`SwapchainAcquireFuture::wait`], [`SwapchainAcquireFuture::is_acquired`] or
+/// [`SwapchainAcquireFuture::fence`].
 pub fn acquire_next_image(
     swapchain: Arc<Swapchain>,
     timeout: Option<Duration>,
@@ -262,6 +272,25 @@ impl SwapchainAcquireFuture {
             None => Ok(()),
         }
     }
+
+    /// Returns whether the swapchain image has been acquired, without blocking.
+    #[inline]
+    pub fn is_acquired(&self) -> Result<bool, VulkanError> {
+        match &self.fence {
+            Some(fence) => fence.is_signaled(),
+            None => Ok(true),
+        }
+    }
+
+    /// Returns the fence that is signaled once the swapchain image has been acquired, or `None`
+    /// if it's already known to have been acquired.
+    ///
+    /// This can be used to wait for the acquire together with other fences, using
+    /// [`Fence::multi_wait`].
+    #[inline]
+    pub fn fence(&self) -> Option<&Arc<Fence>> {
+        self.fence.as_ref()
+    }
 }
 
 unsafe impl GpuFuture for SwapchainAcquireFuture {
@@ -380,6 +409,21 @@ unsafe impl DeviceOwned for SwapchainAcquireFuture {
 /// Presents an image on the screen.
 ///
 /// The actual behavior depends on the present mode that you passed when creating the swapchain.
+///
+/// `queue` doesn't need to be the queue that `before` is executed on. Some devices only support
+/// presentation on a queue family that doesn't support graphics operations, in which case the
+/// image must be rendered on one queue and presented on another. If the queues differ, a
+/// semaphore is signaled after `before`, and the present operation waits on it.
+///
+/// If the queues also belong to different queue families, the queue family that presents must
+/// be allowed to access the image. This is the case if the swapchain was created with an
+/// [`image_sharing`] that is concurrent between the two queue families, which
+/// [`Sharing::from_queue_family_indices`] returns. Otherwise, the ownership of the image must be
+/// transferred to the queue family that presents before presenting it, for example with
+/// [`GpuFuture::then_execute_ownership_transfer`].
+///
+/// [`image_sharing`]: super::SwapchainCreateInfo::image_sharing
+/// [`Sharing::from_queue_family_indices`]: crate::sync::Sharing::from_queue_family_indices
 pub fn present<F>(
     before: F,
     queue: Arc<Queue>,
@@ -397,8 +441,12 @@ where
     // always returns false anyway (by design), we don't need to do it.
     assert!(before.check_image_access(&swapchain_image, ImageLayout::PresentSrc, true, &queue).is_ok());         // TODO: return error instead*/
 
+    let queue_change = !before.queue_change_allowed() && before.queue().as_ref() != Some(&queue);
+
     PresentFuture {
         previous: before,
+        queue_change,
+        wait_semaphore: Mutex::new(None),
         queue,
         swapchain_info,
         flushed: AtomicBool::new(false),
@@ -419,6 +467,18 @@ pub struct PresentInfo {
     /// The default value is empty.
     pub swapchains: Vec<SwapchainPresentInfo>,
 
+    /// Information about the frame that the present operations belong to, for capture tools and
+    /// profilers.
+    ///
+    /// If this is `Some`, the [`ext_frame_boundary`] extension and the [`frame_boundary`]
+    /// feature must be enabled on the device.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`ext_frame_boundary`]: crate::device::DeviceExtensions::ext_frame_boundary
+    /// [`frame_boundary`]: crate::device::DeviceFeatures::frame_boundary
+    pub frame_boundary: Option<FrameBoundary>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -428,6 +488,7 @@ impl Default for PresentInfo {
         Self {
             wait_semaphores: Vec::new(),
             swapchains: Vec::new(),
+            frame_boundary: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -438,6 +499,7 @@ impl PresentInfo {
         let &Self {
             ref wait_semaphores,
             swapchains: ref swapchain_infos,
+            ref frame_boundary,
             _ne: _,
         } = self;
 
@@ -496,6 +558,12 @@ impl PresentInfo {
             }
         }
 
+        if let Some(frame_boundary) = frame_boundary {
+            frame_boundary
+                .validate(device)
+                .map_err(|err| err.add_context("frame_boundary"))?;
+        }
+
         Ok(())
     }
 }
@@ -777,6 +845,10 @@ where
     P: GpuFuture,
 {
     previous: P,
+    // True if `previous` is executed on a different queue, and must signal `wait_semaphore`.
+    queue_change: bool,
+    // The semaphore that is signaled after `previous`, once it has been submitted.
+    wait_semaphore: Mutex<Option<Arc<Semaphore>>>,
     queue: Arc<Queue>,
     swapchain_info: SwapchainPresentInfo,
     // True if `flush()` has been called on the future, which means that the present command has
@@ -831,12 +903,28 @@ where
             swapchain_info.present_regions = Default::default();
         }
 
-        let _queue = self.previous.queue();
-
         // TODO: if the swapchain image layout is not PRESENT, should add a transition command
         // buffer
 
-        Ok(match self.previous.build_submission()? {
+        let previous_submission = if self.queue_change {
+            let mut wait_semaphore = self.wait_semaphore.lock();
+
+            let semaphore = match &*wait_semaphore {
+                Some(semaphore) => semaphore.clone(),
+                None => {
+                    let semaphore = Arc::new(Semaphore::from_pool(device.clone())?);
+                    submit_signal_semaphore(&self.previous, &semaphore)?;
+
+                    wait_semaphore.insert(semaphore).clone()
+                }
+            };
+
+            SubmitAnyBuilder::SemaphoresWait(smallvec![semaphore])
+        } else {
+            self.previous.build_submission()?
+        };
+
+        Ok(match previous_submission {
             SubmitAnyBuilder::Empty => SubmitAnyBuilder::QueuePresent(PresentInfo {
                 swapchains: vec![self.swapchain_info.clone()],
                 ..Default::default()
@@ -965,10 +1053,13 @@ where
     }
 
     fn queue(&self) -> Option<Arc<Queue>> {
-        debug_assert!(match self.previous.queue() {
-            None => true,
-            Some(q) => q == self.queue,
-        });
+        debug_assert!(
+            self.queue_change
+                || match self.previous.queue() {
+                    None => true,
+                    Some(q) => q == self.queue,
+                }
+        );
 
         Some(self.queue.clone())
     }
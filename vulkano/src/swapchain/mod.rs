@@ -420,6 +420,17 @@ impl Swapchain {
     }
 
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                image_extent = ?create_info.image_extent,
+                min_image_count = create_info.min_image_count,
+            ),
+        )
+    )]
     #[inline]
     pub unsafe fn new_unchecked(
         device: Arc<Device>,
@@ -473,6 +484,17 @@ impl Swapchain {
     }
 
     #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                image_extent = ?create_info.image_extent,
+                min_image_count = create_info.min_image_count,
+            ),
+        )
+    )]
     pub unsafe fn recreate_unchecked(
         self: &Arc<Self>,
         create_info: SwapchainCreateInfo,
@@ -528,7 +550,7 @@ impl Swapchain {
             .map_err(|err| err.add_context("create_info"))?;
 
         let &SwapchainCreateInfo {
-            flags: _,
+            flags,
             min_image_count,
             image_format,
             image_view_formats: _,
@@ -648,6 +670,18 @@ impl Swapchain {
                 })?
         };
 
+        if flags.intersects(SwapchainCreateFlags::PROTECTED)
+            && !surface_capabilities.supports_protected
+        {
+            return Err(Box::new(ValidationError {
+                problem: "`create_info.flags` contains `SwapchainCreateFlags::PROTECTED`, but \
+                    the `supports_protected` value of the capabilities of `surface` is `false`"
+                    .into(),
+                vuids: &["VUID-VkSwapchainCreateInfoKHR-flags-03187"],
+                ..Default::default()
+            }));
+        }
+
         if surface_capabilities
             .max_image_count
             .map_or(false, |c| min_image_count > c)
@@ -1861,7 +1895,15 @@ pub struct SwapchainCreateInfo {
 
     /// The number of array layers of the created images.
     ///
+    /// Values greater than 1 are used for stereoscopic presentation, where each layer holds the
+    /// view of one eye. The layers can be rendered to all at once using multiview, with a view
+    /// created by [`ImageView::new_default`], or separately, with the views created by
+    /// [`ImageView::new_per_array_layer`].
+    ///
     /// The default value is `1`.
+    ///
+    /// [`ImageView::new_default`]: crate::image::view::ImageView::new_default
+    /// [`ImageView::new_per_array_layer`]: crate::image::view::ImageView::new_per_array_layer
     pub image_array_layers: u32,
 
     /// How the created images will be used.
@@ -2342,12 +2384,17 @@ vulkan_bitflags! {
         RequiresAllOf([DeviceExtension(khr_device_group)]),
     ]),*/
 
-    /* TODO: enable
     /// Creates swapchain images with the [`ImageCreateFlags::PROTECTED`] flag.
+    ///
+    /// The surface must support protected swapchains, as reported by
+    /// [`SurfaceCapabilities::supports_protected`]. This requires the
+    /// [`khr_surface_protected_capabilities`] extension to be enabled on the instance.
+    ///
+    /// [`khr_surface_protected_capabilities`]: crate::instance::InstanceExtensions::khr_surface_protected_capabilities
     PROTECTED = PROTECTED
     RequiresOneOf([
         RequiresAllOf([APIVersion(V1_1)]),
-    ]),*/
+    ]),
 
     /// Creates swapchain images with both the [`ImageCreateFlags::MUTABLE_FORMAT`] and
     /// [`ImageCreateFlags::EXTENDED_USAGE`] flags.
@@ -2374,10 +2421,9 @@ impl From<SwapchainCreateFlags> for ImageCreateFlags {
             result |= ImageCreateFlags::SPLIT_INSTANCE_BIND_REGIONS;
         } */
 
-        /* TODO: enable
         if flags.intersects(SwapchainCreateFlags::PROTECTED) {
             result |= ImageCreateFlags::PROTECTED;
-        } */
+        }
 
         if flags.intersects(SwapchainCreateFlags::MUTABLE_FORMAT) {
             result |= ImageCreateFlags::MUTABLE_FORMAT | ImageCreateFlags::EXTENDED_USAGE;
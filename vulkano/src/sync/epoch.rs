@@ -0,0 +1,533 @@
+//! Tracking resource lifetimes per frame epoch, instead of per command.
+//!
+//! [`RecordingCommandBuffer`] keeps every resource that a command uses alive by cloning it into
+//! the command buffer, and registers every use for automatic synchronization. When recording
+//! thousands of commands per frame, this bookkeeping can take up a large part of the CPU time.
+//!
+//! An [`EpochTracker`] offers a coarser alternative. Work is grouped into *epochs*, usually one
+//! per frame, and each epoch is completed when a timeline semaphore owned by the tracker reaches
+//! the value of the epoch. Instead of being tracked per command, resources are retired against an
+//! epoch once, and they are kept alive until that epoch has completed. Together with
+//! [`RawRecordingCommandBuffer`], which doesn't track resources at all, this allows recording
+//! commands without any per-command reference counting.
+//!
+//! Because the tracker can't know which resources a command buffer uses, synchronization and
+//! retiring every used resource is left to the user. The semaphore can be signaled by a
+//! [`SubmitInfo`], or by any other submission that the user makes.
+//!
+//! The tracker is only a building block for code that records with [`RawRecordingCommandBuffer`].
+//! [`RecordingCommandBuffer`] and the [`GpuFuture`] machinery still keep resources alive per
+//! command, and don't use epochs, because automatic synchronization needs to know every resource
+//! that every command uses. The `epoch_tracking` benchmark of vulkano compares the two ways of
+//! recording.
+//!
+//! # Examples
+//!
+//! ```
+//! # use vulkano::{buffer::Subbuffer, command_buffer::SubmitInfo, sync::epoch::EpochTracker};
+//! # fn example(tracker: &EpochTracker, buffer: Subbuffer<[u8]>) {
+//! let epoch = tracker.next_epoch();
+//!
+//! // Record commands that use `buffer`, without tracking it per command. Then keep it alive
+//! // until the GPU has finished the epoch.
+//! tracker.retire(epoch, buffer);
+//!
+//! // The last submission of the epoch signals the semaphore of the tracker.
+//! let submit_info = SubmitInfo {
+//!     signal_semaphores: vec![tracker.signal_semaphore_info(epoch)],
+//!     ..Default::default()
+//! };
+//! # drop(submit_info);
+//!
+//! // Later, for example at the start of the next frame, drop everything that the GPU is done
+//! // with.
+//! tracker.collect().unwrap();
+//! # }
+//! ```
+//!
+//! # Deletion queues
+//!
+//! When the point that the GPU has reached is already known in another way, such as a frame
+//! counter that advances when the fence of a frame is waited on, or a timeline semaphore that the
+//! application signals itself, a [`DeletionQueue`] can be used instead. It keeps objects alive
+//! until a value that the application chooses has been reached, and leaves it to the application
+//! to say which value has been reached.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use vulkano::{buffer::Buffer, sync::epoch::DeletionQueue};
+//! # fn example(old_buffer: Arc<Buffer>, frame: u64, completed_frame: u64) {
+//! let mut deletion_queue = DeletionQueue::new();
+//!
+//! // The buffer may still be used by the GPU for the current frame.
+//! deletion_queue.defer(frame, old_buffer);
+//!
+//! // Later, once it's known that the GPU has finished `completed_frame`.
+//! deletion_queue.collect(completed_frame);
+//! # }
+//! ```
+//!
+//! [`RecordingCommandBuffer`]: crate::command_buffer::RecordingCommandBuffer
+//! [`RawRecordingCommandBuffer`]: crate::command_buffer::sys::RawRecordingCommandBuffer
+//! [`SubmitInfo`]: crate::command_buffer::SubmitInfo
+//! [`GpuFuture`]: crate::sync::GpuFuture
+
+use super::semaphore::{Semaphore, SemaphoreCreateInfo, SemaphoreType, SemaphoreWaitInfo};
+use crate::{
+    command_buffer::SemaphoreSubmitInfo,
+    device::{Device, DeviceOwned},
+    Validated, VulkanError,
+};
+use parking_lot::Mutex;
+use std::{any::Any, collections::VecDeque, mem, sync::Arc, time::Duration};
+
+/// Keeps objects alive until the GPU has completed an epoch.
+///
+/// See the [module-level documentation](self) for more information.
+///
+/// # Dropping
+///
+/// When the tracker is dropped, it blocks until the GPU is done with all the objects that it still
+/// keeps alive, and then drops them. If every one of them was retired against an epoch that
+/// [`signal_semaphore_info`] was called for, this waits for the semaphore to reach the last of
+/// those epochs. Otherwise, the semaphore can't tell when the GPU is done with them, and this waits
+/// for the whole device to become idle instead.
+///
+/// **The objects are leaked** if waiting fails with an error other than
+/// [`VulkanError::DeviceLost`], because it's then unknown whether the GPU is still using them.
+///
+/// [`signal_semaphore_info`]: Self::signal_semaphore_info
+#[derive(Debug)]
+pub struct EpochTracker {
+    semaphore: Arc<Semaphore>,
+    state: Mutex<EpochTrackerState>,
+}
+
+#[derive(Debug, Default)]
+struct EpochTrackerState {
+    // The value of the most recently started epoch.
+    current_epoch: u64,
+    // The highest epoch that `signal_semaphore_info` was called for.
+    max_signaled_epoch: u64,
+    // Objects that are kept alive, in increasing order of epoch.
+    retired: VecDeque<(u64, Vec<Box<dyn Any + Send + Sync>>)>,
+}
+
+impl EpochTracker {
+    /// Creates a new `EpochTracker`.
+    ///
+    /// The [`timeline_semaphore`](crate::device::DeviceFeatures::timeline_semaphore) feature must
+    /// be enabled on the device.
+    pub fn new(device: Arc<Device>) -> Result<EpochTracker, Validated<VulkanError>> {
+        let semaphore = Arc::new(Semaphore::new(
+            device,
+            SemaphoreCreateInfo {
+                semaphore_type: SemaphoreType::Timeline,
+                initial_value: 0,
+                ..Default::default()
+            },
+        )?);
+
+        Ok(EpochTracker {
+            semaphore,
+            state: Mutex::new(Default::default()),
+        })
+    }
+
+    /// Returns the timeline semaphore that is signaled when an epoch completes.
+    #[inline]
+    pub fn semaphore(&self) -> &Arc<Semaphore> {
+        &self.semaphore
+    }
+
+    /// Starts a new epoch, and returns it.
+    ///
+    /// The epoch completes when the semaphore of the tracker is signaled with the value of the
+    /// epoch, which is usually done by adding [`signal_semaphore_info`] to the last submission
+    /// that belongs to the epoch. Epochs must be signaled in the order that they were started.
+    ///
+    /// [`signal_semaphore_info`]: Self::signal_semaphore_info
+    #[inline]
+    pub fn next_epoch(&self) -> Epoch {
+        let mut state = self.state.lock();
+        state.current_epoch += 1;
+
+        Epoch(state.current_epoch)
+    }
+
+    /// Returns the most recently started epoch, or `None` if no epoch has been started yet.
+    #[inline]
+    pub fn current_epoch(&self) -> Option<Epoch> {
+        let state = self.state.lock();
+
+        (state.current_epoch != 0).then_some(Epoch(state.current_epoch))
+    }
+
+    /// Returns a `SemaphoreSubmitInfo` that signals the semaphore of the tracker with the value of
+    /// `epoch`, completing it once the submission has finished executing.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `epoch` was not started by this tracker.
+    pub fn signal_semaphore_info(&self, epoch: Epoch) -> SemaphoreSubmitInfo {
+        let mut state = self.state.lock();
+        assert!(epoch.0 <= state.current_epoch);
+        state.max_signaled_epoch = state.max_signaled_epoch.max(epoch.0);
+
+        SemaphoreSubmitInfo {
+            value: epoch.0,
+            ..SemaphoreSubmitInfo::new(self.semaphore.clone())
+        }
+    }
+
+    /// Keeps `object` alive until `epoch` has completed.
+    ///
+    /// The object is dropped by the first call to [`collect`] after that.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `epoch` was not started by this tracker.
+    ///
+    /// [`collect`]: Self::collect
+    pub fn retire(&self, epoch: Epoch, object: impl Send + Sync + 'static) {
+        let mut state = self.state.lock();
+        assert!(epoch.0 <= state.current_epoch);
+
+        let object = Box::new(object);
+
+        insert_retired(&mut state.retired, epoch.0, object);
+    }
+
+    /// Returns the most recent epoch that the GPU has completed.
+    #[inline]
+    pub fn completed_epoch(&self) -> Result<u64, Validated<VulkanError>> {
+        self.semaphore.counter_value()
+    }
+
+    /// Drops all objects that were retired against epochs that the GPU has completed.
+    ///
+    /// Returns the number of objects that were dropped.
+    pub fn collect(&self) -> Result<usize, Validated<VulkanError>> {
+        let completed_epoch = self.completed_epoch()?;
+        let collected: Vec<_> = {
+            let mut state = self.state.lock();
+            let count = state
+                .retired
+                .iter()
+                .take_while(|&&(epoch, _)| epoch <= completed_epoch)
+                .count();
+
+            state.retired.drain(..count).collect()
+        };
+
+        // Drop the objects outside of the lock, in case dropping them is expensive.
+        Ok(collected
+            .into_iter()
+            .map(|(_, objects)| objects.len())
+            .sum())
+    }
+
+    /// Blocks until `epoch` has completed, and then drops the objects that were retired against
+    /// it and any earlier epoch.
+    pub fn wait(
+        &self,
+        epoch: Epoch,
+        timeout: Option<Duration>,
+    ) -> Result<(), Validated<VulkanError>> {
+        self.semaphore.wait(
+            SemaphoreWaitInfo {
+                value: epoch.0,
+                ..Default::default()
+            },
+            timeout,
+        )?;
+        self.collect()?;
+
+        Ok(())
+    }
+}
+
+impl Drop for EpochTracker {
+    fn drop(&mut self) {
+        let state = self.state.get_mut();
+
+        let Some(&(last_epoch, _)) = state.retired.back() else {
+            return;
+        };
+
+        let result = if last_epoch <= state.max_signaled_epoch {
+            unsafe {
+                self.semaphore.wait_unchecked(
+                    SemaphoreWaitInfo {
+                        value: last_epoch,
+                        ..Default::default()
+                    },
+                    None,
+                )
+            }
+        } else {
+            // Some of the epochs were never handed out for signaling, so the semaphore might never
+            // reach them.
+            // FIXME: This isn't synchronized with submissions to the queues of the device.
+            unsafe { self.semaphore.device().wait_idle() }
+        };
+
+        match result {
+            // A lost device doesn't execute anything anymore.
+            Ok(()) | Err(VulkanError::DeviceLost) => state.retired.clear(),
+            // We can't know whether the GPU is still using the objects, so leak them.
+            Err(_) => mem::forget(mem::take(&mut state.retired)),
+        }
+    }
+}
+
+unsafe impl DeviceOwned for EpochTracker {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.semaphore.device()
+    }
+}
+
+/// An epoch of an [`EpochTracker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Epoch(u64);
+
+impl Epoch {
+    /// Returns the value that the semaphore of the tracker has when the epoch is completed.
+    #[inline]
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+/// Keeps objects alive until a value that is tracked by the application has been reached.
+///
+/// The values can be anything that increases over time and that the application knows the
+/// current value of, such as the index of a frame or the value of a timeline semaphore. Objects
+/// are deferred against a value with [`defer`], and dropped once [`collect`] is called with that
+/// value or a later one.
+///
+/// See the [module-level documentation](self) for more information.
+///
+/// Unlike [`EpochTracker`], a `DeletionQueue` doesn't wait for anything when it's dropped; all the
+/// objects that it still holds are dropped immediately.
+///
+/// [`defer`]: Self::defer
+/// [`collect`]: Self::collect
+#[derive(Debug, Default)]
+pub struct DeletionQueue {
+    // Objects that are kept alive, in increasing order of value.
+    deferred: VecDeque<(u64, Vec<Box<dyn Any + Send + Sync>>)>,
+}
+
+impl DeletionQueue {
+    /// Creates a new, empty `DeletionQueue`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of objects that are being kept alive.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.deferred.iter().map(|(_, objects)| objects.len()).sum()
+    }
+
+    /// Returns whether no objects are being kept alive.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.deferred.is_empty()
+    }
+
+    /// Keeps `object` alive until `value` has been reached.
+    ///
+    /// The object is dropped by the first call to [`collect`] with a value that is at least
+    /// `value`.
+    ///
+    /// [`collect`]: Self::collect
+    pub fn defer(&mut self, value: u64, object: impl Send + Sync + 'static) {
+        insert_retired(&mut self.deferred, value, Box::new(object));
+    }
+
+    /// Drops all objects that were deferred against `completed_value` or an earlier value.
+    ///
+    /// Returns the number of objects that were dropped.
+    pub fn collect(&mut self, completed_value: u64) -> usize {
+        let count = self
+            .deferred
+            .iter()
+            .take_while(|&&(value, _)| value <= completed_value)
+            .count();
+
+        self.deferred
+            .drain(..count)
+            .map(|(_, objects)| objects.len())
+            .sum()
+    }
+
+    /// Drops all objects that were deferred against the current value of `semaphore` or an
+    /// earlier value.
+    ///
+    /// Returns the number of objects that were dropped.
+    pub fn collect_semaphore(
+        &mut self,
+        semaphore: &Semaphore,
+    ) -> Result<usize, Validated<VulkanError>> {
+        Ok(self.collect(semaphore.counter_value()?))
+    }
+
+    /// Drops all objects, regardless of the value that they were deferred against.
+    ///
+    /// Returns the number of objects that were dropped.
+    pub fn clear(&mut self) -> usize {
+        let count = self.len();
+        self.deferred.clear();
+
+        count
+    }
+}
+
+// Inserts `object` into `retired`, keeping the entries in increasing order of value.
+fn insert_retired(
+    retired: &mut VecDeque<(u64, Vec<Box<dyn Any + Send + Sync>>)>,
+    value: u64,
+    object: Box<dyn Any + Send + Sync>,
+) {
+    // Objects are almost always retired in order, so the last entry is usually the right one.
+    match retired
+        .iter()
+        .rposition(|&(retired_value, _)| retired_value <= value)
+    {
+        Some(index) if retired[index].0 == value => retired[index].1.push(object),
+        Some(index) => retired.insert(index + 1, (value, vec![object])),
+        None => retired.push_front((value, vec![object])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeletionQueue, EpochTracker};
+    use crate::sync::semaphore::SemaphoreSignalInfo;
+    use std::sync::Arc;
+
+    #[test]
+    fn retire_and_collect() {
+        let (device, _) = gfx_dev_and_queue!(timeline_semaphore);
+
+        let tracker = EpochTracker::new(device).unwrap();
+        assert!(tracker.current_epoch().is_none());
+
+        let first = tracker.next_epoch();
+        let second = tracker.next_epoch();
+        assert!(first < second);
+        assert_eq!(tracker.current_epoch(), Some(second));
+
+        let object = Arc::new(());
+        tracker.retire(second, object.clone());
+        tracker.retire(first, object.clone());
+        assert_eq!(Arc::strong_count(&object), 3);
+
+        // Nothing has completed yet.
+        assert_eq!(tracker.collect().unwrap(), 0);
+
+        unsafe {
+            tracker
+                .semaphore()
+                .signal(SemaphoreSignalInfo {
+                    value: first.value(),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        assert_eq!(tracker.collect().unwrap(), 1);
+        assert_eq!(Arc::strong_count(&object), 2);
+
+        unsafe {
+            tracker
+                .semaphore()
+                .signal(SemaphoreSignalInfo {
+                    value: second.value(),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        tracker.wait(second, None).unwrap();
+        assert_eq!(Arc::strong_count(&object), 1);
+    }
+
+    #[test]
+    fn drop_signaled() {
+        let (device, _) = gfx_dev_and_queue!(timeline_semaphore);
+
+        let tracker = EpochTracker::new(device).unwrap();
+        let epoch = tracker.next_epoch();
+        let _ = tracker.signal_semaphore_info(epoch);
+
+        let object = Arc::new(());
+        tracker.retire(epoch, object.clone());
+
+        unsafe {
+            tracker
+                .semaphore()
+                .signal(SemaphoreSignalInfo {
+                    value: epoch.value(),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        drop(tracker);
+        assert_eq!(Arc::strong_count(&object), 1);
+    }
+
+    #[test]
+    fn drop_unsignaled() {
+        let (device, _) = gfx_dev_and_queue!(timeline_semaphore);
+
+        let tracker = EpochTracker::new(device).unwrap();
+        let first = tracker.next_epoch();
+        let _ = tracker.signal_semaphore_info(first);
+        let second = tracker.next_epoch();
+
+        let object = Arc::new(());
+        tracker.retire(first, object.clone());
+        tracker.retire(second, object.clone());
+
+        unsafe {
+            tracker
+                .semaphore()
+                .signal(SemaphoreSignalInfo {
+                    value: first.value(),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        // The second epoch is never signaled, so dropping waits for the device to be idle.
+        drop(tracker);
+        assert_eq!(Arc::strong_count(&object), 1);
+    }
+
+    #[test]
+    fn deletion_queue() {
+        let mut deletion_queue = DeletionQueue::new();
+        assert!(deletion_queue.is_empty());
+
+        let object = Arc::new(());
+        deletion_queue.defer(3, object.clone());
+        deletion_queue.defer(1, object.clone());
+        deletion_queue.defer(3, object.clone());
+        assert_eq!(deletion_queue.len(), 3);
+        assert_eq!(Arc::strong_count(&object), 4);
+
+        assert_eq!(deletion_queue.collect(0), 0);
+        assert_eq!(deletion_queue.collect(2), 1);
+        assert_eq!(Arc::strong_count(&object), 3);
+
+        assert_eq!(deletion_queue.collect(3), 2);
+        assert!(deletion_queue.is_empty());
+        assert_eq!(Arc::strong_count(&object), 1);
+    }
+}
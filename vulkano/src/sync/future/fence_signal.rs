@@ -198,6 +198,14 @@ where
         }
     }
 
+    // Calls `cleanup_finished_impl`, and returns whether the future has been cleaned up as a
+    // result, or was already cleaned up before.
+    pub(super) fn cleanup_finished_and_check(&self) -> bool {
+        self.cleanup_finished_impl();
+
+        matches!(*self.state.lock(), FenceSignalFutureState::Cleaned)
+    }
+
     // Implementation of `flush`. You must lock the state and pass the mutex guard here.
     fn flush_impl(
         &self,
@@ -236,7 +244,7 @@ where
 
                     queue_submit(
                         &queue,
-                        Default::default(),
+                        [SubmitInfo::default()],
                         Some(new_fence.clone()),
                         &previous,
                     )
@@ -247,7 +255,7 @@ where
 
                     queue_submit(
                         &queue,
-                        SubmitInfo {
+                        [SubmitInfo {
                             wait_semaphores: semaphores
                                 .into_iter()
                                 .map(|semaphore| {
@@ -259,13 +267,13 @@ where
                                 })
                                 .collect(),
                             ..Default::default()
-                        },
+                        }],
                         None,
                         &previous,
                     )
                     .map_err(OutcomeErr::Full)
                 }
-                SubmitAnyBuilder::CommandBuffer(submit_info, fence) => {
+                SubmitAnyBuilder::CommandBuffer(submit_infos, fence) => {
                     debug_assert!(!partially_flushed);
                     // The assert below could technically be a debug assertion as it is part of the
                     // safety contract of the trait. However it is easy to get this wrong if you
@@ -274,7 +282,7 @@ where
                     // assertion.
                     assert!(fence.is_none());
 
-                    queue_submit(&queue, submit_info, Some(new_fence.clone()), &previous)
+                    queue_submit(&queue, submit_infos, Some(new_fence.clone()), &previous)
                         .map_err(OutcomeErr::Full)
                 }
                 SubmitAnyBuilder::BindSparse(bind_infos, fence) => {
@@ -293,7 +301,7 @@ where
                     if partially_flushed {
                         queue_submit(
                             &queue,
-                            Default::default(),
+                            [SubmitInfo::default()],
                             Some(new_fence.clone()),
                             &previous,
                         )
@@ -338,7 +346,7 @@ where
                         match intermediary_result {
                             Ok(()) => queue_submit(
                                 &queue,
-                                Default::default(),
+                                [SubmitInfo::default()],
                                 Some(new_fence.clone()),
                                 &previous,
                             )
@@ -1,14 +1,22 @@
-use super::{AccessCheckError, GpuFuture, SubmitAnyBuilder};
+use super::{submit_signal_semaphore, AccessCheckError, AccessError, GpuFuture, SubmitAnyBuilder};
 use crate::{
     buffer::Buffer,
+    command_buffer::{SemaphoreSubmitInfo, SubmitInfo},
     device::{Device, DeviceOwned, Queue},
     image::{Image, ImageLayout},
     swapchain::Swapchain,
+    sync::{semaphore::Semaphore, PipelineStages},
     DeviceSize, Validated, VulkanError, VulkanObject,
 };
+use parking_lot::Mutex;
+use smallvec::{smallvec, SmallVec};
 use std::{ops::Range, sync::Arc};
 
 /// Joins two futures together.
+///
+/// If the two futures are executed on different queues, then the joined future signals a
+/// semaphore after each of them, and the operations that are executed after the joined future
+/// wait on both semaphores, on any queue.
 // TODO: handle errors
 pub fn join<F, S>(first: F, second: S) -> JoinFuture<F, S>
 where
@@ -17,11 +25,16 @@ where
 {
     assert_eq!(first.device().handle(), second.device().handle());
 
-    if !first.queue_change_allowed() && !second.queue_change_allowed() {
-        assert_eq!(first.queue().unwrap(), second.queue().unwrap());
-    }
+    let queue_change = !first.queue_change_allowed()
+        && !second.queue_change_allowed()
+        && first.queue() != second.queue();
 
-    JoinFuture { first, second }
+    JoinFuture {
+        first,
+        second,
+        queue_change,
+        wait_semaphores: Mutex::new(None),
+    }
 }
 
 /// Two futures joined into one.
@@ -29,6 +42,32 @@ where
 pub struct JoinFuture<A, B> {
     first: A,
     second: B,
+    // True if `first` and `second` are executed on different queues, and must each signal one of
+    // `wait_semaphores`.
+    queue_change: bool,
+    // The semaphores that are signaled after `first` and `second`, once they have been submitted.
+    wait_semaphores: Mutex<Option<[Arc<Semaphore>; 2]>>,
+}
+
+impl<A, B> JoinFuture<A, B>
+where
+    A: GpuFuture,
+    B: GpuFuture,
+{
+    // Checks that, if `first` and `second` are executed on different queues, they don't both
+    // access a resource without a dependency between them while one of them writes to it.
+    fn check_queue_hazard(
+        &self,
+        first: &Result<(), AccessCheckError>,
+        second: &Result<(), AccessCheckError>,
+        is_written: impl FnOnce() -> bool,
+    ) -> Result<(), AccessCheckError> {
+        if self.queue_change && first.is_ok() && second.is_ok() && is_written() {
+            return Err(AccessCheckError::Denied(AccessError::ConcurrentQueueAccess));
+        }
+
+        Ok(())
+    }
 }
 
 unsafe impl<A, B> DeviceOwned for JoinFuture<A, B>
@@ -63,102 +102,29 @@ where
     }
 
     unsafe fn build_submission(&self) -> Result<SubmitAnyBuilder, Validated<VulkanError>> {
-        // TODO: review this function
-        let first = self.first.build_submission()?;
-        let second = self.second.build_submission()?;
+        if self.queue_change {
+            let mut wait_semaphores = self.wait_semaphores.lock();
 
-        // In some cases below we have to submit previous command buffers already, this s done by
-        // flushing previous. Since the implementation should remember being flushed it's
-        // safe to call build_submission multiple times
-        Ok(match (first, second) {
-            (SubmitAnyBuilder::Empty, b) => b,
-            (a, SubmitAnyBuilder::Empty) => a,
-            (SubmitAnyBuilder::SemaphoresWait(mut a), SubmitAnyBuilder::SemaphoresWait(b)) => {
-                a.extend(b);
-                SubmitAnyBuilder::SemaphoresWait(a)
-            }
-            (SubmitAnyBuilder::SemaphoresWait(a), SubmitAnyBuilder::CommandBuffer(_, _)) => {
-                self.second.flush()?;
-                SubmitAnyBuilder::SemaphoresWait(a)
-            }
-            (SubmitAnyBuilder::CommandBuffer(_, _), SubmitAnyBuilder::SemaphoresWait(b)) => {
-                self.first.flush()?;
-                SubmitAnyBuilder::SemaphoresWait(b)
-            }
-            (SubmitAnyBuilder::SemaphoresWait(a), SubmitAnyBuilder::QueuePresent(_)) => {
-                self.second.flush()?;
-                SubmitAnyBuilder::SemaphoresWait(a)
-            }
-            (SubmitAnyBuilder::QueuePresent(_), SubmitAnyBuilder::SemaphoresWait(b)) => {
-                self.first.flush()?;
-                SubmitAnyBuilder::SemaphoresWait(b)
-            }
-            (SubmitAnyBuilder::SemaphoresWait(a), SubmitAnyBuilder::BindSparse(_, _)) => {
-                self.second.flush()?;
-                SubmitAnyBuilder::SemaphoresWait(a)
-            }
-            (SubmitAnyBuilder::BindSparse(_, _), SubmitAnyBuilder::SemaphoresWait(b)) => {
-                self.first.flush()?;
-                SubmitAnyBuilder::SemaphoresWait(b)
-            }
-            (
-                SubmitAnyBuilder::CommandBuffer(mut submit_info_a, fence_a),
-                SubmitAnyBuilder::CommandBuffer(submit_info_b, fence_b),
-            ) => {
-                assert!(
-                    fence_a.is_none() || fence_b.is_none(),
-                    "Can't merge two queue submits that both have a fence"
-                );
+            let [first, second] = match &*wait_semaphores {
+                Some(semaphores) => semaphores.clone(),
+                None => {
+                    let device = self.device();
+                    let first = Arc::new(Semaphore::from_pool(device.clone())?);
+                    let second = Arc::new(Semaphore::from_pool(device.clone())?);
+                    submit_signal_semaphore(&self.first, &first)?;
+                    submit_signal_semaphore(&self.second, &second)?;
 
-                submit_info_a
-                    .wait_semaphores
-                    .extend(submit_info_b.wait_semaphores);
-                submit_info_a
-                    .command_buffers
-                    .extend(submit_info_b.command_buffers);
-                submit_info_a
-                    .signal_semaphores
-                    .extend(submit_info_b.signal_semaphores);
-
-                SubmitAnyBuilder::CommandBuffer(submit_info_a, fence_a.or(fence_b))
-            }
-            (SubmitAnyBuilder::QueuePresent(_), SubmitAnyBuilder::QueuePresent(_)) => {
-                self.first.flush()?;
-                self.second.flush()?;
-                SubmitAnyBuilder::Empty
-            }
-            (SubmitAnyBuilder::CommandBuffer(_, _), SubmitAnyBuilder::QueuePresent(_)) => {
-                unimplemented!()
-            }
-            (SubmitAnyBuilder::QueuePresent(_), SubmitAnyBuilder::CommandBuffer(_, _)) => {
-                unimplemented!()
-            }
-            (SubmitAnyBuilder::BindSparse(_, _), SubmitAnyBuilder::QueuePresent(_)) => {
-                unimplemented!()
-            }
-            (SubmitAnyBuilder::QueuePresent(_), SubmitAnyBuilder::BindSparse(_, _)) => {
-                unimplemented!()
-            }
-            (SubmitAnyBuilder::BindSparse(_, _), SubmitAnyBuilder::CommandBuffer(_, _)) => {
-                unimplemented!()
-            }
-            (SubmitAnyBuilder::CommandBuffer(_, _), SubmitAnyBuilder::BindSparse(_, _)) => {
-                unimplemented!()
-            }
-            (
-                SubmitAnyBuilder::BindSparse(mut bind_infos_a, fence_a),
-                SubmitAnyBuilder::BindSparse(bind_infos_b, fence_b),
-            ) => {
-                if fence_a.is_some() && fence_b.is_some() {
-                    // TODO: this happens if both bind sparse have been given a fence already
-                    //       annoying, but not impossible, to handle
-                    unimplemented!()
+                    wait_semaphores.insert([first, second]).clone()
                 }
+            };
 
-                bind_infos_a.extend(bind_infos_b);
-                SubmitAnyBuilder::BindSparse(bind_infos_a, fence_a)
-            }
-        })
+            return Ok(SubmitAnyBuilder::SemaphoresWait(smallvec![first, second]));
+        }
+
+        let first = self.first.build_submission()?;
+        let second = self.second.build_submission()?;
+
+        merge_submissions(first, || self.first.flush(), second, || self.second.flush())
     }
 
     unsafe fn signal_finished(&self) {
@@ -167,20 +133,20 @@ where
     }
 
     fn queue_change_allowed(&self) -> bool {
-        self.first.queue_change_allowed() && self.second.queue_change_allowed()
+        self.queue_change || self.first.queue_change_allowed() && self.second.queue_change_allowed()
     }
 
     fn queue(&self) -> Option<Arc<Queue>> {
         match (self.first.queue(), self.second.queue()) {
             (Some(q1), Some(q2)) => {
-                if q1 == q2 {
-                    Some(q1)
-                } else if self.first.queue_change_allowed() {
+                if q1 != q2 && self.first.queue_change_allowed() {
                     Some(q2)
-                } else if self.second.queue_change_allowed() {
-                    Some(q1)
                 } else {
-                    None
+                    // If neither future can change queues, the operations after the join wait on
+                    // the semaphores that both futures signal, which works on any queue. The
+                    // queue of the first future is used for the operations that need one, such
+                    // as signaling a fence.
+                    Some(q1)
                 }
             }
             (Some(q), None) => Some(q),
@@ -201,7 +167,16 @@ where
             .check_buffer_access(buffer, range.clone(), exclusive, queue);
         let second = self
             .second
-            .check_buffer_access(buffer, range, exclusive, queue);
+            .check_buffer_access(buffer, range.clone(), exclusive, queue);
+        self.check_queue_hazard(&first, &second, || {
+            self.first
+                .check_buffer_access(buffer, range.clone(), true, queue)
+                .is_ok()
+                || self
+                    .second
+                    .check_buffer_access(buffer, range.clone(), true, queue)
+                    .is_ok()
+        })?;
         debug_assert!(
             !(exclusive && first.is_ok() && second.is_ok()),
             "Two futures gave exclusive access to the same resource"
@@ -234,7 +209,16 @@ where
                 .check_image_access(image, range.clone(), exclusive, expected_layout, queue);
         let second =
             self.second
-                .check_image_access(image, range, exclusive, expected_layout, queue);
+                .check_image_access(image, range.clone(), exclusive, expected_layout, queue);
+        self.check_queue_hazard(&first, &second, || {
+            self.first
+                .check_image_access(image, range.clone(), true, expected_layout, queue)
+                .is_ok()
+                || self
+                    .second
+                    .check_image_access(image, range.clone(), true, expected_layout, queue)
+                    .is_ok()
+        })?;
         debug_assert!(
             !(exclusive && first.is_ok() && second.is_ok()),
             "Two futures gave exclusive access to the same resource"
@@ -279,3 +263,751 @@ where
         }
     }
 }
+
+/// Joins any number of futures together.
+///
+/// This is the same as joining the futures one by one with [`GpuFuture::join`], but produces a
+/// single future rather than a chain of nested ones. This is useful when the number of futures is
+/// only known at runtime, for example when there is one future per window or per view, and they
+/// are collected into a `Vec<Box<dyn GpuFuture>>`.
+///
+/// If all the futures are executed on the same queue, their submissions are merged into one
+/// whenever possible. Otherwise, a semaphore is signaled after each future that is executed on a
+/// queue, and the operations that are executed after the joined future wait on all of them.
+///
+/// # Panics
+///
+/// - Panics if `futures` is empty.
+/// - Panics if the futures don't all belong to the same device.
+pub fn join_all<F>(futures: impl IntoIterator<Item = F>) -> JoinAllFuture<F>
+where
+    F: GpuFuture,
+{
+    let futures: Vec<F> = futures.into_iter().collect();
+    assert!(!futures.is_empty());
+
+    let device = futures[0].device();
+
+    for future in &futures[1..] {
+        assert_eq!(future.device().handle(), device.handle());
+    }
+
+    let mut fixed_queue: Option<Arc<Queue>> = None;
+    let mut queue_change = false;
+
+    for future in &futures {
+        if future.queue_change_allowed() {
+            continue;
+        }
+
+        if let Some(queue) = future.queue() {
+            match &fixed_queue {
+                Some(fixed_queue) => queue_change |= *fixed_queue != queue,
+                None => fixed_queue = Some(queue),
+            }
+        }
+    }
+
+    JoinAllFuture {
+        futures,
+        queue_change,
+        wait_semaphores: Mutex::new(None),
+    }
+}
+
+/// Any number of futures joined into one.
+#[must_use]
+pub struct JoinAllFuture<F> {
+    futures: Vec<F>,
+    // True if the futures are executed on different queues, and must each signal one of
+    // `wait_semaphores`.
+    queue_change: bool,
+    // The semaphores that are signaled after the futures, once they have been submitted.
+    wait_semaphores: Mutex<Option<SmallVec<[Arc<Semaphore>; 8]>>>,
+}
+
+impl<F> JoinAllFuture<F>
+where
+    F: GpuFuture,
+{
+    /// Returns the futures that are joined.
+    #[inline]
+    pub fn futures(&self) -> &[F] {
+        &self.futures
+    }
+
+    // Combines the results of an access check of all the futures, in the same way as
+    // `JoinFuture` does for two futures.
+    fn check_access(
+        &self,
+        check: impl Fn(&F, bool) -> Result<(), AccessCheckError>,
+        exclusive: bool,
+    ) -> Result<(), AccessCheckError> {
+        let results: SmallVec<[_; 8]> = self
+            .futures
+            .iter()
+            .map(|future| check(future, exclusive))
+            .collect();
+
+        if self.queue_change {
+            // Two futures that are executed on different queues can't both access the resource
+            // if one of them writes to it, because there is no dependency between them.
+            let granted: SmallVec<[_; 8]> = self
+                .futures
+                .iter()
+                .zip(&results)
+                .filter(|(_, result)| result.is_ok())
+                .map(|(future, _)| future)
+                .collect();
+
+            for (index, &first) in granted.iter().enumerate() {
+                for &second in &granted[index + 1..] {
+                    if first.queue() != second.queue()
+                        && (check(first, true).is_ok() || check(second, true).is_ok())
+                    {
+                        return Err(AccessCheckError::Denied(AccessError::ConcurrentQueueAccess));
+                    }
+                }
+            }
+        }
+
+        results
+            .into_iter()
+            .reduce(|first, second| {
+                debug_assert!(
+                    !(exclusive && first.is_ok() && second.is_ok()),
+                    "Two futures gave exclusive access to the same resource"
+                );
+
+                match (first, second) {
+                    (v, Err(AccessCheckError::Unknown)) => v,
+                    (Err(AccessCheckError::Unknown), v) => v,
+                    (Err(AccessCheckError::Denied(e1)), Err(AccessCheckError::Denied(_))) => {
+                        Err(AccessCheckError::Denied(e1))
+                    }
+                    (Ok(()), Err(AccessCheckError::Denied(_)))
+                    | (Err(AccessCheckError::Denied(_)), Ok(())) => {
+                        panic!("Contradictory information between two futures")
+                    }
+                    (Ok(()), Ok(())) => Ok(()),
+                }
+            })
+            .unwrap()
+    }
+}
+
+unsafe impl<F> DeviceOwned for JoinAllFuture<F>
+where
+    F: DeviceOwned,
+{
+    fn device(&self) -> &Arc<Device> {
+        self.futures[0].device()
+    }
+}
+
+unsafe impl<F> GpuFuture for JoinAllFuture<F>
+where
+    F: GpuFuture,
+{
+    fn cleanup_finished(&mut self) {
+        for future in &mut self.futures {
+            future.cleanup_finished();
+        }
+    }
+
+    fn flush(&self) -> Result<(), Validated<VulkanError>> {
+        // Since each future remembers whether it has been flushed, there's no safety issue here
+        // if we call this function multiple times.
+        for future in &self.futures {
+            future.flush()?;
+        }
+
+        Ok(())
+    }
+
+    unsafe fn build_submission(&self) -> Result<SubmitAnyBuilder, Validated<VulkanError>> {
+        if self.queue_change {
+            let mut wait_semaphores = self.wait_semaphores.lock();
+
+            let semaphores = match &*wait_semaphores {
+                Some(semaphores) => semaphores.clone(),
+                None => {
+                    let mut semaphores = SmallVec::new();
+
+                    for future in &self.futures {
+                        if future.queue().is_some() {
+                            let semaphore = Arc::new(Semaphore::from_pool(self.device().clone())?);
+                            submit_signal_semaphore(future, &semaphore)?;
+                            semaphores.push(semaphore);
+                        } else {
+                            match future.build_submission()? {
+                                SubmitAnyBuilder::Empty => (),
+                                SubmitAnyBuilder::SemaphoresWait(future_semaphores) => {
+                                    semaphores.extend(future_semaphores);
+                                }
+                                _ => future.flush()?,
+                            }
+                        }
+                    }
+
+                    wait_semaphores.insert(semaphores).clone()
+                }
+            };
+
+            return Ok(SubmitAnyBuilder::SemaphoresWait(semaphores));
+        }
+
+        let mut submission = SubmitAnyBuilder::Empty;
+
+        for (index, future) in self.futures.iter().enumerate() {
+            let future_submission = future.build_submission()?;
+            submission = merge_submissions(
+                submission,
+                || {
+                    for previous in &self.futures[..index] {
+                        previous.flush()?;
+                    }
+
+                    Ok(())
+                },
+                future_submission,
+                || future.flush(),
+            )?;
+        }
+
+        Ok(submission)
+    }
+
+    unsafe fn signal_finished(&self) {
+        for future in &self.futures {
+            future.signal_finished();
+        }
+    }
+
+    fn queue_change_allowed(&self) -> bool {
+        self.queue_change
+            || self
+                .futures
+                .iter()
+                .all(|future| future.queue_change_allowed())
+    }
+
+    fn queue(&self) -> Option<Arc<Queue>> {
+        // The queue of a future that can't change queues takes precedence. If the futures are
+        // executed on different queues, the operations after the join wait on the semaphores that
+        // they all signal, which works on any queue, so the queue of the first such future is
+        // used for the operations that need one, like `JoinFuture` does.
+        self.futures
+            .iter()
+            .filter(|future| !future.queue_change_allowed())
+            .find_map(|future| future.queue())
+            .or_else(|| self.futures.iter().find_map(|future| future.queue()))
+    }
+
+    fn check_buffer_access(
+        &self,
+        buffer: &Buffer,
+        range: Range<DeviceSize>,
+        exclusive: bool,
+        queue: &Queue,
+    ) -> Result<(), AccessCheckError> {
+        self.check_access(
+            |future, exclusive| future.check_buffer_access(buffer, range.clone(), exclusive, queue),
+            exclusive,
+        )
+    }
+
+    fn check_image_access(
+        &self,
+        image: &Image,
+        range: Range<DeviceSize>,
+        exclusive: bool,
+        expected_layout: ImageLayout,
+        queue: &Queue,
+    ) -> Result<(), AccessCheckError> {
+        self.check_access(
+            |future, exclusive| {
+                future.check_image_access(image, range.clone(), exclusive, expected_layout, queue)
+            },
+            exclusive,
+        )
+    }
+
+    #[inline]
+    fn check_swapchain_image_acquired(
+        &self,
+        swapchain: &Swapchain,
+        image_index: u32,
+        _before: bool,
+    ) -> Result<(), AccessCheckError> {
+        self.futures
+            .iter()
+            .map(|future| future.check_swapchain_image_acquired(swapchain, image_index, false))
+            .reduce(|first, second| match (first, second) {
+                (v, Err(AccessCheckError::Unknown)) => v,
+                (Err(AccessCheckError::Unknown), v) => v,
+                (Err(AccessCheckError::Denied(e1)), Err(AccessCheckError::Denied(_))) => {
+                    Err(AccessCheckError::Denied(e1))
+                }
+                (Ok(()), Err(AccessCheckError::Denied(_)))
+                | (Err(AccessCheckError::Denied(_)), Ok(())) => Ok(()),
+                (Ok(()), Ok(())) => Ok(()),
+            })
+            .unwrap()
+    }
+}
+
+// Merges the submissions of two futures that are executed on the same queue into one. If they
+// can't be merged into a single call, one or both of them are flushed with `flush_first` and
+// `flush_second`, and the submission of the other one is returned. There is no dependency between
+// the two futures, so the order in which they are submitted doesn't matter.
+fn merge_submissions(
+    first: SubmitAnyBuilder,
+    flush_first: impl FnOnce() -> Result<(), Validated<VulkanError>>,
+    second: SubmitAnyBuilder,
+    flush_second: impl FnOnce() -> Result<(), Validated<VulkanError>>,
+) -> Result<SubmitAnyBuilder, Validated<VulkanError>> {
+    // Since the futures remember being flushed, it's safe to call `build_submission` on them
+    // again afterwards.
+    Ok(match (first, second) {
+        (SubmitAnyBuilder::Empty, b) => b,
+        (a, SubmitAnyBuilder::Empty) => a,
+        (SubmitAnyBuilder::SemaphoresWait(mut a), SubmitAnyBuilder::SemaphoresWait(b)) => {
+            a.extend(b);
+            SubmitAnyBuilder::SemaphoresWait(a)
+        }
+        (
+            SubmitAnyBuilder::SemaphoresWait(semaphores),
+            SubmitAnyBuilder::CommandBuffer(mut submit_infos, fence),
+        )
+        | (
+            SubmitAnyBuilder::CommandBuffer(mut submit_infos, fence),
+            SubmitAnyBuilder::SemaphoresWait(semaphores),
+        ) => {
+            // Rather than submitting the command buffers on their own, wait on the semaphores
+            // in a new batch of the same submission, so that whatever comes next is added to
+            // that batch.
+            submit_infos.push(SubmitInfo {
+                wait_semaphores: semaphores
+                    .into_iter()
+                    .map(|semaphore| {
+                        SemaphoreSubmitInfo {
+                            // TODO: correct stages ; hard
+                            stages: PipelineStages::ALL_COMMANDS,
+                            ..SemaphoreSubmitInfo::new(semaphore)
+                        }
+                    })
+                    .collect(),
+                ..Default::default()
+            });
+
+            SubmitAnyBuilder::CommandBuffer(submit_infos, fence)
+        }
+        (SubmitAnyBuilder::SemaphoresWait(a), SubmitAnyBuilder::QueuePresent(_)) => {
+            flush_second()?;
+            SubmitAnyBuilder::SemaphoresWait(a)
+        }
+        (SubmitAnyBuilder::QueuePresent(_), SubmitAnyBuilder::SemaphoresWait(b)) => {
+            flush_first()?;
+            SubmitAnyBuilder::SemaphoresWait(b)
+        }
+        (SubmitAnyBuilder::SemaphoresWait(a), SubmitAnyBuilder::BindSparse(_, _)) => {
+            flush_second()?;
+            SubmitAnyBuilder::SemaphoresWait(a)
+        }
+        (SubmitAnyBuilder::BindSparse(_, _), SubmitAnyBuilder::SemaphoresWait(b)) => {
+            flush_first()?;
+            SubmitAnyBuilder::SemaphoresWait(b)
+        }
+        (
+            SubmitAnyBuilder::CommandBuffer(mut submit_infos_a, fence_a),
+            SubmitAnyBuilder::CommandBuffer(mut submit_infos_b, fence_b),
+        ) => {
+            if fence_a.is_some() && fence_b.is_some() {
+                // Only one fence can be signaled by a submission.
+                flush_first()?;
+                flush_second()?;
+
+                return Ok(SubmitAnyBuilder::Empty);
+            }
+
+            // Whatever comes next is added to the last batch, so it must wait on the
+            // semaphores of both last batches. The batches before them are kept as they are.
+            let mut submit_info_a = submit_infos_a.pop().unwrap();
+            let submit_info_b = submit_infos_b.pop().unwrap();
+
+            submit_info_a
+                .wait_semaphores
+                .extend(submit_info_b.wait_semaphores);
+            submit_info_a
+                .command_buffers
+                .extend(submit_info_b.command_buffers);
+            submit_info_a
+                .signal_semaphores
+                .extend(submit_info_b.signal_semaphores);
+
+            submit_infos_a.extend(submit_infos_b);
+            submit_infos_a.push(submit_info_a);
+
+            SubmitAnyBuilder::CommandBuffer(submit_infos_a, fence_a.or(fence_b))
+        }
+        (SubmitAnyBuilder::QueuePresent(_), SubmitAnyBuilder::QueuePresent(_)) => {
+            flush_first()?;
+            flush_second()?;
+            SubmitAnyBuilder::Empty
+        }
+        // Presents, command buffers and sparse binds are submitted with different commands, so
+        // the second kind is flushed on its own.
+        (
+            a @ (SubmitAnyBuilder::CommandBuffer(_, _) | SubmitAnyBuilder::BindSparse(_, _)),
+            SubmitAnyBuilder::QueuePresent(_),
+        )
+        | (a @ SubmitAnyBuilder::CommandBuffer(_, _), SubmitAnyBuilder::BindSparse(_, _)) => {
+            flush_second()?;
+            a
+        }
+        (
+            SubmitAnyBuilder::QueuePresent(_),
+            b @ (SubmitAnyBuilder::CommandBuffer(_, _) | SubmitAnyBuilder::BindSparse(_, _)),
+        )
+        | (SubmitAnyBuilder::BindSparse(_, _), b @ SubmitAnyBuilder::CommandBuffer(_, _)) => {
+            flush_first()?;
+            b
+        }
+        (
+            SubmitAnyBuilder::BindSparse(mut bind_infos_a, fence_a),
+            SubmitAnyBuilder::BindSparse(bind_infos_b, fence_b),
+        ) => {
+            if fence_a.is_some() && fence_b.is_some() {
+                // Only one fence can be signaled by a submission.
+                flush_first()?;
+                flush_second()?;
+
+                return Ok(SubmitAnyBuilder::Empty);
+            }
+
+            bind_infos_a.extend(bind_infos_b);
+            SubmitAnyBuilder::BindSparse(bind_infos_a, fence_a.or(fence_b))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::join_all;
+    use crate::{
+        command_buffer::{
+            allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferLevel,
+            CommandBufferUsage, RecordingCommandBuffer,
+        },
+        device::{Device, DeviceCreateInfo, QueueCreateInfo},
+        sync::{self, future::SubmitAnyBuilder, GpuFuture},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn join_different_queues() {
+        let instance = instance!();
+
+        let Some((physical_device, queue_family_index)) = instance
+            .enumerate_physical_devices()
+            .unwrap()
+            .find_map(|physical_device| {
+                physical_device
+                    .queue_family_properties()
+                    .iter()
+                    .position(|properties| properties.queue_count >= 2)
+                    .map(|index| (physical_device.clone(), index as u32))
+            })
+        else {
+            return;
+        };
+
+        let (device, mut queues) = Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                queue_create_infos: vec![QueueCreateInfo {
+                    queue_family_index,
+                    queues: vec![0.5; 2],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let first_queue = queues.next().unwrap();
+        let second_queue = queues.next().unwrap();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let command_buffer = || {
+            RecordingCommandBuffer::new(
+                cb_allocator.clone(),
+                queue_family_index,
+                CommandBufferLevel::Primary,
+                CommandBufferBeginInfo {
+                    usage: CommandBufferUsage::OneTimeSubmit,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .end()
+            .unwrap()
+        };
+
+        let first = sync::now(device.clone())
+            .then_execute(first_queue.clone(), command_buffer())
+            .unwrap();
+        let second = sync::now(device.clone())
+            .then_execute(second_queue, command_buffer())
+            .unwrap();
+
+        // Neither future can change queues, so the operations after the join are submitted to
+        // the queue of the first future, waiting on semaphores signaled by both futures.
+        let joined = first.join(second);
+        assert_eq!(joined.queue(), Some(first_queue));
+
+        joined
+            .then_signal_semaphore()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+    }
+
+    #[test]
+    fn then_execute_single_batch() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let command_buffer = || {
+            RecordingCommandBuffer::new(
+                cb_allocator.clone(),
+                queue.queue_family_index(),
+                CommandBufferLevel::Primary,
+                CommandBufferBeginInfo {
+                    usage: CommandBufferUsage::OneTimeSubmit,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .end()
+            .unwrap()
+        };
+
+        let future = sync::now(device)
+            .then_execute(queue.clone(), command_buffer())
+            .unwrap()
+            .then_execute(queue, command_buffer())
+            .unwrap();
+
+        match unsafe { future.build_submission() }.unwrap() {
+            SubmitAnyBuilder::CommandBuffer(submit_infos, fence) => {
+                assert_eq!(submit_infos.len(), 1);
+                assert_eq!(submit_infos[0].command_buffers.len(), 2);
+                assert!(fence.is_none());
+            }
+            _ => panic!("expected a command buffer submission"),
+        }
+
+        future
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+    }
+
+    #[test]
+    fn join_semaphore_wait_batch() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let command_buffer = || {
+            RecordingCommandBuffer::new(
+                cb_allocator.clone(),
+                queue.queue_family_index(),
+                CommandBufferLevel::Primary,
+                CommandBufferBeginInfo {
+                    usage: CommandBufferUsage::OneTimeSubmit,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .end()
+            .unwrap()
+        };
+
+        let signaled = sync::now(device.clone())
+            .then_execute(queue.clone(), command_buffer())
+            .unwrap()
+            .then_signal_semaphore();
+        let executed = sync::now(device)
+            .then_execute(queue.clone(), command_buffer())
+            .unwrap();
+
+        // The command buffer that is executed after the join is added to the batch that waits on
+        // the semaphore, which comes after the batch of `executed` in the same submission.
+        let future = executed
+            .join(signaled)
+            .then_execute(queue, command_buffer())
+            .unwrap();
+
+        match unsafe { future.build_submission() }.unwrap() {
+            SubmitAnyBuilder::CommandBuffer(submit_infos, _) => {
+                assert_eq!(submit_infos.len(), 2);
+                assert_eq!(submit_infos[0].command_buffers.len(), 1);
+                assert!(submit_infos[0].wait_semaphores.is_empty());
+                assert_eq!(submit_infos[1].wait_semaphores.len(), 1);
+                assert_eq!(submit_infos[1].command_buffers.len(), 1);
+            }
+            _ => panic!("expected a command buffer submission"),
+        }
+
+        future
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+    }
+
+    #[test]
+    fn join_all_same_queue() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let command_buffer = || {
+            RecordingCommandBuffer::new(
+                cb_allocator.clone(),
+                queue.queue_family_index(),
+                CommandBufferLevel::Primary,
+                CommandBufferBeginInfo {
+                    usage: CommandBufferUsage::OneTimeSubmit,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .end()
+            .unwrap()
+        };
+
+        let futures: Vec<_> = (0..3)
+            .map(|_| {
+                sync::now(device.clone())
+                    .then_execute(queue.clone(), command_buffer())
+                    .unwrap()
+            })
+            .collect();
+        let joined = join_all(futures);
+        assert_eq!(joined.queue(), Some(queue));
+
+        // The futures are executed on the same queue, so their command buffers are merged into a
+        // single batch.
+        match unsafe { joined.build_submission() }.unwrap() {
+            SubmitAnyBuilder::CommandBuffer(submit_infos, _) => {
+                assert_eq!(submit_infos.len(), 1);
+                assert_eq!(submit_infos[0].command_buffers.len(), 3);
+            }
+            _ => panic!("expected a command buffer submission"),
+        }
+
+        joined
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+    }
+
+    #[test]
+    fn join_all_different_queues() {
+        let instance = instance!();
+
+        let Some((physical_device, queue_family_index)) = instance
+            .enumerate_physical_devices()
+            .unwrap()
+            .find_map(|physical_device| {
+                physical_device
+                    .queue_family_properties()
+                    .iter()
+                    .position(|properties| properties.queue_count >= 2)
+                    .map(|index| (physical_device.clone(), index as u32))
+            })
+        else {
+            return;
+        };
+
+        let (device, queues) = Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                queue_create_infos: vec![QueueCreateInfo {
+                    queue_family_index,
+                    queues: vec![0.5; 2],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let queues: Vec<_> = queues.collect();
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+        let command_buffer = || {
+            RecordingCommandBuffer::new(
+                cb_allocator.clone(),
+                queue_family_index,
+                CommandBufferLevel::Primary,
+                CommandBufferBeginInfo {
+                    usage: CommandBufferUsage::OneTimeSubmit,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .end()
+            .unwrap()
+        };
+
+        let futures: Vec<_> = [&queues[1], &queues[0], &queues[1]]
+            .into_iter()
+            .map(|queue| {
+                sync::now(device.clone())
+                    .then_execute(queue.clone(), command_buffer())
+                    .unwrap()
+            })
+            .collect();
+
+        // The operations after the join wait on semaphores signaled by all the futures, and are
+        // submitted to the queue of the first one.
+        let joined = join_all(futures);
+        assert_eq!(joined.queue(), Some(queues[1].clone()));
+
+        match unsafe { joined.build_submission() }.unwrap() {
+            SubmitAnyBuilder::SemaphoresWait(semaphores) => assert_eq!(semaphores.len(), 3),
+            _ => panic!("expected a semaphore wait"),
+        }
+
+        joined
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn join_all_empty() {
+        let _ = join_all(Vec::<sync::future::NowFuture>::new());
+    }
+}
@@ -46,9 +46,9 @@
 //!
 //! ## Between two different GPU queues
 //!
-//! When you want to perform an operation after another operation on two different queues, you
-//! **must** put a *semaphore* between them. Failure to do so would result in a runtime error.
-//! Adding a semaphore is a simple as replacing `prev_future.then_execute(...)` with
+//! When you want to perform an operation after another operation on two different queues, there
+//! must be a *semaphore* between them. `prev_future.then_execute(...)` adds one automatically if
+//! `prev_future` is executed on a different queue, which is the same as calling
 //! `prev_future.then_signal_semaphore().then_execute(...)`.
 //!
 //! > **Note**: A common use-case is using a transfer queue (ie. a queue that is only capable of
@@ -68,9 +68,28 @@
 //!
 //! The `then_signal_semaphore()` method is appropriate when you perform an operation in one queue,
 //! and want to see the result in another queue. However in some situations you want to start
-//! multiple operations on several different queues.
+//! an operation after multiple operations on several different queues, for example when
+//! post-processing on a compute queue and rendering on a graphics queue must both finish before
+//! presenting.
 //!
-//! TODO: this is not yet implemented
+//! This is done by joining the futures with `join()`. If the joined futures are executed on
+//! different queues, a semaphore is signaled after each of them, and the next operation waits on
+//! all of them. Joined futures that are executed on different queues can't both access the same
+//! resource if one of them writes to it, because there is no dependency between them; using such
+//! a resource after the joined future results in an [`AccessError::ConcurrentQueueAccess`] error.
+//!
+//! To join a number of futures that is only known at runtime, such as a `Vec<Box<dyn GpuFuture>>`
+//! with one future per window, use [`join_all`] rather than folding them with `join()`.
+//!
+//! ## Batching submissions
+//!
+//! Nothing is submitted until a future is flushed, and everything that is destined to the same
+//! queue at that point is submitted together with a single call to `vkQueueSubmit2` (or
+//! `vkQueueSubmit`). Command buffers that are chained with `then_execute`, or joined with
+//! `join()`, end up in one submission, even if some of them have to wait on semaphores first:
+//! these waits are put in separate batches of the same submission. To make the most of this,
+//! build the whole chain of futures for a frame before flushing it, rather than flushing after
+//! every command buffer.
 //!
 //! # Fences
 //!
@@ -80,11 +99,20 @@
 //! you are encouraged to use `then_signal_fence_and_flush()` instead.
 //!
 //! Signalling a fence is kind of a "terminator" to a chain of futures
+//!
+//! If several parts of a program need to know when the same submission has finished, put the
+//! [`FenceSignalFuture`] in an `Arc` and give each of them a clone. Each clone can be waited on
+//! independently, and can also be used as the start of a new chain of futures.
+//!
+//! A [`FenceReaper`] can be used to call `cleanup_finished` on fence signal futures automatically,
+//! in a background thread, once they have finished.
 
+pub(crate) use self::semaphore_signal::submit_signal_semaphore;
 pub use self::{
     fence_signal::{FenceSignalFuture, FenceSignalFutureBehavior},
-    join::JoinFuture,
+    join::{join_all, JoinAllFuture, JoinFuture},
     now::{now, NowFuture},
+    reaper::FenceReaper,
     semaphore_signal::SemaphoreSignalFuture,
 };
 use super::{fence::Fence, semaphore::Semaphore};
@@ -103,7 +131,7 @@ use crate::{
 };
 use ahash::HashMap;
 use parking_lot::MutexGuard;
-use smallvec::{smallvec, SmallVec};
+use smallvec::SmallVec;
 use std::{
     error::Error,
     fmt::{Display, Error as FmtError, Formatter},
@@ -114,6 +142,7 @@ use std::{
 mod fence_signal;
 mod join;
 mod now;
+mod reaper;
 mod semaphore_signal;
 
 /// Represents an event that will happen on the GPU in the future.
@@ -265,6 +294,39 @@ pub unsafe trait GpuFuture: DeviceOwned {
         command_buffer.execute_after(self, queue)
     }
 
+    /// Executes a queue family ownership transfer after this future.
+    ///
+    /// `release_command_buffer` is executed on `release_queue`, and must contain the release
+    /// barriers of the transfer. `acquire_command_buffer` is executed on `acquire_queue`, and must
+    /// contain the matching acquire barriers. A semaphore is signaled after the release and waited
+    /// on before the acquire, so the acquire can't be executed before the release has completed.
+    /// The release and acquire barriers can be created with
+    /// [`BufferMemoryBarrier::release_acquire`] and [`ImageMemoryBarrier::release_acquire`].
+    ///
+    /// > **Note**: This is just a shortcut for `then_execute(release_queue,
+    /// > release_command_buffer)`, followed by `then_signal_semaphore()` and
+    /// > `then_execute(acquire_queue, acquire_command_buffer)`.
+    ///
+    /// [`BufferMemoryBarrier::release_acquire`]: crate::sync::BufferMemoryBarrier::release_acquire
+    /// [`ImageMemoryBarrier::release_acquire`]: crate::sync::ImageMemoryBarrier::release_acquire
+    fn then_execute_ownership_transfer(
+        self,
+        release_queue: Arc<Queue>,
+        release_command_buffer: Arc<CommandBuffer>,
+        acquire_queue: Arc<Queue>,
+        acquire_command_buffer: Arc<CommandBuffer>,
+    ) -> Result<
+        CommandBufferExecFuture<SemaphoreSignalFuture<CommandBufferExecFuture<Self>>>,
+        CommandBufferExecError,
+    >
+    where
+        Self: Sized,
+    {
+        self.then_execute(release_queue, release_command_buffer)?
+            .then_signal_semaphore()
+            .then_execute(acquire_queue, acquire_command_buffer)
+    }
+
     /// Signals a semaphore after this future. Returns another future that represents the signal.
     ///
     /// Call this function when you want to execute some operations on a queue and want to see the
@@ -458,7 +520,10 @@ where
 pub enum SubmitAnyBuilder {
     Empty,
     SemaphoresWait(SmallVec<[Arc<Semaphore>; 8]>),
-    CommandBuffer(SubmitInfo, Option<Arc<Fence>>),
+    /// One or more batches that are submitted to the queue with a single call to `vkQueueSubmit`
+    /// or `vkQueueSubmit2`. Each batch has its own wait semaphores, command buffers and signal
+    /// semaphores, and the batches are executed in order.
+    CommandBuffer(SmallVec<[SubmitInfo; 1]>, Option<Arc<Fence>>),
     QueuePresent(PresentInfo),
     BindSparse(SmallVec<[BindSparseInfo; 1]>, Option<Arc<Fence>>),
 }
@@ -491,6 +556,10 @@ pub enum AccessError {
 
     /// Trying to use a swapchain image without depending on a corresponding acquire image future.
     SwapchainImageNotAcquired,
+
+    /// The resource is accessed by two futures that are executed on different queues without a
+    /// dependency between them, and at least one of them writes to it.
+    ConcurrentQueueAccess,
 }
 
 impl Error for AccessError {}
@@ -516,6 +585,10 @@ impl Display for AccessError {
                 "trying to use a swapchain image without depending on a corresponding acquire \
                 image future"
             }
+            AccessError::ConcurrentQueueAccess => {
+                "the resource is accessed by two futures that are executed on different queues \
+                without a dependency between them, and at least one of them writes to it"
+            }
         };
 
         write!(f, "{}", value,)
@@ -572,6 +645,7 @@ pub(crate) unsafe fn queue_present(
     let PresentInfo {
         wait_semaphores: _,
         swapchains,
+        frame_boundary: _,
         _ne: _,
     } = &present_info;
 
@@ -591,11 +665,11 @@ pub(crate) unsafe fn queue_present(
 
 pub(crate) unsafe fn queue_submit(
     queue: &Arc<Queue>,
-    submit_info: SubmitInfo,
+    submit_infos: impl IntoIterator<Item = SubmitInfo>,
     fence: Option<Arc<Fence>>,
     future: &dyn GpuFuture,
 ) -> Result<(), Validated<VulkanError>> {
-    let submit_infos: SmallVec<[_; 4]> = smallvec![submit_info];
+    let submit_infos: SmallVec<[_; 4]> = submit_infos.into_iter().collect();
     let mut states = States::from_submit_infos(&submit_infos);
 
     for submit_info in &submit_infos {
@@ -642,6 +716,16 @@ pub(crate) unsafe fn queue_submit(
                 CommandBufferUsage::SimultaneousUse => (),
             }
 
+            // Earlier submissions of a `SimultaneousUse` command buffer to the same queue end with
+            // a memory barrier, so they can't conflict with this submission. Only conflicts with
+            // other work need to be checked.
+            let pending_writes = match command_buffer.usage() {
+                CommandBufferUsage::SimultaneousUse => state
+                    .pending_submits_only_on(queue)
+                    .map_or(0, |count| count as usize),
+                _ => 0,
+            };
+
             let CommandBufferResourcesUsage {
                 buffers,
                 images,
@@ -673,7 +757,7 @@ pub(crate) unsafe fn queue_submit(
                         }
                         Err(AccessCheckError::Unknown) => {
                             let result = if range_usage.mutable {
-                                state.check_gpu_write(range.clone())
+                                state.check_gpu_write_resubmit(range.clone(), pending_writes)
                             } else {
                                 state.check_gpu_read(range.clone())
                             };
@@ -721,7 +805,11 @@ pub(crate) unsafe fn queue_submit(
                         }
                         Err(AccessCheckError::Unknown) => {
                             let result = if range_usage.mutable {
-                                state.check_gpu_write(range.clone(), range_usage.expected_layout)
+                                state.check_gpu_write_resubmit(
+                                    range.clone(),
+                                    range_usage.expected_layout,
+                                    pending_writes,
+                                )
                             } else {
                                 state.check_gpu_read(range.clone(), range_usage.expected_layout)
                             };
@@ -750,9 +838,12 @@ pub(crate) unsafe fn queue_submit(
 
     for submit_info in &submit_infos {
         let SubmitInfo {
+            flags: _,
             wait_semaphores: _,
             command_buffers,
             signal_semaphores: _,
+            frame_boundary: _,
+            win32_keyed_mutex: _,
             _ne: _,
         } = submit_info;
 
@@ -766,7 +857,7 @@ pub(crate) unsafe fn queue_submit(
                 .command_buffers
                 .get_mut(&command_buffer.handle())
                 .unwrap();
-            state.add_queue_submit();
+            state.add_queue_submit(queue);
 
             let CommandBufferResourcesUsage {
                 buffers,
@@ -821,9 +912,12 @@ impl<'a> States<'a> {
 
         for submit_info in submit_infos {
             let SubmitInfo {
+                flags: _,
                 wait_semaphores: _,
                 command_buffers: info_command_buffers,
                 signal_semaphores: _,
+                frame_boundary: _,
+                win32_keyed_mutex: _,
                 _ne: _,
             } = submit_info;
 
@@ -0,0 +1,118 @@
+use super::{FenceSignalFuture, GpuFuture};
+use std::{
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Calls `cleanup_finished` on fence signal futures in a background thread, until they have
+/// finished.
+///
+/// Futures keep the resources that they use alive until `cleanup_finished` is called after the
+/// GPU has finished with them. An application that never calls it, or only does so for some of
+/// its futures, slowly accumulates resources that could have been freed. A `FenceReaper` takes
+/// care of this for every future that is passed to [`track`]: at every interval, it checks
+/// whether their fence has been signaled, and if so, cleans them up and stops tracking them.
+///
+/// The futures are shared with the reaper through an `Arc`, so the application can keep its own
+/// clone to wait on or to chain more operations after it.
+///
+/// ```
+/// use std::{sync::Arc, time::Duration};
+/// use vulkano::sync::{future::FenceReaper, GpuFuture};
+///
+/// # let future: Box<dyn GpuFuture + Send> = return;
+/// let reaper = FenceReaper::new(Duration::from_millis(100));
+///
+/// let fence_signal = Arc::new(future.then_signal_fence_and_flush().unwrap());
+/// reaper.track(fence_signal.clone());
+/// ```
+///
+/// When the `FenceReaper` is dropped, the background thread drops the futures that it still
+/// tracks, which blocks until the GPU has finished with the ones that it held the last clone of.
+///
+/// [`track`]: Self::track
+#[derive(Debug)]
+pub struct FenceReaper {
+    sender: Option<Sender<Box<dyn Reap>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FenceReaper {
+    /// Starts a background thread that checks the tracked futures every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel::<Box<dyn Reap>>();
+
+        let thread = thread::Builder::new()
+            .name("vulkano fence reaper".into())
+            .spawn(move || {
+                let mut futures = Vec::new();
+                let mut next_reap = Instant::now() + interval;
+
+                loop {
+                    let timeout = next_reap.saturating_duration_since(Instant::now());
+
+                    match receiver.recv_timeout(timeout) {
+                        Ok(future) => futures.push(future),
+                        Err(RecvTimeoutError::Timeout) => {
+                            futures.retain(|future| !future.reap());
+                            next_reap = Instant::now() + interval;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            })
+            .expect("failed to spawn the fence reaper thread");
+
+        FenceReaper {
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    /// Tracks `future` until its fence has been signaled, and calls `cleanup_finished` on it
+    /// once that has happened.
+    ///
+    /// The future must be flushed for its fence to ever be signaled. A future that is never
+    /// flushed is tracked until the `FenceReaper` is dropped.
+    pub fn track<F>(&self, future: Arc<FenceSignalFuture<F>>)
+    where
+        F: GpuFuture + Send + 'static,
+    {
+        // The thread only stops once `self` is dropped.
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Box::new(future))
+            .unwrap();
+    }
+}
+
+impl Drop for FenceReaper {
+    fn drop(&mut self) {
+        // Disconnecting the channel stops the thread.
+        self.sender = None;
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// A future that the reaper can clean up.
+trait Reap: Send {
+    // Cleans up the future if it has finished, and returns whether it has.
+    fn reap(&self) -> bool;
+}
+
+impl<F> Reap for Arc<FenceSignalFuture<F>>
+where
+    F: GpuFuture + Send,
+{
+    fn reap(&self) -> bool {
+        self.cleanup_finished_and_check()
+    }
+}
@@ -17,12 +17,14 @@ pub use self::{
     },
 };
 use crate::{device::Queue, VulkanError};
+use smallvec::SmallVec;
 use std::{
     error::Error,
     fmt::{Display, Formatter},
     sync::Arc,
 };
 
+pub mod epoch;
 pub mod event;
 pub mod fence;
 pub mod future;
@@ -91,6 +93,31 @@ where
     }
 }
 
+impl Sharing<SmallVec<[u32; 4]>> {
+    /// Returns the sharing mode for a resource that is used by queues of all the given queue
+    /// families.
+    ///
+    /// This is `Exclusive` if there is only one distinct queue family index, and `Concurrent` with
+    /// the distinct indices otherwise. This is useful when the queue families are only known at
+    /// runtime, for example for the images of a swapchain that are rendered on one queue family
+    /// and presented on another.
+    pub fn from_queue_family_indices(queue_family_indices: impl IntoIterator<Item = u32>) -> Self {
+        let mut indices: SmallVec<[u32; 4]> = SmallVec::new();
+
+        for queue_family_index in queue_family_indices {
+            if !indices.contains(&queue_family_index) {
+                indices.push(queue_family_index);
+            }
+        }
+
+        if indices.len() > 1 {
+            Self::Concurrent(indices)
+        } else {
+            Self::Exclusive
+        }
+    }
+}
+
 /// How the memory of a resource is currently being accessed.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum CurrentAccess {
@@ -584,7 +584,6 @@ vulkan_bitflags! {
         ///
         /// This may set flags that are not supported by the device, so this is for internal use
         /// only and should not be passed on to Vulkan.
-        #[allow(dead_code)] // TODO: use this function
         pub(crate) fn expand(mut self) -> Self {
             if self.intersects(AccessFlags::SHADER_READ) {
                 self -= AccessFlags::SHADER_READ;
@@ -1188,6 +1187,30 @@ macro_rules! pipeline_stage_access {
             pub(crate) fn contains_enum(self, val: PipelineStageAccess) -> bool {
                 self.intersects(val.into())
             }
+
+            /// Returns the combinations of a stage in `stages` and an access in `access`.
+            ///
+            /// `stages` and `access` must already be expanded. `AccessFlags::MEMORY_READ` and
+            /// `AccessFlags::MEMORY_WRITE` match all read and write accesses respectively.
+            pub(crate) fn from_stages_access(stages: PipelineStages, access: AccessFlags) -> Self {
+                let mut result = Self::empty();
+
+                $(
+                    if stages.intersects(PipelineStages::$stage) {
+                        let memory_access = if Self::$val.contains_write() {
+                            AccessFlags::MEMORY_WRITE
+                        } else {
+                            AccessFlags::MEMORY_READ
+                        };
+
+                        if access.intersects(AccessFlags::$access | memory_access) {
+                            result |= Self::$val;
+                        }
+                    }
+                )*
+
+                result
+            }
         }
 
         impl std::ops::BitAnd for PipelineStageAccessFlags {
@@ -2402,6 +2425,38 @@ impl BufferMemoryBarrier {
         }
     }
 
+    /// Splits a queue family ownership transfer into the matching release and acquire barriers.
+    ///
+    /// `self` describes the whole transfer: `src_stages` and `src_access` are the accesses on the
+    /// source queue family that must complete before the release, and `dst_stages` and
+    /// `dst_access` are the accesses on the destination queue family that must wait for the
+    /// acquire. The returned release barrier must be recorded in a command buffer for the source
+    /// queue family, and the acquire barrier in a command buffer for the destination queue
+    /// family, which must be executed after the release has completed.
+    /// [`GpuFuture::then_execute_ownership_transfer`] can be used for that.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `self.queue_family_ownership_transfer` is `None`.
+    ///
+    /// [`GpuFuture::then_execute_ownership_transfer`]: crate::sync::GpuFuture::then_execute_ownership_transfer
+    pub fn release_acquire(self) -> (Self, Self) {
+        assert!(self.queue_family_ownership_transfer.is_some());
+
+        let release = Self {
+            dst_stages: PipelineStages::empty(),
+            dst_access: AccessFlags::empty(),
+            ..self.clone()
+        };
+        let acquire = Self {
+            src_stages: PipelineStages::empty(),
+            src_access: AccessFlags::empty(),
+            ..self
+        };
+
+        (release, acquire)
+    }
+
     pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
         let &Self {
             src_stages,
@@ -3078,6 +3133,40 @@ impl ImageMemoryBarrier {
         }
     }
 
+    /// Splits a queue family ownership transfer into the matching release and acquire barriers.
+    ///
+    /// `self` describes the whole transfer: `src_stages` and `src_access` are the accesses on the
+    /// source queue family that must complete before the release, and `dst_stages` and
+    /// `dst_access` are the accesses on the destination queue family that must wait for the
+    /// acquire. Both returned barriers keep `old_layout` and `new_layout`, so that the layout
+    /// transition is performed once, between the release and the acquire. The release barrier
+    /// must be recorded in a command buffer for the source queue family, and the acquire barrier
+    /// in a command buffer for the destination queue family, which must be executed after the
+    /// release has completed. [`GpuFuture::then_execute_ownership_transfer`] can be used for
+    /// that.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `self.queue_family_ownership_transfer` is `None`.
+    ///
+    /// [`GpuFuture::then_execute_ownership_transfer`]: crate::sync::GpuFuture::then_execute_ownership_transfer
+    pub fn release_acquire(self) -> (Self, Self) {
+        assert!(self.queue_family_ownership_transfer.is_some());
+
+        let release = Self {
+            dst_stages: PipelineStages::empty(),
+            dst_access: AccessFlags::empty(),
+            ..self.clone()
+        };
+        let acquire = Self {
+            src_stages: PipelineStages::empty(),
+            src_access: AccessFlags::empty(),
+            ..self
+        };
+
+        (release, acquire)
+    }
+
     pub(crate) fn validate(&self, device: &Device) -> Result<(), Box<ValidationError>> {
         let &Self {
             src_stages,
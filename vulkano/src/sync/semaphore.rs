@@ -65,14 +65,16 @@
 //! [`max_timeline_semaphore_value_difference`]: crate::device::DeviceProperties::max_timeline_semaphore_value_difference
 
 use crate::{
-    device::{physical::PhysicalDevice, Device, DeviceOwned},
+    device::{physical::PhysicalDevice, Device, DeviceOwned, ExportMetalObjectTypes},
     instance::InstanceOwnedDebugWrapper,
     macros::{impl_id_counter, vulkan_bitflags, vulkan_bitflags_enum, vulkan_enum},
     Requires, RequiresAllOf, RequiresOneOf, Validated, ValidationError, Version, VulkanError,
     VulkanObject,
 };
 use smallvec::SmallVec;
-use std::{fs::File, mem::MaybeUninit, num::NonZeroU64, ptr, sync::Arc, time::Duration};
+use std::{
+    ffi::c_void, fs::File, mem::MaybeUninit, num::NonZeroU64, ptr, sync::Arc, time::Duration,
+};
 
 /// Used to provide synchronization between command buffers during their execution.
 ///
@@ -86,6 +88,7 @@ pub struct Semaphore {
 
     semaphore_type: SemaphoreType,
     export_handle_types: ExternalSemaphoreHandleTypes,
+    export_metal_object_types: ExportMetalObjectTypes,
 
     must_put_in_pool: bool,
 }
@@ -122,6 +125,7 @@ impl Semaphore {
             semaphore_type,
             initial_value,
             export_handle_types,
+            export_metal_object_types,
             _ne: _,
         } = &create_info;
 
@@ -153,6 +157,14 @@ impl Semaphore {
             create_info_vk.p_next = <*const _>::cast(next);
         }
 
+        let mut export_metal_object_create_infos_vk =
+            export_metal_object_types.to_vulkan_create_infos();
+
+        for next in &mut export_metal_object_create_infos_vk {
+            next.p_next = create_info_vk.p_next;
+            create_info_vk.p_next = <*const _>::cast(next);
+        }
+
         let handle = {
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
@@ -187,6 +199,7 @@ impl Semaphore {
 
                 semaphore_type: SemaphoreType::Binary,
                 export_handle_types: ExternalSemaphoreHandleTypes::empty(),
+                export_metal_object_types: ExportMetalObjectTypes::empty(),
 
                 must_put_in_pool: true,
             },
@@ -218,6 +231,7 @@ impl Semaphore {
             semaphore_type,
             initial_value: _,
             export_handle_types,
+            export_metal_object_types,
             _ne: _,
         } = create_info;
 
@@ -228,6 +242,7 @@ impl Semaphore {
 
             semaphore_type,
             export_handle_types,
+            export_metal_object_types,
 
             must_put_in_pool: false,
         }
@@ -245,6 +260,12 @@ impl Semaphore {
         self.export_handle_types
     }
 
+    /// Returns the types of Metal objects that can be exported from the semaphore.
+    #[inline]
+    pub fn export_metal_object_types(&self) -> ExportMetalObjectTypes {
+        self.export_metal_object_types
+    }
+
     /// If `self` is a timeline semaphore, returns the current counter value of the semaphore.
     ///
     /// The returned value may be immediately out of date, if a signal operation on the semaphore
@@ -1119,6 +1140,53 @@ impl Semaphore {
 
         Ok(())
     }
+
+    /// Returns the `MTLSharedEvent` that the semaphore is implemented with, as an
+    /// `id<MTLSharedEvent>` pointer.
+    ///
+    /// The semaphore must have been created with [`ExportMetalObjectTypes::METAL_SHARED_EVENT`]
+    /// in [`SemaphoreCreateInfo::export_metal_object_types`].
+    #[inline]
+    pub fn export_metal_shared_event(&self) -> Result<*mut c_void, Box<ValidationError>> {
+        self.validate_export_metal_shared_event()?;
+
+        Ok(unsafe { self.export_metal_shared_event_unchecked() })
+    }
+
+    fn validate_export_metal_shared_event(&self) -> Result<(), Box<ValidationError>> {
+        if !self
+            .export_metal_object_types
+            .intersects(ExportMetalObjectTypes::METAL_SHARED_EVENT)
+        {
+            return Err(Box::new(ValidationError {
+                context: "self.export_metal_object_types()".into(),
+                problem: "does not contain `ExportMetalObjectTypes::METAL_SHARED_EVENT`".into(),
+                ..Default::default()
+            }));
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "document_unchecked"), doc(hidden))]
+    pub unsafe fn export_metal_shared_event_unchecked(&self) -> *mut c_void {
+        let mut shared_event_info_vk = ash::vk::ExportMetalSharedEventInfoEXT {
+            semaphore: self.handle,
+            ..Default::default()
+        };
+        let mut objects_info_vk = ash::vk::ExportMetalObjectsInfoEXT {
+            p_next: <*mut _>::cast(&mut shared_event_info_vk),
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        (fns.ext_metal_objects.export_metal_objects_ext)(
+            self.device.handle(),
+            &mut objects_info_vk,
+        );
+
+        shared_event_info_vk.mtl_shared_event
+    }
 }
 
 impl Drop for Semaphore {
@@ -1175,6 +1243,15 @@ pub struct SemaphoreCreateInfo {
     /// The default value is [`ExternalSemaphoreHandleTypes::empty()`].
     pub export_handle_types: ExternalSemaphoreHandleTypes,
 
+    /// The types of Metal objects that can be exported from the semaphore.
+    ///
+    /// Only [`ExportMetalObjectTypes::METAL_SHARED_EVENT`] can be specified. If this is not
+    /// empty, the [`ext_metal_objects`](crate::device::DeviceExtensions::ext_metal_objects)
+    /// extension must be enabled on the device.
+    ///
+    /// The default value is empty.
+    pub export_metal_object_types: ExportMetalObjectTypes,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -1185,6 +1262,7 @@ impl Default for SemaphoreCreateInfo {
             semaphore_type: SemaphoreType::Binary,
             initial_value: 0,
             export_handle_types: ExternalSemaphoreHandleTypes::empty(),
+            export_metal_object_types: ExportMetalObjectTypes::empty(),
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -1196,6 +1274,7 @@ impl SemaphoreCreateInfo {
             semaphore_type,
             initial_value,
             export_handle_types,
+            export_metal_object_types,
             _ne: _,
         } = self;
 
@@ -1296,6 +1375,29 @@ impl SemaphoreCreateInfo {
             }
         }
 
+        if !export_metal_object_types.is_empty() {
+            if !device.enabled_extensions().ext_metal_objects {
+                return Err(Box::new(ValidationError {
+                    context: "export_metal_object_types".into(),
+                    problem: "is not empty".into(),
+                    requires_one_of: RequiresOneOf(&[RequiresAllOf(&[Requires::DeviceExtension(
+                        "ext_metal_objects",
+                    )])]),
+                    ..Default::default()
+                }));
+            }
+
+            if !ExportMetalObjectTypes::METAL_SHARED_EVENT.contains(export_metal_object_types) {
+                return Err(Box::new(ValidationError {
+                    context: "export_metal_object_types".into(),
+                    problem: "contains types other than \
+                        `ExportMetalObjectTypes::METAL_SHARED_EVENT`"
+                        .into(),
+                    ..Default::default()
+                }));
+            }
+        }
+
         Ok(())
     }
 }